@@ -23,6 +23,7 @@ impl WarpApp for App {
                         .title("Dashboard")
                         .component(DashboardPage)
                 }),
+                middleware: vec![],
             },
             Route {
                 path: "/settings".to_string(),
@@ -31,6 +32,7 @@ impl WarpApp for App {
                         .title("Settings")
                         .component(SettingsPage)
                 }),
+                middleware: vec![],
             },
         ]
     }
@@ -143,12 +145,12 @@ impl Component for Header {
                 </nav>
                 
                 <div class="user-menu">
-                    {if let Some(user) = auth.user {
+                    {if let Some(user) = auth.user.clone() {
                         view! {
                             <div>
                                 {Avatar::new()
                                     .src(user.image.as_deref().unwrap_or_default())
-                                    .fallback(&user.name[0..1])
+                                    .name(&user.name)
                                     .render()}
                                 <span>{user.name}</span>
                             </div>
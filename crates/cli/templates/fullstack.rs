@@ -18,6 +18,7 @@ impl WarpApp for App {
                         .title("Full-Stack Layer9")
                         .component(HomePage)
                 }),
+                middleware: vec![],
             },
             Route {
                 path: "/api/todos".to_string(),
@@ -25,6 +26,7 @@ impl WarpApp for App {
                     // This would be handled server-side
                     JsValue::from_str(r#"{"todos": []}"#)
                 }),
+                middleware: vec![],
             },
         ]
     }
@@ -153,7 +155,7 @@ impl Component for Navigation {
                 </div>
                 
                 <div class="auth">
-                    {Protected::new(UserMenu)
+                    {Protected::wrap(UserMenu)
                         .fallback(LoginButton)
                         .render()}
                 </div>
@@ -354,12 +356,13 @@ impl Component for UserMenu {
         
         view! {
             <div class="user-menu">
-                {if let Some(user) = auth.user {
+                {if let Some(user) = auth.user.clone() {
                     view! {
                         <div>
-                            <span>{user.name}</span>
+                            <span>{user.username}</span>
                             {Button::new("Logout")
                                 .variant(ButtonVariant::Ghost)
+                                .on_click(move || auth.logout())
                                 .render()}
                         </div>
                     }
@@ -17,6 +17,7 @@ impl WarpApp for App {
                         .title("Layer9 App")
                         .component(HomePage)
                 }),
+                middleware: vec![],
             },
         ]
     }
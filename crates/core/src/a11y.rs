@@ -0,0 +1,315 @@
+//! Accessibility Utilities - L5
+//!
+//! `FocusTrap` keeps keyboard focus inside a container, as dialogs, open
+//! menus and dropdowns need, and gives it back to whatever had it before:
+//!
+//! ```ignore
+//! let mut trap = FocusTrap::new();
+//! trap.activate(&dialog);
+//! // ... Tab and Shift+Tab now cycle through the dialog's controls
+//! trap.deactivate(); // focus returns to the button that opened it
+//! ```
+//!
+//! The focusable elements are looked up on every Tab, so controls added or
+//! removed while the trap is active are picked up.
+
+use wasm_bindgen::closure::Closure;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsCast;
+
+/// Elements reachable with Tab, unless disabled or hidden
+pub const FOCUSABLE_SELECTOR: &str = "a[href], area[href], button:not([disabled]), \
+    input:not([disabled]):not([type=\"hidden\"]), select:not([disabled]), \
+    textarea:not([disabled]), iframe, [contenteditable=\"true\"], \
+    [tabindex]:not([tabindex=\"-1\"])";
+
+/// Constrains Tab and Shift+Tab to a container's focusable descendants
+#[derive(Default)]
+pub struct FocusTrap {
+    container: Option<web_sys::Element>,
+    /// Focused before activation, and focused again on deactivation
+    previous: Option<web_sys::Element>,
+    on_keydown: Option<Closure<dyn FnMut(web_sys::KeyboardEvent)>>,
+}
+
+impl FocusTrap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.container.is_some()
+    }
+
+    /// Remember the focused element, move focus into `container` and keep
+    /// it there. Activating again first releases the previous container.
+    pub fn activate(&mut self, container: &web_sys::Element) {
+        self.release();
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+                return;
+            };
+            self.previous = document.active_element();
+
+            let trapped = container.clone();
+            let on_keydown = Closure::<dyn FnMut(web_sys::KeyboardEvent)>::new(
+                move |event: web_sys::KeyboardEvent| {
+                    if event.key() == "Tab" && keep_focus_inside(&trapped, event.shift_key()) {
+                        event.prevent_default();
+                    }
+                },
+            );
+            // Capture, so the trap sees Tab before handlers inside it
+            let _ = document.add_event_listener_with_callback_and_bool(
+                "keydown",
+                on_keydown.as_ref().unchecked_ref(),
+                true,
+            );
+            self.on_keydown = Some(on_keydown);
+
+            let already_inside = self
+                .previous
+                .as_ref()
+                .is_some_and(|focused| container.contains(Some(focused.as_ref())));
+            if !already_inside {
+                match focusable_elements(container).first() {
+                    Some(first) => {
+                        let _ = first.focus();
+                    }
+                    None => focus_container(container),
+                }
+            }
+        }
+
+        self.container = Some(container.clone());
+    }
+
+    /// Stop trapping and return focus to the element focused before
+    /// `activate`, if it is still in the document
+    pub fn deactivate(&mut self) {
+        let previous = self.previous.take();
+        if !self.release() {
+            return;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(previous) = previous.and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok()) {
+            if previous.is_connected() {
+                let _ = previous.focus();
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = previous;
+    }
+
+    /// Drop the listener; whether the trap was active
+    fn release(&mut self) -> bool {
+        #[cfg(target_arch = "wasm32")]
+        if let (Some(on_keydown), Some(document)) = (
+            self.on_keydown.as_ref(),
+            web_sys::window().and_then(|w| w.document()),
+        ) {
+            let _ = document.remove_event_listener_with_callback_and_bool(
+                "keydown",
+                on_keydown.as_ref().unchecked_ref(),
+                true,
+            );
+        }
+
+        self.on_keydown = None;
+        self.container.take().is_some()
+    }
+}
+
+impl Drop for FocusTrap {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Where Tab should send focus among `count` focusable elements, when the
+/// browser's own order would leave the container: from the last element
+/// forward to the first, from the first (Shift+Tab) back to the last, and
+/// from outside to the nearest end. `None` lets the browser move focus.
+pub fn wrap_focus(count: usize, current: Option<usize>, backwards: bool) -> Option<usize> {
+    let last = count.checked_sub(1)?;
+    match (current, backwards) {
+        (None, false) => Some(0),
+        (None, true) => Some(last),
+        (Some(index), false) if index >= last => Some(0),
+        (Some(0), true) => Some(last),
+        _ => None,
+    }
+}
+
+/// Move focus for a Tab press if it would leave `container`; whether it did
+#[cfg(target_arch = "wasm32")]
+fn keep_focus_inside(container: &web_sys::Element, backwards: bool) -> bool {
+    let focusable = focusable_elements(container);
+    if focusable.is_empty() {
+        // Nothing to move to, so focus stays on the container itself
+        focus_container(container);
+        return true;
+    }
+
+    let active = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.active_element());
+    let current = active.and_then(|active| {
+        focusable
+            .iter()
+            .position(|element| element.unchecked_ref::<web_sys::Element>() == &active)
+    });
+
+    match wrap_focus(focusable.len(), current, backwards) {
+        Some(index) => {
+            let _ = focusable[index].focus();
+            true
+        }
+        None => false,
+    }
+}
+
+/// The container's focusable descendants in document order, skipping
+/// hidden ones
+pub fn focusable_elements(container: &web_sys::Element) -> Vec<web_sys::HtmlElement> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let Ok(nodes) = container.query_selector_all(FOCUSABLE_SELECTOR) else {
+            return Vec::new();
+        };
+        (0..nodes.length())
+            .filter_map(|i| nodes.item(i))
+            .filter_map(|node| node.dyn_into::<web_sys::HtmlElement>().ok())
+            .filter(|element| !element.hidden() && element.closest("[hidden], [inert]").ok().flatten().is_none())
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = container;
+        Vec::new()
+    }
+}
+
+/// Focus a container with nothing focusable in it, making it focusable by
+/// script first if needed
+#[cfg(target_arch = "wasm32")]
+fn focus_container(container: &web_sys::Element) {
+    if !container.has_attribute("tabindex") {
+        let _ = container.set_attribute("tabindex", "-1");
+    }
+    if let Some(container) = container.dyn_ref::<web_sys::HtmlElement>() {
+        let _ = container.focus();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_wraps_at_the_ends() {
+        // Tab on the last of three goes to the first
+        assert_eq!(wrap_focus(3, Some(2), false), Some(0));
+        // Shift+Tab on the first goes to the last
+        assert_eq!(wrap_focus(3, Some(0), true), Some(2));
+
+        // In the middle the browser moves focus itself
+        assert_eq!(wrap_focus(3, Some(1), false), None);
+        assert_eq!(wrap_focus(3, Some(1), true), None);
+
+        // Focus that escaped comes back at the near end
+        assert_eq!(wrap_focus(3, None, false), Some(0));
+        assert_eq!(wrap_focus(3, None, true), Some(2));
+
+        // A single element keeps focus; an empty container has nowhere to go
+        assert_eq!(wrap_focus(1, Some(0), false), Some(0));
+        assert_eq!(wrap_focus(1, Some(0), true), Some(0));
+        assert_eq!(wrap_focus(0, None, false), None);
+
+        assert!(!FocusTrap::new().is_active());
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn press_tab(shift: bool) {
+        let init = web_sys::KeyboardEventInit::new();
+        init.set_key("Tab");
+        init.set_shift_key(shift);
+        init.set_bubbles(true);
+        init.set_cancelable(true);
+        let event = web_sys::KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+        let document = web_sys::window().unwrap().document().unwrap();
+        document.active_element().unwrap().dispatch_event(&event).unwrap();
+    }
+
+    fn active_id() -> String {
+        let document = web_sys::window().unwrap().document().unwrap();
+        document.active_element().map(|e| e.id()).unwrap_or_default()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_trap_wraps_tab_and_restores_focus() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let body = document.body().unwrap();
+
+        let opener = document.create_element("button").unwrap();
+        opener.set_id("trap-opener");
+        body.append_child(&opener).unwrap();
+        opener.unchecked_ref::<web_sys::HtmlElement>().focus().unwrap();
+
+        let dialog = document.create_element("div").unwrap();
+        dialog.set_inner_html(
+            r##"<button id="trap-first">One</button><input id="trap-middle"><a id="trap-last" href="#">Three</a>"##,
+        );
+        body.append_child(&dialog).unwrap();
+
+        let mut trap = FocusTrap::new();
+        trap.activate(&dialog);
+        assert_eq!(active_id(), "trap-first");
+
+        document
+            .get_element_by_id("trap-last")
+            .unwrap()
+            .unchecked_ref::<web_sys::HtmlElement>()
+            .focus()
+            .unwrap();
+        press_tab(false);
+        assert_eq!(active_id(), "trap-first");
+
+        press_tab(true);
+        assert_eq!(active_id(), "trap-last");
+
+        // A control added while trapped becomes the new last stop
+        let added = document.create_element("button").unwrap();
+        added.set_id("trap-added");
+        dialog.append_child(&added).unwrap();
+        document
+            .get_element_by_id("trap-first")
+            .unwrap()
+            .unchecked_ref::<web_sys::HtmlElement>()
+            .focus()
+            .unwrap();
+        press_tab(true);
+        assert_eq!(active_id(), "trap-added");
+        press_tab(false);
+        assert_eq!(active_id(), "trap-first");
+
+        trap.deactivate();
+        assert_eq!(active_id(), "trap-opener");
+
+        body.remove_child(&dialog).unwrap();
+        body.remove_child(&opener).unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+//! Screen Reader Announcements - L5
+//!
+//! The framework keeps one pair of visually-hidden `aria-live` regions at the
+//! end of the document body. Components announce through `use_announce()`
+//! instead of rendering live regions of their own:
+//!
+//! ```ignore
+//! let announce = use_announce();
+//! announce.polite("Saved 3 items");
+//! announce.assertive("Connection lost");
+//! ```
+//!
+//! `Form::handle_submit` already announces validation errors and the
+//! submission outcome.
+
+use crate::component::{Element, Props};
+use std::cell::RefCell;
+
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::callback::Timeout;
+
+/// DOM id of the container the live regions live in
+pub const ANNOUNCER_ROOT_ID: &str = "layer9-announcer";
+
+/// Hides content visually while keeping it in the accessibility tree
+pub const VISUALLY_HIDDEN_STYLE: &str = "position: absolute; width: 1px; height: 1px; padding: 0; margin: -1px; overflow: hidden; clip: rect(0, 0, 0, 0); white-space: nowrap; border: 0";
+
+/// How urgently an announcement interrupts the screen reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Politeness {
+    /// Read once the user is idle, e.g. "Saved"
+    Polite,
+    /// Read immediately, e.g. validation errors
+    Assertive,
+}
+
+impl Politeness {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Politeness::Polite => "polite",
+            Politeness::Assertive => "assertive",
+        }
+    }
+
+    fn role(&self) -> &'static str {
+        match self {
+            Politeness::Polite => "status",
+            Politeness::Assertive => "alert",
+        }
+    }
+
+    fn region_id(&self) -> String {
+        format!("{}-{}", ANNOUNCER_ROOT_ID, self.as_str())
+    }
+}
+
+#[derive(Default)]
+struct Regions {
+    polite: String,
+    assertive: String,
+}
+
+thread_local! {
+    static REGIONS: RefCell<Regions> = RefCell::new(Regions::default());
+}
+
+/// Handle to the shared live regions, returned by `use_announce()`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Announcer;
+
+impl Announcer {
+    /// Announce `message` without interrupting the user
+    pub fn polite(&self, message: impl Into<String>) {
+        self.announce(message, Politeness::Polite);
+    }
+
+    /// Announce `message` right away, interrupting current speech
+    pub fn assertive(&self, message: impl Into<String>) {
+        self.announce(message, Politeness::Assertive);
+    }
+
+    pub fn announce(&self, message: impl Into<String>, politeness: Politeness) {
+        let message = message.into();
+        if message.trim().is_empty() {
+            return;
+        }
+
+        REGIONS.with(|regions| {
+            let mut regions = regions.borrow_mut();
+            match politeness {
+                Politeness::Polite => regions.polite = message.clone(),
+                Politeness::Assertive => regions.assertive = message.clone(),
+            }
+        });
+        self.sync_region(politeness, message);
+    }
+
+    /// Text currently in a region
+    pub fn message(&self, politeness: Politeness) -> String {
+        REGIONS.with(|regions| {
+            let regions = regions.borrow();
+            match politeness {
+                Politeness::Polite => regions.polite.clone(),
+                Politeness::Assertive => regions.assertive.clone(),
+            }
+        })
+    }
+
+    /// Empty both regions
+    pub fn clear(&self) {
+        REGIONS.with(|regions| *regions.borrow_mut() = Regions::default());
+
+        #[cfg(target_arch = "wasm32")]
+        for politeness in [Politeness::Polite, Politeness::Assertive] {
+            if let Some(region) = self.region(politeness) {
+                region.set_text_content(None);
+            }
+        }
+    }
+
+    /// Render both live regions, e.g. into server-rendered markup
+    pub fn view(&self) -> Element {
+        let region = |politeness: Politeness| Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(politeness.region_id()),
+                attributes: vec![
+                    ("role".to_string(), politeness.role().to_string()),
+                    ("aria-live".to_string(), politeness.as_str().to_string()),
+                    ("aria-atomic".to_string(), "true".to_string()),
+                ],
+                ..Default::default()
+            }),
+            children: vec![Element::Text(self.message(politeness))],
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(ANNOUNCER_ROOT_ID.to_string()),
+                attributes: vec![("style".to_string(), VISUALLY_HIDDEN_STYLE.to_string())],
+                ..Default::default()
+            }),
+            children: vec![region(Politeness::Polite), region(Politeness::Assertive)],
+        }
+    }
+
+    /// The live region element, attaching the regions to the body on first use
+    #[cfg(target_arch = "wasm32")]
+    fn region(&self, politeness: Politeness) -> Option<web_sys::Element> {
+        let document = web_sys::window()?.document()?;
+        if document.get_element_by_id(ANNOUNCER_ROOT_ID).is_none() {
+            let root = self.view().to_dom();
+            document.body()?.append_child(&root).ok()?;
+        }
+        document.get_element_by_id(&politeness.region_id())
+    }
+
+    /// Screen readers only announce changes to a region already in the page,
+    /// so the text is cleared first and set a moment later. That also makes
+    /// a repeated message ("Saved", "Saved") read twice.
+    fn sync_region(&self, politeness: Politeness, message: String) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let Some(region) = self.region(politeness) else {
+                return;
+            };
+            region.set_text_content(None);
+            Timeout::new(100, move || region.set_text_content(Some(&message))).forget();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = (politeness, message);
+    }
+}
+
+/// Announcement hook - screen readers read what is passed to the returned handle
+pub fn use_announce() -> Announcer {
+    Announcer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_text(view: &Element, index: usize) -> (String, String) {
+        let Element::Node { children, .. } = view else {
+            panic!("expected announcer root");
+        };
+        let Element::Node { props, children, .. } = &children[index] else {
+            panic!("expected live region");
+        };
+        let live = props
+            .attributes
+            .iter()
+            .find(|(key, _)| key == "aria-live")
+            .map(|(_, value)| value.clone())
+            .unwrap();
+        let Element::Text(text) = &children[0] else {
+            panic!("expected region text");
+        };
+        (live, text.clone())
+    }
+
+    #[test]
+    fn test_messages_go_to_matching_region() {
+        let announce = use_announce();
+        announce.clear();
+
+        announce.polite("Profile saved");
+        announce.assertive("Email is invalid");
+        announce.polite("   ");
+
+        let view = announce.view();
+        assert_eq!(region_text(&view, 0), ("polite".to_string(), "Profile saved".to_string()));
+        assert_eq!(region_text(&view, 1), ("assertive".to_string(), "Email is invalid".to_string()));
+
+        announce.clear();
+        assert!(announce.message(Politeness::Polite).is_empty());
+    }
+}
@@ -522,13 +522,13 @@ impl Component for ApiDocsViewer {
         let description_element = if let Some(desc) = &self.spec.info.description {
             Element::Node {
                 tag: "p".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text(desc.clone())],
             }
         } else {
             Element::Node {
                 tag: "div".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![],
             }
         };
@@ -539,14 +539,14 @@ impl Component for ApiDocsViewer {
             .iter()
             .map(|(path, item)| Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("endpoint".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "h3".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(path.clone())],
                     },
                     render_path_item(item),
@@ -556,19 +556,19 @@ impl Component for ApiDocsViewer {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("api-docs".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(self.spec.info.title.clone())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(format!(
                         "Version: {}",
                         self.spec.info.version
@@ -577,12 +577,12 @@ impl Component for ApiDocsViewer {
                 description_element,
                 Element::Node {
                     tag: "h2".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Endpoints".to_string())],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: endpoints,
                 },
             ],
@@ -608,10 +608,10 @@ fn render_path_item(item: &PathItem) -> Element {
 
     Element::Node {
         tag: "div".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("path-methods".to_string()),
             ..Default::default()
-        },
+        }),
         children: methods,
     }
 }
@@ -619,20 +619,20 @@ fn render_path_item(item: &PathItem) -> Element {
 fn render_operation(method: &str, op: &Operation) -> Element {
     let mut children = vec![Element::Node {
         tag: "span".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("method".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![Element::Text(method.to_string())],
     }];
 
     if let Some(summary) = &op.summary {
         children.push(Element::Node {
             tag: "span".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("summary".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text(summary.clone())],
         });
     }
@@ -640,20 +640,20 @@ fn render_operation(method: &str, op: &Operation) -> Element {
     if let Some(description) = &op.description {
         children.push(Element::Node {
             tag: "p".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("description".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text(description.clone())],
         });
     }
 
     Element::Node {
         tag: "div".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("operation".to_string()),
             ..Default::default()
-        },
+        }),
         children,
     }
 }
@@ -14,6 +14,7 @@ pub trait Layer9App: L8::Architecture {
 pub fn run_app<T: Layer9App + 'static>(app: T) {
     // Set panic hook for better error messages
     console_error_panic_hook::set_once();
+    crate::head::ensure_viewport();
 
     // Initialize app
     app.initialize();
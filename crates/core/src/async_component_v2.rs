@@ -3,15 +3,20 @@
 //! This module provides a simpler async component implementation
 //! that works within current WASM and lifetime constraints.
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+
+use futures::future::{abortable, AbortHandle};
 
 use wasm_bindgen_futures::spawn_local;
 
 use crate::component::{Component, Element, Props, use_state};
-use crate::error::ErrorBoundary;
-use crate::hooks::use_effect;
-use crate::reactive_v2::queue_current_render;
+use crate::error::{report_error, ErrorBoundary};
+use crate::hooks::{use_effect, use_ref};
+use crate::reactive_v2::{get_current_component, queue_component_render, queue_current_render};
 
 /// Loading state for async components
 #[derive(Clone, Debug)]
@@ -21,61 +26,91 @@ pub enum AsyncState<T> {
     Error(String),
 }
 
+thread_local! {
+    /// One entry per Suspense being rendered, innermost last; set when a
+    /// descendant reads a resource that is still loading
+    static SUSPENSE_FRAMES: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Suspense boundary component
+///
+/// Renders `fallback` while any descendant reads an `AsyncData` that is
+/// still loading, and the children once everything they read has
+/// resolved. Only the innermost boundary around a loading resource falls
+/// back, so nested boundaries each show their own fallback.
 pub struct Suspense {
     children: Box<dyn Component>,
-    fallback: Box<dyn Component>,
+    fallback: Element,
 }
 
 impl Suspense {
     pub fn new(children: impl Component + 'static) -> Self {
         Suspense {
             children: Box::new(children),
-            fallback: Box::new(DefaultLoadingComponent),
+            fallback: default_loading(),
         }
     }
 
-    pub fn fallback(mut self, fallback: impl Component + 'static) -> Self {
-        self.fallback = Box::new(fallback);
+    pub fn fallback(mut self, fallback: Element) -> Self {
+        self.fallback = fallback;
         self
     }
 }
 
 impl Component for Suspense {
     fn render(&self) -> Element {
-        // For now, just render children directly
-        // In a full implementation, we'd track loading state
-        self.children.render()
+        // The children still render while suspended, so every resource
+        // below gets to start loading
+        SUSPENSE_FRAMES.with(|frames| frames.borrow_mut().push(false));
+        let children = panic::catch_unwind(panic::AssertUnwindSafe(|| self.children.render()));
+        let suspended = SUSPENSE_FRAMES.with(|frames| frames.borrow_mut().pop().unwrap_or(false));
+        // Errors pass through to the nearest ErrorBoundary, once this
+        // boundary's frame is gone
+        let children = children.unwrap_or_else(|error| panic::resume_unwind(error));
+
+        if suspended {
+            self.fallback.clone()
+        } else {
+            children
+        }
     }
 }
 
-/// Default loading component
-struct DefaultLoadingComponent;
-
-impl Component for DefaultLoadingComponent {
-    fn render(&self) -> Element {
-        Element::Node {
-            tag: "div".to_string(),
-            props: Props {
-                class: Some("layer9-loading".to_string()),
-                ..Default::default()
-            },
-            children: vec![
-                Element::Text("Loading...".to_string())
-            ],
+/// Suspend the innermost Suspense being rendered, if any
+fn suspend() {
+    SUSPENSE_FRAMES.with(|frames| {
+        if let Some(suspended) = frames.borrow_mut().last_mut() {
+            *suspended = true;
         }
+    });
+}
+
+/// Default loading fallback
+fn default_loading() -> Element {
+    Element::Node {
+        tag: "div".to_string(),
+        props: Box::new(Props {
+            class: Some("layer9-loading".to_string()),
+            ..Default::default()
+        }),
+        children: vec![
+            Element::Text("Loading...".to_string())
+        ],
     }
 }
 
 /// Async data container
 pub struct AsyncData<T: Clone + 'static> {
     state: Rc<RefCell<AsyncState<T>>>,
+    /// Component re-rendered when the data settles
+    owner: Option<u32>,
 }
 
 impl<T: Clone + 'static> AsyncData<T> {
     pub fn new() -> Self {
         AsyncData {
             state: Rc::new(RefCell::new(AsyncState::Loading)),
+            owner: get_current_component(),
         }
     }
 }
@@ -96,28 +131,72 @@ impl<T: Clone + 'static> AsyncData<T> {
     
     pub fn set_loading(&self) {
         *self.state.borrow_mut() = AsyncState::Loading;
-        queue_current_render();
+        self.rerender();
     }
     
     pub fn set_success(&self, data: T) {
         *self.state.borrow_mut() = AsyncState::Success(data);
-        queue_current_render();
+        self.rerender();
     }
     
     pub fn set_error(&self, error: String) {
         *self.state.borrow_mut() = AsyncState::Error(error);
-        queue_current_render();
+        self.rerender();
     }
     
     pub fn get(&self) -> AsyncState<T> {
         self.state.borrow().clone()
     }
+
+    /// The data for rendering. While loading this suspends the nearest
+    /// `Suspense` and returns `Ok(None)`; an error goes to the nearest
+    /// `ErrorBoundary`, which renders its fallback instead, and comes back
+    /// as `Err` for components rendered without one.
+    pub fn read(&self) -> Result<Option<T>, String> {
+        match self.get() {
+            AsyncState::Loading => {
+                suspend();
+                Ok(None)
+            }
+            AsyncState::Success(data) => Ok(Some(data)),
+            AsyncState::Error(error) => {
+                report_error(error.clone());
+                Err(error)
+            }
+        }
+    }
+
+    /// Load from `future`: loading until it finishes, then its result.
+    /// Spawn the returned future to run it.
+    pub fn track(
+        &self,
+        future: impl Future<Output = Result<T, String>> + 'static,
+    ) -> impl Future<Output = ()> + 'static {
+        *self.state.borrow_mut() = AsyncState::Loading;
+        let data = self.clone();
+        async move {
+            match future.await {
+                Ok(value) => data.set_success(value),
+                Err(error) => data.set_error(error),
+            }
+        }
+    }
+
+    /// Completion arrives outside any render, so re-render the component
+    /// that created the data rather than the current one
+    fn rerender(&self) {
+        match self.owner {
+            Some(owner) => queue_component_render(owner),
+            None => queue_current_render(),
+        }
+    }
 }
 
 impl<T: Clone + 'static> Clone for AsyncData<T> {
     fn clone(&self) -> Self {
         AsyncData {
             state: self.state.clone(),
+            owner: self.owner,
         }
     }
 }
@@ -128,6 +207,213 @@ pub fn use_async_data<T: Clone + 'static>() -> AsyncData<T> {
     state.get()
 }
 
+/// Hook that starts `load` on the first render and keeps its result across
+/// re-renders; `read` it inside a `Suspense`. Off the browser nothing runs
+/// the future, so server renders show the fallback.
+pub fn use_resource<T, F, Fut>(load: F) -> AsyncData<T>
+where
+    T: Clone + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, String>> + 'static,
+{
+    let slot = use_ref(None::<AsyncData<T>>);
+    let existing = slot.borrow().clone();
+    if let Some(data) = existing {
+        return data;
+    }
+
+    let data = AsyncData::new();
+    let task = data.track(load());
+    #[cfg(target_arch = "wasm32")]
+    spawn_local(task);
+    #[cfg(not(target_arch = "wasm32"))]
+    drop(task);
+
+    *slot.borrow_mut() = Some(data.clone());
+    data
+}
+
+/// Progress of an operation started by `use_async`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsyncStatus<T, E> {
+    Idle,
+    Loading,
+    Done(T),
+    Failed(E),
+}
+
+/// What triggering an operation does while it is already running
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Retrigger {
+    /// Keep the running operation and ignore the trigger
+    #[default]
+    Ignore,
+    /// Abort the running operation and start again
+    Restart,
+}
+
+type Operation<T, E> = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<T, E>>>>>;
+
+struct OperationState<T, E> {
+    status: RefCell<AsyncStatus<T, E>>,
+    run: Operation<T, E>,
+    retrigger: Retrigger,
+    /// Bumped by every start, so only the latest run reports
+    generation: Cell<u64>,
+    running: RefCell<Option<AbortHandle>>,
+    active: Cell<bool>,
+    on_change: Box<dyn Fn()>,
+}
+
+/// A one-shot async operation that can be started repeatedly, reporting
+/// each status change through `on_change`
+pub struct AsyncOperation<T, E> {
+    state: Rc<OperationState<T, E>>,
+}
+
+impl<T, E> Clone for AsyncOperation<T, E> {
+    fn clone(&self) -> Self {
+        AsyncOperation {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Clone + 'static, E: Clone + 'static> AsyncOperation<T, E> {
+    pub fn new<F, Fut>(retrigger: Retrigger, run: F, on_change: impl Fn() + 'static) -> Self
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = Result<T, E>> + 'static,
+    {
+        AsyncOperation {
+            state: Rc::new(OperationState {
+                status: RefCell::new(AsyncStatus::Idle),
+                run: Rc::new(move || Box::pin(run()) as Pin<Box<dyn Future<Output = Result<T, E>>>>),
+                retrigger,
+                generation: Cell::new(0),
+                running: RefCell::new(None),
+                active: Cell::new(true),
+                on_change: Box::new(on_change),
+            }),
+        }
+    }
+
+    pub fn status(&self) -> AsyncStatus<T, E> {
+        self.state.status.borrow().clone()
+    }
+
+    /// Start a run and return the future that drives it, or `None` when
+    /// the trigger is ignored
+    pub fn start(&self) -> Option<impl Future<Output = ()> + 'static> {
+        let state = &self.state;
+        if !state.active.get() {
+            return None;
+        }
+        let running = state.running.borrow_mut().take();
+        if let Some(running) = running {
+            if state.retrigger == Retrigger::Ignore {
+                state.running.replace(Some(running));
+                return None;
+            }
+            running.abort();
+        }
+
+        let generation = state.generation.get() + 1;
+        state.generation.set(generation);
+        let (run, handle) = abortable((state.run)());
+        state.running.replace(Some(handle));
+        self.set(AsyncStatus::Loading);
+
+        let weak = Rc::downgrade(state);
+        Some(async move {
+            let Ok(result) = run.await else {
+                return;
+            };
+            let Some(state) = Weak::upgrade(&weak) else {
+                return;
+            };
+            if !state.active.get() || state.generation.get() != generation {
+                return;
+            }
+            state.running.replace(None);
+            let operation = AsyncOperation { state };
+            operation.set(match result {
+                Ok(value) => AsyncStatus::Done(value),
+                Err(error) => AsyncStatus::Failed(error),
+            });
+        })
+    }
+
+    /// Abort any running operation and drop results that arrive later,
+    /// e.g. once the component has unmounted
+    pub fn deactivate(&self) {
+        self.state.active.set(false);
+        if let Some(running) = self.state.running.borrow_mut().take() {
+            running.abort();
+        }
+    }
+
+    fn set(&self, status: AsyncStatus<T, E>) {
+        self.state.status.replace(status);
+        (self.state.on_change)();
+    }
+}
+
+/// Hook for a one-shot async operation: the current status and a trigger
+/// that starts it. A trigger while it is running is ignored.
+pub fn use_async<T, E, F, Fut>(future_fn: F) -> (AsyncStatus<T, E>, Rc<dyn Fn()>)
+where
+    T: Clone + 'static,
+    E: Clone + 'static,
+    F: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+{
+    use_async_with(Retrigger::Ignore, future_fn)
+}
+
+/// `use_async` with a choice of what a trigger while running does. The
+/// operation keeps the `future_fn` from the first render, and stops
+/// updating state once the component unmounts.
+pub fn use_async_with<T, E, F, Fut>(retrigger: Retrigger, future_fn: F) -> (AsyncStatus<T, E>, Rc<dyn Fn()>)
+where
+    T: Clone + 'static,
+    E: Clone + 'static,
+    F: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+{
+    let owner = get_current_component();
+    let slot = use_ref(None::<AsyncOperation<T, E>>);
+    let existing = slot.borrow().clone();
+    let operation = existing.unwrap_or_else(|| {
+        let operation = AsyncOperation::new(retrigger, future_fn, move || {
+            if let Some(owner) = owner {
+                queue_component_render(owner);
+            }
+        });
+        *slot.borrow_mut() = Some(operation.clone());
+        operation
+    });
+
+    use_effect((), {
+        let operation = operation.clone();
+        move || move || operation.deactivate()
+    });
+
+    let trigger = {
+        let operation = operation.clone();
+        Rc::new(move || {
+            if let Some(task) = operation.start() {
+                #[cfg(target_arch = "wasm32")]
+                spawn_local(task);
+                #[cfg(not(target_arch = "wasm32"))]
+                drop(task);
+            }
+        }) as Rc<dyn Fn()>
+    };
+
+    (operation.status(), trigger)
+}
+
 /// Example component that loads data asynchronously
 pub struct AsyncExample;
 
@@ -188,6 +474,140 @@ mod tests {
         assert!(matches!(error, AsyncState::Error(_)));
     }
     
+    struct Profile {
+        data: AsyncData<String>,
+    }
+
+    impl Component for Profile {
+        fn render(&self) -> Element {
+            match self.data.read() {
+                Ok(Some(name)) => Element::Text(format!("Signed in as {}", name)),
+                Ok(None) => Element::Text("unused while suspended".to_string()),
+                Err(error) => Element::Text(format!("Could not load profile: {}", error)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_suspense_shows_fallback_until_the_future_resolves() {
+        let (send, receive) = futures::channel::oneshot::channel::<String>();
+        let data = AsyncData::new();
+        let task = data.track(async move { receive.await.map_err(|e| e.to_string()) });
+
+        let suspense = Suspense::new(Profile { data: data.clone() })
+            .fallback(Element::Text("Loading profile".to_string()));
+        assert_eq!(suspense.render().to_html(), "Loading profile");
+
+        send.send("ada".to_string()).unwrap();
+        futures::executor::block_on(task);
+        assert_eq!(suspense.render().to_html(), "Signed in as ada");
+    }
+
+    #[test]
+    fn test_nested_suspense_falls_back_innermost_only() {
+        struct Page {
+            ready: AsyncData<String>,
+            pending: AsyncData<String>,
+        }
+
+        impl Component for Page {
+            fn render(&self) -> Element {
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::default(),
+                    children: vec![
+                        Profile { data: self.ready.clone() }.render(),
+                        Suspense::new(Profile { data: self.pending.clone() })
+                            .fallback(Element::Text("Loading activity".to_string()))
+                            .render(),
+                    ],
+                }
+            }
+        }
+
+        let ready = AsyncData::new();
+        ready.set_success("ada".to_string());
+        let page = Suspense::new(Page { ready, pending: AsyncData::new() })
+            .fallback(Element::Text("Loading page".to_string()));
+
+        let html = page.render().to_html();
+        assert!(html.contains("Signed in as ada") && html.contains("Loading activity"));
+        assert!(!html.contains("Loading page"));
+    }
+
+    #[test]
+    fn test_failed_resource_reaches_error_boundary() {
+        let data = AsyncData::<String>::new();
+        data.set_error("profile service unavailable".to_string());
+
+        // No panic is involved, so debug builds catch it too
+        let boundary = ErrorBoundary::new(Suspense::new(Profile { data: data.clone() }))
+            .fallback(|error| Element::Text(error.message.clone()));
+        assert_eq!(boundary.render().to_html(), "profile service unavailable");
+        assert_eq!(boundary.error().map(|error| error.message), Some("profile service unavailable".to_string()));
+        SUSPENSE_FRAMES.with(|frames| assert!(frames.borrow().is_empty()));
+
+        // Without a boundary the component renders the error itself
+        assert_eq!(
+            Profile { data }.render().to_html(),
+            "Could not load profile: profile service unavailable"
+        );
+    }
+
+    #[test]
+    fn test_async_operation_goes_idle_loading_done() {
+        let renders = Rc::new(Cell::new(0));
+        let operation: AsyncOperation<i32, String> = {
+            let renders = renders.clone();
+            AsyncOperation::new(Retrigger::Ignore, || async { Ok(42) }, move || {
+                renders.set(renders.get() + 1)
+            })
+        };
+        assert_eq!(operation.status(), AsyncStatus::Idle);
+
+        let task = operation.start().expect("starts when idle");
+        assert_eq!(operation.status(), AsyncStatus::Loading);
+        assert!(operation.start().is_none(), "a trigger while loading is ignored");
+
+        futures::executor::block_on(task);
+        assert_eq!(operation.status(), AsyncStatus::Done(42));
+        assert_eq!(renders.get(), 2, "one re-render per transition");
+    }
+
+    #[test]
+    fn test_restarted_operation_reports_latest_result() {
+        let replies = Rc::new(RefCell::new(vec![]));
+        let operation = {
+            let replies = replies.clone();
+            AsyncOperation::new(
+                Retrigger::Restart,
+                move || {
+                    let (send, receive) = futures::channel::oneshot::channel::<i32>();
+                    replies.borrow_mut().push(send);
+                    async move { receive.await.map_err(|_| "cancelled".to_string()) }
+                },
+                || {},
+            )
+        };
+
+        let first = operation.start().unwrap();
+        let second = operation.start().expect("restarts while loading");
+        let mut replies = replies.borrow_mut().drain(..).collect::<Vec<_>>();
+        let _ = replies.remove(0).send(1);
+        replies.remove(0).send(2).unwrap();
+        futures::executor::block_on(async {
+            second.await;
+            first.await;
+        });
+        assert_eq!(operation.status(), AsyncStatus::Done(2));
+
+        // Nothing lands after unmounting
+        let third = operation.start().unwrap();
+        operation.deactivate();
+        futures::executor::block_on(third);
+        assert_eq!(operation.status(), AsyncStatus::Loading);
+    }
+
     #[test]
     fn test_async_data() {
         let data = AsyncData::<String>::new();
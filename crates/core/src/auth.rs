@@ -1,11 +1,29 @@
 //! Authentication support for Layer9
 
+use crate::component::{Component, Element, Props};
 use crate::config::Config;
-use crate::jwt::{Jwt, JwtClaims};
-use sha2::{Digest, Sha256};
-use base64::{engine::general_purpose::STANDARD, Engine};
+use crate::hooks::{provide_context, use_context, Context};
+use crate::jwt::{now_secs, token_expiry, Jwt, JwtClaims};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+use serde::{Deserialize, Serialize};
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::Deref;
+use std::rc::Rc;
 use web_sys::Storage;
 
+pub use crate::oauth::{OAuthConfig, OAuthError, OAuthProvider};
+
+type RefreshFn = Rc<dyn Fn(String) -> LocalBoxFuture<'static, Result<String, String>>>;
+type PendingRefresh = Shared<LocalBoxFuture<'static, Result<String, String>>>;
+
+/// Default `localStorage` key the session is persisted under
+pub const DEFAULT_SESSION_KEY: &str = "layer9_session";
+
 #[derive(Debug, Clone)]
 pub struct AuthContext {
     pub user: Option<User>,
@@ -13,7 +31,7 @@ pub struct AuthContext {
     pub permissions: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub username: String,
@@ -37,6 +55,27 @@ impl AuthContext {
     pub fn has_permission(&self, permission: &str) -> bool {
         self.permissions.contains(&permission.to_string())
     }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.user
+            .as_ref()
+            .is_some_and(|user| user.roles.iter().any(|r| r == role))
+    }
+
+    /// Unix time the token expires at, for JWTs with an `exp` claim
+    pub fn token_expires_at(&self) -> Option<u64> {
+        self.token.as_deref().and_then(token_expiry)
+    }
+
+    pub fn is_token_expired(&self) -> bool {
+        self.expires_within(0)
+    }
+
+    /// Whether the token expires in `secs` seconds or less
+    pub fn expires_within(&self, secs: u64) -> bool {
+        self.token_expires_at()
+            .is_some_and(|exp| exp <= now_secs() + secs)
+    }
     
     pub fn login(&mut self, user: User, token: String) {
         self.user = Some(user);
@@ -54,6 +93,21 @@ pub trait AuthProvider: AuthProviderClone {
     fn authenticate(&self, username: &str, password: &str) -> Result<(User, String), String>;
     fn validate_token(&self, token: &str) -> Result<User, String>;
     fn refresh_token(&self, token: &str) -> Result<String, String>;
+
+    /// Check a session saved by an earlier page load; `saved` is the user
+    /// stored with it, if any
+    fn restore_session(&self, token: &str, saved: Option<&User>) -> Result<User, String> {
+        let user = self.validate_token(token)?;
+        match saved {
+            Some(saved) if saved.id != user.id => Err("Session belongs to another user".to_string()),
+            _ => Ok(user),
+        }
+    }
+
+    /// Redirect-based providers sign in through `AuthService::login_with_oauth`
+    fn as_oauth(&self) -> Option<&OAuthProvider> {
+        None
+    }
 }
 
 // Helper trait to make AuthProvider object-safe and cloneable
@@ -106,11 +160,21 @@ impl AuthProvider for MockAuthProvider {
     }
 }
 
+/// Same error for an unknown user and a wrong password, so neither leaks
+/// which usernames exist
+const INVALID_CREDENTIALS: &str = "Invalid username or password";
+
+/// Verified against when the username is unknown, so both failures take as
+/// long as a real check
+static DUMMY_HASH: Lazy<String> = Lazy::new(|| {
+    JwtAuthProvider::hash_password("layer9-dummy-password").unwrap_or_default()
+});
+
 /// JWT-based authentication provider
 #[derive(Clone)]
 pub struct JwtAuthProvider {
     jwt: Jwt,
-    users: Vec<(String, String, User)>, // (username, password_hash, user)
+    users: Vec<(String, String, User)>, // (username, argon2 PHC hash, user)
 }
 
 impl JwtAuthProvider {
@@ -121,9 +185,29 @@ impl JwtAuthProvider {
         }
     }
 
+    /// Add a user; only an Argon2id hash of `password` is kept
     pub fn add_user(&mut self, username: String, password: String, email: String, roles: Vec<String>) -> String {
+        let password_hash = Self::hash_password(&password)
+            .expect("Argon2 hashing with default parameters cannot fail");
+        self.insert_user(username, password_hash, email, roles)
+    }
+
+    /// Add a user whose password was hashed elsewhere, e.g. with
+    /// `hash_password` at provisioning time. Takes a PHC string such as
+    /// `$argon2id$v=19$m=19456,t=2,p=1$...`.
+    pub fn add_user_with_hash(
+        &mut self,
+        username: String,
+        password_hash: String,
+        email: String,
+        roles: Vec<String>,
+    ) -> Result<String, String> {
+        PasswordHash::new(&password_hash).map_err(|e| format!("Invalid password hash: {}", e))?;
+        Ok(self.insert_user(username, password_hash, email, roles))
+    }
+
+    fn insert_user(&mut self, username: String, password_hash: String, email: String, roles: Vec<String>) -> String {
         let id = format!("user-{}", self.users.len() + 1);
-        let password_hash = self.hash_password(&password);
         let user = User {
             id: id.clone(),
             username: username.clone(),
@@ -134,17 +218,22 @@ impl JwtAuthProvider {
         id
     }
 
-    fn hash_password(&self, password: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        hasher.update(b"layer9-salt"); // In production, use a random salt per user
-        let result = hasher.finalize();
-        STANDARD.encode(result)
+    /// Argon2id hash of `password` with a random salt, as a PHC string
+    pub fn hash_password(password: &str) -> Result<String, String> {
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate salt: {}", e))?;
+        let salt = SaltString::encode_b64(&bytes).map_err(|e| format!("Failed to encode salt: {}", e))?;
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| format!("Failed to hash password: {}", e))
     }
 
-    fn verify_password(&self, password: &str, hash: &str) -> bool {
-        let computed_hash = self.hash_password(password);
-        computed_hash == hash
+    /// Check `password` against a PHC hash; the digest comparison is constant-time
+    fn verify_password(password: &str, hash: &str) -> bool {
+        PasswordHash::new(hash)
+            .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
     }
 
     fn get_local_storage() -> Option<Storage> {
@@ -174,17 +263,19 @@ impl JwtAuthProvider {
 impl AuthProvider for JwtAuthProvider {
     fn authenticate(&self, username: &str, password: &str) -> Result<(User, String), String> {
         // Find user
-        let (_, stored_hash, user) = self.users.iter()
-            .find(|(u, _, _)| u == username)
-            .ok_or_else(|| "Invalid username or password".to_string())?;
+        let Some((_, stored_hash, user)) = self.users.iter().find(|(u, _, _)| u == username) else {
+            // Do the same work as a real check so timing doesn't reveal the miss
+            Self::verify_password(password, &DUMMY_HASH);
+            return Err(INVALID_CREDENTIALS.to_string());
+        };
 
         // Verify password
-        if !self.verify_password(password, stored_hash) {
-            return Err("Invalid username or password".to_string());
+        if !Self::verify_password(password, stored_hash) {
+            return Err(INVALID_CREDENTIALS.to_string());
         }
 
         // Create JWT token
-        let now = js_sys::Date::now() as u64 / 1000;
+        let now = crate::jwt::now_secs();
         let claims = JwtClaims {
             sub: user.id.clone(),
             username: user.username.clone(),
@@ -218,7 +309,7 @@ impl AuthProvider for JwtAuthProvider {
         let claims = self.jwt.verify_token(token)?;
         
         // Create new token with extended expiration
-        let now = js_sys::Date::now() as u64 / 1000;
+        let now = crate::jwt::now_secs();
         let new_claims = JwtClaims {
             sub: claims.sub,
             username: claims.username,
@@ -271,9 +362,165 @@ impl JwtAuthProvider {
     }
 }
 
+thread_local! {
+    /// Auth state of the app, as last published by an `AuthService`
+    static CURRENT_AUTH: RefCell<AuthContext> = RefCell::new(AuthContext::new());
+    /// Components that read `use_auth()` and re-render when it changes
+    static AUTH_SUBSCRIBERS: RefCell<HashSet<u32>> = RefCell::new(HashSet::new());
+}
+
+/// Replace the app's auth state and re-render every component reading it
+pub(crate) fn publish_auth(context: &AuthContext) {
+    CURRENT_AUTH.with(|current| *current.borrow_mut() = context.clone());
+    let subscribers: Vec<_> = AUTH_SUBSCRIBERS.with(|subs| subs.borrow().iter().copied().collect());
+    for component_id in subscribers {
+        crate::reactive_v2::queue_component_render(component_id);
+    }
+}
+
+/// Live auth state returned by `use_auth()`. Derefs to the `AuthContext`
+/// snapshot (`.user`, `.is_authenticated()`, `.has_role()`, ...) and signs
+/// in and out through the `AuthServiceProvider`'s service.
+#[derive(Clone)]
+pub struct AuthHandle {
+    context: AuthContext,
+    service: Option<Rc<RefCell<AuthService>>>,
+}
+
+impl AuthHandle {
+    /// Log in with the shared service; every `use_auth()` consumer re-renders
+    pub async fn login(&self, username: &str, password: &str) -> Result<(), String> {
+        let shared = self.shared_service()?;
+        // Work on a copy so the service isn't borrowed across the await
+        let mut service = shared.borrow().clone();
+        let result = service.login(username, password).await;
+        *shared.borrow_mut() = service;
+        result
+    }
+
+    pub fn logout(&self) {
+        match &self.service {
+            Some(service) => service.borrow_mut().logout(),
+            None => publish_auth(&AuthContext::new()),
+        }
+    }
+
+    /// Send the browser to the OAuth provider's consent screen
+    pub fn login_with_oauth(&self) -> Result<(), String> {
+        self.shared_service()?.borrow().login_with_oauth()
+    }
+
+    /// Finish an OAuth sign-in on the callback route
+    pub async fn handle_oauth_callback(&self, query: &str) -> Result<(), String> {
+        let shared = self.shared_service()?;
+        let mut service = shared.borrow().clone();
+        let result = service.handle_oauth_callback(query).await;
+        *shared.borrow_mut() = service;
+        result
+    }
+
+    /// Sign back in with a saved OAuth session once the provider confirms
+    /// it; see `AuthService::resume_session`
+    pub async fn resume_session(&self) -> Result<(), String> {
+        let shared = self.shared_service()?;
+        let mut service = shared.borrow().clone();
+        let result = service.resume_session().await;
+        *shared.borrow_mut() = service;
+        result
+    }
+
+    /// The service held by the nearest `AuthServiceProvider`
+    pub fn service(&self) -> Option<Rc<RefCell<AuthService>>> {
+        self.service.clone()
+    }
+
+    fn shared_service(&self) -> Result<&Rc<RefCell<AuthService>>, String> {
+        self.service
+            .as_ref()
+            .ok_or_else(|| "use_auth() must be used within an AuthServiceProvider to log in".to_string())
+    }
+}
+
+impl Deref for AuthHandle {
+    type Target = AuthContext;
+
+    fn deref(&self) -> &AuthContext {
+        &self.context
+    }
+}
+
+impl std::fmt::Debug for AuthHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthHandle")
+            .field("context", &self.context)
+            .field("has_service", &self.service.is_some())
+            .finish()
+    }
+}
+
+/// Holds the app's `AuthService` so any component can log in and out
+/// through `use_auth()`
+pub struct AuthServiceProvider {
+    service: Rc<RefCell<AuthService>>,
+    children: Vec<Element>,
+}
+
+impl AuthServiceProvider {
+    pub fn new(service: AuthService) -> Self {
+        let service = Rc::new(RefCell::new(service));
+        provide_context(&Context::<Rc<RefCell<AuthService>>>::new(), service.clone());
+        // Consumers start from the session the service restored
+        publish_auth(service.borrow().get_context());
+
+        AuthServiceProvider {
+            service,
+            children: vec![],
+        }
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn service(&self) -> Rc<RefCell<AuthService>> {
+        self.service.clone()
+    }
+}
+
+impl Component for AuthServiceProvider {
+    fn render(&self) -> Element {
+        provide_context(&Context::<Rc<RefCell<AuthService>>>::new(), self.service.clone());
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("auth-provider".to_string()),
+                ..Default::default()
+            }),
+            children: self.children.clone(),
+        }
+    }
+}
+
 // Hook function for authentication
-pub fn use_auth() -> AuthContext {
-    AuthContext::new()
+/// The current auth state; a component calling this during render re-renders
+/// on login and logout
+pub fn use_auth() -> AuthHandle {
+    if let Some(component_id) = crate::reactive_v2::get_current_component() {
+        AUTH_SUBSCRIBERS.with(|subs| subs.borrow_mut().insert(component_id));
+    }
+    AuthHandle {
+        context: CURRENT_AUTH.with(|current| current.borrow().clone()),
+        service: use_context(&Context::<Rc<RefCell<AuthService>>>::new()),
+    }
+}
+
+/// What survives a page reload
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    token: String,
+    user: User,
 }
 
 // Auth service for managing authentication state
@@ -281,6 +528,13 @@ pub fn use_auth() -> AuthContext {
 pub struct AuthService {
     context: AuthContext,
     provider: Box<dyn AuthProvider>,
+    refresh: Option<RefreshFn>,
+    /// Seconds before expiry at which a token is refreshed
+    refresh_margin: u64,
+    /// Refresh in progress, shared by clones so concurrent callers wait on one
+    pending_refresh: Rc<RefCell<Option<PendingRefresh>>>,
+    /// `localStorage` key the session is persisted under
+    storage_key: String,
 }
 
 impl AuthService {
@@ -288,10 +542,14 @@ impl AuthService {
         let mut service = Self {
             context: AuthContext::new(),
             provider,
+            refresh: None,
+            refresh_margin: 60,
+            pending_refresh: Rc::new(RefCell::new(None)),
+            storage_key: DEFAULT_SESSION_KEY.to_string(),
         };
         
-        // Try to restore session from stored token
-        service.restore_session();
+        // Pick up where the last page load left off
+        service.restore();
         service
     }
 
@@ -313,23 +571,79 @@ impl AuthService {
         }
     }
 
+    /// Renew expired or nearly expired tokens by calling `refresh` with the
+    /// current token; it resolves to the replacement token
+    pub fn with_refresh<F, Fut>(mut self, refresh: F) -> Self
+    where
+        F: Fn(String) -> Fut + 'static,
+        Fut: Future<Output = Result<String, String>> + 'static,
+    {
+        self.refresh = Some(Rc::new(move |token| refresh(token).boxed_local()));
+        self
+    }
+
+    /// Persist the session under `key` instead of `layer9_session`, restoring
+    /// whatever session was saved there
+    pub fn with_storage_key(mut self, key: impl Into<String>) -> Self {
+        let was_authenticated = self.is_authenticated();
+        self.storage_key = key.into();
+        self.context = AuthContext::new();
+        if !self.restore() && was_authenticated {
+            publish_auth(&self.context);
+        }
+        self
+    }
+
+    /// Refresh tokens this many seconds before they expire (default 60)
+    pub fn with_refresh_margin(mut self, secs: u64) -> Self {
+        self.refresh_margin = secs;
+        self
+    }
+
     pub async fn login(&mut self, username: &str, password: &str) -> Result<(), String> {
         let (user, token) = self.provider.authenticate(username, password)?;
-        
+        self.sign_in(user, token);
+        Ok(())
+    }
+
+    /// Send the browser to the OAuth provider's consent screen
+    pub fn login_with_oauth(&self) -> Result<(), String> {
+        self.oauth_provider()?.begin()
+    }
+
+    /// Finish an OAuth sign-in on the callback route, given the callback's
+    /// query string (`window.location.search`)
+    pub async fn handle_oauth_callback(&mut self, query: &str) -> Result<(), String> {
+        let (user, token) = self
+            .oauth_provider()?
+            .complete(query)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.sign_in(user, token);
+        Ok(())
+    }
+
+    fn oauth_provider(&self) -> Result<&OAuthProvider, String> {
+        self.provider
+            .as_oauth()
+            .ok_or_else(|| "Auth provider doesn't support OAuth sign-in".to_string())
+    }
+
+    fn sign_in(&mut self, user: User, token: String) {
         // Update permissions based on user roles
         let permissions = self.get_permissions_for_user(&user);
         
         self.context.user = Some(user);
         self.context.token = Some(token);
         self.context.permissions = permissions;
-        
-        Ok(())
+        self.persist();
+        publish_auth(&self.context);
     }
 
     pub fn logout(&mut self) {
         self.context.logout();
-        // Clear stored token
-        let _ = JwtAuthProvider::clear_stored_token();
+        self.clear_stored_session();
+        publish_auth(&self.context);
     }
 
     pub fn is_authenticated(&self) -> bool {
@@ -348,27 +662,154 @@ impl AuthService {
         if let Some(token) = &self.context.token {
             let new_token = self.provider.refresh_token(token)?;
             self.context.token = Some(new_token);
+            self.persist();
+            publish_auth(&self.context);
             Ok(())
         } else {
             Err("No active session to refresh".to_string())
         }
     }
 
+    /// The session token, refreshed first if it has expired or is about to.
+    ///
+    /// Concurrent callers share a single refresh. If the refresh fails, or the
+    /// token has expired with no refresh configured, the user is logged out.
+    pub async fn fresh_token(&mut self) -> Result<Option<String>, String> {
+        let Some(token) = self.context.token.clone() else {
+            return Ok(None);
+        };
+        if !self.context.expires_within(self.refresh_margin) {
+            return Ok(Some(token));
+        }
+
+        let Some(refresh) = self.refresh.clone() else {
+            if self.context.is_token_expired() {
+                self.logout();
+                return Err("Session expired".to_string());
+            }
+            return Ok(Some(token));
+        };
+
+        let pending = self
+            .pending_refresh
+            .borrow_mut()
+            .get_or_insert_with(|| refresh(token).shared())
+            .clone();
+        let result = pending.clone().await;
+        {
+            let mut slot = self.pending_refresh.borrow_mut();
+            if slot.as_ref().is_some_and(|p| p.ptr_eq(&pending)) {
+                *slot = None;
+            }
+        }
+
+        match result {
+            Ok(new_token) => {
+                let _ = JwtAuthProvider::store_token(&new_token);
+                self.context.token = Some(new_token.clone());
+                self.persist();
+                publish_auth(&self.context);
+                Ok(Some(new_token))
+            }
+            Err(e) => {
+                self.logout();
+                Err(e)
+            }
+        }
+    }
+
     pub fn validate_token(&self, token: &str) -> Result<User, String> {
         self.provider.validate_token(token)
     }
 
-    fn restore_session(&mut self) {
-        if let Some(token) = JwtAuthProvider::get_stored_token() {
-            if let Ok(user) = self.provider.validate_token(&token) {
-                let permissions = self.get_permissions_for_user(&user);
-                self.context.user = Some(user);
-                self.context.token = Some(token);
-                self.context.permissions = permissions;
+    /// Restore the persisted session if its token still validates.
+    ///
+    /// A stored session that fails validation (expired, tampered, or saved for
+    /// a different user) is cleared. Returns whether a session was restored.
+    ///
+    /// OAuth tokens can only be checked by asking the provider, so their
+    /// sessions are left stored for `resume_session`.
+    pub fn restore(&mut self) -> bool {
+        let Some((token, saved_user)) = self.stored_session() else {
+            return false;
+        };
+
+        let Ok(user) = self.provider.restore_session(&token, saved_user.as_ref()) else {
+            if self.provider.as_oauth().is_none() {
+                self.clear_stored_session();
+            }
+            return false;
+        };
+
+        let permissions = self.get_permissions_for_user(&user);
+        self.context.user = Some(user);
+        self.context.token = Some(token);
+        self.context.permissions = permissions;
+        publish_auth(&self.context);
+        true
+    }
+
+    /// Sign in with the stored OAuth session once the provider confirms
+    /// whose token it is, so nothing saved beside the token is trusted. A
+    /// token the provider turns down clears the stored session.
+    pub async fn resume_session(&mut self) -> Result<(), String> {
+        if self.is_authenticated() {
+            return Ok(());
+        }
+        let (Some(provider), Some((token, _))) = (self.provider.as_oauth(), self.stored_session()) else {
+            return Ok(());
+        };
+        match provider.load_profile(&token).await {
+            Ok(user) => {
+                self.sign_in(user, token);
+                Ok(())
             }
+            Err(error) => {
+                self.clear_stored_session();
+                Err(error.to_string())
+            }
+        }
+    }
+
+    /// The token and user saved by an earlier page load
+    fn stored_session(&self) -> Option<(String, Option<User>)> {
+        let stored = JwtAuthProvider::get_local_storage()
+            .and_then(|storage| storage.get_item(&self.storage_key).ok().flatten());
+
+        // Sessions saved before this key existed only kept the bare token
+        match stored {
+            Some(json) => match serde_json::from_str::<StoredSession>(&json) {
+                Ok(session) => Some((session.token, Some(session.user))),
+                Err(_) => {
+                    self.clear_stored_session();
+                    None
+                }
+            },
+            None => JwtAuthProvider::get_stored_token().map(|token| (token, None)),
+        }
+    }
+
+    /// Save the token and user so a reload can `restore` them
+    fn persist(&self) {
+        let (Some(token), Some(user)) = (&self.context.token, &self.context.user) else {
+            return;
+        };
+        let session = StoredSession {
+            token: token.clone(),
+            user: user.clone(),
+        };
+        if let (Some(storage), Ok(json)) = (JwtAuthProvider::get_local_storage(), serde_json::to_string(&session)) {
+            let _ = storage.set_item(&self.storage_key, &json);
         }
     }
 
+    fn clear_stored_session(&self) {
+        if let Some(storage) = JwtAuthProvider::get_local_storage() {
+            let _ = storage.remove_item(&self.storage_key);
+        }
+        let _ = JwtAuthProvider::clear_stored_token();
+    }
+
     fn get_permissions_for_user(&self, user: &User) -> Vec<String> {
         let mut permissions = Vec::new();
         
@@ -426,11 +867,40 @@ impl Default for AuthService {
     }
 }
 
+/// Outcome of a `Protected` access check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Granted,
+    /// Nobody is logged in
+    Unauthenticated,
+    /// Logged in, but missing a required role or permission
+    Forbidden,
+}
+
 // Protected component wrapper
-#[derive(Debug, Clone)]
+/// Renders its child only for users meeting every requirement:
+///
+/// ```ignore
+/// Protected::wrap(AdminPanel)
+///     .require_role("admin")
+///     .require_permission("write")
+///     .fallback(LoginPrompt)       // not logged in
+///     .forbidden(NotAllowed)       // logged in, lacking a role/permission
+///     .render()
+/// ```
+///
+/// Reads `use_auth()`, so it re-renders when the user logs in or out.
+#[derive(Clone)]
 pub struct Protected {
     pub required_permission: Option<String>,
     pub redirect_to: String,
+    /// All must be held
+    pub required_roles: Vec<String>,
+    /// All must be held, along with `required_permission`
+    pub required_permissions: Vec<String>,
+    child: Option<Rc<dyn Component>>,
+    fallback: Option<Rc<dyn Component>>,
+    forbidden: Option<Rc<dyn Component>>,
 }
 
 impl Protected {
@@ -438,9 +908,49 @@ impl Protected {
         Self {
             required_permission: None,
             redirect_to: "/login".to_string(),
+            required_roles: Vec::new(),
+            required_permissions: Vec::new(),
+            child: None,
+            fallback: None,
+            forbidden: None,
         }
     }
 
+    /// Guard `child`
+    pub fn wrap(child: impl Component) -> Self {
+        Self::new().child(child)
+    }
+
+    pub fn child(mut self, child: impl Component) -> Self {
+        self.child = Some(Rc::new(child));
+        self
+    }
+
+    /// Shown to anonymous users, and to users lacking access when no
+    /// `forbidden` view is set
+    pub fn fallback(mut self, fallback: impl Component) -> Self {
+        self.fallback = Some(Rc::new(fallback));
+        self
+    }
+
+    /// Shown to logged-in users lacking a required role or permission
+    pub fn forbidden(mut self, forbidden: impl Component) -> Self {
+        self.forbidden = Some(Rc::new(forbidden));
+        self
+    }
+
+    /// Require `role`; repeated calls require every role
+    pub fn require_role(mut self, role: impl Into<String>) -> Self {
+        self.required_roles.push(role.into());
+        self
+    }
+
+    /// Require `permission`; repeated calls require every permission
+    pub fn require_permission(mut self, permission: impl Into<String>) -> Self {
+        self.required_permissions.push(permission.into());
+        self
+    }
+
     pub fn with_permission(mut self, permission: String) -> Self {
         self.required_permission = Some(permission);
         self
@@ -451,16 +961,49 @@ impl Protected {
         self
     }
 
-    pub fn check_access(&self, auth_context: &AuthContext) -> bool {
+    pub fn access(&self, auth_context: &AuthContext) -> Access {
         if !auth_context.is_authenticated() {
-            return false;
+            return Access::Unauthenticated;
         }
 
-        if let Some(permission) = &self.required_permission {
-            return auth_context.has_permission(permission);
+        let has_permissions = self
+            .required_permission
+            .iter()
+            .chain(&self.required_permissions)
+            .all(|permission| auth_context.has_permission(permission));
+        let has_roles = self.required_roles.iter().all(|role| auth_context.has_role(role));
+
+        if has_permissions && has_roles {
+            Access::Granted
+        } else {
+            Access::Forbidden
         }
+    }
 
-        true
+    pub fn check_access(&self, auth_context: &AuthContext) -> bool {
+        self.access(auth_context) == Access::Granted
+    }
+}
+
+impl Component for Protected {
+    fn render(&self) -> Element {
+        let shown = match self.access(&use_auth()) {
+            Access::Granted => self.child.as_ref(),
+            Access::Unauthenticated => self.fallback.as_ref(),
+            Access::Forbidden => self.forbidden.as_ref().or(self.fallback.as_ref()),
+        };
+        shown.map_or_else(|| Element::Text(String::new()), |component| component.render())
+    }
+}
+
+impl std::fmt::Debug for Protected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Protected")
+            .field("required_permission", &self.required_permission)
+            .field("redirect_to", &self.redirect_to)
+            .field("required_roles", &self.required_roles)
+            .field("required_permissions", &self.required_permissions)
+            .finish()
     }
 }
 
@@ -475,3 +1018,112 @@ impl Default for AuthContext {
         Self::new()
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    fn provider_with_alice() -> JwtAuthProvider {
+        let mut provider = JwtAuthProvider::new("test-secret".to_string());
+        provider.add_user(
+            "alice".to_string(),
+            "password123".to_string(),
+            "alice@example.com".to_string(),
+            vec!["user".to_string()],
+        );
+        provider
+    }
+
+    #[test]
+    fn test_only_salted_argon2_hash_is_stored() {
+        let provider = provider_with_alice();
+        let stored = &provider.users[0].1;
+
+        assert!(!stored.contains("password123"));
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(JwtAuthProvider::verify_password("password123", stored));
+        assert!(!JwtAuthProvider::verify_password("password124", stored));
+
+        // A fresh salt each time, so equal passwords don't share a hash
+        assert_ne!(&JwtAuthProvider::hash_password("password123").unwrap(), stored);
+    }
+
+    struct Text(&'static str);
+
+    impl Component for Text {
+        fn render(&self) -> Element {
+            Element::Text(self.0.to_string())
+        }
+    }
+
+    fn rendered(element: Element) -> String {
+        match element {
+            Element::Text(text) => text,
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+
+    fn signed_in(roles: &[&str], permissions: &[&str]) -> AuthContext {
+        let mut context = AuthContext::new();
+        context.login(
+            User {
+                id: "1".to_string(),
+                username: "sam".to_string(),
+                email: "sam@example.com".to_string(),
+                roles: roles.iter().map(|r| r.to_string()).collect(),
+            },
+            "token".to_string(),
+        );
+        context.permissions = permissions.iter().map(|p| p.to_string()).collect();
+        context
+    }
+
+    #[test]
+    fn test_protected_gates_on_roles_and_permissions() {
+        let admin_only = || {
+            Protected::wrap(Text("admin panel"))
+                .require_role("admin")
+                .require_role("staff")
+                .require_permission("write")
+                .fallback(Text("please log in"))
+                .forbidden(Text("forbidden"))
+        };
+
+        // Anonymous users always get the fallback
+        publish_auth(&AuthContext::new());
+        assert_eq!(rendered(admin_only().render()), "please log in");
+
+        // A guest is denied the admin-gated child
+        publish_auth(&signed_in(&["guest"], &["read"]));
+        assert_eq!(rendered(admin_only().render()), "forbidden");
+
+        // Roles are AND-ed: admin without staff is still denied
+        publish_auth(&signed_in(&["admin"], &["read", "write"]));
+        assert_eq!(admin_only().access(&use_auth()), Access::Forbidden);
+
+        publish_auth(&signed_in(&["admin", "staff"], &["read", "write"]));
+        assert_eq!(rendered(admin_only().render()), "admin panel");
+
+        // Without a forbidden view, denied users see the fallback
+        publish_auth(&signed_in(&["guest"], &["read"]));
+        let protected = Protected::wrap(Text("admin panel")).require_role("admin").fallback(Text("please log in"));
+        assert_eq!(rendered(protected.render()), "please log in");
+    }
+
+    #[test]
+    fn test_add_user_with_hash() {
+        let mut provider = JwtAuthProvider::new("test-secret".to_string());
+        let hash = JwtAuthProvider::hash_password("hunter2").unwrap();
+
+        let id = provider
+            .add_user_with_hash("bob".to_string(), hash, "bob@example.com".to_string(), vec![])
+            .unwrap();
+        assert_eq!(id, "user-1");
+        assert!(JwtAuthProvider::verify_password("hunter2", &provider.users[0].1));
+
+        let plaintext = provider.add_user_with_hash("eve".to_string(), "hunter2".to_string(), String::new(), vec![]);
+        assert!(plaintext.unwrap_err().starts_with("Invalid password hash"));
+        assert_eq!(provider.users.len(), 1);
+    }
+}
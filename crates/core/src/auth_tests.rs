@@ -4,6 +4,11 @@
 #[cfg(target_arch = "wasm32")]
 mod tests {
     use crate::auth::*;
+    use crate::component::{Component, Element};
+    use crate::jwt::{now_secs, Jwt, JwtClaims};
+    use crate::reactive_v2::register_test_component;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
     use wasm_bindgen_test::*;
     
     wasm_bindgen_test_configure!(run_in_browser);
@@ -114,6 +119,18 @@ mod tests {
         assert_eq!(result.unwrap_err(), "Invalid username or password");
     }
 
+    #[wasm_bindgen_test]
+    fn test_jwt_auth_provider_prehashed_user() {
+        let mut provider = JwtAuthProvider::new("test-secret".to_string());
+        let hash = JwtAuthProvider::hash_password("correct horse").unwrap();
+        provider
+            .add_user_with_hash("carol".to_string(), hash, "carol@example.com".to_string(), vec!["user".to_string()])
+            .unwrap();
+
+        assert_eq!(provider.authenticate("carol", "correct horse").unwrap().0.username, "carol");
+        assert_eq!(provider.authenticate("carol", "wrong horse").unwrap_err(), "Invalid username or password");
+    }
+
     #[wasm_bindgen_test]
     fn test_jwt_auth_provider_token_validation() {
         let mut provider = JwtAuthProvider::new("test-secret".to_string());
@@ -285,4 +302,196 @@ mod tests {
         assert!(context.user.is_none());
         assert!(context.token.is_none());
     }
-}
\ No newline at end of file
+
+    /// Records whether it saw a signed-in user on each render
+    struct AuthBadge {
+        renders: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl Component for AuthBadge {
+        fn render(&self) -> Element {
+            let auth = use_auth();
+            self.renders.borrow_mut().push(auth.is_authenticated());
+            Element::Text(auth.user.as_ref().map(|u| u.username.clone()).unwrap_or_default())
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_use_auth_consumers_rerender_on_login() {
+        crate::reactive_v2::init_renderer();
+        let provider = AuthServiceProvider::new(
+            AuthService::new(Box::new(alice_provider())).with_storage_key("test_shared_auth"),
+        );
+
+        // Both mounted before anyone logs in
+        let header = Rc::new(RefCell::new(Vec::new()));
+        let sidebar = Rc::new(RefCell::new(Vec::new()));
+        register_test_component(Box::new(AuthBadge { renders: header.clone() }));
+        register_test_component(Box::new(AuthBadge { renders: sidebar.clone() }));
+        assert_eq!(*header.borrow(), vec![false]);
+        assert_eq!(*sidebar.borrow(), vec![false]);
+
+        use_auth().login("alice", "password123").await.unwrap();
+        assert!(provider.service().borrow().is_authenticated());
+        assert_eq!(*header.borrow(), vec![false, true]);
+        assert_eq!(*sidebar.borrow(), vec![false, true]);
+
+        // Logging out from any component updates every consumer
+        use_auth().logout();
+        assert_eq!(*header.borrow(), vec![false, true, false]);
+        assert_eq!(*sidebar.borrow(), vec![false, true, false]);
+        assert!(!provider.service().borrow().is_authenticated());
+    }
+
+    fn alice_provider() -> JwtAuthProvider {
+        let mut provider = JwtAuthProvider::new("test-secret".to_string());
+        provider.add_user(
+            "alice".to_string(),
+            "password123".to_string(),
+            "alice@example.com".to_string(),
+            vec!["user".to_string()],
+        );
+        provider
+    }
+
+    fn stored_session(key: &str) -> Option<String> {
+        web_sys::window()
+            .unwrap()
+            .local_storage()
+            .unwrap()
+            .unwrap()
+            .get_item(key)
+            .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_session_restored_after_reload() {
+        let provider = alice_provider();
+        let mut service = AuthService::new(Box::new(provider.clone())).with_storage_key("test_session");
+        service.login("alice", "password123").await.unwrap();
+        assert!(stored_session("test_session").is_some());
+
+        // A fresh service, as after a page reload
+        let mut reloaded = AuthService::new(Box::new(provider.clone())).with_storage_key("test_session");
+        assert!(reloaded.is_authenticated());
+        assert_eq!(reloaded.get_current_user().unwrap().username, "alice");
+        assert_eq!(reloaded.get_context().token, service.get_context().token);
+        assert!(reloaded.get_context().has_permission("write"));
+
+        reloaded.logout();
+        assert!(stored_session("test_session").is_none());
+        assert!(!AuthService::new(Box::new(provider)).with_storage_key("test_session").is_authenticated());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_tampered_session_is_cleared() {
+        let provider = alice_provider();
+        let mut service = AuthService::new(Box::new(provider.clone())).with_storage_key("test_session");
+        service.login("alice", "password123").await.unwrap();
+
+        // Same claims, signed with a key the provider doesn't trust
+        let forged = Jwt::new("attacker-secret".to_string())
+            .create_token(&JwtClaims {
+                sub: "1".to_string(),
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                roles: vec!["admin".to_string()],
+                exp: now_secs() + 3600,
+                iat: now_secs(),
+                permissions: vec![],
+            })
+            .unwrap();
+        let session = stored_session("test_session").unwrap();
+        let tampered = session.replace(service.get_context().token.as_deref().unwrap(), &forged);
+        web_sys::window()
+            .unwrap()
+            .local_storage()
+            .unwrap()
+            .unwrap()
+            .set_item("test_session", &tampered)
+            .unwrap();
+
+        let reloaded = AuthService::new(Box::new(provider)).with_storage_key("test_session");
+        assert!(!reloaded.is_authenticated());
+        assert!(stored_session("test_session").is_none());
+        service.logout();
+    }
+
+    fn signed_token(exp: u64) -> String {
+        Jwt::new("test-secret".to_string())
+            .create_token(&JwtClaims {
+                sub: "1".to_string(),
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                roles: vec!["user".to_string()],
+                exp,
+                iat: exp.saturating_sub(3600),
+                permissions: vec![],
+            })
+            .unwrap()
+    }
+
+    /// Hands out a token that expired an hour ago, like a tab left open overnight
+    #[derive(Clone)]
+    struct StaleSessionProvider;
+
+    impl AuthProvider for StaleSessionProvider {
+        fn authenticate(&self, username: &str, _password: &str) -> Result<(User, String), String> {
+            let user = User {
+                id: "1".to_string(),
+                username: username.to_string(),
+                email: format!("{}@example.com", username),
+                roles: vec!["user".to_string()],
+            };
+            Ok((user, signed_token(now_secs() - 3600)))
+        }
+
+        fn validate_token(&self, _token: &str) -> Result<User, String> {
+            Err("Token expired".to_string())
+        }
+
+        fn refresh_token(&self, _token: &str) -> Result<String, String> {
+            Err("Not supported".to_string())
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_expired_token_refreshed_once_for_concurrent_callers() {
+        let refreshes = Rc::new(Cell::new(0));
+        let mut service = {
+            let refreshes = refreshes.clone();
+            AuthService::new(Box::new(StaleSessionProvider)).with_refresh(move |_expired| {
+                refreshes.set(refreshes.get() + 1);
+                async move {
+                    gloo_timers::future::TimeoutFuture::new(10).await;
+                    Ok(signed_token(now_secs() + 3600))
+                }
+            })
+        };
+        service.login("alice", "password").await.unwrap();
+        assert!(service.get_context().is_token_expired());
+
+        // Another request racing this one shares the same refresh
+        let mut other = service.clone();
+        let (first, second) = futures::join!(service.fresh_token(), other.fresh_token());
+        assert_eq!(refreshes.get(), 1);
+        assert_eq!(first.unwrap(), second.unwrap());
+
+        assert!(service.is_authenticated());
+        assert!(!service.get_context().is_token_expired());
+        assert!(service.get_context().token_expires_at().unwrap() > now_secs());
+
+        service.logout();
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_failed_refresh_logs_out() {
+        let mut service = AuthService::new(Box::new(StaleSessionProvider))
+            .with_refresh(|_| async { Err("Refresh token revoked".to_string()) });
+        service.login("alice", "password").await.unwrap();
+
+        assert_eq!(service.fresh_token().await, Err("Refresh token revoked".to_string()));
+        assert!(!service.is_authenticated());
+        assert!(service.get_context().token.is_none());
+    }
+}
@@ -0,0 +1,328 @@
+//! Batched Queries - L4
+//!
+//! One request carries several named queries and gets back a map keyed by
+//! query name, so a page that needs four data sources makes one round trip:
+//!
+//! ```ignore
+//! // Server
+//! let endpoint = BatchEndpoint::new()
+//!     .query("repo", |_| async { Ok(load_repo().await) })
+//!     .query("commits", |params| async move { Ok(load_commits(params["limit"].as_u64()).await) });
+//!
+//! // Client
+//! let results = BatchClient::new("/api/batch")
+//!     .query("repo")
+//!     .query_with("commits", json!({ "limit": 10 }))
+//!     .send()
+//!     .await?;
+//! let repo: RepoInfo = results.get("repo")?;
+//! ```
+//!
+//! Each query succeeds or fails on its own; one failing resolver does not fail
+//! the batch.
+
+use crate::http::{self, HttpError, Transport};
+use futures::future::{join_all, BoxFuture};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+type Resolver = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, String>> + Send + Sync>;
+
+/// Batch request body: query name to its parameters
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BatchRequest {
+    pub queries: BTreeMap<String, Value>,
+}
+
+/// Outcome of a single query in a batch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchOutcome {
+    Data(Value),
+    Error(String),
+}
+
+/// Batch response body: query name to its outcome
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BatchResponse {
+    pub results: BTreeMap<String, BatchOutcome>,
+}
+
+/// Errors reading a single result out of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchError {
+    /// The response has no entry for this query
+    Missing(String),
+    /// The query's resolver failed
+    Query { name: String, message: String },
+    /// The result did not match the requested type
+    Decode { name: String, message: String },
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::Missing(name) => write!(f, "No result for query '{}'", name),
+            BatchError::Query { name, message } => write!(f, "Query '{}' failed: {}", name, message),
+            BatchError::Decode { name, message } => {
+                write!(f, "Could not decode result of '{}': {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl BatchResponse {
+    /// Deserialize the result of query `name`
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T, BatchError> {
+        match self.results.get(name) {
+            Some(BatchOutcome::Data(value)) => {
+                serde_json::from_value(value.clone()).map_err(|e| BatchError::Decode {
+                    name: name.to_string(),
+                    message: e.to_string(),
+                })
+            }
+            Some(BatchOutcome::Error(message)) => Err(BatchError::Query {
+                name: name.to_string(),
+                message: message.clone(),
+            }),
+            None => Err(BatchError::Missing(name.to_string())),
+        }
+    }
+}
+
+/// Server side: named resolvers answered in one request
+#[derive(Clone, Default)]
+pub struct BatchEndpoint {
+    resolvers: BTreeMap<String, Resolver>,
+    max_queries: Option<usize>,
+}
+
+impl BatchEndpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a resolver; it receives the query's params (`null` when none were sent)
+    pub fn query<T, F, Fut>(mut self, name: impl Into<String>, resolver: F) -> Self
+    where
+        T: Serialize,
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, String>> + Send + 'static,
+    {
+        let resolver = Arc::new(resolver);
+        self.resolvers.insert(
+            name.into(),
+            Arc::new(move |params| {
+                let future = resolver(params);
+                Box::pin(async move {
+                    let data = future.await?;
+                    serde_json::to_value(data).map_err(|e| e.to_string())
+                })
+            }),
+        );
+        self
+    }
+
+    /// Reject batches with more than `max` queries
+    pub fn max_queries(mut self, max: usize) -> Self {
+        self.max_queries = Some(max);
+        self
+    }
+
+    /// Run every query in the request concurrently
+    pub async fn execute(&self, request: BatchRequest) -> Result<BatchResponse, String> {
+        if let Some(max) = self.max_queries {
+            if request.queries.len() > max {
+                return Err(format!(
+                    "Batch has {} queries; the limit is {}",
+                    request.queries.len(),
+                    max
+                ));
+            }
+        }
+
+        let pending = request.queries.into_iter().map(|(name, params)| {
+            let resolver = self.resolvers.get(&name).cloned();
+            async move {
+                let outcome = match resolver {
+                    Some(resolver) => match resolver(params).await {
+                        Ok(data) => BatchOutcome::Data(data),
+                        Err(message) => BatchOutcome::Error(message),
+                    },
+                    None => BatchOutcome::Error(format!("Unknown query '{}'", name)),
+                };
+                (name, outcome)
+            }
+        });
+
+        Ok(BatchResponse {
+            results: join_all(pending).await.into_iter().collect(),
+        })
+    }
+
+    /// Axum router answering `POST /` with the batch
+    #[cfg(feature = "ssr")]
+    pub fn into_router(self) -> axum::Router {
+        use axum::{extract::State, http::StatusCode, routing::post, Json};
+
+        async fn handler(
+            State(endpoint): State<Arc<BatchEndpoint>>,
+            Json(request): Json<BatchRequest>,
+        ) -> Result<Json<BatchResponse>, (StatusCode, String)> {
+            endpoint
+                .execute(request)
+                .await
+                .map(Json)
+                .map_err(|message| (StatusCode::BAD_REQUEST, message))
+        }
+
+        axum::Router::new()
+            .route("/", post(handler))
+            .with_state(Arc::new(self))
+    }
+}
+
+/// Client side: collects queries and sends them as one request
+pub struct BatchClient {
+    url: String,
+    request: BatchRequest,
+}
+
+impl BatchClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        BatchClient {
+            url: url.into(),
+            request: BatchRequest::default(),
+        }
+    }
+
+    pub fn query(self, name: impl Into<String>) -> Self {
+        self.query_with(name, Value::Null)
+    }
+
+    pub fn query_with(mut self, name: impl Into<String>, params: Value) -> Self {
+        self.request.queries.insert(name.into(), params);
+        self
+    }
+
+    pub async fn send(self) -> Result<BatchResponse, HttpError> {
+        http::post(&self.url)
+            .json(&self.request)
+            .error_for_status()
+            .await?
+            .json()
+    }
+
+    /// Send through a specific transport instead of `window.fetch`
+    pub async fn send_with(self, transport: &dyn Transport) -> Result<BatchResponse, HttpError> {
+        http::post(&self.url)
+            .json(&self.request)
+            .error_for_status()
+            .send_with(transport)
+            .await?
+            .json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{AbortHandle, HttpRequest, Response};
+    use futures::executor::block_on;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Repo {
+        name: String,
+        stars: u32,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Commit {
+        sha: String,
+    }
+
+    fn dashboard_endpoint() -> BatchEndpoint {
+        BatchEndpoint::new()
+            .query("repo", |_| async {
+                Ok(Repo {
+                    name: "layer9".to_string(),
+                    stars: 42,
+                })
+            })
+            .query("commits", |params: Value| async move {
+                let limit = params["limit"].as_u64().unwrap_or(1) as usize;
+                Ok((0..limit)
+                    .map(|i| Commit { sha: format!("c{}", i) })
+                    .collect::<Vec<_>>())
+            })
+            .query("contributors", |_| async { Err::<Vec<String>, _>("rate limited".to_string()) })
+            .query("languages", |_| async { Ok(vec![("Rust", 65.2), ("TypeScript", 25.8)]) })
+    }
+
+    /// Serves the batch endpoint over the http transport interface
+    struct Server(BatchEndpoint);
+
+    impl Transport for Server {
+        fn send(
+            &self,
+            request: HttpRequest,
+            _abort: Option<AbortHandle>,
+        ) -> Pin<Box<dyn Future<Output = Result<Response, HttpError>>>> {
+            let batch: BatchRequest = serde_json::from_str(&request.body.unwrap()).unwrap();
+            let endpoint = self.0.clone();
+            Box::pin(async move {
+                let response = endpoint.execute(batch).await.unwrap();
+                let body = serde_json::to_string(&response).unwrap();
+                Ok(Response::new(200, HashMap::new(), body))
+            })
+        }
+    }
+
+    #[test]
+    fn test_dashboard_batch_in_one_request() {
+        let server = Server(dashboard_endpoint());
+        let results = block_on(
+            BatchClient::new("/api/batch")
+                .query("repo")
+                .query_with("commits", json!({ "limit": 2 }))
+                .query("contributors")
+                .query("languages")
+                .send_with(&server),
+        )
+        .unwrap();
+
+        let repo: Repo = results.get("repo").unwrap();
+        let commits: Vec<Commit> = results.get("commits").unwrap();
+        let languages: Vec<(String, f64)> = results.get("languages").unwrap();
+
+        assert_eq!(repo.stars, 42);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(languages[0].0, "Rust");
+        assert!(matches!(
+            results.get::<Vec<String>>("contributors"),
+            Err(BatchError::Query { message, .. }) if message == "rate limited"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_queries_and_limits() {
+        let request = BatchRequest {
+            queries: BTreeMap::from([("nope".to_string(), Value::Null)]),
+        };
+        let response = block_on(dashboard_endpoint().execute(request.clone())).unwrap();
+        assert!(matches!(response.get::<Value>("nope"), Err(BatchError::Query { .. })));
+        assert_eq!(response.get::<Value>("repo"), Err(BatchError::Missing("repo".to_string())));
+
+        let limited = dashboard_endpoint().max_queries(0);
+        assert!(block_on(limited.execute(request)).is_err());
+    }
+}
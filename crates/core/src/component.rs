@@ -1,10 +1,15 @@
 //! Component System - L5
 
+use crate::portal::PortalTarget;
+use crate::security::XssProtection;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{Element as DomElement, Event, HtmlElement, HtmlInputElement, MouseEvent, Node};
+use web_sys::{
+    Element as DomElement, Event, FocusEvent, HtmlElement, HtmlInputElement, KeyboardEvent,
+    MouseEvent, Node, PointerEvent,
+};
 
 /// Virtual DOM Element
 #[derive(Clone)]
@@ -12,10 +17,21 @@ pub enum Element {
     Text(String),
     Node {
         tag: String,
-        props: Props,
+        /// Boxed: `Props` is a few hundred bytes of handlers and attributes,
+        /// and inline it would make every `Element`, text included, that big
+        props: Box<Props>,
         children: Vec<Element>,
     },
     Component(Box<dyn Component>),
+    /// Children mounted into another DOM node; see `crate::portal`
+    Portal {
+        target: PortalTarget,
+        children: Vec<Element>,
+    },
+    /// Children spliced into the parent's in its place, so an empty one
+    /// leaves no node at all. Only meaningful among children: a component's
+    /// root is still one node.
+    Fragment(Vec<Element>),
 }
 
 impl std::fmt::Debug for Element {
@@ -30,22 +46,244 @@ impl std::fmt::Debug for Element {
                     .finish()
             }
             Element::Component(_) => f.debug_tuple("Component").field(&"dyn Component").finish(),
+            Element::Portal { target, children } => f
+                .debug_struct("Portal")
+                .field("target", target)
+                .field("children", children)
+                .finish(),
+            Element::Fragment(children) => f.debug_tuple("Fragment").field(children).finish(),
         }
     }
 }
 
+/// A DOM element the application keeps hold of, e.g. as a portal target
+#[derive(Clone, Default)]
+pub struct NodeRef(Rc<RefCell<Option<DomElement>>>);
+
+impl NodeRef {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<DomElement> {
+        self.0.borrow().clone()
+    }
+
+    pub fn set(&self, element: Option<DomElement>) {
+        *self.0.borrow_mut() = element;
+    }
+}
+
+/// Two refs are equal when they are the same ref, not when they hold the
+/// same element
+impl PartialEq for NodeRef {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for NodeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NodeRef").field(&self.0.borrow().is_some()).finish()
+    }
+}
+
 /// Component properties
 #[derive(Default, Clone)]
 pub struct Props {
     pub class: Option<String>,
     pub id: Option<String>,
+    /// Tells the element apart from its siblings, so the reconciler moves
+    /// it along with its item instead of patching whichever element ends up
+    /// at its index; see `list`. Not rendered.
+    pub key: Option<String>,
     pub on_click: Option<Rc<dyn Fn()>>,
     pub on_submit: Option<Rc<dyn Fn(Event)>>,
     pub on_change: Option<Rc<dyn Fn(String)>>,
     pub on_input: Option<Rc<dyn Fn(String)>>,
+    /// Receives the `KeyboardEvent.key` value
+    pub on_keydown: Option<Rc<dyn Fn(String)>>,
+    /// Fires when focus moves into this element or its descendants
+    pub on_focus_in: Option<Rc<dyn Fn()>>,
+    /// Fires when focus moves outside this element and its descendants
+    pub on_focus_out: Option<Rc<dyn Fn()>>,
+    /// Receives `true` when a mouse or pen pointer enters the element and
+    /// `false` when it leaves; touch pointers are ignored
+    pub on_hover: Option<Rc<dyn Fn(bool)>>,
+    /// Receives the pointer's `clientX` and `clientY`; the default action
+    /// (text selection, touch scrolling) is prevented
+    pub on_pointer_down: Option<Rc<dyn Fn(f64, f64)>>,
+    /// Fires when a resource such as an `<img>` source fails to load
+    pub on_error: Option<Rc<dyn Fn()>>,
+    /// Receives the element's `scrollTop` and `clientHeight` after it scrolls
+    pub on_scroll: Option<Rc<dyn Fn(f64, f64)>>,
     pub attributes: Vec<(String, String)>,
 }
 
+/// Typed setters for common attributes, so accessible markup doesn't have
+/// to spell out attribute names and values:
+///
+/// ```ignore
+/// Props { on_click: Some(toggle), ..Default::default() }
+///     .aria_expanded(open)
+///     .aria_label("Show filters")
+/// ```
+impl Props {
+    /// Set `name`, replacing a value set before
+    pub fn attr(mut self, name: &str, value: impl Into<String>) -> Self {
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.attributes.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    fn without_attr(mut self, name: &str) -> Self {
+        self.attributes.retain(|(existing, _)| existing != name);
+        self
+    }
+
+    pub fn role(self, role: &str) -> Self {
+        self.attr("role", role)
+    }
+
+    pub fn aria_label(self, label: impl Into<String>) -> Self {
+        self.attr("aria-label", label)
+    }
+
+    /// `false` leaves the attribute off: content is exposed unless hidden,
+    /// and `aria-hidden="false"` can re-expose content inside hidden
+    /// ancestors in some screen readers
+    pub fn aria_hidden(self, hidden: bool) -> Self {
+        if hidden {
+            self.attr("aria-hidden", "true")
+        } else {
+            self.without_attr("aria-hidden")
+        }
+    }
+
+    /// Written as `"true"` or `"false"`; both tell assistive technology the
+    /// element controls something collapsible
+    pub fn aria_expanded(self, expanded: bool) -> Self {
+        self.attr("aria-expanded", expanded.to_string())
+    }
+
+    pub fn aria_live(self, politeness: crate::announce::Politeness) -> Self {
+        self.attr("aria-live", politeness.as_str())
+    }
+
+    /// `-1` makes the element focusable by script but not by Tab
+    pub fn tab_index(self, index: i32) -> Self {
+        self.attr("tabindex", index.to_string())
+    }
+
+    /// A boolean attribute such as `checked` or `disabled`, on or off.
+    /// Off is written as `"false"`, which renders as no attribute but still
+    /// declares it, so a controlled checkbox is unchecked again on re-render.
+    pub fn bool_attr(self, name: &str, on: bool) -> Self {
+        self.attr(name, if on { "" } else { "false" })
+    }
+
+    pub fn checked(self, checked: bool) -> Self {
+        self.bool_attr("checked", checked)
+    }
+
+    pub fn disabled(self, disabled: bool) -> Self {
+        self.bool_attr("disabled", disabled)
+    }
+
+    pub fn selected(self, selected: bool) -> Self {
+        self.bool_attr("selected", selected)
+    }
+
+    /// The attributes as written to HTML and the DOM: boolean attributes
+    /// set to `"false"` are left out
+    pub(crate) fn rendered_attributes(&self) -> impl Iterator<Item = &(String, String)> {
+        self.attributes
+            .iter()
+            .filter(|(name, value)| !(boolean_property(name).is_some() && value == "false"))
+    }
+}
+
+/// Attributes that are on by being present, with the DOM property that
+/// holds their live state
+const BOOLEAN_ATTRIBUTES: &[(&str, &str)] = &[
+    ("autofocus", "autofocus"),
+    ("checked", "checked"),
+    ("disabled", "disabled"),
+    ("hidden", "hidden"),
+    ("multiple", "multiple"),
+    ("open", "open"),
+    ("readonly", "readOnly"),
+    ("required", "required"),
+    ("selected", "selected"),
+];
+
+/// Attributes whose DOM property the user changes by interacting, so every
+/// render that declares them sets the property again
+pub(crate) const CONTROLLED_ATTRIBUTES: &[&str] = &["checked", "selected", "value"];
+
+fn boolean_property(name: &str) -> Option<&'static str> {
+    BOOLEAN_ATTRIBUTES
+        .iter()
+        .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+        .map(|(_, property)| *property)
+}
+
+/// Form controls whose `value` property is their live value
+fn has_value_property(element: &DomElement) -> bool {
+    ["input", "textarea", "select"]
+        .iter()
+        .any(|tag| element.tag_name().eq_ignore_ascii_case(tag))
+}
+
+/// Set a DOM property, unless it already has the value; an equal `value`
+/// left alone keeps the caret where it is
+fn set_property(element: &DomElement, property: &str, value: JsValue) {
+    let current = js_sys::Reflect::get(element, &property.into()).unwrap_or(JsValue::UNDEFINED);
+    if current != value {
+        let _ = js_sys::Reflect::set(element, &property.into(), &value);
+    }
+}
+
+/// Set attribute `name` on `element` along with the property behind it:
+/// `checked`, `disabled` and the other boolean attributes set theirs to
+/// `true`, or `false` for the value `"false"`; `value` sets a form
+/// control's value
+pub(crate) fn set_dom_attribute(element: &DomElement, name: &str, value: &str) {
+    if let Some(property) = boolean_property(name) {
+        let on = value != "false";
+        if on {
+            element.set_attribute(name, value).unwrap();
+        } else {
+            let _ = element.remove_attribute(name);
+        }
+        set_property(element, property, JsValue::from_bool(on));
+        return;
+    }
+
+    element.set_attribute(name, value).unwrap();
+    if name == "value" && has_value_property(element) {
+        set_property(element, "value", JsValue::from_str(value));
+    }
+}
+
+/// Remove attribute `name` from `element` and clear the property behind it
+pub(crate) fn remove_dom_attribute(element: &DomElement, name: &str) {
+    let _ = element.remove_attribute(name);
+    if let Some(property) = boolean_property(name) {
+        set_property(element, property, JsValue::FALSE);
+    } else if name == "value" && has_value_property(element) {
+        set_property(element, "value", JsValue::from_str(""));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,12 +319,12 @@ mod tests {
         
         let element = Element::Node {
             tag: "input".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 on_change: Some(Rc::new(move |v: String| {
                     *value_ref.borrow_mut() = v;
                 })),
                 ..Props::default()
-            },
+            }),
             children: vec![],
         };
         
@@ -96,6 +334,132 @@ mod tests {
             assert!(props.on_change.is_some());
         }
     }
+
+    #[test]
+    fn test_aria_helpers_render_attributes() {
+        let element = Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props::default()
+                .aria_expanded(false)
+                .aria_label("Filters")
+                .aria_hidden(true)
+                .aria_expanded(true)
+                .tab_index(-1)
+                .aria_live(crate::announce::Politeness::Polite)
+                .aria_hidden(false)),
+            children: vec![],
+        };
+
+        assert_eq!(
+            element.to_html(),
+            "<button aria-expanded=\"true\" aria-label=\"Filters\" tabindex=\"-1\" aria-live=\"polite\"></button>"
+        );
+
+        let checkbox = Element::Node {
+            tag: "input".to_string(),
+            props: Box::new(Props::default().attr("type", "checkbox").checked(false).disabled(true)),
+            children: vec![],
+        };
+        assert_eq!(checkbox.to_html(), "<input type=\"checkbox\" disabled=\"\">");
+
+        let dialog = Props::default().role("dialog").role("alertdialog");
+        assert_eq!(dialog.attributes, vec![("role".to_string(), "alertdialog".to_string())]);
+    }
+
+    #[test]
+    fn test_to_html_escapes_and_closes_tags() {
+        let element = Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("card".to_string()),
+                attributes: vec![("title".to_string(), "\"quoted\"".to_string())],
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Text("<b>&</b>".to_string()),
+                Element::Node {
+                    tag: "br".to_string(),
+                    props: Box::default(),
+                    children: vec![],
+                },
+            ],
+        };
+
+        assert_eq!(
+            element.to_html(),
+            "<div class=\"card\" title=\"&quot;quoted&quot;\">&lt;b&gt;&amp;&lt;&#x2F;b&gt;<br></div>"
+        );
+    }
+
+    #[test]
+    fn test_show_builds_only_the_shown_branch() {
+        let built = std::cell::Cell::new(0);
+        let hint = || {
+            built.set(built.get() + 1);
+            Element::Node {
+                tag: "p".to_string(),
+                props: Box::default(),
+                children: vec![Element::Text("Saved".to_string())],
+            }
+        };
+
+        let hidden = show(false, hint);
+        assert_eq!(built.get(), 0);
+        assert!(matches!(&hidden, Element::Fragment(children) if children.is_empty()));
+        assert_eq!(hidden.to_html(), "");
+
+        // Among siblings it takes no place at all
+        let row = Element::Node {
+            tag: "div".to_string(),
+            props: Box::default(),
+            children: vec![Element::Text("a".to_string()), hidden, Element::Text("b".to_string())],
+        };
+        assert_eq!(row.to_html(), "<div>ab</div>");
+        let Element::Node { children, .. } = &row else { unreachable!() };
+        assert_eq!(flatten_fragments(children).len(), 2);
+
+        assert_eq!(show(true, hint).to_html(), "<p>Saved</p>");
+        assert_eq!(built.get(), 1);
+
+        let fallback = || Element::Text("Unsaved".to_string());
+        assert_eq!(show_else(false, hint, fallback).to_html(), "Unsaved");
+        assert_eq!(show_else(true, hint, fallback).to_html(), "<p>Saved</p>");
+        assert_eq!(built.get(), 2);
+    }
+
+    #[test]
+    fn test_list_keys_its_items() {
+        let item = |name: &str| Element::Node {
+            tag: "li".to_string(),
+            props: Box::default(),
+            children: vec![Element::Text(name.to_string())],
+        };
+        let todos = [(7, "Buy milk"), (9, "Walk the dog")];
+
+        let items = list(&todos, |(id, name)| (id.to_string(), item(name)));
+        let keys: Vec<_> = items
+            .iter()
+            .map(|item| match item {
+                Element::Node { props, .. } => props.key.clone(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keys, vec![Some("7".to_string()), Some("9".to_string())]);
+        assert_eq!(items[0].to_html(), "<li>Buy milk</li>");
+        assert!(list(Vec::<u32>::new(), |id| (id.to_string(), item("never"))).is_empty());
+
+        let unique = [Some("7".to_string()), Some("9".to_string())];
+        assert!(key_problems(&unique).is_empty());
+        let broken = [Some("7".to_string()), Some("7".to_string()), Some(String::new()), None];
+        assert_eq!(
+            key_problems(&broken),
+            vec![
+                "List item 1 repeats the key \"7\"; keys must be unique",
+                "List item 2 has an empty key",
+                "List item 3 isn't an element node, so it can't carry a key",
+            ]
+        );
+    }
 }
 
 impl std::fmt::Debug for Props {
@@ -103,10 +467,18 @@ impl std::fmt::Debug for Props {
         f.debug_struct("Props")
             .field("class", &self.class)
             .field("id", &self.id)
+            .field("key", &self.key)
             .field("on_click", &self.on_click.as_ref().map(|_| "Fn()"))
             .field("on_submit", &self.on_submit.as_ref().map(|_| "Fn(Event)"))
             .field("on_change", &self.on_change.as_ref().map(|_| "Fn(String)"))
             .field("on_input", &self.on_input.as_ref().map(|_| "Fn(String)"))
+            .field("on_keydown", &self.on_keydown.as_ref().map(|_| "Fn(String)"))
+            .field("on_focus_in", &self.on_focus_in.as_ref().map(|_| "Fn()"))
+            .field("on_focus_out", &self.on_focus_out.as_ref().map(|_| "Fn()"))
+            .field("on_hover", &self.on_hover.as_ref().map(|_| "Fn(bool)"))
+            .field("on_pointer_down", &self.on_pointer_down.as_ref().map(|_| "Fn(f64, f64)"))
+            .field("on_error", &self.on_error.as_ref().map(|_| "Fn()"))
+            .field("on_scroll", &self.on_scroll.as_ref().map(|_| "Fn(f64, f64)"))
             .field("attributes", &self.attributes)
             .finish()
     }
@@ -139,6 +511,14 @@ pub trait Component: 'static {
 }
 
 impl Element {
+    /// Render `children` into `target` instead of in place
+    pub fn portal(target: impl Into<PortalTarget>, children: Vec<Element>) -> Self {
+        Element::Portal {
+            target: target.into(),
+            children,
+        }
+    }
+
     pub fn to_dom(&self) -> Node {
         match self {
             Element::Text(text) => web_sys::window()
@@ -165,105 +545,398 @@ impl Element {
 
                 // Apply attributes
                 for (key, value) in &props.attributes {
-                    element.set_attribute(key, value).unwrap();
+                    set_dom_attribute(&element, key, value);
                 }
 
-                // Handle click event
-                if let Some(on_click) = &props.on_click {
-                    if let Some(html_element) = element.dyn_ref::<HtmlElement>() {
-                        let handler = on_click.clone();
-                        let closure = Closure::wrap(Box::new(move |_event: MouseEvent| {
-                            handler();
-                        })
-                            as Box<dyn FnMut(_)>);
-
-                        html_element.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                        closure.forget();
-                    }
+                attach_handlers(&element, tag, props);
+
+                // Add children
+                for child in children {
+                    element.append_child(&child.to_dom()).unwrap();
                 }
 
-                // Handle submit event for forms
-                if let Some(on_submit) = &props.on_submit {
-                    if tag == "form" {
-                        if let Some(form_element) = element.dyn_ref::<HtmlElement>() {
-                            let handler = on_submit.clone();
-                            let closure = Closure::wrap(Box::new(move |event: Event| {
-                                event.prevent_default(); // Prevent form submission
-                                handler(event);
-                            })
-                                as Box<dyn FnMut(_)>);
-
-                            form_element.set_onsubmit(Some(closure.as_ref().unchecked_ref()));
-                            closure.forget();
-                        }
+                // A select's value can only pick among options it has
+                if let Some((_, value)) = props.attributes.iter().find(|(key, _)| key == "value") {
+                    if tag.eq_ignore_ascii_case("select") {
+                        set_property(&element, "value", JsValue::from_str(value));
                     }
                 }
 
-                // Handle change event for inputs
-                if let Some(on_change) = &props.on_change {
-                    if tag == "input" || tag == "select" || tag == "textarea" {
-                        if let Some(input_element) = element.dyn_ref::<HtmlInputElement>() {
-                            let handler = on_change.clone();
-                            let closure = Closure::wrap(Box::new(move |_event: Event| {
-                                if let Some(target) = _event.target() {
-                                    if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
-                                        handler(input.value());
-                                    }
-                                }
-                            })
-                                as Box<dyn FnMut(_)>);
-
-                            input_element.set_onchange(Some(closure.as_ref().unchecked_ref()));
-                            closure.forget();
+                element.into()
+            }
+            Element::Component(component) => component.render().to_dom(),
+            Element::Portal { target, children } => crate::portal::mount(target, children),
+            Element::Fragment(children) => {
+                let fragment = web_sys::window().unwrap().document().unwrap().create_document_fragment();
+                for child in children {
+                    fragment.append_child(&child.to_dom()).unwrap();
+                }
+                fragment.into()
+            }
+        }
+    }
+}
+
+/// Listen for the events `props` has handlers for, on an element created
+/// or adopted for `tag`
+pub(crate) fn attach_handlers(element: &DomElement, tag: &str, props: &Props) {
+    // Handle click event
+    if let Some(on_click) = &props.on_click {
+        if let Some(html_element) = element.dyn_ref::<HtmlElement>() {
+            let handler = on_click.clone();
+            let closure = Closure::wrap(Box::new(move |_event: MouseEvent| {
+                handler();
+            })
+                as Box<dyn FnMut(_)>);
+
+            html_element.set_onclick(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        }
+    }
+
+    // Handle submit event for forms
+    if let Some(on_submit) = &props.on_submit {
+        if tag == "form" {
+            if let Some(form_element) = element.dyn_ref::<HtmlElement>() {
+                let handler = on_submit.clone();
+                let closure = Closure::wrap(Box::new(move |event: Event| {
+                    event.prevent_default(); // Prevent form submission
+                    handler(event);
+                })
+                    as Box<dyn FnMut(_)>);
+
+                form_element.set_onsubmit(Some(closure.as_ref().unchecked_ref()));
+                closure.forget();
+            }
+        }
+    }
+
+    // Handle change event for inputs
+    if let Some(on_change) = &props.on_change {
+        if tag == "input" || tag == "select" || tag == "textarea" {
+            if let Some(input_element) = element.dyn_ref::<HtmlInputElement>() {
+                let handler = on_change.clone();
+                let closure = Closure::wrap(Box::new(move |_event: Event| {
+                    if let Some(target) = _event.target() {
+                        if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+                            handler(input.value());
                         }
                     }
-                }
+                })
+                    as Box<dyn FnMut(_)>);
 
-                // Handle input event for real-time updates
-                if let Some(on_input) = &props.on_input {
-                    if tag == "input" || tag == "textarea" {
-                        if let Some(input_element) = element.dyn_ref::<HtmlInputElement>() {
-                            let handler = on_input.clone();
-                            let closure = Closure::wrap(Box::new(move |_event: Event| {
-                                if let Some(target) = _event.target() {
-                                    if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
-                                        handler(input.value());
-                                    }
-                                }
-                            })
-                                as Box<dyn FnMut(_)>);
-
-                            input_element.set_oninput(Some(closure.as_ref().unchecked_ref()));
-                            closure.forget();
+                input_element.set_onchange(Some(closure.as_ref().unchecked_ref()));
+                closure.forget();
+            }
+        }
+    }
+
+    // Handle input event for real-time updates
+    if let Some(on_input) = &props.on_input {
+        if tag == "input" || tag == "textarea" {
+            if let Some(input_element) = element.dyn_ref::<HtmlInputElement>() {
+                let handler = on_input.clone();
+                let closure = Closure::wrap(Box::new(move |_event: Event| {
+                    if let Some(target) = _event.target() {
+                        if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+                            handler(input.value());
                         }
                     }
+                })
+                    as Box<dyn FnMut(_)>);
+
+                input_element.set_oninput(Some(closure.as_ref().unchecked_ref()));
+                closure.forget();
+            }
+        }
+    }
+
+    // Handle keyboard navigation
+    if let Some(on_keydown) = &props.on_keydown {
+        let handler = on_keydown.clone();
+        let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            handler(event.key());
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    // Handle focus entering the element's subtree
+    if let Some(on_focus_in) = &props.on_focus_in {
+        let handler = on_focus_in.clone();
+        let closure = Closure::wrap(Box::new(move |_event: FocusEvent| {
+            handler();
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("focusin", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    // Handle focus leaving the element's subtree
+    if let Some(on_focus_out) = &props.on_focus_out {
+        let handler = on_focus_out.clone();
+        let container = element.clone();
+        let closure = Closure::wrap(Box::new(move |event: FocusEvent| {
+            let next = event.related_target().and_then(|t| t.dyn_into::<Node>().ok());
+            if !container.contains(next.as_ref()) {
+                handler();
+            }
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("focusout", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    // Handle hovering; a touch pointer leaves as soon as it
+    // lifts, so touch is left to click handlers
+    if let Some(on_hover) = &props.on_hover {
+        for (event_name, entered) in [("pointerenter", true), ("pointerleave", false)] {
+            let handler = on_hover.clone();
+            let closure = Closure::wrap(Box::new(move |event: PointerEvent| {
+                if event.pointer_type() != "touch" {
+                    handler(entered);
                 }
+            })
+                as Box<dyn FnMut(_)>);
 
-                // Add children
+            element
+                .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+                .unwrap();
+            closure.forget();
+        }
+    }
+
+    // Handle the start of a pointer drag
+    if let Some(on_pointer_down) = &props.on_pointer_down {
+        let handler = on_pointer_down.clone();
+        let closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+            event.prevent_default();
+            handler(event.client_x() as f64, event.client_y() as f64);
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("pointerdown", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    // Handle a failed load
+    if let Some(on_error) = &props.on_error {
+        let handler = on_error.clone();
+        let closure = Closure::wrap(Box::new(move |_event: Event| {
+            handler();
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("error", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    // Handle scrolling of the element itself
+    if let Some(on_scroll) = &props.on_scroll {
+        let handler = on_scroll.clone();
+        let target = element.clone();
+        let closure = Closure::wrap(Box::new(move |_event: Event| {
+            handler(target.scroll_top() as f64, target.client_height() as f64);
+        })
+            as Box<dyn FnMut(_)>);
+
+        element
+            .add_event_listener_with_callback("scroll", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+}
+
+/// Elements that never have children or a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+impl Element {
+    /// Serialize to an HTML string; event handlers are dropped
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        self.write_html(&mut html);
+        html
+    }
+
+    fn write_html(&self, html: &mut String) {
+        match self {
+            Element::Text(text) => html.push_str(&XssProtection::sanitize_html(text)),
+            Element::Node {
+                tag,
+                props,
+                children,
+            } => {
+                html.push('<');
+                html.push_str(tag);
+                if let Some(id) = &props.id {
+                    html.push_str(&format!(" id=\"{}\"", XssProtection::sanitize_html(id)));
+                }
+                if let Some(class) = &props.class {
+                    html.push_str(&format!(" class=\"{}\"", XssProtection::sanitize_html(class)));
+                }
+                for (key, value) in props.rendered_attributes() {
+                    html.push_str(&format!(" {}=\"{}\"", key, XssProtection::sanitize_html(value)));
+                }
+                html.push('>');
+
+                if VOID_ELEMENTS.contains(&tag.as_str()) {
+                    return;
+                }
                 for child in children {
-                    element.append_child(&child.to_dom()).unwrap();
+                    child.write_html(html);
                 }
+                html.push_str(&format!("</{}>", tag));
+            }
+            Element::Component(component) => component.render().write_html(html),
+            // Portal children are mounted in the browser
+            Element::Portal { .. } => html.push_str("<!--l9-portal-->"),
+            Element::Fragment(children) => {
+                for child in children {
+                    child.write_html(html);
+                }
+            }
+        }
+    }
+}
 
-                element.into()
+/// `children` as they stand in the DOM, with fragments spliced in
+pub(crate) fn flatten_fragments(children: &[Element]) -> Vec<&Element> {
+    let mut flat = Vec::with_capacity(children.len());
+    for child in children {
+        match child {
+            Element::Fragment(inner) => flat.extend(flatten_fragments(inner)),
+            child => flat.push(child),
+        }
+    }
+    flat
+}
+
+/// `content()` when `cond` holds, otherwise an empty fragment: no node and
+/// no markup. `content` is only called when shown.
+///
+/// ```ignore
+/// show(errors.contains_key("username"), || error_message(&errors["username"]))
+/// ```
+pub fn show(cond: bool, content: impl FnOnce() -> Element) -> Element {
+    if cond {
+        content()
+    } else {
+        Element::Fragment(vec![])
+    }
+}
+
+/// `then()` when `cond` holds, otherwise `otherwise()`; only the chosen
+/// branch is built
+pub fn show_else(cond: bool, then: impl FnOnce() -> Element, otherwise: impl FnOnce() -> Element) -> Element {
+    if cond {
+        then()
+    } else {
+        otherwise()
+    }
+}
+
+/// Keyed children built from `items`: `render` returns each item's key and
+/// element, and the key goes on the element's props
+///
+/// ```ignore
+/// Element::Node {
+///     tag: "ul".to_string(),
+///     props: Box::default(),
+///     children: list(&todos, |todo| (todo.id.to_string(), todo_item(todo))),
+/// }
+/// ```
+///
+/// Keys must be unique among the items and only element nodes can carry
+/// one. Debug builds warn about empty, duplicate and uncarried keys; the
+/// reconciler then falls back to matching the children by index.
+pub fn list<T>(items: impl IntoIterator<Item = T>, mut render: impl FnMut(T) -> (String, Element)) -> Vec<Element> {
+    let mut keys = Vec::new();
+    let children: Vec<Element> = items
+        .into_iter()
+        .map(|item| {
+            let (key, mut element) = render(item);
+            if let Element::Node { props, .. } = &mut element {
+                props.key = Some(key.clone());
+                keys.push(Some(key));
+            } else {
+                keys.push(None);
             }
-            Element::Component(component) => component.render().to_dom(),
+            element
+        })
+        .collect();
+
+    if cfg!(debug_assertions) {
+        for problem in key_problems(&keys) {
+            warn(&problem);
+        }
+    }
+    children
+}
+
+/// What is wrong with a list's keys, one message per problem; `None` is an
+/// item that couldn't carry its key
+fn key_problems(keys: &[Option<String>]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut problems = Vec::new();
+    for (index, key) in keys.iter().enumerate() {
+        match key {
+            None => problems.push(format!("List item {} isn't an element node, so it can't carry a key", index)),
+            Some(key) if key.is_empty() => problems.push(format!("List item {} has an empty key", index)),
+            Some(key) if !seen.insert(key) => {
+                problems.push(format!("List item {} repeats the key {:?}; keys must be unique", index, key))
+            }
+            Some(_) => {}
         }
     }
+    problems
+}
+
+fn warn(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::warn_1(&message.into());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tracing::warn!("{}", message);
 }
 
 /// Reactive state hook
 #[derive(Clone)]
 pub struct State<T> {
     value: Rc<RefCell<T>>,
+    /// Component re-rendered on `set`, for state set from event handlers
+    owner: Option<u32>,
 }
 
 impl<T: Clone> State<T> {
     pub fn new(initial: T) -> Self {
         State {
             value: Rc::new(RefCell::new(initial)),
+            owner: None,
         }
     }
 
+    pub(crate) fn owned_by(mut self, component_id: u32) -> Self {
+        self.owner = Some(component_id);
+        self
+    }
+
     pub fn get(&self) -> T {
         self.value.borrow().clone()
     }
@@ -271,7 +944,10 @@ impl<T: Clone> State<T> {
     pub fn set(&self, new_value: T) {
         *self.value.borrow_mut() = new_value;
         // Trigger automatic re-render through the reactive system
-        crate::reactive_v2::queue_current_render();
+        match self.owner {
+            Some(component_id) => crate::reactive_v2::queue_component_render(component_id),
+            None => crate::reactive_v2::queue_current_render(),
+        }
     }
 }
 
@@ -296,7 +972,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props: $crate::component::Props::default(),
+            props: Box::default(),
             children,
         }
     }};
@@ -305,7 +981,7 @@ macro_rules! view {
     (<$tag:ident />) => {{
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props: $crate::component::Props::default(),
+            props: Box::default(),
             children: vec![],
         }
     }};
@@ -321,7 +997,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -337,7 +1013,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -354,7 +1030,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -370,7 +1046,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -386,7 +1062,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -402,7 +1078,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -418,7 +1094,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -434,7 +1110,7 @@ macro_rules! view {
 
         $crate::component::Element::Node {
             tag: stringify!($tag).to_string(),
-            props,
+            props: Box::new(props),
             children,
         }
     }};
@@ -3,14 +3,21 @@
 //! This module provides a comprehensive CSS-in-Rust system with:
 //! - Runtime CSS generation and injection
 //! - Pseudo-class support (:hover, :focus, :active, etc.)
+//! - Nested selectors scoped to the generated class
 //! - Media query support with responsive breakpoints
+//! - Container queries, for components that adapt to their own space
 //! - CSS variables for dynamic theming
 //! - Scoped styles and CSS modules
 //! - Animation and transition utilities
+//! - Critical CSS collection, so server-rendered pages ship their styles
+//! - Vendor prefixes for properties that still need them
 
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use crate::component::{Component, Element, Props};
+use crate::hooks::use_widget_id;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlStyleElement;
 
@@ -27,6 +34,11 @@ pub struct StyleSheetManager {
     initialized: bool,
     /// Counter for generating unique class names
     class_counter: u32,
+    /// Class names of shared rules, keyed by their CSS
+    shared: HashMap<String, String>,
+    /// Classes the server already inlined, with the CSS it sent
+    server_rendered: HashSet<String>,
+    server_css: String,
 }
 
 impl StyleSheetManager {
@@ -35,12 +47,16 @@ impl StyleSheetManager {
             rules: HashMap::new(),
             initialized: false,
             class_counter: 0,
+            shared: HashMap::new(),
+            server_rendered: HashSet::new(),
+            server_css: String::new(),
         }
     }
 
-    /// Initialize the style element in the DOM
+    /// Initialize the style element in the DOM; outside the browser rules
+    /// are only recorded
     pub fn init(&mut self) {
-        if self.initialized {
+        if self.initialized || !cfg!(target_arch = "wasm32") {
             return;
         }
 
@@ -48,6 +64,13 @@ impl StyleSheetManager {
         let document = window.document().unwrap();
         let head = document.head().unwrap();
 
+        if let Some(critical) = document.get_element_by_id(CRITICAL_STYLES_ID) {
+            if let Some(classes) = critical.get_attribute(CRITICAL_CLASSES_ATTR) {
+                self.server_rendered = classes.split_whitespace().map(String::from).collect();
+            }
+            self.server_css = critical.text_content().unwrap_or_default();
+        }
+
         let style = document.create_element("style").unwrap();
         let style_element = style.dyn_into::<HtmlStyleElement>().unwrap();
         style_element.set_type("text/css");
@@ -79,6 +102,23 @@ impl StyleSheetManager {
         class_name
     }
 
+    /// Add a CSS rule, reusing the class of an identical rule added this
+    /// way before
+    pub fn add_shared_rule(&mut self, css_rule: &CssRule) -> String {
+        let key = format!("{:?}{}", css_rule.prefix, css_rule.to_css("&"));
+        if let Some(class_name) = self.shared.get(&key) {
+            return class_name.clone();
+        }
+        let class_name = self.add_rule(css_rule);
+        self.shared.insert(key, class_name.clone());
+        class_name
+    }
+
+    /// CSS text of the rule behind `class_name`
+    pub fn rule_css(&self, class_name: &str) -> Option<&str> {
+        self.rules.get(class_name).map(String::as_str)
+    }
+
     /// Update the style element with all current rules
     fn update_styles(&self) {
         if self.initialized {
@@ -89,8 +129,9 @@ impl StyleSheetManager {
                 if let Ok(style_element) = element.dyn_into::<HtmlStyleElement>() {
                     let css_content = self
                         .rules
-                        .values()
-                        .cloned()
+                        .iter()
+                        .filter(|(class_name, css)| !self.server_sent(class_name, css))
+                        .map(|(_, css)| css.clone())
                         .collect::<Vec<_>>()
                         .join("\n");
 
@@ -100,15 +141,24 @@ impl StyleSheetManager {
         }
     }
 
+    /// Whether the server's critical CSS already holds this rule. The text
+    /// is compared too, in case the client numbered its classes differently
+    fn server_sent(&self, class_name: &str, css: &str) -> bool {
+        self.server_rendered.contains(class_name)
+            && css.split("\n\n").all(|block| self.server_css.contains(block))
+    }
+
     /// Remove a CSS rule by class name
     pub fn remove_rule(&mut self, class_name: &str) {
         self.rules.remove(class_name);
+        self.shared.retain(|_, shared| shared != class_name);
         self.update_styles();
     }
 
     /// Clear all rules
     pub fn clear(&mut self) {
         self.rules.clear();
+        self.shared.clear();
         self.update_styles();
     }
 }
@@ -122,8 +172,14 @@ pub struct CssRule {
     pub properties: HashMap<String, String>,
     /// Pseudo-class styles (e.g., :hover, :focus)
     pub pseudo_classes: HashMap<String, HashMap<String, String>>,
+    /// Styles for selectors scoped to the generated class, in the order
+    /// they were added
+    pub nested: Vec<(String, HashMap<String, String>)>,
     /// Media query styles
     pub media_queries: HashMap<String, HashMap<String, String>>,
+    /// Container query styles, applied by the size of the nearest
+    /// ancestor with a `container-type`
+    pub container_queries: HashMap<String, HashMap<String, String>>,
     /// CSS variables used in this rule
     pub variables: HashMap<String, String>,
     /// Keyframe animations
@@ -135,45 +191,52 @@ impl CssRule {
         Self::default()
     }
 
-    /// Convert the rule to CSS text
+    /// Convert the rule to CSS text; declarations are sorted so equal rules
+    /// produce equal text
     fn to_css(&self, class_name: &str) -> String {
         let mut css_parts = Vec::new();
 
         // Base rule
         if !self.properties.is_empty() {
-            let props = self
-                .properties
-                .iter()
-                .map(|(k, v)| format!("  {}: {};", k, v))
-                .collect::<Vec<_>>()
-                .join("\n");
+            let props = declarations(&self.properties, "  ");
             css_parts.push(format!(".{} {{\n{}\n}}", class_name, props));
         }
 
         // Pseudo-classes
-        for (pseudo, props) in &self.pseudo_classes {
+        let mut pseudo_classes: Vec<_> = self.pseudo_classes.iter().collect();
+        pseudo_classes.sort_by_key(|(pseudo, _)| pseudo.as_str());
+        for (pseudo, props) in pseudo_classes {
             if !props.is_empty() {
-                let prop_text = props
-                    .iter()
-                    .map(|(k, v)| format!("  {}: {};", k, v))
-                    .collect::<Vec<_>>()
-                    .join("\n");
+                let prop_text = declarations(props, "  ");
                 css_parts.push(format!(".{}:{} {{\n{}\n}}", class_name, pseudo, prop_text));
             }
         }
 
-        // Media queries
-        for (query, props) in &self.media_queries {
+        // Nested selectors
+        for (selector, props) in &self.nested {
             if !props.is_empty() {
-                let prop_text = props
-                    .iter()
-                    .map(|(k, v)| format!("    {}: {};", k, v))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                css_parts.push(format!(
-                    "@media {} {{\n  .{} {{\n{}\n  }}\n}}",
-                    query, class_name, prop_text
-                ));
+                let prop_text = declarations(props, "  ");
+                css_parts.push(format!("{} {{\n{}\n}}", scope_selector(class_name, selector), prop_text));
+            }
+        }
+
+        // Media and container queries; min-width queries go narrowest first
+        // so the wider breakpoints win
+        let at_rules = [
+            ("media", &self.media_queries),
+            ("container", &self.container_queries),
+        ];
+        for (at_rule, queries) in at_rules {
+            let mut queries: Vec<_> = queries.iter().collect();
+            queries.sort_by_key(|(query, _)| (min_width(query), query.as_str()));
+            for (query, props) in queries {
+                if !props.is_empty() {
+                    let prop_text = declarations(props, "    ");
+                    css_parts.push(format!(
+                        "@{} {} {{\n  .{} {{\n{}\n  }}\n}}",
+                        at_rule, query, class_name, prop_text
+                    ));
+                }
             }
         }
 
@@ -186,11 +249,109 @@ impl CssRule {
     }
 }
 
+/// Scope each selector in a list to `.class_name`: `&` stands for the
+/// class itself, and selectors without it match descendants
+fn scope_selector(class_name: &str, selector: &str) -> String {
+    let class = format!(".{}", class_name);
+    selector
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            if part.contains('&') {
+                part.replace('&', &class)
+            } else {
+                format!("{} {}", class, part)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Properties some browsers still only honour with a `-webkit-` prefix
+const WEBKIT_PREFIXED: &[&str] = &[
+    "appearance",
+    "backface-visibility",
+    "background-clip",
+    "transform",
+    "user-select",
+];
+
+static VENDOR_PREFIXING: AtomicBool = AtomicBool::new(true);
+
+/// Turn automatic `-webkit-` prefixing on or off for rules built from now
+/// on; it is on by default
+pub fn set_vendor_prefixing(enabled: bool) {
+    VENDOR_PREFIXING.store(enabled, Ordering::Relaxed);
+}
+
+fn declarations(props: &HashMap<String, String>, indent: &str) -> String {
+    declarations_with(props, indent, VENDOR_PREFIXING.load(Ordering::Relaxed))
+}
+
+/// Declarations sorted by property, each prefixed variant just before its
+/// standard property unless the author already wrote it
+fn declarations_with(props: &HashMap<String, String>, indent: &str, prefix: bool) -> String {
+    let mut sorted: Vec<_> = props.iter().collect();
+    sorted.sort();
+    let mut lines = Vec::with_capacity(sorted.len());
+    for (k, v) in sorted {
+        if prefix && WEBKIT_PREFIXED.contains(&k.as_str()) {
+            let prefixed = format!("-webkit-{}", k);
+            if !props.contains_key(&prefixed) {
+                lines.push(format!("{}{}: {};", indent, prefixed, v));
+            }
+        }
+        lines.push(format!("{}{}: {};", indent, k, v));
+    }
+    lines.join("\n")
+}
+
+/// The pixel width of a `(min-width: Npx)` query
+fn min_width(query: &str) -> Option<u32> {
+    query
+        .trim()
+        .strip_prefix("(min-width:")?
+        .trim()
+        .strip_suffix("px)")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Normalise a keyframe selector list: `from` and `to` become `0%` and
+/// `100%`, and anything but a percentage in that range is an error
+fn keyframe_selector(selector: &str) -> Result<String, String> {
+    let stops = selector
+        .split(',')
+        .map(|stop| match stop.trim() {
+            "from" => Ok("0%".to_string()),
+            "to" => Ok("100%".to_string()),
+            stop => stop
+                .strip_suffix('%')
+                .and_then(|pct| pct.trim().parse::<f32>().ok())
+                .filter(|pct| (0.0..=100.0).contains(pct))
+                .map(|pct| format!("{}%", pct))
+                .ok_or_else(|| {
+                    format!(
+                        "invalid keyframe selector `{}`; expected `from`, `to` or a percentage from 0% to 100%",
+                        stop
+                    )
+                }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(stops.join(", "))
+}
+
 /// CSS animation definition
 #[derive(Clone, Debug)]
 pub struct Animation {
     pub name: String,
     pub keyframes: Vec<(String, HashMap<String, String>)>,
+    duration_ms: Option<u32>,
+    timing_function: Option<String>,
+    delay_ms: Option<u32>,
+    iteration_count: Option<f32>,
 }
 
 impl Animation {
@@ -198,26 +359,88 @@ impl Animation {
         Self {
             name,
             keyframes: Vec::new(),
+            duration_ms: None,
+            timing_function: None,
+            delay_ms: None,
+            iteration_count: None,
+        }
+    }
+
+    /// Add a keyframe at `from`, `to` or a percentage from `0%` to `100%`.
+    /// Panics on any other selector; see `try_keyframe`
+    pub fn keyframe(self, selector: &str, props: HashMap<String, String>) -> Self {
+        match self.try_keyframe(selector, props) {
+            Ok(animation) => animation,
+            Err(message) => panic!("{}", message),
         }
     }
 
-    pub fn keyframe(mut self, percentage: &str, props: HashMap<String, String>) -> Self {
-        self.keyframes.push((percentage.to_string(), props));
+    /// Like `keyframe`, but reports an invalid selector instead of
+    /// panicking. A selector seen before merges into that keyframe, later
+    /// properties winning
+    pub fn try_keyframe(mut self, selector: &str, props: HashMap<String, String>) -> Result<Self, String> {
+        let selector = keyframe_selector(selector)
+            .map_err(|message| format!("animation `{}`: {}", self.name, message))?;
+        match self.keyframes.iter_mut().find(|(existing, _)| *existing == selector) {
+            Some((_, existing)) => existing.extend(props),
+            None => self.keyframes.push((selector, props)),
+        }
+        Ok(self)
+    }
+
+    /// Run time of one cycle in milliseconds. Setting it makes
+    /// `CssBuilder::animation` apply the animation, not just define it
+    pub fn duration(mut self, ms: u32) -> Self {
+        self.duration_ms = Some(ms);
+        self
+    }
+
+    /// e.g. `ease-in-out` or `cubic-bezier(0.4, 0, 0.2, 1)`
+    pub fn timing_function(mut self, timing_function: &str) -> Self {
+        self.timing_function = Some(timing_function.to_string());
+        self
+    }
+
+    pub fn delay(mut self, ms: u32) -> Self {
+        self.delay_ms = Some(ms);
+        self
+    }
+
+    /// Number of cycles; fractions stop part way through
+    pub fn iteration_count(mut self, count: f32) -> Self {
+        self.iteration_count = Some(count.max(0.0));
+        self
+    }
+
+    /// Repeat forever
+    pub fn infinite(mut self) -> Self {
+        self.iteration_count = Some(f32::INFINITY);
         self
     }
 
+    /// The `animation` shorthand, once a duration is set
+    pub fn shorthand(&self) -> Option<String> {
+        let duration = self.duration_ms?;
+        let mut parts = vec![self.name.clone(), format!("{}ms", duration)];
+        if let Some(timing_function) = &self.timing_function {
+            parts.push(timing_function.clone());
+        }
+        if let Some(delay) = self.delay_ms {
+            parts.push(format!("{}ms", delay));
+        }
+        match self.iteration_count {
+            Some(count) if count.is_infinite() => parts.push("infinite".to_string()),
+            Some(count) => parts.push(count.to_string()),
+            None => {}
+        }
+        Some(parts.join(" "))
+    }
+
     fn to_css(&self) -> String {
         let keyframes = self
             .keyframes
             .iter()
-            .map(|(pct, props)| {
-                let prop_text = props
-                    .iter()
-                    .map(|(k, v)| format!("    {}: {};", k, v))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                format!("  {} {{\n{}\n  }}", pct, prop_text)
-            })
+            .map(|(pct, props)| format!("  {} {{\n{}\n  }}", pct, declarations(props, "    ")))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -249,8 +472,9 @@ impl CssBuilder {
         self
     }
 
-    /// Add a CSS property
-    pub fn property(mut self, key: &str, value: &str) -> Self {
+    /// Add a CSS property; the value can be a string or a typed
+    /// `CssValue` such as `px(16)`
+    pub fn property(mut self, key: &str, value: impl std::fmt::Display) -> Self {
         self.rule.properties.insert(key.to_string(), value.to_string());
         self
     }
@@ -294,6 +518,17 @@ impl CssBuilder {
         self
     }
 
+    /// Add styles for a selector scoped to the generated class, e.g.
+    /// `.icon` for descendants or `&.active` and `& > p` to compose on the
+    /// class itself. Styles for the same selector accumulate.
+    pub fn nested(mut self, selector: &str, props: HashMap<String, String>) -> Self {
+        match self.rule.nested.iter_mut().find(|(existing, _)| existing == selector) {
+            Some((_, existing)) => existing.extend(props),
+            None => self.rule.nested.push((selector.to_string(), props)),
+        }
+        self
+    }
+
     /// Add media query styles
     pub fn media(mut self, query: &str, props: HashMap<String, String>) -> Self {
         self.rule.media_queries.insert(query.to_string(), props);
@@ -307,6 +542,24 @@ impl CssBuilder {
         self
     }
 
+    /// Add container query styles
+    pub fn container(mut self, query: &str, props: HashMap<String, String>) -> Self {
+        self.rule.container_queries.insert(query.to_string(), props);
+        self
+    }
+
+    /// Add styles for when the surrounding container is at least as wide
+    /// as `breakpoint`, whatever the viewport
+    pub fn container_breakpoint(
+        mut self,
+        breakpoint: Breakpoint,
+        props: HashMap<String, String>,
+    ) -> Self {
+        let query = breakpoint.to_media_query();
+        self.rule.container_queries.insert(query, props);
+        self
+    }
+
     /// Add CSS variable
     pub fn variable(mut self, name: &str, value: &str) -> Self {
         self.rule.variables.insert(name.to_string(), value.to_string());
@@ -314,7 +567,16 @@ impl CssBuilder {
     }
 
     /// Add animation
+    /// Define the animation's keyframes with the rule, and apply it too if
+    /// it has a duration
     pub fn animation(mut self, animation: Animation) -> Self {
+        if let Some(shorthand) = animation.shorthand() {
+            let value = match self.rule.properties.get("animation") {
+                Some(existing) => format!("{}, {}", existing, shorthand),
+                None => shorthand,
+            };
+            self.rule.properties.insert("animation".to_string(), value);
+        }
         self.rule.animations.push(animation);
         self
     }
@@ -324,6 +586,114 @@ impl CssBuilder {
         let mut manager = STYLESHEET_MANAGER.lock();
         manager.add_rule(&self.rule)
     }
+
+    /// Like `build`, but identical rules share one class, so building in
+    /// `render` doesn't grow the stylesheet on every pass
+    pub fn build_shared(self) -> String {
+        let mut manager = STYLESHEET_MANAGER.lock();
+        manager.add_shared_rule(&self.rule)
+    }
+}
+
+/// Id of the `<style>` element holding a server-rendered page's CSS
+pub const CRITICAL_STYLES_ID: &str = "layer9-critical-styles";
+
+/// Attribute on that element listing the classes it covers, so the client
+/// runtime doesn't inject them again
+pub const CRITICAL_CLASSES_ATTR: &str = "data-l9-classes";
+
+/// The runtime rules a server-rendered page uses, gathered from its markup
+/// so they can be inlined in the `<head>` instead of waiting for hydration
+///
+/// ```ignore
+/// let body = page.render().to_html();
+/// let html = CriticalCss::from_html(&body).inline_into(&template.replace("{{content}}", &body));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CriticalCss {
+    classes: Vec<String>,
+    blocks: Vec<String>,
+}
+
+impl CriticalCss {
+    /// Collect the rules behind every class in `html`. Each class is taken
+    /// once, and blocks several rules share, such as the same keyframes,
+    /// are only kept the first time
+    pub fn from_html(html: &str) -> Self {
+        let manager = STYLESHEET_MANAGER.lock();
+        let mut critical = CriticalCss::default();
+        let mut seen_blocks = HashSet::new();
+
+        for class_name in class_names(html) {
+            if critical.classes.iter().any(|seen| seen == class_name) {
+                continue;
+            }
+            let Some(css) = manager.rule_css(class_name) else {
+                continue;
+            };
+            critical.classes.push(class_name.to_string());
+            for block in css.split("\n\n") {
+                if seen_blocks.insert(block) {
+                    critical.blocks.push(block.to_string());
+                }
+            }
+        }
+
+        critical
+    }
+
+    /// Classes whose rules were collected, in the order they appear
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    pub fn css(&self) -> String {
+        self.blocks.join("\n\n")
+    }
+
+    /// A `<style>` element with the rules, listing its classes for the
+    /// client; empty when nothing was collected
+    pub fn style_tag(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<style id=\"{}\" {}=\"{}\">\n{}\n</style>",
+            CRITICAL_STYLES_ID,
+            CRITICAL_CLASSES_ATTR,
+            self.classes.join(" "),
+            // Keep a stray `</style>` in a value from closing the element
+            self.css().replace("</", "<\\/")
+        )
+    }
+
+    /// Insert the style tag at the end of `document`'s `<head>`, or at the
+    /// start of the document if it has none
+    pub fn inline_into(&self, document: &str) -> String {
+        let tag = self.style_tag();
+        if tag.is_empty() {
+            return document.to_string();
+        }
+        match document.find("</head>") {
+            Some(end) => format!("{}{}\n{}", &document[..end], tag, &document[end..]),
+            None => format!("{}{}", tag, document),
+        }
+    }
+}
+
+/// Every class listed in a `class="..."` attribute
+fn class_names(html: &str) -> impl Iterator<Item = &str> {
+    html.match_indices("class=\"")
+        .filter(|(start, _)| html[..*start].ends_with(char::is_whitespace))
+        .filter_map(|(start, attr)| {
+            let value = &html[start + attr.len()..];
+            value.find('"').map(|end| &value[..end])
+        })
+        .flat_map(str::split_whitespace)
 }
 
 /// Predefined responsive breakpoints
@@ -442,6 +812,129 @@ impl CssVariables {
     }
 }
 
+static NEXT_THEME_PROVIDER_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Sets CSS custom properties on its own root element, so `var(--name)`
+/// below it resolves against the nearest provider. Nested providers
+/// override only the variables they set, and only within their subtree.
+///
+/// ```ignore
+/// ThemeProvider::new(css_props! { "background" => "#1a202c", "text" => "#e2e8f0" })
+///     .children(vec![page])
+/// ```
+pub struct ThemeProvider {
+    id: Option<String>,
+    theme: HashMap<String, String>,
+    children: Vec<Element>,
+}
+
+impl ThemeProvider {
+    /// Variable names may be given with or without the leading `--`
+    pub fn new(theme: HashMap<String, String>) -> Self {
+        ThemeProvider {
+            id: None,
+            theme: custom_properties(theme),
+            children: vec![],
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Switch the mounted provider's theme without rendering its children.
+    /// The provider is found by its `.id()`; without one the handle does
+    /// nothing.
+    pub fn handle(&self) -> ThemeHandle {
+        ThemeHandle {
+            id: self.id.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Changes the variables of a mounted `ThemeProvider`
+#[derive(Clone, Debug)]
+pub struct ThemeHandle {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    id: String,
+}
+
+impl ThemeHandle {
+    /// Replace the provider's variables; ones the new theme leaves out
+    /// fall back to an outer provider or the `var()` default
+    pub fn set(&self, theme: HashMap<String, String>) {
+        let theme = custom_properties(theme);
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(element) = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(&self.id))
+            .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let style = element.style();
+            let previous: Vec<String> = (0..style.length())
+                .map(|i| style.item(i))
+                .filter(|name| name.starts_with("--"))
+                .collect();
+            for name in previous {
+                style.remove_property(&name).ok();
+            }
+            for (name, value) in &theme {
+                style.set_property(name, value).ok();
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = theme;
+    }
+}
+
+/// `var(--name, fallback)`, for values a provider may not set
+pub fn css_var(name: &str, fallback: &str) -> String {
+    format!("var(--{}, {})", name.trim_start_matches("--"), fallback)
+}
+
+fn custom_properties(theme: HashMap<String, String>) -> HashMap<String, String> {
+    theme
+        .into_iter()
+        .map(|(name, value)| (format!("--{}", name.trim_start_matches("--")), value))
+        .collect()
+}
+
+impl Component for ThemeProvider {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-theme-{}", NEXT_THEME_PROVIDER_ID.fetch_add(1, Ordering::Relaxed))
+        });
+
+        // The provider only carries variables; `display: contents` keeps it
+        // out of the layout
+        let mut variables: Vec<_> = self.theme.iter().collect();
+        variables.sort();
+        let style = variables
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}; ", name, value))
+            .collect::<String>();
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("theme-provider".to_string()),
+                attributes: vec![("style".to_string(), format!("{}display: contents", style))],
+                ..Default::default()
+            }),
+            children: self.children.clone(),
+        }
+    }
+}
+
 /// Inject global CSS variables and base styles
 pub fn inject_global_styles() {
     // Initialize the stylesheet manager
@@ -569,7 +1062,9 @@ mod tests {
                 "color" => "red"
             },
             pseudo_classes: HashMap::new(),
+            nested: Vec::new(),
             media_queries: HashMap::new(),
+            container_queries: HashMap::new(),
             variables: HashMap::new(),
             animations: Vec::new(),
         };
@@ -594,6 +1089,25 @@ mod tests {
         assert!(builder.rule.pseudo_classes.contains_key("hover"));
     }
 
+    #[test]
+    fn test_nested_selectors_are_scoped_to_the_class() {
+        let rule = CssBuilder::new()
+            .property("padding", "1.5rem")
+            .nested(".title", css_props! { "font-weight" => "600" })
+            .nested("&:hover .icon, & > p", css_props! { "opacity" => "0.8" })
+            .nested(".title", css_props! { "margin" => "0" })
+            .breakpoint(Breakpoint::Md, css_props! { "padding" => "2rem" })
+            .rule;
+
+        let css = rule.to_css("card");
+        assert!(css.contains(".card .title {\n  font-weight: 600;\n  margin: 0;\n}"));
+        assert!(css.contains(".card:hover .icon, .card > p {\n  opacity: 0.8;\n}"));
+        assert_eq!(css.matches(".card .title").count(), 1);
+
+        // Nested rules precede media queries, so breakpoints still win
+        assert!(css.find(".card > p").unwrap() < css.find("@media (min-width: 768px)").unwrap());
+    }
+
     #[test]
     fn test_breakpoint_media_queries() {
         assert_eq!(Breakpoint::Sm.to_media_query(), "(min-width: 640px)");
@@ -601,6 +1115,24 @@ mod tests {
         assert_eq!(Breakpoint::Custom(500).to_media_query(), "(min-width: 500px)");
     }
 
+    #[test]
+    fn test_container_queries_follow_media_queries() {
+        let rule = CssBuilder::new()
+            .property("display", "flex")
+            .container_breakpoint(Breakpoint::Custom(640), css_props! { "gap" => "2rem" })
+            .container_breakpoint(Breakpoint::Custom(400), css_props! { "flex-direction" => "row" })
+            .breakpoint(Breakpoint::Lg, css_props! { "padding" => "1rem" })
+            .rule;
+
+        let css = rule.to_css("card");
+        let media = css.find("@media (min-width: 1024px)").unwrap();
+        let narrow = css
+            .find("@container (min-width: 400px) {\n  .card {\n    flex-direction: row;")
+            .unwrap();
+        let wide = css.find("@container (min-width: 640px)").unwrap();
+        assert!(media < narrow && narrow < wide);
+    }
+
     #[test]
     fn test_animation_generation() {
         let animation = Animation::new("test-anim".to_string())
@@ -614,4 +1146,228 @@ mod tests {
         assert!(css.contains("opacity: 0"));
         assert!(css.contains("opacity: 1"));
     }
+
+    #[test]
+    fn test_critical_css_is_inlined_for_server_rendered_pages() {
+        struct Badge;
+
+        impl Component for Badge {
+            fn render(&self) -> Element {
+                let class = CssBuilder::new()
+                    .prefix("badge")
+                    .property("color", "teal")
+                    .breakpoint(Breakpoint::Md, css_props! { "font-size" => "1.25rem" })
+                    .animation(
+                        Animation::new("badge-pop".to_string())
+                            .keyframe("from", css_props! { "opacity" => "0", "transform" => "scale(0.9)" })
+                            .keyframe("to", css_props! { "opacity" => "1", "transform" => "none" }),
+                    )
+                    .build_shared();
+                Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        class: Some(format!("{} badge", class)),
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text("New".to_string())],
+                }
+            }
+        }
+
+        // The same component twice yields one class and one copy of its rules
+        let body = format!("{}{}", Badge.render().to_html(), Badge.render().to_html());
+        let class = STYLESHEET_MANAGER
+            .lock()
+            .rules
+            .iter()
+            .find(|(_, css)| css.contains("color: teal"))
+            .map(|(class, _)| class.clone())
+            .unwrap();
+
+        let page = format!("<html><head><title>SSR</title></head><body>{}</body></html>", body);
+        let html = CriticalCss::from_html(&body).inline_into(&page);
+
+        assert!(html.contains(&format!("<span class=\"{} badge\">New</span>", class)));
+        assert!(html.contains(&format!(
+            "<style id=\"layer9-critical-styles\" data-l9-classes=\"{}\">",
+            class
+        )));
+        assert!(html.contains(&format!(".{} {{\n  color: teal;\n}}", class)));
+        assert!(html.contains("@media (min-width: 768px)"));
+        assert_eq!(html.matches("@keyframes badge-pop").count(), 1);
+        assert!(html.find("</style>").unwrap() < html.find("</head>").unwrap());
+
+        // Unstyled markup leaves the page alone
+        assert_eq!(CriticalCss::from_html("<p class=\"plain\"></p>").inline_into(&page), page);
+    }
+
+    #[test]
+    fn test_keyframe_selectors_are_validated() {
+        let animation = Animation::new("fade".to_string())
+            .keyframe("from", css_props! { "opacity" => "0" })
+            .keyframe(" 50% , 75%", css_props! { "opacity" => "0.8" })
+            .keyframe("0%", css_props! { "visibility" => "visible" })
+            .keyframe("to", css_props! { "opacity" => "1" });
+
+        let css = animation.to_css();
+        assert!(css.contains("  0% {\n    opacity: 0;\n    visibility: visible;\n  }"));
+        assert!(css.contains("  50%, 75% {"));
+        assert!(css.contains("  100% {"));
+        assert_eq!(css.matches("\n  0% {").count(), 1);
+
+        for invalid in ["50", "120%", "-5%", "middle", "10%,"] {
+            let result = Animation::new("fade".to_string()).try_keyframe(invalid, HashMap::new());
+            assert!(result.is_err(), "{} should be rejected", invalid);
+        }
+        let message = Animation::new("fade".to_string())
+            .try_keyframe("50", HashMap::new())
+            .unwrap_err();
+        assert!(message.contains("animation `fade`") && message.contains("`50`"));
+
+        let panicked = std::panic::catch_unwind(|| {
+            Animation::new("fade".to_string()).keyframe("half", HashMap::new())
+        });
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    fn test_animation_timing_applies_the_shorthand() {
+        let spin = Animation::new("spin".to_string())
+            .keyframe("to", css_props! { "transform" => "rotate(360deg)" })
+            .duration(1200)
+            .timing_function("linear")
+            .delay(100)
+            .infinite();
+        assert_eq!(spin.shorthand().as_deref(), Some("spin 1200ms linear 100ms infinite"));
+
+        let pulse = Animation::new("pulse".to_string()).duration(300).iteration_count(2.0);
+        let rule = CssBuilder::new().animation(spin).animation(pulse).rule;
+        assert_eq!(
+            rule.properties["animation"],
+            "spin 1200ms linear 100ms infinite, pulse 300ms 2"
+        );
+
+        // Without a duration the keyframes are only defined
+        let rule = CssBuilder::new().animation(Animation::new("idle".to_string())).rule;
+        assert!(!rule.properties.contains_key("animation"));
+    }
+
+    #[test]
+    fn test_vendor_prefixes_precede_the_standard_property() {
+        let rule = CssBuilder::new()
+            .property("transform", "rotate(45deg)")
+            .property("background-clip", "text")
+            .property("-webkit-background-clip", "padding-box")
+            .animation(
+                Animation::new("spin".to_string())
+                    .keyframe("to", css_props! { "transform" => "rotate(360deg)" }),
+            )
+            .rule;
+
+        let css = rule.to_css("icon");
+        assert!(css.contains("  -webkit-transform: rotate(45deg);\n  transform: rotate(45deg);"));
+        assert!(css.contains("    -webkit-transform: rotate(360deg);\n    transform: rotate(360deg);"));
+
+        // An authored prefix is kept as written, not duplicated
+        assert_eq!(css.matches("-webkit-background-clip").count(), 1);
+        assert!(css.contains("-webkit-background-clip: padding-box;"));
+
+        let props = css_props! { "transform" => "none", "color" => "red" };
+        assert_eq!(declarations_with(&props, "", false), "color: red;\ntransform: none;");
+    }
+
+    fn root_style(element: &Element) -> String {
+        match element {
+            Element::Node { props, .. } => props
+                .attributes
+                .iter()
+                .find(|(name, _)| name == "style")
+                .map(|(_, style)| style.clone())
+                .unwrap_or_default(),
+            _ => panic!("expected provider root"),
+        }
+    }
+
+    #[test]
+    fn test_theme_provider_scopes_variables_to_its_root() {
+        let dark = crate::component::State::new(false);
+        let themed = || {
+            let theme = if dark.get() {
+                css_props! { "background" => "#1a202c", "--text" => "#e2e8f0" }
+            } else {
+                css_props! { "background" => "#ffffff", "--text" => "#2d3748" }
+            };
+            ThemeProvider::new(theme)
+                .id("app-theme")
+                .children(vec![ThemeProvider::new(css_props! { "text" => "teal" }).render()])
+                .render()
+        };
+
+        let light = themed();
+        assert!(root_style(&light).contains("--background: #ffffff;"));
+
+        dark.set(true);
+        let toggled = themed();
+        assert!(root_style(&toggled).contains("--background: #1a202c;"));
+        assert!(root_style(&toggled).contains("--text: #e2e8f0;"));
+
+        // The nested provider only overrides what it sets
+        let Element::Node { children, .. } = &toggled else { unreachable!() };
+        assert_eq!(root_style(&children[0]), "--text: teal; display: contents");
+
+        assert_eq!(css_var("accent", "#667eea"), "var(--accent, #667eea)");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Themed;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Themed {
+        fn render(&self) -> Element {
+            ThemeProvider::new(css_props! { "accent" => "teal" }).render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_generated_theme_id_survives_rerenders() {
+        let themed = Themed;
+        let mut tree = crate::testing::render(&themed);
+        let id = |tree: &crate::testing::TestTree| match tree.find(".theme-provider") {
+            Some(Element::Node { props, .. }) => props.id.clone().unwrap(),
+            _ => panic!("expected provider root"),
+        };
+        let first = id(&tree);
+        assert!(first.starts_with("layer9-theme-"));
+
+        tree.rerender();
+        assert_eq!(id(&tree), first);
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_theme_handle_switches_mounted_variables() {
+        let provider = ThemeProvider::new(css_props! { "background" => "#ffffff", "accent" => "red" })
+            .id("mounted-theme");
+        let body = web_sys::window().unwrap().document().unwrap().body().unwrap();
+        let node = provider.render().to_dom();
+        body.append_child(&node).unwrap();
+
+        provider.handle().set(css_props! { "background" => "#1a202c" });
+
+        let root = node.dyn_into::<web_sys::HtmlElement>().unwrap();
+        let style = root.style();
+        assert_eq!(style.get_property_value("--background").unwrap().trim(), "#1a202c");
+        assert_eq!(style.get_property_value("--accent").unwrap(), "");
+
+        body.remove_child(&root).unwrap();
+    }
 }
\ No newline at end of file
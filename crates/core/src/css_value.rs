@@ -0,0 +1,208 @@
+//! CSS Values - Typed lengths for the CSS runtime
+//!
+//! `px(20)`, `rem(1.5)`, `percent(50)` and friends build a `CssValue` that
+//! prints with its unit, so a misspelled unit fails to compile instead of
+//! silently dropping the declaration. `css_props!` and
+//! `CssBuilder::property` take them alongside plain strings:
+//!
+//! ```ignore
+//! css_props! {
+//!     "width" => percent(100) - px(32),
+//!     "padding" => rem(1.5),
+//!     "margin" => px(0),
+//! }
+//! ```
+//!
+//! Adding or subtracting lengths with different units composes a `calc()`.
+
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+/// Units of a CSS length
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CssUnit {
+    Px,
+    Rem,
+    Em,
+    Percent,
+    Vh,
+    Vw,
+}
+
+impl CssUnit {
+    pub fn suffix(self) -> &'static str {
+        match self {
+            CssUnit::Px => "px",
+            CssUnit::Rem => "rem",
+            CssUnit::Em => "em",
+            CssUnit::Percent => "%",
+            CssUnit::Vh => "vh",
+            CssUnit::Vw => "vw",
+        }
+    }
+}
+
+/// A typed CSS length, or a `calc()` of them
+#[derive(Clone, Debug, PartialEq)]
+pub enum CssValue {
+    Length(f64, CssUnit),
+    Calc(Box<CssValue>, CalcOp, Box<CssValue>),
+    Scaled(Box<CssValue>, f64),
+}
+
+/// Operators joining the two sides of a `calc()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalcOp {
+    Add,
+    Sub,
+}
+
+pub fn px(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Px)
+}
+
+pub fn rem(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Rem)
+}
+
+pub fn em(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Em)
+}
+
+pub fn percent(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Percent)
+}
+
+pub fn vh(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Vh)
+}
+
+pub fn vw(value: impl Into<f64>) -> CssValue {
+    CssValue::Length(value.into(), CssUnit::Vw)
+}
+
+impl CssValue {
+    fn combine(self, op: CalcOp, other: CssValue) -> CssValue {
+        match (self, other) {
+            // Same units fold into one length
+            (CssValue::Length(a, unit), CssValue::Length(b, other_unit)) if unit == other_unit => {
+                CssValue::Length(if op == CalcOp::Add { a + b } else { a - b }, unit)
+            }
+            (a, b) => CssValue::Calc(Box::new(a), op, Box::new(b)),
+        }
+    }
+
+    /// The value as a term inside `calc()`, where lengths keep their unit
+    /// even when zero and nested expressions are parenthesised
+    fn write_term(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CssValue::Length(value, unit) => write!(f, "{}{}", value, unit.suffix()),
+            _ => {
+                write!(f, "(")?;
+                self.write_expression(f)?;
+                write!(f, ")")
+            }
+        }
+    }
+
+    fn write_expression(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CssValue::Length(..) => self.write_term(f),
+            CssValue::Calc(a, op, b) => {
+                a.write_term(f)?;
+                write!(f, " {} ", if *op == CalcOp::Add { '+' } else { '-' })?;
+                b.write_term(f)
+            }
+            CssValue::Scaled(value, factor) => {
+                value.write_term(f)?;
+                write!(f, " * {}", factor)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CssValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Zero is the same in every unit
+            CssValue::Length(value, _) if *value == 0.0 => write!(f, "0"),
+            CssValue::Length(..) => self.write_term(f),
+            _ => {
+                write!(f, "calc(")?;
+                self.write_expression(f)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Add for CssValue {
+    type Output = CssValue;
+
+    fn add(self, other: CssValue) -> CssValue {
+        self.combine(CalcOp::Add, other)
+    }
+}
+
+impl Sub for CssValue {
+    type Output = CssValue;
+
+    fn sub(self, other: CssValue) -> CssValue {
+        self.combine(CalcOp::Sub, other)
+    }
+}
+
+impl Mul<f64> for CssValue {
+    type Output = CssValue;
+
+    fn mul(self, factor: f64) -> CssValue {
+        match self {
+            CssValue::Length(value, unit) => CssValue::Length(value * factor, unit),
+            value => CssValue::Scaled(Box::new(value), factor),
+        }
+    }
+}
+
+impl From<CssValue> for String {
+    fn from(value: CssValue) -> String {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css_props;
+
+    #[test]
+    fn test_units_render_with_their_suffix() {
+        assert_eq!(px(20).to_string(), "20px");
+        assert_eq!(rem(1.5).to_string(), "1.5rem");
+        assert_eq!(em(0.75).to_string(), "0.75em");
+        assert_eq!(percent(50).to_string(), "50%");
+        assert_eq!(vh(100).to_string(), "100vh");
+        assert_eq!(vw(-10).to_string(), "-10vw");
+
+        // Zero needs no unit
+        assert_eq!(px(0).to_string(), "0");
+        assert_eq!(rem(-0.0).to_string(), "0");
+
+        let props = css_props! { "padding" => rem(1.5), "color" => "red" };
+        assert_eq!(props["padding"], "1.5rem");
+        assert_eq!(props["color"], "red");
+    }
+
+    #[test]
+    fn test_mixed_units_compose_calc() {
+        assert_eq!((px(20) + percent(10)).to_string(), "calc(20px + 10%)");
+        assert_eq!((percent(100) - px(32) - rem(2)).to_string(), "calc((100% - 32px) - 2rem)");
+        assert_eq!(((vh(100) - px(64)) * 0.5).to_string(), "calc((100vh - 64px) * 0.5)");
+
+        // Inside calc() a zero keeps its unit, as the expression requires
+        assert_eq!((px(0) + percent(10)).to_string(), "calc(0px + 10%)");
+
+        // Equal units fold
+        assert_eq!((px(20) + px(4)).to_string(), "24px");
+        assert_eq!((rem(1) * 1.5).to_string(), "1.5rem");
+    }
+}
@@ -2,11 +2,16 @@
 //! Layer9 Database abstraction with support for multiple backends
 
 use async_trait::async_trait;
+use futures::FutureExt;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 
 /// Database connection trait
 #[cfg(target_arch = "wasm32")]
@@ -18,6 +23,15 @@ pub trait DatabaseConnection: 'static {
     async fn begin_transaction(&self) -> Result<String, DbError>;
     async fn commit_transaction(&self, tx_id: &str) -> Result<(), DbError>;
     async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError>;
+
+    /// Begin a transaction on one connection held until it ends, so its
+    /// statements can't land on other pooled connections. The returned
+    /// connection runs inside the transaction and ends it through its
+    /// `commit_transaction`/`rollback_transaction`. `None` means `self` is a
+    /// single session and `begin_transaction` covers it.
+    async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+        Ok(None)
+    }
 }
 
 /// Database connection trait (server-side with Send)
@@ -30,6 +44,15 @@ pub trait DatabaseConnection: Send + Sync + 'static {
     async fn begin_transaction(&self) -> Result<String, DbError>;
     async fn commit_transaction(&self, tx_id: &str) -> Result<(), DbError>;
     async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError>;
+
+    /// Begin a transaction on one connection held until it ends, so its
+    /// statements can't land on other pooled connections. The returned
+    /// connection runs inside the transaction and ends it through its
+    /// `commit_transaction`/`rollback_transaction`. `None` means `self` is a
+    /// single session and `begin_transaction` covers it.
+    async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+        Ok(None)
+    }
 }
 
 /// Implement DatabaseConnection for Box<dyn DatabaseConnection> (WASM)
@@ -59,6 +82,10 @@ impl DatabaseConnection for Box<dyn DatabaseConnection> {
     async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError> {
         self.as_ref().rollback_transaction(tx_id).await
     }
+
+    async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+        self.as_ref().begin_pinned().await
+    }
 }
 
 /// Implement DatabaseConnection for Box<dyn DatabaseConnection> (Server)
@@ -88,6 +115,10 @@ impl DatabaseConnection for Box<dyn DatabaseConnection> {
     async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError> {
         self.as_ref().rollback_transaction(tx_id).await
     }
+
+    async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+        self.as_ref().begin_pinned().await
+    }
 }
 
 /// Query result
@@ -113,30 +144,160 @@ pub enum DbErrorKind {
     UniqueViolation,
     ForeignKeyViolation,
     Timeout,
+    /// A column name that isn't a plain identifier, e.g. from a sort parameter
+    InvalidColumn,
+}
+
+/// Share one connection between repositories, e.g. `Arc::new(use_db())`
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: DatabaseConnection + ?Sized> DatabaseConnection for Arc<C> {
+    async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+        self.as_ref().execute(query, params).await
+    }
+
+    async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+        self.as_ref().query_one(query, params).await
+    }
+
+    async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+        self.as_ref().query_many(query, params).await
+    }
+
+    async fn begin_transaction(&self) -> Result<String, DbError> {
+        self.as_ref().begin_transaction().await
+    }
+
+    async fn commit_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+        self.as_ref().commit_transaction(tx_id).await
+    }
+
+    async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+        self.as_ref().rollback_transaction(tx_id).await
+    }
+
+    async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+        self.as_ref().begin_pinned().await
+    }
+}
+
+/// Handle to an open transaction; statements issued through it (or a
+/// repository from `Repository::with_tx`) commit or roll back together.
+/// Transactions started on it become savepoints.
+pub struct Transaction<C: DatabaseConnection> {
+    /// The pinned connection, or the caller's when it's a single session
+    conn: Arc<dyn DatabaseConnection>,
+    finished: Arc<AtomicBool>,
+    _backend: PhantomData<C>,
+}
+
+impl<C: DatabaseConnection> Clone for Transaction<C> {
+    fn clone(&self) -> Self {
+        Transaction {
+            conn: self.conn.clone(),
+            finished: self.finished.clone(),
+            _backend: PhantomData,
+        }
+    }
 }
 
-/// Transaction handle
-pub struct Transaction {
-    conn: Box<dyn DatabaseConnection>,
-    _id: String,
+/// Savepoint names are unique process-wide, so savepoints nested at any
+/// depth never shadow each other
+static SAVEPOINTS: AtomicUsize = AtomicUsize::new(0);
+
+impl<C: DatabaseConnection> Transaction<C> {
+    fn check_open(&self) -> Result<(), DbError> {
+        if self.finished.load(Ordering::SeqCst) {
+            return Err(DbError {
+                kind: DbErrorKind::Transaction,
+                message: "Transaction already committed or rolled back".to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
-impl Transaction {
-    pub async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: DatabaseConnection> DatabaseConnection for Transaction<C> {
+    async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+        self.check_open()?;
         self.conn.execute(query, params).await
     }
 
-    pub async fn commit(self) -> Result<(), DbError> {
-        self.conn.execute("COMMIT", vec![]).await?;
+    async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+        self.check_open()?;
+        self.conn.query_one(query, params).await
+    }
+
+    async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+        self.check_open()?;
+        self.conn.query_many(query, params).await
+    }
+
+    async fn begin_transaction(&self) -> Result<String, DbError> {
+        let savepoint = format!("sp_{}", SAVEPOINTS.fetch_add(1, Ordering::SeqCst) + 1);
+        self.execute(&format!("SAVEPOINT {}", savepoint), vec![]).await?;
+        Ok(savepoint)
+    }
+
+    async fn commit_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+        self.execute(&format!("RELEASE SAVEPOINT {}", tx_id), vec![]).await?;
         Ok(())
     }
 
-    pub async fn rollback(self) -> Result<(), DbError> {
-        self.conn.execute("ROLLBACK", vec![]).await?;
+    async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+        self.execute(&format!("ROLLBACK TO SAVEPOINT {}", tx_id), vec![]).await?;
         Ok(())
     }
 }
 
+/// `db.transaction(|tx| async move { ... })` for every cloneable connection
+pub trait Transactional: DatabaseConnection + Clone {
+    /// Run `work` in a transaction: commit when it returns `Ok`, roll back
+    /// when it returns `Err` or panics. Nested calls on `tx` use savepoints.
+    fn transaction<T, F, Fut>(&self, work: F) -> impl Future<Output = Result<T, DbError>>
+    where
+        F: FnOnce(Transaction<Self>) -> Fut,
+        Fut: Future<Output = Result<T, DbError>>;
+}
+
+impl<C: DatabaseConnection + Clone> Transactional for C {
+    async fn transaction<T, F, Fut>(&self, work: F) -> Result<T, DbError>
+    where
+        F: FnOnce(Transaction<Self>) -> Fut,
+        Fut: Future<Output = Result<T, DbError>>,
+    {
+        let (conn, tx_id): (Arc<dyn DatabaseConnection>, String) = match self.begin_pinned().await? {
+            Some(pinned) => (Arc::from(pinned), String::new()),
+            None => (Arc::new(self.clone()), self.begin_transaction().await?),
+        };
+        let tx = Transaction {
+            conn: conn.clone(),
+            finished: Arc::new(AtomicBool::new(false)),
+            _backend: PhantomData,
+        };
+
+        let outcome = AssertUnwindSafe(work(tx.clone())).catch_unwind().await;
+        tx.finished.store(true, Ordering::SeqCst);
+        match outcome {
+            Ok(Ok(value)) => {
+                conn.commit_transaction(&tx_id).await?;
+                Ok(value)
+            }
+            Ok(Err(e)) => {
+                // The work's error matters more than a failed rollback
+                let _ = conn.rollback_transaction(&tx_id).await;
+                Err(e)
+            }
+            Err(panic) => {
+                let _ = conn.rollback_transaction(&tx_id).await;
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+}
+
 /// ORM Model trait
 pub trait Model: Sized + Serialize + DeserializeOwned {
     const TABLE_NAME: &'static str;
@@ -149,6 +310,59 @@ pub trait Model: Sized + Serialize + DeserializeOwned {
     fn primary_key() -> &'static str {
         Self::PRIMARY_KEY
     }
+
+    /// This row's primary key value; `None` for a row that hasn't been saved
+    fn primary_key_value(&self) -> Option<Value> {
+        match serde_json::to_value(self).ok()? {
+            Value::Object(mut row) => row.remove(Self::primary_key()).filter(|id| !id.is_null()),
+            _ => None,
+        }
+    }
+}
+
+/// `Self` has many `M` rows that point back at it through their
+/// `FOREIGN_KEY` column, e.g. a user's posts
+pub trait HasMany<M: Model>: Model {
+    const FOREIGN_KEY: &'static str;
+}
+
+/// `Self` belongs to the `M` row its `FOREIGN_KEY` column names, e.g. a
+/// post's author
+pub trait BelongsTo<M: Model>: Model {
+    const FOREIGN_KEY: &'static str;
+}
+
+/// Sort direction for `QueryBuilder::order_by`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC",
+        }
+    }
+}
+
+/// Column names go into the SQL text, so only plain (optionally
+/// table-qualified) identifiers are accepted; values are always bound
+fn checked_column(column: &str) -> Result<&str, DbError> {
+    let valid = !column.is_empty()
+        && column.split('.').all(|part| {
+            part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if !valid {
+        return Err(DbError {
+            kind: DbErrorKind::InvalidColumn,
+            message: format!("Invalid column name: {:?}", column),
+        });
+    }
+    Ok(column)
 }
 
 /// Query builder
@@ -161,9 +375,28 @@ pub struct QueryBuilder<M: Model> {
     limit: Option<u32>,
     offset: Option<u32>,
     params: Vec<Value>,
+    /// The first invalid column a method was given
+    error: Option<DbError>,
     _phantom: PhantomData<M>,
 }
 
+impl<M: Model> Clone for QueryBuilder<M> {
+    fn clone(&self) -> Self {
+        QueryBuilder {
+            table: self.table.clone(),
+            select: self.select.clone(),
+            joins: self.joins.clone(),
+            where_clause: self.where_clause.clone(),
+            order_by: self.order_by.clone(),
+            limit: self.limit,
+            offset: self.offset,
+            params: self.params.clone(),
+            error: self.error.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<M: Model> Default for QueryBuilder<M> {
     fn default() -> Self {
         Self::new()
@@ -181,6 +414,7 @@ impl<M: Model> QueryBuilder<M> {
             limit: None,
             offset: None,
             params: vec![],
+            error: None,
             _phantom: PhantomData,
         }
     }
@@ -196,14 +430,38 @@ impl<M: Model> QueryBuilder<M> {
         self
     }
 
-    pub fn where_eq(mut self, column: &str, value: impl Into<Value>) -> Self {
-        self.where_clause
-            .push(format!("{} = ${}", column, self.params.len() + 1));
-        self.params.push(value.into());
-        self
+    pub fn where_eq(self, column: &str, value: impl Into<Value>) -> Self {
+        self.where_op(column, "=", value.into())
     }
 
+    pub fn where_gt(self, column: &str, value: impl Into<Value>) -> Self {
+        self.where_op(column, ">", value.into())
+    }
+
+    pub fn where_gte(self, column: &str, value: impl Into<Value>) -> Self {
+        self.where_op(column, ">=", value.into())
+    }
+
+    pub fn where_lt(self, column: &str, value: impl Into<Value>) -> Self {
+        self.where_op(column, "<", value.into())
+    }
+
+    pub fn where_lte(self, column: &str, value: impl Into<Value>) -> Self {
+        self.where_op(column, "<=", value.into())
+    }
+
+    /// Matches no rows when `values` is empty
     pub fn where_in(mut self, column: &str, values: Vec<impl Into<Value>>) -> Self {
+        let column = match checked_column(column) {
+            Ok(column) => column,
+            Err(e) => return self.reject_filter(e),
+        };
+        if values.is_empty() {
+            // `IN ()` isn't valid SQL
+            self.where_clause.push("1 = 0".to_string());
+            return self;
+        }
+
         let placeholders: Vec<String> = values
             .iter()
             .enumerate()
@@ -216,18 +474,50 @@ impl<M: Model> QueryBuilder<M> {
         self
     }
 
-    pub fn where_like(mut self, column: &str, pattern: &str) -> Self {
+    pub fn where_like(self, column: &str, pattern: &str) -> Self {
+        self.where_op(column, "LIKE", Value::String(pattern.to_string()))
+    }
+
+    fn where_op(mut self, column: &str, op: &str, value: Value) -> Self {
+        let column = match checked_column(column) {
+            Ok(column) => column,
+            Err(e) => return self.reject_filter(e),
+        };
         self.where_clause
-            .push(format!("{} LIKE ${}", column, self.params.len() + 1));
-        self.params.push(Value::String(pattern.to_string()));
+            .push(format!("{} {} ${}", column, op, self.params.len() + 1));
+        self.params.push(value);
         self
     }
 
-    pub fn order_by(mut self, column: &str, direction: &str) -> Self {
-        self.order_by.push(format!("{} {}", column, direction));
+    /// Sort by `column`; later calls break ties left by earlier ones
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        match checked_column(column) {
+            Ok(column) => self.order_by.push(format!("{} {}", column, order.as_sql())),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
         self
     }
 
+    /// Keep an invalid column out of the SQL, match nothing in its place,
+    /// and remember it for the query methods to report
+    fn reject_filter(mut self, error: DbError) -> Self {
+        self.where_clause.push("1 = 0".to_string());
+        self.error.get_or_insert(error);
+        self
+    }
+
+    /// Fails with `DbErrorKind::InvalidColumn` if a method was given a
+    /// column name that isn't a plain identifier; `build` leaves such
+    /// columns out and the query methods return this error
+    pub fn check(&self) -> Result<(), DbError> {
+        match &self.error {
+            Some(e) => Err(e.clone()),
+            None => Ok(()),
+        }
+    }
+
     pub fn limit(mut self, limit: u32) -> Self {
         self.limit = Some(limit);
         self
@@ -269,6 +559,7 @@ impl<M: Model> QueryBuilder<M> {
     }
 
     pub async fn execute<C: DatabaseConnection>(&self, conn: &C) -> Result<Vec<M>, DbError> {
+        self.check()?;
         let (query, params) = self.build();
         let values = conn.query_many(&query, params).await?;
         values
@@ -283,6 +574,7 @@ impl<M: Model> QueryBuilder<M> {
     }
 
     pub async fn first<C: DatabaseConnection>(&self, conn: &C) -> Result<M, DbError> {
+        self.check()?;
         let (query, params) = self.build();
         let value = conn.query_one(&query, params).await?;
         serde_json::from_value(value).map_err(|e| DbError {
@@ -290,6 +582,114 @@ impl<M: Model> QueryBuilder<M> {
             message: e.to_string(),
         })
     }
+
+    /// Count query matching the same rows, ignoring order, limit and offset
+    pub fn build_count(&self) -> (String, Vec<Value>) {
+        let mut query = format!("SELECT COUNT(*) AS count FROM {}", self.table);
+
+        for join in &self.joins {
+            query.push_str(&format!(" {}", join));
+        }
+
+        if !self.where_clause.is_empty() {
+            query.push_str(&format!(" WHERE {}", self.where_clause.join(" AND ")));
+        }
+
+        (query, self.params.clone())
+    }
+
+    pub async fn count<C: DatabaseConnection>(&self, conn: &C) -> Result<u64, DbError> {
+        self.check()?;
+        let (query, params) = self.build_count();
+        let value = conn.query_one(&query, params).await?;
+        let count = match &value {
+            Value::Object(row) => row.get("count").or_else(|| row.values().next()),
+            other => Some(other),
+        };
+
+        // Drivers return COUNT(*) as a number or, for BIGINT, a string
+        match count {
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::String(s)) => s.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| DbError {
+            kind: DbErrorKind::Query,
+            message: format!("Unexpected count result: {}", value),
+        })
+    }
+
+    /// Fetch one page (1-based) along with the total row count
+    pub async fn paginate<C: DatabaseConnection>(
+        &self,
+        conn: &C,
+        page: u32,
+        page_size: u32,
+    ) -> Result<Page<M>, DbError> {
+        if page_size == 0 {
+            return Err(DbError {
+                kind: DbErrorKind::Query,
+                message: "page_size must be at least 1".to_string(),
+            });
+        }
+        let page = page.max(1);
+
+        let total = self.count(conn).await?;
+        let mut paged = self.clone();
+        paged.limit = Some(page_size);
+        paged.offset = Some((page - 1).saturating_mul(page_size));
+        if paged.order_by.is_empty() {
+            // Stable pages need a stable order
+            paged.order_by.push(M::primary_key().to_string());
+        }
+        let (query, params) = paged.build();
+
+        let data = conn
+            .query_many(&query, params)
+            .await?
+            .into_iter()
+            .map(|v| {
+                serde_json::from_value(v).map_err(|e| DbError {
+                    kind: DbErrorKind::Query,
+                    message: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<M>, _>>()?;
+
+        Ok(Page::new(data, page, page_size, total))
+    }
+}
+
+/// One page of results with the metadata a pager needs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// 1-based page number
+    pub page: u32,
+    pub page_size: u32,
+    /// Rows across all pages
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+impl<T> Page<T> {
+    pub fn new(data: Vec<T>, page: u32, page_size: u32, total: u64) -> Self {
+        Page {
+            data,
+            page,
+            page_size,
+            total,
+            total_pages: total.div_ceil(page_size.max(1) as u64),
+        }
+    }
+
+    pub fn has_next(&self) -> bool {
+        (self.page as u64) < self.total_pages
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.page > 1
+    }
 }
 
 /// Repository pattern
@@ -306,17 +706,37 @@ impl<M: Model, C: DatabaseConnection> Repository<M, C> {
         }
     }
 
-    pub async fn find_by_id(&self, id: impl Into<Value>) -> Result<M, DbError> {
+    /// The row with this primary key, or `None` if there isn't one
+    pub async fn find_by_id(&self, id: impl Into<Value>) -> Result<Option<M>, DbError> {
         let query = format!(
             "SELECT * FROM {} WHERE {} = $1",
             M::table_name(),
             M::primary_key()
         );
-        let value = self.conn.query_one(&query, vec![id.into()]).await?;
-        serde_json::from_value(value).map_err(|e| DbError {
-            kind: DbErrorKind::Query,
-            message: e.to_string(),
-        })
+        self.fetch_optional(&query, vec![id.into()]).await
+    }
+
+    /// The first row matching `query`, in its order, or `None` if nothing matches
+    pub async fn find_one(&self, query: &QueryBuilder<M>) -> Result<Option<M>, DbError> {
+        query.check()?;
+        let (query, params) = query.clone().limit(1).build();
+        self.fetch_optional(&query, params).await
+    }
+
+    async fn fetch_optional(&self, query: &str, params: Vec<Value>) -> Result<Option<M>, DbError> {
+        match self.conn.query_one(query, params).await {
+            // sqlx reports RowNotFound where the HTTP backend answers `null`
+            Err(DbError {
+                kind: DbErrorKind::NotFound,
+                ..
+            })
+            | Ok(Value::Null) => Ok(None),
+            Err(e) => Err(e),
+            Ok(value) => serde_json::from_value(value).map(Some).map_err(|e| DbError {
+                kind: DbErrorKind::Query,
+                message: e.to_string(),
+            }),
+        }
     }
 
     pub async fn find_all(&self) -> Result<Vec<M>, DbError> {
@@ -334,36 +754,92 @@ impl<M: Model, C: DatabaseConnection> Repository<M, C> {
     }
 
     pub async fn insert(&self, model: &M) -> Result<M, DbError> {
-        let json = serde_json::to_value(model).map_err(|e| DbError {
+        let row = to_row(model)?;
+        let columns: Vec<String> = row.keys().cloned().collect();
+        let values: Vec<Value> = row.values().cloned().collect();
+        let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("${}", i)).collect();
+
+        let query = format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING *",
+            M::table_name(),
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+
+        let value = self.conn.query_one(&query, values).await?;
+        serde_json::from_value(value).map_err(|e| DbError {
             kind: DbErrorKind::Query,
             message: e.to_string(),
-        })?;
-
-        if let Value::Object(map) = json {
-            let columns: Vec<String> = map.keys().cloned().collect();
-            let values: Vec<Value> = map.values().cloned().collect();
-            let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("${}", i)).collect();
+        })
+    }
 
-            let query = format!(
-                "INSERT INTO {} ({}) VALUES ({}) RETURNING *",
-                M::table_name(),
-                columns.join(", "),
-                placeholders.join(", ")
-            );
+    /// Save every column of an existing row, matched by its primary key
+    pub async fn update_model(&self, model: &M) -> Result<M, DbError> {
+        let mut row = to_row(model)?;
+        let id = match row.remove(M::primary_key()) {
+            Some(id) if !id.is_null() => id,
+            _ => {
+                return Err(DbError {
+                    kind: DbErrorKind::Query,
+                    message: format!(
+                        "Can't update a {} row without a {}",
+                        M::table_name(),
+                        M::primary_key()
+                    ),
+                })
+            }
+        };
+        if row.is_empty() {
+            return self.find_by_id(id.clone()).await?.ok_or_else(|| missing_row::<M>(&id));
+        }
 
-            let value = self.conn.query_one(&query, values).await?;
-            serde_json::from_value(value).map_err(|e| DbError {
-                kind: DbErrorKind::Query,
-                message: e.to_string(),
-            })
-        } else {
+        let updates = row.into_iter().collect();
+        match self.update(id.clone(), updates).await {
             Err(DbError {
-                kind: DbErrorKind::Query,
-                message: "Model must serialize to object".to_string(),
-            })
+                kind: DbErrorKind::NotFound,
+                ..
+            }) => Err(missing_row::<M>(&id)),
+            result => result,
+        }
+    }
+
+    /// Insert the row, or overwrite the one already stored under its primary key
+    pub async fn upsert(&self, model: &M) -> Result<M, DbError> {
+        if model.primary_key_value().is_none() {
+            return self.insert(model).await;
+        }
+
+        let row = to_row(model)?;
+        let columns: Vec<String> = row.keys().cloned().collect();
+        let values: Vec<Value> = row.values().cloned().collect();
+        let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("${}", i)).collect();
+        let mut assignments: Vec<String> = columns
+            .iter()
+            .filter(|column| column.as_str() != M::primary_key())
+            .map(|column| format!("{} = EXCLUDED.{}", column, column))
+            .collect();
+        if assignments.is_empty() {
+            // DO NOTHING would return no row; a no-op update returns the stored one
+            assignments.push(format!("{0} = EXCLUDED.{0}", M::primary_key()));
         }
+
+        let query = format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {} RETURNING *",
+            M::table_name(),
+            columns.join(", "),
+            placeholders.join(", "),
+            M::primary_key(),
+            assignments.join(", ")
+        );
+
+        let value = self.conn.query_one(&query, values).await?;
+        serde_json::from_value(value).map_err(|e| DbError {
+            kind: DbErrorKind::Query,
+            message: e.to_string(),
+        })
     }
 
+    /// Set the given columns on the row with this primary key
     pub async fn update(
         &self,
         id: impl Into<Value>,
@@ -388,6 +864,13 @@ impl<M: Model, C: DatabaseConnection> Repository<M, C> {
         );
 
         let value = self.conn.query_one(&query, params).await?;
+        if value.is_null() {
+            // The HTTP backend answers `null` where sqlx reports RowNotFound
+            return Err(DbError {
+                kind: DbErrorKind::NotFound,
+                message: "No rows found".to_string(),
+            });
+        }
         serde_json::from_value(value).map_err(|e| DbError {
             kind: DbErrorKind::Query,
             message: e.to_string(),
@@ -407,9 +890,182 @@ impl<M: Model, C: DatabaseConnection> Repository<M, C> {
     pub fn query(&self) -> QueryBuilder<M> {
         QueryBuilder::new()
     }
+
+    /// A repository for the same model whose statements run in `tx`
+    pub fn with_tx<T: DatabaseConnection + Clone>(
+        &self,
+        tx: &Transaction<T>,
+    ) -> Repository<M, Transaction<T>> {
+        Repository::new(tx.clone())
+    }
+
+    /// Rows matching `query`'s filters
+    pub async fn count(&self, query: &QueryBuilder<M>) -> Result<u64, DbError> {
+        query.count(&self.conn).await
+    }
+
+    /// Fetch one page (1-based) of the whole table
+    pub async fn paginate(&self, page: u32, page_size: u32) -> Result<Page<M>, DbError> {
+        self.query().paginate(&self.conn, page, page_size).await
+    }
+
+    /// Fetch one page (1-based) of the rows matching `query`
+    pub async fn paginate_query(
+        &self,
+        query: &QueryBuilder<M>,
+        page: u32,
+        page_size: u32,
+    ) -> Result<Page<M>, DbError> {
+        query.paginate(&self.conn, page, page_size).await
+    }
+
+    /// The row with this primary key and its `R` children
+    pub async fn with_many<R: Model>(
+        &self,
+        id: impl Into<Value>,
+    ) -> Result<Option<(M, Vec<R>)>, DbError>
+    where
+        M: HasMany<R>,
+    {
+        match self.find_by_id(id).await? {
+            Some(parent) => Ok(self.load_many(vec![parent]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    /// Pair each parent with its `R` children, fetched in one query for the
+    /// whole list
+    pub async fn load_many<R: Model>(&self, parents: Vec<M>) -> Result<Vec<(M, Vec<R>)>, DbError>
+    where
+        M: HasMany<R>,
+    {
+        let mut ids = vec![];
+        for parent in &parents {
+            ids.extend(column_value(parent, M::primary_key())?);
+        }
+        let children = if ids.is_empty() {
+            vec![]
+        } else {
+            QueryBuilder::<R>::new()
+                .where_in(<M as HasMany<R>>::FOREIGN_KEY, ids)
+                .execute(&self.conn)
+                .await?
+        };
+
+        let mut by_parent: HashMap<String, Vec<R>> = HashMap::new();
+        for child in children {
+            if let Some(key) = column_value(&child, <M as HasMany<R>>::FOREIGN_KEY)? {
+                by_parent.entry(key.to_string()).or_default().push(child);
+            }
+        }
+        parents
+            .into_iter()
+            .map(|parent| {
+                let key = column_value(&parent, M::primary_key())?;
+                let children = key.and_then(|key| by_parent.remove(&key.to_string()));
+                Ok((parent, children.unwrap_or_default()))
+            })
+            .collect()
+    }
+
+    /// The `P` row `child` belongs to; `None` when its key is null or names
+    /// no row
+    pub async fn parent<P: Model>(&self, child: &M) -> Result<Option<P>, DbError>
+    where
+        M: BelongsTo<P>,
+    {
+        let Some(id) = column_value(child, <M as BelongsTo<P>>::FOREIGN_KEY)? else {
+            return Ok(None);
+        };
+        let parents = QueryBuilder::<P>::new()
+            .where_eq(P::primary_key(), id)
+            .limit(1)
+            .execute(&self.conn)
+            .await?;
+        Ok(parents.into_iter().next())
+    }
+
+    /// Pair each row with its `P` parent, fetched in one query for the
+    /// whole list; rows whose parent is missing get `None`
+    pub async fn load_parents<P: Model>(
+        &self,
+        children: Vec<M>,
+    ) -> Result<Vec<(M, Option<P>)>, DbError>
+    where
+        M: BelongsTo<P>,
+    {
+        let mut keys = vec![];
+        for child in &children {
+            keys.extend(column_value(child, <M as BelongsTo<P>>::FOREIGN_KEY)?);
+        }
+        keys.sort_by_key(Value::to_string);
+        keys.dedup();
+        let parents = if keys.is_empty() {
+            vec![]
+        } else {
+            QueryBuilder::<P>::new()
+                .where_in(P::primary_key(), keys)
+                .execute(&self.conn)
+                .await?
+        };
+
+        let mut by_key: HashMap<String, P> = HashMap::new();
+        for parent in parents {
+            if let Some(key) = column_value(&parent, P::primary_key())? {
+                by_key.insert(key.to_string(), parent);
+            }
+        }
+        children
+            .into_iter()
+            .map(|child| {
+                let key = column_value(&child, <M as BelongsTo<P>>::FOREIGN_KEY)?;
+                // Serialize again rather than move, since siblings can share a parent
+                let parent = match key.and_then(|key| by_key.get(&key.to_string())) {
+                    Some(parent) => Some(from_row(to_row(parent)?)?),
+                    None => None,
+                };
+                Ok((child, parent))
+            })
+            .collect()
+    }
+}
+
+fn missing_row<M: Model>(id: &Value) -> DbError {
+    DbError {
+        kind: DbErrorKind::NotFound,
+        message: format!("No {} row with {} = {}", M::table_name(), M::primary_key(), id),
+    }
+}
+
+/// One column of a model; `None` when it's missing or null
+fn column_value<M: Model>(model: &M, column: &str) -> Result<Option<Value>, DbError> {
+    Ok(to_row(model)?.remove(column).filter(|value| !value.is_null()))
+}
+
+fn from_row<M: Model>(row: serde_json::Map<String, Value>) -> Result<M, DbError> {
+    serde_json::from_value(Value::Object(row)).map_err(|e| DbError {
+        kind: DbErrorKind::Query,
+        message: e.to_string(),
+    })
+}
+
+/// A model as a column → value map
+fn to_row<M: Model>(model: &M) -> Result<serde_json::Map<String, Value>, DbError> {
+    match serde_json::to_value(model) {
+        Ok(Value::Object(row)) => Ok(row),
+        Ok(_) => Err(DbError {
+            kind: DbErrorKind::Query,
+            message: "Model must serialize to object".to_string(),
+        }),
+        Err(e) => Err(DbError {
+            kind: DbErrorKind::Query,
+            message: e.to_string(),
+        }),
+    }
 }
 
 /// Database migrations
+#[derive(Debug, Clone)]
 pub struct Migration {
     pub version: i32,
     pub name: String,
@@ -417,6 +1073,26 @@ pub struct Migration {
     pub down: String,
 }
 
+impl Migration {
+    pub fn new(
+        version: i32,
+        name: impl Into<String>,
+        up: impl Into<String>,
+        down: impl Into<String>,
+    ) -> Self {
+        Migration {
+            version,
+            name: name.into(),
+            up: up.into(),
+            down: down.into(),
+        }
+    }
+}
+
+/// Table recording which migrations have been applied
+const MIGRATIONS_TABLE: &str = "_layer9_migrations";
+
+/// Applies migrations in version order, each in its own transaction
 pub struct Migrator<C: DatabaseConnection> {
     conn: C,
     migrations: Vec<Migration>,
@@ -436,60 +1112,138 @@ impl<C: DatabaseConnection> Migrator<C> {
         self
     }
 
-    pub async fn migrate(&self) -> Result<(), DbError> {
-        // Create migrations table if not exists
+    /// Hand the connection back once migrations have run
+    pub fn into_connection(self) -> C {
+        self.conn
+    }
+
+    /// Versions already applied, oldest first
+    pub async fn applied(&self) -> Result<Vec<i32>, DbError> {
         self.conn
             .execute(
-                "CREATE TABLE IF NOT EXISTS _migrations (
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {} (
                 version INTEGER PRIMARY KEY,
                 name TEXT NOT NULL,
                 applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )",
+                    MIGRATIONS_TABLE
+                ),
                 vec![],
             )
             .await?;
 
-        // Get applied migrations
-        let applied_values = self
+        let rows = self
             .conn
-            .query_many("SELECT version FROM _migrations ORDER BY version", vec![])
+            .query_many(
+                &format!("SELECT version FROM {} ORDER BY version", MIGRATIONS_TABLE),
+                vec![],
+            )
             .await?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.get("version")?.as_i64())
+            .map(|version| version as i32)
+            .collect())
+    }
 
-        let applied: Vec<i32> = applied_values
-            .into_iter()
-            .filter_map(|v| {
-                if let Value::Object(map) = v {
-                    map.get("version")
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as i32)
-                } else {
-                    None
-                }
-            })
+    /// Apply every pending migration, oldest first, and return their
+    /// versions. Stops at the first failure, whose changes are rolled back.
+    pub async fn run(&self) -> Result<Vec<i32>, DbError> {
+        let duplicate = self.migrations.windows(2).find(|pair| pair[0].version == pair[1].version);
+        if let Some(pair) = duplicate {
+            return Err(migration_error(format!(
+                "Duplicate migration version {}",
+                pair[0].version
+            )));
+        }
+
+        let applied = self.applied().await?;
+        let pending: Vec<&Migration> = self
+            .migrations
+            .iter()
+            .filter(|m| !applied.contains(&m.version))
             .collect();
+        // A pending version below the latest applied one was added after
+        // newer migrations ran; applying it now could break them
+        if let (Some(latest), Some(stale)) = (applied.last(), pending.first()) {
+            if stale.version < *latest {
+                return Err(migration_error(format!(
+                    "Migration {} ({}) is older than the latest applied version {}",
+                    stale.version, stale.name, latest
+                )));
+            }
+        }
 
-        // Apply pending migrations
-        for migration in &self.migrations {
-            if !applied.contains(&migration.version) {
-                // Run migration
-                self.conn.execute(&migration.up, vec![]).await?;
-
-                // Record migration
-                self.conn
-                    .execute(
-                        "INSERT INTO _migrations (version, name) VALUES ($1, $2)",
-                        vec![
-                            Value::Number(migration.version.into()),
-                            Value::String(migration.name.clone()),
-                        ],
-                    )
-                    .await?;
-
-                web_sys::console::log_1(&format!("Applied migration: {}", migration.name).into());
+        let mut ran = vec![];
+        for migration in pending {
+            let record = format!("INSERT INTO {} (version, name) VALUES ($1, $2)", MIGRATIONS_TABLE);
+            let params = vec![migration.version.into(), migration.name.clone().into()];
+            self.in_transaction(migration, &migration.up, &record, params).await?;
+            ran.push(migration.version);
+        }
+        Ok(ran)
+    }
+
+    /// Revert the `steps` most recently applied migrations, newest first,
+    /// and return their versions
+    pub async fn rollback(&self, steps: usize) -> Result<Vec<i32>, DbError> {
+        let applied = self.applied().await?;
+        let mut reverted = vec![];
+        for version in applied.iter().rev().take(steps) {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| m.version == *version)
+                .ok_or_else(|| {
+                    migration_error(format!("Can't roll back unknown migration {}", version))
+                })?;
+            let record = format!("DELETE FROM {} WHERE version = $1", MIGRATIONS_TABLE);
+            self.in_transaction(migration, &migration.down, &record, vec![(*version).into()])
+                .await?;
+            reverted.push(*version);
+        }
+        Ok(reverted)
+    }
+
+    /// Run `sql` and the `_layer9_migrations` bookkeeping together, or not at all
+    async fn in_transaction(
+        &self,
+        migration: &Migration,
+        sql: &str,
+        record: &str,
+        params: Vec<Value>,
+    ) -> Result<(), DbError> {
+        // Both statements must reach the session the transaction began on
+        let pinned = self.conn.begin_pinned().await?;
+        let (session, tx): (&dyn DatabaseConnection, String) = match &pinned {
+            Some(pinned) => (pinned.as_ref(), String::new()),
+            None => (&self.conn, self.conn.begin_transaction().await?),
+        };
+        let result = match session.execute(sql, vec![]).await {
+            Ok(_) => session.execute(record, params).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => session.commit_transaction(&tx).await,
+            Err(e) => {
+                let _ = session.rollback_transaction(&tx).await;
+                Err(DbError {
+                    kind: e.kind,
+                    message: format!(
+                        "Migration {} ({}) failed: {}",
+                        migration.version, migration.name, e.message
+                    ),
+                })
             }
         }
+    }
+}
 
-        Ok(())
+fn migration_error(message: String) -> DbError {
+    DbError {
+        kind: DbErrorKind::Query,
+        message,
     }
 }
 
@@ -841,3 +1595,79 @@ pub fn use_repository<M: Model>() -> Repository<M, Box<dyn DatabaseConnection>>
     let conn = use_db();
     Repository::new(conn)
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: Option<i64>,
+        username: String,
+        email: String,
+    }
+
+    impl Model for User {
+        const TABLE_NAME: &'static str = "users";
+    }
+
+    #[test]
+    fn test_query_builder_binds_every_value() {
+        let (sql, params) = QueryBuilder::<User>::new()
+            .where_gte("id", 2)
+            .where_lt("id", 10)
+            .where_like("email", "%@example.com")
+            .where_in("username", vec!["ada", "grace"])
+            .where_gt("created_at", "2024-01-01")
+            .where_lte("updated_at", "2024-12-31")
+            .order_by("username", Order::Asc)
+            .order_by("id", Order::Desc)
+            .limit(20)
+            .offset(40)
+            .build();
+
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE id >= $1 AND id < $2 AND email LIKE $3 \
+             AND username IN ($4, $5) AND created_at > $6 AND updated_at <= $7 \
+             ORDER BY username ASC, id DESC LIMIT 20 OFFSET 40"
+        );
+        assert_eq!(
+            params,
+            vec![
+                Value::from(2),
+                Value::from(10),
+                Value::from("%@example.com"),
+                Value::from("ada"),
+                Value::from("grace"),
+                Value::from("2024-01-01"),
+                Value::from("2024-12-31"),
+            ]
+        );
+    }
+
+
+    #[test]
+    fn test_query_builder_empty_in_matches_nothing() {
+        let (sql, params) = QueryBuilder::<User>::new()
+            .where_in("id", Vec::<i64>::new())
+            .where_eq("username", "ada")
+            .build();
+        assert_eq!(sql, "SELECT * FROM users WHERE 1 = 0 AND username = $1");
+        assert_eq!(params, vec![Value::from("ada")]);
+    }
+
+
+    #[test]
+    fn test_query_builder_rejects_sql_in_column_names() {
+        let query = QueryBuilder::<User>::new()
+            .where_eq("id = 1 OR 1", 1)
+            .order_by("name; DROP TABLE users", Order::Asc);
+        let error = query.check().unwrap_err();
+        assert!(matches!(error.kind, DbErrorKind::InvalidColumn));
+        assert_eq!(error.message, "Invalid column name: \"id = 1 OR 1\"");
+        // The column never reaches the SQL
+        assert_eq!(query.build().0, "SELECT * FROM users WHERE 1 = 0");
+    }
+}
@@ -47,6 +47,7 @@ mod api_impl {
                 DbErrorKind::UniqueViolation => StatusCode::CONFLICT,
                 DbErrorKind::ForeignKeyViolation => StatusCode::BAD_REQUEST,
                 DbErrorKind::Timeout => StatusCode::REQUEST_TIMEOUT,
+                DbErrorKind::InvalidColumn => StatusCode::BAD_REQUEST,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
 
@@ -11,6 +11,7 @@ mod sqlite_impl {
     use serde_json::Value;
     use sqlx::{sqlite::SqlitePoolOptions, Column, Pool, Row, Sqlite};
     use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     /// SQLite-based connection for lightweight database needs
     #[derive(Clone)]
@@ -113,82 +114,103 @@ mod sqlite_impl {
         }
     }
 
-    #[async_trait]
-    impl DatabaseConnection for SqliteConnection {
-        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
+    async fn execute_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Sqlite>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqliteConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
+        }
 
-            let result = sqlx_query
-                .execute(&*self.pool)
-                .await
-                .map_err(|e| DbError {
-                    kind: match &e {
-                        sqlx::Error::Database(db_err) if db_err.message().contains("UNIQUE") => {
-                            DbErrorKind::UniqueViolation
-                        }
-                        sqlx::Error::Database(db_err) if db_err.message().contains("FOREIGN KEY") => {
-                            DbErrorKind::ForeignKeyViolation
-                        }
-                        _ => DbErrorKind::Query,
-                    },
-                    message: format!("Query execution failed: {}", e),
-                })?;
+        let result = sqlx_query
+            .execute(executor)
+            .await
+            .map_err(|e| DbError {
+                kind: match &e {
+                    sqlx::Error::Database(db_err) if db_err.message().contains("UNIQUE") => {
+                        DbErrorKind::UniqueViolation
+                    }
+                    sqlx::Error::Database(db_err) if db_err.message().contains("FOREIGN KEY") => {
+                        DbErrorKind::ForeignKeyViolation
+                    }
+                    _ => DbErrorKind::Query,
+                },
+                message: format!("Query execution failed: {}", e),
+            })?;
 
-            Ok(QueryResult {
-                rows_affected: result.rows_affected(),
-                last_insert_id: Some(result.last_insert_rowid()),
-            })
+        Ok(QueryResult {
+            rows_affected: result.rows_affected(),
+            last_insert_id: Some(result.last_insert_rowid()),
+        })
+    }
+
+    async fn query_one_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<Value, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Sqlite>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqliteConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
         }
 
-        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
+        let row = sqlx_query
+            .fetch_one(executor)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => DbError {
+                    kind: DbErrorKind::NotFound,
+                    message: "No rows found".to_string(),
+                },
+                _ => DbError {
+                    kind: DbErrorKind::Query,
+                    message: format!("Query failed: {}", e),
+                },
+            })?;
 
-            let row = sqlx_query
-                .fetch_one(&*self.pool)
-                .await
-                .map_err(|e| match e {
-                    sqlx::Error::RowNotFound => DbError {
-                        kind: DbErrorKind::NotFound,
-                        message: "No rows found".to_string(),
-                    },
-                    _ => DbError {
-                        kind: DbErrorKind::Query,
-                        message: format!("Query failed: {}", e),
-                    },
-                })?;
+        SqliteConnection::row_to_json(&row)
+    }
 
-            Self::row_to_json(&row)
+    async fn query_many_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Sqlite>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqliteConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
         }
 
-        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
+        let rows = sqlx_query
+            .fetch_all(executor)
+            .await
+            .map_err(|e| DbError {
+                kind: DbErrorKind::Query,
+                message: format!("Query failed: {}", e),
+            })?;
 
-            let rows = sqlx_query
-                .fetch_all(&*self.pool)
-                .await
-                .map_err(|e| DbError {
-                    kind: DbErrorKind::Query,
-                    message: format!("Query failed: {}", e),
-                })?;
+        rows.iter()
+            .map(SqliteConnection::row_to_json)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for SqliteConnection {
+        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+            execute_on(&*self.pool, query, params).await
+        }
 
-            rows.iter()
-                .map(Self::row_to_json)
-                .collect::<Result<Vec<_>, _>>()
+        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+            query_one_on(&*self.pool, query, params).await
+        }
+
+        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            query_many_on(&*self.pool, query, params).await
         }
 
         async fn begin_transaction(&self) -> Result<String, DbError> {
@@ -206,6 +228,76 @@ mod sqlite_impl {
             self.execute("ROLLBACK", vec![]).await?;
             Ok(())
         }
+
+        async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+            let tx = self.pool.begin().await.map_err(|e| DbError {
+                kind: DbErrorKind::Transaction,
+                message: format!("Failed to begin transaction: {}", e),
+            })?;
+            Ok(Some(Box::new(SqliteTransaction {
+                tx: Mutex::new(Some(tx)),
+            })))
+        }
+    }
+
+    /// A transaction on one pooled connection, held until it commits or rolls
+    /// back; dropping it unfinished rolls back
+    pub struct SqliteTransaction {
+        tx: Mutex<Option<sqlx::Transaction<'static, Sqlite>>>,
+    }
+
+    fn transaction_finished() -> DbError {
+        DbError {
+            kind: DbErrorKind::Transaction,
+            message: "Transaction already committed or rolled back".to_string(),
+        }
+    }
+
+    impl SqliteTransaction {
+        async fn finish(&self, commit: bool) -> Result<(), DbError> {
+            let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+            let result = if commit { tx.commit().await } else { tx.rollback().await };
+            result.map_err(|e| DbError {
+                kind: DbErrorKind::Transaction,
+                message: format!("Failed to end transaction: {}", e),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for SqliteTransaction {
+        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            execute_on(&mut **conn, query, params).await
+        }
+
+        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            query_one_on(&mut **conn, query, params).await
+        }
+
+        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            query_many_on(&mut **conn, query, params).await
+        }
+
+        async fn begin_transaction(&self) -> Result<String, DbError> {
+            Err(DbError {
+                kind: DbErrorKind::Transaction,
+                message: "Already in a transaction; nest with a savepoint".to_string(),
+            })
+        }
+
+        async fn commit_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            self.finish(true).await
+        }
+
+        async fn rollback_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            self.finish(false).await
+        }
     }
 
     /// Create default tables for a new SQLite database
@@ -255,4 +347,504 @@ mod sqlite_impl {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::db::{
+            BelongsTo, HasMany, Migration, Migrator, Model, Order, Repository, Transactional,
+        };
+        use futures::FutureExt;
+        use serde::{Deserialize, Serialize};
+        use std::panic::AssertUnwindSafe;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct User {
+            id: Option<i64>,
+            username: String,
+            email: String,
+        }
+
+        impl Model for User {
+            const TABLE_NAME: &'static str = "users";
+        }
+
+        fn user(id: Option<i64>, username: &str, email: &str) -> User {
+            User {
+                id,
+                username: username.to_string(),
+                email: email.to_string(),
+            }
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Post {
+            id: i64,
+            user_id: Option<i64>,
+            title: String,
+        }
+
+        impl Model for Post {
+            const TABLE_NAME: &'static str = "posts";
+        }
+
+        impl HasMany<Post> for User {
+            const FOREIGN_KEY: &'static str = "user_id";
+        }
+
+        impl BelongsTo<User> for Post {
+            const FOREIGN_KEY: &'static str = "user_id";
+        }
+
+        /// `users` and `posts` without the default schema's constraints, so
+        /// posts can be orphaned
+        async fn blog() -> SqliteConnection {
+            let conn = SqliteConnection::in_memory().await.unwrap();
+            conn.execute(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, username TEXT NOT NULL, email TEXT NOT NULL); \
+                 CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT NOT NULL)",
+                vec![],
+            )
+            .await
+            .unwrap();
+            conn
+        }
+
+        async fn seed(repo: &Repository<User, SqliteConnection>, count: usize) {
+            for n in 1..=count {
+                let name = format!("user{}", n);
+                repo.insert(&user(None, &name, &format!("{}@example.com", name))).await.unwrap();
+            }
+        }
+
+        async fn names(repo: &Repository<User, SqliteConnection>) -> Vec<String> {
+            let users = repo.find_all().await.unwrap();
+            users.into_iter().map(|user| user.username).collect()
+        }
+
+        fn rejected() -> DbError {
+            DbError {
+                kind: DbErrorKind::Query,
+                message: "rejected".to_string(),
+            }
+        }
+
+        /// Passes statements through to SQLite, logging each one
+        struct Logged {
+            conn: SqliteConnection,
+            queries: std::sync::Mutex<Vec<String>>,
+        }
+
+        impl Logged {
+            fn log(&self, query: &str) {
+                self.queries.lock().unwrap().push(query.to_string());
+            }
+        }
+
+        #[async_trait]
+        impl DatabaseConnection for Logged {
+            async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+                self.log(query);
+                self.conn.execute(query, params).await
+            }
+
+            async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+                self.log(query);
+                self.conn.query_one(query, params).await
+            }
+
+            async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+                self.log(query);
+                self.conn.query_many(query, params).await
+            }
+
+            async fn begin_transaction(&self) -> Result<String, DbError> {
+                self.conn.begin_transaction().await
+            }
+
+            async fn commit_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+                self.conn.commit_transaction(tx_id).await
+            }
+
+            async fn rollback_transaction(&self, tx_id: &str) -> Result<(), DbError> {
+                self.conn.rollback_transaction(tx_id).await
+            }
+        }
+
+        async fn logged_blog() -> Logged {
+            let conn = blog().await;
+            for (id, name) in [(1, "user1"), (2, "user2"), (3, "user3")] {
+                conn.execute(
+                    "INSERT INTO users (id, username, email) VALUES ($1, $2, '')",
+                    vec![id.into(), name.into()],
+                )
+                .await
+                .unwrap();
+            }
+            let posts = [(1, Some(1), "a"), (2, Some(2), "b"), (3, Some(1), "c"), (4, None, "d")];
+            for (id, user_id, title) in posts {
+                conn.execute(
+                    "INSERT INTO posts (id, user_id, title) VALUES ($1, $2, $3)",
+                    vec![id.into(), user_id.into(), title.into()],
+                )
+                .await
+                .unwrap();
+            }
+            Logged {
+                conn,
+                queries: Default::default(),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_update_persists_changes() {
+            let repo = Repository::<User, _>::new(blog().await);
+            let mut ada = repo.insert(&user(None, "ada", "ada@example.com")).await.unwrap();
+            assert_eq!(ada.primary_key_value(), Some(Value::from(1)));
+
+            ada.email = "ada@lovelace.dev".to_string();
+            let updated = repo.update_model(&ada).await.unwrap();
+            assert_eq!(updated, ada);
+
+            let stored = repo.find_by_id(1).await.unwrap().unwrap();
+            assert_eq!(stored.email, "ada@lovelace.dev");
+            assert_eq!(stored.username, "ada");
+        }
+
+        #[tokio::test]
+        async fn test_update_requires_an_existing_row() {
+            let repo = Repository::<User, _>::new(blog().await);
+
+            let unsaved = repo.update_model(&user(None, "ada", "ada@example.com")).await.unwrap_err();
+            assert!(matches!(unsaved.kind, DbErrorKind::Query));
+
+            let missing = repo.update_model(&user(Some(7), "ada", "ada@example.com")).await.unwrap_err();
+            assert!(matches!(missing.kind, DbErrorKind::NotFound));
+            assert_eq!(missing.message, "No users row with id = 7");
+            assert!(repo.find_all().await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_upsert_inserts_then_overwrites() {
+            let repo = Repository::<User, _>::new(blog().await);
+
+            let created = repo.upsert(&user(None, "ada", "ada@example.com")).await.unwrap();
+            assert_eq!(created.id, Some(1));
+
+            repo.upsert(&user(Some(1), "ada", "ada@lovelace.dev")).await.unwrap();
+            repo.upsert(&user(Some(5), "grace", "grace@example.com")).await.unwrap();
+
+            assert_eq!(
+                repo.find_all().await.unwrap(),
+                vec![
+                    user(Some(1), "ada", "ada@lovelace.dev"),
+                    user(Some(5), "grace", "grace@example.com"),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_invalid_column_fails_the_query() {
+            let repo = Repository::<User, _>::new(blog().await);
+            let query = repo.query().order_by("1; DROP TABLE users", Order::Desc);
+            let error = repo.count(&query).await.unwrap_err();
+            assert!(matches!(error.kind, DbErrorKind::InvalidColumn));
+            assert!(repo.find_all().await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn test_paginate_and_count() {
+            let repo = Repository::<User, _>::new(blog().await);
+            seed(&repo, 25).await;
+
+            let page = repo.paginate(2, 10).await.unwrap();
+            let ids: Vec<_> = page.data.iter().map(|user| user.id.unwrap()).collect();
+            assert_eq!(ids, (11..=20).collect::<Vec<_>>());
+            assert_eq!((page.page, page.page_size, page.total, page.total_pages), (2, 10, 25, 3));
+            assert!(page.has_next() && page.has_previous());
+
+            let beyond = repo.paginate(4, 10).await.unwrap();
+            assert!(beyond.data.is_empty());
+            assert_eq!(beyond.total, 25);
+            assert!(!beyond.has_next());
+
+            assert!(repo.paginate(1, 0).await.is_err());
+
+            let filtered = repo.query().where_gt("id", 20);
+            assert_eq!(repo.count(&repo.query()).await.unwrap(), 25);
+            assert_eq!(repo.count(&filtered).await.unwrap(), 5);
+            let page = repo.paginate_query(&filtered, 1, 2).await.unwrap();
+            assert_eq!(page.data[0].id, Some(21));
+            assert_eq!((page.total, page.total_pages), (5, 3));
+        }
+
+        #[tokio::test]
+        async fn test_find_by_id_hit_and_miss() {
+            let repo = Repository::<User, _>::new(blog().await);
+            seed(&repo, 3).await;
+
+            let found = repo.find_by_id(2).await.unwrap();
+            assert_eq!(found, Some(user(Some(2), "user2", "user2@example.com")));
+            assert_eq!(repo.find_by_id(9).await.unwrap(), None);
+        }
+
+        #[tokio::test]
+        async fn test_find_one_applies_filters_and_limit() {
+            let repo = Repository::<User, _>::new(blog().await);
+            seed(&repo, 5).await;
+
+            let query = repo.query().where_gt("id", 2);
+            let first = repo.find_one(&query).await.unwrap().unwrap();
+            assert_eq!(first.id, Some(3));
+
+            let named = repo.find_one(&repo.query().where_eq("username", "user4")).await.unwrap();
+            assert_eq!(named.map(|user| user.id), Some(Some(4)));
+
+            let none = repo.find_one(&repo.query().where_eq("username", "nobody")).await.unwrap();
+            assert!(none.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_has_many_loads_children_in_one_query() {
+            let db = Arc::new(logged_blog().await);
+            let users = Repository::<User, _>::new(db.clone());
+            let list = users.query().where_lte("id", 2).execute(&db).await.unwrap();
+            db.queries.lock().unwrap().clear();
+
+            let loaded = users.load_many::<Post>(list).await.unwrap();
+            let grouped: Vec<_> = loaded
+                .iter()
+                .map(|(user, posts)| {
+                    (user.username.as_str(), posts.iter().map(|p| p.title.as_str()).collect::<Vec<_>>())
+                })
+                .collect();
+            assert_eq!(grouped, vec![("user1", vec!["a", "c"]), ("user2", vec!["b"])]);
+            assert_eq!(
+                *db.queries.lock().unwrap(),
+                vec!["SELECT * FROM posts WHERE user_id IN ($1, $2)"]
+            );
+
+            let (user, posts) = users.with_many::<Post>(3).await.unwrap().unwrap();
+            assert_eq!((user.username.as_str(), posts.len()), ("user3", 0));
+            assert!(users.with_many::<Post>(9).await.unwrap().is_none());
+        }
+
+        #[tokio::test]
+        async fn test_belongs_to_batches_parents_and_tolerates_orphans() {
+            let db = Arc::new(logged_blog().await);
+            let posts = Repository::<Post, _>::new(db.clone());
+            let all = posts.find_all().await.unwrap();
+            let orphan = Post {
+                id: 5,
+                user_id: Some(99),
+                title: "e".to_string(),
+            };
+
+            let author = posts.parent::<User>(&all[2]).await.unwrap();
+            assert_eq!(author.map(|user| user.username), Some("user1".to_string()));
+            assert!(posts.parent::<User>(&orphan).await.unwrap().is_none());
+            assert!(posts.parent::<User>(&all[3]).await.unwrap().is_none());
+
+            db.queries.lock().unwrap().clear();
+            let loaded = posts.load_parents::<User>(all).await.unwrap();
+            let authors: Vec<_> = loaded
+                .iter()
+                .map(|(post, user)| (post.id, user.as_ref().and_then(|user| user.id)))
+                .collect();
+            assert_eq!(authors, vec![(1, Some(1)), (2, Some(2)), (3, Some(1)), (4, None)]);
+            assert_eq!(db.queries.lock().unwrap().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_transaction_commits_or_rolls_back_together() {
+            let conn = blog().await;
+            let users = &Repository::<User, _>::new(conn.clone());
+
+            let failed: Result<(), DbError> = conn
+                .transaction(|tx| async move {
+                    users.with_tx(&tx).insert(&user(None, "ada", "ada@example.com")).await?;
+                    users.with_tx(&tx).insert(&user(None, "grace", "grace@example.com")).await?;
+                    Err(rejected())
+                })
+                .await;
+            assert_eq!(failed.unwrap_err().message, "rejected");
+            assert!(names(users).await.is_empty());
+
+            let leaked = conn
+                .transaction(|tx| async move {
+                    let repo = users.with_tx(&tx);
+                    repo.insert(&user(None, "ada", "ada@example.com")).await?;
+                    repo.insert(&user(None, "grace", "grace@example.com")).await?;
+                    Ok(tx)
+                })
+                .await
+                .unwrap();
+            assert_eq!(names(users).await, vec!["ada", "grace"]);
+            // The handle is spent once the transaction ends
+            assert!(leaked.execute("DELETE FROM users", vec![]).await.is_err());
+        }
+
+        #[tokio::test]
+        async fn test_nested_transaction_uses_a_savepoint() {
+            let conn = blog().await;
+            let users = &Repository::<User, _>::new(conn.clone());
+
+            conn.transaction(|tx| async move {
+                users.with_tx(&tx).insert(&user(None, "ada", "ada@example.com")).await?;
+                let inner: Result<(), DbError> = tx
+                    .transaction(|inner| async move {
+                        users.with_tx(&inner).insert(&user(None, "bob", "bob@example.com")).await?;
+                        Err(rejected())
+                    })
+                    .await;
+                assert!(inner.is_err());
+                Ok(())
+            })
+            .await
+            .unwrap();
+            assert_eq!(names(users).await, vec!["ada"]);
+        }
+
+        #[tokio::test]
+        async fn test_panicking_transaction_rolls_back() {
+            let conn = blog().await;
+            let users = &Repository::<User, _>::new(conn.clone());
+
+            let panicked = AssertUnwindSafe(conn.transaction(|tx| async move {
+                users.with_tx(&tx).insert(&user(None, "ada", "ada@example.com")).await?;
+                panic!("boom");
+                #[allow(unreachable_code)]
+                Ok(())
+            }))
+            .catch_unwind()
+            .await;
+            assert!(panicked.is_err());
+            assert!(names(users).await.is_empty());
+            // No connection was left inside the transaction
+            users.insert(&user(None, "grace", "grace@example.com")).await.unwrap();
+            assert_eq!(names(users).await, vec!["grace"]);
+        }
+
+        #[tokio::test]
+        async fn test_query_builder_against_default_schema() {
+            let conn = SqliteConnection::in_memory().await.unwrap();
+            create_default_schema(&conn).await.unwrap();
+            for (id, name) in [(1, "ada"), (2, "grace"), (3, "linus")] {
+                conn.execute(
+                    "INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, 'x')",
+                    vec![id.into(), name.into(), format!("{}@example.com", name).into()],
+                )
+                .await
+                .unwrap();
+            }
+            for (user_id, title) in [(1, "b"), (1, "a"), (2, "c"), (2, "d"), (3, "e")] {
+                conn.execute(
+                    "INSERT INTO posts (user_id, title) VALUES ($1, $2)",
+                    vec![user_id.into(), title.into()],
+                )
+                .await
+                .unwrap();
+            }
+
+            let repo = Repository::<Post, _>::new(conn.clone());
+            let posts = repo
+                .query()
+                .where_in("user_id", vec![1, 2])
+                .where_gte("id", 2)
+                .order_by("user_id", Order::Desc)
+                .order_by("title", Order::Asc)
+                .limit(2)
+                .offset(1)
+                .execute(&conn)
+                .await
+                .unwrap();
+            let titles: Vec<_> = posts.iter().map(|post| post.title.as_str()).collect();
+            assert_eq!(titles, vec!["d", "a"]);
+
+            let none = repo
+                .query()
+                .where_in("user_id", Vec::<i64>::new())
+                .execute(&conn)
+                .await
+                .unwrap();
+            assert!(none.is_empty());
+        }
+
+        async fn tables(conn: &SqliteConnection) -> Vec<String> {
+            let rows = conn
+                .query_many(
+                    "SELECT name FROM sqlite_master WHERE type = 'table' \
+                     AND name NOT LIKE 'sqlite_%' ORDER BY name",
+                    vec![],
+                )
+                .await
+                .unwrap();
+            rows.iter().map(|row| row["name"].as_str().unwrap().to_string()).collect()
+        }
+
+        fn users_and_posts() -> [Migration; 2] {
+            [
+                Migration::new(1, "create_users", "CREATE TABLE users (id INTEGER)", "DROP TABLE users"),
+                Migration::new(2, "create_posts", "CREATE TABLE posts (id INTEGER)", "DROP TABLE posts"),
+            ]
+        }
+
+        #[tokio::test]
+        async fn test_migrator_runs_and_rolls_back() {
+            let [users, posts] = users_and_posts();
+            let migrator = Migrator::new(SqliteConnection::in_memory().await.unwrap())
+                .add_migration(posts)
+                .add_migration(users);
+
+            assert_eq!(migrator.run().await.unwrap(), vec![1, 2]);
+            assert_eq!(migrator.applied().await.unwrap(), vec![1, 2]);
+            // Already applied, so a second run does nothing
+            assert!(migrator.run().await.unwrap().is_empty());
+
+            assert_eq!(migrator.rollback(1).await.unwrap(), vec![2]);
+            assert_eq!(migrator.applied().await.unwrap(), vec![1]);
+            let conn = migrator.into_connection();
+            assert_eq!(tables(&conn).await, vec!["_layer9_migrations", "users"]);
+        }
+
+        #[tokio::test]
+        async fn test_failed_migration_rolls_back_and_stops() {
+            let [users, posts] = users_and_posts();
+            let migrator = Migrator::new(SqliteConnection::in_memory().await.unwrap())
+                .add_migration(users)
+                .add_migration(Migration::new(
+                    2,
+                    "broken",
+                    "CREATE TABLE comments (id INTEGER); INSERT INTO missing VALUES (1)",
+                    "DROP TABLE comments",
+                ))
+                .add_migration(Migration { version: 3, ..posts });
+
+            let error = migrator.run().await.unwrap_err();
+            assert!(error.message.starts_with("Migration 2 (broken) failed"));
+            assert!(error.message.contains("no such table: missing"));
+            assert_eq!(migrator.applied().await.unwrap(), vec![1]);
+            // The half-applied migration left nothing behind
+            let conn = migrator.into_connection();
+            assert_eq!(tables(&conn).await, vec!["_layer9_migrations", "users"]);
+        }
+
+        #[tokio::test]
+        async fn test_migrator_rejects_out_of_order_versions() {
+            let [users, posts] = users_and_posts();
+            let first = Migrator::new(SqliteConnection::in_memory().await.unwrap())
+                .add_migration(Migration { version: 3, ..posts.clone() });
+            first.run().await.unwrap();
+
+            let migrator = Migrator::new(first.into_connection())
+                .add_migration(users)
+                .add_migration(Migration { version: 3, ..posts.clone() });
+            let error = migrator.run().await.unwrap_err();
+            assert!(error.message.starts_with("Migration 1 (create_users) is older"));
+            assert_eq!(migrator.applied().await.unwrap(), vec![3]);
+
+            let duplicate = migrator.add_migration(Migration { version: 3, ..posts });
+            assert!(duplicate.run().await.is_err());
+        }
+    }
+}
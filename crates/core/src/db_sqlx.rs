@@ -11,6 +11,7 @@ mod sqlx_impl {
     use serde_json::Value;
     use sqlx::{postgres::PgPoolOptions, Column, Pool, Postgres, Row};
     use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     /// SQLx-based PostgreSQL connection for server-side use
     #[derive(Clone)]
@@ -100,74 +101,104 @@ mod sqlx_impl {
         }
     }
 
-    #[async_trait]
-    impl DatabaseConnection for SqlxConnection {
-        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
-
-            let result = sqlx_query
-                .execute(&*self.pool)
-                .await
-                .map_err(|e| DbError {
-                    kind: DbErrorKind::Query,
-                    message: format!("Query execution failed: {}", e),
-                })?;
+    /// Run a query in a `db.query` span when the caller is being traced
+    async fn traced<F: std::future::Future>(query: &str, run: F) -> F::Output {
+        if crate::trace::current().is_none() {
+            return run.await;
+        }
+        let mut span = crate::trace::Span::start("db.query");
+        span.set_attribute("db.statement", query);
+        let output = crate::trace::instrument(span.context().clone(), run).await;
+        span.finish();
+        output
+    }
 
-            Ok(QueryResult {
-                rows_affected: result.rows_affected(),
-                last_insert_id: None, // PostgreSQL doesn't have LAST_INSERT_ID
-            })
+    async fn execute_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqlxConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
         }
 
-        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
+        let result = traced(query, sqlx_query.execute(executor))
+            .await
+            .map_err(|e| DbError {
+                kind: DbErrorKind::Query,
+                message: format!("Query execution failed: {}", e),
+            })?;
 
-            let row = sqlx_query
-                .fetch_one(&*self.pool)
-                .await
-                .map_err(|e| match e {
-                    sqlx::Error::RowNotFound => DbError {
-                        kind: DbErrorKind::NotFound,
-                        message: "No rows found".to_string(),
-                    },
-                    _ => DbError {
-                        kind: DbErrorKind::Query,
-                        message: format!("Query failed: {}", e),
-                    },
-                })?;
+        Ok(QueryResult {
+            rows_affected: result.rows_affected(),
+            last_insert_id: None, // PostgreSQL doesn't have LAST_INSERT_ID
+        })
+    }
 
-            Self::row_to_json(&row)
+    async fn query_one_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<Value, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqlxConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
         }
 
-        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
-            let mut sqlx_query = sqlx::query(query);
-            
-            // Bind parameters
-            for param in Self::convert_params(params) {
-                sqlx_query = sqlx_query.bind(param);
-            }
-
-            let rows = sqlx_query
-                .fetch_all(&*self.pool)
-                .await
-                .map_err(|e| DbError {
+        let row = traced(query, sqlx_query.fetch_one(executor))
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => DbError {
+                    kind: DbErrorKind::NotFound,
+                    message: "No rows found".to_string(),
+                },
+                _ => DbError {
                     kind: DbErrorKind::Query,
                     message: format!("Query failed: {}", e),
-                })?;
+                },
+            })?;
+
+        SqlxConnection::row_to_json(&row)
+    }
 
-            rows.iter()
-                .map(Self::row_to_json)
-                .collect::<Result<Vec<_>, _>>()
+    async fn query_many_on<'c, E>(executor: E, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        let mut sqlx_query = sqlx::query(query);
+        
+        // Bind parameters
+        for param in SqlxConnection::convert_params(params) {
+            sqlx_query = sqlx_query.bind(param);
+        }
+
+        let rows = traced(query, sqlx_query.fetch_all(executor))
+            .await
+            .map_err(|e| DbError {
+                kind: DbErrorKind::Query,
+                message: format!("Query failed: {}", e),
+            })?;
+
+        rows.iter()
+            .map(SqlxConnection::row_to_json)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for SqlxConnection {
+        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+            execute_on(&*self.pool, query, params).await
+        }
+
+        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+            query_one_on(&*self.pool, query, params).await
+        }
+
+        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            query_many_on(&*self.pool, query, params).await
         }
 
         async fn begin_transaction(&self) -> Result<String, DbError> {
@@ -186,6 +217,76 @@ mod sqlx_impl {
             self.execute("ROLLBACK", vec![]).await?;
             Ok(())
         }
+
+        async fn begin_pinned(&self) -> Result<Option<Box<dyn DatabaseConnection>>, DbError> {
+            let tx = self.pool.begin().await.map_err(|e| DbError {
+                kind: DbErrorKind::Transaction,
+                message: format!("Failed to begin transaction: {}", e),
+            })?;
+            Ok(Some(Box::new(SqlxTransaction {
+                tx: Mutex::new(Some(tx)),
+            })))
+        }
+    }
+
+    /// A transaction on one pooled connection, held until it commits or rolls
+    /// back; dropping it unfinished rolls back
+    pub struct SqlxTransaction {
+        tx: Mutex<Option<sqlx::Transaction<'static, Postgres>>>,
+    }
+
+    fn transaction_finished() -> DbError {
+        DbError {
+            kind: DbErrorKind::Transaction,
+            message: "Transaction already committed or rolled back".to_string(),
+        }
+    }
+
+    impl SqlxTransaction {
+        async fn finish(&self, commit: bool) -> Result<(), DbError> {
+            let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+            let result = if commit { tx.commit().await } else { tx.rollback().await };
+            result.map_err(|e| DbError {
+                kind: DbErrorKind::Transaction,
+                message: format!("Failed to end transaction: {}", e),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for SqlxTransaction {
+        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            execute_on(&mut **conn, query, params).await
+        }
+
+        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            query_one_on(&mut **conn, query, params).await
+        }
+
+        async fn query_many(&self, query: &str, params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            let mut tx = self.tx.lock().await;
+            let conn = tx.as_mut().ok_or_else(transaction_finished)?;
+            query_many_on(&mut **conn, query, params).await
+        }
+
+        async fn begin_transaction(&self) -> Result<String, DbError> {
+            Err(DbError {
+                kind: DbErrorKind::Transaction,
+                message: "Already in a transaction; nest with a savepoint".to_string(),
+            })
+        }
+
+        async fn commit_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            self.finish(true).await
+        }
+
+        async fn rollback_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            self.finish(false).await
+        }
     }
 
     use once_cell::sync::OnceCell;
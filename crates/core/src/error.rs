@@ -1,21 +1,69 @@
 //! Error Boundaries - L5/L6
 
+use crate::hooks::use_persisted;
 use crate::prelude::*;
 use std::cell::RefCell;
 use std::panic;
 use std::rc::Rc;
+use std::sync::Once;
 
 // Type aliases to simplify complex types
 type ErrorHandler = Box<dyn Fn(&ErrorInfo)>;
 type FallbackRender = Box<dyn Fn(&ErrorInfo) -> Element>;
 
 /// Error boundary state
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct ErrorBoundaryState {
     pub error: Option<ErrorInfo>,
     pub error_count: u32,
 }
 
+impl ErrorBoundaryState {
+    /// Forget the caught error so the next render retries the children
+    pub fn reset(&mut self) {
+        self.error = None;
+        crate::reactive_v2::queue_current_render();
+    }
+}
+
+thread_local! {
+    /// Location of the last panic, recorded by the hook for the boundary
+    /// that catches it
+    static LAST_PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    /// One entry per ErrorBoundary being rendered, innermost last; holds
+    /// the first error a descendant reported without panicking
+    static BOUNDARY_FRAMES: RefCell<Vec<Option<String>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Report `message` to the innermost ErrorBoundary being rendered, which
+/// shows its fallback once its children have rendered. Returns false when
+/// no boundary is rendering, so the caller shows the error itself.
+pub fn report_error(message: impl Into<String>) -> bool {
+    BOUNDARY_FRAMES.with(|frames| match frames.borrow_mut().last_mut() {
+        Some(error) => {
+            error.get_or_insert_with(|| message.into());
+            true
+        }
+        None => false,
+    })
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Record where panics happen, then defer to the hook installed before
+/// (e.g. `console_error_panic_hook`)
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(|location| location.to_string());
+            LAST_PANIC_LOCATION.with(|last| *last.borrow_mut() = location);
+            previous(info);
+        }));
+    });
+}
+
 #[derive(Clone)]
 pub struct ErrorInfo {
     pub message: String,
@@ -24,11 +72,18 @@ pub struct ErrorInfo {
 }
 
 /// Error boundary component
+///
+/// Renders a fallback in place of children that panic while rendering, or
+/// that hand it an error through `report_error`. Catching panics relies on
+/// unwinding, so on targets built with `panic = "abort"`, which includes
+/// `wasm32-unknown-unknown` by default, the panic still aborts after the
+/// hook has reported it; reported errors work everywhere.
 pub struct ErrorBoundary {
     children: Box<dyn Component>,
     fallback: FallbackRender,
     on_error: Option<ErrorHandler>,
     state: Rc<RefCell<ErrorBoundaryState>>,
+    catch_in_debug: bool,
 }
 
 impl ErrorBoundary {
@@ -41,6 +96,7 @@ impl ErrorBoundary {
                 error: None,
                 error_count: 0,
             })),
+            catch_in_debug: false,
         }
     }
 
@@ -54,13 +110,53 @@ impl ErrorBoundary {
         self
     }
 
-    fn catch_error(&self) -> Element {
+    /// Debug builds let panics through so they surface during development;
+    /// opt in to catching them there too
+    pub fn catch_in_debug(mut self, catch: bool) -> Self {
+        self.catch_in_debug = catch;
+        self
+    }
+
+    /// Share the caught error with the parent, so a retry button in the
+    /// fallback can call `reset` on it
+    pub fn state(mut self, state: Rc<RefCell<ErrorBoundaryState>>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// The error being shown, if any
+    pub fn error(&self) -> Option<ErrorInfo> {
+        self.state.borrow().error.clone()
+    }
+
+    /// Clear the error and retry rendering the children
+    pub fn reset(&self) {
+        self.state.borrow_mut().reset();
+    }
+
+    fn catch_error(&self, state: &RefCell<ErrorBoundaryState>) -> Element {
+        install_panic_hook();
+        LAST_PANIC_LOCATION.with(|last| last.borrow_mut().take());
+
         // Try to render children
+        BOUNDARY_FRAMES.with(|frames| frames.borrow_mut().push(None));
         let result = panic::catch_unwind(panic::AssertUnwindSafe(|| self.children.render()));
+        let reported = BOUNDARY_FRAMES.with(|frames| frames.borrow_mut().pop().flatten());
 
         match result {
-            Ok(element) => element,
+            Ok(element) => match reported {
+                Some(message) => self.show_error(state, ErrorInfo {
+                    message,
+                    stack: None,
+                    component_stack: vec![],
+                }),
+                None => element,
+            },
             Err(err) => {
+                if cfg!(debug_assertions) && !self.catch_in_debug {
+                    panic::resume_unwind(err);
+                }
+
                 // Extract error message
                 let message = if let Some(s) = err.downcast_ref::<&str>() {
                     s.to_string()
@@ -70,34 +166,38 @@ impl ErrorBoundary {
                     "Unknown error".to_string()
                 };
 
-                let error_info = ErrorInfo {
+                self.show_error(state, ErrorInfo {
                     message,
-                    stack: None,
+                    stack: LAST_PANIC_LOCATION.with(|last| last.borrow_mut().take()),
                     component_stack: vec![], // TODO: Implement component stack tracking
-                };
-
-                self.state.borrow_mut().error = Some(error_info.clone());
-                self.state.borrow_mut().error_count += 1;
+                })
+            }
+        }
+    }
 
-                if let Some(handler) = &self.on_error {
-                    handler(&error_info);
-                }
+    fn show_error(&self, state: &RefCell<ErrorBoundaryState>, error_info: ErrorInfo) -> Element {
+        state.borrow_mut().error = Some(error_info.clone());
+        state.borrow_mut().error_count += 1;
 
-                (self.fallback)(&error_info)
-            }
+        if let Some(handler) = &self.on_error {
+            handler(&error_info);
         }
+
+        (self.fallback)(&error_info)
     }
 }
 
 impl Component for ErrorBoundary {
     fn render(&self) -> Element {
+        let state = use_persisted(|| self.state.clone()).borrow().clone();
+
         // Check if we already have an error
-        if let Some(error_info) = &self.state.borrow().error {
+        if let Some(error_info) = &state.borrow().error {
             return (self.fallback)(error_info);
         }
 
         // Try to render children with error catching
-        self.catch_error()
+        self.catch_error(&state)
     }
 }
 
@@ -112,16 +212,16 @@ fn default_error_fallback(error: &ErrorInfo) -> Element {
 
     Element::Node {
         tag: "div".to_string(),
-        props,
+        props: Box::new(props),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Something went wrong".to_string())],
             },
             Element::Node {
                 tag: "p".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text(error.message.clone())],
             },
         ],
@@ -210,3 +310,107 @@ pub fn use_error_handler() -> impl Fn(String) {
 // Re-exports
 use crate::fetch::{FetchBuilder, FetchError, Method};
 use web_sys::window;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct MalformedTodos;
+
+    impl Component for MalformedTodos {
+        fn render(&self) -> Element {
+            panic!("malformed todo JSON");
+        }
+    }
+
+    #[test]
+    fn test_boundary_renders_fallback_and_keeps_siblings_working() {
+        let clicks = Rc::new(Cell::new(0));
+        let boundary = ErrorBoundary::new(MalformedTodos)
+            .catch_in_debug(true)
+            .fallback(|error| Element::Text(format!("Could not load todos: {}", error.message)));
+
+        let sibling = Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                on_click: Some({
+                    let clicks = clicks.clone();
+                    Rc::new(move || clicks.set(clicks.get() + 1))
+                }),
+                ..Default::default()
+            }),
+            children: vec![Element::Text("Add todo".to_string())],
+        };
+        let page = Element::Node {
+            tag: "main".to_string(),
+            props: Box::default(),
+            children: vec![boundary.render(), sibling.clone()],
+        };
+
+        let html = page.to_html();
+        assert!(html.contains("Could not load todos: malformed todo JSON"));
+        assert!(html.contains("Add todo"));
+        let Element::Node { props, .. } = sibling else {
+            panic!("expected button");
+        };
+        (props.on_click.unwrap())();
+        assert_eq!(clicks.get(), 1);
+
+        let error = boundary.error().expect("error recorded");
+        assert!(error.stack.is_some_and(|location| location.contains("error.rs")));
+
+        // A reset retries the children, which fail again here
+        boundary.reset();
+        assert!(boundary.error().is_none());
+        boundary.render();
+        assert_eq!(boundary.state.borrow().error_count, 2);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct FlakyTodos(Rc<Cell<bool>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for FlakyTodos {
+        fn render(&self) -> Element {
+            if self.0.get() {
+                panic!("malformed todo JSON");
+            }
+            Element::Text("3 todos".to_string())
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct TodoPage(Rc<Cell<bool>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for TodoPage {
+        fn render(&self) -> Element {
+            ErrorBoundary::new(FlakyTodos(self.0.clone()))
+                .catch_in_debug(true)
+                .fallback(|error| Element::Text(format!("Could not load todos: {}", error.message)))
+                .render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_caught_error_survives_parent_rerenders() {
+        let failing = Rc::new(Cell::new(true));
+        let page = TodoPage(failing.clone());
+        let mut tree = crate::testing::render(&page);
+        assert!(tree.to_html().contains("Could not load todos"));
+
+        // The fallback stays until the boundary is reset
+        failing.set(false);
+        tree.rerender();
+        assert!(tree.to_html().contains("Could not load todos"));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "malformed todo JSON")]
+    fn test_debug_builds_let_panics_through() {
+        ErrorBoundary::new(MalformedTodos).render();
+    }
+}
@@ -18,7 +18,7 @@ pub enum Method {
 }
 
 impl Method {
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Method::GET => "GET",
             Method::POST => "POST",
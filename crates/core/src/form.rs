@@ -1,20 +1,36 @@
 //! Form Handling with Validation - L5/L6
 
+use crate::announce::use_announce;
 use crate::prelude::*;
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 // Re-export form traits
-pub use crate::form_traits::{FormFields, StringFormFields};
+pub use crate::form_traits::{indexed_field, parse_indexed_field, FormFields, StringFormFields};
 
 // Type aliases to simplify complex types
 type ValidationFn<T> = Box<dyn Fn(&T) -> HashMap<String, Vec<String>>>;
 type SubmitFn<T> = Box<dyn Fn(&T) -> Pin<Box<dyn Future<Output = Result<(), String>> + 'static>>>;
 type ValidatorFn = Box<dyn Fn(&str) -> Option<String>>;
+pub type AsyncValidatorFn = Box<dyn Fn(String) -> Pin<Box<dyn Future<Output = Option<String>> + 'static>>>;
+type ValidationTask = Shared<LocalBoxFuture<'static, ()>>;
+
+/// Error key for form-level errors that don't belong to a single field
+/// (e.g. a failed submission). Field-specific cross-field errors such as
+/// "passwords don't match" go under the field that displays them instead.
+pub const FORM_ERROR_KEY: &str = "_form";
+
+/// Announced to screen readers after a successful submit, unless replaced
+/// with `Form::with_success_message`
+pub const DEFAULT_SUCCESS_MESSAGE: &str = "Form submitted successfully";
+
+/// Quiet time after a change before an async field validator runs
+pub const ASYNC_VALIDATION_DEBOUNCE_MS: u32 = 300;
 
 /// Form state
 #[derive(Clone)]
@@ -22,6 +38,8 @@ pub struct FormState<T> {
     pub values: T,
     pub errors: HashMap<String, Vec<String>>,
     pub touched: HashMap<String, bool>,
+    /// Whether a field's value differs from the initial (or last loaded) value
+    pub dirty: HashMap<String, bool>,
     pub submitting: bool,
     pub submitted: bool,
 }
@@ -30,9 +48,35 @@ pub struct FormState<T> {
 pub struct FormConfig<T> {
     pub initial_values: T,
     pub validate: Option<ValidationFn<T>>,
+    /// Per-field checks that need a server round trip ("is this username
+    /// taken?"). Each gets the field's new value after a change and resolves
+    /// to an error, which is merged with the field's sync errors.
+    pub validate_field_async: HashMap<String, AsyncValidatorFn>,
     pub on_submit: SubmitFn<T>,
 }
 
+/// Box an async closure for `FormConfig::validate_field_async`
+pub fn async_validator<F, Fut>(validator: F) -> AsyncValidatorFn
+where
+    F: Fn(String) -> Fut + 'static,
+    Fut: Future<Output = Option<String>> + 'static,
+{
+    Box::new(move |value| Box::pin(validator(value)))
+}
+
+struct PendingValidation {
+    generation: u64,
+    task: ValidationTask,
+}
+
+/// Async field validation in flight and its latest verdicts
+#[derive(Default)]
+struct AsyncValidation {
+    generation: u64,
+    pending: HashMap<String, PendingValidation>,
+    errors: HashMap<String, String>,
+}
+
 /// Form hook
 pub fn use_form<T: Clone + Default + 'static>(config: FormConfig<T>) -> Form<T> {
     let initial_values = config.initial_values.clone();
@@ -40,12 +84,17 @@ pub fn use_form<T: Clone + Default + 'static>(config: FormConfig<T>) -> Form<T>
         values: initial_values,
         errors: HashMap::new(),
         touched: HashMap::new(),
+        dirty: HashMap::new(),
         submitting: false,
         submitted: false,
     }));
 
     Form {
         state: state.clone(),
+        baseline: Rc::new(RefCell::new(config.initial_values.clone())),
+        dependents: Rc::new(RefCell::new(HashMap::new())),
+        success_message: Rc::new(RefCell::new(DEFAULT_SUCCESS_MESSAGE.to_string())),
+        async_validation: Rc::new(RefCell::new(AsyncValidation::default())),
         config: Rc::new(config),
     }
 }
@@ -55,6 +104,13 @@ pub fn use_form<T: Clone + Default + 'static>(config: FormConfig<T>) -> Form<T>
 pub struct Form<T> {
     state: Rc<RefCell<FormState<T>>>,
     config: Rc<FormConfig<T>>,
+    // Values `reset()` returns to; starts as the configured initial values
+    baseline: Rc<RefCell<T>>,
+    // Field -> fields whose validation depends on it
+    dependents: Rc<RefCell<HashMap<String, Vec<String>>>>,
+    // Announced once `on_submit` succeeds
+    success_message: Rc<RefCell<String>>,
+    async_validation: Rc<RefCell<AsyncValidation>>,
 }
 
 impl<T: Clone + 'static> Form<T> {
@@ -74,15 +130,166 @@ impl<T: Clone + 'static> Form<T> {
         self.state.borrow().submitting
     }
 
+    /// Whether the user has left `field` since the form was loaded. Once the
+    /// form has been submitted every field counts as touched, so all errors show.
+    pub fn is_touched(&self, field: &str) -> bool {
+        let state = self.state.borrow();
+        state.submitted || state.touched.get(field).copied().unwrap_or(false)
+    }
+
+    /// Whether `field` differs from its initial (or last loaded) value
+    pub fn is_dirty(&self, field: &str) -> bool {
+        self.state.borrow().dirty.get(field).copied().unwrap_or(false)
+    }
+
+    /// Whether any field differs from its initial value
+    pub fn is_form_dirty(&self) -> bool {
+        self.state.borrow().dirty.values().any(|dirty| *dirty)
+    }
+
+    /// Whether an async validator is still checking `field`
+    pub fn is_validating(&self, field: &str) -> bool {
+        self.async_validation.borrow().pending.contains_key(field)
+    }
+
+    /// Errors stored under `FORM_ERROR_KEY`
+    pub fn form_errors(&self) -> Vec<String> {
+        self.state
+            .borrow()
+            .errors
+            .get(FORM_ERROR_KEY)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Re-validate `field` whenever `depends_on` changes, e.g.
+    /// `form.add_dependency("confirm_password", "password")`
+    pub fn add_dependency(&self, field: &str, depends_on: &str) {
+        let mut dependents = self.dependents.borrow_mut();
+        let entry = dependents.entry(depends_on.to_string()).or_default();
+        if !entry.iter().any(|f| f == field) {
+            entry.push(field.to_string());
+        }
+    }
+
     pub fn set_field_value(&self, field: &str, value: impl Into<String>) 
     where 
         T: FormFields
     {
         let value_string = value.into();
+        // The old verdict doesn't apply to the new value
+        let has_async_validator = self.config.validate_field_async.contains_key(field);
+        if has_async_validator {
+            self.async_validation.borrow_mut().errors.remove(field);
+        }
+
+        self.set_field_with(field, |values| values.set_field(field, value_string.clone()));
+        self.refresh_dirty(field);
+        if has_async_validator {
+            self.validate_field_async(field, value_string);
+        }
+    }
+
+    /// Compare `field` (or an array field's length) with the baseline
+    fn refresh_dirty(&self, field: &str)
+    where
+        T: FormFields,
+    {
+        let baseline = self.baseline.borrow();
+        let mut state = self.state.borrow_mut();
+        let dirty = match state.values.array_len(field) {
+            Some(len) => baseline.array_len(field) != Some(len),
+            None => state.values.get_field(field) != baseline.get_field(field),
+        };
+        state.dirty.insert(field.to_string(), dirty);
+    }
+
+    /// Run `field`'s async validator on `value` after the debounce. A newer
+    /// call for the same field supersedes this one: only the last result lands.
+    fn validate_field_async(&self, field: &str, value: String) {
+        let generation = {
+            let mut validation = self.async_validation.borrow_mut();
+            validation.generation += 1;
+            validation.generation
+        };
+
+        let field_name = field.to_string();
+        let validation = Rc::downgrade(&self.async_validation);
+        let state = self.state.clone();
+        let config = self.config.clone();
+        let task = async move {
+            #[cfg(target_arch = "wasm32")]
+            gloo_timers::future::TimeoutFuture::new(ASYNC_VALIDATION_DEBOUNCE_MS).await;
+
+            if !is_current(&validation, &field_name, generation) {
+                return;
+            }
+            let Some(validator) = config.validate_field_async.get(&field_name) else {
+                return;
+            };
+            let error = validator(value).await;
+
+            let Some(validation) = validation.upgrade() else {
+                return;
+            };
+            let mut validation = validation.borrow_mut();
+            if validation.pending.get(&field_name).map(|p| p.generation) != Some(generation) {
+                return;
+            }
+            validation.pending.remove(&field_name);
+            if let Some(error) = error {
+                validation.errors.insert(field_name.clone(), error.clone());
+                state.borrow_mut().errors.entry(field_name).or_default().push(error);
+            }
+        }
+        .boxed_local()
+        .shared();
+
+        self.async_validation
+            .borrow_mut()
+            .pending
+            .insert(field.to_string(), PendingValidation { generation, task: task.clone() });
+
+        #[cfg(target_arch = "wasm32")]
+        spawn_local(task);
+    }
+
+    /// Wait until no async validation is in flight
+    pub async fn validation_settled(&self) {
+        loop {
+            let tasks: Vec<ValidationTask> = self
+                .async_validation
+                .borrow()
+                .pending
+                .values()
+                .map(|pending| pending.task.clone())
+                .collect();
+            if tasks.is_empty() {
+                return;
+            }
+            futures::future::join_all(tasks).await;
+        }
+    }
+
+    /// Add the latest async verdicts to freshly computed sync errors
+    fn merge_async_errors(&self, errors: &mut HashMap<String, Vec<String>>, fields: Option<&[String]>) {
+        for (field, error) in &self.async_validation.borrow().errors {
+            if fields.is_none_or(|fields| fields.contains(field)) {
+                errors.entry(field.clone()).or_default().push(error.clone());
+            }
+        }
+    }
+
+    /// Apply an update to one field, then re-validate it and its dependents
+    pub(crate) fn set_field_with(
+        &self,
+        field: &str,
+        update: impl FnOnce(&mut T) -> Result<(), String>,
+    ) {
         let mut state = self.state.borrow_mut();
         
         // Update the field value
-        if let Err(e) = state.values.set_field(field, value_string) {
+        if let Err(e) = update(&mut state.values) {
             // If setting fails, add to errors
             state.errors.entry(field.to_string())
                 .or_default()
@@ -91,10 +298,64 @@ impl<T: Clone + 'static> Form<T> {
             // Clear any previous errors for this field
             state.errors.remove(field);
             
-            // Run validation if configured
+            // Re-validate the field, anything depending on it, and form-level errors
             drop(state); // Release borrow before validate
-            self.validate();
+            let mut affected = vec![field.to_string(), FORM_ERROR_KEY.to_string()];
+            if let Some(dependents) = self.dependents.borrow().get(field) {
+                affected.extend(dependents.iter().cloned());
+            }
+            self.validate_fields(&affected);
+        }
+    }
+
+    /// Number of items in an array field
+    pub fn array_len(&self, array: &str) -> usize
+    where
+        T: FormFields,
+    {
+        self.state.borrow().values.array_len(array).unwrap_or(0)
+    }
+
+    /// Append an empty item to an array field and re-validate the array
+    pub fn push_array_item(&self, array: &str)
+    where
+        T: FormFields,
+    {
+        self.set_field_with(array, |values| values.push_array_item(array));
+        self.refresh_dirty(array);
+        self.refresh_dirty(&indexed_field(array, self.array_len(array).saturating_sub(1)));
+    }
+
+    /// Remove an array item. Errors and touched state of the items after it
+    /// move up with them: what was `emails[2]` is now `emails[1]`.
+    pub fn remove_array_item(&self, array: &str, index: usize)
+    where
+        T: FormFields,
+    {
+        let len = self.array_len(array);
+        self.set_field_with(array, |values| values.remove_array_item(array, index));
+        if self.array_len(array) == len {
+            return;
+        }
+
+        {
+            let mut state = self.state.borrow_mut();
+            state.errors = reindex_after_removal(std::mem::take(&mut state.errors), array, index);
+            state.touched = reindex_after_removal(std::mem::take(&mut state.touched), array, index);
+            // Dirty compares by position, so it is worked out again below
+            state.dirty.retain(|field, _| item_index(field, array).is_none());
+        }
+        self.refresh_dirty(array);
+        for item in 0..len - 1 {
+            self.refresh_dirty(&indexed_field(array, item));
         }
+
+        let mut validation = self.async_validation.borrow_mut();
+        validation.errors = reindex_after_removal(std::mem::take(&mut validation.errors), array, index);
+        // Checks of moved items were for the old positions
+        validation
+            .pending
+            .retain(|field, _| item_index(field, array).is_none_or(|(i, _)| i < index));
     }
 
     pub fn set_field_touched(&self, field: &str, touched: bool) {
@@ -106,60 +367,167 @@ impl<T: Clone + 'static> Form<T> {
 
     pub fn validate(&self) {
         if let Some(validate_fn) = &self.config.validate {
-            let errors = validate_fn(&self.state.borrow().values);
+            let mut errors = validate_fn(&self.state.borrow().values);
+            self.merge_async_errors(&mut errors, None);
             self.state.borrow_mut().errors = errors;
         }
     }
 
+    /// Run validation but only update the errors of the given keys
+    pub fn validate_fields(&self, fields: &[String]) {
+        if let Some(validate_fn) = &self.config.validate {
+            let mut errors = validate_fn(&self.state.borrow().values);
+            self.merge_async_errors(&mut errors, Some(fields));
+            let mut state = self.state.borrow_mut();
+            for field in fields {
+                match errors.remove(field) {
+                    Some(field_errors) => state.errors.insert(field.clone(), field_errors),
+                    None => state.errors.remove(field),
+                };
+            }
+        }
+    }
+
+    /// What screen readers hear after a successful submit
+    pub fn with_success_message(self, message: impl Into<String>) -> Self {
+        *self.success_message.borrow_mut() = message.into();
+        self
+    }
+
+    /// Submit handler. Validation errors and a failed submit are announced
+    /// assertively to screen readers, success politely.
     pub fn handle_submit(&self) -> impl Fn() {
-        let state = self.state.clone();
-        let config = self.config.clone();
+        let form = self.clone();
 
         move || {
-            let mut state_mut = state.borrow_mut();
-            state_mut.submitting = true;
-            state_mut.submitted = true;
-
-            // Validate all fields
-            if let Some(validate_fn) = &config.validate {
-                state_mut.errors = validate_fn(&state_mut.values);
+            form.start_submit();
+            // Nothing to wait for: reject invalid forms right away
+            if form.async_validation.borrow().pending.is_empty() && form.reject_invalid() {
+                return;
             }
 
-            if state_mut.errors.is_empty() {
-                // Submit form
-                let values = state_mut.values.clone();
-                let state_clone = state.clone();
-                let config = config.clone();
-
-                spawn_local(async move {
-                    match (config.on_submit)(&values).await {
-                        Ok(_) => {
-                            state_clone.borrow_mut().submitting = false;
-                        }
-                        Err(error) => {
-                            state_clone.borrow_mut().submitting = false;
-                            state_clone
-                                .borrow_mut()
-                                .errors
-                                .insert("_form".to_string(), vec![error]);
-                        }
-                    }
-                });
-            } else {
-                state_mut.submitting = false;
+            let form = form.clone();
+            spawn_local(async move {
+                form.finish_submit().await;
+            });
+        }
+    }
+
+    /// Validate, wait for async validators still in flight, then call
+    /// `on_submit` if everything passed. Returns whether `on_submit` succeeded.
+    pub async fn submit(&self) -> bool {
+        self.start_submit();
+        self.finish_submit().await
+    }
+
+    fn start_submit(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.submitting = true;
+            state.submitted = true;
+        }
+        self.validate();
+    }
+
+    async fn finish_submit(&self) -> bool {
+        self.validation_settled().await;
+        if self.reject_invalid() {
+            return false;
+        }
+
+        let values = self.values();
+        let result = (self.config.on_submit)(&values).await;
+        let mut state = self.state.borrow_mut();
+        state.submitting = false;
+        match result {
+            Ok(()) => {
+                use_announce().polite(self.success_message.borrow().clone());
+                true
+            }
+            Err(error) => {
+                use_announce().assertive(error.clone());
+                state.errors.insert(FORM_ERROR_KEY.to_string(), vec![error]);
+                false
             }
         }
     }
 
+    /// End a submit that has errors, announcing them
+    fn reject_invalid(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        if state.errors.is_empty() {
+            return false;
+        }
+        state.submitting = false;
+        use_announce().assertive(error_summary(&state.errors));
+        true
+    }
+
+    /// Restore the last loaded values (or the initial values) and clear
+    /// errors, touched and dirty state
     pub fn reset(&self) {
+        // Results still in flight no longer match the form
+        *self.async_validation.borrow_mut() = AsyncValidation::default();
         *self.state.borrow_mut() = FormState {
-            values: self.config.initial_values.clone(),
+            values: self.baseline.borrow().clone(),
             errors: HashMap::new(),
             touched: HashMap::new(),
+            dirty: HashMap::new(),
             submitting: false,
             submitted: false,
         };
     }
+
+    /// Load a full set of values, e.g. an existing record into an edit form.
+    /// The loaded values become what `reset()` returns to.
+    pub fn set_values(&self, values: T) {
+        *self.baseline.borrow_mut() = values;
+        self.reset();
+    }
+}
+
+fn is_current(validation: &Weak<RefCell<AsyncValidation>>, field: &str, generation: u64) -> bool {
+    validation.upgrade().is_some_and(|validation| {
+        validation.borrow().pending.get(field).map(|p| p.generation) == Some(generation)
+    })
+}
+
+/// Index and remainder of an item key of `array`: `emails[2].label` gives
+/// `(2, ".label")`
+fn item_index<'a>(key: &'a str, array: &str) -> Option<(usize, &'a str)> {
+    let (index, rest) = key.strip_prefix(array)?.strip_prefix('[')?.split_once(']')?;
+    Some((index.parse().ok()?, rest))
+}
+
+/// Drop the keys of `array[removed]` and shift the keys of later items down
+fn reindex_after_removal<V>(map: HashMap<String, V>, array: &str, removed: usize) -> HashMap<String, V> {
+    map.into_iter()
+        .filter_map(|(key, value)| match item_index(&key, array) {
+            Some((index, _)) if index == removed => None,
+            Some((index, rest)) if index > removed => {
+                Some((format!("{}{}", indexed_field(array, index - 1), rest), value))
+            }
+            _ => Some((key, value)),
+        })
+        .collect()
+}
+
+/// One sentence listing every error, form-level errors first:
+/// "2 errors: Email is invalid. Passwords don't match"
+fn error_summary(errors: &HashMap<String, Vec<String>>) -> String {
+    let mut fields: Vec<&String> = errors.keys().collect();
+    fields.sort_by_key(|field| (field.as_str() != FORM_ERROR_KEY, field.as_str()));
+
+    let messages: Vec<&str> = fields
+        .into_iter()
+        .flat_map(|field| errors[field].iter().map(String::as_str))
+        .collect();
+    let count = if messages.len() == 1 {
+        "1 error".to_string()
+    } else {
+        format!("{} errors", messages.len())
+    };
+    format!("{}: {}", count, messages.join(". "))
 }
 
 /// Validation rules
@@ -214,6 +582,19 @@ pub mod validators {
         }
     }
 
+    /// For array fields, checked against the item count:
+    /// `validators::min_items(1)(values.emails.len())`
+    pub fn min_items(min: usize) -> impl Fn(usize) -> Option<String> {
+        move |count: usize| {
+            if count < min {
+                let noun = if min == 1 { "item" } else { "items" };
+                Some(format!("Add at least {} {}", min, noun))
+            } else {
+                None
+            }
+        }
+    }
+
     pub fn compose(validators: Vec<ValidatorFn>) -> impl Fn(&str) -> Vec<String> {
         move |value: &str| {
             validators
@@ -257,9 +638,11 @@ impl<T: Clone + FormFields + 'static> TextField<T> {
         if let Some(value) = form.values().get_field(&self.name) {
             self.value = value;
         }
-        // Get any errors
-        if let Some(errors) = form.errors().get(&self.name) {
-            self.error = errors.first().cloned();
+        // Errors show once the user has left the field or submitted
+        if form.is_touched(&self.name) {
+            if let Some(errors) = form.errors().get(&self.name) {
+                self.error = errors.first().cloned();
+            }
         }
         self
     }
@@ -270,51 +653,70 @@ impl<T: Clone + FormFields + 'static> Component for TextField<T> {
         let form_clone = self.form.clone();
         let field_name = self.name.clone();
         
-        let on_change = form_clone.map(|form| Rc::new(move |value: String| {
+        let on_change = form_clone.clone().map(|form| Rc::new(move |value: String| {
             form.set_field_value(&field_name, value);
             form.set_field_touched(&field_name, true);
         }) as Rc<dyn Fn(String)>);
 
+        let field_name = self.name.clone();
+        let on_blur = form_clone.map(|form| Rc::new(move || {
+            form.set_field_touched(&field_name, true);
+        }) as Rc<dyn Fn()>);
+
+        // Associate the error message with the input so assistive tech announces it
+        let error_id = field_error_id(&self.name);
+        let mut input_attributes = vec![
+            ("type".to_string(), "text".to_string()),
+            ("name".to_string(), self.name.clone()),
+            ("value".to_string(), self.value.clone()),
+            (
+                "placeholder".to_string(),
+                self.placeholder.as_deref().unwrap_or("").to_string(),
+            ),
+        ];
+        if self.error.is_some() {
+            input_attributes.push(("aria-invalid".to_string(), "true".to_string()));
+            input_attributes.push(("aria-describedby".to_string(), error_id.clone()));
+        }
+        if self.form.as_ref().is_some_and(|form| form.is_validating(&self.name)) {
+            input_attributes.push(("aria-busy".to_string(), "true".to_string()));
+        }
+
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("form-field".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "label".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         attributes: vec![("for".to_string(), self.name.clone())],
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(self.label.clone())],
                 },
                 Element::Node {
                     tag: "input".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("form-input".to_string()),
-                        attributes: vec![
-                            ("type".to_string(), "text".to_string()),
-                            ("id".to_string(), self.name.clone()),
-                            ("name".to_string(), self.name.clone()),
-                            ("value".to_string(), self.value.clone()),
-                            (
-                                "placeholder".to_string(),
-                                self.placeholder.as_deref().unwrap_or("").to_string(),
-                            ),
-                        ],
+                        id: Some(self.name.clone()),
+                        attributes: input_attributes,
                         on_change,
+                        on_focus_out: on_blur,
                         ..Default::default()
-                    },
+                    }),
                     children: vec![],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("form-error".to_string()),
+                        id: Some(error_id),
+                        attributes: vec![("aria-live".to_string(), "polite".to_string())],
                         ..Default::default()
-                    },
+                    }),
                     children: if let Some(error) = &self.error {
                         vec![Element::Text(error.clone())]
                     } else {
@@ -326,6 +728,11 @@ impl<T: Clone + FormFields + 'static> Component for TextField<T> {
     }
 }
 
+/// DOM id of the error message element for a field, used for `aria-describedby`
+pub fn field_error_id(name: &str) -> String {
+    format!("{}-error", name)
+}
+
 /// Form field trait
 #[allow(dead_code)]
 trait FormField {
@@ -405,12 +812,12 @@ impl<T: Clone + 'static> Component for FormComponent<T> {
 
         Element::Node {
             tag: "form".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 on_submit: Some(Rc::new(move |_event| {
                     on_submit();
                 })),
                 ..Default::default()
-            },
+            }),
             children: self.children.clone(),
         }
     }
@@ -419,3 +826,380 @@ impl<T: Clone + 'static> Component for FormComponent<T> {
 // Re-exports
 use crate::fetch::post;
 use wasm_bindgen_futures::spawn_local;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::Cell;
+
+    #[derive(Clone, Default)]
+    struct Profile {
+        email: String,
+        password: String,
+        confirm_password: String,
+    }
+
+    impl FormFields for Profile {
+        fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
+            match field {
+                "email" => self.email = value,
+                "password" => self.password = value,
+                "confirm_password" => self.confirm_password = value,
+                _ => return Err(format!("Unknown field: {}", field)),
+            }
+            Ok(())
+        }
+
+        fn get_field(&self, field: &str) -> Option<String> {
+            match field {
+                "email" => Some(self.email.clone()),
+                "password" => Some(self.password.clone()),
+                "confirm_password" => Some(self.confirm_password.clone()),
+                _ => None,
+            }
+        }
+
+        fn field_names(&self) -> Vec<&'static str> {
+            vec!["email", "password", "confirm_password"]
+        }
+    }
+
+    fn attr<'a>(props: &'a Props, name: &str) -> Option<&'a str> {
+        props
+            .attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn profile_form() -> Form<Profile> {
+        use_form(FormConfig {
+            initial_values: Profile::default(),
+            validate: Some(Box::new(|values: &Profile| {
+                let mut errors = HashMap::new();
+                if let Some(error) = validators::email(&values.email) {
+                    errors.insert("email".to_string(), vec![error]);
+                }
+                if values.password != values.confirm_password {
+                    errors.insert(
+                        "confirm_password".to_string(),
+                        vec!["Passwords don't match".to_string()],
+                    );
+                }
+                errors
+            })),
+            validate_field_async: HashMap::new(),
+            on_submit: Box::new(|_| Box::pin(async { Ok(()) })),
+        })
+    }
+
+    #[test]
+    fn test_set_values_then_reset_restores_loaded_record() {
+        let form = profile_form();
+        form.set_values(Profile {
+            email: "ada@example.com".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(form.values().email, "ada@example.com");
+
+        form.set_field_value("email", "not-an-email");
+        form.set_field_touched("email", true);
+        assert!(!form.is_valid());
+
+        form.reset();
+        assert_eq!(form.values().email, "ada@example.com");
+        assert!(form.is_valid());
+        assert!(form.state.borrow().touched.is_empty());
+    }
+
+    #[test]
+    fn test_touched_on_blur_and_dirty_against_initial_value() {
+        let form = profile_form();
+        form.set_values(Profile {
+            email: "ada@example.com".to_string(),
+            ..Default::default()
+        });
+
+        let field = TextField::new("email", "Email").bind(&form);
+        let Element::Node { children, .. } = field.render() else {
+            panic!("expected a node");
+        };
+        let Element::Node { props: input, .. } = &children[1] else {
+            panic!("expected input");
+        };
+        assert!(!form.is_touched("email"));
+        (input.on_focus_out.clone().unwrap())();
+        assert!(form.is_touched("email"));
+        assert!(!form.is_dirty("email"));
+
+        form.set_field_value("email", "grace@example.com");
+        assert!(form.is_dirty("email"));
+        form.set_field_value("email", "ada@example.com");
+        assert!(!form.is_dirty("email"));
+        assert!(!form.is_form_dirty());
+
+        // An untouched field's error stays hidden until submit
+        form.set_field_value("confirm_password", "secret");
+        assert!(form.errors().contains_key("confirm_password"));
+        assert!(TextField::new("confirm_password", "Confirm").bind(&form).error.is_none());
+        assert!(!block_on(form.submit()));
+        assert!(form.is_touched("password"));
+        assert!(TextField::new("confirm_password", "Confirm").bind(&form).error.is_some());
+
+        form.reset();
+        assert!(!form.is_touched("email"));
+        assert!(!form.is_dirty("confirm_password"));
+        assert_eq!(form.values().email, "ada@example.com");
+    }
+
+    #[test]
+    fn test_dependent_field_revalidates() {
+        let form = profile_form();
+        form.add_dependency("confirm_password", "password");
+
+        form.set_field_value("password", "secret");
+        form.set_field_value("confirm_password", "secret");
+        assert!(!form.errors().contains_key("confirm_password"));
+        // Only the changed field (and its dependents) are re-validated
+        assert!(!form.errors().contains_key("email"));
+
+        form.set_field_value("password", "secret2");
+        assert_eq!(
+            form.errors().get("confirm_password"),
+            Some(&vec!["Passwords don't match".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_invalid_submit_announces_errors() {
+        let form = profile_form();
+        form.set_field_value("password", "secret");
+        form.set_field_value("confirm_password", "other");
+
+        form.handle_submit()();
+
+        let announced = use_announce().message(crate::announce::Politeness::Assertive);
+        assert_eq!(announced, "2 errors: Passwords don't match. Invalid email address");
+    }
+
+    #[derive(Clone, Default)]
+    struct Signup {
+        username: String,
+    }
+
+    impl FormFields for Signup {
+        fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
+            match field {
+                "username" => self.username = value,
+                _ => return Err(format!("Unknown field: {}", field)),
+            }
+            Ok(())
+        }
+
+        fn get_field(&self, field: &str) -> Option<String> {
+            (field == "username").then(|| self.username.clone())
+        }
+
+        fn field_names(&self) -> Vec<&'static str> {
+            vec!["username"]
+        }
+    }
+
+    #[test]
+    fn test_taken_username_blocks_submit() {
+        let checks = Rc::new(Cell::new(0));
+        let submitted = Rc::new(Cell::new(false));
+        let (checked, on_submit_called) = (checks.clone(), submitted.clone());
+
+        let form = use_form(FormConfig {
+            initial_values: Signup::default(),
+            validate: Some(Box::new(|values: &Signup| {
+                let mut errors = HashMap::new();
+                if let Some(error) = validators::required(&values.username) {
+                    errors.insert("username".to_string(), vec![error]);
+                }
+                errors
+            })),
+            validate_field_async: HashMap::from([(
+                "username".to_string(),
+                async_validator(move |username: String| {
+                    checked.set(checked.get() + 1);
+                    async move { (username == "ada").then(|| "Username is taken".to_string()) }
+                }),
+            )]),
+            on_submit: Box::new(move |_| {
+                on_submit_called.set(true);
+                Box::pin(async { Ok(()) })
+            }),
+        });
+
+        // The newer keystroke supersedes the check of "ad"
+        form.set_field_value("username", "ad");
+        form.set_field_value("username", "ada");
+        assert!(form.is_validating("username"));
+
+        // Submit waits for the check, then refuses
+        assert!(!block_on(form.submit()));
+        assert_eq!(checks.get(), 1);
+        assert!(!submitted.get());
+        assert!(!form.is_validating("username"));
+        assert_eq!(form.errors()["username"], vec!["Username is taken".to_string()]);
+
+        form.set_field_value("username", "grace");
+        assert!(block_on(form.submit()));
+        assert!(submitted.get());
+        assert!(form.is_valid());
+    }
+
+    #[test]
+    fn test_text_field_associates_label_and_error() {
+        let mut field = TextField::<Profile>::new("email", "Email");
+        field.error = Some("Invalid email address".to_string());
+
+        let Element::Node { children, .. } = field.render() else {
+            panic!("expected a node");
+        };
+
+        let Element::Node { props: label, .. } = &children[0] else {
+            panic!("expected label");
+        };
+        let Element::Node { props: input, .. } = &children[1] else {
+            panic!("expected input");
+        };
+        let Element::Node { props: error, .. } = &children[2] else {
+            panic!("expected error");
+        };
+
+        assert_eq!(attr(label, "for"), input.id.as_deref());
+        assert_eq!(attr(input, "aria-invalid"), Some("true"));
+        assert_eq!(attr(input, "aria-describedby"), error.id.as_deref());
+    }
+
+    #[test]
+    fn test_valid_text_field_has_no_aria_invalid() {
+        let field = TextField::<Profile>::new("email", "Email");
+
+        let Element::Node { children, .. } = field.render() else {
+            panic!("expected a node");
+        };
+        let Element::Node { props: input, .. } = &children[1] else {
+            panic!("expected input");
+        };
+
+        assert_eq!(attr(input, "aria-invalid"), None);
+        assert_eq!(attr(input, "aria-describedby"), None);
+    }
+
+    #[derive(Clone, Default)]
+    struct Contacts {
+        emails: Vec<String>,
+    }
+
+    impl FormFields for Contacts {
+        fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
+            match parse_indexed_field(field) {
+                Some(("emails", index)) if index < self.emails.len() => {
+                    self.emails[index] = value;
+                    Ok(())
+                }
+                _ => Err(format!("Unknown field: {}", field)),
+            }
+        }
+
+        fn get_field(&self, field: &str) -> Option<String> {
+            match parse_indexed_field(field) {
+                Some(("emails", index)) => self.emails.get(index).cloned(),
+                _ => None,
+            }
+        }
+
+        fn field_names(&self) -> Vec<&'static str> {
+            vec![]
+        }
+
+        fn push_array_item(&mut self, array: &str) -> Result<(), String> {
+            match array {
+                "emails" => self.emails.push(String::new()),
+                _ => return Err(format!("Unknown array field: {}", array)),
+            }
+            Ok(())
+        }
+
+        fn remove_array_item(&mut self, array: &str, index: usize) -> Result<(), String> {
+            match array {
+                "emails" if index < self.emails.len() => {
+                    self.emails.remove(index);
+                    Ok(())
+                }
+                _ => Err(format!("No item {} in {}", index, array)),
+            }
+        }
+
+        fn array_len(&self, array: &str) -> Option<usize> {
+            (array == "emails").then_some(self.emails.len())
+        }
+
+        fn array_field_names(&self) -> Vec<&'static str> {
+            vec!["emails"]
+        }
+    }
+
+    fn contacts_form(min_emails: usize) -> Form<Contacts> {
+        use_form(FormConfig {
+            initial_values: Contacts::default(),
+            validate: Some(Box::new(move |values: &Contacts| {
+                let mut errors = HashMap::new();
+                if let Some(error) = validators::min_items(min_emails)(values.emails.len()) {
+                    errors.insert("emails".to_string(), vec![error]);
+                }
+                for (index, email) in values.emails.iter().enumerate() {
+                    if let Some(error) = validators::email(email) {
+                        errors.insert(indexed_field("emails", index), vec![error]);
+                    }
+                }
+                errors
+            })),
+            validate_field_async: HashMap::new(),
+            on_submit: Box::new(|_| Box::pin(async { Ok(()) })),
+        })
+    }
+
+    #[test]
+    fn test_removing_array_item_reindexes_values_and_errors() {
+        let form = contacts_form(0);
+        form.validate();
+        assert!(form.is_valid(), "an empty array is valid without min_items");
+
+        form.push_array_item("emails");
+        form.push_array_item("emails");
+        form.set_field_value("emails[0]", "ada@example.com");
+        form.set_field_value("emails[1]", "grace");
+        form.set_field_touched("emails[1]", true);
+        assert!(form.errors().contains_key("emails[1]"));
+
+        form.remove_array_item("emails", 0);
+
+        assert_eq!(form.array_len("emails"), 1);
+        assert_eq!(form.values().emails, vec!["grace".to_string()]);
+        let errors = form.errors();
+        assert_eq!(errors["emails[0]"], vec!["Invalid email address".to_string()]);
+        assert!(!errors.contains_key("emails[1]"));
+        let touched = form.state.borrow().touched.clone();
+        assert_eq!(touched.get("emails[0]"), Some(&true));
+        assert!(!touched.contains_key("emails[1]"));
+    }
+
+    #[test]
+    fn test_min_items_rejects_empty_array() {
+        let form = contacts_form(1);
+        form.validate();
+        assert_eq!(form.errors()["emails"], vec!["Add at least 1 item".to_string()]);
+
+        form.push_array_item("emails");
+        assert!(!form.errors().contains_key("emails"));
+
+        form.remove_array_item("emails", 5);
+        assert_eq!(form.errors()["emails"], vec!["No item 5 in emails".to_string()]);
+    }
+}
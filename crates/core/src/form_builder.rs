@@ -1,15 +1,20 @@
 //! Form builder utilities for easier form creation
 
-use crate::form::{Form, FormConfig, FormFields};
+use crate::form::{async_validator, Form, FormConfig, FormFields, FORM_ERROR_KEY};
+use crate::form_files::FileRules;
 use std::collections::HashMap;
+use std::future::Future;
 
 type ValidatorFn = Box<dyn Fn(&str) -> Option<String>>;
 type ValidatorsMap = HashMap<String, Vec<ValidatorFn>>;
+type CrossFieldValidatorFn<T> = Box<dyn Fn(&T) -> Option<String>>;
 
 /// Fluent form builder
 pub struct FormBuilder<T> {
     initial_values: T,
     validators: ValidatorsMap,
+    cross_field_validators: Vec<(String, Vec<String>, CrossFieldValidatorFn<T>)>,
+    async_validators: HashMap<String, crate::form::AsyncValidatorFn>,
 }
 
 impl<T: Clone + Default + 'static> Default for FormBuilder<T> {
@@ -17,6 +22,8 @@ impl<T: Clone + Default + 'static> Default for FormBuilder<T> {
         Self {
             initial_values: T::default(),
             validators: HashMap::new(),
+            cross_field_validators: Vec::new(),
+            async_validators: HashMap::new(),
         }
     }
 }
@@ -44,6 +51,61 @@ impl<T: Clone + Default + 'static> FormBuilder<T> {
         self
     }
     
+    /// Add a validator that reads several fields. Its error is reported under
+    /// `field` and re-checked whenever any of `depends_on` changes.
+    pub fn add_cross_field_validator<F>(mut self, field: &str, depends_on: &[&str], validator: F) -> Self
+    where
+        F: Fn(&T) -> Option<String> + 'static
+    {
+        self.cross_field_validators.push((
+            field.to_string(),
+            depends_on.iter().map(|d| d.to_string()).collect(),
+            Box::new(validator),
+        ));
+        self
+    }
+
+    /// Check a field against the server once typing pauses, e.g. whether a
+    /// username is taken. Replaces any async validator already set for `field`.
+    pub fn add_async_validator<F, Fut>(mut self, field: &str, validator: F) -> Self
+    where
+        F: Fn(String) -> Fut + 'static,
+        Fut: Future<Output = Option<String>> + 'static,
+    {
+        self.async_validators.insert(field.to_string(), async_validator(validator));
+        self
+    }
+
+    /// Validate a file field with the given rules
+    pub fn add_file_rules(mut self, field: &str, rules: FileRules) -> Self
+    where
+        T: FormFields
+    {
+        let name = field.to_string();
+        self.cross_field_validators.push((
+            field.to_string(),
+            Vec::new(),
+            Box::new(move |values: &T| {
+                let files = values.get_files(&name).unwrap_or_default();
+                let errors = rules.validate(&files);
+                if errors.is_empty() {
+                    None
+                } else {
+                    Some(errors.join(". "))
+                }
+            }),
+        ));
+        self
+    }
+
+    /// Add a form-level validator; its error is reported under `FORM_ERROR_KEY`
+    pub fn add_form_validator<F>(self, validator: F) -> Self
+    where
+        F: Fn(&T) -> Option<String> + 'static
+    {
+        self.add_cross_field_validator(FORM_ERROR_KEY, &[], validator)
+    }
+
     /// Build the form
     pub fn build<F>(self, on_submit: F) -> Form<T>
     where
@@ -51,6 +113,13 @@ impl<T: Clone + Default + 'static> FormBuilder<T> {
         T: FormFields,
     {
         let validators = self.validators;
+        let cross_field_validators = self.cross_field_validators;
+        let dependencies: Vec<(String, String)> = cross_field_validators
+            .iter()
+            .flat_map(|(field, depends_on, _)| {
+                depends_on.iter().map(move |d| (field.clone(), d.clone()))
+            })
+            .collect();
         
         let validate = move |values: &T| -> HashMap<String, Vec<String>> {
             let mut errors = HashMap::new();
@@ -67,15 +136,28 @@ impl<T: Clone + Default + 'static> FormBuilder<T> {
                     }
                 }
             }
+
+            for (field, _, validator) in &cross_field_validators {
+                if let Some(error) = validator(values) {
+                    errors.entry(field.clone()).or_insert_with(Vec::new).push(error);
+                }
+            }
             
             errors
         };
         
-        crate::form::use_form(FormConfig {
+        let form = crate::form::use_form(FormConfig {
             initial_values: self.initial_values,
             validate: Some(Box::new(validate)),
+            validate_field_async: self.async_validators,
             on_submit: Box::new(on_submit),
-        })
+        });
+
+        for (field, depends_on) in &dependencies {
+            form.add_dependency(field, depends_on);
+        }
+
+        form
     }
 }
 
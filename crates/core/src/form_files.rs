@@ -0,0 +1,462 @@
+//! File fields for forms - L5/L6
+//!
+//! Lets a `Form<T>` hold selected files next to its text fields, validate them
+//! (required, size, type) and submit everything together as multipart data.
+
+use crate::component::{Component, Element, Props};
+use crate::form::{field_error_id, indexed_field, Form, FormFields};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{File, FormData, Headers, HtmlInputElement, Request, RequestInit, Response};
+
+/// Metadata needed to validate a file
+pub trait FileLike {
+    fn file_name(&self) -> String;
+    fn file_size(&self) -> u64;
+    fn mime_type(&self) -> String;
+}
+
+impl FileLike for File {
+    fn file_name(&self) -> String {
+        self.name()
+    }
+
+    fn file_size(&self) -> u64 {
+        self.size() as u64
+    }
+
+    fn mime_type(&self) -> String {
+        self.type_()
+    }
+}
+
+/// Files selected in a file input; use as the type of a file field
+#[derive(Debug, Clone, Default)]
+pub struct FormFiles {
+    files: Vec<File>,
+}
+
+impl FormFiles {
+    pub fn new(files: Vec<File>) -> Self {
+        FormFiles { files }
+    }
+
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    pub fn first(&self) -> Option<&File> {
+        self.files.first()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// Validation rules for a file field
+#[derive(Debug, Clone, Default)]
+pub struct FileRules {
+    required: bool,
+    max_size: Option<u64>,
+    allowed_types: Vec<String>,
+}
+
+impl FileRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Maximum size of each file in bytes
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Allowed MIME types; `image/*` style wildcards are supported
+    pub fn allowed_types(mut self, types: Vec<&str>) -> Self {
+        self.allowed_types = types.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Value for the input's `accept` attribute
+    pub fn accept(&self) -> Option<String> {
+        if self.allowed_types.is_empty() {
+            None
+        } else {
+            Some(self.allowed_types.join(","))
+        }
+    }
+
+    fn type_allowed(&self, mime_type: &str) -> bool {
+        self.allowed_types.is_empty()
+            || self.allowed_types.iter().any(|allowed| {
+                match allowed.strip_suffix("/*") {
+                    Some(prefix) => mime_type
+                        .split('/')
+                        .next()
+                        .map(|kind| kind == prefix)
+                        .unwrap_or(false),
+                    None => allowed == mime_type,
+                }
+            })
+    }
+
+    /// Validate the selected files, returning one message per problem
+    pub fn validate<F: FileLike>(&self, files: &[F]) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.required && files.is_empty() {
+            errors.push("Please select a file".to_string());
+        }
+
+        for file in files {
+            if let Some(max) = self.max_size {
+                if file.file_size() > max {
+                    errors.push(format!(
+                        "{} is too large. Maximum size: {} bytes",
+                        file.file_name(),
+                        max
+                    ));
+                }
+            }
+
+            if !self.type_allowed(&file.mime_type()) {
+                errors.push(format!(
+                    "{} has a file type that is not allowed: {}",
+                    file.file_name(),
+                    file.mime_type()
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+impl<T: Clone + FormFields + 'static> Form<T> {
+    /// Store the files selected for a file field and re-validate it
+    pub fn set_field_files(&self, field: &str, files: Vec<File>) {
+        self.set_field_with(field, |values| values.set_files(field, files));
+    }
+}
+
+/// Build multipart form data from all text and file fields
+pub fn to_form_data<T: FormFields>(values: &T) -> Result<FormData, JsValue> {
+    let form_data = FormData::new()?;
+
+    for field in values.field_names() {
+        if let Some(value) = values.get_field(field) {
+            form_data.append_with_str(field, &value)?;
+        }
+    }
+
+    for array in values.array_field_names() {
+        for index in 0..values.array_len(array).unwrap_or(0) {
+            let field = indexed_field(array, index);
+            if let Some(value) = values.get_field(&field) {
+                form_data.append_with_str(&field, &value)?;
+            }
+        }
+    }
+
+    for field in values.file_field_names() {
+        for file in values.get_files(field).unwrap_or_default() {
+            form_data.append_with_blob_and_filename(field, &file, &file.name())?;
+        }
+    }
+
+    Ok(form_data)
+}
+
+/// POST the form's values to `url` as `multipart/form-data`
+pub async fn submit_multipart<T: FormFields>(url: &str, values: &T) -> Result<Response, String> {
+    let form_data = to_form_data(values).map_err(|_| "Failed to build form data".to_string())?;
+
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    // The browser sets the multipart boundary itself, so no Content-Type here
+    opts.set_body(&form_data.into());
+
+    let headers = Headers::new().map_err(|_| "Failed to create headers")?;
+    if let Some(token) = crate::auth::JwtAuthProvider::get_stored_token() {
+        headers
+            .set("Authorization", &format!("Bearer {}", token))
+            .map_err(|_| "Failed to set auth header")?;
+    }
+    opts.set_headers(&headers.into());
+
+    let request = Request::new_with_str_and_init(url, &opts)
+        .map_err(|_| "Failed to create request".to_string())?;
+    let window = web_sys::window().ok_or("No window found")?;
+    let response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|_| "Form submission failed".to_string())?;
+
+    let response: Response = response
+        .dyn_into()
+        .map_err(|_| "Failed to get response".to_string())?;
+    if response.ok() {
+        Ok(response)
+    } else {
+        Err(format!("Server error: {}", response.status()))
+    }
+}
+
+/// File input bound to a form's file field
+pub struct FileField<T> {
+    name: String,
+    label: String,
+    multiple: bool,
+    accept: Option<String>,
+    form: Option<Form<T>>,
+    error: Option<String>,
+}
+
+impl<T: Clone + FormFields + 'static> FileField<T> {
+    pub fn new(name: impl Into<String>, label: impl Into<String>) -> Self {
+        FileField {
+            name: name.into(),
+            label: label.into(),
+            multiple: false,
+            accept: None,
+            form: None,
+            error: None,
+        }
+    }
+
+    pub fn multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Restrict the file picker to the types allowed by `rules`
+    pub fn rules(mut self, rules: &FileRules) -> Self {
+        self.accept = rules.accept();
+        self
+    }
+
+    pub fn bind(mut self, form: &Form<T>) -> Self {
+        self.form = Some(form.clone());
+        if let Some(errors) = form.errors().get(&self.name) {
+            self.error = errors.first().cloned();
+        }
+        self
+    }
+}
+
+impl<T: Clone + FormFields + 'static> Component for FileField<T> {
+    fn render(&self) -> Element {
+        let error_id = field_error_id(&self.name);
+
+        // The change event only carries the input's text value, so read the
+        // FileList from the element itself
+        let on_change = self.form.clone().map(|form| {
+            let field_name = self.name.clone();
+            Rc::new(move |_value: String| {
+                let input = web_sys::window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.get_element_by_id(&field_name))
+                    .and_then(|e| e.dyn_into::<HtmlInputElement>().ok());
+
+                if let Some(list) = input.and_then(|input| input.files()) {
+                    let files = (0..list.length()).filter_map(|i| list.item(i)).collect();
+                    form.set_field_files(&field_name, files);
+                    form.set_field_touched(&field_name, true);
+                }
+            }) as Rc<dyn Fn(String)>
+        });
+
+        let mut attributes = vec![
+            ("type".to_string(), "file".to_string()),
+            ("name".to_string(), self.name.clone()),
+        ];
+        if self.multiple {
+            attributes.push(("multiple".to_string(), "multiple".to_string()));
+        }
+        if let Some(accept) = &self.accept {
+            attributes.push(("accept".to_string(), accept.clone()));
+        }
+        if self.error.is_some() {
+            attributes.push(("aria-invalid".to_string(), "true".to_string()));
+            attributes.push(("aria-describedby".to_string(), error_id.clone()));
+        }
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("form-field".to_string()),
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "label".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![("for".to_string(), self.name.clone())],
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(self.label.clone())],
+                },
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props {
+                        class: Some("form-input".to_string()),
+                        id: Some(self.name.clone()),
+                        attributes,
+                        on_change,
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                },
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("form-error".to_string()),
+                        id: Some(error_id),
+                        attributes: vec![("aria-live".to_string(), "polite".to_string())],
+                        ..Default::default()
+                    }),
+                    children: self.error.iter().cloned().map(Element::Text).collect(),
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockFile(&'static str, u64, &'static str);
+
+    impl FileLike for MockFile {
+        fn file_name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn file_size(&self) -> u64 {
+            self.1
+        }
+
+        fn mime_type(&self) -> String {
+            self.2.to_string()
+        }
+    }
+
+    #[test]
+    fn test_file_rules() {
+        let rules = FileRules::new()
+            .required()
+            .max_size(1024)
+            .allowed_types(vec!["image/*", "application/pdf"]);
+
+        assert_eq!(rules.validate::<MockFile>(&[]).len(), 1);
+        assert!(rules.validate(&[MockFile("avatar.png", 512, "image/png")]).is_empty());
+        assert!(rules.validate(&[MockFile("cv.pdf", 100, "application/pdf")]).is_empty());
+
+        let errors = rules.validate(&[MockFile("big.zip", 4096, "application/zip")]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(rules.accept().as_deref(), Some("image/*,application/pdf"));
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use crate::form_builder::FormBuilder;
+    use js_sys::{Array, Uint8Array};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[derive(Clone, Default)]
+    struct ProfileForm {
+        name: String,
+        avatar: FormFiles,
+    }
+
+    impl FormFields for ProfileForm {
+        fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
+            match field {
+                "name" => {
+                    self.name = value;
+                    Ok(())
+                }
+                _ => Err(format!("Unknown field: {}", field)),
+            }
+        }
+
+        fn get_field(&self, field: &str) -> Option<String> {
+            match field {
+                "name" => Some(self.name.clone()),
+                _ => None,
+            }
+        }
+
+        fn field_names(&self) -> Vec<&'static str> {
+            vec!["name"]
+        }
+
+        fn set_files(&mut self, field: &str, files: Vec<File>) -> Result<(), String> {
+            match field {
+                "avatar" => {
+                    self.avatar = FormFiles::new(files);
+                    Ok(())
+                }
+                _ => Err(format!("Unknown file field: {}", field)),
+            }
+        }
+
+        fn get_files(&self, field: &str) -> Option<Vec<File>> {
+            match field {
+                "avatar" => Some(self.avatar.files().to_vec()),
+                _ => None,
+            }
+        }
+
+        fn file_field_names(&self) -> Vec<&'static str> {
+            vec!["avatar"]
+        }
+    }
+
+    fn create_file(name: &str, content: &str) -> File {
+        let bytes = content.as_bytes();
+        let array = Uint8Array::new_with_length(bytes.len() as u32);
+        array.copy_from(bytes);
+        let parts = Array::new();
+        parts.push(&array.buffer());
+        File::new_with_blob_sequence(&parts.into(), name).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_profile_form_with_avatar() {
+        let form = FormBuilder::<ProfileForm>::new()
+            .add_validator("name", crate::form_builder::validators::required("Name is required"))
+            .add_file_rules("avatar", FileRules::new().required().max_size(1024))
+            .build(|_| Box::pin(async { Ok(()) }));
+
+        form.validate();
+        assert!(form.errors().contains_key("name"));
+        assert!(form.errors().contains_key("avatar"));
+
+        form.set_field_value("name", "Ada");
+        form.set_field_files("avatar", vec![create_file("avatar.png", "png-bytes")]);
+        assert!(form.is_valid());
+
+        let data = to_form_data(&form.values()).unwrap();
+        assert_eq!(data.get("name").as_string().as_deref(), Some("Ada"));
+        assert!(data.get("avatar").is_instance_of::<File>());
+    }
+}
@@ -11,6 +11,53 @@ pub trait FormFields {
     
     /// Get all field names
     fn field_names(&self) -> Vec<&'static str>;
+
+    /// Set the selected files of a file field
+    fn set_files(&mut self, field: &str, _files: Vec<web_sys::File>) -> Result<(), String> {
+        Err(format!("Unknown file field: {}", field))
+    }
+
+    /// Get the selected files of a file field
+    fn get_files(&self, _field: &str) -> Option<Vec<web_sys::File>> {
+        None
+    }
+
+    /// Get all file field names
+    fn file_field_names(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    /// Append an empty item to an array field. Items are set and read
+    /// through `set_field`/`get_field` as `emails[0]`, `emails[1]`, ...
+    fn push_array_item(&mut self, array: &str) -> Result<(), String> {
+        Err(format!("Unknown array field: {}", array))
+    }
+
+    /// Remove the item at `index` from an array field
+    fn remove_array_item(&mut self, array: &str, _index: usize) -> Result<(), String> {
+        Err(format!("Unknown array field: {}", array))
+    }
+
+    /// Number of items in an array field
+    fn array_len(&self, _array: &str) -> Option<usize> {
+        None
+    }
+
+    /// Get all array field names
+    fn array_field_names(&self) -> Vec<&'static str> {
+        vec![]
+    }
+}
+
+/// Field name of an array item: `indexed_field("emails", 2)` is `emails[2]`
+pub fn indexed_field(array: &str, index: usize) -> String {
+    format!("{}[{}]", array, index)
+}
+
+/// Split an array item's field name, `emails[2]`, into `("emails", 2)`
+pub fn parse_indexed_field(field: &str) -> Option<(&str, usize)> {
+    let (array, rest) = field.strip_suffix(']')?.split_once('[')?;
+    Some((array, rest.parse().ok()?))
 }
 
 /// Macro to implement FormFields for a struct
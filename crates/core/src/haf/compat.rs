@@ -24,12 +24,18 @@ pub fn element_to_vnode(element: &old::Element) -> VNode<L1> {
             // In production, we'd need a proper adapter
             VNode::Text("[Legacy Component]".to_string())
         }
+        // HAF has no portals yet, so the children render in place
+        old::Element::Portal { children, .. } => {
+            VNode::Fragment(children.iter().map(element_to_vnode).collect())
+        }
+        old::Element::Fragment(children) => VNode::Fragment(children.iter().map(element_to_vnode).collect()),
     }
 }
 
 /// Convert old Props to HAF VProps
 fn props_to_vprops(props: &old::Props) -> VProps {
     let mut vprops = VProps {
+        key: props.key.clone(),
         class: props.class.clone(),
         id: props.id.clone(),
         attributes: props.attributes.clone(),
@@ -50,6 +56,28 @@ fn props_to_vprops(props: &old::Props) -> VProps {
     if props.on_input.is_some() {
         vprops.events.push(("input".to_string(), EventId(4)));
     }
+    if props.on_keydown.is_some() {
+        vprops.events.push(("keydown".to_string(), EventId(5)));
+    }
+    if props.on_focus_out.is_some() {
+        vprops.events.push(("focusout".to_string(), EventId(6)));
+    }
+    if props.on_pointer_down.is_some() {
+        vprops.events.push(("pointerdown".to_string(), EventId(7)));
+    }
+    if props.on_focus_in.is_some() {
+        vprops.events.push(("focusin".to_string(), EventId(8)));
+    }
+    if props.on_hover.is_some() {
+        vprops.events.push(("pointerenter".to_string(), EventId(9)));
+        vprops.events.push(("pointerleave".to_string(), EventId(9)));
+    }
+    if props.on_error.is_some() {
+        vprops.events.push(("error".to_string(), EventId(10)));
+    }
+    if props.on_scroll.is_some() {
+        vprops.events.push(("scroll".to_string(), EventId(11)));
+    }
     
     vprops
 }
@@ -92,7 +120,7 @@ fn vnode_to_element<L: Layer>(vnode: &VNode<L>) -> old::Element {
         VNode::Element { tag, props, children } => {
             old::Element::Node {
                 tag: tag.clone(),
-                props: vprops_to_props(props),
+                props: Box::new(vprops_to_props(props)),
                 children: children.iter().map(vnode_to_element).collect(),
             }
         }
@@ -104,7 +132,7 @@ fn vnode_to_element<L: Layer>(vnode: &VNode<L>) -> old::Element {
             // Old system doesn't have fragments, wrap in div
             old::Element::Node {
                 tag: "div".to_string(),
-                props: old::Props::default(),
+                props: Box::new(old::Props::default()),
                 children: children.iter().map(vnode_to_element).collect(),
             }
         }
@@ -117,12 +145,20 @@ fn vprops_to_props(vprops: &VProps) -> old::Props {
     old::Props {
         class: vprops.class.clone(),
         id: vprops.id.clone(),
+        key: vprops.key.clone(),
         attributes: vprops.attributes.clone(),
         // Events would need proper handling with closures
         on_click: None,
         on_submit: None,
         on_change: None,
         on_input: None,
+        on_keydown: None,
+        on_focus_in: None,
+        on_focus_out: None,
+        on_hover: None,
+        on_pointer_down: None,
+        on_error: None,
+        on_scroll: None,
     }
 }
 
@@ -163,10 +199,10 @@ mod compat_tests {
         fn render(&self) -> old::Element {
             old::Element::Node {
                 tag: "button".to_string(),
-                props: old::Props {
+                props: Box::new(old::Props {
                     class: Some("btn".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![old::Element::Text(self.label.clone())],
             }
         }
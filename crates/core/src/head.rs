@@ -0,0 +1,550 @@
+//! Document Head - L7
+//!
+//! Meta tags the framework manages in `<head>`, on the server and in the
+//! browser. Every page gets a mobile-friendly viewport without touching the
+//! HTML template; apps that need zoom control say so:
+//!
+//! ```ignore
+//! // Client, before mounting
+//! Viewport::default().maximum_scale(1.0).user_scalable(false).apply();
+//!
+//! // Server
+//! let ctx = SSRContext::new().with_viewport(Viewport::default().viewport_fit(ViewportFit::Cover));
+//! ```
+//!
+//! Components declare their own title, meta and link tags with `Head`,
+//! anywhere in the tree:
+//!
+//! ```ignore
+//! Head::new()
+//!     .title(format!("{} - Todos", list.name))
+//!     .meta("description", "Your todo lists")
+//!     .meta_property("og:title", &list.name)
+//!     .link("canonical", "https://example.com/todos")
+//! ```
+//!
+//! The last `Head` rendered wins a title or a meta tag with the same
+//! `name`/`property`. In the browser `document.head` follows as `Head`s
+//! mount, re-render and unmount; the page's own title comes back when no
+//! `Head` sets one. The server renderer writes the collected tags into the
+//! `<head>` it emits.
+
+use crate::component::{Component, Element, Props};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How the page fits displays with notches and rounded corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportFit {
+    Auto,
+    Contain,
+    /// Extend under the notch; pad with `env(safe-area-inset-*)`
+    Cover,
+}
+
+impl ViewportFit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ViewportFit::Auto => "auto",
+            ViewportFit::Contain => "contain",
+            ViewportFit::Cover => "cover",
+        }
+    }
+}
+
+/// `<meta name="viewport">` settings. The default,
+/// `width=device-width, initial-scale=1`, is right for almost every app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    /// `device-width` or a pixel count
+    pub width: String,
+    pub initial_scale: Option<f32>,
+    pub minimum_scale: Option<f32>,
+    pub maximum_scale: Option<f32>,
+    /// `Some(false)` stops pinch zoom, which hurts low-vision users; prefer
+    /// leaving it unset
+    pub user_scalable: Option<bool>,
+    pub viewport_fit: Option<ViewportFit>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            width: "device-width".to_string(),
+            initial_scale: Some(1.0),
+            minimum_scale: None,
+            maximum_scale: None,
+            user_scalable: None,
+            viewport_fit: None,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn width(mut self, width: impl Into<String>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn initial_scale(mut self, scale: f32) -> Self {
+        self.initial_scale = Some(scale);
+        self
+    }
+
+    pub fn minimum_scale(mut self, scale: f32) -> Self {
+        self.minimum_scale = Some(scale);
+        self
+    }
+
+    pub fn maximum_scale(mut self, scale: f32) -> Self {
+        self.maximum_scale = Some(scale);
+        self
+    }
+
+    pub fn user_scalable(mut self, scalable: bool) -> Self {
+        self.user_scalable = Some(scalable);
+        self
+    }
+
+    pub fn viewport_fit(mut self, fit: ViewportFit) -> Self {
+        self.viewport_fit = Some(fit);
+        self
+    }
+
+    /// Value of the meta tag's `content` attribute
+    pub fn content(&self) -> String {
+        let mut parts = vec![format!("width={}", self.width)];
+        let scales = [
+            ("initial-scale", self.initial_scale),
+            ("minimum-scale", self.minimum_scale),
+            ("maximum-scale", self.maximum_scale),
+        ];
+        for (name, scale) in scales {
+            if let Some(scale) = scale {
+                // `1` rather than `1.0`
+                parts.push(format!("{}={}", name, scale));
+            }
+        }
+        if let Some(scalable) = self.user_scalable {
+            parts.push(format!("user-scalable={}", if scalable { "yes" } else { "no" }));
+        }
+        if let Some(fit) = self.viewport_fit {
+            parts.push(format!("viewport-fit={}", fit.as_str()));
+        }
+        parts.join(", ")
+    }
+
+    /// The tag for server-rendered `<head>`s
+    pub fn meta_tag(&self) -> String {
+        format!(
+            r#"<meta name="viewport" content="{}">"#,
+            html_escape::encode_double_quoted_attribute(&self.content())
+        )
+    }
+
+    /// Set the page's viewport, replacing any already there
+    pub fn apply(&self) {
+        set_meta("viewport", &self.content());
+    }
+}
+
+/// Create or update `<meta name="{name}">` in the document head
+pub fn set_meta(name: &str, content: &str) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+            return;
+        };
+        let selector = format!(r#"meta[name="{}"]"#, name);
+        let meta = match document.query_selector(&selector).ok().flatten() {
+            Some(meta) => meta,
+            None => {
+                let (Some(head), Ok(meta)) = (document.head(), document.create_element("meta")) else {
+                    return;
+                };
+                let _ = meta.set_attribute("name", name);
+                let _ = head.append_child(&meta);
+                meta
+            }
+        };
+        let _ = meta.set_attribute("content", content);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = (name, content);
+}
+
+/// Give the page the default viewport unless its HTML or the app set one
+pub fn ensure_viewport() {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let has_viewport = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.query_selector(r#"meta[name="viewport"]"#).ok().flatten())
+            .is_some();
+        if !has_viewport {
+            Viewport::default().apply();
+        }
+    }
+}
+
+/// Marks the tags `Head` manages, and the element standing in for a `Head`
+/// in the body
+pub(crate) const HEAD_ATTR: &str = "data-l9-head";
+
+static NEXT_HEAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Rendered `Head`s, oldest first
+    static HEADS: RefCell<Vec<HeadEntry>> = const { RefCell::new(Vec::new()) };
+    /// The document's title before any `Head` set one
+    #[cfg(target_arch = "wasm32")]
+    static PAGE_TITLE: RefCell<Option<String>> = const { RefCell::new(None) };
+    #[cfg(target_arch = "wasm32")]
+    static SYNC_SCHEDULED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Title, meta and link tags for the document head
+#[derive(Clone, Debug, Default)]
+pub struct Head {
+    id: u64,
+    title: Option<String>,
+    /// `(attribute, key, content)`, e.g. `("property", "og:title", ...)`
+    metas: Vec<(&'static str, String, String)>,
+    /// `(rel, href)`
+    links: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug)]
+struct HeadEntry {
+    head: Head,
+    /// Whether its marker has been in the document, so leaving it means the
+    /// `Head` was removed rather than not inserted yet
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    mounted: bool,
+}
+
+impl Head {
+    pub fn new() -> Self {
+        Head {
+            id: NEXT_HEAD_ID.fetch_add(1, Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// `<meta name="{name}" content="{content}">`
+    pub fn meta(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.metas.push(("name", name.into(), content.into()));
+        self
+    }
+
+    /// `<meta property="{property}" content="{content}">`, as Open Graph uses
+    pub fn meta_property(mut self, property: impl Into<String>, content: impl Into<String>) -> Self {
+        self.metas.push(("property", property.into(), content.into()));
+        self
+    }
+
+    /// `<link rel="{rel}" href="{href}">`
+    pub fn link(mut self, rel: impl Into<String>, href: impl Into<String>) -> Self {
+        self.links.push((rel.into(), href.into()));
+        self
+    }
+
+    fn register(&self) {
+        HEADS.with(|heads| {
+            let mut heads = heads.borrow_mut();
+            match heads.iter_mut().find(|entry| entry.head.id == self.id) {
+                Some(entry) => entry.head = self.clone(),
+                None => heads.push(HeadEntry {
+                    head: self.clone(),
+                    mounted: false,
+                }),
+            }
+        });
+    }
+}
+
+impl Component for Head {
+    fn render(&self) -> Element {
+        self.register();
+        schedule_sync();
+
+        // Inert, and how the browser side tells the Head is still mounted
+        Element::Node {
+            tag: "template".to_string(),
+            props: Box::new(Props::default().attr(HEAD_ATTR, self.id.to_string())),
+            children: vec![],
+        }
+    }
+}
+
+/// What the rendered `Head`s add up to, later ones overriding earlier
+#[derive(Debug, Default, PartialEq)]
+struct MergedHead {
+    title: Option<String>,
+    metas: Vec<(&'static str, String, String)>,
+    links: Vec<(String, String)>,
+}
+
+fn merged(heads: &[HeadEntry]) -> MergedHead {
+    let mut merged = MergedHead::default();
+    for Head { title, metas, links, .. } in heads.iter().map(|entry| &entry.head) {
+        if title.is_some() {
+            merged.title = title.clone();
+        }
+        for (attr, key, content) in metas {
+            match merged.metas.iter_mut().find(|(a, k, _)| a == attr && k == key) {
+                Some(existing) => existing.2 = content.clone(),
+                None => merged.metas.push((*attr, key.clone(), content.clone())),
+            }
+        }
+        for link in links {
+            if !merged.links.contains(link) {
+                merged.links.push(link.clone());
+            }
+        }
+    }
+    merged
+}
+
+/// Forget the `Head`s rendered so far, before server-rendering a page
+pub fn begin_server_head() {
+    HEADS.with(|heads| heads.borrow_mut().clear());
+}
+
+/// The title and tags of the `Head`s rendered since `begin_server_head`,
+/// for the page's `<head>`; the title is `None` if none set one
+pub fn take_server_head() -> (Option<String>, String) {
+    let heads = HEADS.with(|heads| std::mem::take(&mut *heads.borrow_mut()));
+    let merged = merged(&heads);
+    let attr = html_escape::encode_double_quoted_attribute;
+
+    let mut tags: Vec<String> = merged
+        .metas
+        .iter()
+        .map(|(kind, key, content)| {
+            format!(r#"<meta {}="{}" content="{}" {}>"#, kind, attr(key), attr(content), HEAD_ATTR)
+        })
+        .collect();
+    tags.extend(
+        merged
+            .links
+            .iter()
+            .map(|(rel, href)| format!(r#"<link rel="{}" href="{}" {}>"#, attr(rel), attr(href), HEAD_ATTR)),
+    );
+    (merged.title, tags.join("\n    "))
+}
+
+/// Bring `document.head` up to date once the current render is in the
+/// document, dropping the entries of `Head`s no longer in it
+pub fn schedule_sync() {
+    #[cfg(target_arch = "wasm32")]
+    {
+        if SYNC_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+            return;
+        }
+        if HEADS.with(|heads| heads.borrow().is_empty()) && PAGE_TITLE.with(|title| title.borrow().is_none()) {
+            SYNC_SCHEDULED.with(|scheduled| scheduled.set(false));
+            return;
+        }
+        gloo_timers::callback::Timeout::new(0, || {
+            SYNC_SCHEDULED.with(|scheduled| scheduled.set(false));
+            sync();
+        })
+        .forget();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn sync() {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Some(head) = document.head() else {
+        return;
+    };
+
+    let merged = HEADS.with(|heads| {
+        let mut heads = heads.borrow_mut();
+        heads.retain_mut(|entry| {
+            let selector = format!(r#"template[{}="{}"]"#, HEAD_ATTR, entry.head.id);
+            let in_document = document.query_selector(&selector).ok().flatten().is_some();
+            entry.mounted |= in_document;
+            in_document || !entry.mounted
+        });
+        merged(&heads)
+    });
+
+    match &merged.title {
+        Some(title) => {
+            PAGE_TITLE.with(|page_title| {
+                page_title.borrow_mut().get_or_insert_with(|| document.title());
+            });
+            document.set_title(title);
+        }
+        None => {
+            if let Some(page_title) = PAGE_TITLE.with(|page_title| page_title.borrow_mut().take()) {
+                document.set_title(&page_title);
+            }
+        }
+    }
+
+    // Tags this module added or adopted, minus the ones still wanted
+    let mut stale = Vec::new();
+    if let Ok(managed) = head.query_selector_all(&format!("[{}]", HEAD_ATTR)) {
+        stale.extend((0..managed.length()).filter_map(|i| managed.item(i)));
+    }
+    let mut keep = |element: &web_sys::Element| {
+        let _ = element.set_attribute(HEAD_ATTR, "");
+        let node: &web_sys::Node = element.as_ref();
+        stale.retain(|managed| !managed.is_same_node(Some(node)));
+    };
+
+    for (attr, key, content) in &merged.metas {
+        let selector = format!(r#"meta[{}="{}"]"#, attr, key.replace('"', "\\\""));
+        let meta = match head.query_selector(&selector).ok().flatten() {
+            Some(meta) => meta,
+            None => {
+                let Ok(meta) = document.create_element("meta") else {
+                    continue;
+                };
+                let _ = meta.set_attribute(attr, key);
+                let _ = head.append_child(&meta);
+                meta
+            }
+        };
+        let _ = meta.set_attribute("content", content);
+        keep(&meta);
+    }
+
+    for (rel, href) in &merged.links {
+        let selector = format!(
+            r#"link[rel="{}"][href="{}"]"#,
+            rel.replace('"', "\\\""),
+            href.replace('"', "\\\"")
+        );
+        let link = match head.query_selector(&selector).ok().flatten() {
+            Some(link) => link,
+            None => {
+                let Ok(link) = document.create_element("link") else {
+                    continue;
+                };
+                let _ = link.set_attribute("rel", rel);
+                let _ = link.set_attribute("href", href);
+                let _ = head.append_child(&link);
+                link
+            }
+        };
+        keep(&link);
+    }
+
+    for element in stale {
+        if let Some(parent) = element.parent_node() {
+            let _ = parent.remove_child(&element);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_content() {
+        assert_eq!(Viewport::default().content(), "width=device-width, initial-scale=1");
+        assert_eq!(
+            Viewport::default()
+                .maximum_scale(1.0)
+                .user_scalable(false)
+                .viewport_fit(ViewportFit::Cover)
+                .content(),
+            "width=device-width, initial-scale=1, maximum-scale=1, user-scalable=no, viewport-fit=cover"
+        );
+        assert_eq!(
+            Viewport::default().width("480").initial_scale(0.5).meta_tag(),
+            r#"<meta name="viewport" content="width=480, initial-scale=0.5">"#
+        );
+    }
+
+    #[test]
+    fn test_later_heads_override_earlier_ones() {
+        begin_server_head();
+        let page = Element::Node {
+            tag: "main".to_string(),
+            props: Box::default(),
+            children: vec![
+                Element::Component(Box::new(
+                    Head::new()
+                        .title("Layer9")
+                        .meta("description", "A web framework")
+                        .meta_property("og:type", "website")
+                        .link("icon", "/favicon.ico"),
+                )),
+                Element::Component(Box::new(
+                    Head::new()
+                        .title("Todos - Layer9")
+                        .meta("description", "Your \"todo\" lists")
+                        .link("icon", "/favicon.ico"),
+                )),
+            ],
+        };
+        assert!(page.to_html().contains("<template data-l9-head="));
+
+        let (title, tags) = take_server_head();
+        assert_eq!(title.as_deref(), Some("Todos - Layer9"));
+        assert_eq!(
+            tags,
+            "<meta name=\"description\" content=\"Your &quot;todo&quot; lists\" data-l9-head>\n    \
+             <meta property=\"og:type\" content=\"website\" data-l9-head>\n    \
+             <link rel=\"icon\" href=\"/favicon.ico\" data-l9-head>"
+        );
+
+        // Taken, so the next page starts empty
+        assert_eq!(take_server_head(), (None, String::new()));
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_title_follows_mounted_head() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        document.set_title("Layer9");
+
+        let page = Element::Node {
+            tag: "div".to_string(),
+            props: Box::default(),
+            children: vec![Element::Component(Box::new(
+                Head::new().title("Settings").meta("description", "Account settings"),
+            ))],
+        };
+        let node = page.to_dom();
+        document.body().unwrap().append_child(&node).unwrap();
+
+        gloo_timers::future::TimeoutFuture::new(10).await;
+        assert_eq!(document.title(), "Settings");
+        let description = document
+            .query_selector(r#"meta[name="description"]"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(description.get_attribute("content").as_deref(), Some("Account settings"));
+
+        // Unmounting takes its entries with it
+        document.body().unwrap().remove_child(&node).unwrap();
+        schedule_sync();
+        gloo_timers::future::TimeoutFuture::new(10).await;
+        assert_eq!(document.title(), "Layer9");
+        assert!(document.query_selector(r#"meta[name="description"]"#).unwrap().is_none());
+    }
+}
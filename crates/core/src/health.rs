@@ -0,0 +1,316 @@
+//! Health Checks - L4
+//!
+//! Liveness and readiness probes for orchestrators:
+//!
+//! ```ignore
+//! let health = HealthChecks::new()
+//!     .database(db.clone())
+//!     .check("cache", || async { redis_ping().await.map_err(|e| e.to_string()) });
+//!
+//! let app = Router::new().merge(health.into_router());
+//! ```
+//!
+//! `/healthz` answers 200 whenever the process can serve requests at all.
+//! `/readyz` runs every registered check and answers 503 if any fails or
+//! times out, so traffic is held back until dependencies are reachable.
+
+use crate::db::DatabaseConnection;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type CheckFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Liveness probe path
+pub const LIVENESS_PATH: &str = "/healthz";
+/// Readiness probe path
+pub const READINESS_PATH: &str = "/readyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+/// Outcome of one readiness check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Body of a probe response
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    pub fn is_ok(&self) -> bool {
+        self.status == HealthStatus::Ok
+    }
+
+    /// 200 when healthy, 503 otherwise
+    pub fn http_status(&self) -> u16 {
+        if self.is_ok() {
+            200
+        } else {
+            503
+        }
+    }
+}
+
+/// Dependencies that must be reachable before the server takes traffic
+#[derive(Clone)]
+pub struct HealthChecks {
+    checks: Vec<(String, CheckFn)>,
+    timeout: Duration,
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthChecks {
+    pub fn new() -> Self {
+        HealthChecks {
+            checks: Vec::new(),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Register a readiness check; an `Err` marks the server not ready
+    pub fn check<F, Fut>(mut self, name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.checks
+            .push((name.into(), Arc::new(move || Box::pin(check()))));
+        self
+    }
+
+    /// Check the database answers `SELECT 1`
+    pub fn database<C: DatabaseConnection + Clone>(self, conn: C) -> Self {
+        self.check("database", move || {
+            let conn = conn.clone();
+            async move {
+                conn.query_one("SELECT 1", vec![])
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.message)
+            }
+        })
+    }
+
+    /// Longest a single check may take before it counts as failed
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Liveness: the process is up, regardless of its dependencies
+    pub fn liveness(&self) -> HealthReport {
+        HealthReport {
+            status: HealthStatus::Ok,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Readiness: run every check concurrently and report each outcome
+    pub async fn readiness(&self) -> HealthReport {
+        let runs = self.checks.iter().map(|(name, check)| {
+            let name = name.clone();
+            let run = self.run_check(check);
+            async move {
+                let started = Instant::now();
+                let result = run.await;
+                CheckResult {
+                    name,
+                    status: if result.is_ok() {
+                        HealthStatus::Ok
+                    } else {
+                        HealthStatus::Unavailable
+                    },
+                    error: result.err(),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                }
+            }
+        });
+        let checks = futures::future::join_all(runs).await;
+
+        HealthReport {
+            status: if checks.iter().all(|c| c.status == HealthStatus::Ok) {
+                HealthStatus::Ok
+            } else {
+                HealthStatus::Unavailable
+            },
+            checks,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_check(&self, check: &CheckFn) -> BoxFuture<'static, Result<(), String>> {
+        use futures::future::{select, Either};
+
+        let (run, timeout) = (check(), self.timeout);
+        Box::pin(async move {
+            match select(run, Box::pin(delay(timeout))).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(format!("Timed out after {}ms", timeout.as_millis())),
+            }
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn run_check(&self, check: &CheckFn) -> BoxFuture<'static, Result<(), String>> {
+        check()
+    }
+
+    /// Axum router serving `/healthz` and `/readyz`
+    #[cfg(feature = "ssr")]
+    pub fn into_router(self) -> axum::Router {
+        use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json};
+
+        fn respond(report: HealthReport) -> axum::response::Response {
+            let status = StatusCode::from_u16(report.http_status())
+                .unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+            (status, Json(report)).into_response()
+        }
+
+        axum::Router::new()
+            .route(
+                LIVENESS_PATH,
+                get(|State(health): State<Arc<HealthChecks>>| async move {
+                    respond(health.liveness())
+                }),
+            )
+            .route(
+                READINESS_PATH,
+                get(|State(health): State<Arc<HealthChecks>>| async move {
+                    respond(health.readiness().await)
+                }),
+            )
+            .with_state(Arc::new(self))
+    }
+}
+
+/// Resolves once `duration` has passed, on any executor: a thread waits
+/// out the duration and exits early when the future is dropped
+#[cfg(not(target_arch = "wasm32"))]
+fn delay(duration: Duration) -> impl Future<Output = ()> {
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    let (fire, fired) = futures::channel::oneshot::channel::<()>();
+    let (cancel, cancelled) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        if let Err(RecvTimeoutError::Timeout) = cancelled.recv_timeout(duration) {
+            let _ = fire.send(());
+        }
+    });
+    async move {
+        let _cancel = cancel;
+        let _ = fired.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DbError, DbErrorKind, QueryResult};
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Pool stand-in that can be disconnected
+    #[derive(Clone)]
+    struct Pool(Arc<AtomicBool>);
+
+    impl Pool {
+        fn ping(&self) -> Result<Value, DbError> {
+            if self.0.load(Ordering::SeqCst) {
+                Ok(json!({ "?column?": 1 }))
+            } else {
+                Err(DbError {
+                    kind: DbErrorKind::Connection,
+                    message: "connection refused".to_string(),
+                })
+            }
+        }
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for Pool {
+        async fn execute(&self, _: &str, _: Vec<Value>) -> Result<QueryResult, DbError> {
+            self.ping().map(|_| QueryResult { rows_affected: 0, last_insert_id: None })
+        }
+        async fn query_one(&self, _: &str, _: Vec<Value>) -> Result<Value, DbError> {
+            self.ping()
+        }
+        async fn query_many(&self, _: &str, _: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            self.ping().map(|row| vec![row])
+        }
+        async fn begin_transaction(&self) -> Result<String, DbError> {
+            Ok("tx".to_string())
+        }
+        async fn commit_transaction(&self, _: &str) -> Result<(), DbError> {
+            Ok(())
+        }
+        async fn rollback_transaction(&self, _: &str) -> Result<(), DbError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_readiness_follows_database_connectivity() {
+        let connected = Arc::new(AtomicBool::new(false));
+        let health = HealthChecks::new()
+            .database(Pool(connected.clone()))
+            .check("cache", || async { Ok(()) });
+
+        // Alive but not ready while the pool can't connect
+        assert!(health.liveness().is_ok());
+        let report = block_on(health.readiness());
+        assert_eq!(report.http_status(), 503);
+        assert_eq!(report.checks[0].error.as_deref(), Some("connection refused"));
+        assert_eq!(report.checks[1].status, HealthStatus::Ok);
+
+        connected.store(true, Ordering::SeqCst);
+        let report = block_on(health.readiness());
+        assert_eq!(report.http_status(), 200);
+        assert!(report.checks.iter().all(|c| c.error.is_none()));
+    }
+
+    #[test]
+    fn test_report_json_shape() {
+        let health = HealthChecks::new().check("queue", || async { Err("backlog full".to_string()) });
+        let body = serde_json::to_value(block_on(health.readiness())).unwrap();
+
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["checks"][0]["name"], "queue");
+        assert_eq!(body["checks"][0]["error"], "backlog full");
+        assert_eq!(serde_json::to_value(health.liveness()).unwrap(), json!({ "status": "ok" }));
+    }
+
+    #[test]
+    fn test_hung_check_times_out_without_a_runtime() {
+        let health = HealthChecks::new()
+            .timeout(Duration::from_millis(10))
+            .check("hung", futures::future::pending);
+
+        let report = block_on(health.readiness());
+        assert_eq!(report.http_status(), 503);
+        assert_eq!(report.checks[0].error.as_deref(), Some("Timed out after 10ms"));
+    }
+}
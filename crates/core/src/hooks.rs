@@ -5,11 +5,13 @@
 //! reactive rendering system for automatic updates.
 
 use std::any::{Any, TypeId};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
-use crate::reactive_v2::{get_current_component, run_current_effect};
+use crate::component::{Component, Element, State};
+use crate::reactive_v2::{get_current_component, run_current_effect, with_current_component};
 
 thread_local! {
     /// Global hook state storage per component
@@ -17,6 +19,19 @@ thread_local! {
     
     /// Current hook index for the executing component
     static CURRENT_HOOK_INDEX: RefCell<usize> = const { RefCell::new(0) };
+
+    /// Memoized children, by the component id they render under
+    static MEMO_SCOPES: RefCell<HashMap<u32, MemoScope>> = RefCell::new(HashMap::new());
+}
+
+/// Ids of memoized children, above the renderer's and test instances'
+static NEXT_MEMO_ID: AtomicU32 = AtomicU32::new(3 << 30);
+
+/// A memoized child's parent, and whether the child's own state changed
+/// since it last rendered
+struct MemoScope {
+    owner: u32,
+    stale: Rc<Cell<bool>>,
 }
 
 /// Storage for all hooks of a component
@@ -67,6 +82,37 @@ fn use_hook_state<T: 'static, F: FnOnce() -> T>(init: F) -> Rc<RefCell<T>> {
     })
 }
 
+/// Hook state during a render; outside one (tests, a static `to_html`)
+/// a fresh value, as there's no component to keep it in. Lets widgets keep
+/// their state across the parent's re-renders.
+pub(crate) fn use_persisted<T: 'static>(init: impl FnOnce() -> T) -> Rc<RefCell<T>> {
+    if get_current_component().is_none() {
+        return Rc::new(RefCell::new(init()));
+    }
+    use_hook_state(init)
+}
+
+/// A widget's `State` during a render: the one it had on its first
+/// render, re-rendering the component when set. Outside a render, `state`.
+pub(crate) fn use_widget_state<T: Clone + 'static>(state: &State<T>) -> State<T> {
+    match get_current_component() {
+        Some(component_id) => use_hook_state(|| state.clone().owned_by(component_id)).borrow().clone(),
+        None => state.clone(),
+    }
+}
+
+/// A widget's DOM id during a render: `explicit` when given, otherwise one
+/// made by `generate` on the first render and kept from then on. Outside a
+/// render, a fresh one.
+pub(crate) fn use_widget_id(explicit: Option<&str>, generate: impl FnOnce() -> String) -> String {
+    let generated = use_persisted(|| None::<String>);
+    if let Some(id) = explicit {
+        return id.to_string();
+    }
+    let mut generated = generated.borrow_mut();
+    generated.get_or_insert_with(generate).clone()
+}
+
 /// State hook with functional updates
 pub fn use_state<T: Clone + 'static>(initial: T) -> (T, impl Fn(T) + Clone) {
     let state = use_hook_state(|| initial);
@@ -223,6 +269,114 @@ pub fn use_ref<T: 'static>(initial: T) -> Rc<RefCell<T>> {
     use_hook_state(|| initial)
 }
 
+/// Memoized child render: skips `render` while `props` stay equal and
+/// returns the previous element, which the reconciler leaves in place.
+///
+/// The child renders under a component id of its own, so its hooks keep
+/// their own slots and the parent's hooks line up whether or not the child
+/// ran. Its id and cached element live in the calling component's hooks,
+/// so call `render()` on a `Memo` from the parent's `render` rather than
+/// nesting it as `Element::Component`. A state change inside the child
+/// re-renders the parent, which then renders the child again. Handlers in
+/// the cached element are the ones from the render that produced it, so
+/// they should only depend on `props` and the child's own hooks.
+pub struct Memo<P> {
+    props: P,
+    render: Rc<dyn Fn(&P) -> Element>,
+}
+
+impl<P: PartialEq + Clone + 'static> Memo<P> {
+    pub fn new(props: P, render: impl Fn(&P) -> Element + 'static) -> Self {
+        Memo {
+            props,
+            render: Rc::new(render),
+        }
+    }
+}
+
+/// What a `Memo` keeps in its parent's hooks
+struct MemoSlot<P> {
+    component_id: u32,
+    stale: Rc<Cell<bool>>,
+    cache: Option<(P, Element)>,
+}
+
+impl<P: PartialEq + Clone + 'static> Component for Memo<P> {
+    fn render(&self) -> Element {
+        // Outside a render (e.g. `to_html` on a static tree) there's
+        // nothing to remember between calls
+        let Some(owner) = get_current_component() else {
+            return (self.render)(&self.props);
+        };
+
+        let slot = use_hook_state(|| {
+            let component_id = NEXT_MEMO_ID.fetch_add(1, Ordering::Relaxed);
+            let stale = Rc::new(Cell::new(false));
+            MEMO_SCOPES.with(|scopes| {
+                scopes.borrow_mut().insert(component_id, MemoScope { owner, stale: stale.clone() });
+            });
+            MemoSlot { component_id, stale, cache: None::<(P, Element)> }
+        });
+        let mut slot = slot.borrow_mut();
+        if let Some((props, element)) = &slot.cache {
+            if *props == self.props && !slot.stale.get() {
+                return element.clone();
+            }
+        }
+
+        slot.stale.set(false);
+        let element = render_scoped(slot.component_id, || (self.render)(&self.props));
+        slot.cache = Some((self.props.clone(), element.clone()));
+        element
+    }
+}
+
+/// Run `render` as component `component_id` with its hooks from the first
+/// slot, then carry on with the caller's component and hook index
+fn render_scoped(component_id: u32, render: impl FnOnce() -> Element) -> Element {
+    let hook_index = CURRENT_HOOK_INDEX.with(|index| index.replace(0));
+    let element = with_current_component(component_id, render);
+    CURRENT_HOOK_INDEX.with(|index| *index.borrow_mut() = hook_index);
+    element
+}
+
+/// The component a render queued for `component_id` should go to: a
+/// memoized child is re-rendered through the component that renders it,
+/// with every memo on the way marked so it doesn't return its cache.
+pub(crate) fn render_target(component_id: u32) -> u32 {
+    MEMO_SCOPES.with(|scopes| {
+        let scopes = scopes.borrow();
+        let mut target = component_id;
+        while let Some(scope) = scopes.get(&target) {
+            scope.stale.set(true);
+            target = scope.owner;
+        }
+        target
+    })
+}
+
+/// The component that owns `component_id`'s effects: itself, or for a
+/// memoized child the component the renderer mounted
+pub(crate) fn effect_owner(component_id: u32) -> u32 {
+    MEMO_SCOPES.with(|scopes| {
+        let scopes = scopes.borrow();
+        let mut owner = component_id;
+        while let Some(scope) = scopes.get(&owner) {
+            owner = scope.owner;
+        }
+        owner
+    })
+}
+
+/// Render `component(&props)` only when `props` differ from the last render
+pub fn memo<P, C>(props: P, component: impl Fn(&P) -> C + 'static) -> Element
+where
+    P: PartialEq + Clone + 'static,
+    C: Component,
+{
+    Memo::new(props, move |props| component(props).render()).render()
+}
+
 /// Layout effect hook (runs synchronously after DOM mutations)
 pub fn use_layout_effect<D, F, C>(deps: D, effect: F)
 where
@@ -427,6 +581,23 @@ pub fn cleanup_component_hooks(component_id: u32) {
             }
         }
     });
+
+    // Memoized children go with the component that renders them
+    let children: Vec<u32> = MEMO_SCOPES.with(|scopes| {
+        let mut scopes = scopes.borrow_mut();
+        let children: Vec<u32> = scopes
+            .iter()
+            .filter(|(_, scope)| scope.owner == component_id)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &children {
+            scopes.remove(id);
+        }
+        children
+    });
+    for child in children {
+        cleanup_component_hooks(child);
+    }
 }
 
 /// Custom hook example: useCounter
@@ -446,53 +617,185 @@ pub fn use_counter(initial: i32) -> (i32, impl Fn() + Clone, impl Fn() + Clone)
     (count, increment, decrement)
 }
 
-/// Custom hook example: usePrevious  
-pub fn use_previous<T>(value: T) -> Option<T>
-where
-    T: Clone + PartialEq + 'static + DepsList,
-{
-    let prev_ref = use_ref(None::<T>);
-    let prev_value = prev_ref.borrow().clone();
-    
-    use_effect(value.clone(), {
-        let prev_ref = prev_ref.clone();
-        let value = value.clone();
-        move || {
-            let mut prev = prev_ref.borrow_mut();
-            *prev = Some(value);
-            || {}
+/// Previous hook: the value passed on the component's last render, or
+/// `None` on its first
+///
+/// The slot is updated as the hook returns, so within one render `value` is
+/// the current value and the result the previous one, e.g. to tell whether
+/// a counter went up or down.
+pub fn use_previous<T: Clone + 'static>(value: T) -> Option<Rc<T>> {
+    let last = use_hook_state(|| None::<Rc<T>>);
+    let previous = last.borrow_mut().replace(Rc::new(value));
+    previous
+}
+
+/// A debounced value: the one handed out, and the newer one waiting for
+/// `ms` without changes
+struct Debounced<T> {
+    settled: Rc<T>,
+    pending: Option<(T, f64)>,
+    #[cfg(target_arch = "wasm32")]
+    timer: Option<gloo_timers::callback::Timeout>,
+}
+
+impl<T: PartialEq> Debounced<T> {
+    fn new(value: T) -> Self {
+        Debounced {
+            settled: Rc::new(value),
+            pending: None,
+            #[cfg(target_arch = "wasm32")]
+            timer: None,
         }
-    });
-    
-    prev_value
+    }
+
+    /// Note the value rendered at `now`; whether it restarts the wait. A
+    /// value back at the settled one drops the wait altogether.
+    fn observe(&mut self, value: T, now: f64, ms: f64) -> bool {
+        if *self.settled == value {
+            self.pending = None;
+            return false;
+        }
+        match &mut self.pending {
+            Some((pending, _)) if *pending == value => false,
+            pending => {
+                *pending = Some((value, now + ms));
+                true
+            }
+        }
+    }
+
+    /// Hand out the waiting value if it has been quiet until `now`; whether
+    /// it did
+    fn settle(&mut self, now: f64) -> bool {
+        match self.pending.take() {
+            Some((value, due)) if due <= now => {
+                self.settled = Rc::new(value);
+                true
+            }
+            pending => {
+                self.pending = pending;
+                false
+            }
+        }
+    }
 }
 
-/// Custom hook example: useDebounce
-pub fn use_debounce<T>(value: T, delay_ms: u32) -> T
-where
-    T: Clone + PartialEq + 'static + DepsList,
-{
-    let (debounced, set_debounced) = use_state(value.clone());
-    
-    use_effect((value.clone(), delay_ms), {
-        let set_debounced = set_debounced.clone();
-        let value = value.clone();
-        move || {
-            // In a real implementation, we'd use setTimeout
-            // For now, just update immediately
-            set_debounced(value);
-            || {}
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Debounce hook: `value` once it has stayed the same for `ms`, e.g. to
+/// validate or search only after typing pauses
+///
+/// Every change restarts the wait, so rapid changes settle only on the last
+/// one, and the component re-renders once when it does. Unmounting cancels
+/// a pending update. Off the browser there are no timers; a value that has
+/// waited long enough settles on the next render instead.
+pub fn use_debounce<T: Clone + PartialEq + 'static>(value: T, ms: i32) -> Rc<T> {
+    let ms = ms.max(0);
+    let debounced = use_hook_state(|| Debounced::new(value.clone()));
+    let component_id = get_current_component().expect("use_debounce must be called during render");
+
+    let now = now_ms();
+    let mut state = debounced.borrow_mut();
+    state.settle(now);
+    if state.observe(value, now, ms as f64) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            // The slot is dropped on unmount, and the timer with it
+            let slot = Rc::downgrade(&debounced);
+            state.timer = Some(gloo_timers::callback::Timeout::new(ms as u32, move || {
+                let Some(slot) = slot.upgrade() else {
+                    return;
+                };
+                // This timer outlived every later change, so the wait is over
+                let settled = slot.borrow_mut().settle(f64::INFINITY);
+                if settled {
+                    crate::reactive_v2::queue_component_render(component_id);
+                }
+            }));
         }
-    });
-    
-    debounced
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = component_id;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    if state.pending.is_none() {
+        state.timer = None;
+    }
+
+    state.settled.clone()
+}
+
+/// Outcome of the last copy to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyStatus {
+    #[default]
+    Idle,
+    Copied,
+    Failed,
+}
+
+/// Write `text` to the system clipboard and report the outcome to
+/// `on_status`, which is told `Idle` again after `reset_ms`
+///
+/// Fails where the Clipboard API is unavailable, e.g. outside the browser
+/// or on pages not served over HTTPS.
+pub fn copy_to_clipboard(text: &str, reset_ms: u32, on_status: impl Fn(CopyStatus) + 'static) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let clipboard = web_sys::window()
+            .and_then(|window| js_sys::Reflect::get(&window.navigator(), &"clipboard".into()).ok())
+            .filter(|clipboard| !clipboard.is_undefined())
+            .map(|clipboard| clipboard.unchecked_into::<web_sys::Clipboard>());
+        let Some(clipboard) = clipboard else {
+            on_status(CopyStatus::Failed);
+            return;
+        };
+
+        let written = wasm_bindgen_futures::JsFuture::from(clipboard.write_text(text));
+        wasm_bindgen_futures::spawn_local(async move {
+            on_status(match written.await {
+                Ok(_) => CopyStatus::Copied,
+                Err(_) => CopyStatus::Failed,
+            });
+            gloo_timers::future::TimeoutFuture::new(reset_ms).await;
+            on_status(CopyStatus::Idle);
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = (text, reset_ms);
+        on_status(CopyStatus::Failed);
+    }
+}
+
+/// Clipboard hook: the status of the last copy and a function that copies
+/// text, for "Copied!" confirmations that clear after `reset_ms`
+pub fn use_clipboard(reset_ms: u32) -> (CopyStatus, impl Fn(&str) + Clone) {
+    let (status, set_status) = use_state(CopyStatus::Idle);
+    let copy = move |text: &str| copy_to_clipboard(text, reset_ms, set_status.clone());
+    (status, copy)
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::reactive_v2::{init_renderer, with_current_component};
+    use crate::reactive_v2::init_renderer;
     use std::rc::Rc;
     use std::cell::RefCell;
 
@@ -575,6 +878,96 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_memo_skips_unchanged_props() {
+        struct Greeting {
+            name: String,
+            renders: Rc<RefCell<usize>>,
+        }
+
+        impl Component for Greeting {
+            fn render(&self) -> Element {
+                *self.renders.borrow_mut() += 1;
+                Element::Text(format!("Hello, {}", self.name))
+            }
+        }
+
+        let renders = Rc::new(RefCell::new(0));
+        let render_parent = |name: &str| {
+            let renders = renders.clone();
+            with_test_component(|| {
+                memo(name.to_string(), move |name| Greeting {
+                    name: name.clone(),
+                    renders: renders.clone(),
+                })
+            })
+        };
+
+        // The first render always runs
+        assert_eq!(render_parent("Ada").to_html(), "Hello, Ada");
+        assert_eq!(*renders.borrow(), 1);
+
+        assert_eq!(render_parent("Ada").to_html(), "Hello, Ada");
+        assert_eq!(*renders.borrow(), 1);
+
+        assert_eq!(render_parent("Grace").to_html(), "Hello, Grace");
+        assert_eq!(*renders.borrow(), 2);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_memo_child_has_its_own_hooks() {
+        use crate::component::Props;
+
+        struct Toggle {
+            label: String,
+        }
+
+        impl Component for Toggle {
+            fn render(&self) -> Element {
+                let (on, set_on) = use_state(false);
+                Element::Node {
+                    tag: "button".to_string(),
+                    props: Box::new(Props {
+                        class: Some("toggle".to_string()),
+                        on_click: Some(Rc::new(move || set_on(!on))),
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(format!("{}: {}", self.label, if on { "on" } else { "off" }))],
+                }
+            }
+        }
+
+        struct Page;
+
+        impl Component for Page {
+            fn render(&self) -> Element {
+                let toggle = memo("Dark mode".to_string(), |label| Toggle { label: label.clone() });
+                // A hook after the memo gets the same slot on a cache hit
+                let title = use_ref("Settings".to_string());
+                let title = title.borrow().clone();
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::default(),
+                    children: vec![Element::Text(title), toggle],
+                }
+            }
+        }
+
+        let page = Page;
+        let mut tree = crate::testing::render(&page);
+        assert_eq!(tree.to_html(), "<div>Settings<button class=\"toggle\">Dark mode: off</button></div>");
+
+        tree.rerender();
+        assert_eq!(tree.to_html(), "<div>Settings<button class=\"toggle\">Dark mode: off</button></div>");
+
+        // The child's own state change gets past the cache
+        tree.click("button.toggle");
+        assert_eq!(tree.to_html(), "<div>Settings<button class=\"toggle\">Dark mode: on</button></div>");
+        tree.rerender();
+        assert!(tree.to_html().contains("Dark mode: on"));
+    }
+
     #[test]
     fn test_use_callback() {
         with_test_component(|| {
@@ -751,4 +1144,123 @@ mod tests {
             assert!(!state.borrow().contains_key(&component_id));
         });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_use_clipboard_reports_failure_without_clipboard() {
+        with_test_component(|| {
+            let (status, copy) = use_clipboard(2000);
+            assert_eq!(status, CopyStatus::Idle);
+            copy("cargo add layer9-core");
+        });
+
+        // The next render sees the outcome
+        reset_hook_index();
+        with_current_component(1, || {
+            let (status, _) = use_clipboard(2000);
+            assert_eq!(status, CopyStatus::Failed);
+        });
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_use_previous_lags_one_render() {
+        use crate::component::Props;
+
+        struct Counter {
+            seen: Rc<RefCell<Vec<Option<i32>>>>,
+        }
+
+        impl Component for Counter {
+            fn render(&self) -> Element {
+                let (count, set_count) = use_state(0);
+                let previous = use_previous(count);
+                self.seen.borrow_mut().push(previous.map(|previous| *previous));
+                Element::Node {
+                    tag: "button".to_string(),
+                    props: Box::new(Props {
+                        on_click: Some(Rc::new(move || set_count(count + 1))),
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(count.to_string())],
+                }
+            }
+        }
+
+        let counter = Counter {
+            seen: Rc::new(RefCell::new(Vec::new())),
+        };
+        let mut tree = crate::testing::render(&counter);
+        tree.click("button");
+        tree.click("button");
+
+        assert_eq!(*counter.seen.borrow(), vec![None, Some(0), Some(1)]);
+        assert!(tree.find_by_text("2").is_some());
+    }
+
+    #[test]
+    fn test_debounce_settles_once_on_the_last_value() {
+        let mut debounced = Debounced::new("");
+        assert!(debounced.observe("l", 0.0, 300.0));
+        assert!(debounced.observe("la", 100.0, 300.0));
+        assert!(debounced.observe("lay", 200.0, 300.0));
+        // Re-rendering with the same value doesn't restart the wait
+        assert!(!debounced.observe("lay", 250.0, 300.0));
+
+        // 300ms after the first change, but not after the last
+        assert!(!debounced.settle(300.0));
+        assert_eq!(*debounced.settled, "");
+        assert!(debounced.settle(500.0));
+        assert_eq!(*debounced.settled, "lay");
+        assert!(!debounced.settle(900.0));
+
+        // Changing back before the wait ends leaves nothing to settle
+        debounced.observe("layer", 1000.0, 300.0);
+        debounced.observe("lay", 1100.0, 300.0);
+        assert!(!debounced.settle(2000.0));
+    }
+
+    #[test]
+    fn test_use_debounce_holds_rapid_changes() {
+        let render = |query: &str| {
+            reset_hook_index();
+            with_current_component(2, || use_debounce(query.to_string(), 60_000))
+        };
+
+        assert_eq!(*render(""), "");
+        for query in ["l", "la", "lay"] {
+            assert_eq!(*render(query), "");
+        }
+
+        // Unmounting drops the pending value with the hook
+        cleanup_component_hooks(2);
+        assert_eq!(*render("layer"), "layer");
+        cleanup_component_hooks(2);
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use crate::reactive_v2::with_current_component;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_debounce_timer_settles_on_the_last_value() {
+        let render = |query: &str| {
+            reset_hook_index();
+            with_current_component(7001, || use_debounce(query.to_string(), 50))
+        };
+
+        assert_eq!(*render(""), "");
+        for query in ["l", "la", "lay"] {
+            assert_eq!(*render(query), "");
+        }
+
+        gloo_timers::future::TimeoutFuture::new(100).await;
+        assert_eq!(*render("lay"), "lay");
+        cleanup_component_hooks(7001);
+    }
+}
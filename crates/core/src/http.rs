@@ -0,0 +1,406 @@
+//! HTTP Client - L4
+//!
+//! Thin async wrapper around the browser Fetch API:
+//!
+//! ```ignore
+//! let user: User = http::get("/api/users/1").await?.json()?;
+//! let created = http::post("/api/users").json(&new_user).error_for_status().await?;
+//! ```
+//!
+//! Non-2xx responses are returned as `Ok(Response)` unless the request opts in
+//! with `error_for_status()`.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+pub use crate::fetch::Method;
+
+type ResponseFuture = Pin<Box<dyn Future<Output = Result<Response, HttpError>>>>;
+
+/// HTTP client errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum HttpError {
+    /// The request never produced a response (offline, DNS, CORS, ...)
+    Network(String),
+    /// The server answered with a non-2xx status and the caller opted in via `error_for_status()`
+    Status { status: u16, body: String },
+    /// The body could not be serialized or deserialized
+    Body(String),
+    /// The request was cancelled through its `AbortHandle`
+    Aborted,
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpError::Network(message) => write!(f, "Network error: {}", message),
+            HttpError::Status { status, .. } => write!(f, "HTTP error: {}", status),
+            HttpError::Body(message) => write!(f, "Body error: {}", message),
+            HttpError::Aborted => write!(f, "Request aborted"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Response with its body already read
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    /// Header names are lower-cased
+    pub headers: HashMap<String, String>,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, headers: HashMap<String, String>, body: impl Into<String>) -> Self {
+        Response {
+            status,
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_lowercase(), value))
+                .collect(),
+            body: body.into(),
+        }
+    }
+
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.body
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpError> {
+        serde_json::from_str(&self.body).map_err(|e| HttpError::Body(e.to_string()))
+    }
+}
+
+/// A request ready to be sent
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub url: String,
+    pub method: Method,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// Sends requests; the default transport uses `window.fetch`
+pub trait Transport {
+    fn send(&self, request: HttpRequest, abort: Option<AbortHandle>) -> ResponseFuture;
+}
+
+/// Cancels in-flight requests it has been attached to
+#[derive(Clone)]
+pub struct AbortHandle {
+    controller: web_sys::AbortController,
+}
+
+impl AbortHandle {
+    pub fn new() -> Result<Self, HttpError> {
+        web_sys::AbortController::new()
+            .map(|controller| AbortHandle { controller })
+            .map_err(|e| HttpError::Network(format!("{:?}", e)))
+    }
+
+    pub fn abort(&self) {
+        self.controller.abort();
+    }
+
+    fn signal(&self) -> web_sys::AbortSignal {
+        self.controller.signal()
+    }
+}
+
+/// Request builder; `.await` it to send
+pub struct RequestBuilder {
+    request: HttpRequest,
+    body_error: Option<String>,
+    error_for_status: bool,
+    abort: Option<AbortHandle>,
+}
+
+impl RequestBuilder {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        RequestBuilder {
+            request: HttpRequest {
+                url: url.into(),
+                method,
+                headers: Vec::new(),
+                body: None,
+            },
+            body_error: None,
+            error_for_status: false,
+            abort: None,
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.request
+            .headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+        self.request.headers.push((name, value.into()));
+        self
+    }
+
+    pub fn bearer_token(self, token: impl Into<String>) -> Self {
+        let token = token.into();
+        self.header("Authorization", format!("Bearer {}", token))
+    }
+
+    /// Serialize `body` as JSON and set the JSON content type
+    pub fn json<T: Serialize>(mut self, body: &T) -> Self {
+        match serde_json::to_string(body) {
+            Ok(json) => self.request.body = Some(json),
+            Err(e) => self.body_error = Some(e.to_string()),
+        }
+        self.header("Content-Type", "application/json")
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.request.body = Some(body.into());
+        self
+    }
+
+    /// Turn non-2xx responses into `HttpError::Status`
+    pub fn error_for_status(mut self) -> Self {
+        self.error_for_status = true;
+        self
+    }
+
+    pub fn abort_handle(mut self, handle: &AbortHandle) -> Self {
+        self.abort = Some(handle.clone());
+        self
+    }
+
+    /// Send through a specific transport instead of `window.fetch`
+    pub fn send_with(self, transport: &dyn Transport) -> ResponseFuture {
+        if let Some(error) = self.body_error {
+            return Box::pin(async move { Err(HttpError::Body(error)) });
+        }
+
+        let mut request = self.request;
+        // Continue the caller's trace upstream
+        if let Some(trace) = crate::trace::current() {
+            if !request.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(crate::trace::TRACEPARENT_HEADER)) {
+                request
+                    .headers
+                    .push((crate::trace::TRACEPARENT_HEADER.to_string(), trace.to_traceparent()));
+            }
+        }
+
+        let error_for_status = self.error_for_status;
+        let response = transport.send(request, self.abort);
+
+        Box::pin(async move {
+            let response = response.await?;
+            if error_for_status && !response.ok() {
+                return Err(HttpError::Status {
+                    status: response.status,
+                    body: response.body,
+                });
+            }
+            Ok(response)
+        })
+    }
+}
+
+impl IntoFuture for RequestBuilder {
+    type Output = Result<Response, HttpError>;
+    type IntoFuture = ResponseFuture;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send_with(&FetchTransport)
+    }
+}
+
+/// Transport backed by the browser Fetch API
+pub struct FetchTransport;
+
+impl Transport for FetchTransport {
+    fn send(&self, request: HttpRequest, abort: Option<AbortHandle>) -> ResponseFuture {
+        Box::pin(async move {
+            let opts = web_sys::RequestInit::new();
+            opts.set_method(request.method.as_str());
+
+            let headers = web_sys::Headers::new().map_err(network_error)?;
+            for (name, value) in &request.headers {
+                headers.set(name, value).map_err(network_error)?;
+            }
+            opts.set_headers(&headers.into());
+
+            if let Some(body) = &request.body {
+                opts.set_body(&JsValue::from_str(body));
+            }
+            if let Some(abort) = &abort {
+                opts.set_signal(Some(&abort.signal()));
+            }
+
+            let js_request = web_sys::Request::new_with_str_and_init(&request.url, &opts)
+                .map_err(network_error)?;
+            let window =
+                web_sys::window().ok_or_else(|| HttpError::Network("No window".to_string()))?;
+
+            let response: web_sys::Response =
+                JsFuture::from(window.fetch_with_request(&js_request))
+                    .await
+                    .map_err(fetch_error)?
+                    .dyn_into()
+                    .map_err(network_error)?;
+
+            let mut response_headers = HashMap::new();
+            if let Ok(Some(entries)) = js_sys::try_iter(&response.headers()) {
+                for entry in entries.flatten() {
+                    let pair: js_sys::Array = entry.unchecked_into();
+                    if let (Some(name), Some(value)) =
+                        (pair.get(0).as_string(), pair.get(1).as_string())
+                    {
+                        response_headers.insert(name, value);
+                    }
+                }
+            }
+
+            let body = JsFuture::from(response.text().map_err(network_error)?)
+                .await
+                .map_err(fetch_error)?
+                .as_string()
+                .unwrap_or_default();
+
+            Ok(Response::new(response.status(), response_headers, body))
+        })
+    }
+}
+
+fn network_error(value: JsValue) -> HttpError {
+    HttpError::Network(format!("{:?}", value))
+}
+
+fn fetch_error(value: JsValue) -> HttpError {
+    let name = js_sys::Reflect::get(&value, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string());
+    if name.as_deref() == Some("AbortError") {
+        HttpError::Aborted
+    } else {
+        network_error(value)
+    }
+}
+
+pub fn get(url: impl Into<String>) -> RequestBuilder {
+    RequestBuilder::new(Method::GET, url)
+}
+
+pub fn post(url: impl Into<String>) -> RequestBuilder {
+    RequestBuilder::new(Method::POST, url)
+}
+
+pub fn put(url: impl Into<String>) -> RequestBuilder {
+    RequestBuilder::new(Method::PUT, url)
+}
+
+pub fn patch(url: impl Into<String>) -> RequestBuilder {
+    RequestBuilder::new(Method::PATCH, url)
+}
+
+pub fn delete(url: impl Into<String>) -> RequestBuilder {
+    RequestBuilder::new(Method::DELETE, url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+    use std::cell::RefCell;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    /// Mock endpoint that echoes JSON bodies and serves `/users/1`
+    #[derive(Default)]
+    struct MockEndpoint {
+        last_request: RefCell<Option<HttpRequest>>,
+    }
+
+    impl Transport for MockEndpoint {
+        fn send(&self, request: HttpRequest, _abort: Option<AbortHandle>) -> ResponseFuture {
+            *self.last_request.borrow_mut() = Some(request.clone());
+            let response = match (request.method, request.url.as_str()) {
+                (Method::GET, "/users/1") => Response::new(
+                    200,
+                    HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+                    r#"{"id":1,"name":"Ada"}"#,
+                ),
+                (Method::POST, "/users") => {
+                    Response::new(201, HashMap::new(), request.body.unwrap_or_default())
+                }
+                _ => Response::new(404, HashMap::new(), "not found"),
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[test]
+    fn test_get_json_from_mock_endpoint() {
+        let endpoint = MockEndpoint::default();
+        let response = block_on(get("/users/1").send_with(&endpoint)).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(
+            response.json::<User>().unwrap(),
+            User {
+                id: 1,
+                name: "Ada".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_post_json_sets_content_type() {
+        let endpoint = MockEndpoint::default();
+        let user = User {
+            id: 2,
+            name: "Grace".to_string(),
+        };
+        let response = block_on(post("/users").json(&user).send_with(&endpoint)).unwrap();
+
+        assert_eq!(response.json::<User>().unwrap(), user);
+        let sent = endpoint.last_request.borrow().clone().unwrap();
+        assert!(sent
+            .headers
+            .contains(&("Content-Type".to_string(), "application/json".to_string())));
+    }
+
+    #[test]
+    fn test_non_2xx_is_only_an_error_when_opted_in() {
+        let endpoint = MockEndpoint::default();
+
+        let response = block_on(get("/missing").send_with(&endpoint)).unwrap();
+        assert_eq!(response.status, 404);
+        assert!(!response.ok());
+
+        let error = block_on(get("/missing").error_for_status().send_with(&endpoint)).unwrap_err();
+        assert_eq!(
+            error,
+            HttpError::Status {
+                status: 404,
+                body: "not found".to_string()
+            }
+        );
+    }
+}
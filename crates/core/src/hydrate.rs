@@ -0,0 +1,495 @@
+//! Hydration - L3
+//!
+//! `hydrate` adopts the server-rendered DOM instead of building it again:
+//! it walks the existing nodes alongside the client's first render,
+//! attaching event handlers and mounting portals as it goes.
+//!
+//! ```ignore
+//! // Server: renderer.add_component(Box::new(ServerRendered(TodoApp)));
+//! // Client, with the server HTML inside #app:
+//! reactive_v2::hydrate(Box::new(TodoApp), "app");
+//! ```
+//!
+//! Where the server HTML differs from the client's render, a warning names
+//! the path, the expected node and the one found, and the mismatched
+//! subtree is replaced with the client's. Text differing only in whitespace,
+//! attributes in another order and whitespace-only text between elements
+//! are not mismatches. `debug_hydration(true)` makes mismatches panic, to
+//! catch them in development.
+
+use crate::component::{attach_handlers, flatten_fragments, Element, Props};
+use std::cell::Cell;
+use std::fmt;
+use wasm_bindgen::JsCast;
+use web_sys::{Element as DomElement, Node};
+
+/// What the server writes where a portal sits
+const PORTAL_PLACEHOLDER: &str = "l9-portal";
+
+/// Attributes holding client instance ids, which differ from the server's
+/// by design: only their presence has to match
+const INSTANCE_ATTRS: &[&str] = &[crate::head::HEAD_ATTR];
+
+thread_local! {
+    static DEBUG_HYDRATION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Panic on hydration mismatches instead of warning and repairing them
+pub fn debug_hydration(enabled: bool) {
+    DEBUG_HYDRATION.with(|debug| debug.set(enabled));
+}
+
+/// A difference between the server HTML and the client's render
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// Tags from the root down, with the child index of each
+    pub path: Vec<String>,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Hydration mismatch at {}: expected {}, found {}",
+            self.path.join(" > "),
+            self.expected,
+            self.found
+        )
+    }
+}
+
+/// Warn about `mismatch`, or panic under `debug_hydration`
+fn report(mismatch: &Mismatch) {
+    if DEBUG_HYDRATION.with(Cell::get) {
+        panic!("{}", mismatch);
+    }
+    web_sys::console::warn_1(&mismatch.to_string().into());
+}
+
+/// Text with runs of whitespace collapsed and the ends trimmed, as the
+/// browser lays it out
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether rendered text and server text only differ in whitespace
+pub fn same_text(expected: &str, actual: &str) -> bool {
+    normalize_whitespace(expected) == normalize_whitespace(actual)
+}
+
+/// The attributes an element renders with, by name
+fn expected_attributes(props: &Props) -> Vec<(String, String)> {
+    let mut attributes: Vec<(String, String)> = props.rendered_attributes().cloned().collect();
+    if let Some(id) = &props.id {
+        attributes.push(("id".to_string(), id.clone()));
+    }
+    if let Some(class) = &props.class {
+        attributes.push(("class".to_string(), class.clone()));
+    }
+    attributes
+}
+
+/// Whether two attribute lists match, in any order
+pub fn same_attributes(expected: &[(String, String)], actual: &[(String, String)]) -> bool {
+    let sorted = |attributes: &[(String, String)]| {
+        let mut attributes: Vec<(String, String)> = attributes
+            .iter()
+            .map(|(name, value)| {
+                let name = name.to_ascii_lowercase();
+                let value = if INSTANCE_ATTRS.contains(&name.as_str()) {
+                    String::new()
+                } else {
+                    value.clone()
+                };
+                (name, value)
+            })
+            .collect();
+        attributes.sort();
+        attributes
+    };
+    sorted(expected) == sorted(actual)
+}
+
+/// `<tag a="b">` for messages
+fn describe_tag(tag: &str, attributes: &[(String, String)]) -> String {
+    let mut description = format!("<{}", tag.to_ascii_lowercase());
+    for (name, value) in attributes {
+        description.push_str(&format!(" {}=\"{}\"", name, value));
+    }
+    description.push('>');
+    description
+}
+
+fn describe_element(element: &Element) -> String {
+    match element {
+        Element::Text(text) => format!("text {:?}", text),
+        Element::Node { tag, props, .. } => describe_tag(tag, &expected_attributes(props)),
+        Element::Component(component) => describe_element(&component.render()),
+        Element::Portal { .. } => "a portal".to_string(),
+        Element::Fragment(_) => "a fragment".to_string(),
+    }
+}
+
+fn describe_node(node: Option<&Node>) -> String {
+    let Some(node) = node else {
+        return "nothing".to_string();
+    };
+    match node.node_type() {
+        Node::TEXT_NODE => format!("text {:?}", node.text_content().unwrap_or_default()),
+        Node::COMMENT_NODE => format!("comment {:?}", node.text_content().unwrap_or_default()),
+        _ => match node.dyn_ref::<DomElement>() {
+            Some(element) => describe_tag(&element.tag_name(), &dom_attributes(element)),
+            None => node.node_name(),
+        },
+    }
+}
+
+fn dom_attributes(element: &DomElement) -> Vec<(String, String)> {
+    element
+        .get_attribute_names()
+        .iter()
+        .filter_map(|name| name.as_string())
+        .map(|name| {
+            let value = element.get_attribute(&name).unwrap_or_default();
+            (name, value)
+        })
+        .collect()
+}
+
+/// Whitespace between elements and comments other than portal
+/// placeholders, which the client's render has no node for
+fn is_ignorable(node: &Node) -> bool {
+    match node.node_type() {
+        Node::TEXT_NODE => node.text_content().unwrap_or_default().trim().is_empty(),
+        Node::COMMENT_NODE => node.text_content().as_deref() != Some(PORTAL_PLACEHOLDER),
+        _ => false,
+    }
+}
+
+/// Adopt the server-rendered DOM in `container` for `element`, the
+/// client's first render, and return the root node
+pub fn hydrate(element: &Element, container: &DomElement) -> Node {
+    let mut path = vec![describe_container(container), segment(element)];
+    let mut cursor = skip_ignorable(container, container.first_child(), false);
+    let root = hydrate_node(element, container, cursor.as_ref(), false, &mut path);
+
+    // Anything after the root isn't part of this render
+    path.pop();
+    cursor = root.next_sibling();
+    remove_extra(container, cursor, &path);
+    root
+}
+
+fn describe_container(container: &DomElement) -> String {
+    match container.id() {
+        id if id.is_empty() => container.tag_name().to_ascii_lowercase(),
+        id => format!("#{}", id),
+    }
+}
+
+/// Remove the ignorable nodes from `node` on, unless text is expected
+/// there, and return the first that is left
+fn skip_ignorable(parent: &DomElement, mut node: Option<Node>, text_expected: bool) -> Option<Node> {
+    while let Some(current) = node {
+        let is_text = current.node_type() == Node::TEXT_NODE;
+        if !is_ignorable(&current) || (text_expected && is_text) {
+            return Some(current);
+        }
+        node = current.next_sibling();
+        let _ = parent.remove_child(&current);
+    }
+    None
+}
+
+/// Report and remove `node` and everything after it
+fn remove_extra(parent: &DomElement, mut node: Option<Node>, path: &[String]) {
+    while let Some(extra) = node {
+        node = extra.next_sibling();
+        if !is_ignorable(&extra) {
+            report(&Mismatch {
+                path: path.to_vec(),
+                expected: "nothing".to_string(),
+                found: describe_node(Some(&extra)),
+            });
+        }
+        crate::portal::unmount_within(&extra);
+        let _ = parent.remove_child(&extra);
+    }
+}
+
+/// Put the client's node for `element` where `found` is, or at the end
+fn repair(element: &Element, parent: &DomElement, found: Option<&Node>) -> Node {
+    let node = element.to_dom();
+    match found {
+        Some(found) => {
+            crate::portal::unmount_within(found);
+            parent.replace_child(&node, found).unwrap();
+        }
+        None => {
+            parent.append_child(&node).unwrap();
+        }
+    }
+    node
+}
+
+/// Hydrate `element` against `found`, a child of `parent`, and return the
+/// node that stands for it. `text_follows` says whether the next sibling
+/// renders as text, which the server HTML merged with this one.
+fn hydrate_node(
+    element: &Element,
+    parent: &DomElement,
+    found: Option<&Node>,
+    text_follows: bool,
+    path: &mut Vec<String>,
+) -> Node {
+    let mismatch = |path: &Vec<String>| {
+        report(&Mismatch {
+            path: path.clone(),
+            expected: describe_element(element),
+            found: describe_node(found),
+        });
+        repair(element, parent, found)
+    };
+
+    match element {
+        Element::Component(component) => {
+            hydrate_node(&component.render(), parent, found, text_follows, path)
+        }
+
+        Element::Text(expected) => {
+            let Some(text) = found.filter(|node| node.node_type() == Node::TEXT_NODE) else {
+                // Empty text renders as nothing on the server
+                if expected.trim().is_empty() {
+                    let node = element.to_dom();
+                    parent.insert_before(&node, found).unwrap();
+                    return node;
+                }
+                return mismatch(path);
+            };
+
+            // Adjacent texts arrive as one node; split off this one's part
+            let actual = text.text_content().unwrap_or_default();
+            if text_follows && actual.len() > expected.len() && actual.starts_with(expected.as_str()) {
+                let at = expected.encode_utf16().count() as u32;
+                let _ = text.unchecked_ref::<web_sys::Text>().split_text(at);
+            }
+
+            let actual = text.text_content().unwrap_or_default();
+            if !same_text(expected, &actual) {
+                return mismatch(path);
+            }
+            if &actual != expected {
+                text.set_text_content(Some(expected));
+            }
+            text.clone()
+        }
+
+        // Children splice fragments in before getting here; one standing
+        // alone, as a component's root, has no single node to adopt
+        Element::Fragment(_) => mismatch(path),
+
+        Element::Portal { target, children } => {
+            // Portal children are only ever rendered in the browser
+            match found.filter(|node| {
+                node.node_type() == Node::COMMENT_NODE
+                    && node.text_content().as_deref() == Some(PORTAL_PLACEHOLDER)
+            }) {
+                Some(placeholder) => {
+                    let node = crate::portal::mount(target, children);
+                    parent.replace_child(&node, placeholder).unwrap();
+                    node
+                }
+                None => mismatch(path),
+            }
+        }
+
+        Element::Node { tag, props, children } => {
+            let Some(dom) = found
+                .and_then(|node| node.dyn_ref::<DomElement>())
+                .filter(|dom| dom.tag_name().eq_ignore_ascii_case(tag))
+            else {
+                return mismatch(path);
+            };
+            let expected = expected_attributes(props);
+            if !same_attributes(&expected, &dom_attributes(dom)) {
+                return mismatch(path);
+            }
+
+            // Instance ids are the client's from now on
+            for (name, value) in &expected {
+                if INSTANCE_ATTRS.contains(&name.as_str()) {
+                    let _ = dom.set_attribute(name, value);
+                }
+            }
+            attach_handlers(dom, tag, props);
+            hydrate_children(dom, children, path);
+            dom.clone().into()
+        }
+    }
+}
+
+fn hydrate_children(parent: &DomElement, children: &[Element], path: &mut Vec<String>) {
+    let is_text = |element: Option<&&Element>| matches!(element, Some(Element::Text(_)));
+    let children = flatten_fragments(children);
+
+    let mut cursor = parent.first_child();
+    for (index, child) in children.iter().enumerate() {
+        cursor = skip_ignorable(parent, cursor, is_text(Some(child)));
+
+        path.push(format!("{}[{}]", segment(child), index));
+        let node = hydrate_node(child, parent, cursor.as_ref(), is_text(children.get(index + 1)), path);
+        path.pop();
+        cursor = node.next_sibling();
+    }
+    remove_extra(parent, cursor, path);
+}
+
+/// How `element` shows in a mismatch path
+fn segment(element: &Element) -> String {
+    match element {
+        Element::Text(_) => "text".to_string(),
+        Element::Node { tag, .. } => tag.clone(),
+        Element::Component(_) => "component".to_string(),
+        Element::Portal { .. } => "portal".to_string(),
+        Element::Fragment(_) => "fragment".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_whitespace_and_attribute_order_are_tolerated() {
+        assert!(same_text("Buy milk", "\n  Buy   milk "));
+        assert!(same_text("", "  \n"));
+        assert!(!same_text("Buy milk", "Buy bread"));
+
+        let rendered = attributes(&[("id", "save"), ("class", "btn primary"), ("type", "submit")]);
+        assert!(same_attributes(&rendered, &attributes(&[("type", "submit"), ("class", "btn primary"), ("id", "save")])));
+        assert!(!same_attributes(&rendered, &attributes(&[("id", "save"), ("class", "btn"), ("type", "submit")])));
+        assert!(!same_attributes(&rendered, &attributes(&[("id", "save"), ("class", "btn primary")])));
+
+        // Head markers carry each side's own instance id
+        let marker = |id: &str| attributes(&[(crate::head::HEAD_ATTR, id)]);
+        assert!(same_attributes(&marker("3"), &marker("17")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Hydration mismatch at #app > ul[2] > li[0]: expected text \"Buy milk\", found text \"Buy bread\"")]
+    fn test_debug_hydration_panics_on_mismatch() {
+        debug_hydration(true);
+        report(&Mismatch {
+            path: vec!["#app".to_string(), "ul[2]".to_string(), "li[0]".to_string()],
+            expected: describe_element(&Element::Text("Buy milk".to_string())),
+            found: "text \"Buy bread\"".to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Run `f` with `console.warn` recording its messages
+    fn capture_warnings(f: impl FnOnce()) -> Vec<String> {
+        let console = js_sys::Reflect::get(&js_sys::global(), &"console".into()).unwrap();
+        let original = js_sys::Reflect::get(&console, &"warn".into()).unwrap();
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let record = {
+            let warnings = warnings.clone();
+            Closure::<dyn FnMut(wasm_bindgen::JsValue)>::new(move |message: wasm_bindgen::JsValue| {
+                warnings.borrow_mut().push(message.as_string().unwrap_or_default());
+            })
+        };
+        js_sys::Reflect::set(&console, &"warn".into(), record.as_ref()).unwrap();
+        f();
+        js_sys::Reflect::set(&console, &"warn".into(), &original).unwrap();
+        let warnings = warnings.borrow().clone();
+        warnings
+    }
+
+    fn node(tag: &str, props: Props, children: Vec<Element>) -> Element {
+        Element::Node {
+            tag: tag.to_string(),
+            props: Box::new(props),
+            children,
+        }
+    }
+
+    fn text(value: &str) -> Element {
+        Element::Text(value.to_string())
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mismatched_subtree_is_reported_and_repaired() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let container = document.create_element("div").unwrap();
+        container.set_id("hydrate-test");
+        document.body().unwrap().append_child(&container).unwrap();
+
+        // Formatted server HTML, with the second item out of date
+        container.set_inner_html(
+            r#"
+            <ul data-kind="todos" class="todos">
+                <li>  Buy milk </li>
+                <li><em>Walk the dog</em></li>
+            </ul>
+            "#,
+        );
+        let kept = container.query_selector("li").unwrap().unwrap();
+
+        let clicks = Rc::new(Cell::new(0));
+        let on_click = {
+            let clicks = clicks.clone();
+            Rc::new(move || clicks.set(clicks.get() + 1))
+        };
+        let client = node(
+            "ul",
+            Props {
+                class: Some("todos".to_string()),
+                attributes: vec![("data-kind".to_string(), "todos".to_string())],
+                ..Default::default()
+            },
+            vec![
+                node("li", Props { on_click: Some(on_click), ..Default::default() }, vec![text("Buy milk")]),
+                node("li", Props::default(), vec![text("Walk the cat")]),
+            ],
+        );
+
+        let mut root = None;
+        let warnings = capture_warnings(|| root = Some(hydrate(&client, &container)));
+        assert_eq!(
+            warnings,
+            vec!["Hydration mismatch at #hydrate-test > ul > li[1] > text[0]: \
+                  expected text \"Walk the cat\", found <em>"
+                .to_string()]
+        );
+
+        // The matching item was adopted, handlers and all; the other repaired
+        let first = container.query_selector("li").unwrap().unwrap();
+        assert!(kept.is_same_node(Some(first.as_ref())));
+        kept.unchecked_ref::<web_sys::HtmlElement>().click();
+        assert_eq!(clicks.get(), 1);
+        assert_eq!(
+            container.inner_html(),
+            r#"<ul data-kind="todos" class="todos"><li>Buy milk</li><li>Walk the cat</li></ul>"#
+        );
+        assert!(root.unwrap().is_same_node(container.first_child().as_ref()));
+
+        container.remove();
+    }
+}
@@ -1,20 +1,38 @@
 //! Internationalization (i18n) Support - L5/L6
 //! Multi-language support with dynamic locale switching
+//!
+//! During SSR the locale comes from the request (see `detect_request_locale`)
+//! and `in_locale` makes `t()` render in it, so the HTML arrives translated.
+//!
+//! Components translate with `use_translation()`, under an `I18nProvider`
+//! or the catalog given to `init_i18n`, and re-render when the locale
+//! changes.
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use wasm_bindgen::prelude::*;
 
+use crate::component::{Component, Element, Props};
+use crate::hooks::{provide_context, use_context, use_persisted, Context as HookContext};
+
 // Type aliases to simplify complex types
 type TranslateFn = Box<dyn Fn(&str, Option<&HashMap<String, String>>) -> String>;
 type PluralFn = Box<dyn Fn(&str, i32, Option<&HashMap<String, String>>) -> String>;
 type SetLocaleFn = Box<dyn Fn(Locale)>;
 type SimpleTFn = Box<dyn Fn(&str) -> String>;
 
+/// Cookie (and localStorage key) holding the user's chosen locale
+pub const LOCALE_COOKIE: &str = "layer9-locale";
+
 /// Supported locales
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Locale {
+    #[default]
     EnUS,
     EnGB,
     ZhCN,
@@ -65,6 +83,21 @@ impl Locale {
         }
     }
 
+    /// Lenient match for a BCP 47 tag from a header or cookie: any case,
+    /// `_` or `-`, falling back to the language alone (`en-AU` -> `en`)
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.trim().replace('_', "-");
+        let mut parts = tag.splitn(2, '-');
+        let language = parts.next()?.to_ascii_lowercase();
+        if let Some(region) = parts.next() {
+            let code = format!("{}-{}", language, region.to_ascii_uppercase());
+            if let Some(locale) = Self::from_code(&code) {
+                return Some(locale);
+            }
+        }
+        Self::from_code(&language)
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Locale::EnUS => "English (US)",
@@ -139,46 +172,92 @@ impl TranslationCatalog {
     }
 }
 
+/// Plain messages keyed by locale tag, then message id; tags that aren't
+/// a supported locale are skipped
+impl From<HashMap<String, HashMap<String, String>>> for TranslationCatalog {
+    fn from(catalog: HashMap<String, HashMap<String, String>>) -> Self {
+        let mut locales: HashMap<Locale, Messages> = HashMap::new();
+        for (tag, messages) in catalog {
+            if let Some(locale) = Locale::from_tag(&tag) {
+                locales.entry(locale).or_default().extend(
+                    messages
+                        .into_iter()
+                        .map(|(key, text)| (key, TranslationValue::Text(text))),
+                );
+            }
+        }
+        TranslationCatalog { locales }
+    }
+}
+
 /// i18n context
+#[derive(Clone)]
 pub struct I18nContext {
     current_locale: Rc<RefCell<Locale>>,
-    catalog: Rc<TranslationCatalog>,
+    catalog: Arc<TranslationCatalog>,
     fallback_locale: Locale,
 }
 
 impl I18nContext {
     pub fn new(catalog: TranslationCatalog) -> Self {
+        Self::from_shared(Arc::new(catalog))
+    }
+
+    fn from_shared(catalog: Arc<TranslationCatalog>) -> Self {
         // Detect browser locale
         let browser_locale = detect_browser_locale().unwrap_or(Locale::EnUS);
 
         I18nContext {
             current_locale: Rc::new(RefCell::new(browser_locale)),
-            catalog: Rc::new(catalog),
+            catalog,
             fallback_locale: Locale::EnUS,
         }
     }
 
+    /// The request's locale while rendering under `in_locale`, otherwise the
+    /// user's current choice
     pub fn locale(&self) -> Locale {
-        *self.current_locale.borrow()
+        REQUEST_LOCALE
+            .with(Cell::get)
+            .unwrap_or_else(|| *self.current_locale.borrow())
     }
 
     pub fn set_locale(&self, locale: Locale) {
         *self.current_locale.borrow_mut() = locale;
 
-        // Persist to localStorage
-        if let Some(storage) = web_sys::window()
-            .and_then(|w| w.local_storage().ok())
-            .flatten()
+        #[cfg(target_arch = "wasm32")]
         {
-            let _ = storage.set_item("layer9-locale", locale.code());
-        }
+            // Persist to localStorage
+            if let Some(storage) = web_sys::window()
+                .and_then(|w| w.local_storage().ok())
+                .flatten()
+            {
+                let _ = storage.set_item(LOCALE_COOKIE, locale.code());
+            }
 
-        // Update document lang attribute
-        if let Some(document) = web_sys::window().and_then(|w| w.document()) {
-            if let Some(html) = document.document_element() {
-                let _ = html.set_attribute("lang", locale.code());
+            // And to a cookie, so the server renders the next page in it
+            crate::security::SecureCookie::set(
+                LOCALE_COOKIE,
+                locale.code(),
+                crate::security::CookieOptions {
+                    max_age: Some(60 * 60 * 24 * 365),
+                    path: Some("/".to_string()),
+                    ..Default::default()
+                },
+            );
+
+            // Update document lang attribute
+            if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+                if let Some(html) = document.document_element() {
+                    let _ = html.set_attribute("lang", locale.code());
+                }
             }
         }
+
+        let subscribers: Vec<_> = LOCALE_SUBSCRIBERS.with(|subs| subs.borrow().iter().copied().collect());
+        for component_id in subscribers {
+            crate::reactive_v2::queue_component_render(component_id);
+        }
     }
 
     pub fn t(&self, key: &str) -> String {
@@ -202,41 +281,40 @@ impl I18nContext {
         key.to_string()
     }
 
+    /// Translate with `count` available as `{count}`, choosing the form by
+    /// the locale's plural rules. Text messages can do the same with ICU
+    /// syntax: `"{count, plural, one {# item} other {# items}}"`.
     pub fn plural(&self, key: &str, count: i32, args: Option<&HashMap<String, String>>) -> String {
         let locale = self.locale();
+        let (locale, value) = match self.catalog.get(locale, key) {
+            Some(value) => (locale, value),
+            None => match self.catalog.get(self.fallback_locale, key) {
+                Some(value) => (self.fallback_locale, value),
+                None => return format!("{} ({})", key, count),
+            },
+        };
 
-        if let Some(value) = self.catalog.get(locale, key) {
-            match value {
-                TranslationValue::Plural {
-                    zero,
-                    one,
-                    few,
-                    many,
-                    other,
-                } => {
-                    let text = match count {
-                        0 if zero.is_some() => zero.as_ref().unwrap(),
-                        1 => one,
-                        2..=4 if few.is_some() => few.as_ref().unwrap(),
-                        _ if count > 20 && many.is_some() => many.as_ref().unwrap(),
-                        _ => other,
-                    };
-
-                    let mut final_args = args.cloned().unwrap_or_default();
-                    final_args.insert("count".to_string(), count.to_string());
-
-                    self.interpolate(text, &final_args)
-                }
-                TranslationValue::Text(text) => {
-                    let mut final_args = args.cloned().unwrap_or_default();
-                    final_args.insert("count".to_string(), count.to_string());
+        let mut final_args = args.cloned().unwrap_or_default();
+        final_args.insert("count".to_string(), count.to_string());
 
-                    self.interpolate(text, &final_args)
-                }
-            }
-        } else {
-            format!("{} ({})", key, count)
-        }
+        let text = match value {
+            TranslationValue::Plural {
+                zero,
+                one,
+                few,
+                many,
+                other,
+            } => match (count, locale.plural_category(count as f64)) {
+                (0, _) if zero.is_some() => zero.as_ref().unwrap(),
+                (_, PluralCategory::One) => one,
+                (_, PluralCategory::Few) if few.is_some() => few.as_ref().unwrap(),
+                (_, PluralCategory::Many) if many.is_some() => many.as_ref().unwrap(),
+                _ => other,
+            },
+            TranslationValue::Text(text) => text,
+        };
+
+        format_message(locale, text, &final_args)
     }
 
     fn format_translation(
@@ -245,43 +323,503 @@ impl I18nContext {
         args: Option<&HashMap<String, String>>,
     ) -> String {
         match value {
-            TranslationValue::Text(text) => {
-                if let Some(args) = args {
-                    self.interpolate(text, args)
+            TranslationValue::Text(text) => match args {
+                Some(args) => format_message(self.locale(), text, args),
+                None => format_message(self.locale(), text, &HashMap::new()),
+            },
+            TranslationValue::Plural { other, .. } => other.clone(),
+        }
+    }
+}
+
+/// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Keyword used for this category in ICU messages
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+impl Locale {
+    /// Cardinal plural category of `n`, as in "1 item" / "2 items"
+    pub fn plural_category(&self, n: f64) -> PluralCategory {
+        let n = n.abs();
+        let i = n.trunc() as u64;
+        let whole = n.fract() == 0.0;
+        let millions = whole && i != 0 && i.is_multiple_of(1_000_000);
+
+        match self {
+            Locale::EnUS | Locale::EnGB | Locale::DeDE | Locale::ItIT => {
+                if i == 1 && whole {
+                    PluralCategory::One
                 } else {
-                    text.clone()
+                    PluralCategory::Other
                 }
             }
-            TranslationValue::Plural { other, .. } => other.clone(),
+            Locale::EsES => match n {
+                _ if n == 1.0 => PluralCategory::One,
+                _ if millions => PluralCategory::Many,
+                _ => PluralCategory::Other,
+            },
+            Locale::FrFR | Locale::PtBR => match i {
+                0 | 1 => PluralCategory::One,
+                _ if millions => PluralCategory::Many,
+                _ => PluralCategory::Other,
+            },
+            Locale::RuRU => match (whole, i % 10, i % 100) {
+                (false, _, _) => PluralCategory::Other,
+                (true, 1, rem) if rem != 11 => PluralCategory::One,
+                (true, 2..=4, rem) if !(12..=14).contains(&rem) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            Locale::ZhCN | Locale::ZhTW | Locale::JaJP | Locale::KoKR => PluralCategory::Other,
+        }
+    }
+
+    /// Ordinal plural category of `n`, as in "1st" / "2nd" / "3rd"
+    pub fn ordinal_category(&self, n: f64) -> PluralCategory {
+        if n.fract() != 0.0 {
+            return PluralCategory::Other;
+        }
+        let i = n.abs() as u64;
+
+        match self {
+            Locale::EnUS | Locale::EnGB => match (i % 10, i % 100) {
+                (1, rem) if rem != 11 => PluralCategory::One,
+                (2, rem) if rem != 12 => PluralCategory::Two,
+                (3, rem) if rem != 13 => PluralCategory::Few,
+                _ => PluralCategory::Other,
+            },
+            Locale::FrFR if i == 1 => PluralCategory::One,
+            Locale::ItIT if matches!(i, 8 | 11 | 80 | 800) => PluralCategory::Many,
+            _ => PluralCategory::Other,
+        }
+    }
+}
+
+/// Piece of a parsed ICU message
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    Arg(String),
+    /// `#` inside a plural case
+    Number,
+    Plural {
+        arg: String,
+        offset: f64,
+        ordinal: bool,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+    Select {
+        arg: String,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+}
+
+/// Format an ICU MessageFormat pattern, e.g.
+///
+/// ```ignore
+/// format_message(Locale::EnUS, "{count, plural, =0 {No todos} one {# todo} other {# todos}}", &args)
+/// format_message(Locale::EnUS, "{gender, select, female {She} male {He} other {They}} replied", &args)
+/// ```
+///
+/// Supports simple arguments, `plural` (with `offset:` and `=N` cases),
+/// `selectordinal`, `select` and apostrophe quoting. Unknown arguments are
+/// left as `{name}`. Patterns that don't parse as ICU fall back to plain
+/// `{name}` / `{{ name }}` substitution.
+pub fn format_message(locale: Locale, pattern: &str, args: &HashMap<String, String>) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut pos = 0;
+    match parse_message(&chars, &mut pos, false) {
+        Ok(parts) if pos == chars.len() => {
+            let mut out = String::new();
+            render_parts(&parts, locale, args, None, &mut out);
+            out
+        }
+        _ => interpolate(pattern, args),
+    }
+}
+
+fn interpolate(text: &str, args: &HashMap<String, String>) -> String {
+    let mut result = text.to_string();
+
+    for (key, value) in args {
+        result = result.replace(&format!("{{{}}}", key), value);
+        result = result.replace(&format!("{{{{ {} }}}}", key), value);
+    }
+
+    result
+}
+
+/// Parse until the end of input or the `}` closing a case (left unconsumed)
+fn parse_message(chars: &[char], pos: &mut usize, in_plural: bool) -> Result<Vec<Part>, ()> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+
+    while let Some(&c) = chars.get(*pos) {
+        match c {
+            '\'' => {
+                *pos += 1;
+                match chars.get(*pos) {
+                    Some('\'') => {
+                        text.push('\'');
+                        *pos += 1;
+                    }
+                    // Quoted literal text, where '' is still an apostrophe
+                    Some(&next) if next == '{' || next == '}' || (in_plural && next == '#') => {
+                        while let Some(&quoted) = chars.get(*pos) {
+                            *pos += 1;
+                            if quoted != '\'' {
+                                text.push(quoted);
+                            } else if chars.get(*pos) == Some(&'\'') {
+                                text.push('\'');
+                                *pos += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+                    _ => text.push('\''),
+                }
+            }
+            '#' if in_plural => {
+                flush_text(&mut text, &mut parts);
+                parts.push(Part::Number);
+                *pos += 1;
+            }
+            '{' => {
+                flush_text(&mut text, &mut parts);
+                *pos += 1;
+                parts.push(parse_argument(chars, pos, in_plural)?);
+            }
+            '}' => break,
+            _ => {
+                text.push(c);
+                *pos += 1;
+            }
+        }
+    }
+
+    flush_text(&mut text, &mut parts);
+    Ok(parts)
+}
+
+fn flush_text(text: &mut String, parts: &mut Vec<Part>) {
+    if !text.is_empty() {
+        parts.push(Part::Text(std::mem::take(text)));
+    }
+}
+
+/// Parse an argument after its opening `{`, through its closing `}`
+fn parse_argument(chars: &[char], pos: &mut usize, in_plural: bool) -> Result<Part, ()> {
+    let name = read_token(chars, pos);
+    if name.is_empty() {
+        return Err(());
+    }
+    skip_whitespace(chars, pos);
+
+    match chars.get(*pos) {
+        Some('}') => {
+            *pos += 1;
+            return Ok(Part::Arg(name));
+        }
+        Some(',') => *pos += 1,
+        _ => return Err(()),
+    }
+
+    skip_whitespace(chars, pos);
+    let kind = read_token(chars, pos);
+    skip_whitespace(chars, pos);
+
+    let (plural, ordinal) = match kind.as_str() {
+        "plural" => (true, false),
+        "selectordinal" => (true, true),
+        "select" => (false, false),
+        // Formatted types like `number` render as the plain value
+        _ => {
+            while let Some(&c) = chars.get(*pos) {
+                *pos += 1;
+                if c == '}' {
+                    return Ok(Part::Arg(name));
+                }
+            }
+            return Err(());
         }
+    };
+    if chars.get(*pos) != Some(&',') {
+        return Err(());
     }
+    *pos += 1;
+
+    let mut offset = 0.0;
+    let mut cases = Vec::new();
+    loop {
+        skip_whitespace(chars, pos);
+        match chars.get(*pos) {
+            Some('}') => {
+                *pos += 1;
+                break;
+            }
+            None => return Err(()),
+            _ => {}
+        }
 
-    fn interpolate(&self, text: &str, args: &HashMap<String, String>) -> String {
-        let mut result = text.to_string();
+        let selector = read_token(chars, pos);
+        if plural && cases.is_empty() {
+            if let Some(value) = selector.strip_prefix("offset:") {
+                offset = value.parse().map_err(|_| ())?;
+                continue;
+            }
+        }
+        skip_whitespace(chars, pos);
+        if selector.is_empty() || chars.get(*pos) != Some(&'{') {
+            return Err(());
+        }
+        *pos += 1;
+        // `#` still means the count inside a select nested in a plural
+        let message = parse_message(chars, pos, plural || in_plural)?;
+        if chars.get(*pos) != Some(&'}') {
+            return Err(());
+        }
+        *pos += 1;
+        cases.push((selector, message));
+    }
 
-        for (key, value) in args {
-            result = result.replace(&format!("{{{}}}", key), value);
-            result = result.replace(&format!("{{{{ {} }}}}", key), value);
+    Ok(if plural {
+        Part::Plural {
+            arg: name,
+            offset,
+            ordinal,
+            cases,
         }
+    } else {
+        Part::Select { arg: name, cases }
+    })
+}
 
-        result
+fn read_token(chars: &[char], pos: &mut usize) -> String {
+    let mut token = String::new();
+    while let Some(&c) = chars.get(*pos) {
+        if c.is_whitespace() || matches!(c, ',' | '{' | '}') {
+            break;
+        }
+        token.push(c);
+        *pos += 1;
+    }
+    token
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+fn render_parts(
+    parts: &[Part],
+    locale: Locale,
+    args: &HashMap<String, String>,
+    number: Option<f64>,
+    out: &mut String,
+) {
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Arg(name) => match args.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    out.push('{');
+                    out.push_str(name);
+                    out.push('}');
+                }
+            },
+            Part::Number => {
+                if let Some(n) = number {
+                    out.push_str(&format_number(n));
+                }
+            }
+            Part::Plural {
+                arg,
+                offset,
+                ordinal,
+                cases,
+            } => {
+                let value = args.get(arg).and_then(|v| v.trim().parse::<f64>().ok());
+                let case = value.and_then(|value| {
+                    // Exact matches compare against the value before the offset
+                    let exact = cases
+                        .iter()
+                        .find(|(selector, _)| selector.strip_prefix('=').and_then(|n| n.parse::<f64>().ok()) == Some(value));
+                    exact.or_else(|| {
+                        let category = if *ordinal {
+                            locale.ordinal_category(value - offset)
+                        } else {
+                            locale.plural_category(value - offset)
+                        };
+                        cases.iter().find(|(selector, _)| selector == category.as_str())
+                    })
+                });
+                if let Some((_, message)) = case.or_else(|| cases.iter().find(|(selector, _)| selector == "other")) {
+                    render_parts(message, locale, args, value.map(|v| v - offset), out);
+                }
+            }
+            Part::Select { arg, cases } => {
+                let value = args.get(arg).map(String::as_str);
+                let case = cases
+                    .iter()
+                    .find(|(selector, _)| Some(selector.as_str()) == value)
+                    .or_else(|| cases.iter().find(|(selector, _)| selector == "other"));
+                if let Some((_, message)) = case {
+                    render_parts(message, locale, args, number, out);
+                }
+            }
+        }
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
     }
 }
 
 thread_local! {
     static I18N: RefCell<Option<I18nContext>> = const { RefCell::new(None) };
+    static REQUEST_LOCALE: Cell<Option<Locale>> = const { Cell::new(None) };
+    /// Components that read `use_translation()` and re-render when the
+    /// locale changes
+    static LOCALE_SUBSCRIBERS: RefCell<HashSet<u32>> = RefCell::new(HashSet::new());
 }
 
+/// Catalog shared with server worker threads, which each build their own
+/// context from it on first use
+#[cfg(not(target_arch = "wasm32"))]
+static SHARED_CATALOG: once_cell::sync::OnceCell<Arc<TranslationCatalog>> = once_cell::sync::OnceCell::new();
+
 /// Initialize i18n
 pub fn init_i18n(catalog: TranslationCatalog) {
-    let ctx = I18nContext::new(catalog);
+    let catalog = Arc::new(catalog);
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = SHARED_CATALOG.set(catalog.clone());
+
+    let ctx = I18nContext::from_shared(catalog);
     I18N.with(|i18n| {
         *i18n.borrow_mut() = Some(ctx);
     });
 }
 
+/// Make sure this thread has a context, building it from the shared catalog
+/// when `init_i18n` ran on another thread
+fn ensure_context() {
+    #[cfg(not(target_arch = "wasm32"))]
+    I18N.with(|i18n| {
+        if i18n.borrow().is_none() {
+            if let Some(catalog) = SHARED_CATALOG.get() {
+                *i18n.borrow_mut() = Some(I18nContext::from_shared(catalog.clone()));
+            }
+        }
+    });
+}
+
+/// Run `render` with `locale` as the locale `t()` translates into
+pub fn with_locale<R>(locale: Locale, render: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Locale>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            REQUEST_LOCALE.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(REQUEST_LOCALE.with(|current| current.replace(Some(locale))));
+    render()
+}
+
+/// Run `future` with `locale` as the locale `t()` translates into, e.g. an
+/// SSR handler rendering for one request
+pub fn in_locale<F: Future>(locale: Locale, future: F) -> InLocale<F> {
+    InLocale {
+        locale,
+        inner: Box::pin(future),
+    }
+}
+
+/// Future that renders in a fixed locale each time it is polled
+pub struct InLocale<F> {
+    locale: Locale,
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for InLocale<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let locale = self.locale;
+        with_locale(locale, || self.inner.as_mut().poll(cx))
+    }
+}
+
+/// Locale for a request: the saved `layer9-locale` cookie, then the best
+/// supported `Accept-Language` entry
+pub fn detect_request_locale(cookie_header: Option<&str>, accept_language: Option<&str>) -> Option<Locale> {
+    let saved = cookie_header.and_then(|cookies| {
+        cookies
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == LOCALE_COOKIE)
+            .and_then(|(_, value)| Locale::from_tag(value))
+    });
+
+    saved.or_else(|| accept_language.and_then(|header| parse_accept_language(header).into_iter().next()))
+}
+
+/// Supported locales in an `Accept-Language` header, most preferred first
+pub fn parse_accept_language(header: &str) -> Vec<Locale> {
+    let mut ranked: Vec<(f32, Locale)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let locale = Locale::from_tag(parts.next()?)?;
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (quality > 0.0).then_some((quality, locale))
+        })
+        .collect();
+
+    // Stable, so equal weights keep the header's order
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut locales = Vec::new();
+    for (_, locale) in ranked {
+        if !locales.contains(&locale) {
+            locales.push(locale);
+        }
+    }
+    locales
+}
+
 /// i18n hook
 pub fn use_i18n() -> I18n {
+    ensure_context();
+
     // First check if initialized
     let locale = I18N.with(|i18n| {
         let borrowed = i18n.borrow();
@@ -338,20 +876,177 @@ pub struct I18n {
     pub plural: PluralFn,
 }
 
+/// Provides a locale and catalog to every `use_translation()` in the app
+///
+/// ```ignore
+/// I18nProvider::new(Locale::KoKR, catalog).children(vec![app.render()])
+/// ```
+///
+/// The context is app-wide: the provider created last is the one
+/// translations use, so nesting a second provider doesn't give part of the
+/// tree a locale of its own. Built during a render, the locale lives in
+/// that component's hooks, so `locale` is only the initial one and a
+/// switch made with `set_locale` survives re-renders.
+pub struct I18nProvider {
+    context: I18nContext,
+    children: Vec<Element>,
+}
+
+impl I18nProvider {
+    pub fn new(locale: Locale, catalog: impl Into<TranslationCatalog>) -> Self {
+        let mut context = I18nContext::new(catalog.into());
+        context.current_locale = use_persisted(|| locale);
+        provide_context(&HookContext::<I18nContext>::new(), context.clone());
+
+        I18nProvider {
+            context,
+            children: vec![],
+        }
+    }
+
+    /// Locale to look a key up in when the current one lacks it; `en-US`
+    /// by default
+    pub fn fallback_locale(mut self, locale: Locale) -> Self {
+        self.context.fallback_locale = locale;
+        provide_context(&HookContext::<I18nContext>::new(), self.context.clone());
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn translation(&self) -> Translation {
+        Translation {
+            context: Some(self.context.clone()),
+        }
+    }
+}
+
+impl Component for I18nProvider {
+    fn render(&self) -> Element {
+        provide_context(&HookContext::<I18nContext>::new(), self.context.clone());
+
+        let locale = self.context.locale();
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("i18n-provider".to_string()),
+                attributes: vec![
+                    ("lang".to_string(), locale.code().to_string()),
+                    ("dir".to_string(), if locale.rtl() { "rtl" } else { "ltr" }.to_string()),
+                ],
+                ..Default::default()
+            }),
+            children: self.children.clone(),
+        }
+    }
+}
+
+/// Translations in the current locale, returned by `use_translation()`
+#[derive(Clone)]
+pub struct Translation {
+    context: Option<I18nContext>,
+}
+
+impl Translation {
+    pub fn locale(&self) -> Locale {
+        self.context
+            .as_ref()
+            .map(I18nContext::locale)
+            .unwrap_or_else(current_locale)
+    }
+
+    /// Switch locale, re-rendering every component that translates
+    pub fn set_locale(&self, locale: Locale) {
+        if let Some(context) = &self.context {
+            context.set_locale(locale);
+        }
+    }
+
+    /// The message for `key`, from the fallback locale if the current one
+    /// lacks it, or the key itself if neither has it
+    pub fn t(&self, key: &str) -> String {
+        self.t_with(key, &[])
+    }
+
+    /// Like `t`, filling `{name}` placeholders from `args`
+    pub fn t_with(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let Some(context) = &self.context else {
+            return key.to_string();
+        };
+        let args: HashMap<String, String> = args
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        context.translate(key, Some(&args))
+    }
+
+    /// The form of `key` for `count`, chosen by the locale's plural rules
+    pub fn t_plural(&self, key: &str, count: i32) -> String {
+        match &self.context {
+            Some(context) => context.plural(key, count, None),
+            None => key.to_string(),
+        }
+    }
+}
+
+/// Translate in the nearest `I18nProvider`'s locale, or the one set up by
+/// `init_i18n`. A component calling this during render re-renders when the
+/// locale changes
+pub fn use_translation() -> Translation {
+    if let Some(component_id) = crate::reactive_v2::get_current_component() {
+        LOCALE_SUBSCRIBERS.with(|subs| subs.borrow_mut().insert(component_id));
+        use_persisted(|| LocaleSubscription(component_id));
+    }
+
+    let context = use_context(&HookContext::<I18nContext>::new()).or_else(|| {
+        ensure_context();
+        I18N.with(|i18n| i18n.borrow().clone())
+    });
+    Translation { context }
+}
+
+/// Kept in a translating component's hooks; dropped with them when the
+/// component unmounts, which stops its locale re-renders
+struct LocaleSubscription(u32);
+
+impl Drop for LocaleSubscription {
+    fn drop(&mut self) {
+        // Thread-locals may already be gone when the thread exits
+        let _ = LOCALE_SUBSCRIBERS.try_with(|subs| subs.borrow_mut().remove(&self.0));
+    }
+}
+
 /// Detect browser locale
+#[cfg(not(target_arch = "wasm32"))]
+fn detect_browser_locale() -> Option<Locale> {
+    None
+}
+
+/// Detect browser locale
+#[cfg(target_arch = "wasm32")]
 fn detect_browser_locale() -> Option<Locale> {
     // Check localStorage first
     if let Some(storage) = web_sys::window()
         .and_then(|w| w.local_storage().ok())
         .flatten()
     {
-        if let Ok(Some(stored)) = storage.get_item("layer9-locale") {
+        if let Ok(Some(stored)) = storage.get_item(LOCALE_COOKIE) {
             if let Some(locale) = Locale::from_code(&stored) {
                 return Some(locale);
             }
         }
     }
 
+    // Then the cookie the server renders from
+    if let Some(locale) =
+        crate::security::SecureCookie::get(LOCALE_COOKIE).and_then(|value| Locale::from_tag(&value))
+    {
+        return Some(locale);
+    }
+
     // Check navigator language
     if let Some(window) = web_sys::window() {
         if let Some(navigator) = window.navigator().language() {
@@ -364,8 +1059,39 @@ fn detect_browser_locale() -> Option<Locale> {
     None
 }
 
+/// Locale's current language, for formatting: the request's locale during
+/// SSR, else the i18n context's, else the browser's
+pub fn current_locale() -> Locale {
+    if let Some(locale) = REQUEST_LOCALE.with(Cell::get) {
+        return locale;
+    }
+    ensure_context();
+    I18N.with(|i18n| i18n.borrow().as_ref().map(|ctx| ctx.locale()))
+        .or_else(detect_browser_locale)
+        .unwrap_or_default()
+}
+
+impl Locale {
+    /// Decimal and grouping separators
+    fn number_symbols(&self) -> (char, char) {
+        match self {
+            Locale::DeDE | Locale::EsES | Locale::ItIT | Locale::PtBR => (',', '.'),
+            Locale::FrFR => (',', '\u{202F}'),
+            Locale::RuRU => (',', '\u{00A0}'),
+            _ => ('.', ','),
+        }
+    }
+
+    /// Spanish leaves four-digit numbers ungrouped ("1234" but "12.345")
+    fn min_grouping_digits(&self) -> usize {
+        match self {
+            Locale::EsES => 5,
+            _ => 4,
+        }
+    }
+}
+
 /// Number formatting
-#[allow(dead_code)]
 pub struct NumberFormat {
     locale: Locale,
 }
@@ -375,30 +1101,200 @@ impl NumberFormat {
         NumberFormat { locale }
     }
 
+    /// Grouped number with up to three decimals, e.g. `1,234.5` / `1.234,5`
     pub fn format(&self, number: f64) -> String {
-        // Use Intl.NumberFormat
-        format!("{:.2}", number) // Simplified for now
+        let fixed = self.fixed(number, 3);
+        let (decimal, _) = self.locale.number_symbols();
+        match fixed.split_once(decimal) {
+            Some((whole, fraction)) => {
+                let fraction = fraction.trim_end_matches('0');
+                if fraction.is_empty() {
+                    whole.to_string()
+                } else {
+                    format!("{}{}{}", whole, decimal, fraction)
+                }
+            }
+            None => fixed,
+        }
+    }
+
+    /// Grouped number with exactly `decimals` decimals
+    pub fn fixed(&self, number: f64, decimals: usize) -> String {
+        let (decimal, group) = self.locale.number_symbols();
+        // Round half away from zero like Intl, not half to even like `{:.2}`
+        let scale = 10f64.powi(decimals as i32);
+        let formatted = format!("{:.*}", decimals, (number.abs() * scale).round() / scale);
+        let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+        let mut out = String::new();
+        if number < 0.0 && formatted.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+            out.push('-');
+        }
+        let grouped = whole.len() >= self.locale.min_grouping_digits();
+        for (i, digit) in whole.chars().enumerate() {
+            if grouped && i > 0 && (whole.len() - i) % 3 == 0 {
+                out.push(group);
+            }
+            out.push(digit);
+        }
+        if !fraction.is_empty() {
+            out.push(decimal);
+            out.push_str(fraction);
+        }
+        out
     }
 
+    /// Amount in an ISO 4217 currency, with the symbol where the locale puts
+    /// it: `$1,234.56`, `1.234,56 €`, `R$ 1.234,56`, `₩1,235`
     pub fn currency(&self, amount: f64, currency: &str) -> String {
-        match currency {
-            "USD" => format!("${:.2}", amount),
-            "EUR" => format!("€{:.2}", amount),
-            "GBP" => format!("£{:.2}", amount),
-            "JPY" => format!("¥{:.0}", amount),
-            "CNY" => format!("¥{:.2}", amount),
-            "KRW" => format!("₩{:.0}", amount),
-            _ => format!("{} {:.2}", currency, amount),
+        let decimals = match currency {
+            "JPY" | "KRW" => 0,
+            _ => 2,
+        };
+        let symbol = match (currency, self.locale) {
+            ("USD", Locale::EnUS | Locale::ZhCN | Locale::ZhTW | Locale::JaJP | Locale::KoKR) => "$",
+            ("USD", Locale::EnGB | Locale::PtBR) => "US$",
+            ("USD", _) => "$",
+            ("EUR", _) => "€",
+            ("GBP", _) => "£",
+            ("JPY", Locale::JaJP) => "￥",
+            ("JPY", _) => "¥",
+            ("CNY", Locale::ZhCN) => "¥",
+            ("CNY", _) => "CN¥",
+            ("KRW", _) => "₩",
+            ("BRL", _) => "R$",
+            ("RUB", Locale::RuRU) => "₽",
+            (code, _) => code,
+        };
+        let number = self.fixed(amount.abs(), decimals);
+        let sign = if amount < 0.0 && number.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+            "-"
+        } else {
+            ""
+        };
+
+        match self.locale {
+            Locale::DeDE | Locale::FrFR | Locale::EsES | Locale::ItIT | Locale::RuRU => {
+                format!("{}{}\u{00A0}{}", sign, number, symbol)
+            }
+            Locale::PtBR => format!("{}{}\u{00A0}{}", sign, symbol, number),
+            _ if symbol.len() == 3 && symbol.bytes().all(|b| b.is_ascii_uppercase()) => {
+                format!("{}{}\u{00A0}{}", sign, symbol, number)
+            }
+            _ => format!("{}{}{}", sign, symbol, number),
         }
     }
 
+    /// `0.125` as `12.5%`, or `12,5 %` where the locale spaces it
     pub fn percent(&self, value: f64) -> String {
-        format!("{:.1}%", value * 100.0)
+        let number = self.format((value * 1000.0).round() / 10.0);
+        match self.locale {
+            Locale::DeDE | Locale::FrFR | Locale::EsES | Locale::RuRU => format!("{}\u{00A0}%", number),
+            _ => format!("{}%", number),
+        }
+    }
+
+    /// Storage size in binary units, e.g. `44.3 MB`
+    pub fn bytes(&self, bytes: f64) -> String {
+        const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+        let mut value = bytes;
+        let mut unit = 0;
+        while value.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        let number = if unit == 0 { self.fixed(value, 0) } else { self.format((value * 10.0).round() / 10.0) };
+        format!("{}\u{00A0}{}", number, UNITS[unit])
+    }
+}
+
+/// Unit for relative times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeTimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Unit names by plural form, `[one, few, many, other]`
+type UnitForms = [&'static str; 4];
+
+/// How a locale phrases relative times
+struct RelativeTimeWords {
+    now: &'static str,
+    past: &'static str,
+    future: &'static str,
+    /// Between number and unit
+    joiner: &'static str,
+    units: [UnitForms; 7],
+}
+
+const fn two(one: &'static str, other: &'static str) -> UnitForms {
+    [one, other, other, other]
+}
+
+impl Locale {
+    fn relative_time_words(&self) -> RelativeTimeWords {
+        let words = |now, past, future, joiner, units| RelativeTimeWords { now, past, future, joiner, units };
+        match self {
+            Locale::EnUS | Locale::EnGB => words("now", "{} ago", "in {}", " ", [
+                two("second", "seconds"), two("minute", "minutes"), two("hour", "hours"), two("day", "days"),
+                two("week", "weeks"), two("month", "months"), two("year", "years"),
+            ]),
+            Locale::DeDE => words("jetzt", "vor {}", "in {}", " ", [
+                two("Sekunde", "Sekunden"), two("Minute", "Minuten"), two("Stunde", "Stunden"), two("Tag", "Tagen"),
+                two("Woche", "Wochen"), two("Monat", "Monaten"), two("Jahr", "Jahren"),
+            ]),
+            Locale::FrFR => words("maintenant", "il y a {}", "dans {}", " ", [
+                two("seconde", "secondes"), two("minute", "minutes"), two("heure", "heures"), two("jour", "jours"),
+                two("semaine", "semaines"), two("mois", "mois"), two("an", "ans"),
+            ]),
+            Locale::EsES => words("ahora", "hace {}", "dentro de {}", " ", [
+                two("segundo", "segundos"), two("minuto", "minutos"), two("hora", "horas"), two("día", "días"),
+                two("semana", "semanas"), two("mes", "meses"), two("año", "años"),
+            ]),
+            Locale::ItIT => words("ora", "{} fa", "tra {}", " ", [
+                two("secondo", "secondi"), two("minuto", "minuti"), two("ora", "ore"), two("giorno", "giorni"),
+                two("settimana", "settimane"), two("mese", "mesi"), two("anno", "anni"),
+            ]),
+            Locale::PtBR => words("agora", "há {}", "em {}", " ", [
+                two("segundo", "segundos"), two("minuto", "minutos"), two("hora", "horas"), two("dia", "dias"),
+                two("semana", "semanas"), two("mês", "meses"), two("ano", "anos"),
+            ]),
+            Locale::RuRU => words("сейчас", "{} назад", "через {}", " ", [
+                ["секунду", "секунды", "секунд", "секунды"],
+                ["минуту", "минуты", "минут", "минуты"],
+                ["час", "часа", "часов", "часа"],
+                ["день", "дня", "дней", "дня"],
+                ["неделю", "недели", "недель", "недели"],
+                ["месяц", "месяца", "месяцев", "месяца"],
+                ["год", "года", "лет", "года"],
+            ]),
+            Locale::JaJP => words("今", "{}前", "{}後", " ", [
+                two("秒", "秒"), two("分", "分"), two("時間", "時間"), two("日", "日"),
+                two("週間", "週間"), two("か月", "か月"), two("年", "年"),
+            ]),
+            Locale::KoKR => words("지금", "{} 전", "{} 후", "", [
+                two("초", "초"), two("분", "분"), two("시간", "시간"), two("일", "일"),
+                two("주", "주"), two("개월", "개월"), two("년", "년"),
+            ]),
+            Locale::ZhCN => words("现在", "{}前", "{}后", "", [
+                two("秒钟", "秒钟"), two("分钟", "分钟"), two("小时", "小时"), two("天", "天"),
+                two("周", "周"), two("个月", "个月"), two("年", "年"),
+            ]),
+            Locale::ZhTW => words("現在", "{}前", "{}後", " ", [
+                two("秒", "秒"), two("分鐘", "分鐘"), two("小時", "小時"), two("天", "天"),
+                two("週", "週"), two("個月", "個月"), two("年", "年"),
+            ]),
+        }
     }
 }
 
 /// Date/time formatting
-#[allow(dead_code)]
 pub struct DateTimeFormat {
     locale: Locale,
 }
@@ -426,29 +1322,69 @@ impl DateTimeFormat {
             .into()
     }
 
+    /// How long ago (or until) `timestamp`, in milliseconds since the epoch:
+    /// "5 minutes ago", "in 2 days"
     pub fn relative(&self, timestamp: f64) -> String {
-        let now = js_sys::Date::now();
-        let diff = (now - timestamp) / 1000.0; // seconds
+        self.relative_to(timestamp, now_ms())
+    }
 
-        match diff {
-            d if d < 60.0 => "just now".to_string(),
-            d if d < 3600.0 => format!("{} minutes ago", (d / 60.0) as i32),
-            d if d < 86400.0 => format!("{} hours ago", (d / 3600.0) as i32),
-            d if d < 604800.0 => format!("{} days ago", (d / 86400.0) as i32),
-            _ => self.date(timestamp),
-        }
+    /// `relative` measured from `now` instead of the clock
+    pub fn relative_to(&self, timestamp: f64, now: f64) -> String {
+        let seconds = (timestamp - now) / 1000.0;
+        let abs = seconds.abs();
+
+        let (unit, size) = match abs {
+            a if a < 45.0 => return self.locale.relative_time_words().now.to_string(),
+            a if a < 45.0 * 60.0 => (RelativeTimeUnit::Minute, 60.0),
+            a if a < 22.0 * 3600.0 => (RelativeTimeUnit::Hour, 3600.0),
+            a if a < 6.0 * 86400.0 => (RelativeTimeUnit::Day, 86400.0),
+            a if a < 28.0 * 86400.0 => (RelativeTimeUnit::Week, 7.0 * 86400.0),
+            a if a < 330.0 * 86400.0 => (RelativeTimeUnit::Month, 30.44 * 86400.0),
+            _ => (RelativeTimeUnit::Year, 365.25 * 86400.0),
+        };
+        let count = (abs / size).round().max(1.0);
+        self.relative_time(count.copysign(seconds), unit)
+    }
+
+    /// `value` units from now; negative is in the past: `(-3, Day)` is "3 days ago"
+    pub fn relative_time(&self, value: f64, unit: RelativeTimeUnit) -> String {
+        let words = self.locale.relative_time_words();
+        let count = value.abs();
+        let forms = words.units[unit as usize];
+        let name = match self.locale.plural_category(count) {
+            PluralCategory::One => forms[0],
+            PluralCategory::Few => forms[1],
+            PluralCategory::Many => forms[2],
+            _ => forms[3],
+        };
+
+        let amount = format!("{}{}{}", NumberFormat::new(self.locale).format(count), words.joiner, name);
+        let pattern = if value < 0.0 { words.past } else { words.future };
+        pattern.replace("{}", &amount)
+    }
+}
+
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
     }
 }
 
-/// Format hooks
+/// Format hooks, bound to the active locale
 pub fn use_number_format() -> NumberFormat {
-    let i18n = use_i18n();
-    NumberFormat::new(i18n.locale)
+    NumberFormat::new(current_locale())
 }
 
 pub fn use_date_format() -> DateTimeFormat {
-    let i18n = use_i18n();
-    DateTimeFormat::new(i18n.locale)
+    DateTimeFormat::new(current_locale())
 }
 
 /// Translation macros
@@ -532,3 +1468,215 @@ impl TranslationsBuilder {
         messages
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_cookie_beats_accept_language() {
+        let cookies = "session=abc; layer9-locale=ko-KR; theme=dark";
+        let accept = "fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5";
+
+        assert_eq!(detect_request_locale(Some(cookies), Some(accept)), Some(Locale::KoKR));
+        assert_eq!(detect_request_locale(Some("session=abc"), Some(accept)), Some(Locale::FrFR));
+        assert_eq!(detect_request_locale(None, Some("xx, tlh")), None);
+    }
+
+    #[test]
+    fn test_accept_language_is_ranked_by_quality() {
+        assert_eq!(
+            parse_accept_language("en;q=0.5, de-de;q=0.8, ja, en-US;q=0, pt_br;q=0.5"),
+            vec![Locale::JaJP, Locale::DeDE, Locale::EnUS, Locale::PtBR]
+        );
+        assert_eq!(Locale::from_tag("en-AU"), Some(Locale::EnUS));
+        assert_eq!(Locale::from_tag("ZH-tw"), Some(Locale::ZhTW));
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_icu_plural_follows_locale_rules() {
+        let todos = "{count, plural, =0 {No todos} one {# todo} other {# todos}}";
+        let format = |locale, count: &str| format_message(locale, todos, &args(&[("count", count)]));
+
+        assert_eq!(format(Locale::EnUS, "0"), "No todos");
+        assert_eq!(format(Locale::EnUS, "1"), "1 todo");
+        assert_eq!(format(Locale::EnUS, "3"), "3 todos");
+        assert_eq!(format(Locale::EnUS, "1.5"), "1.5 todos");
+        // French counts 0 and 1 alike
+        assert_eq!(Locale::FrFR.plural_category(0.0), PluralCategory::One);
+        assert_eq!(Locale::EnUS.plural_category(0.0), PluralCategory::Other);
+
+        let files = "{n, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+        let russian = |n: &str| format_message(Locale::RuRU, files, &args(&[("n", n)]));
+        assert_eq!(russian("1"), "1 файл");
+        assert_eq!(russian("3"), "3 файла");
+        assert_eq!(russian("11"), "11 файлов");
+        assert_eq!(russian("22"), "22 файла");
+        assert_eq!(russian("25"), "25 файлов");
+
+        let place = "{pos, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        let ordinal = |n: &str| format_message(Locale::EnUS, place, &args(&[("pos", n)]));
+        assert_eq!([ordinal("1"), ordinal("2"), ordinal("3"), ordinal("11"), ordinal("22")], ["1st", "2nd", "3rd", "11th", "22nd"]);
+    }
+
+    #[test]
+    fn test_icu_select_offset_and_quoting() {
+        let liked = "{gender, select, female {She} male {He} other {They}} and {count, plural, offset:1 =0 {nobody else} =1 {{name} alone} one {{name} and # other} other {{name} and # others}} liked '{this}'";
+        let format = |gender: &str, count: &str| {
+            format_message(Locale::EnUS, liked, &args(&[("gender", gender), ("count", count), ("name", "Ann")]))
+        };
+
+        assert_eq!(format("female", "1"), "She and Ann alone liked {this}");
+        assert_eq!(format("male", "2"), "He and Ann and 1 other liked {this}");
+        assert_eq!(format("unknown", "4"), "They and Ann and 3 others liked {this}");
+        assert_eq!(format_message(Locale::EnUS, "It''s {n}", &args(&[])), "It's {n}");
+
+        // Legacy `{{ name }}` placeholders still interpolate
+        assert_eq!(format_message(Locale::EnUS, "Hi {{ name }}", &args(&[("name", "Bo")])), "Hi Bo");
+    }
+
+    #[test]
+    fn test_numbers_and_currency_follow_locale() {
+        let en = NumberFormat::new(Locale::EnUS);
+        let de = NumberFormat::new(Locale::DeDE);
+
+        assert_eq!(en.format(1234567.891), "1,234,567.891");
+        assert_eq!(de.format(1234567.5), "1.234.567,5");
+        assert_eq!(NumberFormat::new(Locale::EsES).format(1234.0), "1234");
+        assert_eq!(NumberFormat::new(Locale::EsES).format(12345.0), "12.345");
+
+        assert_eq!(en.currency(1234.5, "USD"), "$1,234.50");
+        assert_eq!(en.currency(-0.5, "EUR"), "-€0.50");
+        assert_eq!(de.currency(1234.5, "EUR"), "1.234,50\u{a0}€");
+        assert_eq!(NumberFormat::new(Locale::KoKR).currency(1234.5, "KRW"), "₩1,235");
+        assert_eq!(NumberFormat::new(Locale::PtBR).currency(10.0, "BRL"), "R$\u{a0}10,00");
+        assert_eq!(en.currency(3.0, "CHF"), "CHF\u{a0}3.00");
+
+        assert_eq!(en.percent(0.125), "12.5%");
+        assert_eq!(NumberFormat::new(Locale::FrFR).percent(0.125), "12,5\u{a0}%");
+        assert_eq!(en.bytes(45312.0 * 1024.0), "44.3\u{a0}MB");
+        assert_eq!(de.bytes(512.0), "512\u{a0}B");
+    }
+
+    #[test]
+    fn test_relative_time_per_locale() {
+        let now = 1_700_000_000_000.0;
+        let minute = 60_000.0;
+        let day = 1440.0 * minute;
+        let en = DateTimeFormat::new(Locale::EnUS);
+
+        assert_eq!(en.relative_to(now - 10_000.0, now), "now");
+        assert_eq!(en.relative_to(now - 5.0 * minute, now), "5 minutes ago");
+        assert_eq!(en.relative_to(now - minute, now), "1 minute ago");
+        assert_eq!(en.relative_to(now + 2.0 * day, now), "in 2 days");
+        assert_eq!(en.relative_to(now - 14.0 * day, now), "2 weeks ago");
+        assert_eq!(en.relative_to(now - 400.0 * day, now), "1 year ago");
+
+        let ru = DateTimeFormat::new(Locale::RuRU);
+        assert_eq!(ru.relative_to(now - 5.0 * minute, now), "5 минут назад");
+        assert_eq!(ru.relative_to(now - 21.0 * minute, now), "21 минуту назад");
+        assert_eq!(ru.relative_to(now + 3.0 * day, now), "через 3 дня");
+
+        assert_eq!(DateTimeFormat::new(Locale::DeDE).relative_time(-2.0, RelativeTimeUnit::Day), "vor 2 Tagen");
+        assert_eq!(DateTimeFormat::new(Locale::KoKR).relative_time(-5.0, RelativeTimeUnit::Minute), "5분 전");
+        assert_eq!(DateTimeFormat::new(Locale::JaJP).relative_time(3.0, RelativeTimeUnit::Hour), "3 時間後");
+    }
+
+    #[test]
+    fn test_t_renders_in_request_locale_on_any_thread() {
+        init_i18n(
+            TranslationCatalog::new()
+                .add_locale(Locale::EnUS, TranslationsBuilder::en_us())
+                .add_locale(Locale::KoKR, TranslationsBuilder::ko_kr()),
+        );
+
+        // A worker thread that never called init_i18n, as under a server runtime
+        let rendered = std::thread::spawn(|| {
+            let korean = futures::executor::block_on(in_locale(Locale::KoKR, async { (use_i18n().t)("app.title") }));
+            (korean, (use_i18n().t)("app.title"))
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(rendered.0, "내 애플리케이션");
+        assert_eq!(rendered.1, "My Application");
+    }
+
+    #[test]
+    fn test_use_translation_follows_the_provider_locale() {
+        let catalog: HashMap<String, HashMap<String, String>> = HashMap::from([
+            (
+                "en-US".to_string(),
+                HashMap::from([
+                    ("greeting".to_string(), "Hello, {name}!".to_string()),
+                    ("farewell".to_string(), "Goodbye".to_string()),
+                    (
+                        "todos".to_string(),
+                        "{count, plural, one {# todo left} other {# todos left}}".to_string(),
+                    ),
+                ]),
+            ),
+            (
+                "ko-KR".to_string(),
+                HashMap::from([("greeting".to_string(), "안녕하세요, {name}님!".to_string())]),
+            ),
+        ]);
+        let provider = I18nProvider::new(Locale::EnUS, catalog);
+
+        let t = crate::reactive_v2::with_current_component(41, use_translation);
+        assert_eq!(t.t_with("greeting", &[("name", "Ada")]), "Hello, Ada!");
+        assert_eq!(t.t_plural("todos", 1), "1 todo left");
+        assert_eq!(t.t_plural("todos", 3), "3 todos left");
+
+        t.set_locale(Locale::KoKR);
+        assert_eq!(t.t_with("greeting", &[("name", "Ada")]), "안녕하세요, Ada님!");
+        assert_eq!(provider.translation().locale(), Locale::KoKR);
+        assert!(LOCALE_SUBSCRIBERS.with(|subs| subs.borrow().contains(&41)));
+
+        // Missing in Korean falls back to English, then to the key
+        assert_eq!(t.t("farewell"), "Goodbye");
+        assert_eq!(t.t("nav.home"), "nav.home");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_provider_keeps_switched_locale_and_unsubscribes_on_unmount() {
+        struct App {
+            component_id: Cell<Option<u32>>,
+        }
+
+        impl Component for App {
+            fn render(&self) -> Element {
+                let catalog = HashMap::from([
+                    ("en-US".to_string(), HashMap::from([("title".to_string(), "Settings".to_string())])),
+                    ("ko-KR".to_string(), HashMap::from([("title".to_string(), "설정".to_string())])),
+                ]);
+                let provider = I18nProvider::new(Locale::EnUS, catalog);
+                self.component_id.set(crate::reactive_v2::get_current_component());
+                let t = use_translation();
+                provider.children(vec![Element::Text(t.t("title"))]).render()
+            }
+        }
+
+        let app = App { component_id: Cell::new(None) };
+        let mut tree = crate::testing::render(&app);
+        assert!(tree.to_html().contains("Settings"));
+
+        // The parent builds a new provider every render, still in Korean
+        use_translation().set_locale(Locale::KoKR);
+        tree.rerender();
+        assert!(tree.to_html().contains("설정"));
+        tree.rerender();
+        assert!(tree.to_html().contains("lang=\"ko-KR\""));
+
+        let component_id = app.component_id.get().unwrap();
+        assert!(LOCALE_SUBSCRIBERS.with(|subs| subs.borrow().contains(&component_id)));
+        drop(tree);
+        assert!(!LOCALE_SUBSCRIBERS.with(|subs| subs.borrow().contains(&component_id)));
+    }
+}
@@ -8,6 +8,9 @@ use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use web_sys::{IntersectionObserver, IntersectionObserverEntry};
 
+/// Built-in image transform route served by the image handler
+const DEFAULT_IMAGE_ENDPOINT: &str = "/_layer9/image";
+
 /// Optimized image component
 pub struct Image {
     src: String,
@@ -17,6 +20,8 @@ pub struct Image {
     loading: ImageLoading,
     sizes: Option<String>,
     srcset: Option<String>,
+    widths: Option<Vec<u32>>,
+    endpoint: String,
     placeholder: Option<ImagePlaceholder>,
     priority: bool,
     quality: u8,
@@ -47,6 +52,8 @@ impl Image {
             loading: ImageLoading::Lazy,
             sizes: None,
             srcset: None,
+            widths: None,
+            endpoint: DEFAULT_IMAGE_ENDPOINT.to_string(),
             placeholder: None,
             priority: false,
             quality: 75,
@@ -85,6 +92,25 @@ impl Image {
         self
     }
 
+    /// Generate `srcset` from transform URLs at each width
+    ///
+    /// Widths are sorted and deduped, and widths of 0 are skipped. If no
+    /// usable width is left the default set is kept. Unless `sizes` is set,
+    /// the image is assumed to fill the viewport up to the largest width.
+    pub fn responsive_widths(mut self, widths: &[u32]) -> Self {
+        let mut widths: Vec<u32> = widths.iter().copied().filter(|&w| w > 0).collect();
+        widths.sort_unstable();
+        widths.dedup();
+        self.widths = (!widths.is_empty()).then_some(widths);
+        self
+    }
+
+    /// Transform endpoint for generated URLs (defaults to `/_layer9/image`)
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
     pub fn placeholder(mut self, placeholder: ImagePlaceholder) -> Self {
         self.placeholder = Some(placeholder);
         self
@@ -117,7 +143,10 @@ impl Image {
         }
 
         // Generate responsive srcset with Layer9 optimization
-        let widths = [640, 750, 828, 1080, 1200, 1920, 2048, 3840];
+        let widths: &[u32] = match &self.widths {
+            Some(widths) => widths,
+            None => &[640, 750, 828, 1080, 1200, 1920, 2048, 3840],
+        };
 
         widths
             .iter()
@@ -126,6 +155,15 @@ impl Image {
             .join(", ")
     }
 
+    fn effective_sizes(&self) -> Option<String> {
+        if self.sizes.is_some() {
+            return self.sizes.clone();
+        }
+
+        let largest = self.widths.as_ref()?.last()?;
+        Some(format!("(max-width: {0}px) 100vw, {0}px", largest))
+    }
+
     fn optimize_url(&self, url: &str) -> String {
         // Use Layer9 image optimization endpoint
         if self.width.is_some() || self.height.is_some() || self.quality != 75 {
@@ -140,7 +178,8 @@ impl Image {
             params.push(format!("q={}", self.quality));
             
             format!(
-                "/_layer9/image?src={}&{}",
+                "{}?src={}&{}",
+                self.endpoint,
                 urlencoding::encode(url),
                 params.join("&")
             )
@@ -150,12 +189,11 @@ impl Image {
     }
 
     fn optimize_url_with_width(&self, url: &str, width: u32) -> String {
-        format!(
-            "/_layer9/image?src={}&w={}&q={}",
-            urlencoding::encode(url),
-            width,
-            self.quality
-        )
+        let mut url = format!("{}?src={}&w={}", self.endpoint, urlencoding::encode(url), width);
+        if self.quality != 75 {
+            url.push_str(&format!("&q={}", self.quality));
+        }
+        url
     }
 }
 
@@ -176,8 +214,8 @@ impl Component for Image {
             attrs.push(("height".to_string(), height.to_string()));
         }
 
-        if let Some(sizes) = &self.sizes {
-            attrs.push(("sizes".to_string(), sizes.clone()));
+        if let Some(sizes) = self.effective_sizes() {
+            attrs.push(("sizes".to_string(), sizes));
         }
 
         // Generate srcset for responsive images
@@ -226,7 +264,7 @@ impl Component for Image {
                 ImagePlaceholder::Blur(blur_url) => {
                     Element::Node {
                         tag: "img".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             attributes: vec![
                                 ("src".to_string(), blur_url.clone()),
                                 ("alt".to_string(), "".to_string()),
@@ -234,34 +272,34 @@ impl Component for Image {
                                 ("style".to_string(), "position: absolute; inset: 0; filter: blur(20px); transform: scale(1.05);".to_string()),
                             ],
                             ..Default::default()
-                        },
+                        }),
                         children: vec![],
                     }
                 }
                 ImagePlaceholder::Color(color) => {
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             attributes: vec![
                                 ("style".to_string(), format!("position: absolute; inset: 0; background-color: {}", color)),
                                 ("aria-hidden".to_string(), "true".to_string()),
                             ],
                             ..Default::default()
-                        },
+                        }),
                         children: vec![],
                     }
                 }
                 ImagePlaceholder::Shimmer => {
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("shimmer".to_string()),
                             attributes: vec![
                                 ("style".to_string(), "position: absolute; inset: 0;".to_string()),
                                 ("aria-hidden".to_string(), "true".to_string()),
                             ],
                             ..Default::default()
-                        },
+                        }),
                         children: vec![],
                     }
                 }
@@ -269,7 +307,7 @@ impl Component for Image {
         } else {
             Element::Node {
                 tag: "div".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![],
             }
         };
@@ -288,16 +326,16 @@ impl Component for Image {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("image-container".to_string()),
                 attributes: vec![("style".to_string(), container_style.to_string())],
                 ..Default::default()
-            },
+            }),
             children: vec![
                 placeholder_element,
                 Element::Node {
                     tag: "img".to_string(),
-                    props: img_props,
+                    props: Box::new(img_props),
                     children: vec![],
                 },
             ],
@@ -353,7 +391,7 @@ impl Component for Picture {
     fn render(&self) -> Element {
         Element::Node {
             tag: "picture".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: {
                 let mut children: Vec<Element> = self
                     .sources
@@ -370,10 +408,10 @@ impl Component for Picture {
 
                         Element::Node {
                             tag: "source".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 attributes: attrs,
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         }
                     })
@@ -460,14 +498,14 @@ impl Component for BackgroundImage {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 id: Some(container_id),
                 attributes: vec![
                     ("style".to_string(), style),
                     ("class".to_string(), "background-image".to_string()),
                 ],
                 ..Default::default()
-            },
+            }),
             children: self.children.clone(),
         }
     }
@@ -487,3 +525,42 @@ pub fn preload_image(src: &str) {
 }
 
 // Re-exports
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_responsive_widths_srcset() {
+        let image = Image::new("/images/gallery/sunset.jpg").responsive_widths(&[1200, 400, 800, 400]);
+
+        assert_eq!(
+            image.generate_srcset(),
+            "/_layer9/image?src=%2Fimages%2Fgallery%2Fsunset.jpg&w=400 400w, \
+             /_layer9/image?src=%2Fimages%2Fgallery%2Fsunset.jpg&w=800 800w, \
+             /_layer9/image?src=%2Fimages%2Fgallery%2Fsunset.jpg&w=1200 1200w"
+        );
+        assert_eq!(image.effective_sizes().as_deref(), Some("(max-width: 1200px) 100vw, 1200px"));
+
+        let custom = Image::new("a.png")
+            .responsive_widths(&[320])
+            .endpoint("/cdn/image")
+            .quality(60)
+            .sizes("50vw");
+        assert_eq!(custom.generate_srcset(), "/cdn/image?src=a.png&w=320&q=60 320w");
+        assert_eq!(custom.effective_sizes().as_deref(), Some("50vw"));
+    }
+
+    #[test]
+    fn test_responsive_widths_skips_zero() {
+        let image = Image::new("a.png").responsive_widths(&[0, 400]);
+        assert_eq!(image.generate_srcset(), "/_layer9/image?src=a.png&w=400 400w");
+
+        let defaults = Image::new("a.png").generate_srcset();
+        for widths in [&[0][..], &[]] {
+            let image = Image::new("a.png").responsive_widths(widths);
+            assert_eq!(image.generate_srcset(), defaults);
+            assert_eq!(image.effective_sizes(), None);
+        }
+    }
+}
@@ -2,8 +2,9 @@
 
 use crate::component::{use_state, Component};
 use crate::prelude::*;
-use crate::hooks::{use_effect, use_ref};
+use crate::hooks::{use_effect, use_persisted, use_ref};
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{
@@ -38,10 +39,13 @@ impl Default for LazyConfig {
 /// Global lazy loading manager
 pub struct LazyLoadManager {
     observer: Option<IntersectionObserver>,
-    #[allow(dead_code)]
     config: LazyConfig,
+    /// Elements currently observed; the observer is disconnected once none are left
+    observed: Vec<DomElement>,
 }
 
+static NEXT_LAZY_ID: AtomicUsize = AtomicUsize::new(0);
+
 thread_local! {
     static LAZY_MANAGER: RefCell<Option<LazyLoadManager>> = const { RefCell::new(None) };
 }
@@ -52,74 +56,151 @@ impl LazyLoadManager {
         LAZY_MANAGER.with(|manager| {
             let mut manager = manager.borrow_mut();
             if manager.is_none() {
-                *manager = Some(LazyLoadManager::new(config));
+                *manager = Some(LazyLoadManager {
+                    observer: None,
+                    config,
+                    observed: Vec::new(),
+                });
             }
         });
     }
 
-    fn new(config: LazyConfig) -> Self {
-        let observer = create_intersection_observer(&config);
-        Self {
-            observer,
-            config,
+    /// Observe an element for lazy loading.
+    ///
+    /// Elements already within the viewport (plus root margin) are loaded
+    /// straight away instead of waiting for the first observer callback.
+    pub fn observe(element: &DomElement) {
+        Self::init(LazyConfig::default());
+
+        let config = LAZY_MANAGER.with(|manager| manager.borrow().as_ref().map(|m| m.config.clone()));
+        let Some(config) = config else {
+            return;
+        };
+
+        if is_near_viewport(element, margin_px(&config.root_margin)) {
+            reveal(element);
+            if config.unobserve_on_load {
+                return;
+            }
         }
-    }
 
-    /// Observe an element for lazy loading
-    pub fn observe(element: &DomElement) {
         LAZY_MANAGER.with(|manager| {
-            if let Some(ref manager) = *manager.borrow() {
-                if let Some(ref observer) = manager.observer {
-                    observer.observe(element);
+            if let Some(manager) = manager.borrow_mut().as_mut() {
+                if manager.observer.is_none() {
+                    manager.observer = create_intersection_observer(&manager.config);
+                }
+                if let Some(observer) = &manager.observer {
+                    if !manager.observed.contains(element) {
+                        observer.observe(element);
+                        manager.observed.push(element.clone());
+                    }
                 }
             }
         });
     }
 
-    /// Unobserve an element
+    /// Unobserve an element, disconnecting the observer once nothing is left
+    /// to watch. Elements that aren't observed, e.g. unobserved on load and
+    /// again on unmount, are ignored.
     pub fn unobserve(element: &DomElement) {
         LAZY_MANAGER.with(|manager| {
-            if let Some(ref manager) = *manager.borrow() {
-                if let Some(ref observer) = manager.observer {
+            if let Some(manager) = manager.borrow_mut().as_mut() {
+                let Some(index) = manager.observed.iter().position(|observed| observed == element) else {
+                    return;
+                };
+                manager.observed.swap_remove(index);
+                if let Some(observer) = &manager.observer {
                     observer.unobserve(element);
+                    if manager.observed.is_empty() {
+                        observer.disconnect();
+                        manager.observer = None;
+                    }
                 }
             }
         });
     }
+
+    /// Stop observing every element
+    pub fn disconnect() {
+        LAZY_MANAGER.with(|manager| {
+            if let Some(manager) = manager.borrow_mut().as_mut() {
+                if let Some(observer) = manager.observer.take() {
+                    observer.disconnect();
+                }
+                manager.observed.clear();
+            }
+        });
+    }
+
+    fn unobserve_on_load() -> bool {
+        LAZY_MANAGER.with(|manager| {
+            manager
+                .borrow()
+                .as_ref()
+                .map(|m| m.config.unobserve_on_load)
+                .unwrap_or(true)
+        })
+    }
+}
+
+/// Swap `data-src`/`data-srcset` into place so the browser starts fetching
+fn reveal(element: &DomElement) {
+    if let Some(src) = element.get_attribute("data-src") {
+        element.set_attribute("src", &src).ok();
+        element.remove_attribute("data-src").ok();
+    }
+    if let Some(srcset) = element.get_attribute("data-srcset") {
+        element.set_attribute("srcset", &srcset).ok();
+        element.remove_attribute("data-srcset").ok();
+    }
+
+    let class_list = element.class_list();
+    class_list.remove_1("loading").ok();
+    class_list.add_1("loaded").ok();
+}
+
+/// Handle an observer entry for `target` that became visible
+fn handle_intersection(target: &DomElement) {
+    reveal(target);
+    if LazyLoadManager::unobserve_on_load() {
+        LazyLoadManager::unobserve(target);
+    }
+}
+
+/// Leading pixel value of a CSS margin such as `"50px"` or `"200px 0px"`
+fn margin_px(root_margin: &str) -> f64 {
+    root_margin
+        .split_whitespace()
+        .next()
+        .and_then(|value| value.trim_end_matches("px").parse().ok())
+        .unwrap_or(0.0)
+}
+
+fn is_near_viewport(element: &DomElement, margin: f64) -> bool {
+    let Some(window) = web_sys::window() else {
+        return false;
+    };
+    // Detached elements report an empty rect at the origin
+    if !element.is_connected() {
+        return false;
+    }
+
+    let height = window.inner_height().ok().and_then(|h| h.as_f64()).unwrap_or(0.0);
+    let width = window.inner_width().ok().and_then(|w| w.as_f64()).unwrap_or(0.0);
+    let rect = element.get_bounding_client_rect();
+
+    rect.bottom() >= -margin
+        && rect.top() <= height + margin
+        && rect.right() >= -margin
+        && rect.left() <= width + margin
 }
 
 /// Create intersection observer for lazy loading
 fn create_intersection_observer(config: &LazyConfig) -> Option<IntersectionObserver> {
-    let unobserve_on_load = config.unobserve_on_load;
-    let callback = Closure::wrap(Box::new(move |entries: Vec<IntersectionObserverEntry>, observer: IntersectionObserver| {
+    let callback = Closure::wrap(Box::new(move |entries: Vec<IntersectionObserverEntry>, _observer: IntersectionObserver| {
         for entry in entries {
             if entry.is_intersecting() {
-                let target = entry.target();
-                
-                // Load image
-                if let Ok(img) = target.dyn_into::<HtmlImageElement>() {
-                    // Get data-src attribute
-                    if let Some(src) = img.get_attribute("data-src") {
-                        img.set_src(&src);
-                        img.remove_attribute("data-src").ok();
-                        
-                        // Add loaded class
-                        let class_list = img.class_list();
-                        class_list.remove_1("loading").ok();
-                        class_list.add_1("loaded").ok();
-                    }
-                    
-                    // Get data-srcset attribute
-                    if let Some(srcset) = img.get_attribute("data-srcset") {
-                        img.set_attribute("srcset", &srcset).ok();
-                        img.remove_attribute("data-srcset").ok();
-                    }
-                    
-                    // Unobserve if configured
-                    if unobserve_on_load {
-                        observer.unobserve(&img);
-                    }
-                }
+                handle_intersection(&entry.target());
             }
         }
     }) as Box<dyn FnMut(Vec<IntersectionObserverEntry>, IntersectionObserver)>);
@@ -251,78 +332,66 @@ impl Component for LazyImage {
     fn render(&self) -> Element {
         let loaded = use_state(|| false);
         let img_ref = use_ref::<Option<DomElement>>(None);
-        
-        // Set up lazy loading
+        let id = use_persisted(|| format!("layer9-lazy-{}", NEXT_LAZY_ID.fetch_add(1, Ordering::Relaxed)))
+            .borrow()
+            .clone();
+
         let loaded_clone = loaded.clone();
         let img_ref_clone = img_ref.clone();
-        let _src_clone = self.src.clone();
-        
-        // Note: Since we can't directly return different closure types,
-        // we'll handle cleanup differently
+        let element_id = id.clone();
         let cleanup_ref = img_ref.clone();
-        
+
         use_effect((), move || {
-            // Initialize lazy manager
-            LazyLoadManager::init(LazyConfig::default());
-            
             if let Some(window) = web_sys::window() {
                 if let Some(document) = window.document() {
-                    // Use a unique ID to find our element
-                    let id = format!("lazy-img-{}", js_sys::Math::random());
-                    
-                    // Set up a timeout to find and observe the element
+                    // The element is attached after this render; look it up on the next tick
                     let closure = Closure::wrap(Box::new(move || {
-                        if let Some(element) = document.get_element_by_id(&id) {
+                        if let Some(element) = document.get_element_by_id(&element_id) {
                             *img_ref_clone.borrow_mut() = Some(element.clone());
-                            
-                            // Set up load handler
+
                             let loaded_inner = loaded_clone.clone();
                             let load_handler = Closure::wrap(Box::new(move || {
                                 loaded_inner.set(true);
                             }) as Box<dyn Fn()>);
-                            
+
                             if let Some(img) = element.dyn_ref::<HtmlImageElement>() {
                                 img.set_onload(Some(load_handler.as_ref().unchecked_ref()));
                                 load_handler.forget();
-                                
-                                // Observe for lazy loading
-                                LazyLoadManager::observe(&element.clone());
                             }
+
+                            LazyLoadManager::observe(&element);
                         }
                     }) as Box<dyn Fn()>);
-                    
+
                     window.set_timeout_with_callback_and_timeout_and_arguments_0(
                         closure.as_ref().unchecked_ref(),
                         0,
                     ).ok();
-                    
+
                     closure.forget();
                 }
             }
-            
-            // Cleanup function
+
             move || {
                 if let Some(element) = cleanup_ref.borrow().as_ref() {
                     LazyLoadManager::unobserve(element);
                 }
             }
         });
-        
-        // Build attributes
+
         let mut attrs = vec![
             ("alt".to_string(), self.alt.clone()),
-            ("id".to_string(), format!("lazy-img-{}", js_sys::Math::random())),
+            ("id".to_string(), id),
         ];
-        
-        // Add dimensions
+
         if let Some(width) = self.width {
             attrs.push(("width".to_string(), width.to_string()));
         }
         if let Some(height) = self.height {
             attrs.push(("height".to_string(), height.to_string()));
         }
-        
-        // Handle src/data-src based on loaded state
+
+        // No `src` until the image nears the viewport, so nothing is requested early
         if loaded.get() {
             attrs.push(("src".to_string(), self.src.clone()));
             if let Some(ref srcset) = self.srcset {
@@ -330,25 +399,24 @@ impl Component for LazyImage {
             }
         } else {
             attrs.push(("data-src".to_string(), self.src.clone()));
-            if let Some(ref placeholder) = self.placeholder {
-                attrs.push(("src".to_string(), placeholder.clone()));
-            } else {
-                attrs.push(("src".to_string(), "data:image/svg+xml,%3Csvg%20xmlns='http://www.w3.org/2000/svg'%20width='1'%20height='1'%3E%3C/svg%3E".to_string()));
-            }
             if let Some(ref srcset) = self.srcset {
                 attrs.push(("data-srcset".to_string(), srcset.clone()));
             }
+            if let Some(ref placeholder) = self.placeholder {
+                attrs.push((
+                    "style".to_string(),
+                    format!(
+                        "background-image: url('{}'); background-size: cover; filter: blur(8px)",
+                        placeholder
+                    ),
+                ));
+            }
         }
-        
-        // Add sizes
+
         if let Some(ref sizes) = self.sizes {
             attrs.push(("sizes".to_string(), sizes.clone()));
         }
-        
-        // Add loading attribute
-        attrs.push(("loading".to_string(), "lazy".to_string()));
-        
-        // Build class
+
         let mut classes = vec![];
         if let Some(ref class) = self.class {
             classes.push(class.clone());
@@ -357,11 +425,11 @@ impl Component for LazyImage {
         
         Element::Node {
             tag: "img".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(classes.join(" ")),
                 attributes: attrs,
                 ..Default::default()
-            },
+            }),
             children: vec![],
         }
     }
@@ -378,4 +446,95 @@ mod tests {
         assert_eq!(config.threshold, 0.01);
         assert!(config.unobserve_on_load);
     }
-}
\ No newline at end of file
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_image_keeps_its_id_across_renders() {
+        fn id(tree: &crate::testing::TestTree) -> String {
+            match tree.find("img") {
+                Some(Element::Node { props, .. }) => props
+                    .attributes
+                    .iter()
+                    .find(|(name, _)| name == "id")
+                    .map(|(_, value)| value.clone())
+                    .unwrap(),
+                _ => panic!("no img"),
+            }
+        }
+
+        let image = LazyImage::new("/photo.jpg");
+        let mut tree = crate::testing::render(&image);
+        let first = id(&tree);
+        let next = NEXT_LAZY_ID.load(Ordering::Relaxed);
+
+        tree.rerender();
+        tree.rerender();
+        assert_eq!(id(&tree), first);
+        assert_eq!(NEXT_LAZY_ID.load(Ordering::Relaxed), next);
+    }
+}
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn lazy_img(top: &str) -> DomElement {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let img = document.create_element("img").unwrap();
+        img.set_attribute("data-src", "/photo.jpg").unwrap();
+        img.set_attribute("data-srcset", "/photo-400.jpg 400w").unwrap();
+        img.set_attribute("style", &format!("position: absolute; top: {}", top)).unwrap();
+        img.class_list().add_1("loading").unwrap();
+        document.body().unwrap().append_child(&img).unwrap();
+        img
+    }
+
+    #[wasm_bindgen_test]
+    fn test_below_fold_image_waits_for_intersection() {
+        let img = lazy_img("5000px");
+        LazyLoadManager::observe(&img);
+        assert!(img.get_attribute("src").is_none());
+
+        // Simulate the observer reporting the image scrolled into view
+        handle_intersection(&img);
+        assert_eq!(img.get_attribute("src").as_deref(), Some("/photo.jpg"));
+        assert_eq!(img.get_attribute("srcset").as_deref(), Some("/photo-400.jpg 400w"));
+        assert!(img.class_list().contains("loaded"));
+        img.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_unobserving_twice_keeps_watching_the_others() {
+        let first = lazy_img("5000px");
+        let second = lazy_img("6000px");
+        LazyLoadManager::observe(&first);
+        LazyLoadManager::observe(&second);
+
+        // Loaded, then unmounted: the second unobserve is a no-op
+        handle_intersection(&first);
+        LazyLoadManager::unobserve(&first);
+        let watching = LAZY_MANAGER.with(|manager| {
+            let manager = manager.borrow();
+            let manager = manager.as_ref().unwrap();
+            (manager.observer.is_some(), manager.observed.contains(&second))
+        });
+        assert_eq!(watching, (true, true));
+
+        LazyLoadManager::unobserve(&second);
+        assert!(LAZY_MANAGER.with(|manager| manager.borrow().as_ref().unwrap().observer.is_none()));
+        first.remove();
+        second.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_image_in_viewport_loads_immediately() {
+        let img = lazy_img("0px");
+        LazyLoadManager::observe(&img);
+        assert_eq!(img.get_attribute("src").as_deref(), Some("/photo.jpg"));
+        assert!(img.get_attribute("data-src").is_none());
+        img.remove();
+    }
+}
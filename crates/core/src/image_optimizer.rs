@@ -0,0 +1,341 @@
+//! Image optimization middleware for `/_layer9/image`
+//!
+//! Resizes and re-encodes images on request, e.g.
+//! `/_layer9/image?src=/images/hero.jpg&w=800&q=75&f=webp`. Results are cached
+//! on disk keyed by the parameters and the source's modification time, so an
+//! edited source is re-encoded rather than served stale, and sent with a
+//! year-long immutable `Cache-Control`.
+
+use crate::image_transform::{calculate_dimensions, Format};
+use crate::middleware_v2::{Context, Middleware, MiddlewareError, Next, Response};
+use async_trait::async_trait;
+use image::{DynamicImage, ImageFormat};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::{Component as PathComponent, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Route the optimizer answers
+const IMAGE_ROUTE: &str = "/_layer9/image";
+
+/// Parsed and validated `/_layer9/image` query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRequest {
+    pub src: String,
+    pub width: Option<u32>,
+    pub quality: u8,
+    pub format: Option<Format>,
+}
+
+impl ImageRequest {
+    fn parse(query: &str, max_width: u32) -> Result<Self, MiddlewareError> {
+        let mut request = ImageRequest {
+            src: String::new(),
+            width: None,
+            quality: 75,
+            format: None,
+        };
+
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = urlencoding::decode(value)
+                .map_err(|_| bad_request(format!("Invalid {} parameter", key)))?;
+            match key {
+                "src" => request.src = value.into_owned(),
+                "w" => match value.parse::<u32>() {
+                    Ok(w) if (1..=max_width).contains(&w) => request.width = Some(w),
+                    _ => {
+                        return Err(bad_request(format!(
+                            "Width must be between 1 and {}",
+                            max_width
+                        )))
+                    }
+                },
+                "q" => match value.parse::<u8>() {
+                    Ok(q) if (1..=100).contains(&q) => request.quality = q,
+                    _ => return Err(bad_request("Quality must be between 1 and 100")),
+                },
+                "f" => match value.as_ref() {
+                    "webp" => request.format = Some(Format::WebP),
+                    "avif" => request.format = Some(Format::Avif),
+                    "jpeg" | "jpg" => request.format = Some(Format::Jpeg),
+                    other => return Err(bad_request(format!("Unsupported format '{}'", other))),
+                },
+                _ => {}
+            }
+        }
+
+        if request.src.is_empty() {
+            return Err(bad_request("Missing src parameter"));
+        }
+        Ok(request)
+    }
+
+    /// Key of the output for a source last modified at `modified`
+    fn cache_key(&self, modified: SystemTime) -> String {
+        let modified = modified.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let key = format!(
+            "{}|m={}|w={:?}|q={}|f={:?}",
+            self.src, modified, self.width, self.quality, self.format
+        );
+        Sha256::digest(key.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Middleware serving resized, re-encoded images from a source directory
+#[derive(Clone)]
+pub struct ImageOptimizer {
+    source_dir: PathBuf,
+    cache_dir: PathBuf,
+    max_width: u32,
+}
+
+impl ImageOptimizer {
+    /// Serve images under `source_dir`, caching output in `cache_dir`
+    pub fn new(source_dir: impl Into<PathBuf>, cache_dir: impl Into<PathBuf>) -> Self {
+        ImageOptimizer {
+            source_dir: source_dir.into(),
+            cache_dir: cache_dir.into(),
+            max_width: 3840,
+        }
+    }
+
+    /// Largest width a request may ask for
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Produce the response for a validated request. Blocks on the disk
+    /// and the encoder; `handle` runs it on the blocking pool.
+    pub fn optimize(&self, request: &ImageRequest) -> Result<Response, MiddlewareError> {
+        let not_found = || MiddlewareError {
+            status: 404,
+            message: format!("Image not found: {}", request.src),
+        };
+
+        // The source decides: gone means 404 whatever is cached, and a newer
+        // one has a key of its own
+        let source = self.source_path(&request.src)?;
+        let metadata = std::fs::metadata(&source).map_err(|_| not_found())?;
+        if !metadata.is_file() {
+            return Err(not_found());
+        }
+        let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+
+        let cached = self.cache_dir.join(request.cache_key(modified));
+        if let Ok(data) = std::fs::read(&cached) {
+            if let Some(format) = sniff_format(&data) {
+                return Ok(image_response(data, format));
+            }
+        }
+
+        let data = std::fs::read(&source).map_err(|_| not_found())?;
+        let source_format = sniff_format(&data).ok_or_else(|| bad_request("Source is not an image"))?;
+
+        let mut img = image::load_from_memory(&data).map_err(|e| MiddlewareError {
+            status: 422,
+            message: e.to_string(),
+        })?;
+        if let Some(width) = request.width {
+            let (w, h) = calculate_dimensions(img.width(), img.height(), Some(width), None);
+            img = img.resize_exact(w, h.max(1), image::imageops::FilterType::Lanczos3);
+        }
+
+        // Fall back to the source's own format if the requested encoder fails
+        let target = request.format.unwrap_or(source_format);
+        let (output, format) = match encode(&img, target, request.quality) {
+            Ok(output) => (output, target),
+            Err(_) => (
+                encode(&img, source_format, request.quality).map_err(|e| MiddlewareError {
+                    status: 500,
+                    message: e,
+                })?,
+                source_format,
+            ),
+        };
+
+        // A failed cache write only costs a re-encode next time
+        if std::fs::create_dir_all(&self.cache_dir).is_ok() {
+            let _ = std::fs::write(&cached, &output);
+        }
+
+        Ok(image_response(output, format))
+    }
+
+    fn source_path(&self, src: &str) -> Result<PathBuf, MiddlewareError> {
+        let relative = Path::new(src.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|c| !matches!(c, PathComponent::Normal(_)))
+        {
+            return Err(bad_request("Invalid src path"));
+        }
+        Ok(self.source_dir.join(relative))
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for ImageOptimizer {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        let (path, query) = ctx
+            .request
+            .url
+            .split_once('?')
+            .unwrap_or((ctx.request.url.as_str(), ""));
+        if path != IMAGE_ROUTE {
+            return next(ctx).await;
+        }
+
+        let request = ImageRequest::parse(query, self.max_width)?;
+        let optimizer = self.clone();
+        tokio::task::spawn_blocking(move || optimizer.optimize(&request))
+            .await
+            .map_err(|e| MiddlewareError {
+                status: 500,
+                message: e.to_string(),
+            })?
+    }
+}
+
+fn bad_request(message: impl Into<String>) -> MiddlewareError {
+    MiddlewareError {
+        status: 400,
+        message: message.into(),
+    }
+}
+
+fn image_response(data: Vec<u8>, format: Format) -> Response {
+    Response::new()
+        .with_header("Content-Type", format.to_mime_type())
+        .with_header("Cache-Control", "public, max-age=31536000, immutable")
+        .with_bytes(data)
+}
+
+fn sniff_format(data: &[u8]) -> Option<Format> {
+    match image::guess_format(data).ok()? {
+        ImageFormat::Jpeg => Some(Format::Jpeg),
+        ImageFormat::Png => Some(Format::Png),
+        ImageFormat::WebP => Some(Format::WebP),
+        ImageFormat::Avif => Some(Format::Avif),
+        ImageFormat::Gif => Some(Format::Gif),
+        _ => None,
+    }
+}
+
+fn encode(img: &DynamicImage, format: Format, quality: u8) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    match format {
+        Format::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+            let encoder =
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, quality);
+            rgb.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+        }
+        Format::WebP => {
+            let encoder = webp::Encoder::from_image(img).map_err(|e| e.to_string())?;
+            output.extend_from_slice(&encoder.encode(quality as f32));
+        }
+        Format::Avif => {
+            let encoder =
+                image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut output, 8, quality);
+            img.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+        }
+        Format::Png => img
+            .write_to(&mut Cursor::new(&mut output), ImageFormat::Png)
+            .map_err(|e| e.to_string())?,
+        Format::Gif => img
+            .write_to(&mut Cursor::new(&mut output), ImageFormat::Gif)
+            .map_err(|e| e.to_string())?,
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware_v2::{next_fn, test_context};
+    use std::time::Duration;
+
+    async fn request(optimizer: &ImageOptimizer, url: &str) -> Result<Response, MiddlewareError> {
+        let next = next_fn(|_| Box::pin(async { Ok(Response::new().with_status(404)) }));
+        optimizer.handle(&mut test_context(url), next).await
+    }
+
+    async fn status(optimizer: &ImageOptimizer, url: &str) -> Option<u16> {
+        request(optimizer, url).await.err().map(|e| e.status)
+    }
+
+    fn optimizer(name: &str) -> ImageOptimizer {
+        let root = std::env::temp_dir().join(format!("layer9-{}-{}", name, std::process::id()));
+        let images = root.join("images");
+        std::fs::create_dir_all(&images).unwrap();
+        image::RgbaImage::from_pixel(200, 100, image::Rgba([200, 80, 40, 255]))
+            .save(images.join("photo.png"))
+            .unwrap();
+        ImageOptimizer::new(&root, root.join("cache"))
+    }
+
+    fn dimensions(response: Response) -> (u32, u32) {
+        let img = image::load_from_memory(&response.bytes.unwrap()).unwrap();
+        (img.width(), img.height())
+    }
+
+    #[tokio::test]
+    async fn test_resize_and_reencode() {
+        let optimizer = optimizer("resize");
+        let response = request(&optimizer, "/_layer9/image?src=%2Fimages%2Fphoto.png&w=50&f=jpeg").await.unwrap();
+
+        assert_eq!(response.headers["Content-Type"], "image/jpeg");
+        assert!(response.headers["Cache-Control"].contains("immutable"));
+        assert_eq!(dimensions(response), (50, 25));
+
+        // Served from the disk cache the second time
+        let cached = request(&optimizer, "/_layer9/image?src=%2Fimages%2Fphoto.png&w=50&f=jpeg").await.unwrap();
+        assert_eq!(cached.headers["Content-Type"], "image/jpeg");
+        assert_eq!(std::fs::read_dir(&optimizer.cache_dir).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_edited_or_removed_source_is_not_served_from_cache() {
+        let optimizer = optimizer("edited");
+        let url = "/_layer9/image?src=/images/photo.png&w=50";
+        assert_eq!(dimensions(request(&optimizer, url).await.unwrap()), (50, 25));
+
+        // A square photo in its place, modified later than the first
+        let photo = optimizer.source_dir.join("images").join("photo.png");
+        image::RgbaImage::from_pixel(100, 100, image::Rgba([40, 80, 200, 255]))
+            .save(&photo)
+            .unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&photo)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(dimensions(request(&optimizer, url).await.unwrap()), (50, 50));
+
+        std::fs::remove_file(&photo).unwrap();
+        assert_eq!(status(&optimizer, url).await, Some(404));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_params_and_missing_sources() {
+        let optimizer = optimizer("errors");
+
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images/photo.png&w=0").await, Some(400));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images/photo.png&w=99999").await, Some(400));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images/photo.png&q=0").await, Some(400));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images/photo.png&f=bmp").await, Some(400));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/../secret.png").await, Some(400));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images/missing.png").await, Some(404));
+        assert_eq!(status(&optimizer, "/_layer9/image?src=/images").await, Some(404));
+
+        // Other routes pass through
+        assert_eq!(request(&optimizer, "/api/users").await.unwrap().status, 404);
+    }
+}
@@ -312,7 +312,7 @@ impl ImageTransformer {
 }
 
 /// Calculate target dimensions preserving aspect ratio
-fn calculate_dimensions(
+pub(crate) fn calculate_dimensions(
     orig_width: u32,
     orig_height: u32,
     target_width: Option<u32>,
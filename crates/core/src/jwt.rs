@@ -74,8 +74,7 @@ impl Jwt {
             .map_err(|e| format!("Failed to parse claims: {}", e))?;
 
         // Check expiration
-        let now = js_sys::Date::now() as u64 / 1000;
-        if claims.exp < now {
+        if claims.exp < now_secs() {
             return Err("Token expired".to_string());
         }
 
@@ -91,6 +90,31 @@ impl Jwt {
     }
 }
 
+/// `exp` claim of a token, read without checking its signature.
+///
+/// For deciding when to refresh a token; use `Jwt::verify_token` to trust one.
+pub fn token_expiry(token: &str) -> Option<u64> {
+    let claims = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(claims).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp")?.as_u64()
+}
+
+/// Current Unix time in seconds, in the browser or on the server
+pub(crate) fn now_secs() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now() as u64 / 1000
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
 #[cfg(all(test, target_arch = "wasm32"))]
 mod tests {
     use super::*;
@@ -165,4 +189,21 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Invalid signature");
     }
-}
\ No newline at end of file
+
+    #[wasm_bindgen_test]
+    fn test_token_expiry_reads_exp_claim() {
+        let jwt = Jwt::new("test-secret".to_string());
+        let claims = JwtClaims {
+            sub: "123".to_string(),
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            roles: vec![],
+            exp: 1_700_000_000,
+            iat: 1_699_996_400,
+            permissions: vec![],
+        };
+
+        assert_eq!(token_expiry(&jwt.create_token(&claims).unwrap()), Some(1_700_000_000));
+        assert_eq!(token_expiry("mock-token-123"), None);
+    }
+}
@@ -0,0 +1,687 @@
+//! Layout Primitives - L5
+//!
+//! `Grid`, `Stack` and `Flex` replace hand-written grid and flex style
+//! strings. Each compiles its settings to a rule with `CssBuilder` and
+//! renders a `div` with that class; identical layouts share one rule.
+//! Gaps use the `StyleBuilder` spacing scale (`gap(6)` is `1.5rem`), and
+//! responsive settings apply from a `Breakpoint` up, mobile first.
+//!
+//! ```ignore
+//! Grid::cols(1)
+//!     .gap(6)
+//!     .responsive(Breakpoint::Md, 2)
+//!     .responsive(Breakpoint::Lg, 4)
+//!     .children(cards)
+//! ```
+//!
+//! `container` settings respond to the space a layout is given instead of
+//! the viewport, so the same component can sit in a narrow sidebar or a
+//! wide main column. Such a layout renders inside a wrapper `div` with
+//! `container-type: inline-size`; the wrapper has no intrinsic width, so
+//! give it one (e.g. `flex: 1`) when it sits in a row.
+//!
+//! ```ignore
+//! Flex::column().gap(4).container(Breakpoint::Custom(480), Direction::Row)
+//! ```
+//!
+//! `AppShell` frames a whole page: header, sidebar, content and footer.
+
+use crate::component::{Component, Element, Props};
+use crate::css_runtime::{Breakpoint, CssBuilder};
+use std::collections::HashMap;
+
+/// Cross-axis alignment of the children (`align-items`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    Stretch,
+    Baseline,
+}
+
+impl Align {
+    fn as_css(self) -> &'static str {
+        match self {
+            Align::Start => "start",
+            Align::Center => "center",
+            Align::End => "end",
+            Align::Stretch => "stretch",
+            Align::Baseline => "baseline",
+        }
+    }
+}
+
+/// Main-axis distribution of the children (`justify-content`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Justify {
+    Start,
+    Center,
+    End,
+    Between,
+    Around,
+    Evenly,
+}
+
+impl Justify {
+    fn as_css(self) -> &'static str {
+        match self {
+            Justify::Start => "start",
+            Justify::Center => "center",
+            Justify::End => "end",
+            Justify::Between => "space-between",
+            Justify::Around => "space-around",
+            Justify::Evenly => "space-evenly",
+        }
+    }
+}
+
+/// Main axis of a `Flex`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Row,
+    Column,
+}
+
+impl Direction {
+    fn as_css(self) -> &'static str {
+        match self {
+            Direction::Row => "row",
+            Direction::Column => "column",
+        }
+    }
+}
+
+/// A `StyleBuilder` spacing step as CSS
+fn spacing(size: u8) -> String {
+    format!("{}rem", size as f32 * 0.25)
+}
+
+/// Settings shared by every layout primitive
+#[derive(Clone, Default)]
+struct LayoutBox {
+    class: Option<String>,
+    gap: Option<u8>,
+    align: Option<Align>,
+    justify: Option<Justify>,
+    children: Vec<Element>,
+}
+
+impl LayoutBox {
+    fn properties(&self, properties: &mut HashMap<String, String>) {
+        if let Some(gap) = self.gap {
+            properties.insert("gap".to_string(), spacing(gap));
+        }
+        if let Some(align) = self.align {
+            properties.insert("align-items".to_string(), align.as_css().to_string());
+        }
+        if let Some(justify) = self.justify {
+            properties.insert("justify-content".to_string(), justify.as_css().to_string());
+        }
+    }
+
+    /// `contained` wraps the layout in a size container for its
+    /// container queries to measure
+    fn render(&self, builder: CssBuilder, contained: bool) -> Element {
+        let generated = builder.build_shared();
+        let element = Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some(match &self.class {
+                    Some(class) => format!("{} {}", generated, class),
+                    None => generated,
+                }),
+                ..Default::default()
+            }),
+            children: self.children.clone(),
+        };
+        if !contained {
+            return element;
+        }
+
+        let container = CssBuilder::new()
+            .prefix("container")
+            .property("container-type", "inline-size")
+            .build_shared();
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some(container),
+                ..Default::default()
+            }),
+            children: vec![element],
+        }
+    }
+}
+
+/// Builders every layout primitive has
+macro_rules! box_builders {
+    () => {
+        /// Space between children, in 0.25rem steps
+        pub fn gap(mut self, size: u8) -> Self {
+            self.inner.gap = Some(size);
+            self
+        }
+
+        pub fn align(mut self, align: Align) -> Self {
+            self.inner.align = Some(align);
+            self
+        }
+
+        pub fn justify(mut self, justify: Justify) -> Self {
+            self.inner.justify = Some(justify);
+            self
+        }
+
+        /// Extra class names for the container
+        pub fn class(mut self, class: impl Into<String>) -> Self {
+            self.inner.class = Some(class.into());
+            self
+        }
+
+        pub fn child(mut self, child: Element) -> Self {
+            self.inner.children.push(child);
+            self
+        }
+
+        pub fn children(mut self, children: Vec<Element>) -> Self {
+            self.inner.children.extend(children);
+            self
+        }
+    };
+}
+
+/// How a `Grid` sizes its columns
+#[derive(Clone, Debug, PartialEq)]
+enum Columns {
+    Count(u32),
+    /// As many columns of at least this width as fit
+    AutoFill(String),
+}
+
+impl Columns {
+    fn as_css(&self) -> String {
+        match self {
+            Columns::Count(count) => format!("repeat({}, minmax(0, 1fr))", (*count).max(1)),
+            Columns::AutoFill(min) => format!("repeat(auto-fill, minmax(min({}, 100%), 1fr))", min),
+        }
+    }
+}
+
+/// CSS grid container
+#[derive(Clone)]
+pub struct Grid {
+    inner: LayoutBox,
+    columns: Columns,
+    responsive: Vec<(Breakpoint, u32)>,
+    container: Vec<(Breakpoint, u32)>,
+}
+
+impl Grid {
+    /// `count` equal columns
+    pub fn cols(count: u32) -> Self {
+        Grid {
+            inner: LayoutBox::default(),
+            columns: Columns::Count(count),
+            responsive: vec![],
+            container: vec![],
+        }
+    }
+
+    /// As many columns as fit, each at least `min_width` wide
+    pub fn auto_fill(min_width: impl Into<String>) -> Self {
+        Grid {
+            columns: Columns::AutoFill(min_width.into()),
+            ..Grid::cols(1)
+        }
+    }
+
+    /// Switch to `count` columns from `breakpoint` up
+    pub fn responsive(mut self, breakpoint: Breakpoint, count: u32) -> Self {
+        self.responsive.push((breakpoint, count));
+        self
+    }
+
+    /// Switch to `count` columns once the grid's own space is at least
+    /// `breakpoint` wide, whatever the viewport
+    pub fn container(mut self, breakpoint: Breakpoint, count: u32) -> Self {
+        self.container.push((breakpoint, count));
+        self
+    }
+
+    box_builders!();
+
+    fn css(&self) -> CssBuilder {
+        let mut properties = HashMap::new();
+        properties.insert("display".to_string(), "grid".to_string());
+        properties.insert("grid-template-columns".to_string(), self.columns.as_css());
+        self.inner.properties(&mut properties);
+
+        let mut builder = CssBuilder::new().prefix("grid").properties(properties);
+        let columns = |count: u32| {
+            HashMap::from([("grid-template-columns".to_string(), Columns::Count(count).as_css())])
+        };
+        for (breakpoint, count) in &self.responsive {
+            builder = builder.breakpoint(*breakpoint, columns(*count));
+        }
+        for (breakpoint, count) in &self.container {
+            builder = builder.container_breakpoint(*breakpoint, columns(*count));
+        }
+        builder
+    }
+}
+
+impl Component for Grid {
+    fn render(&self) -> Element {
+        self.inner.render(self.css(), !self.container.is_empty())
+    }
+}
+
+/// Flexbox container
+#[derive(Clone)]
+pub struct Flex {
+    inner: LayoutBox,
+    direction: Direction,
+    wrap: bool,
+    responsive: Vec<(Breakpoint, Direction)>,
+    container: Vec<(Breakpoint, Direction)>,
+}
+
+impl Flex {
+    pub fn row() -> Self {
+        Flex {
+            inner: LayoutBox::default(),
+            direction: Direction::Row,
+            wrap: false,
+            responsive: vec![],
+            container: vec![],
+        }
+    }
+
+    pub fn column() -> Self {
+        Flex {
+            direction: Direction::Column,
+            ..Flex::row()
+        }
+    }
+
+    /// Let children flow onto more lines
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Switch direction from `breakpoint` up, e.g. stack on phones and sit
+    /// side by side on wider screens
+    pub fn responsive(mut self, breakpoint: Breakpoint, direction: Direction) -> Self {
+        self.responsive.push((breakpoint, direction));
+        self
+    }
+
+    /// Switch direction once the flex's own space is at least `breakpoint`
+    /// wide, e.g. a card that stacks in a sidebar and sits in a row in the
+    /// main column
+    pub fn container(mut self, breakpoint: Breakpoint, direction: Direction) -> Self {
+        self.container.push((breakpoint, direction));
+        self
+    }
+
+    box_builders!();
+
+    fn css(&self) -> CssBuilder {
+        let mut properties = HashMap::new();
+        properties.insert("display".to_string(), "flex".to_string());
+        properties.insert("flex-direction".to_string(), self.direction.as_css().to_string());
+        if self.wrap {
+            properties.insert("flex-wrap".to_string(), "wrap".to_string());
+        }
+        self.inner.properties(&mut properties);
+
+        let mut builder = CssBuilder::new().prefix("flex").properties(properties);
+        let direction = |direction: Direction| {
+            HashMap::from([("flex-direction".to_string(), direction.as_css().to_string())])
+        };
+        for (breakpoint, to) in &self.responsive {
+            builder = builder.breakpoint(*breakpoint, direction(*to));
+        }
+        for (breakpoint, to) in &self.container {
+            builder = builder.container_breakpoint(*breakpoint, direction(*to));
+        }
+        builder
+    }
+}
+
+impl Component for Flex {
+    fn render(&self) -> Element {
+        self.inner.render(self.css(), !self.container.is_empty())
+    }
+}
+
+/// Children one above the other with even spacing
+#[derive(Clone)]
+pub struct Stack {
+    inner: LayoutBox,
+}
+
+impl Stack {
+    pub fn new() -> Self {
+        Stack {
+            inner: LayoutBox {
+                gap: Some(4),
+                ..LayoutBox::default()
+            },
+        }
+    }
+
+    box_builders!();
+
+    fn css(&self) -> CssBuilder {
+        let mut properties = HashMap::new();
+        properties.insert("display".to_string(), "flex".to_string());
+        properties.insert("flex-direction".to_string(), "column".to_string());
+        self.inner.properties(&mut properties);
+        CssBuilder::new().prefix("stack").properties(properties)
+    }
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for Stack {
+    fn render(&self) -> Element {
+        self.inner.render(self.css(), false)
+    }
+}
+
+/// Page frame with header, sidebar, footer and content slots.
+///
+/// The content area is the scroll container, so a sticky header or footer
+/// stays in view without any positioning CSS and in-page links scroll the
+/// content beneath it. Styles are inline, so server-rendered pages need no
+/// stylesheet for the frame.
+pub struct AppShell {
+    header: Option<Element>,
+    sidebar: Option<Element>,
+    footer: Option<Element>,
+    content: Vec<Element>,
+    sticky_header: bool,
+    sticky_footer: bool,
+    sidebar_width: String,
+    sidebar_label: String,
+}
+
+impl AppShell {
+    pub fn new() -> Self {
+        AppShell {
+            header: None,
+            sidebar: None,
+            footer: None,
+            content: vec![],
+            sticky_header: true,
+            sticky_footer: false,
+            sidebar_width: "16rem".to_string(),
+            sidebar_label: "Sidebar".to_string(),
+        }
+    }
+
+    pub fn header(mut self, header: Element) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Keep the header in view while the content scrolls; on by default
+    pub fn sticky_header(mut self, sticky: bool) -> Self {
+        self.sticky_header = sticky;
+        self
+    }
+
+    pub fn sidebar(mut self, sidebar: Element) -> Self {
+        self.sidebar = Some(sidebar);
+        self
+    }
+
+    /// Any CSS length; `16rem` by default
+    pub fn sidebar_width(mut self, width: impl Into<String>) -> Self {
+        self.sidebar_width = width.into();
+        self
+    }
+
+    /// Accessible name of the sidebar landmark
+    pub fn sidebar_label(mut self, label: impl Into<String>) -> Self {
+        self.sidebar_label = label.into();
+        self
+    }
+
+    pub fn footer(mut self, footer: Element) -> Self {
+        self.footer = Some(footer);
+        self
+    }
+
+    /// Keep the footer in view instead of after the content
+    pub fn sticky_footer(mut self, sticky: bool) -> Self {
+        self.sticky_footer = sticky;
+        self
+    }
+
+    pub fn child(mut self, child: Element) -> Self {
+        self.content.push(child);
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.content.extend(children);
+        self
+    }
+}
+
+impl Default for AppShell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn shell_region(
+    tag: &str,
+    class: &str,
+    mut attributes: Vec<(String, String)>,
+    style: String,
+    children: Vec<Element>,
+) -> Element {
+    attributes.push(("style".to_string(), style));
+    Element::Node {
+        tag: tag.to_string(),
+        props: Box::new(Props {
+            class: Some(class.to_string()),
+            attributes,
+            ..Default::default()
+        }),
+        children,
+    }
+}
+
+impl Component for AppShell {
+    fn render(&self) -> Element {
+        let header = self.header.clone().map(|header| {
+            shell_region("header", "app-shell-header", vec![], "flex: none".to_string(), vec![header])
+        });
+        let footer = self.footer.clone().map(|footer| {
+            shell_region("footer", "app-shell-footer", vec![], "flex: none".to_string(), vec![footer])
+        });
+        let (fixed_header, scrolling_header) = if self.sticky_header { (header, None) } else { (None, header) };
+        let (fixed_footer, scrolling_footer) = if self.sticky_footer { (footer, None) } else { (None, footer) };
+
+        let main = shell_region(
+            "main",
+            "app-shell-main",
+            vec![],
+            // Grows so a short page still puts a scrolling footer at the bottom
+            "flex: 1 0 auto".to_string(),
+            self.content.clone(),
+        );
+        let scroll = shell_region(
+            "div",
+            "app-shell-scroll",
+            vec![],
+            "flex: 1; min-width: 0; overflow-y: auto; display: flex; flex-direction: column".to_string(),
+            scrolling_header.into_iter().chain([main]).chain(scrolling_footer).collect(),
+        );
+        let sidebar = self.sidebar.clone().map(|sidebar| {
+            shell_region(
+                "aside",
+                "app-shell-sidebar",
+                vec![("aria-label".to_string(), self.sidebar_label.clone())],
+                format!("flex: 0 0 {}; overflow-y: auto", self.sidebar_width),
+                vec![sidebar],
+            )
+        });
+        let frame = shell_region(
+            "div",
+            "app-shell-frame",
+            vec![],
+            "flex: 1; min-height: 0; display: flex".to_string(),
+            sidebar.into_iter().chain([scroll]).collect(),
+        );
+
+        shell_region(
+            "div",
+            "app-shell",
+            vec![],
+            // dvh tracks mobile browser toolbars; vh is the fallback
+            "display: flex; flex-direction: column; height: 100vh; height: 100dvh".to_string(),
+            fixed_header.into_iter().chain([frame]).chain(fixed_footer).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css_runtime::STYLESHEET_MANAGER;
+
+    fn class(element: &Element) -> String {
+        match element {
+            Element::Node { props, .. } => props.class.clone().unwrap(),
+            _ => panic!("expected a node"),
+        }
+    }
+
+    #[test]
+    fn test_grid_compiles_to_a_shared_responsive_rule() {
+        let grid = || {
+            Grid::cols(1)
+                .gap(6)
+                .align(Align::Center)
+                .responsive(Breakpoint::Lg, 4)
+                .responsive(Breakpoint::Md, 2)
+                .child(Element::Text("card".into()))
+        };
+
+        let first = grid().render();
+        let again = grid().render();
+        assert_eq!(class(&first), class(&again));
+
+        let manager = STYLESHEET_MANAGER.lock();
+        let css = manager.rule_css(&class(&first)).unwrap();
+        assert!(css.contains("display: grid;"));
+        assert!(css.contains("gap: 1.5rem;"));
+        assert!(css.contains("align-items: center;"));
+        assert!(css.contains("grid-template-columns: repeat(1, minmax(0, 1fr));"));
+        // Mobile first: the wider breakpoint comes last so it wins
+        let md = css.find("(min-width: 768px)").unwrap();
+        let lg = css.find("(min-width: 1024px)").unwrap();
+        assert!(md < lg);
+        assert!(css[lg..].contains("repeat(4, minmax(0, 1fr))"));
+    }
+
+    #[test]
+    fn test_flex_and_stack_rules() {
+        let flex = Flex::column()
+            .justify(Justify::Between)
+            .wrap(true)
+            .responsive(Breakpoint::Md, Direction::Row)
+            .class("toolbar")
+            .render();
+        let stack = Stack::new().render();
+
+        let flex_class = class(&flex);
+        assert!(flex_class.ends_with(" toolbar"));
+        let manager = STYLESHEET_MANAGER.lock();
+        let css = manager.rule_css(flex_class.split(' ').next().unwrap()).unwrap();
+        assert!(css.contains("flex-direction: column;"));
+        assert!(css.contains("flex-wrap: wrap;"));
+        assert!(css.contains("justify-content: space-between;"));
+        assert!(css.contains("@media (min-width: 768px)"));
+
+        let css = manager.rule_css(&class(&stack)).unwrap();
+        assert!(css.contains("flex-direction: column;"));
+        assert!(css.contains("gap: 1rem;"));
+    }
+
+    #[test]
+    fn test_container_layouts_query_their_wrapper() {
+        let flex = Flex::column()
+            .gap(4)
+            .container(Breakpoint::Custom(480), Direction::Row)
+            .class("card-body")
+            .render();
+        let grid = Grid::cols(1).container(Breakpoint::Custom(600), 3).render();
+
+        let Element::Node { props, children, .. } = &flex else {
+            panic!("expected a node");
+        };
+        let wrapper = props.class.clone().unwrap();
+        assert_eq!(wrapper, class(&grid));
+        let inner = class(&children[0]);
+        assert!(inner.ends_with(" card-body"));
+
+        let manager = STYLESHEET_MANAGER.lock();
+        assert!(manager.rule_css(&wrapper).unwrap().contains("container-type: inline-size;"));
+        let css = manager.rule_css(inner.split(' ').next().unwrap()).unwrap();
+        assert!(css.contains("@container (min-width: 480px)"));
+        assert!(!css.contains("@media"));
+        let Element::Node { children, .. } = &grid else {
+            panic!("expected a node");
+        };
+        let css = manager.rule_css(&class(&children[0])).unwrap();
+        assert!(css.contains("@container (min-width: 600px) {\n  .l9-grid-"));
+        assert!(css.contains("repeat(3, minmax(0, 1fr))"));
+    }
+
+    #[test]
+    fn test_app_shell_scrolls_only_the_content() {
+        let shell = || {
+            AppShell::new()
+                .header(Element::Text("Nav".into()))
+                .sidebar(Element::Text("Links".into()))
+                .footer(Element::Text("Footer".into()))
+                .child(Element::Text("Page".into()))
+        };
+
+        let sticky = shell().render().to_html();
+        let scroll = sticky.find("app-shell-scroll").unwrap();
+        assert!(sticky.find("<header").unwrap() < scroll);
+        assert!(sticky.find("<aside").unwrap() < scroll);
+        // The footer scrolls with the content unless it's sticky too
+        assert!(sticky.find("<footer").unwrap() > sticky.find("<main").unwrap());
+        assert!(sticky.contains("aria-label=\"Sidebar\""));
+
+        let scrolling = shell().sticky_header(false).sticky_footer(true).render();
+        let Element::Node { children, .. } = &scrolling else {
+            panic!("expected a node");
+        };
+        let outer: Vec<_> = children
+            .iter()
+            .map(|child| match child {
+                Element::Node { tag, .. } => tag.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(outer, vec!["div", "footer"]);
+        let html = scrolling.to_html();
+        assert!(html.find("app-shell-scroll").unwrap() < html.find("<header").unwrap());
+    }
+}
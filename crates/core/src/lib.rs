@@ -10,11 +10,14 @@
 //! L2 Platform: Next.js compatibility
 //! L1 Infrastructure: Build and deploy
 
+pub mod a11y;
+pub mod announce;
 pub mod api_docs;
 pub mod app;
 // pub mod async_component; // Using v2 instead
 pub mod async_component_v2;
 pub mod auth;
+pub mod batch;
 #[cfg(test)]
 mod auth_tests;
 #[cfg(test)]
@@ -23,9 +26,12 @@ mod auth_config_tests;
 mod auth_upload_integration_tests;
 pub mod cache;
 pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod jobs;
 pub mod jwt;
 pub mod component;
 pub mod css_runtime;
+pub mod css_value;
 pub mod styled_component;
 pub mod db;
 #[cfg(feature = "ssr")]
@@ -40,7 +46,13 @@ pub mod fetch;
 pub mod form;
 pub mod form_traits;
 pub mod form_builder;
+pub mod form_files;
+pub mod head;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod health;
 pub mod hooks;
+pub mod http;
+pub mod hydrate;
 pub mod i18n;
 pub mod image;
 pub mod image_lazy;
@@ -48,16 +60,30 @@ pub mod image_lazy;
 pub mod image_transform;
 #[cfg(feature = "ssr")]
 pub mod image_handler;
+#[cfg(feature = "ssr")]
+pub mod image_optimizer;
 pub mod layers;
+pub mod layout;
+pub mod menu;
 pub mod middleware;
 pub mod middleware_v2;
+pub mod modal;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod metrics;
 pub mod monitoring;
+pub mod oauth;
+pub mod portal;
 // pub mod reactive; // Using v2 to fix borrowing issues
+pub mod query;
 pub mod reactive_v2;
+pub mod resource;
 pub mod router;
 pub mod router_v2;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scheduler;
 pub mod security;
 pub mod server;
+pub mod split_pane;
 #[cfg(feature = "ssr")]
 pub mod ssr;
 #[cfg(all(test, feature = "ssr"))]
@@ -65,43 +91,64 @@ mod ssr_tests;
 pub mod state;
 pub mod styles;
 pub mod test;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+pub mod trace;
+pub mod toast;
+pub mod transition;
+pub mod tree;
 pub mod ui;
 pub mod upload;
 #[cfg(test)]
 mod upload_tests;
 pub mod vdom;
+pub mod virtual_list;
 pub mod websocket;
+pub mod ws;
+pub mod ws_server;
 
 // HAF (Hierarchical Architecture First) system
 pub mod haf;
 
 pub mod prelude {
+    pub use crate::a11y::FocusTrap;
+    pub use crate::announce::{use_announce, Announcer, Politeness};
     pub use crate::api_docs::{ApiDoc, OpenApiBuilder, SchemaBuilder};
     pub use crate::app::{run_app, Layer9App};
     pub use crate::async_component_v2::{
-        use_async_data, with_error_boundary, 
-        AsyncData, AsyncState, Suspense
+        use_async, use_async_data, use_async_with, use_resource, with_error_boundary, 
+        AsyncData, AsyncOperation, AsyncState, AsyncStatus, Retrigger, Suspense
     };
-    pub use crate::auth::{use_auth, AuthService, Protected};
+    pub use crate::auth::{use_auth, AuthHandle, AuthService, AuthServiceProvider, Protected};
     pub use crate::cache::{use_cache, use_http_cache, InvalidationStrategy};
-    pub use crate::component::{use_state, view, Component, Element, Props, State};
-    pub use crate::db::{use_db, use_repository, Model, QueryBuilder};
+    pub use crate::component::{list, show, show_else, use_state, view, Component, Element, NodeRef, Props, State};
+    pub use crate::portal::PortalTarget;
+    pub use crate::db::{
+        use_db, use_repository, BelongsTo, HasMany, Model, Order, QueryBuilder, Transactional,
+    };
     pub use crate::env::{env, env_or, is_development, is_production};
-    pub use crate::error::{use_error_handler, ErrorBoundary};
+    pub use crate::error::{use_error_handler, ErrorBoundary, ErrorBoundaryState, ErrorInfo};
     pub use crate::fetch::{get, post, FetchBuilder, Method, SWR};
     pub use crate::form::{use_form, Form, FormConfig};
+    pub use crate::form_files::{FileField, FileRules, FormFiles};
+    pub use crate::head::{set_meta, Head, Viewport, ViewportFit};
     pub use crate::hooks::{
         use_state as use_state_hook, use_reducer, use_effect, use_memo, use_callback, 
         use_ref, use_layout_effect, use_context, provide_context, Context as HookContext,
-        use_counter, use_previous, use_debounce
+        use_counter, use_previous, use_debounce, use_clipboard, copy_to_clipboard, CopyStatus,
+        memo, Memo
     };
-    pub use crate::i18n::{use_i18n, Locale};
+    pub use crate::i18n::{use_i18n, use_translation, I18nProvider, Locale, Translation};
     pub use crate::image::{Image, Picture};
     pub use crate::image_lazy::{LazyImage, LazyLoadManager, use_lazy_image};
     pub use crate::layers::*;
+    pub use crate::layout::{Align, AppShell, Direction, Flex, Grid, Justify, Stack};
+    pub use crate::menu::{Menu, MenuBar, MenuItem};
     pub use crate::middleware::{Context, Middleware, MiddlewareStack};
+    pub use crate::modal::{use_modal, use_url_modal, Modal, ModalHandle};
     pub use crate::monitoring::{use_analytics, use_metrics, use_performance};
-    pub use crate::reactive_v2::{init_renderer, mount, queue_current_render};
+    pub use crate::query::{use_query, QueryResult};
+    pub use crate::reactive_v2::{hydrate, init_renderer, is_server_render, mount, queue_current_render};
     pub use crate::router::{Page, Route, RouteHandler};
     pub use crate::router_v2::{init_router, navigate, route, use_route, use_router, Link};
     pub use crate::security::{use_csrf_token, use_security, XssProtection};
@@ -109,24 +156,31 @@ pub mod prelude {
     #[cfg(feature = "ssr")]
     pub use crate::ssr::{
         SSRComponent, SSRContext, SSRRenderer, SSRApp, SSRRoute, SSRRouteHandler,
-        create_ssr_server, SSRData
+        create_ssr_server, render_to_html_string, SSRData, ServerRendered
     };
     
     #[cfg(all(feature = "ssr", target_arch = "wasm32"))]
     pub use crate::ssr::{use_ssr_data, hydrate_app};
+    pub use crate::split_pane::{SplitDirection, SplitPane};
     pub use crate::state::{
         create_app_store, create_atom, use_atom, use_selector, AppAction, AppState,
         // Note: use_effect is now provided by hooks module
     };
-    pub use crate::styles::{inject_global_styles, style, StyleBuilder};
+    pub use crate::styles::{inject_global_styles, style, ColorScheme, StyleBuilder, ThemeColor};
     pub use crate::css_runtime::{
         css_props, inject_global_styles as inject_css_runtime, 
-        Animation, Breakpoint, CssBuilder, CssVariables
+        css_var, set_vendor_prefixing, Animation, Breakpoint, CriticalCss, CssBuilder, CssVariables, ThemeHandle,
+        ThemeProvider,
     };
+    pub use crate::css_value::{em, percent, px, rem, vh, vw, CssUnit, CssValue};
     pub use crate::styled_component::{styled, ComponentStyling, StyledComponent, styles};
     pub use crate::test::{TestContext, TestResult, TestUtils};
+    pub use crate::toast::{use_toast, ToastHandle, ToastKind, ToastOptions, ToastProvider};
+    pub use crate::transition::Transition;
+    pub use crate::tree::{TreeNode, TreeView, TreeViewState};
     pub use crate::ui::*;
     pub use crate::upload::{FileUpload, FileUploadManager, UploadStatus};
+    pub use crate::virtual_list::VirtualList;
     pub use crate::websocket::{use_websocket, WsMessage, WsState};
 }
 
@@ -0,0 +1,828 @@
+//! Menus - L5
+//!
+//! `MenuBar` and `Menu` follow the WAI-ARIA menu pattern: one item in the
+//! bar is tabbable (roving `tabindex`), arrow keys move between items,
+//! Home/End jump to the ends, typing a label's first letters jumps to it, and
+//! Escape closes the open submenu and returns focus to its parent.
+//!
+//! ```ignore
+//! MenuBar::new("Main", vec![
+//!     MenuItem::link("Home", "/"),
+//!     MenuItem::submenu("Docs", vec![
+//!         MenuItem::link("Guide", "/docs/guide"),
+//!         MenuItem::link("API", "/docs/api"),
+//!     ]),
+//!     MenuItem::action("Sign out", || auth.logout()),
+//! ])
+//! ```
+
+use crate::component::{Component, Element, Props, State};
+use crate::hooks::{use_widget_id, use_widget_state};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_MENU_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Entry of a `MenuBar` or `Menu`
+#[derive(Clone)]
+pub struct MenuItem {
+    pub label: String,
+    pub href: Option<String>,
+    pub on_select: Option<Rc<dyn Fn()>>,
+    /// Items of the submenu this item opens; only used in a `MenuBar`
+    pub submenu: Vec<MenuItem>,
+    pub disabled: bool,
+}
+
+impl MenuItem {
+    /// Item that navigates to `href`
+    pub fn link(label: impl Into<String>, href: impl Into<String>) -> Self {
+        MenuItem {
+            label: label.into(),
+            href: Some(href.into()),
+            on_select: None,
+            submenu: vec![],
+            disabled: false,
+        }
+    }
+
+    /// Item that runs `on_select` when chosen
+    pub fn action(label: impl Into<String>, on_select: impl Fn() + 'static) -> Self {
+        MenuItem {
+            label: label.into(),
+            href: None,
+            on_select: Some(Rc::new(on_select)),
+            submenu: vec![],
+            disabled: false,
+        }
+    }
+
+    /// Item that opens a submenu
+    pub fn submenu(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        MenuItem {
+            label: label.into(),
+            href: None,
+            on_select: None,
+            submenu: items,
+            disabled: false,
+        }
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    fn has_submenu(&self) -> bool {
+        !self.submenu.is_empty()
+    }
+
+    fn activate(&self) {
+        if self.disabled {
+            return;
+        }
+        if let Some(on_select) = &self.on_select {
+            on_select();
+        } else if let Some(href) = &self.href {
+            follow_link(href);
+        }
+    }
+}
+
+/// Next enabled item in the given direction, wrapping around
+fn step_enabled(items: &[MenuItem], from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = items.len();
+    if len == 0 {
+        return None;
+    }
+
+    let start = match (from, forward) {
+        (Some(i), true) => i + 1,
+        (Some(i), false) => i + len - 1,
+        (None, true) => 0,
+        (None, false) => len - 1,
+    };
+
+    (0..len)
+        .map(|offset| {
+            if forward {
+                (start + offset) % len
+            } else {
+                (start + len - offset) % len
+            }
+        })
+        .find(|&i| !items[i].disabled)
+}
+
+/// Item whose label starts with the typed prefix. A single letter searches
+/// from the item after `from`, so pressing it again cycles through matches.
+fn typeahead(items: &[MenuItem], from: usize, typed: &mut String, key: &str) -> Option<usize> {
+    let len = items.len();
+    let find = |prefix: &str, skip_current: bool| {
+        let first = usize::from(skip_current);
+        (first..len + first)
+            .map(|offset| (from + offset) % len)
+            .find(|&i| !items[i].disabled && items[i].label.to_lowercase().starts_with(prefix))
+    };
+
+    typed.push_str(&key.to_lowercase());
+    find(typed, typed.chars().count() == 1).or_else(|| {
+        *typed = key.to_lowercase();
+        find(typed, true)
+    })
+}
+
+fn is_printable(key: &str) -> bool {
+    key.chars().count() == 1 && key != " "
+}
+
+/// Follow an item's link
+fn follow_link(href: &str) {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_href(href);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = href;
+}
+
+/// Move DOM focus once the re-render has put the element in place
+pub(crate) fn focus_later(id: String) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::callback::Timeout::new(0, move || {
+        use wasm_bindgen::JsCast;
+        if let Some(element) = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(&id))
+            .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let _ = element.focus();
+        }
+    })
+    .forget();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = id;
+}
+
+/// A `menuitem` link or button. `on_done` runs after a click selects it,
+/// e.g. to close the menu it is in.
+fn item_element(
+    item: &MenuItem,
+    id: String,
+    tabbable: bool,
+    mut attributes: Vec<(String, String)>,
+    on_done: Option<Rc<dyn Fn()>>,
+) -> Element {
+    attributes.extend([
+        ("role".to_string(), "menuitem".to_string()),
+        ("tabindex".to_string(), if tabbable { "0" } else { "-1" }.to_string()),
+    ]);
+    if item.disabled {
+        attributes.push(("aria-disabled".to_string(), "true".to_string()));
+    }
+    let tag = match &item.href {
+        Some(href) if !item.disabled => {
+            attributes.push(("href".to_string(), href.clone()));
+            "a"
+        }
+        _ => {
+            attributes.push(("type".to_string(), "button".to_string()));
+            "button"
+        }
+    };
+
+    let on_click = if item.disabled || item.has_submenu() || item.on_select.is_none() {
+        None
+    } else {
+        let item = item.clone();
+        Some(Rc::new(move || {
+            item.activate();
+            if let Some(on_done) = &on_done {
+                on_done();
+            }
+        }) as Rc<dyn Fn()>)
+    };
+
+    Element::Node {
+        tag: tag.to_string(),
+        props: Box::new(Props {
+            id: Some(id),
+            attributes,
+            on_click,
+            ..Default::default()
+        }),
+        children: vec![Element::Text(item.label.clone())],
+    }
+}
+
+fn menu_list(id: String, label: &str, items: Vec<Element>) -> Element {
+    Element::Node {
+        tag: "ul".to_string(),
+        props: Box::new(Props {
+            id: Some(id),
+            attributes: vec![
+                ("role".to_string(), "menu".to_string()),
+                ("aria-label".to_string(), label.to_string()),
+                (
+                    "style".to_string(),
+                    "position: absolute; z-index: 10; margin: 0; padding: 0.25rem 0; list-style: none; background: white; border: 1px solid #e5e7eb; border-radius: 0.25rem; min-width: 10rem".to_string(),
+                ),
+            ],
+            ..Default::default()
+        }),
+        children: items
+            .into_iter()
+            .map(|item| Element::Node {
+                tag: "li".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("role".to_string(), "none".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![item],
+            })
+            .collect(),
+    }
+}
+
+#[derive(Clone, Default)]
+struct MenuBarState {
+    /// Bar item holding the roving tabindex
+    active: usize,
+    /// Bar item whose submenu is open
+    open: Option<usize>,
+    /// Focused submenu item, when focus is in the submenu
+    sub_active: Option<usize>,
+    typeahead: String,
+}
+
+impl MenuBarState {
+    /// Open the active item's submenu, focusing its first or last item
+    fn open_active(&mut self, items: &[MenuItem], first: bool) {
+        let item = &items[self.active];
+        if item.has_submenu() {
+            self.open = Some(self.active);
+            self.sub_active = step_enabled(&item.submenu, None, first);
+        } else {
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        self.open = None;
+        self.sub_active = None;
+    }
+}
+
+/// Horizontal bar of menu items with one level of dropdown submenus
+pub struct MenuBar {
+    id: Option<String>,
+    label: String,
+    items: Rc<Vec<MenuItem>>,
+    state: State<MenuBarState>,
+}
+
+impl MenuBar {
+    /// `label` names the bar for screen readers, e.g. "Main"
+    pub fn new(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        let active = step_enabled(&items, None, true).unwrap_or(0);
+        MenuBar {
+            id: None,
+            label: label.into(),
+            items: Rc::new(items),
+            state: State::new(MenuBarState {
+                active,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    fn item_id(id: &str, index: usize) -> String {
+        format!("{}-item-{}", id, index)
+    }
+
+    fn sub_item_id(id: &str, index: usize, sub: usize) -> String {
+        format!("{}-item-{}-{}", id, index, sub)
+    }
+
+    fn focused_id(id: &str, state: &MenuBarState) -> String {
+        match state.sub_active {
+            Some(sub) => Self::sub_item_id(id, state.active, sub),
+            None => Self::item_id(id, state.active),
+        }
+    }
+}
+
+fn handle_menubar_key(key: &str, items: &[MenuItem], state: &State<MenuBarState>) {
+    if items.is_empty() {
+        return;
+    }
+    let mut next = state.get();
+    if !is_printable(key) {
+        next.typeahead.clear();
+    }
+    let active = next.active;
+
+    match next.sub_active {
+        Some(sub) => {
+            let submenu = &items[active].submenu;
+            match key {
+                "ArrowDown" => next.sub_active = step_enabled(submenu, Some(sub), true),
+                "ArrowUp" => next.sub_active = step_enabled(submenu, Some(sub), false),
+                "Home" => next.sub_active = step_enabled(submenu, None, true),
+                "End" => next.sub_active = step_enabled(submenu, None, false),
+                "ArrowRight" | "ArrowLeft" => {
+                    // Move to the neighbouring bar item, opening its submenu
+                    next.active = step_enabled(items, Some(active), key == "ArrowRight").unwrap_or(active);
+                    next.open_active(items, true);
+                }
+                "Enter" | " " => {
+                    submenu[sub].activate();
+                    next.close();
+                }
+                "Escape" | "Tab" => next.close(),
+                _ if is_printable(key) => {
+                    if let Some(found) = typeahead(submenu, sub, &mut next.typeahead, key) {
+                        next.sub_active = Some(found);
+                    }
+                }
+                _ => return,
+            }
+        }
+        None => match key {
+            "ArrowRight" | "ArrowLeft" => {
+                next.active = step_enabled(items, Some(active), key == "ArrowRight").unwrap_or(active);
+                if next.open.is_some() {
+                    next.open_active(items, true);
+                }
+            }
+            "Home" => next.active = step_enabled(items, None, true).unwrap_or(active),
+            "End" => next.active = step_enabled(items, None, false).unwrap_or(active),
+            "ArrowDown" if items[active].has_submenu() => next.open_active(items, true),
+            "ArrowUp" if items[active].has_submenu() => next.open_active(items, false),
+            "Enter" | " " => {
+                if items[active].has_submenu() {
+                    next.open_active(items, true);
+                } else {
+                    items[active].activate();
+                }
+            }
+            "Escape" => next.close(),
+            _ if is_printable(key) => {
+                if let Some(found) = typeahead(items, active, &mut next.typeahead, key) {
+                    next.active = found;
+                    next.close();
+                }
+            }
+            _ => return,
+        },
+    }
+
+    state.set(next);
+}
+
+impl Component for MenuBar {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-menubar-{}", NEXT_MENU_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let state = use_widget_state(&self.state);
+        let current = state.get();
+
+        let on_keydown = {
+            let id = id.clone();
+            let items = self.items.clone();
+            let state = state.clone();
+            Rc::new(move |key: String| {
+                handle_menubar_key(&key, &items, &state);
+                focus_later(MenuBar::focused_id(&id, &state.get()));
+            }) as Rc<dyn Fn(String)>
+        };
+
+        let entries = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let is_open = current.open == Some(index);
+                let mut attributes = vec![];
+                if item.has_submenu() {
+                    attributes.push(("aria-haspopup".to_string(), "menu".to_string()));
+                    attributes.push(("aria-expanded".to_string(), is_open.to_string()));
+                }
+                let mut trigger =
+                    item_element(item, Self::item_id(&id, index), index == current.active, attributes, None);
+
+                if item.has_submenu() && !item.disabled {
+                    // Pointer users open and close the submenu by clicking
+                    let state = state.clone();
+                    if let Element::Node { props, .. } = &mut trigger {
+                        props.on_click = Some(Rc::new(move || {
+                            let mut next = state.get();
+                            next.active = index;
+                            next.sub_active = None;
+                            next.open = if next.open == Some(index) { None } else { Some(index) };
+                            state.set(next);
+                        }));
+                    }
+                }
+
+                let mut children = vec![trigger];
+                if is_open {
+                    let state = state.clone();
+                    let close = Rc::new(move || {
+                        let mut next = state.get();
+                        next.close();
+                        state.set(next);
+                    }) as Rc<dyn Fn()>;
+                    let sub_items = item
+                        .submenu
+                        .iter()
+                        .enumerate()
+                        .map(|(sub, sub_item)| {
+                            let id = Self::sub_item_id(&id, index, sub);
+                            item_element(sub_item, id, false, vec![], Some(close.clone()))
+                        })
+                        .collect();
+                    children.push(menu_list(format!("{}-menu-{}", id, index), &item.label, sub_items));
+                }
+
+                Element::Node {
+                    tag: "li".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![
+                            ("role".to_string(), "none".to_string()),
+                            ("style".to_string(), "position: relative".to_string()),
+                        ],
+                        ..Default::default()
+                    }),
+                    children,
+                }
+            })
+            .collect();
+
+        Element::Node {
+            tag: "ul".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("menubar".to_string()),
+                attributes: vec![
+                    ("role".to_string(), "menubar".to_string()),
+                    ("aria-label".to_string(), self.label.clone()),
+                    (
+                        "style".to_string(),
+                        "display: flex; gap: 1rem; margin: 0; padding: 0; list-style: none".to_string(),
+                    ),
+                ],
+                on_keydown: Some(on_keydown),
+                on_focus_out: Some(Rc::new(move || {
+                    if state.get().open.is_some() {
+                        let mut next = state.get();
+                        next.close();
+                        state.set(next);
+                    }
+                })),
+                ..Default::default()
+            }),
+            children: entries,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct MenuState {
+    open: bool,
+    active: Option<usize>,
+    typeahead: String,
+}
+
+/// Menu button: a button that opens a vertical menu
+pub struct Menu {
+    id: Option<String>,
+    label: String,
+    items: Rc<Vec<MenuItem>>,
+    state: State<MenuState>,
+}
+
+impl Menu {
+    /// `label` is the button text
+    pub fn new(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        Menu {
+            id: None,
+            label: label.into(),
+            items: Rc::new(items),
+            state: State::new(MenuState::default()),
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    fn button_id(id: &str) -> String {
+        format!("{}-button", id)
+    }
+
+    fn item_id(id: &str, index: usize) -> String {
+        format!("{}-item-{}", id, index)
+    }
+
+    fn focused_id(id: &str, state: &MenuState) -> String {
+        match (state.open, state.active) {
+            (true, Some(index)) => Self::item_id(id, index),
+            _ => Self::button_id(id),
+        }
+    }
+}
+
+fn handle_menu_key(key: &str, items: &[MenuItem], state: &State<MenuState>) {
+    let mut next = state.get();
+    if !is_printable(key) {
+        next.typeahead.clear();
+    }
+
+    let Some(active) = next.active.filter(|_| next.open) else {
+        // Focus is on the button
+        if matches!(key, "ArrowDown" | "ArrowUp" | "Enter" | " ") {
+            next.open = true;
+            next.active = step_enabled(items, None, key != "ArrowUp");
+            state.set(next);
+        }
+        return;
+    };
+
+    match key {
+        "ArrowDown" => next.active = step_enabled(items, Some(active), true),
+        "ArrowUp" => next.active = step_enabled(items, Some(active), false),
+        "Home" => next.active = step_enabled(items, None, true),
+        "End" => next.active = step_enabled(items, None, false),
+        "Enter" | " " => {
+            items[active].activate();
+            next = MenuState::default();
+        }
+        "Escape" | "Tab" => next = MenuState::default(),
+        _ if is_printable(key) => {
+            if let Some(found) = typeahead(items, active, &mut next.typeahead, key) {
+                next.active = Some(found);
+            }
+        }
+        _ => return,
+    }
+
+    state.set(next);
+}
+
+impl Component for Menu {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-menu-{}", NEXT_MENU_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let state = use_widget_state(&self.state);
+        let current = state.get();
+        let menu_id = format!("{}-menu", id);
+
+        let on_keydown = {
+            let id = id.clone();
+            let items = self.items.clone();
+            let state = state.clone();
+            Rc::new(move |key: String| {
+                let was_open = state.get().open;
+                handle_menu_key(&key, &items, &state);
+                let next = state.get();
+                // Tab moves on by itself; everything else places focus
+                if next.open || (was_open && key != "Tab") {
+                    focus_later(Menu::focused_id(&id, &next));
+                }
+            }) as Rc<dyn Fn(String)>
+        };
+
+        let on_toggle = {
+            let items = self.items.clone();
+            let state = state.clone();
+            Rc::new(move || {
+                let next = if state.get().open {
+                    MenuState::default()
+                } else {
+                    MenuState {
+                        open: true,
+                        active: step_enabled(&items, None, true),
+                        typeahead: String::new(),
+                    }
+                };
+                state.set(next);
+            }) as Rc<dyn Fn()>
+        };
+
+        let mut children = vec![Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                id: Some(Self::button_id(&id)),
+                attributes: vec![
+                    ("type".to_string(), "button".to_string()),
+                    ("aria-haspopup".to_string(), "menu".to_string()),
+                    ("aria-expanded".to_string(), current.open.to_string()),
+                    ("aria-controls".to_string(), menu_id.clone()),
+                ],
+                on_click: Some(on_toggle),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(self.label.clone())],
+        }];
+
+        if current.open {
+            let state = state.clone();
+            let close = Rc::new(move || state.set(MenuState::default())) as Rc<dyn Fn()>;
+            let items = self
+                .items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    item_element(item, Self::item_id(&id, index), false, vec![], Some(close.clone()))
+                })
+                .collect();
+            children.push(menu_list(menu_id, &self.label, items));
+        }
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("menu".to_string()),
+                attributes: vec![("style".to_string(), "position: relative; display: inline-block".to_string())],
+                on_keydown: Some(on_keydown),
+                on_focus_out: Some(Rc::new(move || {
+                    if state.get().open {
+                        state.set(MenuState::default());
+                    }
+                })),
+                ..Default::default()
+            }),
+            children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn attr(element: &Element, name: &str) -> Option<String> {
+        let Element::Node { props, .. } = element else {
+            return None;
+        };
+        props
+            .attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn bar_item(bar: &MenuBar, index: usize) -> Element {
+        let Element::Node { children, .. } = bar.render() else {
+            panic!("expected menubar");
+        };
+        let Element::Node { children, .. } = &children[index] else {
+            panic!("expected li");
+        };
+        children[0].clone()
+    }
+
+    fn press(bar: &MenuBar, key: &str) {
+        handle_menubar_key(key, &bar.items, &bar.state);
+    }
+
+    #[test]
+    fn test_menubar_keyboard_navigation() {
+        let chosen = Rc::new(RefCell::new(Vec::new()));
+        let choose = |label: &'static str| {
+            let chosen = chosen.clone();
+            MenuItem::action(label, move || chosen.borrow_mut().push(label))
+        };
+
+        let bar = MenuBar::new(
+            "Main",
+            vec![
+                MenuItem::link("Home", "/"),
+                MenuItem::submenu(
+                    "Docs",
+                    vec![choose("Guide"), choose("API"), choose("Changelog").disabled(true)],
+                ),
+                MenuItem::link("About", "/about"),
+            ],
+        )
+        .id("nav");
+
+        assert_eq!(attr(&bar_item(&bar, 0), "tabindex").as_deref(), Some("0"));
+
+        press(&bar, "ArrowRight");
+        assert_eq!(attr(&bar_item(&bar, 1), "tabindex").as_deref(), Some("0"));
+        assert_eq!(attr(&bar_item(&bar, 0), "tabindex").as_deref(), Some("-1"));
+        assert_eq!(attr(&bar_item(&bar, 1), "aria-expanded").as_deref(), Some("false"));
+
+        // Up opens at the last enabled item, skipping "Changelog"
+        press(&bar, "ArrowUp");
+        assert_eq!(bar.state.get().sub_active, Some(1));
+        assert_eq!(attr(&bar_item(&bar, 1), "aria-expanded").as_deref(), Some("true"));
+        assert_eq!(MenuBar::focused_id("nav", &bar.state.get()), "nav-item-1-1");
+
+        press(&bar, "g");
+        assert_eq!(bar.state.get().sub_active, Some(0));
+        press(&bar, "Enter");
+        assert_eq!(*chosen.borrow(), vec!["Guide"]);
+        assert_eq!(bar.state.get().open, None);
+        assert_eq!(MenuBar::focused_id("nav", &bar.state.get()), "nav-item-1");
+
+        // Escape closes the submenu and returns focus to "Docs"
+        press(&bar, "ArrowDown");
+        press(&bar, "Escape");
+        assert_eq!(bar.state.get().open, None);
+        assert_eq!(bar.state.get().active, 1);
+
+        press(&bar, "End");
+        assert_eq!(bar.state.get().active, 2);
+        press(&bar, "ArrowRight");
+        assert_eq!(bar.state.get().active, 0);
+        press(&bar, "a");
+        assert_eq!(bar.state.get().active, 2);
+    }
+
+    #[test]
+    fn test_menu_button_opens_and_closes() {
+        let menu = Menu::new(
+            "Actions",
+            vec![
+                MenuItem::action("Rename", || {}),
+                MenuItem::action("Archive", || {}).disabled(true),
+                MenuItem::action("Delete", || {}),
+            ],
+        )
+        .id("actions");
+
+        handle_menu_key("ArrowDown", &menu.items, &menu.state);
+        assert!(menu.state.get().open);
+        assert_eq!(Menu::focused_id("actions", &menu.state.get()), "actions-item-0");
+
+        handle_menu_key("ArrowDown", &menu.items, &menu.state);
+        assert_eq!(menu.state.get().active, Some(2));
+
+        let Element::Node { children, .. } = menu.render() else {
+            panic!("expected menu");
+        };
+        assert_eq!(attr(&children[0], "aria-expanded").as_deref(), Some("true"));
+        assert_eq!(attr(&children[1], "role").as_deref(), Some("menu"));
+
+        handle_menu_key("Escape", &menu.items, &menu.state);
+        assert!(!menu.state.get().open);
+        assert_eq!(Menu::focused_id("actions", &menu.state.get()), "actions-button");
+    }
+
+    /// Builds its menus afresh on every render, as components do
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Toolbar;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Toolbar {
+        fn render(&self) -> Element {
+            let docs = vec![MenuItem::link("Guide", "/docs/guide"), MenuItem::link("API", "/docs/api")];
+            Element::Node {
+                tag: "div".to_string(),
+                props: Box::default(),
+                children: vec![
+                    MenuBar::new("Main", vec![MenuItem::link("Home", "/"), MenuItem::submenu("Docs", docs)])
+                        .id("nav")
+                        .render(),
+                    Menu::new("Actions", vec![MenuItem::action("Rename", || {})]).render(),
+                ],
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_menus_keep_their_state_across_rerenders() {
+        let toolbar = Toolbar;
+        let mut tree = crate::testing::render(&toolbar);
+        let menu_id = tree.find("div.menu").and_then(|menu| match menu {
+            Element::Node { props, .. } => props.id.clone(),
+            _ => None,
+        });
+        let menu_id = menu_id.expect("menu has an id");
+
+        tree.click("#nav-item-1");
+        tree.click(&format!("#{}-button", menu_id));
+        tree.rerender();
+
+        assert_eq!(tree.find("#nav-item-1").and_then(|item| attr(item, "aria-expanded")).as_deref(), Some("true"));
+        assert!(tree.find("#nav-item-1-0").is_some());
+        let button = tree.find(&format!("#{}-button", menu_id));
+        assert_eq!(button.and_then(|button| attr(button, "aria-expanded")).as_deref(), Some("true"));
+    }
+}
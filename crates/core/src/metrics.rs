@@ -0,0 +1,281 @@
+//! Request Metrics - L4
+//!
+//! Request counts, status codes and latency histograms per route, exposed
+//! at `/metrics` in the Prometheus text format:
+//!
+//! ```ignore
+//! let metrics = Metrics::new();
+//! let app = Router::new()
+//!     .route("/users/:id", get(show_user))
+//!     .route_layer(axum::middleware::from_fn_with_state(metrics.clone(), track_metrics))
+//!     .merge(metrics.clone().into_router());
+//! ```
+//!
+//! Routes are labelled by their pattern (`/users/:id`, not `/users/42`) so
+//! each route is one series. Latency percentiles come from the histogram, e.g.
+//! `histogram_quantile(0.95, sum by (route, le) (rate(layer9_http_request_duration_seconds_bucket[5m])))`.
+
+use crate::middleware_v2::{Context, Middleware, MiddlewareError, Next, Response};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Path the metrics are served on
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Route label of requests that matched no route
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// `Content-Type` of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Latency bucket bounds in seconds
+pub const DEFAULT_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+struct Histogram {
+    /// Observations per bucket, not cumulative
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    requests: BTreeMap<(String, String, u16), u64>,
+    durations: BTreeMap<(String, String), Histogram>,
+}
+
+/// Shared store of request metrics
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+    buckets: Arc<Vec<f64>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Use custom latency bucket bounds, in seconds
+    pub fn with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.retain(|b| b.is_finite());
+        buckets.sort_by(|a, b| a.total_cmp(b));
+        buckets.dedup();
+        Metrics {
+            registry: Arc::new(Mutex::new(Registry::default())),
+            buckets: Arc::new(buckets),
+        }
+    }
+
+    /// Record one finished request
+    pub fn record(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut registry = self.registry.lock().unwrap();
+
+        *registry
+            .requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_insert(0) += 1;
+
+        let histogram = registry
+            .durations
+            .entry((method.to_string(), route.to_string()))
+            .or_insert_with(|| Histogram {
+                counts: vec![0; self.buckets.len()],
+                sum: 0.0,
+                count: 0,
+            });
+        if let Some(bucket) = self.buckets.iter().position(|le| seconds <= *le) {
+            histogram.counts[bucket] += 1;
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    /// Everything recorded so far, in Prometheus text format
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP layer9_http_requests_total Total HTTP requests.\n");
+        out.push_str("# TYPE layer9_http_requests_total counter\n");
+        for ((method, route, status), count) in &registry.requests {
+            let _ = writeln!(
+                out,
+                "layer9_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                escape(method),
+                escape(route),
+                status,
+                count
+            );
+        }
+
+        out.push_str("# HELP layer9_http_request_duration_seconds HTTP request latency.\n");
+        out.push_str("# TYPE layer9_http_request_duration_seconds histogram\n");
+        for ((method, route), histogram) in &registry.durations {
+            let labels = format!("method=\"{}\",route=\"{}\"", escape(method), escape(route));
+            let mut cumulative = 0;
+            for (le, count) in self.buckets.iter().zip(&histogram.counts) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "layer9_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "layer9_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, histogram.count
+            );
+            let _ = writeln!(out, "layer9_http_request_duration_seconds_sum{{{}}} {}", labels, histogram.sum);
+            let _ = writeln!(out, "layer9_http_request_duration_seconds_count{{{}}} {}", labels, histogram.count);
+        }
+
+        out
+    }
+
+    /// Axum router serving `GET /metrics`
+    #[cfg(feature = "ssr")]
+    pub fn into_router(self) -> axum::Router {
+        use axum::{extract::State, http::header, routing::get};
+
+        axum::Router::new()
+            .route(
+                METRICS_PATH,
+                get(|State(metrics): State<Metrics>| async move {
+                    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], metrics.render())
+                }),
+            )
+            .with_state(self)
+    }
+}
+
+/// Axum middleware recording each request under its matched route pattern.
+///
+/// Add it with `route_layer` so the pattern is known; unmatched requests are
+/// counted under `unmatched`.
+#[cfg(feature = "ssr")]
+pub async fn track_metrics(
+    axum::extract::State(metrics): axum::extract::State<Metrics>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let method = request.method().as_str().to_string();
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.record(&method, &route, response.status().as_u16(), started.elapsed());
+    response
+}
+
+/// Layer9 middleware that records requests and answers `/metrics`
+///
+/// Requests are labelled with the route `MiddlewareStack::routes` matched,
+/// and requests no route matched share the `unmatched` label, so stray
+/// paths can't grow the number of series.
+pub struct MetricsMiddleware {
+    metrics: Metrics,
+}
+
+impl MetricsMiddleware {
+    pub fn new(metrics: Metrics) -> Self {
+        MetricsMiddleware { metrics }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for MetricsMiddleware {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        let path = ctx.request.url.split('?').next().unwrap_or_default();
+        if path == METRICS_PATH {
+            return Ok(Response::new()
+                .with_header("Content-Type", PROMETHEUS_CONTENT_TYPE)
+                .with_body(self.metrics.render()));
+        }
+
+        let method = ctx.request.method.as_str();
+        let route = ctx.matched_route().unwrap_or(UNMATCHED_ROUTE).to_string();
+        let started = Instant::now();
+        let result = next(ctx).await;
+
+        let status = match &result {
+            Ok(response) => response.status,
+            Err(error) => error.status,
+        };
+        self.metrics.record(method, &route, status, started.elapsed());
+        result
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware_v2::{test_context, MiddlewareStack};
+    use crate::router::{Page, Route, RouteHandler};
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let metrics = Metrics::with_buckets(vec![0.1, 0.5]);
+        metrics.record("GET", "/users/:id", 200, Duration::from_millis(50));
+        metrics.record("GET", "/users/:id", 200, Duration::from_millis(300));
+        metrics.record("GET", "/users/:id", 404, Duration::from_secs(2));
+
+        let text = metrics.render();
+        assert!(text.contains("layer9_http_requests_total{method=\"GET\",route=\"/users/:id\",status=\"200\"} 2\n"));
+        assert!(text.contains("layer9_http_requests_total{method=\"GET\",route=\"/users/:id\",status=\"404\"} 1\n"));
+        assert!(text.contains("_bucket{method=\"GET\",route=\"/users/:id\",le=\"0.1\"} 1\n"));
+        assert!(text.contains("_bucket{method=\"GET\",route=\"/users/:id\",le=\"0.5\"} 2\n"));
+        assert!(text.contains("_bucket{method=\"GET\",route=\"/users/:id\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("layer9_http_request_duration_seconds_sum{method=\"GET\",route=\"/users/:id\"} 2.35\n"));
+        assert!(text.contains("layer9_http_request_duration_seconds_count{method=\"GET\",route=\"/users/:id\"} 3\n"));
+    }
+
+    #[test]
+    fn test_middleware_records_by_route_and_serves_metrics() {
+        let stack = MiddlewareStack::new()
+            .use_middleware(MetricsMiddleware::new(Metrics::new()))
+            .routes(vec![Route::new("/users/:id", RouteHandler::Page(Page::new))]);
+        let request = |url: &str| block_on(stack.run(test_context(url))).unwrap();
+
+        request("/users/42?tab=posts");
+        request("/users/7");
+        // Paths no route matches, e.g. scanners probing, share one series
+        request("/wp-login.php");
+        request("/users/7/../../.env");
+
+        let response = request("/metrics");
+        assert_eq!(response.headers["Content-Type"], PROMETHEUS_CONTENT_TYPE);
+        let body = response.body.unwrap();
+        assert!(body.contains("route=\"/users/:id\",status=\"200\"} 2\n"));
+        assert!(body.contains("route=\"unmatched\",status=\"200\"} 2\n"));
+        assert!(!body.contains("/users/42") && !body.contains("wp-login") && !body.contains(".env"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -18,6 +18,94 @@ pub struct Context {
     pub params: RouteParams,
 }
 
+impl Context {
+    /// Pick the type from `offered` that the request's `Accept` header prefers.
+    ///
+    /// Offers are tried in order, so on a tie the first one wins; a missing
+    /// `Accept` header accepts anything. Returns `None` when nothing offered is
+    /// acceptable (the handler would usually answer 406).
+    pub fn negotiate<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        let accept = self
+            .request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Accept"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or("*/*");
+        let ranges = parse_accept(accept);
+
+        let mut best: Option<(&'a str, f32)> = None;
+        for &media_type in offered {
+            let quality = ranges
+                .iter()
+                .filter(|range| range.matches(media_type))
+                .max_by_key(|range| range.specificity())
+                .map(|range| range.quality)
+                .unwrap_or(0.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((media_type, quality));
+            }
+        }
+
+        best.map(|(media_type, _)| media_type)
+    }
+
+    /// Set a JSON response body and return the response
+    pub fn respond_json<T: Serialize>(&mut self, value: &T) -> Result<Response, MiddlewareError> {
+        self.response = std::mem::take(&mut self.response).json(value)?;
+        Ok(self.response.clone())
+    }
+
+    /// Render `element` as the HTML response body and return the response
+    pub fn respond_html(&mut self, element: &Element) -> Response {
+        self.response
+            .headers
+            .insert("Content-Type".to_string(), "text/html; charset=utf-8".to_string());
+        self.response.body = Some(element.to_html());
+        self.response.clone()
+    }
+}
+
+/// One media range from an `Accept` header
+struct MediaRange {
+    media_type: String,
+    subtype: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    fn matches(&self, offered: &str) -> bool {
+        let (media_type, subtype) = offered.split_once('/').unwrap_or((offered, ""));
+        (self.media_type == "*" || self.media_type.eq_ignore_ascii_case(media_type))
+            && (self.subtype == "*" || self.subtype.eq_ignore_ascii_case(subtype))
+    }
+
+    fn specificity(&self) -> u8 {
+        (self.media_type != "*") as u8 + (self.subtype != "*") as u8
+    }
+}
+
+fn parse_accept(header: &str) -> Vec<MediaRange> {
+    header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let (media_type, subtype) = parts.next()?.trim().split_once('/')?;
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            Some(MediaRange {
+                media_type: media_type.to_string(),
+                subtype: subtype.to_string(),
+                quality,
+            })
+        })
+        .collect()
+}
+
 /// Request object
 #[derive(Clone)]
 pub struct Request {
@@ -425,6 +513,254 @@ impl Middleware for SecurityMiddleware {
     }
 }
 
+/// Body size limit middleware
+///
+/// Rejects requests whose `Content-Length` or buffered body exceeds the
+/// limit with 413; a small declared length doesn't let a larger body
+/// through. Servers that stream the body use `read_body`, which stops
+/// reading as soon as the limit is passed.
+pub struct BodyLimitMiddleware {
+    default_limit: usize,
+    route_limits: Vec<(String, usize)>,
+}
+
+impl Default for BodyLimitMiddleware {
+    fn default() -> Self {
+        Self::new(1024 * 1024)
+    }
+}
+
+impl BodyLimitMiddleware {
+    pub fn new(default_limit: usize) -> Self {
+        BodyLimitMiddleware {
+            default_limit,
+            route_limits: vec![],
+        }
+    }
+
+    /// Override the limit for every path under `prefix`; the longest matching prefix wins
+    pub fn route_limit(mut self, prefix: impl Into<String>, limit: usize) -> Self {
+        self.route_limits.push((prefix.into(), limit));
+        self
+    }
+
+    pub fn limit_for(&self, url: &str) -> usize {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        self.route_limits
+            .iter()
+            .filter(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, limit)| *limit)
+            .unwrap_or(self.default_limit)
+    }
+
+    fn too_large(limit: usize) -> MiddlewareError {
+        MiddlewareError {
+            status: 413,
+            message: format!("Request body exceeds the {} byte limit", limit),
+        }
+    }
+
+    /// Read `request`'s body from `chunks`, failing with 413 as soon as the
+    /// bytes read pass the limit, or before reading when `Content-Length`
+    /// already does
+    pub async fn read_body<S, B, E>(&self, request: &Request, mut chunks: S) -> Result<Vec<u8>, MiddlewareError>
+    where
+        S: futures::Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]>,
+        E: std::fmt::Display,
+    {
+        use futures::StreamExt;
+
+        let limit = self.limit_for(&request.url);
+        if declared_length(request).is_some_and(|declared| declared > limit) {
+            return Err(Self::too_large(limit));
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| MiddlewareError {
+                status: 400,
+                message: format!("Failed to read request body: {}", e),
+            })?;
+            if body.len() + chunk.as_ref().len() > limit {
+                return Err(Self::too_large(limit));
+            }
+            body.extend_from_slice(chunk.as_ref());
+        }
+        Ok(body)
+    }
+}
+
+fn declared_length(request: &Request) -> Option<usize> {
+    request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+}
+
+#[async_trait(?Send)]
+impl Middleware for BodyLimitMiddleware {
+    async fn handle(&self, ctx: &mut Context) -> Result<Response, MiddlewareError> {
+        let limit = self.limit_for(&ctx.request.url);
+
+        // The client controls the header, so the body counts too
+        let declared = declared_length(&ctx.request);
+        let actual = ctx.request.body.as_ref().map(|body| body.len());
+        if declared.max(actual).is_some_and(|size| size > limit) {
+            return Err(Self::too_large(limit));
+        }
+
+        Ok(ctx.response.clone())
+    }
+}
+
 // Re-exports
 use serde::Serialize;
-use std::collections::HashMap;
\ No newline at end of file
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router_v2::RouteParams;
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    fn context(url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Context {
+        Context {
+            request: Request {
+                method: Method::POST,
+                url: url.to_string(),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                body: body.map(str::to_string),
+                user: None,
+            },
+            response: Response::new(),
+            state: State::new(),
+            params: RouteParams {
+                params: HashMap::new(),
+                query: HashMap::new(),
+            },
+        }
+    }
+
+    fn page(ctx: &mut Context) -> Result<Response, MiddlewareError> {
+        let users = vec!["ada", "grace"];
+        match ctx.negotiate(&["text/html", "application/json"]) {
+            Some("application/json") => ctx.respond_json(&users),
+            Some(_) => Ok(ctx.respond_html(&Element::Node {
+                tag: "ul".to_string(),
+                props: Box::default(),
+                children: users
+                    .iter()
+                    .map(|user| Element::Node {
+                        tag: "li".to_string(),
+                        props: Box::default(),
+                        children: vec![Element::Text(user.to_string())],
+                    })
+                    .collect(),
+            })),
+            None => Err(MiddlewareError {
+                status: 406,
+                message: "Not Acceptable".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_same_route_serves_html_and_json() {
+        let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+        let mut ctx = context("/users", &[("Accept", browser)], None);
+        let response = page(&mut ctx).unwrap();
+        assert_eq!(response.headers["Content-Type"], "text/html; charset=utf-8");
+        assert_eq!(response.body.unwrap(), "<ul><li>ada</li><li>grace</li></ul>");
+
+        let mut ctx = context("/users", &[("accept", "application/json")], None);
+        let response = page(&mut ctx).unwrap();
+        assert_eq!(response.headers["Content-Type"], "application/json");
+        assert_eq!(response.body.unwrap(), r#"["ada","grace"]"#);
+
+        let mut ctx = context("/users", &[("Accept", "image/png")], None);
+        assert_eq!(page(&mut ctx).err().unwrap().status, 406);
+    }
+
+    #[test]
+    fn test_negotiate_respects_quality_and_wildcards() {
+        let ctx = context("/", &[("Accept", "text/html;q=0.5, application/*")], None);
+        assert_eq!(ctx.negotiate(&["text/html", "application/json"]), Some("application/json"));
+
+        let ctx = context("/", &[("Accept", "*/*, text/html;q=0")], None);
+        assert_eq!(ctx.negotiate(&["text/html", "application/json"]), Some("application/json"));
+
+        let ctx = context("/", &[], None);
+        assert_eq!(ctx.negotiate(&["text/html", "application/json"]), Some("text/html"));
+    }
+
+    fn limits() -> BodyLimitMiddleware {
+        BodyLimitMiddleware::new(1024 * 1024).route_limit("/api/upload", 50 * 1024 * 1024)
+    }
+
+    #[test]
+    fn test_rejects_oversized_content_length_with_413() {
+        let mut ctx = context("/api/users", &[("content-length", "2097152")], None);
+        let error = block_on(limits().handle(&mut ctx)).err().unwrap();
+        assert_eq!(error.status, 413);
+    }
+
+    #[test]
+    fn test_upload_route_gets_higher_limit() {
+        let mut ctx = context("/api/upload/avatar?resume=1", &[("Content-Length", "2097152")], None);
+        assert!(block_on(limits().handle(&mut ctx)).is_ok());
+
+        let mut ctx = context("/api/uploads", &[("Content-Length", "2097152")], None);
+        assert!(block_on(limits().handle(&mut ctx)).is_err());
+    }
+
+    #[test]
+    fn test_falls_back_to_body_length() {
+        let middleware = BodyLimitMiddleware::new(8);
+
+        let mut ctx = context("/api/echo", &[], Some("tiny"));
+        assert!(block_on(middleware.handle(&mut ctx)).is_ok());
+
+        let mut ctx = context("/api/echo", &[], Some("far too large"));
+        assert_eq!(block_on(middleware.handle(&mut ctx)).err().unwrap().status, 413);
+    }
+
+    #[test]
+    fn test_small_content_length_does_not_hide_a_large_body() {
+        let middleware = BodyLimitMiddleware::new(8);
+        let mut ctx = context("/api/echo", &[("Content-Length", "4")], Some("far too large"));
+        assert_eq!(block_on(middleware.handle(&mut ctx)).err().unwrap().status, 413);
+    }
+
+    #[test]
+    fn test_streamed_body_stops_at_the_limit() {
+        let middleware = BodyLimitMiddleware::new(8);
+        let request = context("/api/echo", &[("Content-Length", "4")], None).request;
+
+        let read = std::cell::Cell::new(0);
+        let chunks = futures::stream::iter(["abcd", "efgh", "ijkl", "mnop"]).map(|chunk| {
+            read.set(read.get() + 1);
+            Ok::<_, String>(chunk)
+        });
+        let error = block_on(middleware.read_body(&request, chunks)).unwrap_err();
+        assert_eq!(error.status, 413);
+        // The chunk that passed the limit was the last one read
+        assert_eq!(read.get(), 3);
+
+        let chunks = futures::stream::iter(["abcd", "efgh"]).map(Ok::<_, String>);
+        assert_eq!(block_on(middleware.read_body(&request, chunks)).unwrap(), b"abcdefgh");
+
+        let declared = context("/api/echo", &[("Content-Length", "9")], None).request;
+        let chunks = futures::stream::iter(["abcd"]).map(Ok::<_, String>);
+        assert!(block_on(middleware.read_body(&declared, chunks)).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 //! Middleware System V2 - Proper chaining implementation
 
-use crate::auth::User;
+use crate::auth::{AuthProvider, JwtAuthProvider, User};
 use crate::prelude::*;
 pub use crate::router_v2::RouteParams;
 use async_trait::async_trait;
-use std::cell::RefCell;
-use std::future::Future;
-use std::pin::Pin;
+use futures::future::LocalBoxFuture;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::net::IpAddr;
 use std::rc::Rc;
+use std::time::Duration;
 
 /// Middleware context
 pub struct Context {
@@ -17,6 +19,64 @@ pub struct Context {
     pub params: RouteParams,
 }
 
+/// Key of the body parsed by `BodyParserMiddleware` in `Context::state`
+const JSON_BODY_KEY: &str = "json_body";
+
+/// Key of the route pattern `MiddlewareStack::run` matched in `Context::state`
+const MATCHED_ROUTE_KEY: &str = "matched_route";
+
+impl Context {
+    /// Path pattern of the route the request matched (`/users/:id`), if any
+    /// of the `MiddlewareStack`'s routes did
+    pub fn matched_route(&self) -> Option<&str> {
+        self.state
+            .get(MATCHED_ROUTE_KEY)
+            .and_then(|route| route.downcast_ref::<String>())
+            .map(String::as_str)
+    }
+
+    /// The JSON body parsed by `BodyParserMiddleware`, as a `T`
+    ///
+    /// Fails with 415 when the request had no JSON body, and with 400 when
+    /// the body doesn't fit `T`.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, MiddlewareError> {
+        let value = self
+            .state
+            .get(JSON_BODY_KEY)
+            .and_then(|value| value.downcast_ref::<serde_json::Value>())
+            .ok_or_else(|| MiddlewareError {
+                status: 415,
+                message: "Expected a JSON request body".to_string(),
+            })?;
+
+        T::deserialize(value).map_err(|e| MiddlewareError {
+            status: 400,
+            message: format!("Invalid request body: {}", e),
+        })
+    }
+}
+
+/// A bare GET of `url`, for tests that run middleware by hand
+#[cfg(test)]
+pub(crate) fn test_context(url: &str) -> Context {
+    Context {
+        request: Request {
+            method: Method::GET,
+            url: url.to_string(),
+            headers: HashMap::new(),
+            body: None,
+            user: None,
+            peer_addr: None,
+        },
+        response: Response::new(),
+        state: HashMap::new(),
+        params: RouteParams {
+            params: HashMap::new(),
+            query: HashMap::new(),
+        },
+    }
+}
+
 /// Request object
 #[derive(Clone)]
 pub struct Request {
@@ -25,6 +85,20 @@ pub struct Request {
     pub headers: HashMap<String, String>,
     pub body: Option<String>,
     pub user: Option<User>,
+    /// Address of the connection the request arrived on; a proxy's when the
+    /// app sits behind one. Whoever builds the `Request` from the server's
+    /// request fills it in, e.g. from axum's `ConnectInfo`.
+    pub peer_addr: Option<IpAddr>,
+}
+
+impl Request {
+    /// Value of the header `name`, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
 }
 
 /// Response object
@@ -33,6 +107,8 @@ pub struct Response {
     pub status: u16,
     pub headers: HashMap<String, String>,
     pub body: Option<String>,
+    /// Binary body (images, downloads); sent instead of `body` when set
+    pub bytes: Option<Vec<u8>>,
 }
 
 impl Default for Response {
@@ -47,6 +123,7 @@ impl Response {
             status: 200,
             headers: HashMap::new(),
             body: None,
+            bytes: None,
         }
     }
 
@@ -65,6 +142,11 @@ impl Response {
         self
     }
 
+    pub fn with_bytes(mut self, bytes: Vec<u8>) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
     pub fn json<T: Serialize>(mut self, data: &T) -> Result<Self, String> {
         let json = serde_json::to_string(data).map_err(|e| e.to_string())?;
         self.headers
@@ -77,10 +159,26 @@ impl Response {
 /// State container for middleware
 pub type State = HashMap<String, Box<dyn std::any::Any>>;
 
-/// Next function type
-pub type Next = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = Result<Response, MiddlewareError>>>>>;
+/// The rest of the chain after the current middleware
+pub type Next = Box<dyn for<'a> FnOnce(&'a mut Context) -> LocalBoxFuture<'a, Result<Response, MiddlewareError>>>;
+
+/// Box a closure as a `Next`; mostly for calling a middleware directly in tests
+pub fn next_fn(
+    f: impl for<'a> FnOnce(&'a mut Context) -> LocalBoxFuture<'a, Result<Response, MiddlewareError>> + 'static,
+) -> Next {
+    Box::new(f)
+}
 
 /// Middleware trait
+///
+/// A middleware either passes the request on with `next(ctx).await`, or ends
+/// the chain by returning without calling `next`:
+///
+/// - `Ok(response)` is what `MiddlewareStack::run` yields, and nothing
+///   downstream runs (a redirect, a cached page, a 401)
+/// - `Err(error)` propagates to the caller the same way
+///
+/// When every middleware calls `next`, the stack yields `ctx.response`.
 #[async_trait(?Send)]
 pub trait Middleware {
     async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError>;
@@ -105,6 +203,7 @@ impl From<String> for MiddlewareError {
 /// Middleware chain builder
 pub struct MiddlewareStack {
     middlewares: Vec<Rc<dyn Middleware>>,
+    routes: Vec<Route>,
 }
 
 impl Default for MiddlewareStack {
@@ -117,6 +216,7 @@ impl MiddlewareStack {
     pub fn new() -> Self {
         MiddlewareStack {
             middlewares: vec![],
+            routes: vec![],
         }
     }
 
@@ -125,44 +225,780 @@ impl MiddlewareStack {
         self
     }
 
-    #[allow(clippy::await_holding_refcell_ref)]
-    pub async fn run(&self, ctx: Context) -> Result<Response, MiddlewareError> {
-        // Create a shared context wrapped in Rc<RefCell>
-        let ctx_rc = Rc::new(RefCell::new(ctx));
-        
-        // Create the final handler that returns the response from context
-        let final_handler = {
-            let ctx_clone = ctx_rc.clone();
-            move || -> Pin<Box<dyn Future<Output = Result<Response, MiddlewareError>>>> {
-                Box::pin(async move {
-                    Ok(ctx_clone.borrow().response.clone())
-                })
+    /// The app's routes: each one's `middleware` runs after the global
+    /// ones, only for requests matching its `path` (`/users/:id`, or
+    /// `/admin/*` for everything below `/admin`)
+    ///
+    /// The first matching route wins. The path is matched once, with `.`
+    /// and `..` segments resolved, before any middleware runs, so they all
+    /// see the params and query in `ctx.params` and the pattern in
+    /// `ctx.matched_route()`. Anything a route's middleware returns still
+    /// passes back through the global middlewares.
+    pub fn routes(mut self, routes: impl IntoIterator<Item = Route>) -> Self {
+        self.routes.extend(routes);
+        self
+    }
+
+    /// Run the middlewares in order; see `Middleware` for how a chain ends
+    pub async fn run(&self, mut ctx: Context) -> Result<Response, MiddlewareError> {
+        let url = ctx.request.url.clone();
+        let (raw_path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let path = crate::router_v2::normalize_path(raw_path);
+        if path != raw_path {
+            ctx.request.url = url.replacen(raw_path, &path, 1);
+        }
+
+        let matched = self.routes.iter().find_map(|route| {
+            crate::router_v2::match_path(&route.path, &path).map(|params| (route, params))
+        });
+        let route_middleware = match matched {
+            Some((route, params)) => {
+                ctx.params = RouteParams {
+                    params,
+                    query: crate::router_v2::parse_query(&format!("?{}", query)),
+                };
+                ctx.state.insert(MATCHED_ROUTE_KEY.to_string(), Box::new(route.path.clone()));
+                route.middleware.clone()
             }
+            None => vec![],
         };
-        
-        // Build the middleware chain in reverse order
-        let chain = self.middlewares.iter().rev().fold(
-            Box::new(final_handler) as Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = Result<Response, MiddlewareError>>>>>,
-            |next, middleware| {
-                let middleware = middleware.clone();
-                let ctx_clone = ctx_rc.clone();
-                Box::new(move || {
-                    Box::pin(async move {
-                        let result = {
-                            let mut ctx_mut = ctx_clone.borrow_mut();
-                            middleware.handle(&mut ctx_mut, next).await
-                        };
-                        result
-                    })
-                })
+
+        let end = chain(&route_middleware, respond());
+        chain(&self.middlewares, end)(&mut ctx).await
+    }
+}
+
+/// Wrap `end` in `middlewares`, the first one outermost
+fn chain<M>(middlewares: &[M], end: Next) -> Next
+where
+    M: std::ops::Deref<Target = dyn Middleware> + Clone + 'static,
+{
+    middlewares.iter().rev().fold(end, |next, middleware| {
+        let middleware = middleware.clone();
+        next_fn(move |ctx| Box::pin(async move { middleware.handle(ctx, next).await }))
+    })
+}
+
+/// Past the last middleware, answer with whatever it left in the context
+fn respond() -> Next {
+    next_fn(|ctx| Box::pin(async move { Ok(ctx.response.clone()) }))
+}
+
+/// Fills `ctx.request.user` from an `Authorization: Bearer` token
+///
+/// A missing, invalid or expired token leaves the request anonymous; with
+/// `require_auth` it is rejected with 401 before the rest of the chain runs.
+pub struct AuthMiddleware {
+    provider: Box<dyn AuthProvider>,
+    require_auth: bool,
+}
+
+impl AuthMiddleware {
+    pub fn new(provider: impl AuthProvider + 'static) -> Self {
+        AuthMiddleware {
+            provider: Box::new(provider),
+            require_auth: false,
+        }
+    }
+
+    /// Verify tokens signed with `secret`, as issued by `JwtAuthProvider`
+    pub fn with_secret(secret: impl Into<String>) -> Self {
+        Self::new(JwtAuthProvider::new(secret.into()))
+    }
+
+    pub fn require_auth(mut self, require_auth: bool) -> Self {
+        self.require_auth = require_auth;
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for AuthMiddleware {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        let user = ctx
+            .request
+            .header("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| self.provider.validate_token(token.trim()).ok());
+
+        if user.is_none() && self.require_auth {
+            return Err(MiddlewareError {
+                status: 401,
+                message: "Authentication required".to_string(),
+            });
+        }
+
+        ctx.request.user = user;
+        next(ctx).await
+    }
+}
+
+/// Outcome of counting one request against a rate limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimit {
+    Allowed { remaining: u32 },
+    /// Over the limit; the oldest counted request leaves the window after
+    /// `retry_after_ms`
+    Limited { retry_after_ms: u64 },
+}
+
+/// Where request counts are kept, per key
+///
+/// `InMemoryRateLimitStore` is the default; implement this over a shared
+/// store (e.g. Redis) when several servers must agree on the counts.
+#[async_trait(?Send)]
+pub trait RateLimitStore {
+    /// Count a request for `key` at `now_ms` unless `limit` requests were
+    /// already counted in the `window_ms` before it
+    async fn hit(&self, key: &str, now_ms: u64, window_ms: u64, limit: u32) -> RateLimit;
+}
+
+/// Sliding-window log kept in this process
+///
+/// Each key keeps the times of its requests inside the window, so a client
+/// can't fit 2N requests around a window boundary the way it can with fixed
+/// windows. Keys with no recent requests are dropped.
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    hits: RefCell<HashMap<String, VecDeque<u64>>>,
+    next_sweep_ms: Cell<u64>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of keys currently tracked
+    pub fn len(&self) -> usize {
+        self.hits.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait(?Send)]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn hit(&self, key: &str, now_ms: u64, window_ms: u64, limit: u32) -> RateLimit {
+        let window_start = now_ms.saturating_sub(window_ms);
+        let mut hits = self.hits.borrow_mut();
+
+        // Forget clients that have gone quiet, at most once per window
+        if now_ms >= self.next_sweep_ms.get() {
+            hits.retain(|_, times| times.back().is_some_and(|&t| t > window_start));
+            self.next_sweep_ms.set(now_ms + window_ms);
+        }
+
+        let times = hits.entry(key.to_string()).or_default();
+        while times.front().is_some_and(|&t| t <= window_start) {
+            times.pop_front();
+        }
+
+        if times.len() >= limit as usize {
+            let oldest = times.front().copied().unwrap_or(now_ms);
+            return RateLimit::Limited {
+                retry_after_ms: (oldest + window_ms).saturating_sub(now_ms).max(1),
+            };
+        }
+
+        times.push_back(now_ms);
+        RateLimit::Allowed {
+            remaining: limit - times.len() as u32,
+        }
+    }
+}
+
+type RateLimitKeyFn = Box<dyn Fn(&Context) -> Option<String>>;
+
+/// Allows each client `limit` requests per sliding `window`
+///
+/// Clients are told apart by the `key` given to `new`. Over the limit, the
+/// request is answered with 429 and `Retry-After` without running the rest
+/// of the chain.
+pub struct RateLimitMiddleware {
+    limit: u32,
+    window_ms: u64,
+    store: Rc<dyn RateLimitStore>,
+    key: RateLimitKeyFn,
+    clock: Box<dyn Fn() -> u64>,
+}
+
+impl RateLimitMiddleware {
+    /// `key` names the bucket a request counts against; every request it
+    /// returns `None` for shares one bucket. `client_key` covers the usual
+    /// case of one bucket per user or client address.
+    pub fn new(limit: u32, window: Duration, key: impl Fn(&Context) -> Option<String> + 'static) -> Self {
+        RateLimitMiddleware {
+            limit,
+            window_ms: window.as_millis() as u64,
+            store: Rc::new(InMemoryRateLimitStore::new()),
+            key: Box::new(key),
+            clock: Box::new(now_ms),
+        }
+    }
+
+    /// Key by the signed-in user, then the client IP: the request's
+    /// `peer_addr`, or behind `trusted_proxies` reverse proxies the address
+    /// they recorded in `X-Forwarded-For`/`X-Real-IP`. Each proxy appends
+    /// the address it received from, so the client is that many hops back
+    /// from the peer; entries further left, and the headers when there are
+    /// no proxies, come from the client and aren't trusted.
+    ///
+    /// Anonymous requests without a `peer_addr` all share one bucket, so
+    /// set it wherever the server's requests are turned into `Request`s.
+    pub fn client_key(trusted_proxies: usize) -> impl Fn(&Context) -> Option<String> + 'static {
+        move |ctx| {
+            if let Some(user) = &ctx.request.user {
+                return Some(format!("user:{}", user.id));
             }
-        );
-        
-        // Execute the chain
-        chain().await
+            client_ip(&ctx.request, trusted_proxies).map(|ip| format!("ip:{}", ip))
+        }
+    }
+
+    pub fn store(mut self, store: impl RateLimitStore + 'static) -> Self {
+        self.store = Rc::new(store);
+        self
+    }
+
+    /// Milliseconds since the epoch; replace to simulate time in tests
+    pub fn clock(mut self, now_ms: impl Fn() -> u64 + 'static) -> Self {
+        self.clock = Box::new(now_ms);
+        self
+    }
+}
+
+/// The client's address, `hops` trusted proxies back from the peer
+fn client_ip(request: &Request, hops: usize) -> Option<String> {
+    let peer = request.peer_addr.map(|addr| addr.to_string());
+    if hops == 0 {
+        return peer;
+    }
+
+    let mut chain: Vec<String> = request
+        .header("X-Forwarded-For")
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+        .collect();
+    if chain.is_empty() {
+        // A single proxy may only set X-Real-IP
+        if let Some(ip) = request.header("X-Real-IP").map(str::trim).filter(|ip| !ip.is_empty()) {
+            chain.push(ip.to_string());
+        }
+    }
+    chain.extend(peer);
+
+    // With fewer entries than proxies, the first is as far back as we know
+    let index = chain.len().saturating_sub(hops + 1);
+    chain.get(index).cloned()
+}
+
+#[async_trait(?Send)]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        let key = (self.key)(ctx).unwrap_or_else(|| "unkeyed".to_string());
+        let now = (self.clock)();
+
+        match self.store.hit(&key, now, self.window_ms, self.limit).await {
+            RateLimit::Limited { retry_after_ms } => Ok(Response::new()
+                .with_status(429)
+                .with_header("Retry-After", retry_after_ms.div_ceil(1000).to_string())
+                .with_header("X-RateLimit-Limit", self.limit.to_string())
+                .with_header("X-RateLimit-Remaining", "0")
+                .with_body("Too many requests")),
+            RateLimit::Allowed { remaining } => {
+                let mut response = next(ctx).await?;
+                response
+                    .headers
+                    .insert("X-RateLimit-Limit".to_string(), self.limit.to_string());
+                response
+                    .headers
+                    .insert("X-RateLimit-Remaining".to_string(), remaining.to_string());
+                Ok(response)
+            }
+        }
+    }
+}
+
+/// Parses `application/json` request bodies for `Context::json`
+///
+/// Requests with another content type pass through untouched. A JSON request
+/// with an empty or malformed body is answered with 400.
+pub struct BodyParserMiddleware;
+
+#[async_trait(?Send)]
+impl Middleware for BodyParserMiddleware {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        if !ctx.request.header("Content-Type").is_some_and(is_json_content_type) {
+            return next(ctx).await;
+        }
+
+        let body = ctx.request.body.as_deref().unwrap_or_default();
+        if body.trim().is_empty() {
+            return Err(MiddlewareError {
+                status: 400,
+                message: "Request body is empty; expected JSON".to_string(),
+            });
+        }
+        let value: serde_json::Value = serde_json::from_str(body).map_err(|e| MiddlewareError {
+            status: 400,
+            message: format!("Malformed JSON body: {}", e),
+        })?;
+
+        ctx.state.insert(JSON_BODY_KEY.to_string(), Box::new(value));
+        next(ctx).await
+    }
+}
+
+/// `application/json`, or a `+json` type such as `application/problem+json`,
+/// with any parameters
+fn is_json_content_type(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    media_type == "application/json" || (media_type.starts_with("application/") && media_type.ends_with("+json"))
+}
+
+/// Milliseconds since the epoch, in the browser or on the server
+fn now_ms() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now() as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
     }
 }
 
 // Re-exports
+use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
\ No newline at end of file
+use std::collections::HashMap;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::{now_secs, Jwt, JwtClaims};
+    use futures::executor::block_on;
+    use serde::Deserialize;
+
+    const SECRET: &str = "middleware-secret";
+
+    fn token(exp: u64) -> String {
+        Jwt::new(SECRET.to_string())
+            .create_token(&JwtClaims {
+                sub: "user-1".to_string(),
+                username: "ada".to_string(),
+                email: "ada@example.com".to_string(),
+                roles: vec!["user".to_string()],
+                exp,
+                iat: exp - 3600,
+                permissions: vec![],
+            })
+            .unwrap()
+    }
+
+    fn context(authorization: Option<String>) -> Context {
+        let mut ctx = test_context("/account");
+        ctx.request.headers.extend(authorization.map(|value| ("authorization".to_string(), value)));
+        ctx
+    }
+
+    /// Runs `middleware`, returning its result and whether `next` was called
+    fn run(middleware: &AuthMiddleware, ctx: &mut Context) -> (Result<Response, MiddlewareError>, bool) {
+        let called = Rc::new(RefCell::new(false));
+        let next = {
+            let called = called.clone();
+            next_fn(move |_| {
+                Box::pin(async move {
+                    *called.borrow_mut() = true;
+                    Ok(Response::new())
+                })
+            })
+        };
+        let result = block_on(middleware.handle(ctx, next));
+        let called = *called.borrow();
+        (result, called)
+    }
+
+    #[test]
+    fn test_valid_token_sets_user() {
+        let middleware = AuthMiddleware::with_secret(SECRET).require_auth(true);
+        let mut ctx = context(Some(format!("Bearer {}", token(now_secs() + 3600))));
+
+        let (result, called) = run(&middleware, &mut ctx);
+        assert!(result.is_ok() && called);
+        let user = ctx.request.user.unwrap();
+        assert_eq!(user.id, "user-1");
+        assert_eq!(user.username, "ada");
+    }
+
+    #[test]
+    fn test_missing_token_is_anonymous_unless_required() {
+        let mut ctx = context(None);
+        let (result, called) = run(&AuthMiddleware::with_secret(SECRET), &mut ctx);
+        assert!(result.is_ok() && called);
+        assert!(ctx.request.user.is_none());
+
+        let mut ctx = context(None);
+        let (result, called) = run(&AuthMiddleware::with_secret(SECRET).require_auth(true), &mut ctx);
+        assert_eq!(result.err().unwrap().status, 401);
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_expired_or_foreign_token_is_unauthenticated() {
+        let expired = format!("Bearer {}", token(now_secs() - 60));
+
+        let mut ctx = context(Some(expired.clone()));
+        let (result, called) = run(&AuthMiddleware::with_secret(SECRET), &mut ctx);
+        assert!(result.is_ok() && called);
+        assert!(ctx.request.user.is_none());
+
+        let mut ctx = context(Some(expired));
+        let (result, called) = run(&AuthMiddleware::with_secret(SECRET).require_auth(true), &mut ctx);
+        assert_eq!(result.err().unwrap().status, 401);
+        assert!(!called);
+
+        let mut ctx = context(Some(format!("Bearer {}", token(now_secs() + 3600))));
+        let (result, _) = run(&AuthMiddleware::with_secret("other-secret").require_auth(true), &mut ctx);
+        assert_eq!(result.err().unwrap().status, 401);
+    }
+
+    /// Redirects everything under `/old`, passing other requests on
+    struct Redirect;
+
+    #[async_trait(?Send)]
+    impl Middleware for Redirect {
+        async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+            if let Some(rest) = ctx.request.url.strip_prefix("/old") {
+                return Ok(Response::new()
+                    .with_status(302)
+                    .with_header("Location", format!("/new{}", rest)));
+            }
+            next(ctx).await
+        }
+    }
+
+    /// Records that it ran and who the request was for
+    struct Record(Rc<RefCell<Vec<String>>>);
+
+    #[async_trait(?Send)]
+    impl Middleware for Record {
+        async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+            let user = ctx.request.user.as_ref().map_or("anonymous", |u| u.username.as_str());
+            self.0.borrow_mut().push(user.to_string());
+            ctx.response = Response::new().with_body("page");
+            next(ctx).await
+        }
+    }
+
+    #[test]
+    fn test_returning_without_next_ends_the_chain() {
+        let ran = Rc::new(RefCell::new(vec![]));
+        let stack = MiddlewareStack::new()
+            .use_middleware(Redirect)
+            .use_middleware(Record(ran.clone()));
+
+        let mut ctx = context(None);
+        ctx.request.url = "/old/profile".to_string();
+        let response = block_on(stack.run(ctx)).unwrap();
+        assert_eq!(response.status, 302);
+        assert_eq!(response.headers["Location"], "/new/profile");
+        assert!(ran.borrow().is_empty());
+
+        let response = block_on(stack.run(context(None))).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.as_deref(), Some("page"));
+        assert_eq!(*ran.borrow(), vec!["anonymous"]);
+    }
+
+    #[test]
+    fn test_downstream_middleware_sees_authenticated_user() {
+        let ran = Rc::new(RefCell::new(vec![]));
+        let stack = MiddlewareStack::new()
+            .use_middleware(AuthMiddleware::with_secret(SECRET))
+            .use_middleware(Record(ran.clone()));
+
+        let ctx = context(Some(format!("Bearer {}", token(now_secs() + 3600))));
+        assert!(block_on(stack.run(ctx)).is_ok());
+        assert_eq!(*ran.borrow(), vec!["ada"]);
+    }
+
+    /// Adds a header on the way out, after the rest of the chain answered
+    struct Stamp;
+
+    #[async_trait(?Send)]
+    impl Middleware for Stamp {
+        async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+            let response = next(ctx).await?;
+            Ok(response.with_header("X-Stamp", "global"))
+        }
+    }
+
+    /// Rejects requests for a section other than `:section` = "users"
+    struct OnlyUsers;
+
+    #[async_trait(?Send)]
+    impl Middleware for OnlyUsers {
+        async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+            if ctx.params.params.get("section").map(String::as_str) != Some("users") {
+                return Ok(Response::new().with_status(403));
+            }
+            next(ctx).await
+        }
+    }
+
+    #[test]
+    fn test_route_middleware_only_runs_for_matching_routes() {
+        let ran = Rc::new(RefCell::new(vec![]));
+        let stack = MiddlewareStack::new()
+            .use_middleware(Record(ran.clone()))
+            .routes(vec![
+                page("/about"),
+                page("/admin/*").middleware(AuthMiddleware::with_secret(SECRET).require_auth(true)),
+            ]);
+
+        let public = |url: &str, authorization: Option<String>| {
+            let mut ctx = context(authorization);
+            ctx.request.url = url.to_string();
+            block_on(stack.run(ctx))
+        };
+
+        // Unprotected routes skip the auth middleware
+        assert_eq!(public("/about", None).unwrap().status, 200);
+        assert_eq!(public("/administrator", None).unwrap().status, 200);
+
+        assert_eq!(public("/admin", None).err().unwrap().status, 401);
+        assert_eq!(public("/admin/users?page=2", None).err().unwrap().status, 401);
+        let bearer = Some(format!("Bearer {}", token(now_secs() + 3600)));
+        assert_eq!(public("/admin/users", bearer).unwrap().body.as_deref(), Some("page"));
+        assert_eq!(ran.borrow().len(), 5);
+
+        // Dot segments can't walk around the protected prefix
+        assert_eq!(public("/about/../admin/users", None).err().unwrap().status, 401);
+        assert_eq!(public("/about/%2e%2e/admin", None).err().unwrap().status, 401);
+        assert_eq!(public("//admin/./users", None).err().unwrap().status, 401);
+    }
+
+    fn page(path: &str) -> Route {
+        Route::new(path, RouteHandler::Page(Page::new))
+    }
+
+    /// Matched route, `:id` param and URL
+    type Seen = (Option<String>, Option<String>, String);
+
+    /// Records the params and route each request reached it with
+    struct SeeParams(Rc<RefCell<Vec<Seen>>>);
+
+    #[async_trait(?Send)]
+    impl Middleware for SeeParams {
+        async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+            self.0.borrow_mut().push((
+                ctx.matched_route().map(str::to_string),
+                ctx.params.params.get("id").cloned(),
+                ctx.request.url.clone(),
+            ));
+            next(ctx).await
+        }
+    }
+
+    #[test]
+    fn test_global_middleware_sees_the_matched_route() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let stack = MiddlewareStack::new()
+            .use_middleware(SeeParams(seen.clone()))
+            .routes(vec![page("/users/:id")]);
+
+        let mut ctx = context(None);
+        ctx.request.url = "/teams/../users/7?tab=repos".to_string();
+        block_on(stack.run(ctx)).unwrap();
+        let mut ctx = context(None);
+        ctx.request.url = "/teams/7".to_string();
+        block_on(stack.run(ctx)).unwrap();
+
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                (Some("/users/:id".to_string()), Some("7".to_string()), "/users/7?tab=repos".to_string()),
+                (None, None, "/teams/7".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_short_circuit_passes_through_global_middleware() {
+        let stack = MiddlewareStack::new()
+            .use_middleware(Stamp)
+            .routes(vec![page("/admin/:section").middleware(OnlyUsers)]);
+
+        let mut ctx = context(None);
+        ctx.request.url = "/admin/billing?tab=invoices".to_string();
+        let response = block_on(stack.run(ctx)).unwrap();
+        assert_eq!(response.status, 403);
+        assert_eq!(response.headers["X-Stamp"], "global");
+
+        let mut ctx = context(None);
+        ctx.request.url = "/admin/users".to_string();
+        assert_eq!(block_on(stack.run(ctx)).unwrap().status, 200);
+    }
+
+    fn post(content_type: &str, body: &str) -> Context {
+        let mut ctx = context(None);
+        ctx.request.method = Method::POST;
+        ctx.request.headers.insert("Content-Type".to_string(), content_type.to_string());
+        ctx.request.body = Some(body.to_string());
+        ctx
+    }
+
+    #[derive(Deserialize)]
+    struct Signup {
+        email: String,
+        age: u32,
+    }
+
+    /// Answers with the `Signup` in the body
+    struct Echo;
+
+    #[async_trait(?Send)]
+    impl Middleware for Echo {
+        async fn handle(&self, ctx: &mut Context, _next: Next) -> Result<Response, MiddlewareError> {
+            let signup: Signup = ctx.json()?;
+            Ok(Response::new().with_body(format!("{} {}", signup.email, signup.age)))
+        }
+    }
+
+    #[test]
+    fn test_json_body_is_parsed_for_handlers() {
+        let stack = MiddlewareStack::new().use_middleware(BodyParserMiddleware).use_middleware(Echo);
+
+        let ctx = post("application/json; charset=utf-8", r#"{"email": "ada@example.com", "age": 36}"#);
+        let response = block_on(stack.run(ctx)).unwrap();
+        assert_eq!(response.body.as_deref(), Some("ada@example.com 36"));
+
+        // Valid JSON of the wrong shape
+        let error = block_on(stack.run(post("application/json", r#"{"email": "ada@example.com"}"#))).err().unwrap();
+        assert_eq!(error.status, 400);
+        assert!(error.message.contains("age"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_malformed_or_empty_json_body_is_rejected() {
+        let stack = MiddlewareStack::new().use_middleware(BodyParserMiddleware).use_middleware(Echo);
+
+        let error = block_on(stack.run(post("application/json", r#"{"email": "#))).err().unwrap();
+        assert_eq!(error.status, 400);
+        assert!(error.message.starts_with("Malformed JSON body"), "{}", error.message);
+
+        let error = block_on(stack.run(post("application/json", "  "))).err().unwrap();
+        assert_eq!(error.status, 400);
+    }
+
+    #[test]
+    fn test_non_json_request_passes_through_untouched() {
+        let stack = MiddlewareStack::new().use_middleware(BodyParserMiddleware);
+        let mut ctx = post("text/plain", "{not json");
+        ctx.response = Response::new().with_body("ok");
+        assert_eq!(block_on(stack.run(ctx)).unwrap().body.as_deref(), Some("ok"));
+
+        // A handler asking for JSON gets told it isn't there
+        let ctx = post("application/x-www-form-urlencoded", "email=ada%40example.com");
+        assert_eq!(ctx.json::<Signup>().err().unwrap().status, 415);
+    }
+
+    fn from_ip(ip: &str) -> Context {
+        let mut ctx = context(None);
+        ctx.request.peer_addr = Some(ip.parse().unwrap());
+        ctx
+    }
+
+    #[test]
+    fn test_request_over_limit_gets_429_with_retry_after() {
+        let now = Rc::new(Cell::new(1_000_000u64));
+        let clock = now.clone();
+        let stack = MiddlewareStack::new()
+            .use_middleware(RateLimitMiddleware::new(3, Duration::from_secs(60), RateLimitMiddleware::client_key(0))
+                .clock(move || clock.get()));
+
+        for remaining in ["2", "1", "0"] {
+            let response = block_on(stack.run(from_ip("203.0.113.7"))).unwrap();
+            assert_eq!(response.status, 200);
+            assert_eq!(response.headers["X-RateLimit-Remaining"], remaining);
+            now.set(now.get() + 10_000);
+        }
+
+        let response = block_on(stack.run(from_ip("203.0.113.7"))).unwrap();
+        assert_eq!(response.status, 429);
+        // The first request leaves the window 30s from now
+        assert_eq!(response.headers["Retry-After"], "30");
+
+        // Other clients have their own budget
+        assert_eq!(block_on(stack.run(from_ip("198.51.100.2"))).unwrap().status, 200);
+    }
+
+    #[test]
+    fn test_forwarded_for_is_only_read_behind_trusted_proxies() {
+        let spoofed = |peer: &str, forwarded_for: &str| {
+            let mut ctx = from_ip(peer);
+            ctx.request.headers.insert("X-Forwarded-For".to_string(), forwarded_for.to_string());
+            ctx
+        };
+
+        // Straight from the client, a made-up header doesn't buy a new bucket
+        let direct = MiddlewareStack::new()
+            .use_middleware(RateLimitMiddleware::new(1, Duration::from_secs(60), RateLimitMiddleware::client_key(0)));
+        assert_eq!(block_on(direct.run(spoofed("203.0.113.7", "1.1.1.1"))).unwrap().status, 200);
+        assert_eq!(block_on(direct.run(spoofed("203.0.113.7", "2.2.2.2"))).unwrap().status, 429);
+
+        // Behind one proxy the client is the address it appended, whatever
+        // the client put before it
+        let proxied = MiddlewareStack::new()
+            .use_middleware(RateLimitMiddleware::new(1, Duration::from_secs(60), RateLimitMiddleware::client_key(1)));
+        assert_eq!(block_on(proxied.run(spoofed("10.0.0.1", "1.1.1.1, 203.0.113.7"))).unwrap().status, 200);
+        assert_eq!(block_on(proxied.run(spoofed("10.0.0.1", "2.2.2.2, 203.0.113.7"))).unwrap().status, 429);
+        assert_eq!(block_on(proxied.run(spoofed("10.0.0.1", "198.51.100.2"))).unwrap().status, 200);
+
+        let mut real_ip = from_ip("10.0.0.1");
+        real_ip.request.headers.insert("X-Real-IP".to_string(), "192.0.2.9".to_string());
+        assert_eq!(client_ip(&real_ip.request, 1).as_deref(), Some("192.0.2.9"));
+        assert_eq!(client_ip(&real_ip.request, 0).as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_sliding_window_blocks_bursts_across_boundary() {
+        let store = InMemoryRateLimitStore::new();
+        let hit = |now| block_on(store.hit("ip:1", now, 1_000, 2));
+
+        // Two requests at the end of one window...
+        assert!(matches!(hit(900), RateLimit::Allowed { .. }));
+        assert!(matches!(hit(950), RateLimit::Allowed { .. }));
+        // ...leave no room at the start of the next
+        assert_eq!(hit(1_100), RateLimit::Limited { retry_after_ms: 800 });
+        assert!(matches!(hit(1_900), RateLimit::Allowed { remaining: 0 }));
+
+        // Quiet keys are swept once their window has passed
+        block_on(store.hit("ip:2", 5_000, 1_000, 2));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_unkeyed_requests_share_a_bucket() {
+        let stack = MiddlewareStack::new()
+            .use_middleware(RateLimitMiddleware::new(1, Duration::from_secs(1), RateLimitMiddleware::client_key(0)));
+        assert_eq!(block_on(stack.run(context(None))).unwrap().status, 200);
+        assert_eq!(block_on(stack.run(context(None))).unwrap().status, 429);
+
+        let mut signed_in = context(None);
+        signed_in.request.user = Some(User {
+            id: "user-1".to_string(),
+            username: "ada".to_string(),
+            email: "ada@example.com".to_string(),
+            roles: vec![],
+        });
+        assert_eq!(block_on(stack.run(signed_in)).unwrap().status, 200);
+    }
+}
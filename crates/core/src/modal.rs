@@ -0,0 +1,538 @@
+//! Modal Dialogs - L5
+//!
+//! A `ModalHandle` holds whether a dialog is open; `Modal` renders it. Bind
+//! the handle to a query parameter and the dialog becomes part of the URL:
+//! opening pushes a history entry, the back button closes it, and a link to
+//! `/users?modal=create` opens it on load.
+//!
+//! While open, Tab cycles through the dialog's controls; closing returns
+//! focus to whatever had it before.
+//!
+//! ```ignore
+//! let create = use_url_modal("modal", "create");
+//! Button::new("New user").on_click({ let create = create.clone(); move || create.open() });
+//! Modal::new(create, "Create user").children(vec![...])
+//! ```
+
+use crate::a11y::FocusTrap;
+use crate::component::{Component, Element, Props};
+use crate::hooks::use_persisted;
+use crate::reactive_v2::{get_current_component, queue_component_render};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+struct ModalInner {
+    open: Cell<bool>,
+    /// Query parameter and value that mean "open", e.g. `modal=create`
+    binding: Option<(String, String)>,
+    /// The open state has its own history entry, so closing goes back
+    pushed: Cell<bool>,
+    /// Component that rendered the modal last, re-rendered on changes
+    owner: Cell<Option<u32>>,
+    /// Keeps focus in the open dialog, then returns it to the opener
+    focus_trap: RefCell<FocusTrap>,
+}
+
+thread_local! {
+    static BOUND_MODALS: RefCell<Vec<Weak<ModalInner>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Shared open/closed state of a modal
+#[derive(Clone)]
+pub struct ModalHandle {
+    inner: Rc<ModalInner>,
+}
+
+impl Default for ModalHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModalHandle {
+    /// A modal that lives only in component state
+    pub fn new() -> Self {
+        ModalHandle {
+            inner: Rc::new(ModalInner {
+                open: Cell::new(false),
+                binding: None,
+                pushed: Cell::new(false),
+                owner: Cell::new(None),
+                focus_trap: RefCell::new(FocusTrap::new()),
+            }),
+        }
+    }
+
+    /// A modal that is open while the URL has `?{param}={value}`
+    pub fn bound_to_query(param: impl Into<String>, value: impl Into<String>) -> Self {
+        let handle = ModalHandle {
+            inner: Rc::new(ModalInner {
+                open: Cell::new(false),
+                binding: Some((param.into(), value.into())),
+                pushed: Cell::new(false),
+                owner: Cell::new(None),
+                focus_trap: RefCell::new(FocusTrap::new()),
+            }),
+        };
+
+        BOUND_MODALS.with(|modals| {
+            let mut modals = modals.borrow_mut();
+            modals.retain(|modal| modal.strong_count() > 0);
+            modals.push(Rc::downgrade(&handle.inner));
+        });
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            listen_for_history();
+            if let Some(search) = current_search() {
+                // A deep link has no entry of ours behind it to go back to
+                handle.inner.open.set(handle.matches(&search));
+            }
+        }
+
+        handle
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.inner.open.get()
+    }
+
+    pub fn open(&self) {
+        if self.is_open() {
+            return;
+        }
+
+        if let Some((param, value)) = &self.inner.binding {
+            if push_search(|search| query_with(search, param, Some(value))) {
+                self.inner.pushed.set(true);
+            }
+        }
+
+        self.set_open(true);
+    }
+
+    /// Close the modal. A bound modal opened in this page goes back in
+    /// history, so the entry it pushed doesn't linger behind the back button.
+    pub fn close(&self) {
+        if !self.is_open() {
+            return;
+        }
+
+        if let Some((param, _)) = &self.inner.binding {
+            // `popstate` closes the modal
+            if self.inner.pushed.replace(false) && history_back() {
+                return;
+            }
+            replace_search(|search| query_with(search, param, None));
+        }
+
+        self.set_open(false);
+    }
+
+    pub fn toggle(&self) {
+        if self.is_open() {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    fn matches(&self, search: &str) -> bool {
+        let Some((param, value)) = &self.inner.binding else {
+            return false;
+        };
+        crate::router_v2::parse_query(search).get(param) == Some(value)
+    }
+
+    /// Follow the URL after a history navigation
+    fn sync_with_search(&self, search: &str) {
+        if self.inner.binding.is_none() {
+            return;
+        }
+        let open = self.matches(search);
+        // Reached by back/forward, so there's an entry of ours to return to
+        self.inner.pushed.set(open);
+        self.set_open(open);
+    }
+
+    fn set_open(&self, open: bool) {
+        if self.inner.open.replace(open) != open {
+            if !open {
+                self.inner.focus_trap.borrow_mut().deactivate();
+            }
+            if let Some(owner) = self.inner.owner.get() {
+                queue_component_render(owner);
+            }
+        }
+    }
+
+    /// Trap focus in the dialog once the render has put it in the document
+    fn trap_focus_later(&self, dialog_id: &str) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let handle = self.clone();
+            let dialog_id = dialog_id.to_string();
+            gloo_timers::callback::Timeout::new(0, move || {
+                if !handle.is_open() || handle.inner.focus_trap.borrow().is_active() {
+                    return;
+                }
+                if let Some(dialog) = web_sys::window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.get_element_by_id(&dialog_id))
+                {
+                    handle.inner.focus_trap.borrow_mut().activate(&dialog);
+                }
+            })
+            .forget();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = dialog_id;
+    }
+}
+
+/// Modal state hook; the same handle comes back on every render
+pub fn use_modal() -> ModalHandle {
+    use_persisted(ModalHandle::new).borrow().clone()
+}
+
+/// Modal state hook bound to `?{param}={value}` in the URL
+pub fn use_url_modal(param: impl Into<String>, value: impl Into<String>) -> ModalHandle {
+    use_persisted(|| ModalHandle::bound_to_query(param, value)).borrow().clone()
+}
+
+/// `search` with `param` set to `value`, or removed when `value` is `None`.
+/// Other parameters keep their order.
+fn query_with(search: &str, param: &str, value: Option<&str>) -> String {
+    let mut pairs: Vec<String> = search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map(|(key, _)| key).unwrap_or(pair);
+            urlencoding::decode(key).map(|key| key != param).unwrap_or(true)
+        })
+        .map(str::to_string)
+        .collect();
+
+    if let Some(value) = value {
+        pairs.push(format!("{}={}", urlencoding::encode(param), urlencoding::encode(value)));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", pairs.join("&"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn current_search() -> Option<String> {
+    web_sys::window()?.location().search().ok()
+}
+
+/// Pathname, the new query and the hash
+#[cfg(target_arch = "wasm32")]
+fn url_with_search(update: impl FnOnce(&str) -> String) -> Option<String> {
+    let location = web_sys::window()?.location();
+    let search = update(&location.search().ok()?);
+    Some(format!("{}{}{}", location.pathname().ok()?, search, location.hash().ok()?))
+}
+
+/// Push a history entry with the query rewritten by `update`
+fn push_search(update: impl FnOnce(&str) -> String) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let (Some(url), Some(history)) = (url_with_search(update), web_sys::window().and_then(|w| w.history().ok())) else {
+            return false;
+        };
+        history.push_state_with_url(&JsValue::NULL, "", Some(&url)).is_ok()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = update;
+        false
+    }
+}
+
+fn replace_search(update: impl FnOnce(&str) -> String) {
+    #[cfg(target_arch = "wasm32")]
+    if let (Some(url), Some(history)) = (url_with_search(update), web_sys::window().and_then(|w| w.history().ok())) {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = update;
+}
+
+fn history_back() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()
+            .and_then(|w| w.history().ok())
+            .map(|history| history.back().is_ok())
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    false
+}
+
+/// Open or close every URL-bound modal to match `search`
+pub(crate) fn sync_bound_modals(search: &str) {
+    let modals: Vec<_> = BOUND_MODALS.with(|modals| modals.borrow().iter().filter_map(Weak::upgrade).collect());
+    for inner in modals {
+        ModalHandle { inner }.sync_with_search(search);
+    }
+}
+
+/// Sync bound modals on back/forward, installing one listener per page
+#[cfg(target_arch = "wasm32")]
+fn listen_for_history() {
+    thread_local! {
+        static LISTENING: Cell<bool> = const { Cell::new(false) };
+    }
+    if LISTENING.with(|listening| listening.replace(true)) {
+        return;
+    }
+
+    let closure = Closure::<dyn FnMut(web_sys::PopStateEvent)>::new(|_event| {
+        if let Some(search) = current_search() {
+            sync_bound_modals(&search);
+        }
+    });
+    if let Some(window) = web_sys::window() {
+        let _ = window.add_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref());
+    }
+    closure.forget();
+}
+
+/// Dialog rendered while its handle is open; Escape and the close button
+/// close it
+pub struct Modal {
+    handle: ModalHandle,
+    title: String,
+    id: String,
+    children: Vec<Element>,
+}
+
+impl Modal {
+    pub fn new(handle: ModalHandle, title: impl Into<String>) -> Self {
+        let title = title.into();
+        let id = format!(
+            "modal-{}",
+            title
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+        );
+        Modal {
+            handle,
+            title,
+            id,
+            children: vec![],
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+impl Component for Modal {
+    fn render(&self) -> Element {
+        if let Some(owner) = get_current_component() {
+            self.handle.inner.owner.set(Some(owner));
+        }
+
+        if !self.handle.is_open() {
+            return Element::Text(String::new());
+        }
+
+        self.handle.trap_focus_later(&self.id);
+
+        let title_id = format!("{}-title", self.id);
+        let on_keydown = {
+            let handle = self.handle.clone();
+            Rc::new(move |key: String| {
+                if key == "Escape" {
+                    handle.close();
+                }
+            }) as Rc<dyn Fn(String)>
+        };
+        let on_close = {
+            let handle = self.handle.clone();
+            Rc::new(move || handle.close()) as Rc<dyn Fn()>
+        };
+
+        let mut body = vec![
+            Element::Node {
+                tag: "h2".to_string(),
+                props: Box::new(Props {
+                    id: Some(title_id.clone()),
+                    attributes: vec![("style".to_string(), "margin: 0 0 1rem; font-size: 1.25rem".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text(self.title.clone())],
+            },
+            Element::Node {
+                tag: "button".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![
+                        ("type".to_string(), "button".to_string()),
+                        (
+                            "style".to_string(),
+                            "position: absolute; top: 0.75rem; right: 0.75rem; border: 0; background: none; font-size: 1.25rem; cursor: pointer".to_string(),
+                        ),
+                    ],
+                    on_click: Some(on_close),
+                    ..Default::default()
+                }
+                .aria_label("Close")),
+                children: vec![Element::Text("×".to_string())],
+            },
+        ];
+        body.extend(self.children.iter().cloned());
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("modal-backdrop".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    "position: fixed; inset: 0; z-index: 50; display: flex; align-items: center; justify-content: center; background: rgba(0, 0, 0, 0.5)".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children: vec![Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    id: Some(self.id.clone()),
+                    attributes: vec![
+                        ("aria-modal".to_string(), "true".to_string()),
+                        ("aria-labelledby".to_string(), title_id),
+                        (
+                            "style".to_string(),
+                            "position: relative; background: white; border-radius: 0.5rem; padding: 1.5rem; min-width: 20rem; max-width: 90vw".to_string(),
+                        ),
+                    ],
+                    on_keydown: Some(on_keydown),
+                    ..Default::default()
+                }
+                .role("dialog")
+                .tab_index(-1)),
+                children: body,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialog_props(modal: &Modal) -> Option<Props> {
+        let Element::Node { children, .. } = modal.render() else {
+            return None;
+        };
+        let Element::Node { props, .. } = &children[0] else {
+            panic!("expected dialog");
+        };
+        Some((**props).clone())
+    }
+
+    #[test]
+    fn test_query_with_sets_and_removes_param() {
+        assert_eq!(query_with("?tab=2&modal=edit", "modal", Some("create")), "?tab=2&modal=create");
+        assert_eq!(query_with("", "modal", Some("create")), "?modal=create");
+        assert_eq!(query_with("?modal=create&tab=2", "modal", None), "?tab=2");
+        assert_eq!(query_with("?modal=create", "modal", None), "");
+    }
+
+    #[test]
+    fn test_url_bound_modal_follows_history() {
+        let handle = use_url_modal("modal", "create");
+        let modal = Modal::new(handle.clone(), "Create user");
+        assert!(dialog_props(&modal).is_none());
+
+        // `/users?modal=create` opens it
+        handle.sync_with_search("?page=2&modal=create");
+        let props = dialog_props(&modal).unwrap();
+        assert!(props.attributes.contains(&("role".to_string(), "dialog".to_string())));
+        assert!(props
+            .attributes
+            .contains(&("aria-labelledby".to_string(), "modal-create-user-title".to_string())));
+
+        // Back to `/users?page=2` dismisses it
+        handle.sync_with_search("?page=2");
+        assert!(!handle.is_open());
+        assert!(dialog_props(&modal).is_none());
+
+        handle.sync_with_search("?modal=edit");
+        assert!(!handle.is_open());
+    }
+
+    /// Renders a modal and keeps the handle it got
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Dialog {
+        bound: bool,
+        handle: RefCell<Option<ModalHandle>>,
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Dialog {
+        fn render(&self) -> Element {
+            let handle = if self.bound { use_url_modal("modal", "edit") } else { use_modal() };
+            *self.handle.borrow_mut() = Some(handle.clone());
+            Modal::new(handle, "Edit").render()
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_modal_handle_survives_rerenders() {
+        for bound in [false, true] {
+            let dialog = Dialog {
+                bound,
+                handle: RefCell::new(None),
+            };
+            let mut tree = crate::testing::render(&dialog);
+            let handle = dialog.handle.borrow().clone().unwrap();
+            handle.open();
+            tree.rerender();
+            assert!(tree.find("#modal-edit").is_some());
+
+            let rerendered = dialog.handle.borrow().clone().unwrap();
+            assert!(Rc::ptr_eq(&handle.inner, &rerendered.inner));
+            // One entry per bound handle, however often it renders
+            let registered = BOUND_MODALS.with(|modals| modals.borrow().len());
+            assert_eq!(registered, usize::from(bound));
+        }
+    }
+
+    #[test]
+    fn test_escape_closes_modal() {
+        let handle = use_modal();
+        handle.open();
+        let modal = Modal::new(handle.clone(), "Delete project");
+
+        (dialog_props(&modal).unwrap().on_keydown.unwrap())("Escape".to_string());
+        assert!(!handle.is_open());
+
+        // Unbound modals ignore the URL
+        handle.sync_with_search("?modal=create");
+        assert!(!handle.is_open());
+    }
+}
@@ -0,0 +1,492 @@
+//! OAuth Sign-in - L4
+//!
+//! Authorization-code flow for third-party accounts, starting with GitHub:
+//!
+//! ```ignore
+//! let config = OAuthConfig::new("Iv1.client-id", "https://app.example.com/auth/callback");
+//! let mut auth = AuthService::new(Box::new(OAuthProvider::github(config)));
+//!
+//! // "Login with GitHub"
+//! auth.login_with_oauth()?;
+//!
+//! // On the `/auth/callback` route
+//! auth.handle_oauth_callback(&window.location().search()?).await?;
+//!
+//! // On later page loads, once GitHub confirms the saved token
+//! auth.resume_session().await?;
+//! ```
+//!
+//! The client secret never reaches the browser: the callback posts the code
+//! to `OAuthConfig::exchange_endpoint` on your server, which trades it for an
+//! access token (see `GitHubTokenExchange` with the `ssr` feature).
+//!
+//! A random `state` is kept in `sessionStorage` for the round trip and must
+//! come back unchanged, so a callback started by another site is rejected.
+
+use crate::auth::{AuthProvider, User};
+use crate::http::{self, FetchTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub const GITHUB_AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+pub const GITHUB_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+pub const GITHUB_USER_URL: &str = "https://api.github.com/user";
+
+/// Server route `exchange_endpoint` points at by default
+pub const DEFAULT_EXCHANGE_PATH: &str = "/api/auth/oauth/token";
+
+/// `sessionStorage` key holding the pending `state`
+pub const OAUTH_STATE_KEY: &str = "layer9_oauth_state";
+
+/// Why a sign-in didn't complete
+#[derive(Debug, Clone, PartialEq)]
+pub enum OAuthError {
+    /// The user declined on the provider's consent screen
+    AccessDenied,
+    /// `state` was missing or differs from the one we sent
+    StateMismatch,
+    MissingCode,
+    /// The provider reported another error
+    Provider(String),
+    /// Our server couldn't trade the code for a token
+    Exchange(String),
+    /// The token didn't get us a profile
+    Profile(String),
+}
+
+impl std::fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OAuthError::AccessDenied => write!(f, "Sign-in was cancelled"),
+            OAuthError::StateMismatch => write!(f, "Sign-in request expired or was tampered with, please try again"),
+            OAuthError::MissingCode => write!(f, "Authorization response had no code"),
+            OAuthError::Provider(message) => write!(f, "Authorization failed: {}", message),
+            OAuthError::Exchange(message) => write!(f, "Token exchange failed: {}", message),
+            OAuthError::Profile(message) => write!(f, "Could not load profile: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for OAuthError {}
+
+/// Public half of an OAuth app registration
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthConfig {
+    pub client_id: String,
+    /// Must match the callback URL registered with the provider
+    pub redirect_uri: String,
+    /// Defaults to the provider's profile scopes
+    pub scopes: Vec<String>,
+    /// Our server endpoint that exchanges the code for a token
+    pub exchange_endpoint: String,
+}
+
+impl OAuthConfig {
+    pub fn new(client_id: impl Into<String>, redirect_uri: impl Into<String>) -> Self {
+        OAuthConfig {
+            client_id: client_id.into(),
+            redirect_uri: redirect_uri.into(),
+            scopes: Vec::new(),
+            exchange_endpoint: DEFAULT_EXCHANGE_PATH.to_string(),
+        }
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    pub fn exchange_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.exchange_endpoint = endpoint.into();
+        self
+    }
+}
+
+/// Body posted to `exchange_endpoint`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExchangeRequest {
+    pub code: String,
+    pub redirect_uri: String,
+}
+
+/// Answer from `exchange_endpoint`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExchangeResponse {
+    pub access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubProfile {
+    id: u64,
+    login: String,
+    email: Option<String>,
+}
+
+/// Signs users in through an OAuth provider's consent screen
+#[derive(Clone)]
+pub struct OAuthProvider {
+    config: OAuthConfig,
+    authorize_url: String,
+    user_url: String,
+    /// Profiles of tokens obtained this page load
+    sessions: Rc<RefCell<HashMap<String, User>>>,
+}
+
+impl OAuthProvider {
+    /// Sign in with GitHub; asks for `read:user user:email` unless the
+    /// config names its own scopes
+    pub fn github(mut config: OAuthConfig) -> Self {
+        if config.scopes.is_empty() {
+            config.scopes = vec!["read:user".to_string(), "user:email".to_string()];
+        }
+        OAuthProvider {
+            config,
+            authorize_url: GITHUB_AUTHORIZE_URL.to_string(),
+            user_url: GITHUB_USER_URL.to_string(),
+            sessions: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    /// Consent screen URL carrying `state`
+    pub fn authorize_url(&self, state: &str) -> String {
+        format!(
+            "{}?client_id={}&redirect_uri={}&scope={}&state={}",
+            self.authorize_url,
+            urlencoding::encode(&self.config.client_id),
+            urlencoding::encode(&self.config.redirect_uri),
+            urlencoding::encode(&self.config.scopes.join(" ")),
+            urlencoding::encode(state),
+        )
+    }
+
+    /// Remember a fresh `state` and send the browser to the consent screen
+    pub fn begin(&self) -> Result<(), String> {
+        let state = new_state()?;
+        let window = web_sys::window().ok_or("No window")?;
+        window
+            .session_storage()
+            .ok()
+            .flatten()
+            .ok_or("Session storage not available")?
+            .set_item(OAUTH_STATE_KEY, &state)
+            .map_err(|_| "Failed to store sign-in state".to_string())?;
+        window
+            .location()
+            .assign(&self.authorize_url(&state))
+            .map_err(|_| "Failed to redirect to sign-in".to_string())
+    }
+
+    /// The authorization code in a callback query (`?code=...&state=...`),
+    /// once its `state` matches `expected_state`
+    pub fn parse_callback(&self, query: &str, expected_state: Option<&str>) -> Result<String, OAuthError> {
+        let params = crate::router_v2::parse_query(&format!("?{}", query.trim_start_matches('?')));
+
+        match (params.get("state"), expected_state) {
+            (Some(state), Some(expected)) if !expected.is_empty() && state == expected => {}
+            _ => return Err(OAuthError::StateMismatch),
+        }
+
+        match params.get("error").map(String::as_str) {
+            Some("access_denied") => return Err(OAuthError::AccessDenied),
+            Some(error) => {
+                let description = params.get("error_description").map(String::as_str).unwrap_or(error);
+                return Err(OAuthError::Provider(description.to_string()));
+            }
+            None => {}
+        }
+
+        params
+            .get("code")
+            .filter(|code| !code.is_empty())
+            .cloned()
+            .ok_or(OAuthError::MissingCode)
+    }
+
+    /// Finish the sign-in the browser was redirected back from
+    pub async fn complete(&self, query: &str) -> Result<(User, String), OAuthError> {
+        // Each state is good for one callback
+        let expected = web_sys::window()
+            .and_then(|w| w.session_storage().ok().flatten())
+            .and_then(|storage| {
+                let state = storage.get_item(OAUTH_STATE_KEY).ok().flatten();
+                let _ = storage.remove_item(OAUTH_STATE_KEY);
+                state
+            });
+        self.complete_with(query, expected.as_deref(), &FetchTransport).await
+    }
+
+    /// `complete` with the expected state and transport supplied by the caller
+    pub async fn complete_with(
+        &self,
+        query: &str,
+        expected_state: Option<&str>,
+        transport: &dyn Transport,
+    ) -> Result<(User, String), OAuthError> {
+        let code = self.parse_callback(query, expected_state)?;
+
+        let exchange = http::post(self.config.exchange_endpoint.as_str())
+            .json(&TokenExchangeRequest {
+                code,
+                redirect_uri: self.config.redirect_uri.clone(),
+            })
+            .error_for_status()
+            .send_with(transport)
+            .await
+            .and_then(|response| response.json::<TokenExchangeResponse>())
+            .map_err(|e| OAuthError::Exchange(e.to_string()))?;
+        let token = exchange.access_token;
+        let user = self.load_profile_with(&token, transport).await?;
+        Ok((user, token))
+    }
+
+    /// The profile `token` belongs to, asked of the provider. Restores a
+    /// session saved by an earlier page load, whose token alone is trusted.
+    pub async fn load_profile(&self, token: &str) -> Result<User, OAuthError> {
+        self.load_profile_with(token, &FetchTransport).await
+    }
+
+    /// `load_profile` with the transport supplied by the caller
+    pub async fn load_profile_with(&self, token: &str, transport: &dyn Transport) -> Result<User, OAuthError> {
+        let profile = http::get(self.user_url.as_str())
+            .bearer_token(token)
+            .header("Accept", "application/vnd.github+json")
+            .error_for_status()
+            .send_with(transport)
+            .await
+            .and_then(|response| response.json::<GitHubProfile>())
+            .map_err(|e| OAuthError::Profile(e.to_string()))?;
+
+        let user = User {
+            id: profile.id.to_string(),
+            username: profile.login,
+            email: profile.email.unwrap_or_default(),
+            roles: vec!["user".to_string()],
+        };
+        self.sessions.borrow_mut().insert(token.to_string(), user.clone());
+        Ok(user)
+    }
+}
+
+impl AuthProvider for OAuthProvider {
+    fn authenticate(&self, _username: &str, _password: &str) -> Result<(User, String), String> {
+        Err("This account signs in through its provider; use AuthService::login_with_oauth".to_string())
+    }
+
+    fn validate_token(&self, token: &str) -> Result<User, String> {
+        self.sessions
+            .borrow()
+            .get(token)
+            .cloned()
+            .ok_or_else(|| "Unknown OAuth session".to_string())
+    }
+
+    // GitHub OAuth app tokens don't expire
+    fn refresh_token(&self, token: &str) -> Result<String, String> {
+        Ok(token.to_string())
+    }
+
+    /// Provider tokens are opaque and the saved user is whatever was left in
+    /// `localStorage`, so only sessions whose profile was loaded this page
+    /// load pass; `AuthService::resume_session` loads it for the others
+    fn restore_session(&self, token: &str, _saved: Option<&User>) -> Result<User, String> {
+        self.validate_token(token)
+    }
+
+    fn as_oauth(&self) -> Option<&OAuthProvider> {
+        Some(self)
+    }
+}
+
+/// Unguessable `state` value, hex encoded
+fn new_state() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Server half of GitHub sign-in: trades codes for tokens with the client secret
+#[cfg(feature = "ssr")]
+#[derive(Clone)]
+pub struct GitHubTokenExchange {
+    client_id: String,
+    client_secret: String,
+}
+
+#[cfg(feature = "ssr")]
+impl GitHubTokenExchange {
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        GitHubTokenExchange {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        }
+    }
+
+    pub async fn exchange(&self, code: &str, redirect_uri: &str) -> Result<String, OAuthError> {
+        #[derive(Deserialize)]
+        struct GitHubToken {
+            access_token: Option<String>,
+            error: Option<String>,
+            error_description: Option<String>,
+        }
+
+        let response: GitHubToken = reqwest::Client::new()
+            .post(GITHUB_TOKEN_URL)
+            .header("Accept", "application/json")
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| OAuthError::Exchange(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OAuthError::Exchange(e.to_string()))?;
+
+        // GitHub answers 200 with an `error` field for bad or reused codes
+        match (response.access_token, response.error) {
+            (Some(token), None) => Ok(token),
+            (_, error) => Err(OAuthError::Exchange(
+                response
+                    .error_description
+                    .or(error)
+                    .unwrap_or_else(|| "no access token".to_string()),
+            )),
+        }
+    }
+
+    /// Axum router serving `POST /api/auth/oauth/token`
+    pub fn into_router(self) -> axum::Router {
+        use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json};
+
+        axum::Router::new()
+            .route(
+                DEFAULT_EXCHANGE_PATH,
+                post(
+                    |State(exchange): State<GitHubTokenExchange>, Json(request): Json<TokenExchangeRequest>| async move {
+                        match exchange.exchange(&request.code, &request.redirect_uri).await {
+                            Ok(access_token) => Json(TokenExchangeResponse { access_token }).into_response(),
+                            Err(error) => (
+                                StatusCode::BAD_REQUEST,
+                                Json(serde_json::json!({ "error": error.to_string() })),
+                            )
+                                .into_response(),
+                        }
+                    },
+                ),
+            )
+            .with_state(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::Method;
+    use crate::http::{AbortHandle, HttpRequest, Response};
+    use futures::executor::block_on;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    fn github() -> OAuthProvider {
+        OAuthProvider::github(OAuthConfig::new("Iv1.abc", "https://app.example.com/auth/callback"))
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        assert_eq!(
+            github().authorize_url("s3cr3t"),
+            "https://github.com/login/oauth/authorize?client_id=Iv1.abc\
+             &redirect_uri=https%3A%2F%2Fapp.example.com%2Fauth%2Fcallback\
+             &scope=read%3Auser%20user%3Aemail&state=s3cr3t"
+        );
+
+        let repo_scope = OAuthProvider::github(OAuthConfig::new("Iv1.abc", "/cb").scope("repo"));
+        assert!(repo_scope.authorize_url("x").contains("&scope=repo&"));
+        assert_ne!(new_state().unwrap(), new_state().unwrap());
+    }
+
+    #[test]
+    fn test_callback_state_validation() {
+        let provider = github();
+
+        assert_eq!(provider.parse_callback("?code=abc&state=s1", Some("s1")), Ok("abc".to_string()));
+        // Forged or replayed callbacks
+        assert_eq!(provider.parse_callback("?code=abc&state=evil", Some("s1")), Err(OAuthError::StateMismatch));
+        assert_eq!(provider.parse_callback("?code=abc", Some("s1")), Err(OAuthError::StateMismatch));
+        assert_eq!(provider.parse_callback("?code=abc&state=s1", None), Err(OAuthError::StateMismatch));
+        // The user clicked "Cancel"
+        assert_eq!(
+            provider.parse_callback("error=access_denied&error_description=The+user+denied&state=s1", Some("s1")),
+            Err(OAuthError::AccessDenied)
+        );
+        assert_eq!(provider.parse_callback("?state=s1", Some("s1")), Err(OAuthError::MissingCode));
+    }
+
+    /// Stands in for our exchange endpoint and the GitHub API
+    struct FakeGitHub;
+
+    impl Transport for FakeGitHub {
+        fn send(
+            &self,
+            request: HttpRequest,
+            _abort: Option<AbortHandle>,
+        ) -> Pin<Box<dyn Future<Output = Result<Response, http::HttpError>>>> {
+            let authorized = request.headers.contains(&("Authorization".to_string(), "Bearer gho_token".to_string()));
+            let response = match (request.method, request.url.as_str()) {
+                (Method::POST, DEFAULT_EXCHANGE_PATH) if request.body.as_deref().unwrap_or("").contains("\"code\":\"abc\"") => {
+                    Response::new(200, HashMap::new(), r#"{"access_token":"gho_token"}"#)
+                }
+                (Method::GET, GITHUB_USER_URL) if authorized => Response::new(
+                    200,
+                    HashMap::new(),
+                    r#"{"id":42,"login":"octocat","email":null,"name":"The Octocat"}"#,
+                ),
+                _ => Response::new(401, HashMap::new(), "bad credentials"),
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[test]
+    fn test_complete_loads_github_profile() {
+        let provider = github();
+        let (user, token) = block_on(provider.complete_with("?code=abc&state=s1", Some("s1"), &FakeGitHub)).unwrap();
+
+        assert_eq!(token, "gho_token");
+        assert_eq!((user.id.as_str(), user.username.as_str()), ("42", "octocat"));
+        assert_eq!(provider.validate_token("gho_token"), Ok(user));
+
+        let rejected = block_on(provider.complete_with("?code=stale&state=s1", Some("s1"), &FakeGitHub));
+        assert!(matches!(rejected, Err(OAuthError::Exchange(_))));
+    }
+
+    #[test]
+    fn test_saved_session_is_checked_with_github() {
+        let provider = github();
+        // A saved user edited to claim more than GitHub says
+        let saved = User {
+            id: "42".to_string(),
+            username: "octocat".to_string(),
+            email: String::new(),
+            roles: vec!["admin".to_string()],
+        };
+        assert!(provider.restore_session("gho_token", Some(&saved)).is_err());
+
+        let user = block_on(provider.load_profile_with("gho_token", &FakeGitHub)).unwrap();
+        assert_eq!(user.roles, vec!["user".to_string()]);
+        assert_eq!(provider.restore_session("gho_token", Some(&saved)), Ok(user));
+
+        let revoked = block_on(provider.load_profile_with("gho_revoked", &FakeGitHub));
+        assert!(matches!(revoked, Err(OAuthError::Profile(_))));
+    }
+}
@@ -0,0 +1,262 @@
+//! Portals - L5
+//!
+//! `Element::Portal` renders its children into another DOM node, usually
+//! `document.body`, so overlays escape an ancestor's `overflow: hidden` or
+//! stacking context:
+//!
+//! ```ignore
+//! Element::portal("body", vec![dialog])
+//! Element::portal("#tooltip-root", vec![tooltip])
+//! Element::portal(anchor.clone(), vec![menu])
+//! ```
+//!
+//! The children stay part of the component that rendered them: its
+//! re-renders patch them, they are removed when it unmounts, and they see
+//! the same context. Where the portal sits, the component's own DOM holds
+//! an empty comment. Each portal gets its own host element in the target,
+//! so several portals into one target sit side by side in render order.
+
+use crate::component::{Element, NodeRef};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use web_sys::{Document, Element as DomElement, Node};
+
+/// Comment text marking where a portal sits, followed by its id
+const PORTAL_MARKER: &str = "l9-portal:";
+
+static NEXT_PORTAL_ID: AtomicU32 = AtomicU32::new(1);
+
+thread_local! {
+    /// Host elements of mounted portals, by id
+    static PORTAL_HOSTS: RefCell<HashMap<u32, DomElement>> = RefCell::new(HashMap::new());
+}
+
+/// Where a portal's children are mounted
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortalTarget {
+    /// The first element matching a CSS selector. A missing `#id` target is
+    /// created at the end of the body.
+    Selector(String),
+    /// An element the application already holds
+    Node(NodeRef),
+}
+
+impl From<&str> for PortalTarget {
+    fn from(selector: &str) -> Self {
+        PortalTarget::Selector(selector.to_string())
+    }
+}
+
+impl From<String> for PortalTarget {
+    fn from(selector: String) -> Self {
+        PortalTarget::Selector(selector)
+    }
+}
+
+impl From<NodeRef> for PortalTarget {
+    fn from(node: NodeRef) -> Self {
+        PortalTarget::Node(node)
+    }
+}
+
+impl PortalTarget {
+    /// The target element, falling back to the body with a warning when it
+    /// can't be found or created
+    fn element(&self, document: &Document) -> DomElement {
+        let body: DomElement = document.body().expect("document has no body").into();
+        let found = match self {
+            PortalTarget::Selector(selector) => document
+                .query_selector(selector)
+                .ok()
+                .flatten()
+                .or_else(|| create_by_id(document, &body, selector)),
+            PortalTarget::Node(node) => node.get(),
+        };
+
+        found.unwrap_or_else(|| {
+            web_sys::console::warn_1(
+                &format!("Portal target {:?} not found; rendering into document.body", self).into(),
+            );
+            body
+        })
+    }
+}
+
+/// Create the element a `#id` selector names, as a portal root
+fn create_by_id(document: &Document, body: &DomElement, selector: &str) -> Option<DomElement> {
+    let id = selector
+        .strip_prefix('#')
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))?;
+    let root = document.create_element("div").ok()?;
+    root.set_id(id);
+    body.append_child(&root).ok()?;
+    Some(root)
+}
+
+/// Mount `children` into a new host inside `target` and return the
+/// placeholder to put where the portal sits
+pub(crate) fn mount(target: &PortalTarget, children: &[Element]) -> Node {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let id = NEXT_PORTAL_ID.fetch_add(1, Ordering::Relaxed);
+
+    let host = document.create_element("div").unwrap();
+    host.set_class_name("layer9-portal");
+    host.set_attribute("style", "display: contents").unwrap();
+    for child in children {
+        host.append_child(&child.to_dom()).unwrap();
+    }
+    target.element(&document).append_child(&host).unwrap();
+    PORTAL_HOSTS.with(|hosts| hosts.borrow_mut().insert(id, host));
+
+    document.create_comment(&format!("{}{}", PORTAL_MARKER, id)).into()
+}
+
+/// Id of the portal `node` is the placeholder of
+fn portal_id(node: &Node) -> Option<u32> {
+    if node.node_type() != Node::COMMENT_NODE {
+        return None;
+    }
+    node.text_content()?.strip_prefix(PORTAL_MARKER)?.parse().ok()
+}
+
+/// The element holding the children of the portal whose placeholder is
+/// `node`
+pub(crate) fn host(node: &Node) -> Option<DomElement> {
+    let id = portal_id(node)?;
+    PORTAL_HOSTS.with(|hosts| hosts.borrow().get(&id).cloned())
+}
+
+/// Remove the children of every portal placed inside `node`, including
+/// portals nested in them, before `node` leaves the document
+pub(crate) fn unmount_within(node: &Node) {
+    if let Some(id) = portal_id(node) {
+        if let Some(host) = PORTAL_HOSTS.with(|hosts| hosts.borrow_mut().remove(&id)) {
+            unmount_within(&host);
+            host.remove();
+        }
+        return;
+    }
+
+    let children = node.child_nodes();
+    for index in 0..children.length() {
+        if let Some(child) = children.item(index) {
+            unmount_within(&child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdom::{Patch, VDom};
+
+    fn portal(texts: &[&str]) -> Element {
+        Element::portal(
+            "body",
+            texts.iter().map(|text| Element::Text(text.to_string())).collect(),
+        )
+    }
+
+    #[test]
+    fn test_portal_leaves_a_placeholder_in_place() {
+        let page = Element::Node {
+            tag: "main".to_string(),
+            props: Box::default(),
+            children: vec![portal(&["Dialog"]), Element::Text("Content".to_string())],
+        };
+        assert_eq!(page.to_html(), "<main><!--l9-portal-->Content</main>");
+    }
+
+    #[test]
+    fn test_portal_children_diff_against_their_host() {
+        let vdom = VDom::new();
+        let wrap = |portal| Element::Node {
+            tag: "main".to_string(),
+            props: Box::default(),
+            children: vec![Element::Text("Content".to_string()), portal],
+        };
+
+        let patches = vdom.diff(&wrap(portal(&["One"])), &wrap(portal(&["One", "Two"])), &[]);
+        let [Patch::Portal { path, patches }] = patches.as_slice() else {
+            panic!("expected one portal patch");
+        };
+        assert_eq!(path, &vec![1]);
+        assert!(matches!(
+            patches.as_slice(),
+            [Patch::InsertChild { path, index: 1, .. }] if path.is_empty()
+        ));
+
+        // Moving a portal elsewhere mounts it afresh
+        let moved = wrap(Element::portal("#other", vec![]));
+        let patches = vdom.diff(&wrap(portal(&[])), &moved, &[]);
+        assert!(matches!(patches.as_slice(), [Patch::Replace { .. }]));
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use crate::component::Props;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn paragraph(id: &str) -> Element {
+        Element::Node {
+            tag: "p".to_string(),
+            props: Box::new(Props {
+                id: Some(id.to_string()),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(id.to_string())],
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_portal_children_mount_under_the_body() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let body = document.body().unwrap();
+
+        let root = document.create_element("div").unwrap();
+        root.set_id("portal-test-root");
+        body.append_child(&root).unwrap();
+
+        let page = Element::Node {
+            tag: "section".to_string(),
+            props: Box::default(),
+            children: vec![
+                Element::portal("body", vec![paragraph("portal-first")]),
+                Element::portal("body", vec![paragraph("portal-second")]),
+                Element::portal("#portal-created-root", vec![paragraph("portal-created")]),
+            ],
+        };
+        let node = page.to_dom();
+        root.append_child(&node).unwrap();
+
+        for id in ["portal-first", "portal-second"] {
+            let child = document.get_element_by_id(id).unwrap();
+            assert!(!root.contains(Some(child.as_ref())));
+            let host = child.parent_element().unwrap();
+            assert_eq!(host.class_name(), "layer9-portal");
+            assert!(host.parent_element().unwrap().is_same_node(Some(body.as_ref())));
+        }
+
+        // Both portals into the body were appended, in order
+        let first = document.get_element_by_id("portal-first").unwrap();
+        let second = document.get_element_by_id("portal-second").unwrap();
+        assert!(first.parent_element().unwrap().next_element_sibling().unwrap().contains(Some(second.as_ref())));
+
+        // A missing #id target is created
+        let created = document.get_element_by_id("portal-created-root").unwrap();
+        assert!(created.query_selector("#portal-created").unwrap().is_some());
+
+        unmount_within(&node);
+        root.remove();
+        for id in ["portal-first", "portal-second", "portal-created"] {
+            assert!(document.get_element_by_id(id).is_none());
+        }
+        created.remove();
+    }
+}
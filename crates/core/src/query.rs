@@ -0,0 +1,321 @@
+//! Data Fetching Hook - L5
+//!
+//! `use_query` runs an async fetcher when a component first renders, tracks
+//! loading/data/error for it and re-renders the component when it settles.
+//! Results are cached per key, and components that query the same key while a
+//! fetch is in flight share that fetch instead of starting another.
+
+use crate::hooks::use_ref;
+use crate::reactive_v2::{get_current_component, queue_component_render};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::{Rc, Weak};
+
+type RefetchFn = Rc<dyn Fn()>;
+
+thread_local! {
+    static QUERIES: RefCell<HashMap<String, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static LOCAL_POOL: RefCell<futures::executor::LocalPool> =
+        RefCell::new(futures::executor::LocalPool::new());
+    static LOCAL_SPAWNER: futures::executor::LocalSpawner =
+        LOCAL_POOL.with(|pool| pool.borrow().spawner());
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn(future: impl Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn(future: impl Future<Output = ()> + 'static) {
+    use futures::task::LocalSpawnExt;
+    LOCAL_SPAWNER.with(|spawner| {
+        let _ = spawner.spawn_local(future);
+    });
+}
+
+/// Drive in-flight queries outside the browser (SSR, tests)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_pending_queries() {
+    LOCAL_POOL.with(|pool| pool.borrow_mut().run_until_stalled());
+}
+
+struct QueryState<T, E> {
+    is_loading: bool,
+    data: Option<Rc<T>>,
+    error: Option<Rc<E>>,
+}
+
+/// Shared state for one query key
+struct QueryCell<T, E> {
+    state: RefCell<QueryState<T, E>>,
+    in_flight: Cell<bool>,
+    started: Cell<bool>,
+    // Components that rendered this query; dead entries belong to unmounted components
+    subscribers: RefCell<Vec<(Weak<RefCell<()>>, u32)>>,
+}
+
+impl<T: 'static, E: 'static> QueryCell<T, E> {
+    fn for_key(key: &str) -> Rc<Self> {
+        QUERIES.with(|queries| {
+            queries
+                .borrow_mut()
+                .entry(key.to_string())
+                .or_insert_with(|| {
+                    Box::new(Rc::new(QueryCell::<T, E> {
+                        state: RefCell::new(QueryState {
+                            is_loading: true,
+                            data: None,
+                            error: None,
+                        }),
+                        in_flight: Cell::new(false),
+                        started: Cell::new(false),
+                        subscribers: RefCell::new(Vec::new()),
+                    }))
+                })
+                .downcast_ref::<Rc<QueryCell<T, E>>>()
+                .unwrap_or_else(|| panic!("use_query key {:?} reused with a different type", key))
+                .clone()
+        })
+    }
+
+    fn subscribe(&self, token: &Rc<RefCell<()>>, component_id: u32) {
+        let mut subscribers = self.subscribers.borrow_mut();
+        subscribers.retain(|(subscriber, _)| subscriber.strong_count() > 0);
+        if !subscribers
+            .iter()
+            .any(|(subscriber, _)| subscriber.as_ptr() == Rc::as_ptr(token))
+        {
+            subscribers.push((Rc::downgrade(token), component_id));
+        }
+    }
+
+    fn notify(&self) {
+        let component_ids: Vec<u32> = {
+            let mut subscribers = self.subscribers.borrow_mut();
+            subscribers.retain(|(subscriber, _)| subscriber.strong_count() > 0);
+            subscribers.iter().map(|(_, id)| *id).collect()
+        };
+
+        for component_id in component_ids {
+            queue_component_render(component_id);
+        }
+    }
+
+    fn start<F, Fut>(self: &Rc<Self>, fetcher: &F, notify_loading: bool)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>> + 'static,
+    {
+        self.started.set(true);
+        self.state.borrow_mut().is_loading = true;
+        if self.in_flight.replace(true) {
+            return;
+        }
+        if notify_loading {
+            self.notify();
+        }
+
+        let future = fetcher();
+        let cell = Rc::downgrade(self);
+        spawn(async move {
+            let result = future.await;
+            let Some(cell) = cell.upgrade() else {
+                return;
+            };
+
+            {
+                let mut state = cell.state.borrow_mut();
+                state.is_loading = false;
+                match result {
+                    Ok(data) => {
+                        state.data = Some(Rc::new(data));
+                        state.error = None;
+                    }
+                    Err(error) => state.error = Some(Rc::new(error)),
+                }
+            }
+            cell.in_flight.set(false);
+            cell.notify();
+        });
+    }
+
+    fn snapshot(&self, refetch: RefetchFn) -> QueryResult<T, E> {
+        let state = self.state.borrow();
+        QueryResult {
+            is_loading: state.is_loading,
+            data: state.data.clone(),
+            error: state.error.clone(),
+            refetch,
+        }
+    }
+}
+
+/// State of a query at render time
+pub struct QueryResult<T, E> {
+    pub is_loading: bool,
+    /// Last successful result; kept while refetching or after a failed refetch
+    pub data: Option<Rc<T>>,
+    pub error: Option<Rc<E>>,
+    refetch: RefetchFn,
+}
+
+impl<T, E> Clone for QueryResult<T, E> {
+    fn clone(&self) -> Self {
+        QueryResult {
+            is_loading: self.is_loading,
+            data: self.data.clone(),
+            error: self.error.clone(),
+            refetch: self.refetch.clone(),
+        }
+    }
+}
+
+impl<T, E> QueryResult<T, E> {
+    /// Run the fetcher again; `is_loading` is true until it settles
+    pub fn refetch(&self) {
+        (self.refetch)();
+    }
+}
+
+/// Query hook - fetch data for `key`, re-rendering the component when it arrives
+pub fn use_query<T, E, F, Fut>(key: &str, fetcher: F) -> QueryResult<T, E>
+where
+    T: 'static,
+    E: 'static,
+    F: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+{
+    let cell = QueryCell::<T, E>::for_key(key);
+
+    // Outside a component there is nothing to re-render, so skip the subscription
+    if let Some(component_id) = get_current_component() {
+        let token = use_ref(());
+        cell.subscribe(&token, component_id);
+    }
+
+    if !cell.started.get() {
+        cell.start(&fetcher, false);
+    }
+
+    let refetch: RefetchFn = {
+        let cell = Rc::downgrade(&cell);
+        Rc::new(move || {
+            if let Some(cell) = cell.upgrade() {
+                cell.start(&fetcher, true);
+            }
+        })
+    };
+
+    cell.snapshot(refetch)
+}
+
+/// Drop the cached result for `key` so the next render fetches again
+pub fn invalidate_query(key: &str) {
+    QUERIES.with(|queries| {
+        queries.borrow_mut().remove(key);
+    });
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::hooks::{cleanup_component_hooks, reset_hook_index};
+    use crate::reactive_v2::{init_renderer, with_current_component};
+    use futures::channel::oneshot;
+
+    fn render<T>(component_id: u32, f: impl FnOnce() -> T) -> T {
+        init_renderer();
+        reset_hook_index();
+        with_current_component(component_id, f)
+    }
+
+    #[test]
+    fn test_success_then_error() {
+        let calls = Rc::new(Cell::new(0));
+        let fetcher = {
+            let calls = calls.clone();
+            move || {
+                let call = calls.get() + 1;
+                calls.set(call);
+                async move {
+                    if call == 1 {
+                        Ok(42)
+                    } else {
+                        Err("rate limited".to_string())
+                    }
+                }
+            }
+        };
+
+        let query = render(101, || use_query("success-then-error", fetcher.clone()));
+        assert!(query.is_loading);
+        assert!(query.data.is_none());
+
+        run_pending_queries();
+        let query = render(101, || use_query("success-then-error", fetcher.clone()));
+        assert!(!query.is_loading);
+        assert_eq!(query.data.as_deref(), Some(&42));
+        assert!(query.error.is_none());
+
+        query.refetch();
+        let refetching = render(101, || use_query("success-then-error", fetcher.clone()));
+        assert!(refetching.is_loading);
+
+        run_pending_queries();
+        let query = render(101, || use_query("success-then-error", fetcher.clone()));
+        assert!(!query.is_loading);
+        assert_eq!(query.error.as_deref().map(String::as_str), Some("rate limited"));
+        assert_eq!(query.data.as_deref(), Some(&42));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_fetches_are_deduped() {
+        let calls = Rc::new(Cell::new(0));
+        let fetcher = {
+            let calls = calls.clone();
+            move || {
+                calls.set(calls.get() + 1);
+                async { Ok::<_, ()>("stats") }
+            }
+        };
+
+        render(201, || use_query("deduped", fetcher.clone()));
+        render(202, || use_query("deduped", fetcher.clone())).refetch();
+        run_pending_queries();
+
+        let query = render(202, || use_query("deduped", fetcher.clone()));
+        assert_eq!(query.data.as_deref(), Some(&"stats"));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_unmount_mid_fetch_does_not_rerender() {
+        let (sender, receiver) = oneshot::channel::<u32>();
+        let receiver = Rc::new(RefCell::new(Some(receiver)));
+        let fetcher = move || {
+            let receiver = receiver.borrow_mut().take();
+            async move {
+                match receiver {
+                    Some(receiver) => receiver.await.map_err(|_| ()),
+                    None => Err(()),
+                }
+            }
+        };
+
+        render(301, || use_query("unmounted", fetcher));
+        cleanup_component_hooks(301);
+
+        // Completing the fetch must not try to re-render the unmounted component
+        sender.send(7).unwrap();
+        run_pending_queries();
+    }
+}
@@ -2,7 +2,7 @@
 //! 
 //! Fixed version that avoids borrowing issues by deferring effect execution
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use web_sys::{Element as DomElement, Node};
 use wasm_bindgen::JsCast;
@@ -15,6 +15,8 @@ type PendingEffect = (ComponentId, Box<dyn FnOnce() -> EffectCleanup>);
 thread_local! {
     static RENDERER: RefCell<Option<Renderer>> = const { RefCell::new(None) };
     static PENDING_EFFECTS: RefCell<Vec<PendingEffect>> = RefCell::new(Vec::new());
+    /// Set while components render to HTML on the server
+    static SERVER_RENDER: Cell<bool> = const { Cell::new(false) };
 }
 
 /// Initialize the global renderer
@@ -88,6 +90,29 @@ impl Renderer {
         self.run_pending_effects();
     }
 
+    /// Adopt the server-rendered DOM inside `root_id` for `component`
+    /// instead of building it, repairing where it differs
+    pub fn hydrate_root(&mut self, component: Box<dyn Component>, root_id: &str) {
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        let root_element = document
+            .get_element_by_id(root_id)
+            .expect("Root element not found");
+
+        self.root_element = Some(root_element.clone());
+
+        let component_id = self.create_component_instance(component, None);
+
+        crate::hooks::reset_hook_index();
+        if let Some(instance) = self.components.get_mut(&component_id) {
+            let vdom = with_current_component(component_id, || instance.component.render());
+            instance.dom_node = Some(crate::hydrate::hydrate(&vdom, &root_element));
+            instance.vdom = Some(vdom);
+        }
+
+        self.run_pending_effects();
+    }
+
     /// Create a new component instance
     fn create_component_instance(
         &mut self,
@@ -152,7 +177,10 @@ impl Renderer {
         
         // Get component and render new VDOM
         let (new_vdom, old_vdom) = {
-            let instance = self.components.get(&component_id).unwrap();
+            // The component may have unmounted since the render was queued
+            let Some(instance) = self.components.get(&component_id) else {
+                return;
+            };
             // Render within component context for hooks
             let new_vdom = with_current_component(component_id, || {
                 instance.component.render()
@@ -260,12 +288,14 @@ impl Renderer {
                 self.unmount_component(child_id);
             }
 
-            // Remove from DOM
+            // Remove from DOM, along with anything it portaled elsewhere
             if let Some(dom_node) = instance.dom_node {
+                crate::portal::unmount_within(&dom_node);
                 if let Some(parent) = dom_node.parent_node() {
                     parent.remove_child(&dom_node).unwrap();
                 }
             }
+            crate::head::schedule_sync();
         }
     }
 }
@@ -277,17 +307,17 @@ thread_local! {
 }
 
 pub fn with_current_component<T>(component_id: ComponentId, f: impl FnOnce() -> T) -> T {
-    CURRENT_COMPONENT.with(|c| {
-        *c.borrow_mut() = Some(component_id);
-    });
-    
-    let result = f();
-    
-    CURRENT_COMPONENT.with(|c| {
-        *c.borrow_mut() = None;
-    });
-    
-    result
+    // Restored afterwards, as memoized children render inside their parent,
+    // and on a panic, so a failed server render doesn't leak into the next
+    struct Restore(Option<ComponentId>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT_COMPONENT.with(|c| *c.borrow_mut() = self.0);
+        }
+    }
+
+    let _restore = Restore(CURRENT_COMPONENT.with(|c| c.borrow_mut().replace(component_id)));
+    f()
 }
 
 pub fn get_current_component() -> Option<ComponentId> {
@@ -302,6 +332,7 @@ fn get_current_component_id() -> Option<ComponentId> {
 /// Queue a re-render for the current component
 pub fn queue_current_render() {
     if let Some(component_id) = get_current_component_id() {
+        let component_id = crate::hooks::render_target(component_id);
         RENDERER.with(|r| {
             if let Some(renderer) = r.borrow_mut().as_mut() {
                 renderer.queue_render(component_id);
@@ -312,6 +343,7 @@ pub fn queue_current_render() {
 
 /// Queue a specific component for re-rendering by ID
 pub fn queue_component_render(component_id: ComponentId) {
+    let component_id = crate::hooks::render_target(component_id);
     RENDERER.with(|r| {
         if let Some(renderer) = r.borrow_mut().as_mut() {
             renderer.queue_render(component_id);
@@ -319,23 +351,74 @@ pub fn queue_component_render(component_id: ComponentId) {
     });
 }
 
+/// Whether components are rendering to HTML on the server, where browser
+/// APIs are unavailable. Reach for `window()` and friends in effects, which
+/// only run in the browser, or check this first.
+pub fn is_server_render() -> bool {
+    SERVER_RENDER.with(Cell::get)
+}
+
+/// Run `f` as a server render: effects are dropped instead of queued
+#[cfg(feature = "ssr")]
+pub(crate) fn server_render<T>(f: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SERVER_RENDER.with(|flag| flag.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SERVER_RENDER.with(|flag| flag.replace(true)));
+    f()
+}
+
 /// Queue an effect to run after rendering completes
 pub fn queue_effect_for_current_component(effect: impl FnOnce() -> EffectCleanup + 'static) {
+    // The markup is all a server render produces
+    if is_server_render() {
+        return;
+    }
     if let Some(component_id) = get_current_component() {
+        let component_id = crate::hooks::effect_owner(component_id);
         PENDING_EFFECTS.with(|e| {
             e.borrow_mut().push((component_id, Box::new(effect)));
         });
     }
 }
 
+/// Remove and return the effects `component_id` has queued, leaving other
+/// components' in place
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn take_pending_effects(component_id: ComponentId) -> Vec<Box<dyn FnOnce() -> EffectCleanup>> {
+    PENDING_EFFECTS.with(|effects| {
+        let (mine, others): (Vec<_>, Vec<_>) = std::mem::take(&mut *effects.borrow_mut())
+            .into_iter()
+            .partition(|(id, _)| *id == component_id);
+        *effects.borrow_mut() = others;
+        mine.into_iter().map(|(_, effect)| effect).collect()
+    })
+}
+
 /// Run an effect for the current component (deferred)
 pub fn run_current_effect(effect: impl FnOnce() -> EffectCleanup + 'static) {
     queue_effect_for_current_component(effect);
 }
 
+/// Render `component` with the global renderer without attaching it to the page
+#[cfg(all(test, target_arch = "wasm32"))]
+pub(crate) fn register_test_component(component: Box<dyn Component>) -> ComponentId {
+    RENDERER.with(|r| {
+        r.borrow_mut()
+            .as_mut()
+            .expect("call init_renderer first")
+            .register_component(component, None)
+    })
+}
+
 /// Mount a component to the DOM
 pub fn mount(component: Box<dyn Component>, root_id: &str) {
     init_renderer();
+    crate::head::ensure_viewport();
     
     RENDERER.with(|r| {
         if let Some(renderer) = r.borrow_mut().as_mut() {
@@ -344,11 +427,24 @@ pub fn mount(component: Box<dyn Component>, root_id: &str) {
     });
 }
 
+/// Take over a server-rendered page: like `mount`, but the DOM the server
+/// rendered into `root_id` is kept and brought to life
+pub fn hydrate(component: Box<dyn Component>, root_id: &str) {
+    init_renderer();
+    crate::head::ensure_viewport();
+
+    RENDERER.with(|r| {
+        if let Some(renderer) = r.borrow_mut().as_mut() {
+            renderer.hydrate_root(component, root_id);
+        }
+    });
+}
+
 #[cfg(test)]
 #[allow(dead_code)]
 mod tests {
     use super::*;
-    use crate::component::{Component, Element, Props};
+    use crate::component::{Component, Element};
     use std::rc::Rc;
     use wasm_bindgen_test::*;
 
@@ -434,7 +530,7 @@ mod tests {
             fn render(&self) -> Element {
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Component(Box::new(ChildComponent))],
                 }
             }
@@ -0,0 +1,465 @@
+//! REST Resources - L4
+//!
+//! Maps the five CRUD routes of a model onto its `Repository`:
+//!
+//! | Route              | Repository          | Success |
+//! |--------------------|---------------------|---------|
+//! | `GET /`            | `find_all`          | 200     |
+//! | `GET /:id`         | `find_by_id`        | 200     |
+//! | `POST /`           | `insert`            | 201     |
+//! | `PUT /:id`         | `update`            | 200     |
+//! | `DELETE /:id`      | `delete`            | 204     |
+//!
+//! `GET /?page=2&page_size=20` answers a `Page` envelope
+//! (`{ data, page, page_size, total, total_pages }`) instead of a bare array.
+//! Missing rows answer 404, malformed bodies 400, and other database errors
+//! use the same status mapping as the database API. With the `ssr` feature,
+//! `resource` turns this into an axum router:
+//!
+//! ```ignore
+//! let app = Router::new().nest("/users", resource::<User, _>(repo));
+//! ```
+
+use crate::db::{DatabaseConnection, DbError, DbErrorKind, Model, Repository};
+use crate::fetch::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Status and JSON body produced by a resource route
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+impl ResourceResponse {
+    fn json<T: Serialize>(status: u16, data: &T) -> Self {
+        match serde_json::to_value(data) {
+            Ok(body) => ResourceResponse {
+                status,
+                body: Some(body),
+            },
+            Err(e) => Self::error(500, e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        ResourceResponse {
+            status,
+            body: Some(json!({ "error": message.into() })),
+        }
+    }
+
+    fn from_db_error(error: DbError) -> Self {
+        let status = match error.kind {
+            DbErrorKind::NotFound => 404,
+            DbErrorKind::UniqueViolation => 409,
+            DbErrorKind::ForeignKeyViolation => 400,
+            DbErrorKind::Timeout => 408,
+            DbErrorKind::InvalidColumn => 400,
+            _ => 500,
+        };
+        Self::error(status, error.message)
+    }
+}
+
+/// CRUD routes for one model
+pub struct Resource<M: Model, C: DatabaseConnection> {
+    repo: Arc<Repository<M, C>>,
+}
+
+impl<M: Model, C: DatabaseConnection> Clone for Resource<M, C> {
+    fn clone(&self) -> Self {
+        Resource {
+            repo: self.repo.clone(),
+        }
+    }
+}
+
+impl<M: Model, C: DatabaseConnection> Resource<M, C> {
+    pub fn new(repo: Repository<M, C>) -> Self {
+        Resource {
+            repo: Arc::new(repo),
+        }
+    }
+
+    /// Dispatch a request; `path` is relative to the resource (`/` or `/:id`)
+    pub async fn handle(&self, method: Method, path: &str, body: Option<&str>) -> ResourceResponse {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let id = path.trim_matches('/');
+        match (method, id.is_empty()) {
+            (Method::GET, true) => match page_params(query) {
+                Ok(Some((page, page_size))) => self.list_page(page, page_size).await,
+                Ok(None) => self.list().await,
+                Err(response) => response,
+            },
+            (Method::POST, true) => self.create(body).await,
+            (Method::GET, false) => self.show(id).await,
+            (Method::PUT, false) => self.update(id, body).await,
+            (Method::DELETE, false) => self.destroy(id).await,
+            _ => ResourceResponse::error(405, "Method not allowed"),
+        }
+    }
+
+    pub async fn list(&self) -> ResourceResponse {
+        match self.repo.find_all().await {
+            Ok(models) => ResourceResponse::json(200, &models),
+            Err(e) => ResourceResponse::from_db_error(e),
+        }
+    }
+
+    pub async fn list_page(&self, page: u32, page_size: u32) -> ResourceResponse {
+        match self.repo.paginate(page, page_size).await {
+            Ok(page) => ResourceResponse::json(200, &page),
+            Err(e) => ResourceResponse::from_db_error(e),
+        }
+    }
+
+    pub async fn show(&self, id: &str) -> ResourceResponse {
+        match self.require(id).await {
+            Ok(model) => ResourceResponse::json(200, &model),
+            Err(response) => response,
+        }
+    }
+
+    pub async fn create(&self, body: Option<&str>) -> ResourceResponse {
+        let model: M = match serde_json::from_str(body.unwrap_or_default()) {
+            Ok(model) => model,
+            Err(e) => return ResourceResponse::error(400, e.to_string()),
+        };
+
+        match self.repo.insert(&model).await {
+            Ok(created) => ResourceResponse::json(201, &created),
+            Err(e) => ResourceResponse::from_db_error(e),
+        }
+    }
+
+    pub async fn update(&self, id: &str, body: Option<&str>) -> ResourceResponse {
+        let mut updates: HashMap<String, Value> = match serde_json::from_str(body.unwrap_or_default()) {
+            Ok(updates) => updates,
+            Err(e) => return ResourceResponse::error(400, e.to_string()),
+        };
+        // The id comes from the path; a body can't move a row to another key
+        updates.remove(M::primary_key());
+        if updates.is_empty() {
+            return ResourceResponse::error(400, "No fields to update");
+        }
+
+        if let Err(response) = self.require(id).await {
+            return response;
+        }
+        match self.repo.update(id_value(id), updates).await {
+            Ok(updated) => ResourceResponse::json(200, &updated),
+            Err(e) => ResourceResponse::from_db_error(e),
+        }
+    }
+
+    pub async fn destroy(&self, id: &str) -> ResourceResponse {
+        if let Err(response) = self.require(id).await {
+            return response;
+        }
+        match self.repo.delete(id_value(id)).await {
+            Ok(()) => ResourceResponse {
+                status: 204,
+                body: None,
+            },
+            Err(e) => ResourceResponse::from_db_error(e),
+        }
+    }
+
+    /// The row with this id, or the 404 to answer with
+    async fn require(&self, id: &str) -> Result<M, ResourceResponse> {
+        match self.repo.find_by_id(id_value(id)).await {
+            Ok(Some(model)) => Ok(model),
+            Ok(None) => Err(ResourceResponse::error(
+                404,
+                format!("No {} row with {} = {}", M::table_name(), M::primary_key(), id),
+            )),
+            Err(e) => Err(ResourceResponse::from_db_error(e)),
+        }
+    }
+}
+
+/// Numeric path ids are passed to the database as numbers
+fn id_value(id: &str) -> Value {
+    id.parse::<i64>()
+        .map(Value::from)
+        .unwrap_or_else(|_| Value::from(id))
+}
+
+/// Page size used when only `page` is given
+const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// `page`/`page_size` from a query string; `None` when neither is present
+fn page_params(query: &str) -> Result<Option<(u32, u32)>, ResourceResponse> {
+    let mut page = None;
+    let mut page_size = None;
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let slot = match key {
+            "page" => &mut page,
+            "page_size" => &mut page_size,
+            _ => continue,
+        };
+        match value.parse::<u32>() {
+            Ok(n) if n > 0 => *slot = Some(n),
+            _ => return Err(ResourceResponse::error(400, format!("Invalid {}: {}", key, value))),
+        }
+    }
+
+    if page.is_none() && page_size.is_none() {
+        return Ok(None);
+    }
+    Ok(Some((page.unwrap_or(1), page_size.unwrap_or(DEFAULT_PAGE_SIZE))))
+}
+
+#[cfg(feature = "ssr")]
+mod router {
+    use super::*;
+    use axum::{
+        extract::{Path, RawQuery, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+        Json, Router,
+    };
+
+    impl IntoResponse for ResourceResponse {
+        fn into_response(self) -> Response {
+            let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            match self.body {
+                Some(body) => (status, Json(body)).into_response(),
+                None => status.into_response(),
+            }
+        }
+    }
+
+    /// Axum router exposing the CRUD routes for `M`; nest it under the collection path
+    pub fn resource<M, C>(repo: Repository<M, C>) -> Router
+    where
+        M: Model + Send + Sync + 'static,
+        C: DatabaseConnection,
+    {
+        async fn list<M: Model + Send + Sync, C: DatabaseConnection>(
+            State(resource): State<Resource<M, C>>,
+            RawQuery(query): RawQuery,
+        ) -> ResourceResponse {
+            match page_params(query.as_deref().unwrap_or_default()) {
+                Ok(Some((page, page_size))) => resource.list_page(page, page_size).await,
+                Ok(None) => resource.list().await,
+                Err(response) => response,
+            }
+        }
+
+        async fn create<M: Model + Send + Sync, C: DatabaseConnection>(
+            State(resource): State<Resource<M, C>>,
+            body: String,
+        ) -> ResourceResponse {
+            resource.create(Some(&body)).await
+        }
+
+        async fn show<M: Model + Send + Sync, C: DatabaseConnection>(
+            State(resource): State<Resource<M, C>>,
+            Path(id): Path<String>,
+        ) -> ResourceResponse {
+            resource.show(&id).await
+        }
+
+        async fn update<M: Model + Send + Sync, C: DatabaseConnection>(
+            State(resource): State<Resource<M, C>>,
+            Path(id): Path<String>,
+            body: String,
+        ) -> ResourceResponse {
+            resource.update(&id, Some(&body)).await
+        }
+
+        async fn destroy<M: Model + Send + Sync, C: DatabaseConnection>(
+            State(resource): State<Resource<M, C>>,
+            Path(id): Path<String>,
+        ) -> ResourceResponse {
+            resource.destroy(&id).await
+        }
+
+        Router::new()
+            .route("/", get(list::<M, C>).post(create::<M, C>))
+            .route(
+                "/:id",
+                get(show::<M, C>).put(update::<M, C>).delete(destroy::<M, C>),
+            )
+            .with_state(Resource::new(repo))
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use router::resource;
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::db::QueryResult;
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: i64,
+        name: String,
+    }
+
+    impl Model for User {
+        const TABLE_NAME: &'static str = "users";
+    }
+
+    /// Understands the statements `Repository` generates for a single table
+    #[derive(Default)]
+    struct MemoryDb {
+        rows: Mutex<Vec<Value>>,
+    }
+
+    fn not_found() -> DbError {
+        DbError {
+            kind: DbErrorKind::NotFound,
+            message: "Row not found".to_string(),
+        }
+    }
+
+    fn columns(list: &str) -> Vec<String> {
+        list.split(',').map(|c| c.trim().to_string()).collect()
+    }
+
+    #[async_trait]
+    impl DatabaseConnection for MemoryDb {
+        async fn execute(&self, query: &str, params: Vec<Value>) -> Result<QueryResult, DbError> {
+            let mut rows = self.rows.lock().unwrap();
+            let before = rows.len();
+            if query.starts_with("DELETE") {
+                rows.retain(|row| row["id"] != params[0]);
+            }
+            Ok(QueryResult {
+                rows_affected: (before - rows.len()) as u64,
+                last_insert_id: None,
+            })
+        }
+
+        async fn query_one(&self, query: &str, params: Vec<Value>) -> Result<Value, DbError> {
+            let mut rows = self.rows.lock().unwrap();
+            if query.starts_with("SELECT COUNT(*)") {
+                return Ok(json!({ "count": rows.len() }));
+            }
+            if query.starts_with("INSERT") {
+                let names = columns(query.split(['(', ')']).nth(1).unwrap());
+                let row: serde_json::Map<String, Value> = names.into_iter().zip(params).collect();
+                rows.push(Value::Object(row.clone()));
+                return Ok(Value::Object(row));
+            }
+            if query.starts_with("UPDATE") {
+                let id = params.last().unwrap().clone();
+                let set = query.split(" SET ").nth(1).unwrap().split(" WHERE ").next().unwrap();
+                let row = rows.iter_mut().find(|row| row["id"] == id).ok_or_else(not_found)?;
+                for (assignment, value) in set.split(", ").zip(params) {
+                    let column = assignment.split(" = ").next().unwrap();
+                    row[column] = value;
+                }
+                return Ok(row.clone());
+            }
+            rows.iter()
+                .find(|row| row["id"] == params[0])
+                .cloned()
+                .ok_or_else(not_found)
+        }
+
+        async fn query_many(&self, query: &str, _params: Vec<Value>) -> Result<Vec<Value>, DbError> {
+            let clause = |keyword: &str| {
+                query
+                    .split(keyword)
+                    .nth(1)
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .and_then(|n| n.parse::<usize>().ok())
+            };
+            let rows = self.rows.lock().unwrap();
+            Ok(rows
+                .iter()
+                .skip(clause(" OFFSET ").unwrap_or(0))
+                .take(clause(" LIMIT ").unwrap_or(usize::MAX))
+                .cloned()
+                .collect())
+        }
+
+        async fn begin_transaction(&self) -> Result<String, DbError> {
+            Ok("tx".to_string())
+        }
+
+        async fn commit_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            Ok(())
+        }
+
+        async fn rollback_transaction(&self, _tx_id: &str) -> Result<(), DbError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_users_crud_status_codes() {
+        let users = Resource::new(Repository::<User, _>::new(MemoryDb::default()));
+
+        let created = block_on(users.handle(Method::POST, "/", Some(r#"{"id":1,"name":"Ada"}"#)));
+        assert_eq!(created.status, 201);
+        assert_eq!(created.body.unwrap()["name"], "Ada");
+
+        let list = block_on(users.handle(Method::GET, "/", None));
+        assert_eq!(list.status, 200);
+        assert_eq!(list.body.unwrap().as_array().unwrap().len(), 1);
+
+        let updated = block_on(users.handle(Method::PUT, "/1", Some(r#"{"name":"Ada L."}"#)));
+        assert_eq!(updated.status, 200);
+        assert_eq!(updated.body.unwrap()["name"], "Ada L.");
+
+        let shown = block_on(users.handle(Method::GET, "/1", None));
+        assert_eq!(shown.body.unwrap()["name"], "Ada L.");
+
+        let deleted = block_on(users.handle(Method::DELETE, "/1", None));
+        assert_eq!(deleted, ResourceResponse { status: 204, body: None });
+        assert_eq!(block_on(users.handle(Method::GET, "/1", None)).status, 404);
+    }
+
+    #[test]
+    fn test_errors_use_consistent_statuses() {
+        let users = Resource::new(Repository::<User, _>::new(MemoryDb::default()));
+
+        assert_eq!(block_on(users.handle(Method::POST, "/", Some("{"))).status, 400);
+        assert_eq!(block_on(users.handle(Method::PUT, "/7", Some(r#"{"name":"x"}"#))).status, 404);
+        assert_eq!(block_on(users.handle(Method::PUT, "/7", Some(r#"{"id":7}"#))).status, 400);
+        assert_eq!(block_on(users.handle(Method::DELETE, "/7", None)).status, 404);
+        assert_eq!(block_on(users.handle(Method::DELETE, "/", None)).status, 405);
+    }
+
+    #[test]
+    fn test_paginated_list_envelope() {
+        let users = Resource::new(Repository::<User, _>::new(MemoryDb::default()));
+        for id in 1..=5 {
+            let body = format!(r#"{{"id":{},"name":"user{}"}}"#, id, id);
+            block_on(users.handle(Method::POST, "/", Some(&body)));
+        }
+
+        let page = block_on(users.handle(Method::GET, "/?page=2&page_size=2", None));
+        assert_eq!(page.status, 200);
+        let body = page.body.unwrap();
+        assert_eq!(body["page"], 2);
+        assert_eq!(body["page_size"], 2);
+        assert_eq!(body["total"], 5);
+        assert_eq!(body["total_pages"], 3);
+        let ids: Vec<_> = body["data"].as_array().unwrap().iter().map(|u| u["id"].clone()).collect();
+        assert_eq!(ids, vec![json!(3), json!(4)]);
+
+        let last = block_on(users.handle(Method::GET, "/?page=3&page_size=2", None));
+        assert_eq!(last.body.unwrap()["data"].as_array().unwrap().len(), 1);
+
+        assert_eq!(block_on(users.handle(Method::GET, "/?page_size=0", None)).status, 400);
+        assert_eq!(block_on(users.handle(Method::GET, "/?page=abc", None)).status, 400);
+    }
+}
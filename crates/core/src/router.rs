@@ -1,7 +1,10 @@
 //! Router System - L7
 
 use crate::component::{Component, Element, Props};
+use crate::head::{set_meta, Viewport};
+use crate::middleware_v2::Middleware;
 use std::collections::HashMap;
+use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
 /// Page definition
@@ -40,6 +43,11 @@ impl Page {
         self.meta.insert(key.into(), value.into());
         self
     }
+
+    /// Viewport for this page, e.g. a canvas page that disables zoom
+    pub fn viewport(self, viewport: Viewport) -> Self {
+        self.meta("viewport", viewport.content())
+    }
 }
 
 /// Route definition
@@ -47,6 +55,24 @@ impl Page {
 pub struct Route {
     pub path: String,
     pub handler: RouteHandler,
+    /// Runs after the global middleware, only for requests matching `path`;
+    /// see `MiddlewareStack::routes`
+    pub middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl Route {
+    pub fn new(path: impl Into<String>, handler: RouteHandler) -> Self {
+        Route {
+            path: path.into(),
+            handler,
+            middleware: vec![],
+        }
+    }
+
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -104,6 +130,9 @@ impl Router {
             .document()
             .unwrap()
             .set_title(&page.title);
+        for (name, content) in &page.meta {
+            set_meta(name, content);
+        }
 
         // Render component
         let root = web_sys::window()
@@ -135,7 +164,7 @@ impl Component for EmptyComponent {
     fn render(&self) -> Element {
         Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![Element::Text("Empty Page".to_string())],
         }
     }
@@ -148,19 +177,19 @@ impl Component for NotFoundComponent {
     fn render(&self) -> Element {
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("not-found".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("404".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Page not found".to_string())],
                 },
             ],
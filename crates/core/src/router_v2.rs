@@ -113,6 +113,7 @@ impl Router {
         // Update browser history
         self.history
             .push_state_with_url(&JsValue::NULL, "", Some(path))?;
+        crate::modal::sync_bound_modals(search_of(path));
 
         // Update router state
         let new_state = RouteState {
@@ -128,6 +129,7 @@ impl Router {
         // Replace current history entry
         self.history
             .replace_state_with_url(&JsValue::NULL, "", Some(path))?;
+        crate::modal::sync_bound_modals(search_of(path));
 
         // Update router state
         let new_state = RouteState {
@@ -166,16 +168,28 @@ fn match_route(path: &str, routes: &[RouteDefinition]) -> RouteParams {
 }
 
 /// Match path pattern against actual path
-fn match_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
-    let pattern_parts: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
-    let path_parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+///
+/// A trailing `*` matches the rest of the path, including nothing, and
+/// captures it as the `*` param.
+pub(crate) fn match_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let mut pattern_parts: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let mut path_parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let mut params = HashMap::new();
+
+    if pattern_parts.last() == Some(&"*") {
+        pattern_parts.pop();
+        if path_parts.len() < pattern_parts.len() {
+            return None;
+        }
+        let rest = path_parts.split_off(pattern_parts.len());
+        params.insert("*".to_string(), rest.join("/"));
+    }
 
     if pattern_parts.len() != path_parts.len() {
         return None;
     }
 
-    let mut params = HashMap::new();
-
     for (pattern_part, path_part) in pattern_parts.iter().zip(path_parts.iter()) {
         if let Some(param_name) = pattern_part.strip_prefix(':') {
             // Dynamic segment
@@ -189,8 +203,36 @@ fn match_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
     Some(params)
 }
 
+/// `path` with empty and `.` segments dropped and `..` segments resolved,
+/// percent-encoded ones included, so `/public/../admin` is `/admin`. A
+/// trailing slash is kept.
+pub(crate) fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match urlencoding::decode(segment).as_deref() {
+            Ok("") | Ok(".") => {}
+            Ok("..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut normalized = format!("/{}", segments.join("/"));
+    if path.ends_with('/') && !segments.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+/// The `?query` part of a path, without any `#fragment`
+fn search_of(path: &str) -> &str {
+    let path = path.split('#').next().unwrap_or_default();
+    path.find('?').map(|start| &path[start..]).unwrap_or_default()
+}
+
 /// Parse query string
-fn parse_query(query: &str) -> HashMap<String, String> {
+pub(crate) fn parse_query(query: &str) -> HashMap<String, String> {
     let mut params = HashMap::new();
 
     if let Some(query) = query.strip_prefix('?') {
@@ -270,7 +312,7 @@ impl Component for Link {
 
         Element::Node {
             tag: "a".to_string(),
-            props: crate::component::Props {
+            props: Box::new(crate::component::Props {
                 class: self.class.clone(),
                 on_click,
                 attributes: vec![
@@ -278,7 +320,7 @@ impl Component for Link {
                     ("onclick".to_string(), "event.preventDefault()".to_string()),
                 ],
                 ..Default::default()
-            },
+            }),
             children: self.children.clone(),
         }
     }
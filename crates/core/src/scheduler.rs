@@ -0,0 +1,429 @@
+//! Scheduled Tasks - L4
+//!
+//! Periodic server work such as refreshing cached data or cleaning up
+//! expired rows:
+//!
+//! ```ignore
+//! let scheduler = Scheduler::new()
+//!     .every("refresh_github_stats", Duration::from_secs(5 * 60), move || {
+//!         let stats = stats.clone();
+//!         async move {
+//!             let fresh = fetch_github_stats().await.map_err(|e| e.to_string())?;
+//!             *stats.write() = fresh;
+//!             Ok(())
+//!         }
+//!     })
+//!     .cron("purge_sessions", "0 3 * * *", || async { purge_sessions().await })?;
+//! scheduler.start();
+//! ```
+//!
+//! Interval tasks first run when the scheduler starts; cron tasks wait for
+//! their first matching minute (UTC). A task never overlaps itself: if a run
+//! is still going when the next one is due, that run is skipped.
+
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type TaskFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// When a task runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronExpr),
+}
+
+impl Schedule {
+    /// Next run strictly after `after_ms`, or `None` if the schedule never fires
+    pub fn next_after(&self, after_ms: u64) -> Option<u64> {
+        match self {
+            Schedule::Every(interval) => Some(after_ms + (interval.as_millis() as u64).max(1)),
+            Schedule::Cron(expr) => expr.next_after(after_ms / 1000).map(|secs| secs * 1000),
+        }
+    }
+}
+
+/// Five-field cron expression: minute, hour, day of month, month, day of week.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// comma-separated lists. `@hourly`, `@daily` and `@weekly` are also accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("Expected 5 cron fields, got {}", fields.len()));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(CronExpr {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+
+    /// First matching minute strictly after `after_secs` (Unix time, UTC)
+    pub fn next_after(&self, after_secs: u64) -> Option<u64> {
+        let mut t = (after_secs / 60 + 1) * 60;
+        // A valid expression matches within a leap-year cycle; anything else never will
+        let limit = t + 4 * 366 * 86_400;
+
+        while t < limit {
+            let days = t / 86_400;
+            let (_, month, day) = civil_from_days(days);
+            // 1970-01-01 was a Thursday
+            let weekday = (days + 4) % 7;
+            if !self.day_matches(month, day, weekday) {
+                t = (days + 1) * 86_400;
+                continue;
+            }
+
+            let hour = (t % 86_400) / 3600;
+            if self.hours & (1 << hour) == 0 {
+                t = (t / 3600 + 1) * 3600;
+                continue;
+            }
+
+            let minute = (t % 3600) / 60;
+            if self.minutes & (1 << minute) == 0 {
+                t += 60;
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn day_matches(&self, month: u32, day: u32, weekday: u64) -> bool {
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << day) != 0;
+        let dow = self.days_of_week & (1 << weekday) != 0;
+        // Like cron, a restricted day of month and day of week match either one
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (false, true) => dom,
+            (true, false) => dow,
+            (false, false) => dom || dow,
+        }
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid step in '{}'", field))?,
+            ),
+            None => (part, 1),
+        };
+
+        let number = |value: &str| {
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("'{}' is out of range {}-{} in '{}'", value, min, max, field))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/10` means from 5 to the end of the range
+                None if part.contains('/') => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("Invalid range in '{}'", field));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// Days since the Unix epoch to a (year, month, day) date
+fn civil_from_days(days: u64) -> (i64, u32, u32) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+struct ScheduledTask {
+    name: String,
+    schedule: Schedule,
+    task: TaskFn,
+    running: Arc<AtomicBool>,
+    next_run_ms: Mutex<Option<u64>>,
+}
+
+/// Clears a task's running flag when its run finishes or is dropped
+struct RunningGuard(Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Set of named tasks run on intervals or cron schedules
+#[derive(Clone)]
+pub struct Scheduler {
+    tasks: Vec<Arc<ScheduledTask>>,
+    tick_interval: Duration,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler {
+            tasks: Vec::new(),
+            tick_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Run `task` every `interval`, starting when the scheduler starts
+    pub fn every<F, Fut>(self, name: impl Into<String>, interval: Duration, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.schedule(name, Schedule::Every(interval), task)
+    }
+
+    /// Run `task` on a cron expression, e.g. `"*/5 * * * *"`
+    pub fn cron<F, Fut>(self, name: impl Into<String>, expr: &str, task: F) -> Result<Self, String>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        Ok(self.schedule(name, Schedule::Cron(CronExpr::parse(expr)?), task))
+    }
+
+    pub fn schedule<F, Fut>(mut self, name: impl Into<String>, schedule: Schedule, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.tasks.push(Arc::new(ScheduledTask {
+            name: name.into(),
+            schedule,
+            task: Arc::new(move || Box::pin(task())),
+            running: Arc::new(AtomicBool::new(false)),
+            next_run_ms: Mutex::new(None),
+        }));
+        self
+    }
+
+    /// How often the scheduler checks for due tasks
+    pub fn tick_interval(mut self, interval: Duration) -> Self {
+        self.tick_interval = interval;
+        self
+    }
+
+    /// Whether a run of the task named `name` is in progress
+    pub fn is_running(&self, name: &str) -> bool {
+        self.tasks
+            .iter()
+            .any(|task| task.name == name && task.running.load(Ordering::SeqCst))
+    }
+
+    /// Runs for every task due at `now_ms`, skipping tasks still running.
+    ///
+    /// Each due task's next run is scheduled whether or not it was skipped.
+    pub fn due(&self, now_ms: u64) -> Vec<BoxFuture<'static, ()>> {
+        let mut runs = Vec::new();
+        for task in &self.tasks {
+            let mut next_run = task.next_run_ms.lock().unwrap();
+            let at = match (*next_run, &task.schedule) {
+                (Some(at), _) => at,
+                (None, Schedule::Every(_)) => now_ms,
+                (None, Schedule::Cron(_)) => {
+                    *next_run = task.schedule.next_after(now_ms);
+                    continue;
+                }
+            };
+            if at > now_ms {
+                continue;
+            }
+            *next_run = task.schedule.next_after(now_ms);
+
+            if task.running.swap(true, Ordering::SeqCst) {
+                continue;
+            }
+            let guard = RunningGuard(task.running.clone());
+            let run = (task.task)();
+            let name = task.name.clone();
+            runs.push(Box::pin(async move {
+                let _guard = guard;
+                if let Err(error) = run.await {
+                    let message = format!("Scheduled task '{}' failed: {}", name, error);
+                    #[cfg(target_arch = "wasm32")]
+                    web_sys::console::error_1(&message.into());
+                    #[cfg(not(target_arch = "wasm32"))]
+                    tracing::error!("{}", message);
+                }
+            }) as BoxFuture<'static, ()>);
+        }
+        runs
+    }
+
+    /// Run every task due now and wait for them to finish
+    pub async fn run_due(&self) {
+        futures::future::join_all(self.due(now_ms())).await;
+    }
+
+    /// Check for due tasks every tick, running each on its own task
+    #[cfg(feature = "ssr")]
+    pub fn start(&self) -> tokio::task::JoinHandle<()> {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                for run in scheduler.due(now_ms()) {
+                    tokio::spawn(run);
+                }
+                tokio::time::sleep(scheduler.tick_interval).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use std::sync::atomic::AtomicU32;
+
+    // 2024-03-15 12:02:30 UTC, a Friday
+    const NOW: u64 = 1_710_504_150;
+
+    #[test]
+    fn test_cron_next_after() {
+        let every_five = CronExpr::parse("*/5 * * * *").unwrap();
+        assert_eq!(every_five.next_after(NOW), Some(1_710_504_300)); // 12:05
+
+        let nightly = CronExpr::parse("0 3 * * *").unwrap();
+        assert_eq!(nightly.next_after(NOW), Some(1_710_558_000)); // 03:00 on the 16th
+
+        // Monday 09:30; 7 also means Sunday
+        let weekly = CronExpr::parse("30 9 * * 1").unwrap();
+        assert_eq!(weekly.next_after(NOW), Some(1_710_754_200));
+        assert_eq!(CronExpr::parse("0 0 * * 7").unwrap(), CronExpr::parse("0 0 * * 0").unwrap());
+
+        assert_eq!(CronExpr::parse("0 0 30 2 *").unwrap().next_after(NOW), None);
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("* * *").is_err());
+    }
+
+    #[test]
+    fn test_refresh_runs_every_interval() {
+        let refreshes = Arc::new(AtomicU32::new(0));
+        let scheduler = {
+            let refreshes = refreshes.clone();
+            Scheduler::new().every("refresh_github_stats", Duration::from_secs(300), move || {
+                let refreshes = refreshes.clone();
+                async move {
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+        };
+
+        let start = NOW * 1000;
+        // Runs on start, then not again until five minutes have passed
+        block_on(futures::future::join_all(scheduler.due(start)));
+        assert!(scheduler.due(start + 299_000).is_empty());
+        block_on(futures::future::join_all(scheduler.due(start + 300_000)));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_overlapping_runs_are_skipped() {
+        let (release, wait) = oneshot::channel::<()>();
+        let wait = Arc::new(Mutex::new(Some(wait)));
+        let scheduler = Scheduler::new().every("slow", Duration::from_secs(60), move || {
+            let wait = wait.lock().unwrap().take();
+            async move {
+                if let Some(wait) = wait {
+                    let _ = wait.await;
+                }
+                Ok(())
+            }
+        });
+
+        let start = NOW * 1000;
+        let mut first = scheduler.due(start);
+        assert_eq!(first.len(), 1);
+        assert!(scheduler.is_running("slow"));
+
+        // Still running when the next run comes due
+        assert!(scheduler.due(start + 60_000).is_empty());
+
+        release.send(()).unwrap();
+        block_on(first.remove(0));
+        assert!(!scheduler.is_running("slow"));
+        assert_eq!(scheduler.due(start + 120_000).len(), 1);
+    }
+}
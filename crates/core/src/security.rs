@@ -225,7 +225,9 @@ pub struct XssProtection;
 impl XssProtection {
     /// Sanitize HTML input
     pub fn sanitize_html(input: &str) -> String {
+        // `&` first, so the entities below aren't escaped twice
         input
+            .replace('&', "&amp;")
             .replace('<', "&lt;")
             .replace('>', "&gt;")
             .replace('"', "&quot;")
@@ -840,6 +842,7 @@ mod tests {
             sanitizer.sanitize_html("<script>alert('xss')</script>Hello"),
             "&lt;script&gt;alert(&#x27;xss&#x27;)&lt;&#x2F;script&gt;Hello"
         );
+        assert_eq!(sanitizer.sanitize_html("Tom &amp; Jerry"), "Tom &amp;amp; Jerry");
         
         // URL sanitization
         assert_eq!(
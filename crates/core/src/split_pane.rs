@@ -0,0 +1,506 @@
+//! Split Pane - L5
+//!
+//! `SplitPane` lays two panes out side by side (or stacked) with a divider
+//! between them that can be dragged with a pointer or moved from the
+//! keyboard. The size is the first pane's share of the container, in
+//! percent; the second pane takes the rest.
+//!
+//! ```ignore
+//! SplitPane::new(sidebar, dashboard)
+//!     .label("Resize sidebar")
+//!     .default_size(25.0)
+//!     .min_size(15.0)
+//!     .max_size(45.0)
+//!     .persist("dashboard-sidebar")
+//! ```
+//!
+//! The divider follows the WAI-ARIA window splitter pattern: it is a
+//! focusable `separator`, Left/Right (Up/Down when stacked) move it and
+//! Home/End jump to the minimum and maximum. Nest split panes for more than
+//! two panels.
+
+use crate::component::{Component, Element, Props, State};
+use crate::hooks::{use_widget_id, use_widget_state};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_SPLIT_PANE_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Percentage points an arrow key moves the divider
+const KEYBOARD_STEP: f64 = 5.0;
+
+/// Which way the panes are laid out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Side by side; the divider moves left and right
+    #[default]
+    Horizontal,
+    /// Stacked; the divider moves up and down
+    Vertical,
+}
+
+/// Two resizable panes with a draggable divider
+pub struct SplitPane {
+    id: Option<String>,
+    label: String,
+    first: Element,
+    second: Element,
+    direction: SplitDirection,
+    size: State<f64>,
+    min_size: f64,
+    max_size: f64,
+    persist_key: Option<String>,
+}
+
+impl SplitPane {
+    pub fn new(first: Element, second: Element) -> Self {
+        SplitPane {
+            id: None,
+            label: "Resize".to_string(),
+            first,
+            second,
+            direction: SplitDirection::Horizontal,
+            size: State::new(50.0),
+            min_size: 0.0,
+            max_size: 100.0,
+            persist_key: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Accessible name of the divider
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn direction(mut self, direction: SplitDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// First pane's starting size, in percent
+    pub fn default_size(mut self, size: f64) -> Self {
+        self.size = State::new(size);
+        self
+    }
+
+    pub fn min_size(mut self, size: f64) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    pub fn max_size(mut self, size: f64) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Remember the size in `localStorage` under `key`; a size saved by an
+    /// earlier visit replaces the default
+    pub fn persist(mut self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        if let Some(saved) = stored_size(&key) {
+            self.size = State::new(saved);
+        }
+        self.persist_key = Some(key);
+        self
+    }
+
+    /// Share the size with the parent. Seed it from `stored_size` when
+    /// combining with `persist`.
+    pub fn state(mut self, size: State<f64>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// First pane's size in percent, within the min and max
+    pub fn size(&self) -> f64 {
+        self.handle().size()
+    }
+
+    pub fn set_size(&self, size: f64) {
+        self.handle().set(size);
+    }
+
+    /// Handle a key pressed on the divider, as the keydown handler does
+    pub fn key(&self, key: &str) {
+        self.handle().key(key);
+    }
+
+    fn handle(&self) -> SplitHandle {
+        SplitHandle {
+            // Without an explicit id the render supplies the generated one
+            id: self.id.clone().unwrap_or_default(),
+            direction: self.direction,
+            size: self.size.clone(),
+            min_size: self.min_size,
+            max_size: self.max_size.max(self.min_size),
+            persist_key: self.persist_key.clone(),
+        }
+    }
+}
+
+/// A size saved by a `SplitPane` with `persist(key)`
+pub fn stored_size(key: &str) -> Option<f64> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()?
+            .local_storage()
+            .ok()??
+            .get_item(key)
+            .ok()??
+            .parse()
+            .ok()
+            .filter(|size: &f64| size.is_finite())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = key;
+        None
+    }
+}
+
+/// Everything the event handlers need, shared between them
+#[derive(Clone)]
+struct SplitHandle {
+    id: String,
+    direction: SplitDirection,
+    size: State<f64>,
+    min_size: f64,
+    max_size: f64,
+    persist_key: Option<String>,
+}
+
+impl SplitHandle {
+    fn size(&self) -> f64 {
+        self.size.get().clamp(self.min_size, self.max_size)
+    }
+
+    fn set(&self, size: f64) {
+        let size = size.clamp(self.min_size, self.max_size);
+        if size == self.size.get() {
+            return;
+        }
+        self.size.set(size);
+        self.show(size);
+        self.save(size);
+    }
+
+    fn key(&self, key: &str) {
+        let (back, forward) = match self.direction {
+            SplitDirection::Horizontal => ("ArrowLeft", "ArrowRight"),
+            SplitDirection::Vertical => ("ArrowUp", "ArrowDown"),
+        };
+        match key {
+            k if k == back => self.set(self.size() - KEYBOARD_STEP),
+            k if k == forward => self.set(self.size() + KEYBOARD_STEP),
+            "Home" => self.set(self.min_size),
+            "End" => self.set(self.max_size),
+            _ => {}
+        }
+    }
+
+    /// Write the size straight to the DOM, so dragging follows the pointer
+    /// without waiting for a re-render
+    fn show(&self, size: f64) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+                return;
+            };
+            if let Some(pane) = document.get_element_by_id(&format!("{}-first", self.id)) {
+                let _ = pane.set_attribute("style", &first_pane_style(size));
+            }
+            if let Some(divider) = document.get_element_by_id(&format!("{}-divider", self.id)) {
+                let _ = divider.set_attribute("aria-valuenow", &format!("{:.0}", size));
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = (size, &self.id);
+    }
+
+    fn save(&self, size: f64) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(key) = &self.persist_key {
+            if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+                let _ = storage.set_item(key, &size.to_string());
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = (size, &self.persist_key);
+    }
+
+    /// Follow the pointer across the window until it is released
+    #[cfg(target_arch = "wasm32")]
+    fn drag(&self) {
+        use std::cell::RefCell;
+        use wasm_bindgen::closure::Closure;
+        use wasm_bindgen::JsCast;
+
+        type PointerListener = Closure<dyn FnMut(web_sys::MouseEvent)>;
+
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let Some(container) = window.document().and_then(|d| d.get_element_by_id(&self.id)) else {
+            return;
+        };
+        let rect = container.get_bounding_client_rect();
+
+        let on_move: PointerListener = {
+            let handle = self.clone();
+            Closure::new(move |event: web_sys::MouseEvent| {
+                let (offset, length) = match handle.direction {
+                    SplitDirection::Horizontal => (event.client_x() as f64 - rect.left(), rect.width()),
+                    SplitDirection::Vertical => (event.client_y() as f64 - rect.top(), rect.height()),
+                };
+                if length > 0.0 {
+                    handle.set(offset / length * 100.0);
+                }
+            })
+        };
+
+        let listeners: Rc<RefCell<Option<(PointerListener, PointerListener)>>> = Rc::default();
+        let on_up: PointerListener = {
+            let listeners = listeners.clone();
+            let window = window.clone();
+            Closure::new(move |_event: web_sys::MouseEvent| {
+                if let Some((on_move, on_up)) = listeners.borrow_mut().take() {
+                    for (event, listener) in [("pointermove", &on_move), ("pointerup", &on_up), ("pointercancel", &on_up)] {
+                        let _ = window.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+                    }
+                    // This closure is one of them; drop them once it returns
+                    gloo_timers::callback::Timeout::new(0, move || drop((on_move, on_up))).forget();
+                }
+            })
+        };
+
+        for (event, listener) in [("pointermove", &on_move), ("pointerup", &on_up), ("pointercancel", &on_up)] {
+            let _ = window.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        }
+        *listeners.borrow_mut() = Some((on_move, on_up));
+    }
+}
+
+fn first_pane_style(size: f64) -> String {
+    format!("flex: 0 0 {}%; min-width: 0; min-height: 0; overflow: auto", size)
+}
+
+impl Component for SplitPane {
+    fn render(&self) -> Element {
+        let handle = SplitHandle {
+            id: use_widget_id(self.id.as_deref(), || {
+                format!("layer9-split-pane-{}", NEXT_SPLIT_PANE_ID.fetch_add(1, Ordering::Relaxed))
+            }),
+            size: use_widget_state(&self.size),
+            ..self.handle()
+        };
+        let size = handle.size();
+        let (flex_direction, orientation, cursor, divider_size) = match self.direction {
+            SplitDirection::Horizontal => ("row", "vertical", "col-resize", "width: 6px"),
+            SplitDirection::Vertical => ("column", "horizontal", "row-resize", "height: 6px"),
+        };
+        let first_id = format!("{}-first", handle.id);
+
+        let on_keydown = {
+            let handle = handle.clone();
+            Rc::new(move |key: String| handle.key(&key)) as Rc<dyn Fn(String)>
+        };
+        let on_pointer_down = {
+            #[cfg(target_arch = "wasm32")]
+            let handle = handle.clone();
+            Rc::new(move |_x: f64, _y: f64| {
+                #[cfg(target_arch = "wasm32")]
+                handle.drag();
+            }) as Rc<dyn Fn(f64, f64)>
+        };
+
+        let divider = Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(format!("{}-divider", handle.id)),
+                class: Some("split-pane-divider".to_string()),
+                attributes: vec![
+                    ("role".to_string(), "separator".to_string()),
+                    ("tabindex".to_string(), "0".to_string()),
+                    ("aria-label".to_string(), self.label.clone()),
+                    ("aria-controls".to_string(), first_id.clone()),
+                    ("aria-orientation".to_string(), orientation.to_string()),
+                    ("aria-valuenow".to_string(), format!("{:.0}", size)),
+                    ("aria-valuemin".to_string(), format!("{:.0}", handle.min_size)),
+                    ("aria-valuemax".to_string(), format!("{:.0}", handle.max_size)),
+                    (
+                        "style".to_string(),
+                        format!(
+                            "flex: 0 0 auto; {}; cursor: {}; touch-action: none; user-select: none; background: rgba(0, 0, 0, 0.1)",
+                            divider_size, cursor
+                        ),
+                    ),
+                ],
+                on_keydown: Some(on_keydown),
+                on_pointer_down: Some(on_pointer_down),
+                ..Default::default()
+            }),
+            children: vec![],
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(handle.id.clone()),
+                class: Some(format!("split-pane split-pane-{}", flex_direction)),
+                attributes: vec![(
+                    "style".to_string(),
+                    format!("display: flex; flex-direction: {}; width: 100%; height: 100%", flex_direction),
+                )],
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        id: Some(first_id),
+                        class: Some("split-pane-first".to_string()),
+                        attributes: vec![("style".to_string(), first_pane_style(size))],
+                        ..Default::default()
+                    }),
+                    children: vec![self.first.clone()],
+                },
+                divider,
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("split-pane-second".to_string()),
+                        attributes: vec![(
+                            "style".to_string(),
+                            "flex: 1 1 0; min-width: 0; min-height: 0; overflow: auto".to_string(),
+                        )],
+                        ..Default::default()
+                    }),
+                    children: vec![self.second.clone()],
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(element: &Element, name: &str) -> Option<String> {
+        match element {
+            Element::Node { props, .. } => props
+                .attributes
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone()),
+            _ => None,
+        }
+    }
+
+    fn children(element: &Element) -> &[Element] {
+        match element {
+            Element::Node { children, .. } => children,
+            _ => &[],
+        }
+    }
+
+    #[test]
+    fn test_split_pane_keyboard_resizing_stays_within_bounds() {
+        let size = State::new(25.0);
+        let pane = SplitPane::new(Element::Text("Sidebar".into()), Element::Text("Main".into()))
+            .id("layout")
+            .min_size(15.0)
+            .max_size(40.0)
+            .state(size.clone());
+
+        pane.key("ArrowRight");
+        assert_eq!(size.get(), 30.0);
+        pane.key("ArrowDown");
+        assert_eq!(size.get(), 30.0);
+        pane.key("End");
+        assert_eq!(pane.size(), 40.0);
+        pane.key("ArrowRight");
+        assert_eq!(pane.size(), 40.0);
+        pane.key("Home");
+        pane.key("ArrowLeft");
+        assert_eq!(pane.size(), 15.0);
+
+        pane.set_size(90.0);
+        assert_eq!(pane.size(), 40.0);
+
+        let rendered = pane.render();
+        let [first, divider, _second] = children(&rendered) else {
+            panic!("expected two panes and a divider");
+        };
+        assert_eq!(attribute(first, "style").unwrap(), first_pane_style(40.0));
+        assert_eq!(attribute(divider, "role").as_deref(), Some("separator"));
+        assert_eq!(attribute(divider, "aria-orientation").as_deref(), Some("vertical"));
+        assert_eq!(attribute(divider, "aria-valuenow").as_deref(), Some("40"));
+        assert_eq!(attribute(divider, "aria-controls").as_deref(), Some("layout-first"));
+
+        let stacked = SplitPane::new(Element::Text("Top".into()), Element::Text("Bottom".into()))
+            .direction(SplitDirection::Vertical);
+        stacked.key("ArrowDown");
+        assert_eq!(stacked.size(), 55.0);
+        stacked.key("ArrowRight");
+        assert_eq!(stacked.size(), 55.0);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Layout;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Layout {
+        fn render(&self) -> Element {
+            SplitPane::new(Element::Text("Sidebar".into()), Element::Text("Main".into()))
+                .id("layout")
+                .render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_split_pane_keeps_its_size_across_rerenders() {
+        let layout = Layout;
+        let mut tree = crate::testing::render(&layout);
+        tree.keydown("#layout-divider", "ArrowRight");
+        tree.rerender();
+
+        let divider = tree.find("#layout-divider").unwrap();
+        assert_eq!(attribute(divider, "aria-valuenow").as_deref(), Some("55"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Untitled;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Untitled {
+        fn render(&self) -> Element {
+            SplitPane::new(Element::Text("Sidebar".into()), Element::Text("Main".into())).render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_generated_id_survives_rerenders() {
+        let untitled = Untitled;
+        let mut tree = crate::testing::render(&untitled);
+        let controls = |tree: &crate::testing::TestTree| {
+            attribute(tree.find(".split-pane-divider").unwrap(), "aria-controls").unwrap()
+        };
+        let first = controls(&tree);
+        assert!(first.starts_with("layer9-split-pane-"));
+
+        tree.rerender();
+        assert_eq!(controls(&tree), first);
+    }
+}
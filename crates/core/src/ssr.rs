@@ -4,6 +4,12 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use serde_json;
 use async_trait::async_trait;
+use crate::component::Component;
+use crate::head::Viewport;
+use crate::hooks::{cleanup_component_hooks, reset_hook_index};
+use crate::i18n::Locale;
+use crate::reactive_v2::with_current_component;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -27,6 +33,12 @@ pub struct SSRContext {
     pub query_params: HashMap<String, String>,
     #[serde(skip)]
     pub request_headers: HashMap<String, String>,
+    /// Locale the page renders in; sent along so hydration keeps it
+    #[serde(default)]
+    pub locale: Locale,
+    /// Fills the template's `{{viewport}}` placeholder
+    #[serde(skip)]
+    pub viewport: Viewport,
 }
 
 impl SSRContext {
@@ -38,6 +50,8 @@ impl SSRContext {
             route: "/".to_string(),
             query_params: HashMap::new(),
             request_headers: HashMap::new(),
+            locale: Locale::default(),
+            viewport: Viewport::default(),
         }
     }
     
@@ -66,10 +80,37 @@ impl SSRContext {
         self
     }
     
+    /// Also picks the locale from the `layer9-locale` cookie or `Accept-Language`
     pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        };
+        if let Some(locale) = crate::i18n::detect_request_locale(header("cookie"), header("accept-language")) {
+            self.locale = locale;
+        }
         self.request_headers = headers;
         self
     }
+
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Color the mobile browser chrome to match `scheme`; the client keeps it
+    /// in sync with `ThemeColor::apply`
+    pub fn with_theme_color(mut self, colors: &crate::styles::ThemeColor, scheme: crate::styles::ColorScheme) -> Self {
+        self.meta_tags.extend(colors.meta_tags(scheme));
+        self
+    }
 }
 
 impl Default for SSRContext {
@@ -91,6 +132,22 @@ pub trait SSRComponent: Send + Sync {
     fn get_data_requirements(&self) -> Vec<String> {
         Vec::new()
     }
+
+    /// Markup with the resolved `get_server_props`, for streamed boundaries
+    fn render_with_props(&self, ctx: &SSRContext, _props: &serde_json::Value) -> String {
+        self.render_to_string(ctx)
+    }
+
+    /// Shown in the shell while `render_streaming` waits for this
+    /// component's props; `None` renders it in the shell straight away
+    fn stream_fallback(&self, _ctx: &SSRContext) -> Option<String> {
+        None
+    }
+
+    /// Streamed in place of the content when `get_server_props` fails
+    fn error_fallback(&self, ctx: &SSRContext, _error: &str) -> String {
+        self.stream_fallback(ctx).unwrap_or_default()
+    }
 }
 
 /// Trait for SSR-capable components (server-side)
@@ -106,6 +163,115 @@ pub trait SSRComponent: Send + Sync {
     fn get_data_requirements(&self) -> Vec<String> {
         Vec::new()
     }
+
+    /// Markup with the resolved `get_server_props`, for streamed boundaries
+    fn render_with_props(&self, ctx: &SSRContext, _props: &serde_json::Value) -> String {
+        self.render_to_string(ctx)
+    }
+
+    /// Shown in the shell while `render_streaming` waits for this
+    /// component's props; `None` renders it in the shell straight away
+    fn stream_fallback(&self, _ctx: &SSRContext) -> Option<String> {
+        None
+    }
+
+    /// Streamed in place of the content when `get_server_props` fails
+    fn error_fallback(&self, ctx: &SSRContext, _error: &str) -> String {
+        self.stream_fallback(ctx).unwrap_or_default()
+    }
+}
+
+/// Id prefix of the element holding a streamed component's fallback
+const STREAM_BOUNDARY_PREFIX: &str = "l9-boundary-";
+/// Id prefix of the template carrying a streamed component's markup
+const STREAM_CHUNK_PREFIX: &str = "l9-chunk-";
+
+/// Swaps a streamed chunk in for its fallback, then removes itself
+const STREAM_STITCH_SCRIPT: &str = r#"<script>function __l9Stitch(n){var c=document.getElementById("l9-chunk-"+n),b=document.getElementById("l9-boundary-"+n);if(c&&b){b.replaceWith(c.content)}if(c){c.remove()}var s=document.currentScript;if(s){s.remove()}}</script>"#;
+
+/// `document` with its `<title>` set to `title`, adding one to the head
+/// if it has none
+fn with_title(document: &str, title: &str) -> String {
+    let title = html_escape::encode_text(title);
+    if let Some(start) = document.find("<title>") {
+        if let Some(end) = document[start..].find("</title>") {
+            let end = start + end;
+            return format!("{}<title>{}{}", &document[..start], title, &document[end..]);
+        }
+    }
+    match document.find("</head>") {
+        Some(end) => format!("{}<title>{}</title>\n{}", &document[..end], title, &document[end..]),
+        None => document.to_string(),
+    }
+}
+
+/// A streamed component's markup and the script that stitches it in
+#[cfg(not(target_arch = "wasm32"))]
+fn stream_chunk(index: usize, html: &str) -> String {
+    // Classes first seen here weren't in the shell's critical CSS
+    let critical = crate::css_runtime::CriticalCss::from_html(html);
+    let style = if critical.is_empty() {
+        String::new()
+    } else {
+        format!("<style>{}</style>", critical.css().replace("</", "<\\/"))
+    };
+    format!(
+        r#"{}<template id="{}{}">{}</template><script>__l9Stitch({})</script>"#,
+        style, STREAM_CHUNK_PREFIX, index, html, index
+    )
+}
+
+/// Ids of server render instances, clear of the renderer's and the test
+/// harness's
+static NEXT_SERVER_COMPONENT_ID: AtomicU32 = AtomicU32::new(3 << 30);
+
+/// Render an ordinary `Component` to HTML: the markup of its first paint in
+/// the browser, so one definition serves both
+///
+/// Hooks start from their initial values, effects don't run and event
+/// handlers are left out. Nested components render within the same
+/// instance, as they do in the browser. Components that touch browser APIs
+/// while rendering must check `is_server_render` first. A panicking
+/// component unwinds to the caller; `create_ssr_server` answers it with a
+/// 500.
+pub fn render_to_html_string(component: &dyn Component) -> String {
+    struct Cleanup(u32);
+    impl Drop for Cleanup {
+        fn drop(&mut self) {
+            cleanup_component_hooks(self.0);
+        }
+    }
+
+    let component_id = NEXT_SERVER_COMPONENT_ID.fetch_add(1, Ordering::Relaxed);
+    let _cleanup = Cleanup(component_id);
+    crate::reactive_v2::server_render(|| {
+        with_current_component(component_id, || {
+            reset_hook_index();
+            component.render().to_html()
+        })
+    })
+}
+
+/// Serves an ordinary `Component` from an `SSRRenderer`, through
+/// `render_to_html_string`:
+///
+/// ```ignore
+/// renderer.add_component(Box::new(ServerRendered(TodoApp)));
+/// ```
+pub struct ServerRendered<C>(pub C);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C: Component + Send + Sync> SSRComponent for ServerRendered<C> {
+    fn render_to_string(&self, _ctx: &SSRContext) -> String {
+        render_to_html_string(&self.0)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<C: Component + Send + Sync> SSRComponent for ServerRendered<C> {
+    fn render_to_string(&self, _ctx: &SSRContext) -> String {
+        render_to_html_string(&self.0)
+    }
 }
 
 /// SSR Renderer
@@ -141,16 +307,94 @@ impl SSRRenderer {
     pub async fn render(&self, ctx: &SSRContext) -> String {
         let mut body = String::new();
         
-        for component in &self.components {
-            body.push_str(&component.render_to_string(ctx));
-        }
+        // Translate into the request's locale, not the server's
+        crate::head::begin_server_head();
+        crate::i18n::with_locale(ctx.locale, || {
+            for component in &self.components {
+                body.push_str(&component.render_to_string(ctx));
+            }
+        });
         
+        let (before, after) = self.page_around(ctx, &body, crate::head::take_server_head());
+        format!("{}{}{}", before, body, after)
+    }
+
+    /// Stream the page: first the shell, with the whole head and every
+    /// component that has no `stream_fallback`, then each streamed
+    /// component as its `get_server_props` resolves, then the rest of the
+    /// template
+    ///
+    /// Streamed components show their fallback in the shell until their
+    /// chunk arrives and an inline script swaps it in. A failed
+    /// `get_server_props` streams the component's `error_fallback`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_streaming<'a>(&'a self, ctx: &'a SSRContext) -> impl futures::Stream<Item = String> + 'a {
+        use futures::stream::{self, FuturesUnordered, StreamExt};
+
+        let mut body = String::new();
+        let mut streamed = Vec::new();
+        crate::head::begin_server_head();
+        crate::i18n::with_locale(ctx.locale, || {
+            for (index, component) in self.components.iter().enumerate() {
+                match component.stream_fallback(ctx) {
+                    Some(fallback) => {
+                        body.push_str(&format!(
+                            r#"<div id="{}{}" style="display: contents">{}</div>"#,
+                            STREAM_BOUNDARY_PREFIX, index, fallback
+                        ));
+                        streamed.push((index, component));
+                    }
+                    None => body.push_str(&component.render_to_string(ctx)),
+                }
+            }
+        });
+
+        // Heads in streamed components come too late for the shell's head
+        let (before, after) = self.page_around(ctx, &body, crate::head::take_server_head());
+        let mut shell = before;
+        shell.push_str(&body);
+        if !streamed.is_empty() {
+            shell.push_str(STREAM_STITCH_SCRIPT);
+        }
+
+        let chunks: FuturesUnordered<_> = streamed
+            .into_iter()
+            .map(|(index, component)| async move {
+                let props = component.get_server_props(ctx).await;
+                let html = crate::i18n::with_locale(ctx.locale, || match props {
+                    Ok(props) => component.render_with_props(ctx, &props),
+                    Err(error) => component.error_fallback(ctx, &error),
+                });
+                stream_chunk(index, &html)
+            })
+            .collect();
+
+        stream::once(async move { shell })
+            .chain(chunks)
+            .chain(stream::once(async move { after }))
+    }
+
+    /// The template with everything but the content filled in, split where
+    /// the content goes. `head` is what the body's `Head`s declared.
+    fn page_around(&self, ctx: &SSRContext, body: &str, head: (Option<String>, String)) -> (String, String) {
+        let (title, head_tags) = head;
         let mut html = self.template.clone();
-        html = html.replace("{{content}}", &body);
+        html = html.replace("{{lang}}", ctx.locale.code());
+        html = html.replace("{{viewport}}", &ctx.viewport.meta_tag());
         
         // Add meta tags
-        let meta_tags = ctx.meta_tags.join("\n    ");
-        html = html.replace("{{meta}}", &meta_tags);
+        let mut meta_tags = ctx.meta_tags.clone();
+        if !head_tags.is_empty() {
+            meta_tags.push(head_tags);
+        }
+        html = html.replace("{{meta}}", &meta_tags.join("\n    "));
+        if let Some(title) = title {
+            html = with_title(&html, &title);
+        }
+
+        // Inline the styles the markup uses, so it isn't unstyled until
+        // hydration injects them
+        html = crate::css_runtime::CriticalCss::from_html(body).inline_into(&html);
         
         // Add initial state and hydration data
         let mut state_scripts = Vec::new();
@@ -188,15 +432,18 @@ impl SSRRenderer {
             html = html.replace("{{hydration_script}}", "");
         }
         
-        html
+        match html.split_once("{{content}}") {
+            Some((before, after)) => (before.to_string(), after.to_string()),
+            None => (html, String::new()),
+        }
     }
     
     fn default_template() -> String {
         r#"<!DOCTYPE html>
-<html lang="en">
+<html lang="{{lang}}">
 <head>
     <meta charset="utf-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1">
+    {{viewport}}
     <title>Layer9 SSR</title>
     {{meta}}
 </head>
@@ -243,7 +490,7 @@ pub trait SSRApp: Send + Sync + 'static {
 <html lang="en">
 <head>
     <meta charset="utf-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1">
+    {{viewport}}
     <title>Layer9 SSR</title>
     {{meta}}
 </head>
@@ -295,6 +542,9 @@ pub fn create_ssr_server<A: SSRApp>(app: Arc<A>) -> Router {
             let handler = handler.clone();
             
             async move {
+                use axum::http::StatusCode;
+                use futures::FutureExt;
+
                 // Build SSR context
                 let mut ctx = SSRContext::new()
                     .with_route(path_clone)
@@ -307,12 +557,36 @@ pub fn create_ssr_server<A: SSRApp>(app: Arc<A>) -> Router {
                         req_headers.insert(key.to_string(), v.to_string());
                     }
                 }
+                // Continue the caller's trace so DB queries and upstream calls join it
+                let parent = req_headers
+                    .get(crate::trace::TRACEPARENT_HEADER)
+                    .and_then(|value| crate::trace::TraceContext::parse(value));
+                let span_name = format!("ssr.render {}", ctx.route);
+                let span = match &parent {
+                    Some(parent) => crate::trace::Span::child_of(parent, span_name),
+                    None => crate::trace::Span::start(span_name),
+                };
                 ctx = ctx.with_headers(req_headers);
                 
                 // Handle the route
-                match handler.handle(ctx).await {
-                    Ok(html) => Html(html),
-                    Err(e) => Html(format!("<h1>Error</h1><p>{}</p>", e)),
+                let locale = ctx.locale;
+                let route = ctx.route.clone();
+                let render = crate::i18n::in_locale(locale, handler.handle(ctx));
+                let render = crate::trace::instrument(span.context().clone(), render);
+                // A panicking component fails its own request rather than
+                // dropping the connection
+                let result = std::panic::AssertUnwindSafe(render).catch_unwind().await;
+                span.finish();
+                match result {
+                    Ok(Ok(html)) => (StatusCode::OK, Html(html)),
+                    Ok(Err(e)) => (StatusCode::OK, Html(format!("<h1>Error</h1><p>{}</p>", e))),
+                    Err(_) => {
+                        tracing::error!("Rendering {} panicked", route);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Html("<h1>500 - Internal Server Error</h1>".to_string()),
+                        )
+                    }
                 }
             }
         }));
@@ -324,10 +598,34 @@ pub fn create_ssr_server<A: SSRApp>(app: Arc<A>) -> Router {
     // Add database API endpoints if configured
     #[cfg(feature = "ssr")]
     {
+        let mut health = crate::health::HealthChecks::new();
+
         // Create a simple database connection for the API
-        if let Ok(conn) = crate::db_sqlx::get_db_connection() {
-            router = router.nest("/api/db", crate::db_api::create_db_api_router(conn));
+        match crate::db_sqlx::get_db_connection() {
+            Ok(conn) => {
+                health = health.database(conn.clone());
+                router = router.nest("/api/db", crate::db_api::create_db_api_router(conn));
+            }
+            // Not ready until a pool exists
+            Err(e) => {
+                health = health.check("database", move || {
+                    let message = e.message.clone();
+                    async move { Err(message) }
+                });
+            }
         }
+
+        // Request metrics per route pattern, scraped from /metrics
+        let metrics = crate::metrics::Metrics::new();
+        router = router.route_layer(axum::middleware::from_fn_with_state(
+            metrics.clone(),
+            crate::metrics::track_metrics,
+        ));
+
+        // Liveness and readiness probes; readiness waits on the database pool
+        router = router
+            .merge(health.into_router())
+            .merge(metrics.into_router());
     }
     
     router
@@ -3,6 +3,7 @@
 #[cfg(test)]
 #[cfg(feature = "ssr")]
 mod tests {
+    use crate::head::Viewport;
     use crate::ssr::*;
     use std::collections::HashMap;
     use std::sync::Arc;
@@ -54,6 +55,41 @@ mod tests {
         assert_eq!(ctx.query_params.get("page").unwrap(), "2");
         assert_eq!(ctx.query_params.get("sort").unwrap(), "date");
     }
+
+    #[tokio::test]
+    async fn test_ssr_renders_in_saved_locale() {
+        let mut headers = HashMap::new();
+        headers.insert("cookie".to_string(), "layer9-locale=ko-KR".to_string());
+        headers.insert("accept-language".to_string(), "de-DE,de;q=0.9".to_string());
+
+        let ctx = SSRContext::new().with_headers(headers);
+        assert_eq!(ctx.locale, crate::i18n::Locale::KoKR);
+
+        let html = SSRRenderer::new().enable_hydration(false).render(&ctx).await;
+        assert!(html.contains(r#"<html lang="ko-KR">"#));
+    }
+
+    #[tokio::test]
+    async fn test_ssr_emits_theme_color_for_dark_theme() {
+        let chrome = crate::styles::ThemeColor::new("#ffffff", "#0b0b0f");
+        let ctx = SSRContext::new().with_theme_color(&chrome, crate::styles::ColorScheme::Dark);
+
+        let html = SSRRenderer::new().enable_hydration(false).render(&ctx).await;
+        assert!(html.contains(r#"<meta name="color-scheme" content="dark">"#));
+        assert!(html.contains(r##"<meta name="theme-color" content="#0b0b0f">"##));
+        assert!(!html.contains("#ffffff"));
+    }
+
+    #[tokio::test]
+    async fn test_ssr_emits_viewport() {
+        let html = SSRRenderer::new().enable_hydration(false).render(&SSRContext::new()).await;
+        assert!(html.contains(r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#));
+
+        let ctx = SSRContext::new().with_viewport(Viewport::default().maximum_scale(1.0).user_scalable(false));
+        let html = SSRRenderer::new().enable_hydration(false).render(&ctx).await;
+        assert!(html.contains(r#"content="width=device-width, initial-scale=1, maximum-scale=1, user-scalable=no""#));
+        assert!(!html.contains("{{viewport}}"));
+    }
     
     #[tokio::test]
     async fn test_ssr_renderer_basic() {
@@ -252,4 +288,261 @@ mod tests {
         assert!(html.contains("Route: /test"));
         assert!(html.contains("123"));
     }
-}
\ No newline at end of file
+
+    /// Stats that take a while to load, or fail to
+    struct SlowStats {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl SSRComponent for SlowStats {
+        fn render_to_string(&self, _ctx: &SSRContext) -> String {
+            "<section>Stats</section>".to_string()
+        }
+
+        async fn get_server_props(&self, _ctx: &SSRContext) -> Result<serde_json::Value, String> {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            if self.fail {
+                return Err("stats service unavailable".to_string());
+            }
+            Ok(serde_json::json!({ "stars": 1200 }))
+        }
+
+        fn render_with_props(&self, _ctx: &SSRContext, props: &serde_json::Value) -> String {
+            format!("<section>Stars: {}</section>", props["stars"])
+        }
+
+        fn stream_fallback(&self, _ctx: &SSRContext) -> Option<String> {
+            Some("<section>Loading stats</section>".to_string())
+        }
+
+        fn error_fallback(&self, _ctx: &SSRContext, error: &str) -> String {
+            format!("<section>Stats unavailable: {}</section>", error)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_sends_the_shell_before_data() {
+        use futures::StreamExt;
+
+        let mut renderer = SSRRenderer::new().enable_hydration(false);
+        renderer.add_component(Box::new(TestComponent {
+            content: "Above the fold".to_string(),
+        }));
+        renderer.add_component(Box::new(SlowStats { fail: false }));
+        renderer.add_component(Box::new(SlowStats { fail: true }));
+
+        let ctx = SSRContext::new();
+        let chunks: Vec<String> = renderer.render_streaming(&ctx).collect().await;
+        assert_eq!(chunks.len(), 4);
+
+        // The shell has the whole head, the static content and fallbacks
+        let shell = &chunks[0];
+        assert!(shell.contains("</head>"));
+        assert!(shell.contains("Above the fold"));
+        assert_eq!(shell.matches("Loading stats").count(), 2);
+        assert!(!shell.contains("Stars:"));
+
+        // Each boundary arrives with the script that swaps it in
+        let streamed = chunks[1..3].concat();
+        assert!(streamed.contains(r#"<template id="l9-chunk-1"><section>Stars: 1200</section></template><script>__l9Stitch(1)</script>"#));
+        assert!(streamed.contains("Stats unavailable: stats service unavailable"));
+        assert!(!streamed.contains("</head>"));
+
+        assert!(chunks[3].contains("</html>"));
+
+        // Without streamed components the page matches a plain render
+        let mut plain = SSRRenderer::new().enable_hydration(false);
+        plain.add_component(Box::new(TestComponent {
+            content: "Above the fold".to_string(),
+        }));
+        let streamed: Vec<String> = plain.render_streaming(&ctx).collect().await;
+        assert_eq!(streamed.concat(), plain.render(&ctx).await);
+    }
+
+    struct SettingsPage;
+
+    #[async_trait]
+    impl SSRComponent for SettingsPage {
+        fn render_to_string(&self, _ctx: &SSRContext) -> String {
+            use crate::component::Element;
+            use crate::head::Head;
+
+            Element::Node {
+                tag: "main".to_string(),
+                props: Box::default(),
+                children: vec![Element::Component(Box::new(
+                    Head::new().title("Settings & Privacy").meta("description", "Account settings"),
+                ))],
+            }
+            .to_html()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ssr_writes_head_declarations() {
+        let mut renderer = SSRRenderer::new().enable_hydration(false);
+        renderer.add_component(Box::new(SettingsPage));
+        let ctx = SSRContext::new().add_meta_tag(r#"<meta name="author" content="Layer9">"#.to_string());
+
+        let html = renderer.render(&ctx).await;
+        let head = &html[..html.find("</head>").unwrap()];
+        assert!(head.contains("<title>Settings &amp; Privacy</title>"));
+        assert!(!html.contains("Layer9 SSR"));
+        assert!(head.contains(r#"<meta name="author" content="Layer9">"#));
+        assert!(head.contains(r#"<meta name="description" content="Account settings" data-l9-head>"#));
+    }
+
+    /// The todo app, written once for the client and the server
+    #[derive(Clone)]
+    struct TodoApp;
+
+    impl crate::component::Component for TodoApp {
+        fn render(&self) -> crate::component::Element {
+            use crate::component::{Element, Props};
+            use crate::hooks::{use_effect, use_state};
+            use std::rc::Rc;
+
+            let (todos, set_todos) = use_state(vec!["Buy milk".to_string(), "Walk the dog".to_string()]);
+            let (draft, set_draft) = use_state(String::new());
+
+            // Browser-only work belongs in effects, which the server skips
+            use_effect(todos.len(), move || {
+                let window = web_sys::window().expect("effects only run in the browser");
+                let _ = window.document();
+                || {}
+            });
+
+            let on_add = {
+                let todos = todos.clone();
+                let draft = draft.clone();
+                Rc::new(move || {
+                    let mut todos = todos.clone();
+                    todos.push(draft.clone());
+                    set_todos(todos);
+                })
+            };
+            let node = |tag: &str, props: Props, children: Vec<Element>| Element::Node {
+                tag: tag.to_string(),
+                props: Box::new(props),
+                children,
+            };
+
+            node(
+                "div",
+                Props {
+                    class: Some("todo-app".to_string()),
+                    ..Default::default()
+                },
+                vec![
+                    node(
+                        "input",
+                        Props {
+                            id: Some("new-todo".to_string()),
+                            on_input: Some(Rc::new(set_draft)),
+                            attributes: vec![("value".to_string(), draft)],
+                            ..Default::default()
+                        },
+                        vec![],
+                    ),
+                    node(
+                        "button",
+                        Props {
+                            on_click: Some(on_add),
+                            ..Default::default()
+                        },
+                        vec![Element::Text("Add".to_string())],
+                    ),
+                    node(
+                        "ul",
+                        Props::default(),
+                        todos
+                            .iter()
+                            .map(|todo| node("li", Props::default(), vec![Element::Text(todo.clone())]))
+                            .collect(),
+                    ),
+                ],
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reactive_component_renders_its_first_paint() {
+        let html = render_to_html_string(&TodoApp);
+        assert_eq!(
+            html,
+            r#"<div class="todo-app"><input id="new-todo" value=""><button>Add</button><ul><li>Buy milk</li><li>Walk the dog</li></ul></div>"#
+        );
+
+        // The same markup the client renders first
+        let client = crate::testing::render(&TodoApp);
+        assert_eq!(client.to_html(), html);
+        assert!(!crate::reactive_v2::is_server_render());
+
+        // And it serves as a page section without a second definition
+        let mut renderer = SSRRenderer::new().enable_hydration(false);
+        renderer.add_component(Box::new(ServerRendered(TodoApp)));
+        let page = renderer.render(&SSRContext::new()).await;
+        assert!(page.contains(&html));
+    }
+
+    /// A component whose render panics on the server
+    #[derive(Clone)]
+    struct Broken;
+
+    impl crate::component::Component for Broken {
+        fn render(&self) -> crate::component::Element {
+            let _ = crate::hooks::use_state(0);
+            panic!("broken component");
+        }
+    }
+
+    struct PageHandler<C>(C);
+
+    #[async_trait]
+    impl<C: crate::component::Component + Clone + Send + Sync + 'static> SSRRouteHandler for PageHandler<C> {
+        async fn handle(&self, ctx: SSRContext) -> Result<String, String> {
+            let mut renderer = SSRRenderer::new().enable_hydration(false);
+            renderer.add_component(Box::new(ServerRendered(self.0.clone())));
+            Ok(renderer.render(&ctx).await)
+        }
+    }
+
+    struct PanickingApp;
+
+    #[async_trait]
+    impl SSRApp for PanickingApp {
+        fn routes(&self) -> Vec<SSRRoute> {
+            vec![
+                SSRRoute {
+                    path: "/broken".to_string(),
+                    handler: Arc::new(PageHandler(Broken)),
+                },
+                SSRRoute {
+                    path: "/todos".to_string(),
+                    handler: Arc::new(PageHandler(TodoApp)),
+                },
+            ]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_component_answers_500() {
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::Service;
+
+        let mut router = create_ssr_server(Arc::new(PanickingApp));
+
+        let request = Request::builder().uri("/broken").body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // The failed render leaves nothing behind for the next request
+        assert!(!crate::reactive_v2::is_server_render());
+        assert_eq!(crate::reactive_v2::get_current_component(), None);
+        let request = Request::builder().uri("/todos").body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
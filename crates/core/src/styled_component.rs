@@ -57,10 +57,10 @@ impl<C: Component> Component for StyledComponent<C> {
                 // Wrap non-node elements in a div with the class
                 element = Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(self.class_name.clone()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![element],
                 };
             }
@@ -261,10 +261,10 @@ impl<T: Component> Component for Styled<T> {
         // Wrap with styled div
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: vec![("style".to_string(), self.styles.clone())],
                 ..Default::default()
-            },
+            }),
             children: vec![inner],
         }
     }
@@ -312,6 +312,131 @@ static DEFAULT_THEME: Lazy<Theme> = Lazy::new(|| Theme {
     },
 });
 
+/// Light or dark appearance of the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+    /// Follow the OS setting
+    #[default]
+    System,
+}
+
+impl ColorScheme {
+    /// Value of the `color-scheme` meta tag
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorScheme::Light => "light",
+            ColorScheme::Dark => "dark",
+            ColorScheme::System => "light dark",
+        }
+    }
+
+    /// Light <-> dark; `System` switches to dark
+    pub fn toggled(&self) -> Self {
+        match self {
+            ColorScheme::Dark => ColorScheme::Light,
+            ColorScheme::Light | ColorScheme::System => ColorScheme::Dark,
+        }
+    }
+}
+
+/// Colors mobile browsers paint their address bar and chrome with:
+///
+/// ```ignore
+/// let chrome = ThemeColor::new("#ffffff", "#0b0b0f");
+///
+/// // Server: <meta name="color-scheme"> and <meta name="theme-color"> in <head>
+/// let ctx = SSRContext::new().with_theme_color(&chrome, ColorScheme::Dark);
+///
+/// // Client, after the user flips the theme
+/// chrome.apply(ColorScheme::Light);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeColor {
+    pub light: String,
+    pub dark: String,
+}
+
+impl ThemeColor {
+    pub fn new(light: impl Into<String>, dark: impl Into<String>) -> Self {
+        ThemeColor {
+            light: light.into(),
+            dark: dark.into(),
+        }
+    }
+
+    /// `(name, content, media)` of each meta tag for `scheme`. Following the
+    /// system gives one `theme-color` per `prefers-color-scheme`.
+    fn metas(&self, scheme: ColorScheme) -> Vec<(&'static str, &str, Option<&'static str>)> {
+        let mut metas = vec![("color-scheme", scheme.as_str(), None)];
+        match scheme {
+            ColorScheme::Light => metas.push(("theme-color", self.light.as_str(), None)),
+            ColorScheme::Dark => metas.push(("theme-color", self.dark.as_str(), None)),
+            ColorScheme::System => {
+                metas.push(("theme-color", self.light.as_str(), Some("(prefers-color-scheme: light)")));
+                metas.push(("theme-color", self.dark.as_str(), Some("(prefers-color-scheme: dark)")));
+            }
+        }
+        metas
+    }
+
+    /// The `<meta>` tags for `scheme`, for server-rendered `<head>`s
+    pub fn meta_tags(&self, scheme: ColorScheme) -> Vec<String> {
+        self.metas(scheme)
+            .into_iter()
+            .map(|(name, content, media)| {
+                let media = media
+                    .map(|media| format!(r#" media="{}""#, media))
+                    .unwrap_or_default();
+                format!(
+                    r#"<meta name="{}" content="{}"{}>"#,
+                    name,
+                    html_escape::encode_double_quoted_attribute(content),
+                    media
+                )
+            })
+            .collect()
+    }
+
+    /// Replace the page's `color-scheme` and `theme-color` meta tags, e.g.
+    /// when the user toggles the theme
+    pub fn apply(&self, scheme: ColorScheme) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+                return;
+            };
+            let Some(head) = document.head() else {
+                return;
+            };
+
+            if let Ok(existing) = document.query_selector_all(r#"meta[name="color-scheme"], meta[name="theme-color"]"#) {
+                for i in 0..existing.length() {
+                    if let Some(node) = existing.item(i) {
+                        let _ = head.remove_child(&node);
+                    }
+                }
+            }
+
+            for (name, content, media) in self.metas(scheme) {
+                let Ok(meta) = document.create_element("meta") else {
+                    continue;
+                };
+                let _ = meta.set_attribute("name", name);
+                let _ = meta.set_attribute("content", content);
+                if let Some(media) = media {
+                    let _ = meta.set_attribute("media", media);
+                }
+                let _ = head.append_child(&meta);
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = scheme;
+    }
+}
+
 /// Global styles injection
 pub fn inject_global_styles() {
     let window = web_sys::window().unwrap();
@@ -352,6 +477,12 @@ pub fn inject_global_styles() {
             animation: pulse 1.5s ease-in-out infinite;
         }
         
+        /* Indeterminate progress bars */
+        @keyframes progress-indeterminate {
+            from { transform: translateX(-100%); }
+            to { transform: translateX(250%); }
+        }
+        
         /* Reality glitch effect */
         @keyframes glitch {
             0% { transform: translate(0); }
@@ -399,3 +530,30 @@ pub use style;
 //     transition,
 //     hover_bg_gray_100,
 // ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_color_meta_tags() {
+        let chrome = ThemeColor::new("#ffffff", "#0b0b0f");
+
+        assert_eq!(
+            chrome.meta_tags(ColorScheme::Dark),
+            vec![
+                r#"<meta name="color-scheme" content="dark">"#,
+                r##"<meta name="theme-color" content="#0b0b0f">"##,
+            ]
+        );
+        assert_eq!(
+            chrome.meta_tags(ColorScheme::System),
+            vec![
+                r#"<meta name="color-scheme" content="light dark">"#,
+                r##"<meta name="theme-color" content="#ffffff" media="(prefers-color-scheme: light)">"##,
+                r##"<meta name="theme-color" content="#0b0b0f" media="(prefers-color-scheme: dark)">"##,
+            ]
+        );
+        assert_eq!(ColorScheme::Dark.toggled(), ColorScheme::Light);
+    }
+}
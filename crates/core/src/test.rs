@@ -311,23 +311,23 @@ mod tests {
 
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![
                         Element::Node {
                             tag: "span".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 id: Some("count".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![Element::Text(count.get().to_string())],
                         },
                         Element::Node {
                             tag: "button".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 id: Some("increment".to_string()),
                                 on_click: Some(Rc::new(move || count.set(count.get() + 1))),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![Element::Text("Increment".to_string())],
                         },
                     ],
@@ -0,0 +1,410 @@
+//! Component Testing - L7
+//!
+//! Render a component natively, query its `Element` tree and fire its
+//! handlers, without a browser:
+//!
+//! ```ignore
+//! let app = TodoApp;
+//! let mut tree = testing::render(&app);
+//! tree.input("#new-todo", "Write docs");
+//! tree.click("button.add");
+//! assert!(tree.find_by_text("Write docs").is_some());
+//! ```
+//!
+//! The component renders as one test instance, so hook state (`use_state`,
+//! `use_reducer`, `use_ref`, ...) carries over from render to render. Every
+//! simulated event re-renders; effects only run when `flush_effects` is
+//! called. Selectors are a single compound of a tag, an `#id` and
+//! `.class`es: `button`, `#save`, `.todo-item`, `li.todo-item.done`.
+
+use crate::component::{Component, Element, Props};
+use crate::hooks::{cleanup_component_hooks, reset_hook_index};
+use crate::reactive_v2::{take_pending_effects, with_current_component};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Ids of test instances, far above any the renderer hands out
+static NEXT_TEST_COMPONENT_ID: AtomicU32 = AtomicU32::new(1 << 30);
+
+/// Render `component` and return its tree for querying
+pub fn render(component: &dyn Component) -> TestTree<'_> {
+    let mut tree = TestTree {
+        component,
+        component_id: NEXT_TEST_COMPONENT_ID.fetch_add(1, Ordering::Relaxed),
+        root: Element::Text(String::new()),
+        effect_cleanups: Vec::new(),
+    };
+    tree.rerender();
+    tree
+}
+
+/// A rendered component, with nested components rendered in place and
+/// portal children queried where the portal sits
+pub struct TestTree<'a> {
+    component: &'a dyn Component,
+    component_id: u32,
+    root: Element,
+    effect_cleanups: Vec<Box<dyn FnOnce()>>,
+}
+
+impl TestTree<'_> {
+    pub fn root(&self) -> &Element {
+        &self.root
+    }
+
+    pub fn to_html(&self) -> String {
+        self.root.to_html()
+    }
+
+    /// Render again, as the renderer would after a state change
+    pub fn rerender(&mut self) {
+        let component = self.component;
+        self.root = with_current_component(self.component_id, || {
+            reset_hook_index();
+            resolve(component.render())
+        });
+    }
+
+    /// Run the effects queued by renders so far
+    pub fn flush_effects(&mut self) {
+        for effect in take_pending_effects(self.component_id) {
+            self.effect_cleanups.push(effect());
+        }
+    }
+
+    /// The innermost element whose text is exactly `text`, ignoring
+    /// surrounding whitespace
+    pub fn find_by_text(&self, text: &str) -> Option<&Element> {
+        fn find<'e>(element: &'e Element, text: &str) -> Option<&'e Element> {
+            let (Element::Node { children, .. } | Element::Portal { children, .. } | Element::Fragment(children)) =
+                element
+            else {
+                return None;
+            };
+            children
+                .iter()
+                .find_map(|child| find(child, text))
+                .or_else(|| (text_content(element).trim() == text).then_some(element))
+        }
+        find(&self.root, text.trim())
+    }
+
+    pub fn find_by_class(&self, class: &str) -> Option<&Element> {
+        self.find_all_by_class(class).into_iter().next()
+    }
+
+    pub fn find_all_by_class(&self, class: &str) -> Vec<&Element> {
+        self.find_all(&format!(".{}", class))
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Option<&Element> {
+        self.find_all_by_tag(tag).into_iter().next()
+    }
+
+    pub fn find_all_by_tag(&self, tag: &str) -> Vec<&Element> {
+        self.find_all(tag)
+    }
+
+    /// The first element matching `selector`, in document order
+    pub fn find(&self, selector: &str) -> Option<&Element> {
+        self.find_all(selector).into_iter().next()
+    }
+
+    /// Every element matching `selector`, in document order
+    pub fn find_all(&self, selector: &str) -> Vec<&Element> {
+        let selector = Selector::parse(selector);
+        let mut found = Vec::new();
+        collect(&self.root, &selector, &mut found);
+        found
+    }
+
+    /// Call the `on_click` of the first element matching `selector`, then
+    /// re-render. Panics if nothing matches or it has no click handler.
+    pub fn click(&mut self, selector: &str) {
+        let on_click = self
+            .handler(selector, |props| props.on_click.clone())
+            .unwrap_or_else(|| panic!("`{}` has no on_click handler", selector));
+        on_click();
+        self.rerender();
+    }
+
+    /// Type `value` into the first element matching `selector`: its
+    /// `on_input` and then `on_change` handlers receive it, then re-render
+    pub fn input(&mut self, selector: &str, value: &str) {
+        let on_input = self.handler(selector, |props| props.on_input.clone());
+        let on_change = self.handler(selector, |props| props.on_change.clone());
+        if on_input.is_none() && on_change.is_none() {
+            panic!("`{}` has no on_input or on_change handler", selector);
+        }
+        if let Some(on_input) = on_input {
+            on_input(value.to_string());
+        }
+        if let Some(on_change) = on_change {
+            on_change(value.to_string());
+        }
+        self.rerender();
+    }
+
+    /// Press `key` on the first element matching `selector`, then re-render
+    pub fn keydown(&mut self, selector: &str, key: &str) {
+        let on_keydown = self
+            .handler(selector, |props| props.on_keydown.clone())
+            .unwrap_or_else(|| panic!("`{}` has no on_keydown handler", selector));
+        on_keydown(key.to_string());
+        self.rerender();
+    }
+
+    /// Cloned out of the tree so the handler can't observe it mid-borrow
+    fn handler<H>(&self, selector: &str, get: impl Fn(&Props) -> Option<H>) -> Option<H> {
+        match self.find(selector) {
+            Some(Element::Node { props, .. }) => get(props),
+            _ => panic!("no element matches `{}`", selector),
+        }
+    }
+}
+
+impl Drop for TestTree<'_> {
+    fn drop(&mut self) {
+        for cleanup in self.effect_cleanups.drain(..) {
+            cleanup();
+        }
+        take_pending_effects(self.component_id);
+        cleanup_component_hooks(self.component_id);
+    }
+}
+
+/// All text inside `element`, concatenated
+pub fn text_content(element: &Element) -> String {
+    match element {
+        Element::Text(text) => text.clone(),
+        Element::Node { children, .. } | Element::Portal { children, .. } | Element::Fragment(children) => {
+            children.iter().map(text_content).collect()
+        }
+        Element::Component(component) => text_content(&component.render()),
+    }
+}
+
+/// Replace nested components with what they render, within the test
+/// instance's hook context as the renderer does
+fn resolve(element: Element) -> Element {
+    match element {
+        Element::Component(component) => resolve(component.render()),
+        Element::Node { tag, props, children } => Element::Node {
+            tag,
+            props,
+            children: children.into_iter().map(resolve).collect(),
+        },
+        Element::Portal { target, children } => Element::Portal {
+            target,
+            children: children.into_iter().map(resolve).collect(),
+        },
+        Element::Fragment(children) => Element::Fragment(children.into_iter().map(resolve).collect()),
+        text => text,
+    }
+}
+
+fn collect<'e>(element: &'e Element, selector: &Selector, found: &mut Vec<&'e Element>) {
+    match element {
+        Element::Node { tag, props, children } => {
+            if selector.matches(tag, props) {
+                found.push(element);
+            }
+            for child in children {
+                collect(child, selector, found);
+            }
+        }
+        // Portal and fragment children are queried as if rendered in place
+        Element::Portal { children, .. } | Element::Fragment(children) => {
+            for child in children {
+                collect(child, selector, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Default)]
+struct Selector {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+impl Selector {
+    fn parse(selector: &str) -> Self {
+        let mut parsed = Selector::default();
+        let selector = selector.trim();
+        let split = selector.find(['#', '.']).unwrap_or(selector.len());
+        if split > 0 {
+            parsed.tag = Some(selector[..split].to_ascii_lowercase());
+        }
+
+        let mut rest = &selector[split..];
+        while let Some(kind) = rest.chars().next() {
+            let end = rest[1..].find(['#', '.']).map_or(rest.len(), |i| i + 1);
+            let name = rest[1..end].to_string();
+            if kind == '#' {
+                parsed.id = Some(name);
+            } else {
+                parsed.classes.push(name);
+            }
+            rest = &rest[end..];
+        }
+        parsed
+    }
+
+    fn matches(&self, tag: &str, props: &Props) -> bool {
+        let classes: Vec<&str> = props.class.as_deref().unwrap_or("").split_whitespace().collect();
+        self.tag.as_deref().is_none_or(|wanted| tag.eq_ignore_ascii_case(wanted))
+            && self.id.as_deref().is_none_or(|wanted| props.id.as_deref() == Some(wanted))
+            && self.classes.iter().all(|class| classes.contains(&class.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::{use_effect, use_state};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn node(tag: &str, class: &str, props: Props, children: Vec<Element>) -> Element {
+        Element::Node {
+            tag: tag.to_string(),
+            props: Box::new(Props {
+                class: (!class.is_empty()).then(|| class.to_string()),
+                ..props
+            }),
+            children,
+        }
+    }
+
+    fn text(value: &str) -> Element {
+        Element::Text(value.to_string())
+    }
+
+    struct TodoList {
+        saves: Rc<Cell<usize>>,
+    }
+
+    impl Component for TodoList {
+        fn render(&self) -> Element {
+            let (todos, set_todos) = use_state(vec!["Buy milk".to_string()]);
+            let (draft, set_draft) = use_state(String::new());
+
+            let saves = self.saves.clone();
+            use_effect(todos.len(), move || {
+                saves.set(saves.get() + 1);
+                || {}
+            });
+
+            let on_input = {
+                let set_draft = set_draft.clone();
+                Rc::new(move |value: String| set_draft(value))
+            };
+            let on_add = {
+                let todos = todos.clone();
+                let draft = draft.clone();
+                Rc::new(move || {
+                    if !draft.trim().is_empty() {
+                        let mut todos = todos.clone();
+                        todos.push(draft.trim().to_string());
+                        set_todos(todos);
+                        set_draft(String::new());
+                    }
+                })
+            };
+
+            node(
+                "div",
+                "todo-app",
+                Props::default(),
+                vec![
+                    node(
+                        "input",
+                        "",
+                        Props {
+                            id: Some("new-todo".to_string()),
+                            on_input: Some(on_input),
+                            attributes: vec![("value".to_string(), draft)],
+                            ..Default::default()
+                        },
+                        vec![],
+                    ),
+                    node(
+                        "button",
+                        "add primary",
+                        Props {
+                            on_click: Some(on_add),
+                            ..Default::default()
+                        },
+                        vec![text("Add")],
+                    ),
+                    node(
+                        "ul",
+                        "",
+                        Props::default(),
+                        todos
+                            .iter()
+                            .map(|todo| node("li", "todo-item", Props::default(), vec![text(todo)]))
+                            .collect(),
+                    ),
+                ],
+            )
+        }
+    }
+
+    #[test]
+    fn test_todo_add_flow() {
+        let app = TodoList {
+            saves: Rc::new(Cell::new(0)),
+        };
+        let mut tree = render(&app);
+        assert_eq!(tree.find_all_by_class("todo-item").len(), 1);
+        assert!(tree.find_by_text("Write docs").is_none());
+
+        tree.input("#new-todo", "  Write docs ");
+        tree.click("button.add");
+
+        let items = tree.find_all("li.todo-item");
+        assert_eq!(items.len(), 2);
+        assert_eq!(text_content(items[1]), "Write docs");
+        assert!(matches!(tree.find_by_text("Write docs"), Some(Element::Node { tag, .. }) if tag == "li"));
+
+        // The input was cleared through its state
+        assert!(tree.to_html().contains("value=\"\""));
+
+        // Effects wait for flush_effects; the effect only re-ran because the
+        // list changed between renders
+        assert_eq!(app.saves.get(), 0);
+        tree.flush_effects();
+        assert_eq!(app.saves.get(), 2);
+        tree.flush_effects();
+        assert_eq!(app.saves.get(), 2);
+    }
+
+    #[test]
+    fn test_queries_and_missing_handlers() {
+        let tree_root = node(
+            "section",
+            "card",
+            Props::default(),
+            vec![node("h2", "title", Props::default(), vec![text("Stats")])],
+        );
+        struct Static(Element);
+        impl Component for Static {
+            fn render(&self) -> Element {
+                self.0.clone()
+            }
+        }
+
+        let page = Static(tree_root);
+        let mut tree = render(&page);
+        assert!(tree.find_by_tag("H2").is_some());
+        assert!(tree.find("section.card").is_some());
+        assert!(tree.find(".card.missing").is_none());
+        assert!(matches!(tree.find_by_text("Stats"), Some(Element::Node { tag, .. }) if tag == "h2"));
+
+        let missing = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| tree.click("h2")));
+        assert!(missing.is_err());
+    }
+}
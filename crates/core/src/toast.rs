@@ -0,0 +1,400 @@
+//! Toast Notifications - L5
+//!
+//! A `ToastProvider` owns a queue of transient notifications and renders them
+//! into a container attached to the document body, so page layout (overflow,
+//! stacking contexts) never clips them. Components reach the queue through
+//! `use_toast()`.
+
+use crate::component::{Component, Element, Props};
+use crate::hooks::{provide_context, use_context, use_persisted, Context};
+use crate::reactive_v2::{get_current_component, queue_component_render};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::callback::Timeout;
+
+/// DOM id of the container toasts are rendered into
+pub const TOAST_ROOT_ID: &str = "layer9-toast-root";
+
+/// Toast identifier, returned by `ToastHandle::show`
+pub type ToastId = u32;
+
+/// Visual style of a toast
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    fn color(&self) -> &'static str {
+        match self {
+            ToastKind::Info => "#3b82f6",
+            ToastKind::Success => "#10b981",
+            ToastKind::Warning => "#f59e0b",
+            ToastKind::Error => "#ef4444",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ToastKind::Info => "info",
+            ToastKind::Success => "success",
+            ToastKind::Warning => "warning",
+            ToastKind::Error => "error",
+        }
+    }
+}
+
+/// Options for a single toast
+#[derive(Debug, Clone)]
+pub struct ToastOptions {
+    pub message: String,
+    pub kind: ToastKind,
+    /// Time before the toast dismisses itself; `0` keeps it until clicked
+    pub duration_ms: u32,
+}
+
+impl Default for ToastOptions {
+    fn default() -> Self {
+        ToastOptions {
+            message: String::new(),
+            kind: ToastKind::Info,
+            duration_ms: 4000,
+        }
+    }
+}
+
+/// A toast currently on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub id: ToastId,
+    pub message: String,
+    pub kind: ToastKind,
+}
+
+struct ToastEntry {
+    toast: Toast,
+    // Dropping the timer cancels it, so dismissing early never fires a stale callback
+    #[cfg(target_arch = "wasm32")]
+    _timer: Option<Timeout>,
+}
+
+#[derive(Default)]
+struct ToastQueue {
+    entries: Vec<ToastEntry>,
+    next_id: ToastId,
+    /// Component rendering the toasts, re-rendered when they change
+    owner: Option<u32>,
+}
+
+/// Handle to the toast queue, shared by the provider and `use_toast()`
+#[derive(Clone, Default)]
+pub struct ToastHandle {
+    queue: Rc<RefCell<ToastQueue>>,
+}
+
+impl ToastHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show a toast and return its id
+    pub fn show(&self, options: ToastOptions) -> ToastId {
+        let id = {
+            let mut queue = self.queue.borrow_mut();
+            queue.next_id += 1;
+            queue.next_id
+        };
+
+        let entry = ToastEntry {
+            toast: Toast {
+                id,
+                message: options.message,
+                kind: options.kind,
+            },
+            #[cfg(target_arch = "wasm32")]
+            _timer: self.schedule_dismiss(id, options.duration_ms),
+        };
+
+        self.queue.borrow_mut().entries.push(entry);
+        self.changed();
+        id
+    }
+
+    /// Remove a toast, cancelling its timer if it is still pending
+    pub fn dismiss(&self, id: ToastId) {
+        let removed = {
+            let mut queue = self.queue.borrow_mut();
+            let before = queue.entries.len();
+            queue.entries.retain(|entry| entry.toast.id != id);
+            queue.entries.len() != before
+        };
+
+        if removed {
+            self.changed();
+        }
+    }
+
+    /// Remove every toast
+    pub fn clear(&self) {
+        self.queue.borrow_mut().entries.clear();
+        self.changed();
+    }
+
+    /// Toasts currently on screen, oldest first
+    pub fn toasts(&self) -> Vec<Toast> {
+        self.queue
+            .borrow()
+            .entries
+            .iter()
+            .map(|entry| entry.toast.clone())
+            .collect()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn schedule_dismiss(&self, id: ToastId, duration_ms: u32) -> Option<Timeout> {
+        if duration_ms == 0 {
+            return None;
+        }
+
+        let queue = Rc::downgrade(&self.queue);
+        Some(Timeout::new(duration_ms, move || {
+            if let Some(queue) = queue.upgrade() {
+                // Defer so the timer isn't dropped while its own callback is running
+                wasm_bindgen_futures::spawn_local(async move {
+                    ToastHandle { queue }.dismiss(id);
+                });
+            }
+        }))
+    }
+
+    /// Render the toast stack
+    pub fn view(&self) -> Element {
+        let toasts = self
+            .toasts()
+            .into_iter()
+            .map(|toast| {
+                let handle = self.clone();
+                let id = toast.id;
+                let role = if toast.kind == ToastKind::Error { "alert" } else { "status" };
+
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some(format!("toast toast-{}", toast.kind.as_str())),
+                        attributes: vec![
+                            ("role".to_string(), role.to_string()),
+                            (
+                                "style".to_string(),
+                                format!(
+                                    "background: {}; color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; box-shadow: 0 4px 12px rgba(0,0,0,0.15); cursor: pointer; min-width: 240px",
+                                    toast.kind.color()
+                                ),
+                            ),
+                        ],
+                        on_click: Some(Rc::new(move || handle.dismiss(id))),
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(toast.message)],
+                }
+            })
+            .collect();
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("toast-stack".to_string()),
+                attributes: vec![
+                    ("aria-live".to_string(), "polite".to_string()),
+                    (
+                        "style".to_string(),
+                        "position: fixed; bottom: 1rem; right: 1rem; z-index: 9999; display: flex; flex-direction: column; gap: 0.5rem".to_string(),
+                    ),
+                ],
+                ..Default::default()
+            }),
+            children: toasts,
+        }
+    }
+
+    fn changed(&self) {
+        let owner = self.queue.borrow().owner;
+        if let Some(owner) = owner {
+            queue_component_render(owner);
+        }
+    }
+}
+
+/// Provides a toast queue to its subtree
+pub struct ToastProvider {
+    children: Vec<Element>,
+}
+
+impl Default for ToastProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToastProvider {
+    pub fn new() -> Self {
+        ToastProvider { children: vec![] }
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+impl Component for ToastProvider {
+    fn render(&self) -> Element {
+        // The queue of the first render, so toasts outlive the parent's re-renders
+        let handle = use_persisted(ToastHandle::new).borrow().clone();
+        handle.queue.borrow_mut().owner = get_current_component();
+        provide_context(&Context::<ToastHandle>::new(), handle.clone());
+
+        let mut children = self.children.clone();
+        children.push(Element::portal(format!("#{}", TOAST_ROOT_ID), vec![handle.view()]));
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("toast-provider".to_string()),
+                ..Default::default()
+            }),
+            children,
+        }
+    }
+}
+
+/// Toast hook - returns the handle of the nearest `ToastProvider`
+pub fn use_toast() -> ToastHandle {
+    use_context(&Context::<ToastHandle>::new())
+        .expect("use_toast must be used within a ToastProvider")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str, duration_ms: u32) -> ToastOptions {
+        ToastOptions {
+            message: text.to_string(),
+            kind: ToastKind::Success,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_show_and_dismiss() {
+        let toast = ToastHandle::new();
+
+        let first = toast.show(message("User created", 0));
+        let second = toast.show(message("Saved", 0));
+        assert_eq!(toast.toasts().len(), 2);
+
+        toast.dismiss(first);
+        let remaining = toast.toasts();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, second);
+    }
+
+    #[test]
+    fn test_click_dismisses_toast() {
+        let toast = ToastHandle::new();
+        toast.show(message("Sticky", 0));
+
+        let Element::Node { children, .. } = toast.view() else {
+            panic!("expected toast stack");
+        };
+        let Element::Node { props, .. } = &children[0] else {
+            panic!("expected toast");
+        };
+        (props.on_click.as_ref().unwrap())();
+
+        assert!(toast.toasts().is_empty());
+    }
+
+    /// Builds its provider afresh on every render, as components do
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Page;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Page {
+        fn render(&self) -> Element {
+            let save = Element::Node {
+                tag: "button".to_string(),
+                props: Box::new(Props {
+                    id: Some("save".to_string()),
+                    on_click: Some(Rc::new(|| {
+                        use_toast().show(message("Saved", 0));
+                    })),
+                    ..Default::default()
+                }),
+                children: vec![Element::Text("Save".to_string())],
+            };
+            ToastProvider::new().children(vec![save]).render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_provider_keeps_its_toasts_across_rerenders() {
+        let page = Page;
+        let mut tree = crate::testing::render(&page);
+        tree.click("#save");
+        tree.rerender();
+
+        let Some(Element::Portal { target, children }) = tree.find("div.toast-provider").and_then(|provider| match provider {
+            Element::Node { children, .. } => children.last(),
+            _ => None,
+        }) else {
+            panic!("expected the toast portal");
+        };
+        assert_eq!(*target, crate::portal::PortalTarget::from("#layer9-toast-root"));
+        assert_eq!(crate::testing::text_content(&children[0]), "Saved");
+
+        tree.click("div.toast");
+        assert!(tree.find("div.toast").is_none());
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_toast_disappears_after_duration() {
+        let toast = ToastHandle::new();
+        toast.show(ToastOptions {
+            message: "User created".to_string(),
+            kind: ToastKind::Success,
+            duration_ms: 50,
+        });
+        assert_eq!(toast.toasts().len(), 1);
+
+        gloo_timers::future::TimeoutFuture::new(100).await;
+        assert!(toast.toasts().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_sticky_toast_stays() {
+        let toast = ToastHandle::new();
+        toast.show(ToastOptions {
+            message: "Sticky".to_string(),
+            duration_ms: 0,
+            ..Default::default()
+        });
+
+        gloo_timers::future::TimeoutFuture::new(50).await;
+        assert_eq!(toast.toasts().len(), 1);
+    }
+}
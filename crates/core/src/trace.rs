@@ -0,0 +1,408 @@
+//! Trace Context - L4
+//!
+//! W3C Trace Context (`traceparent`) propagation, so one trace id connects a
+//! request's SSR render, its DB queries and the upstream calls it makes:
+//!
+//! ```ignore
+//! trace::set_exporter(|span| println!("{} {} {:.1}ms", span.trace_id, span.name, span.duration_ms));
+//!
+//! let stack = MiddlewareStack::new().use_middleware(TracingMiddleware);
+//! // Inside a handler:
+//! trace::log("loading dashboard");                      // tagged with trace_id/span_id
+//! let stats = trace::in_span("github.stats", http::get(url)).await?; // sends traceparent
+//! ```
+//!
+//! The current context follows a future wherever it is polled (see
+//! `instrument`), so it works the same on the server and in the browser.
+
+use crate::middleware_v2::{Context, Middleware, MiddlewareError, Next, Response};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+
+/// Header carrying the trace context between services
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+type Exporter = Box<dyn Fn(&SpanRecord) + Send + Sync>;
+
+static EXPORTER: Lazy<RwLock<Option<Exporter>>> = Lazy::new(|| RwLock::new(None));
+
+thread_local! {
+    static CURRENT: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+/// Position in a trace: which trace, and which span within it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits
+    pub trace_id: String,
+    /// 16 lowercase hex digits identifying the current span
+    pub span_id: String,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Start a new trace
+    pub fn new_root() -> Self {
+        TraceContext {
+            trace_id: format!("{:016x}{:016x}", random_id(), random_id()),
+            span_id: format!("{:016x}", random_id()),
+            sampled: true,
+        }
+    }
+
+    /// Parse a `traceparent` header, e.g.
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        // Later versions may append fields; version 00 may not
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_hex(trace_id, 32) || !is_hex(span_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+        if trace_id.bytes().all(|b| b == b'0') || span_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+
+        Some(TraceContext {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            sampled: u8::from_str_radix(flags, 16).ok()? & 1 == 1,
+        })
+    }
+
+    /// Same trace, new span
+    pub fn child(&self) -> Self {
+        TraceContext {
+            trace_id: self.trace_id.clone(),
+            span_id: format!("{:016x}", random_id()),
+            sampled: self.sampled,
+        }
+    }
+
+    pub fn to_traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id,
+            self.span_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn random_id() -> u64 {
+    let high = (js_sys::Math::random() * 4_294_967_296.0) as u64;
+    let low = (js_sys::Math::random() * 4_294_967_296.0) as u64;
+    (high << 32 | low).max(1)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn random_id() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    // RandomState is seeded per process; the counter keeps ids unique within it
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish().max(1)
+}
+
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Trace context of the code running now, if any
+pub fn current() -> Option<TraceContext> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Run `future` with `context` as the current trace context
+pub fn instrument<F: Future>(context: TraceContext, future: F) -> Traced<F> {
+    Traced {
+        context,
+        inner: Box::pin(future),
+    }
+}
+
+/// Future that makes its trace context current each time it is polled
+pub struct Traced<F> {
+    context: TraceContext,
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Traced<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<F::Output> {
+        struct Restore(Option<TraceContext>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+
+        let previous = CURRENT.with(|current| current.replace(Some(self.context.clone())));
+        let _restore = Restore(previous);
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+/// A finished span, as handed to the exporter
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanRecord {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub duration_ms: f64,
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Send every finished span to `exporter`, replacing any previous one
+pub fn set_exporter(exporter: impl Fn(&SpanRecord) + Send + Sync + 'static) {
+    *EXPORTER.write() = Some(Box::new(exporter));
+}
+
+/// A timed unit of work within a trace
+pub struct Span {
+    context: TraceContext,
+    parent_id: Option<String>,
+    name: String,
+    started_ms: f64,
+    attributes: Vec<(String, String)>,
+}
+
+impl Span {
+    /// Start a span under the current context, or a new trace if there is none
+    pub fn start(name: impl Into<String>) -> Self {
+        match current() {
+            Some(parent) => Self::child_of(&parent, name),
+            None => Self::new(TraceContext::new_root(), None, name.into()),
+        }
+    }
+
+    /// Start a span under `parent`, e.g. one parsed from an incoming request
+    pub fn child_of(parent: &TraceContext, name: impl Into<String>) -> Self {
+        Self::new(parent.child(), Some(parent.span_id.clone()), name.into())
+    }
+
+    fn new(context: TraceContext, parent_id: Option<String>, name: String) -> Self {
+        Span {
+            context,
+            parent_id,
+            name,
+            started_ms: now_ms(),
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn context(&self) -> &TraceContext {
+        &self.context
+    }
+
+    pub fn set_attribute(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.attributes.push((key.into(), value.into()));
+    }
+
+    /// End the span and export it if sampled
+    pub fn finish(self) -> SpanRecord {
+        let record = SpanRecord {
+            trace_id: self.context.trace_id,
+            span_id: self.context.span_id,
+            parent_id: self.parent_id,
+            name: self.name,
+            duration_ms: (now_ms() - self.started_ms).max(0.0),
+            attributes: self.attributes,
+        };
+        if self.context.sampled {
+            if let Some(exporter) = EXPORTER.read().as_ref() {
+                exporter(&record);
+            }
+        }
+        record
+    }
+}
+
+/// Run `future` in a child span of the current context
+pub async fn in_span<F: Future>(name: impl Into<String>, future: F) -> F::Output {
+    let span = Span::start(name);
+    let output = instrument(span.context().clone(), future).await;
+    span.finish();
+    output
+}
+
+/// `message` prefixed with the current trace and span ids
+pub fn with_trace_ids(message: &str) -> String {
+    match current() {
+        Some(trace) => format!("[trace_id={} span_id={}] {}", trace.trace_id, trace.span_id, message),
+        None => message.to_string(),
+    }
+}
+
+/// Log `message` tagged with the current trace
+pub fn log(message: &str) {
+    let line = with_trace_ids(message);
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&line.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    tracing::info!("{}", line);
+}
+
+/// Continues the caller's trace (or starts one) around each request
+pub struct TracingMiddleware;
+
+#[async_trait(?Send)]
+impl Middleware for TracingMiddleware {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+        let incoming = ctx
+            .request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(TRACEPARENT_HEADER))
+            .and_then(|(_, value)| TraceContext::parse(value));
+
+        let name = format!("{} {}", ctx.request.method.as_str(), ctx.request.url.split('?').next().unwrap_or_default());
+        let mut span = match &incoming {
+            Some(parent) => Span::child_of(parent, name),
+            None => Span::new(TraceContext::new_root(), None, name),
+        };
+        ctx.state.insert(
+            "trace_context".to_string(),
+            Box::new(span.context().clone()),
+        );
+
+        let result = instrument(span.context().clone(), next(ctx)).await;
+        let status = match &result {
+            Ok(response) => response.status,
+            Err(error) => error.status,
+        };
+        span.set_attribute("http.status_code", status.to_string());
+        span.finish();
+        result
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::http::{self, AbortHandle, HttpRequest, Transport};
+    use crate::middleware_v2::{next_fn, test_context};
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const INCOMING: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_and_format_traceparent() {
+        let trace = TraceContext::parse(INCOMING).unwrap();
+        assert_eq!(trace.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace.span_id, "00f067aa0ba902b7");
+        assert!(trace.sampled);
+        assert_eq!(trace.to_traceparent(), INCOMING);
+
+        let child = trace.child();
+        assert_eq!(child.trace_id, trace.trace_id);
+        assert_ne!(child.span_id, trace.span_id);
+
+        assert!(TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+        // Future versions may carry extra fields
+        assert!(TraceContext::parse(&format!("01{}-extra", &INCOMING[2..])).is_some());
+    }
+
+    /// Upstream API that records the headers it was called with
+    struct Upstream(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Transport for Upstream {
+        fn send(
+            &self,
+            request: HttpRequest,
+            _abort: Option<AbortHandle>,
+        ) -> Pin<Box<dyn Future<Output = Result<http::Response, http::HttpError>>>> {
+            *self.0.lock().unwrap() = request.headers;
+            Box::pin(async { Ok(http::Response::new(200, HashMap::new(), "{}")) })
+        }
+    }
+
+    #[test]
+    fn test_request_trace_reaches_logs_and_upstream_calls() {
+        let spans = Arc::new(Mutex::new(Vec::new()));
+        {
+            let spans = spans.clone();
+            set_exporter(move |span| spans.lock().unwrap().push(span.clone()));
+        }
+
+        let upstream_headers = Arc::new(Mutex::new(Vec::new()));
+        let log_line = Arc::new(Mutex::new(String::new()));
+        let next = {
+            let (upstream_headers, log_line) = (upstream_headers.clone(), log_line.clone());
+            next_fn(move |_| {
+                Box::pin(async move {
+                    *log_line.lock().unwrap() = with_trace_ids("rendering dashboard");
+                    let upstream = Upstream(upstream_headers);
+                    in_span("github.stats", http::get("/repos/stats").send_with(&upstream))
+                        .await
+                        .unwrap();
+                    Ok(Response::new())
+                })
+            })
+        };
+
+        let mut ctx = test_context("/dashboard");
+        ctx.request.headers.insert("Traceparent".to_string(), INCOMING.to_string());
+        block_on(TracingMiddleware.handle(&mut ctx, next)).unwrap();
+
+        let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+        assert!(log_line.lock().unwrap().starts_with(&format!("[trace_id={} ", trace_id)));
+
+        let headers = upstream_headers.lock().unwrap();
+        let outgoing = headers.iter().find(|(name, _)| name == TRACEPARENT_HEADER).unwrap();
+        assert_eq!(TraceContext::parse(&outgoing.1).unwrap().trace_id, trace_id);
+
+        // Other tests may export spans too; keep this trace's
+        let spans: Vec<SpanRecord> = spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.trace_id == trace_id)
+            .cloned()
+            .collect();
+        let server = spans.iter().find(|s| s.name == "GET /dashboard").unwrap();
+        let upstream = spans.iter().find(|s| s.name == "github.stats").unwrap();
+        assert_eq!(server.parent_id.as_deref(), Some("00f067aa0ba902b7"));
+        assert_eq!(upstream.parent_id.as_ref(), Some(&server.span_id));
+        assert!(server.attributes.contains(&("http.status_code".to_string(), "200".to_string())));
+        assert!(current().is_none());
+    }
+}
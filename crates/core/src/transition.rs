@@ -0,0 +1,368 @@
+//! Enter/Exit Transitions - L5
+//!
+//! `Transition` animates its children in when shown and out when hidden,
+//! with CSS classes named after the transition:
+//!
+//! ```ignore
+//! Transition::new(show_hint)
+//!     .name("fade")
+//!     .duration(200)
+//!     .children(vec![hint()])
+//! ```
+//!
+//! ```css
+//! .fade-enter, .fade-exit-active { opacity: 0 }
+//! .fade-enter-active, .fade-exit-active { transition: opacity 200ms }
+//! .fade-enter-active { opacity: 1 }
+//! ```
+//!
+//! When the wrapper is inserted it gets `fade-enter`, then `fade-enter-active`
+//! on the next style recalculation, both removed once the enter duration has
+//! passed. When it is removed or replaced it gets `fade-exit`, then
+//! `fade-exit-active`, and stays in the document until `transitionend` fires
+//! on it or the exit duration passes, whichever is first. Showing it again
+//! while it is still leaving drops the leaving copy and enters afresh.
+//!
+//! The patcher does the timing, so any element carrying the transition
+//! attributes gets it, e.g. list items removed from the middle of a list.
+//! Only the root of an inserted, removed or replaced subtree transitions.
+
+use crate::component::{Component, Element, Props};
+use wasm_bindgen::JsCast;
+use web_sys::{Element as DomElement, Node};
+
+/// Names the transition's classes on the wrapper
+const TRANSITION_ATTR: &str = "data-l9-transition";
+const ENTER_MS_ATTR: &str = "data-l9-enter-ms";
+const EXIT_MS_ATTR: &str = "data-l9-exit-ms";
+/// Marks an element that has left the virtual DOM but is still animating
+/// out: `replaced` when something took its place, `removed` otherwise
+const EXITING_ATTR: &str = "data-l9-exiting";
+
+/// Shows or hides its children with enter and exit classes
+pub struct Transition {
+    show: bool,
+    name: String,
+    enter_ms: u32,
+    exit_ms: u32,
+    class: Option<String>,
+    children: Vec<Element>,
+}
+
+impl Transition {
+    pub fn new(show: bool) -> Self {
+        Transition {
+            show,
+            name: "transition".to_string(),
+            enter_ms: 300,
+            exit_ms: 300,
+            class: None,
+            children: vec![],
+        }
+    }
+
+    /// Prefix of the classes, e.g. `fade` for `fade-enter-active`
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Both durations in milliseconds
+    pub fn duration(self, ms: u32) -> Self {
+        self.enter_ms(ms).exit_ms(ms)
+    }
+
+    pub fn enter_ms(mut self, ms: u32) -> Self {
+        self.enter_ms = ms;
+        self
+    }
+
+    /// `0` removes the children as soon as they are hidden
+    pub fn exit_ms(mut self, ms: u32) -> Self {
+        self.exit_ms = ms;
+        self
+    }
+
+    /// Extra classes on the wrapper
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+impl Component for Transition {
+    fn render(&self) -> Element {
+        if !self.show {
+            // Holds the place, so showing again replaces it
+            return Element::Text(String::new());
+        }
+
+        let class = match &self.class {
+            Some(class) => format!("layer9-transition {}", class),
+            None => "layer9-transition".to_string(),
+        };
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some(class),
+                attributes: vec![
+                    (TRANSITION_ATTR.to_string(), self.name.clone()),
+                    (ENTER_MS_ATTR.to_string(), self.enter_ms.to_string()),
+                    (EXIT_MS_ATTR.to_string(), self.exit_ms.to_string()),
+                ],
+                ..Default::default()
+            }),
+            children: self.children.clone(),
+        }
+    }
+}
+
+/// The transition name and one of its durations, if `node` transitions
+fn transition_of(node: &Node, duration_attr: &str) -> Option<(DomElement, String, u32)> {
+    let element = node.dyn_ref::<DomElement>()?;
+    let name = element.get_attribute(TRANSITION_ATTR)?;
+    let ms = element
+        .get_attribute(duration_attr)
+        .and_then(|ms| ms.parse().ok())
+        .unwrap_or(0);
+    Some((element.clone(), name, ms))
+}
+
+/// Whether `node` is only still in the document to animate out; the patcher
+/// doesn't count such nodes when it finds children by index
+pub(crate) fn is_exiting(node: &Node) -> bool {
+    node.dyn_ref::<DomElement>()
+        .is_some_and(|element| element.has_attribute(EXITING_ATTR))
+}
+
+/// The `index`th child of `parent` that isn't exiting
+pub(crate) fn child_at(parent: &Node, index: usize) -> Option<Node> {
+    let children = parent.child_nodes();
+    (0..children.length())
+        .filter_map(|i| children.item(i))
+        .filter(|child| !is_exiting(child))
+        .nth(index)
+}
+
+/// Insert `node` before `before`, or last, and start its enter transition
+pub(crate) fn insert(parent: &Node, node: &Node, before: Option<&Node>) {
+    parent.insert_before(node, before).unwrap();
+    enter(node);
+}
+
+/// Put `new` where `old` is, letting `old` transition out
+pub(crate) fn replace(old: &Node, new: &Node) {
+    let Some(parent) = old.parent_node() else {
+        return;
+    };
+
+    // The same transition re-rendered, e.g. by its parent, stays as it is
+    let old_name = transition_of(old, EXIT_MS_ATTR).map(|(_, name, _)| name);
+    if old_name.is_some() && old_name == transition_of(new, ENTER_MS_ATTR).map(|(_, name, _)| name) {
+        crate::portal::unmount_within(old);
+        parent.replace_child(new, old).unwrap();
+        return;
+    }
+
+    // Shown again while the copy that left this place is still leaving
+    if let Some(leaving) = old.next_sibling().filter(|next| {
+        next.dyn_ref::<DomElement>()
+            .and_then(|element| element.get_attribute(EXITING_ATTR))
+            .as_deref()
+            == Some("replaced")
+    }) {
+        detach(&leaving);
+    }
+
+    insert(&parent, new, Some(old));
+    leave(old, "replaced");
+}
+
+/// Take `node` out of the document, after its exit transition if it has one
+pub(crate) fn remove(node: &Node) {
+    leave(node, "removed");
+}
+
+fn enter(node: &Node) {
+    let Some((element, name, ms)) = transition_of(node, ENTER_MS_ATTR) else {
+        return;
+    };
+    if ms == 0 {
+        return;
+    }
+
+    let (enter, active) = (format!("{}-enter", name), format!("{}-enter-active", name));
+    let classes = element.class_list();
+    let _ = classes.add_1(&enter);
+    reflow(&element);
+    let _ = classes.add_1(&active);
+
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::callback::Timeout::new(ms, move || {
+        let _ = element.class_list().remove_2(&enter, &active);
+    })
+    .forget();
+}
+
+fn leave(node: &Node, reason: &str) {
+    let Some((element, name, ms)) = transition_of(node, EXIT_MS_ATTR) else {
+        detach(node);
+        return;
+    };
+    if ms == 0 || is_exiting(node) {
+        detach(node);
+        return;
+    }
+
+    let _ = element.set_attribute(EXITING_ATTR, reason);
+    let classes = element.class_list();
+    let _ = classes.remove_2(&format!("{}-enter", name), &format!("{}-enter-active", name));
+    let _ = classes.add_1(&format!("{}-exit", name));
+    reflow(&element);
+    let _ = classes.add_1(&format!("{}-exit-active", name));
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::closure::Closure;
+
+        // Either way, only a node still leaving is removed; an interrupted
+        // exit has already detached it
+        let finish = {
+            let element = element.clone();
+            move || {
+                if element.is_connected() && element.has_attribute(EXITING_ATTR) {
+                    detach(&element);
+                }
+            }
+        };
+
+        let on_end = {
+            let element = element.clone();
+            let finish = finish.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                // Transitions of descendants bubble up too
+                if event.target().as_ref() == Some(element.as_ref()) {
+                    finish();
+                }
+            })
+        };
+        let _ = element.add_event_listener_with_callback("transitionend", on_end.as_ref().unchecked_ref());
+        on_end.forget();
+
+        gloo_timers::callback::Timeout::new(ms, finish).forget();
+    }
+
+    // There are no timers outside the browser, so it leaves straight away
+    #[cfg(not(target_arch = "wasm32"))]
+    detach(&element);
+}
+
+/// Remove `node` now, with anything it portaled elsewhere
+fn detach(node: &Node) {
+    crate::portal::unmount_within(node);
+    if let Some(parent) = node.parent_node() {
+        let _ = parent.remove_child(node);
+    }
+}
+
+/// Make the browser apply the classes so far, so the next ones transition
+/// from them rather than replacing them
+fn reflow(element: &DomElement) {
+    if let Some(element) = element.dyn_ref::<web_sys::HtmlElement>() {
+        let _ = element.offset_width();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapper_carries_the_transition_timing() {
+        let shown = Transition::new(true)
+            .name("fade")
+            .enter_ms(150)
+            .exit_ms(250)
+            .class("card")
+            .children(vec![Element::Text("Matched!".to_string())]);
+        assert_eq!(
+            shown.render().to_html(),
+            "<div class=\"layer9-transition card\" data-l9-transition=\"fade\" \
+             data-l9-enter-ms=\"150\" data-l9-exit-ms=\"250\">Matched!</div>"
+        );
+
+        // Hidden, only an empty placeholder is left
+        let hidden = Transition::new(false).children(vec![Element::Text("Matched!".to_string())]);
+        assert_eq!(hidden.render().to_html(), "");
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use crate::vdom::VDom;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn list(shown: bool, after: &str) -> Element {
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::default(),
+            children: vec![
+                Transition::new(shown)
+                    .name("fade")
+                    .duration(60)
+                    .children(vec![Element::Text("Leaving".to_string())])
+                    .render(),
+                Element::Text(after.to_string()),
+            ],
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_removed_child_stays_until_the_exit_ends() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let root = document.create_element("div").unwrap();
+        document.body().unwrap().append_child(&root).unwrap();
+        root.append_child(&list(true, "After").to_dom()).unwrap();
+
+        let vdom = VDom::new();
+        let hide = vdom.diff(&list(true, "After"), &list(false, "After"), &[0]);
+        vdom.apply_patches(&hide, &root);
+
+        let leaving = root.query_selector(".layer9-transition").unwrap().unwrap();
+        assert!(leaving.class_list().contains("fade-exit-active"));
+        assert_eq!(leaving.get_attribute(EXITING_ATTR).as_deref(), Some("replaced"));
+
+        // Patches by index skip the leaving node
+        let rename = vdom.diff(&list(false, "After"), &list(false, "Next"), &[0]);
+        vdom.apply_patches(&rename, &root);
+        assert_eq!(root.text_content().unwrap(), "LeavingNext");
+
+        gloo_timers::future::TimeoutFuture::new(30).await;
+        assert!(leaving.is_connected());
+
+        gloo_timers::future::TimeoutFuture::new(80).await;
+        assert!(!leaving.is_connected());
+        assert_eq!(root.text_content().unwrap(), "Next");
+
+        // Showing again mid-exit drops the leaving copy
+        let show = vdom.diff(&list(false, "Next"), &list(true, "Next"), &[0]);
+        let hide = vdom.diff(&list(true, "Next"), &list(false, "Next"), &[0]);
+        vdom.apply_patches(&show, &root);
+        vdom.apply_patches(&hide, &root);
+        vdom.apply_patches(&show, &root);
+        assert_eq!(root.query_selector_all(".layer9-transition").unwrap().length(), 1);
+        assert!(root.query_selector("[data-l9-exiting]").unwrap().is_none());
+        assert!(root.query_selector(".layer9-transition").unwrap().unwrap().class_list().contains("fade-enter-active"));
+
+        root.remove();
+    }
+}
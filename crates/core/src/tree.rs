@@ -0,0 +1,658 @@
+//! Tree View - L5
+//!
+//! `TreeView` follows the WAI-ARIA tree pattern: one item is tabbable
+//! (roving `tabindex`), Up/Down move through the visible items, Right opens a
+//! branch or steps into it, Left closes it or steps out to the parent,
+//! Home/End jump to the ends, and Enter/Space select.
+//!
+//! ```ignore
+//! TreeView::new("Categories", vec![
+//!     TreeNode::branch("books", "Books", vec![
+//!         TreeNode::leaf("fiction", "Fiction"),
+//!         TreeNode::leaf("poetry", "Poetry"),
+//!     ]),
+//!     TreeNode::lazy("music", "Music"),
+//! ])
+//! .load_children(|id| async move { fetch_categories(&id).await })
+//! .on_select(|id| navigate(&format!("/categories/{}", id)))
+//! ```
+//!
+//! Collapsed branches stay in the DOM, hidden, so each item keeps the click
+//! handler it was first rendered with as others open and close.
+
+use crate::component::{Component, Element, Props, State};
+use crate::hooks::{use_widget_id, use_widget_state};
+use crate::menu::focus_later;
+use futures::future::LocalBoxFuture;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_TREE_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Node of a `TreeView`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeNode {
+    /// Unique within the tree; what `on_select` is called with
+    pub id: String,
+    pub label: String,
+    pub children: Vec<TreeNode>,
+    /// Children come from `TreeView::load_children` on first expand
+    pub lazy: bool,
+}
+
+impl TreeNode {
+    pub fn leaf(id: impl Into<String>, label: impl Into<String>) -> Self {
+        TreeNode {
+            id: id.into(),
+            label: label.into(),
+            children: vec![],
+            lazy: false,
+        }
+    }
+
+    pub fn branch(id: impl Into<String>, label: impl Into<String>, children: Vec<TreeNode>) -> Self {
+        TreeNode {
+            children,
+            ..TreeNode::leaf(id, label)
+        }
+    }
+
+    /// Branch whose children are loaded the first time it opens
+    pub fn lazy(id: impl Into<String>, label: impl Into<String>) -> Self {
+        TreeNode {
+            lazy: true,
+            ..TreeNode::leaf(id, label)
+        }
+    }
+
+    pub fn is_branch(&self) -> bool {
+        self.lazy || !self.children.is_empty()
+    }
+}
+
+/// What the user has opened, selected and focused, plus lazily loaded
+/// children
+#[derive(Clone, Debug, Default)]
+pub struct TreeViewState {
+    pub expanded: HashSet<String>,
+    pub selected: Option<String>,
+    /// The tabbable item; the first one when `None`
+    pub focused: Option<String>,
+    loaded: HashMap<String, Vec<TreeNode>>,
+    loading: HashSet<String>,
+}
+
+impl TreeViewState {
+    /// Children of `node`, once loaded if it's lazy
+    fn children<'a>(&'a self, node: &'a TreeNode) -> &'a [TreeNode] {
+        if node.lazy {
+            self.loaded.get(&node.id).map(Vec::as_slice).unwrap_or_default()
+        } else {
+            &node.children
+        }
+    }
+}
+
+/// Loads the children of a lazy node, given its id
+pub type ChildLoader = Rc<dyn Fn(String) -> LocalBoxFuture<'static, Vec<TreeNode>>>;
+
+/// Called with the id of the selected node
+type SelectHandler = Rc<dyn Fn(&str)>;
+
+/// A visible item, in display order
+struct Visible<'a> {
+    node: &'a TreeNode,
+    parent: Option<&'a str>,
+}
+
+fn visible<'a>(nodes: &'a [TreeNode], state: &'a TreeViewState) -> Vec<Visible<'a>> {
+    fn walk<'a>(
+        nodes: &'a [TreeNode],
+        parent: Option<&'a str>,
+        state: &'a TreeViewState,
+        out: &mut Vec<Visible<'a>>,
+    ) {
+        for node in nodes {
+            out.push(Visible { node, parent });
+            if state.expanded.contains(&node.id) {
+                walk(state.children(node), Some(&node.id), state, out);
+            }
+        }
+    }
+
+    let mut out = vec![];
+    walk(nodes, None, state, &mut out);
+    out
+}
+
+/// Tree view component - nested, expandable items with one selection
+pub struct TreeView {
+    id: Option<String>,
+    label: String,
+    nodes: Rc<Vec<TreeNode>>,
+    state: State<TreeViewState>,
+    load_children: Option<ChildLoader>,
+    on_select: Option<SelectHandler>,
+}
+
+impl TreeView {
+    /// `label` names the tree for screen readers, e.g. "Categories"
+    pub fn new(label: impl Into<String>, nodes: Vec<TreeNode>) -> Self {
+        TreeView {
+            id: None,
+            label: label.into(),
+            nodes: Rc::new(nodes),
+            state: State::new(TreeViewState::default()),
+            load_children: None,
+            on_select: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Share expansion and selection with the parent
+    pub fn state(mut self, state: State<TreeViewState>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Fetch the children of `TreeNode::lazy` nodes
+    pub fn load_children<F>(mut self, load: impl Fn(String) -> F + 'static) -> Self
+    where
+        F: Future<Output = Vec<TreeNode>> + 'static,
+    {
+        self.load_children = Some(Rc::new(move |id| Box::pin(load(id))));
+        self
+    }
+
+    pub fn on_select(mut self, handler: impl Fn(&str) + 'static) -> Self {
+        self.on_select = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn selected(&self) -> Option<String> {
+        self.state.get().selected
+    }
+
+    pub fn is_expanded(&self, id: &str) -> bool {
+        self.state.get().expanded.contains(id)
+    }
+
+    /// Open or close the branch `id`, loading its children if needed
+    pub fn toggle(&self, id: &str) {
+        self.handle().toggle(id);
+    }
+
+    pub fn select(&self, id: &str) {
+        self.handle().select(id);
+    }
+
+    /// Handle a key pressed on the tree, as the keydown handler does
+    pub fn key(&self, key: &str) {
+        self.handle().key(key);
+    }
+
+    fn handle(&self) -> TreeHandle {
+        TreeHandle {
+            nodes: self.nodes.clone(),
+            state: self.state.clone(),
+            load_children: self.load_children.clone(),
+            on_select: self.on_select.clone(),
+        }
+    }
+
+    fn item_id(id: &str, node_id: &str) -> String {
+        let node_id: String = node_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        format!("{}-item-{}", id, node_id)
+    }
+
+    fn items(
+        &self,
+        id: &str,
+        handle: &TreeHandle,
+        nodes: &[TreeNode],
+        level: usize,
+        current: &TreeViewState,
+        tabbable: &str,
+    ) -> Vec<Element> {
+        nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let expanded = current.expanded.contains(&node.id);
+                let loading = current.loading.contains(&node.id);
+                let mut attributes = vec![
+                    ("role".to_string(), "treeitem".to_string()),
+                    ("aria-level".to_string(), level.to_string()),
+                    ("aria-setsize".to_string(), nodes.len().to_string()),
+                    ("aria-posinset".to_string(), (index + 1).to_string()),
+                    (
+                        "aria-selected".to_string(),
+                        (current.selected.as_deref() == Some(node.id.as_str())).to_string(),
+                    ),
+                    (
+                        "tabindex".to_string(),
+                        if node.id == tabbable { "0" } else { "-1" }.to_string(),
+                    ),
+                    ("style".to_string(), "outline-offset: -2px".to_string()),
+                ];
+                if node.is_branch() {
+                    attributes.push(("aria-expanded".to_string(), expanded.to_string()));
+                }
+                if loading {
+                    attributes.push(("aria-busy".to_string(), "true".to_string()));
+                }
+
+                let on_click = {
+                    let handle = handle.clone();
+                    let node_id = node.id.clone();
+                    let item_id = Self::item_id(id, &node.id);
+                    Rc::new(move || {
+                        handle.click(&node_id);
+                        focus_later(item_id.clone());
+                    }) as Rc<dyn Fn()>
+                };
+
+                let mut row = vec![
+                    Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![
+                                ("aria-hidden".to_string(), "true".to_string()),
+                                (
+                                    "style".to_string(),
+                                    format!(
+                                        "display: inline-block; width: 1rem; transition: transform 150ms ease; transform: rotate({}deg)",
+                                        if expanded { 90 } else { 0 }
+                                    ),
+                                ),
+                            ],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text(if node.is_branch() { "▸" } else { "" }.to_string())],
+                    },
+                    Element::Text(node.label.clone()),
+                ];
+                if loading {
+                    row.push(Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![("style".to_string(), "margin-left: 0.5rem; color: #6b7280".to_string())],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text("Loading…".to_string())],
+                    });
+                }
+
+                // The click handler sits on the row rather than the item, so
+                // clicks inside a nested group don't also reach its parent
+                let mut children = vec![Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("tree-row".to_string()),
+                        attributes: vec![(
+                            "style".to_string(),
+                            format!(
+                                "display: flex; align-items: center; gap: 0.25rem; padding: 0.25rem 0.5rem 0.25rem {}rem; cursor: pointer; border-radius: 0.25rem{}",
+                                0.5 + (level - 1) as f32 * 1.25,
+                                if current.selected.as_deref() == Some(node.id.as_str()) { "; background: #eef2ff" } else { "" }
+                            ),
+                        )],
+                        on_click: Some(on_click),
+                        ..Default::default()
+                    }),
+                    children: row,
+                }];
+
+                if node.is_branch() {
+                    let mut group_attributes = vec![
+                        ("role".to_string(), "group".to_string()),
+                        ("style".to_string(), "margin: 0; padding: 0; list-style: none".to_string()),
+                    ];
+                    if !expanded {
+                        group_attributes.push(("hidden".to_string(), "hidden".to_string()));
+                    }
+                    children.push(Element::Node {
+                        tag: "ul".to_string(),
+                        props: Box::new(Props {
+                            attributes: group_attributes,
+                            ..Default::default()
+                        }),
+                        children: self.items(id, handle, current.children(node), level + 1, current, tabbable),
+                    });
+                }
+
+                Element::Node {
+                    tag: "li".to_string(),
+                    props: Box::new(Props {
+                        id: Some(Self::item_id(id, &node.id)),
+                        attributes,
+                        ..Default::default()
+                    }),
+                    children,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Everything the event handlers need, shared between them
+#[derive(Clone)]
+struct TreeHandle {
+    nodes: Rc<Vec<TreeNode>>,
+    state: State<TreeViewState>,
+    load_children: Option<ChildLoader>,
+    on_select: Option<SelectHandler>,
+}
+
+impl TreeHandle {
+    fn find<'a>(nodes: &'a [TreeNode], state: &'a TreeViewState, id: &str) -> Option<&'a TreeNode> {
+        nodes.iter().find_map(|node| {
+            if node.id == id {
+                Some(node)
+            } else {
+                Self::find(state.children(node), state, id)
+            }
+        })
+    }
+
+    fn select(&self, id: &str) {
+        let mut next = self.state.get();
+        next.selected = Some(id.to_string());
+        next.focused = Some(id.to_string());
+        self.state.set(next);
+        if let Some(on_select) = &self.on_select {
+            on_select(id);
+        }
+    }
+
+    /// A click selects the item, and opens or closes a branch
+    fn click(&self, id: &str) {
+        let current = self.state.get();
+        let is_branch = Self::find(&self.nodes, &current, id).is_some_and(TreeNode::is_branch);
+        self.select(id);
+        if is_branch {
+            self.toggle(id);
+        }
+    }
+
+    fn toggle(&self, id: &str) {
+        if self.state.get().expanded.contains(id) {
+            self.collapse(id);
+        } else {
+            self.expand(id);
+        }
+    }
+
+    fn collapse(&self, id: &str) {
+        let mut next = self.state.get();
+        next.expanded.remove(id);
+        self.state.set(next);
+    }
+
+    fn expand(&self, id: &str) {
+        let mut next = self.state.get();
+        let Some(node) = Self::find(&self.nodes, &next, id) else {
+            return;
+        };
+        let needs_load = node.lazy && !next.loaded.contains_key(id) && !next.loading.contains(id);
+        next.expanded.insert(id.to_string());
+
+        let Some(load) = self.load_children.clone().filter(|_| needs_load) else {
+            self.state.set(next);
+            return;
+        };
+        next.loading.insert(id.to_string());
+        self.state.set(next);
+
+        let state = self.state.clone();
+        let id = id.to_string();
+        run_local(async move {
+            let children = load(id.clone()).await;
+            let mut next = state.get();
+            next.loading.remove(&id);
+            next.loaded.insert(id, children);
+            state.set(next);
+        });
+    }
+
+    fn key(&self, key: &str) {
+        let current = self.state.get();
+        let items = visible(&self.nodes, &current);
+        if items.is_empty() {
+            return;
+        }
+        let position = current
+            .focused
+            .as_deref()
+            .and_then(|focused| items.iter().position(|item| item.node.id == focused))
+            .unwrap_or(0);
+        let item = &items[position];
+        let expanded = current.expanded.contains(&item.node.id);
+
+        let focus = |index: usize| {
+            let mut next = self.state.get();
+            next.focused = Some(items[index].node.id.clone());
+            self.state.set(next);
+        };
+
+        match key {
+            "ArrowDown" if position + 1 < items.len() => focus(position + 1),
+            "ArrowUp" if position > 0 => focus(position - 1),
+            "Home" => focus(0),
+            "End" => focus(items.len() - 1),
+            "ArrowRight" if item.node.is_branch() && !expanded => self.expand(&item.node.id),
+            // The first child follows its open parent, if it has loaded
+            "ArrowRight" if items.get(position + 1).is_some_and(|next| next.parent == Some(&item.node.id)) => {
+                focus(position + 1)
+            }
+            "ArrowLeft" if expanded => self.collapse(&item.node.id),
+            "ArrowLeft" => {
+                if let Some(parent) = items.iter().position(|other| Some(other.node.id.as_str()) == item.parent) {
+                    focus(parent);
+                }
+            }
+            "Enter" | " " => self.select(&item.node.id),
+            _ => {}
+        }
+    }
+}
+
+/// Run a child load; in the browser it completes on a later tick
+fn run_local(future: impl Future<Output = ()> + 'static) {
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    futures::executor::block_on(future);
+}
+
+impl Component for TreeView {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-tree-{}", NEXT_TREE_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let handle = TreeHandle {
+            state: use_widget_state(&self.state),
+            ..self.handle()
+        };
+        let current = handle.state.get();
+        let tabbable = current
+            .focused
+            .clone()
+            .filter(|focused| visible(&self.nodes, &current).iter().any(|item| &item.node.id == focused))
+            .or_else(|| self.nodes.first().map(|node| node.id.clone()))
+            .unwrap_or_default();
+
+        let on_keydown = {
+            let id = id.clone();
+            let handle = handle.clone();
+            Rc::new(move |key: String| {
+                handle.key(&key);
+                if let Some(focused) = handle.state.get().focused {
+                    focus_later(TreeView::item_id(&id, &focused));
+                }
+            }) as Rc<dyn Fn(String)>
+        };
+
+        Element::Node {
+            tag: "ul".to_string(),
+            props: Box::new(Props {
+                id: Some(id.clone()),
+                class: Some("tree-view".to_string()),
+                attributes: vec![
+                    ("role".to_string(), "tree".to_string()),
+                    ("aria-label".to_string(), self.label.clone()),
+                    ("style".to_string(), "margin: 0; padding: 0; list-style: none".to_string()),
+                ],
+                on_keydown: Some(on_keydown),
+                ..Default::default()
+            }),
+            children: self.items(&id, &handle, &self.nodes, 1, &current, &tabbable),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn categories() -> TreeView {
+        TreeView::new(
+            "Categories",
+            vec![
+                TreeNode::branch(
+                    "books",
+                    "Books",
+                    vec![TreeNode::leaf("fiction", "Fiction"), TreeNode::leaf("poetry", "Poetry")],
+                ),
+                TreeNode::lazy("music", "Music"),
+                TreeNode::leaf("games", "Games"),
+            ],
+        )
+        .id("tree")
+    }
+
+    fn focused(tree: &TreeView) -> Option<String> {
+        tree.state.get().focused
+    }
+
+    #[test]
+    fn test_tree_keyboard_navigation_and_selection() {
+        let chosen = Rc::new(RefCell::new(vec![]));
+        let tree = {
+            let chosen = chosen.clone();
+            categories().on_select(move |id| chosen.borrow_mut().push(id.to_string()))
+        };
+
+        // Collapsed children are skipped
+        tree.key("ArrowDown");
+        assert_eq!(focused(&tree).as_deref(), Some("music"));
+        tree.key("ArrowUp");
+        tree.key("ArrowRight");
+        assert!(tree.is_expanded("books"));
+        tree.key("ArrowRight");
+        assert_eq!(focused(&tree).as_deref(), Some("fiction"));
+        tree.key("End");
+        assert_eq!(focused(&tree).as_deref(), Some("games"));
+        tree.key("Home");
+        tree.key("ArrowDown");
+        tree.key("ArrowDown");
+        tree.key("Enter");
+        assert_eq!(tree.selected().as_deref(), Some("poetry"));
+        assert_eq!(*chosen.borrow(), vec!["poetry"]);
+
+        // Left steps out to the parent, then closes it
+        tree.key("ArrowLeft");
+        assert_eq!(focused(&tree).as_deref(), Some("books"));
+        tree.key("ArrowLeft");
+        assert!(!tree.is_expanded("books"));
+
+        let html = tree.render().to_html();
+        assert!(html.contains(r#"id="tree-item-poetry""#));
+        assert!(html.contains(r#"aria-selected="true""#));
+        // Closed groups stay rendered, hidden
+        assert!(html.contains(r#"role="group" style="margin: 0; padding: 0; list-style: none" hidden="hidden""#));
+    }
+
+    #[test]
+    fn test_lazy_children_load_on_first_expand() {
+        let loads = Rc::new(RefCell::new(0));
+        let tree = {
+            let loads = loads.clone();
+            categories().load_children(move |id| {
+                *loads.borrow_mut() += 1;
+                async move { vec![TreeNode::leaf(format!("{}-jazz", id), "Jazz")] }
+            })
+        };
+
+        tree.toggle("music");
+        assert!(tree.render().to_html().contains("music-jazz"));
+        tree.toggle("music");
+        tree.toggle("music");
+        assert_eq!(*loads.borrow(), 1);
+
+        // Clicking a leaf selects it without expanding anything
+        tree.handle().click("music-jazz");
+        assert_eq!(tree.selected().as_deref(), Some("music-jazz"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Sidebar;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Sidebar {
+        fn render(&self) -> Element {
+            categories().render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_tree_keeps_expansion_across_rerenders() {
+        let sidebar = Sidebar;
+        let mut tree = crate::testing::render(&sidebar);
+        tree.keydown("#tree", "ArrowRight");
+        tree.rerender();
+
+        let Some(Element::Node { props, .. }) = tree.find("#tree-item-books") else {
+            panic!("expected the books item");
+        };
+        assert!(props.attributes.contains(&("aria-expanded".to_string(), "true".to_string())));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Untitled;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Untitled {
+        fn render(&self) -> Element {
+            TreeView::new("Categories", vec![TreeNode::leaf("games", "Games")]).render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_generated_id_survives_rerenders() {
+        let untitled = Untitled;
+        let mut tree = crate::testing::render(&untitled);
+        let id = |tree: &crate::testing::TestTree| match tree.find(".tree-view") {
+            Some(Element::Node { props, .. }) => props.id.clone().unwrap(),
+            _ => panic!("expected the tree"),
+        };
+        let first = id(&tree);
+        assert!(first.starts_with("layer9-tree-"));
+
+        tree.rerender();
+        assert_eq!(id(&tree), first);
+    }
+}
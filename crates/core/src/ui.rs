@@ -1,14 +1,26 @@
 //! UI Component Library - L5 (shadcn/ui in Rust)
 
-use crate::component::{Component, Element, Props};
+use crate::announce::{use_announce, VISUALLY_HIDDEN_STYLE};
+use crate::component::{Component, Element, Props, State};
+use crate::css_runtime::Breakpoint;
+use crate::hooks::{copy_to_clipboard, use_persisted, use_widget_id, use_widget_state, CopyStatus};
+use crate::layout::{Direction, Flex};
+use crate::menu::focus_later;
 use crate::styles::style;
+use std::cell::RefCell;
 use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Button component
 pub struct Button {
     text: String,
     variant: ButtonVariant,
     on_click: Option<Rc<dyn Fn()>>,
+    class: Option<String>,
+    button_type: Option<String>,
+    loading: bool,
+    disabled: bool,
 }
 
 #[derive(Clone, Copy)]
@@ -26,6 +38,10 @@ impl Button {
             text: text.into(),
             variant: ButtonVariant::Primary,
             on_click: None,
+            class: None,
+            button_type: None,
+            loading: false,
+            disabled: false,
         }
     }
 
@@ -38,6 +54,28 @@ impl Button {
         self.on_click = Some(Rc::new(handler));
         self
     }
+
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// The `type` attribute, e.g. `"button"` to avoid submitting an enclosing form
+    pub fn button_type(mut self, button_type: impl Into<String>) -> Self {
+        self.button_type = Some(button_type.into());
+        self
+    }
+
+    /// Show a spinner and ignore clicks while an action is in flight
+    pub fn loading(mut self, loading: bool) -> Self {
+        self.loading = loading;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
 }
 
 impl Component for Button {
@@ -54,16 +92,62 @@ impl Component for Button {
             ButtonVariant::Destructive => style![text_white()], // bg_red_500
         };
 
-        let style_str = format!("{};{}", base_style.build(), variant_style.build());
+        let interactive = !self.loading && !self.disabled;
+        let mut style_str = format!("{};{}", base_style.build(), variant_style.build());
+        if !interactive {
+            style_str.push_str(";opacity:0.6;cursor:not-allowed");
+        }
+
+        let mut attributes = vec![("style".to_string(), style_str)];
+        if let Some(button_type) = &self.button_type {
+            attributes.push(("type".to_string(), button_type.clone()));
+        }
+        if self.disabled {
+            attributes.push(("disabled".to_string(), "disabled".to_string()));
+        }
+        if !interactive {
+            attributes.push(("aria-disabled".to_string(), "true".to_string()));
+        }
+        if self.loading {
+            attributes.push(("aria-busy".to_string(), "true".to_string()));
+        }
+
+        let mut children = vec![];
+        if self.loading {
+            children.push(Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    class: Some("spinner".to_string()),
+                    attributes: vec![
+                        ("aria-hidden".to_string(), "true".to_string()),
+                        (
+                            "style".to_string(),
+                            "display:inline-block;width:1em;height:1em;margin-right:0.5em;vertical-align:middle;border:2px solid currentColor;border-right-color:transparent;border-radius:50%;animation:spin 0.8s linear infinite".to_string(),
+                        ),
+                    ],
+                    ..Default::default()
+                }),
+                children: vec![],
+            });
+        }
+        children.push(Element::Text(self.text.clone()));
+
+        let class = match (&self.class, self.loading) {
+            (Some(class), true) => Some(format!("{} loading", class)),
+            (Some(class), false) => Some(class.clone()),
+            (None, true) => Some("loading".to_string()),
+            (None, false) => None,
+        };
 
         Element::Node {
             tag: "button".to_string(),
-            props: Props {
-                attributes: vec![("style".to_string(), style_str)],
-                on_click: self.on_click.clone(),
+            props: Box::new(Props {
+                class,
+                attributes,
+                on_click: if interactive { self.on_click.clone() } else { None },
                 ..Default::default()
-            },
-            children: vec![Element::Text(self.text.clone())],
+            }),
+            children,
         }
     }
 }
@@ -72,6 +156,7 @@ impl Component for Button {
 pub struct Card {
     children: Vec<Element>,
     class: Option<String>,
+    row_from: Option<Breakpoint>,
 }
 
 impl Default for Card {
@@ -85,6 +170,7 @@ impl Card {
         Card {
             children: vec![],
             class: None,
+            row_from: None,
         }
     }
 
@@ -97,6 +183,13 @@ impl Card {
         self.class = Some(class.into());
         self
     }
+
+    /// Put the children side by side once the card itself is at least
+    /// `breakpoint` wide, and stack them when it's narrower
+    pub fn row_from(mut self, breakpoint: Breakpoint) -> Self {
+        self.row_from = Some(breakpoint);
+        self
+    }
 }
 
 impl Component for Card {
@@ -113,12 +206,19 @@ impl Component for Card {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: self.class.clone(),
                 attributes: vec![("style".to_string(), style.build())],
                 ..Default::default()
+            }),
+            children: match self.row_from {
+                Some(breakpoint) => vec![Flex::column()
+                    .gap(4)
+                    .container(breakpoint, Direction::Row)
+                    .children(self.children.clone())
+                    .render()],
+                None => self.children.clone(),
             },
-            children: self.children.clone(),
         }
     }
 }
@@ -186,15 +286,346 @@ impl Component for Input {
 
         Element::Node {
             tag: "input".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: attrs,
                 ..Default::default()
-            },
+            }),
             children: vec![],
         }
     }
 }
 
+/// Numbers a `NumberInput` can hold
+pub trait NumberValue: Copy + PartialOrd + FromStr + ToString + 'static {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! impl_number_value {
+    ($round:path; $($t:ty),*) => {$(
+        impl NumberValue for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(value: f64) -> Self {
+                $round(value) as $t
+            }
+        }
+    )*};
+}
+
+impl_number_value!(f64::round; i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_number_value!(std::convert::identity; f32, f64);
+
+/// Range and step shared by a `NumberInput`'s handlers
+#[derive(Clone, Copy)]
+struct NumberRange {
+    min: Option<f64>,
+    max: Option<f64>,
+    step: f64,
+}
+
+impl NumberRange {
+    fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    /// `steps` steps away from `base`; an empty input starts from the
+    /// nearest bound
+    fn step_from(&self, base: Option<f64>, steps: f64) -> f64 {
+        let Some(base) = base else {
+            let start = if steps > 0.0 { self.min } else { self.max };
+            return self.clamp(start.unwrap_or(0.0));
+        };
+        // Round to the step's precision so 0.1 + 0.2 shows as 0.3
+        let decimals = self.step.to_string().split('.').nth(1).map_or(0, str::len) as i32;
+        let scale = 10f64.powi(decimals);
+        self.clamp(((base + steps * self.step) * scale).round() / scale)
+    }
+}
+
+static NEXT_NUMBER_INPUT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Number input component - a text field with up/down steppers
+pub struct NumberInput<T: NumberValue> {
+    id: Option<String>,
+    value: Option<T>,
+    range: NumberRange,
+    on_change: Option<Rc<dyn Fn(T)>>,
+    label: Option<String>,
+    placeholder: Option<String>,
+    attributes: Vec<(String, String)>,
+}
+
+impl<T: NumberValue> Default for NumberInput<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: NumberValue> NumberInput<T> {
+    pub fn new() -> Self {
+        NumberInput {
+            id: None,
+            value: None,
+            range: NumberRange {
+                min: None,
+                max: None,
+                step: 1.0,
+            },
+            on_change: None,
+            label: None,
+            placeholder: None,
+            attributes: vec![],
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn value(mut self, value: Option<T>) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn min(mut self, min: T) -> Self {
+        self.range.min = Some(min.to_f64());
+        self
+    }
+
+    pub fn max(mut self, max: T) -> Self {
+        self.range.max = Some(max.to_f64());
+        self
+    }
+
+    pub fn step(mut self, step: T) -> Self {
+        self.range.step = step.to_f64();
+        self
+    }
+
+    /// Called with the clamped number whenever it changes
+    pub fn on_change(mut self, handler: impl Fn(T) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+
+    /// Accessible name, for inputs without a `<label for>`
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Extra attribute on the text field, e.g. `aria-describedby`
+    pub fn attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
+    fn stepper(&self, id: &str, label: &str, text: &str, steps: f64, disabled: bool) -> Element {
+        let mut attributes = vec![
+            ("type".to_string(), "button".to_string()),
+            ("tabindex".to_string(), "-1".to_string()),
+            ("aria-label".to_string(), label.to_string()),
+            ("aria-controls".to_string(), id.to_string()),
+            (
+                "style".to_string(),
+                "width: 2rem; border: 0; background: #f3f4f6; font: inherit; cursor: pointer".to_string(),
+            ),
+        ];
+        if disabled {
+            attributes.push(("disabled".to_string(), "disabled".to_string()));
+        }
+
+        let on_click = {
+            let handle = self.handle(id);
+            Rc::new(move || handle.step(steps)) as Rc<dyn Fn()>
+        };
+
+        Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                attributes,
+                on_click: (!disabled).then_some(on_click),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(text.to_string())],
+        }
+    }
+
+    fn handle(&self, id: &str) -> NumberHandle<T> {
+        NumberHandle {
+            id: id.to_string(),
+            value: self.value,
+            range: self.range,
+            on_change: self.on_change.clone(),
+        }
+    }
+}
+
+/// What a `NumberInput`'s event handlers need. Handlers outlive the render
+/// that created them, so the current number is read back from the field.
+struct NumberHandle<T: NumberValue> {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    id: String,
+    value: Option<T>,
+    range: NumberRange,
+    on_change: Option<Rc<dyn Fn(T)>>,
+}
+
+impl<T: NumberValue> NumberHandle<T> {
+    #[cfg(target_arch = "wasm32")]
+    fn field(&self) -> Option<web_sys::HtmlInputElement> {
+        use wasm_bindgen::JsCast;
+
+        web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(&self.id))
+            .and_then(|e| e.dyn_into().ok())
+    }
+
+    fn current(&self) -> Option<T> {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(input) = self.field() {
+            return input.value().trim().parse().ok();
+        }
+        self.value
+    }
+
+    fn show(&self, text: &str) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(input) = self.field() {
+            input.set_value(text);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = text;
+    }
+
+    fn emit(&self, value: T) {
+        self.show(&value.to_string());
+        if let Some(on_change) = &self.on_change {
+            on_change(value);
+        }
+    }
+
+    fn step(&self, steps: f64) {
+        let base = self.current().map(T::to_f64);
+        self.emit(T::from_f64(self.range.step_from(base, steps)));
+    }
+
+    fn to_bound(&self, bound: Option<f64>) {
+        if let Some(bound) = bound {
+            self.emit(T::from_f64(bound));
+        }
+    }
+
+    /// Typed text, committed on change: clamp it, or put back the last
+    /// good number if it doesn't parse
+    fn commit(&self, text: &str) {
+        match text.trim().parse::<T>() {
+            Ok(value) => self.emit(T::from_f64(self.range.clamp(value.to_f64()))),
+            Err(_) => self.show(&self.value.map(|v| v.to_string()).unwrap_or_default()),
+        }
+    }
+}
+
+impl<T: NumberValue> Component for NumberInput<T> {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-number-{}", NEXT_NUMBER_INPUT_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let value = self.value.map(T::to_f64);
+        let at_min = matches!((value, self.range.min), (Some(v), Some(min)) if v <= min);
+        let at_max = matches!((value, self.range.max), (Some(v), Some(max)) if v >= max);
+
+        let mut attributes = vec![
+            ("type".to_string(), "text".to_string()),
+            ("role".to_string(), "spinbutton".to_string()),
+            (
+                "inputmode".to_string(),
+                if self.range.step.fract() == 0.0 { "numeric" } else { "decimal" }.to_string(),
+            ),
+            ("autocomplete".to_string(), "off".to_string()),
+            (
+                "value".to_string(),
+                self.value.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            (
+                "style".to_string(),
+                "width: 100%; min-width: 0; border: 0; padding: 0.5rem; font: inherit; text-align: center".to_string(),
+            ),
+        ];
+        if let Some(value) = self.value {
+            attributes.push(("aria-valuenow".to_string(), value.to_string()));
+        }
+        if let Some(min) = self.range.min {
+            attributes.push(("aria-valuemin".to_string(), min.to_string()));
+        }
+        if let Some(max) = self.range.max {
+            attributes.push(("aria-valuemax".to_string(), max.to_string()));
+        }
+        if let Some(label) = &self.label {
+            attributes.push(("aria-label".to_string(), label.clone()));
+        }
+        if let Some(placeholder) = &self.placeholder {
+            attributes.push(("placeholder".to_string(), placeholder.clone()));
+        }
+        attributes.extend(self.attributes.iter().cloned());
+
+        let on_keydown = {
+            let handle = self.handle(&id);
+            Rc::new(move |key: String| match key.as_str() {
+                "ArrowUp" => handle.step(1.0),
+                "ArrowDown" => handle.step(-1.0),
+                "PageUp" => handle.step(10.0),
+                "PageDown" => handle.step(-10.0),
+                "Home" => handle.to_bound(handle.range.min),
+                "End" => handle.to_bound(handle.range.max),
+                _ => {}
+            }) as Rc<dyn Fn(String)>
+        };
+        let on_change = {
+            let handle = self.handle(&id);
+            Rc::new(move |text: String| handle.commit(&text)) as Rc<dyn Fn(String)>
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("number-input".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    "display: inline-flex; align-items: stretch; border: 1px solid #e5e7eb; border-radius: 0.25rem; overflow: hidden".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children: vec![
+                self.stepper(&id, "Decrease", "−", -1.0, at_min),
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props {
+                        id: Some(id.clone()),
+                        attributes,
+                        on_change: Some(on_change),
+                        on_keydown: Some(on_keydown),
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                },
+                self.stepper(&id, "Increase", "+", 1.0, at_max),
+            ],
+        }
+    }
+}
+
 /// Badge component
 pub struct Badge {
     text: String,
@@ -238,10 +669,10 @@ impl Component for Badge {
 
         Element::Node {
             tag: "span".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: vec![("style".to_string(), style_str)],
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text(self.text.clone())],
         }
     }
@@ -249,63 +680,116 @@ impl Component for Badge {
 
 /// Progress component
 pub struct Progress {
-    value: f32, // 0.0 to 100.0
+    value: Option<f32>, // 0.0 to 100.0, or None while indeterminate
+    label: Option<String>,
     class: Option<String>,
 }
 
 impl Progress {
+    /// A bar filled to `value` percent, clamped to 0–100; NaN counts as 0
     pub fn new(value: f32) -> Self {
+        let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 100.0) };
+        Progress {
+            value: Some(value),
+            label: None,
+            class: None,
+        }
+    }
+
+    /// An animated bar for work of unknown length. The animation comes
+    /// from `inject_global_styles`.
+    pub fn indeterminate() -> Self {
         Progress {
-            value: value.clamp(0.0, 100.0),
+            value: None,
+            label: None,
             class: None,
         }
     }
 
+    /// What is progressing, read out as the bar's `aria-label`
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     pub fn class(mut self, class: impl Into<String>) -> Self {
         self.class = Some(class.into());
         self
     }
+
+    /// The clamped percentage, or `None` when indeterminate
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
 }
 
 impl Component for Progress {
     fn render(&self) -> Element {
         let container_style = style![bg_white(), dark_bg_gray_800(), rounded(), shadow(),];
 
-        let bar_style = format!(
-            "width: {}%; height: 8px; background-color: #667eea; border-radius: 0.25rem; transition: width 0.3s ease",
-            self.value
-        );
+        let mut attributes = vec![("role".to_string(), "progressbar".to_string())];
+        let bar_style = match self.value {
+            Some(value) => {
+                attributes.extend([
+                    ("aria-valuenow".to_string(), value.to_string()),
+                    ("aria-valuemin".to_string(), "0".to_string()),
+                    ("aria-valuemax".to_string(), "100".to_string()),
+                ]);
+                attributes.push(("style".to_string(), container_style.build()));
+                format!(
+                    "width: {}%; height: 8px; background-color: #667eea; border-radius: 0.25rem; transition: width 0.3s ease",
+                    value
+                )
+            }
+            // Without aria-valuenow, assistive tech reports the bar as busy
+            None => {
+                attributes.push((
+                    "style".to_string(),
+                    format!("{};position: relative; overflow: hidden", container_style.build()),
+                ));
+                "width: 40%; height: 8px; background-color: #667eea; border-radius: 0.25rem; animation: progress-indeterminate 1.2s ease-in-out infinite".to_string()
+            }
+        };
+        if let Some(label) = &self.label {
+            attributes.push(("aria-label".to_string(), label.clone()));
+        }
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: self.class.clone(),
-                attributes: vec![
-                    ("style".to_string(), container_style.build()),
-                    ("role".to_string(), "progressbar".to_string()),
-                    ("aria-valuenow".to_string(), self.value.to_string()),
-                    ("aria-valuemin".to_string(), "0".to_string()),
-                    ("aria-valuemax".to_string(), "100".to_string()),
-                ],
+                attributes,
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     attributes: vec![("style".to_string(), bar_style)],
                     ..Default::default()
-                },
+                }),
                 children: vec![],
             }],
         }
     }
 }
 
-/// Avatar component
+/// Colours for initials avatars, picked by name
+const AVATAR_COLORS: [&str; 8] = [
+    "#ef4444", "#f97316", "#ca8a04", "#16a34a", "#0d9488", "#2563eb", "#7c3aed", "#db2777",
+];
+
+/// Background for an avatar with no name to draw from
+const AVATAR_NEUTRAL: &str = "#9ca3af";
+
+/// Avatar component - an image, or a coloured circle with initials when
+/// there is no image or it fails to load
 pub struct Avatar {
     src: Option<String>,
     alt: String,
-    fallback: String,
+    name: String,
+    initials: Option<String>,
+    /// The source that failed to load
+    failed: State<Option<String>>,
 }
 
 impl Default for Avatar {
@@ -319,7 +803,9 @@ impl Avatar {
         Avatar {
             src: None,
             alt: String::new(),
-            fallback: String::new(),
+            name: String::new(),
+            initials: None,
+            failed: State::new(None),
         }
     }
 
@@ -333,30 +819,102 @@ impl Avatar {
         self
     }
 
-    pub fn fallback(mut self, fallback: impl Into<String>) -> Self {
-        self.fallback = fallback.into();
+    /// Whose avatar this is; gives the initials and colour when there is
+    /// no image
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
         self
     }
+
+    /// Initials to show instead of ones derived from the name
+    pub fn initials(mut self, initials: impl Into<String>) -> Self {
+        self.initials = Some(initials.into());
+        self
+    }
+
+    /// Same as [`Avatar::initials`]
+    pub fn fallback(self, fallback: impl Into<String>) -> Self {
+        self.initials(fallback)
+    }
+
+    /// Whether the image is shown rather than initials
+    pub fn shows_image(&self) -> bool {
+        self.shows_image_with(&self.failed)
+    }
+
+    fn shows_image_with(&self, failed: &State<Option<String>>) -> bool {
+        self.src.is_some() && failed.get() != self.src
+    }
+
+    /// The explicit initials, or the first letters of the name's first and
+    /// last words; empty when there is neither
+    pub fn display_initials(&self) -> String {
+        if let Some(initials) = &self.initials {
+            return initials.trim().to_string();
+        }
+        let words: Vec<&str> = self.name.split_whitespace().collect();
+        let (Some(first), Some(last)) = (words.first(), words.last()) else {
+            return String::new();
+        };
+        let mut initials: String = first.chars().take(1).collect();
+        if words.len() > 1 {
+            initials.extend(last.chars().take(1));
+        }
+        initials.to_uppercase()
+    }
+
+    /// Stable colour for the name, so the same person always gets the same
+    /// circle
+    fn color(&self) -> &'static str {
+        let key = if self.name.trim().is_empty() {
+            self.initials.as_deref().unwrap_or("").trim()
+        } else {
+            self.name.trim()
+        };
+        if key.is_empty() {
+            return AVATAR_NEUTRAL;
+        }
+        // FNV-1a, which unlike `DefaultHasher` is the same on every build
+        let hash = key.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+        AVATAR_COLORS[hash as usize % AVATAR_COLORS.len()]
+    }
+
+    fn label(&self) -> &str {
+        if self.alt.is_empty() {
+            self.name.trim()
+        } else {
+            &self.alt
+        }
+    }
 }
 
 impl Component for Avatar {
     fn render(&self) -> Element {
         let style = style![rounded(), shadow(),];
+        let failed = use_widget_state(&self.failed);
+
+        if let (Some(src), true) = (&self.src, self.shows_image_with(&failed)) {
+            let on_error = {
+                let src = src.clone();
+                Rc::new(move || failed.set(Some(src.clone()))) as Rc<dyn Fn()>
+            };
 
-        if let Some(src) = &self.src {
             Element::Node {
                 tag: "img".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     attributes: vec![
                         ("src".to_string(), src.clone()),
-                        ("alt".to_string(), self.alt.clone()),
+                        ("alt".to_string(), self.label().to_string()),
                         (
                             "style".to_string(),
                             format!("{};width:40px;height:40px;border-radius:50%", style.build()),
                         ),
                     ],
+                    on_error: Some(on_error),
                     ..Default::default()
-                },
+                }),
                 children: vec![],
             }
         } else {
@@ -365,25 +923,34 @@ impl Component for Avatar {
                 flex(),
                 items_center(),
                 justify_center(),
-                bg_black(),
                 text_white(),
                 font_bold(),
             ];
 
+            let mut attributes = vec![(
+                "style".to_string(),
+                format!(
+                    "{};{};width:40px;height:40px;border-radius:50%;background-color:{}",
+                    style.build(),
+                    fallback_style.build(),
+                    self.color()
+                ),
+            )];
+            if self.label().is_empty() {
+                attributes.push(("aria-hidden".to_string(), "true".to_string()));
+            } else {
+                attributes.push(("role".to_string(), "img".to_string()));
+                attributes.push(("aria-label".to_string(), self.label().to_string()));
+            }
+
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
-                    attributes: vec![(
-                        "style".to_string(),
-                        format!(
-                            "{};{};width:40px;height:40px;border-radius:50%",
-                            style.build(),
-                            fallback_style.build()
-                        ),
-                    )],
+                props: Box::new(Props {
+                    class: Some("avatar-initials".to_string()),
+                    attributes,
                     ..Default::default()
-                },
-                children: vec![Element::Text(self.fallback.clone())],
+                }),
+                children: vec![Element::Text(self.display_initials())],
             }
         }
     }
@@ -450,10 +1017,10 @@ impl Component for Tabs {
 
             tab_buttons.push(Element::Node {
                 tag: "button".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     attributes: vec![("style".to_string(), tab_style.build())],
                     ..Default::default()
-                },
+                }),
                 children: vec![Element::Text(tab.label.clone())],
             });
         }
@@ -466,25 +1033,3151 @@ impl Component for Tabs {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         attributes: vec![("style".to_string(), tab_list_style.build())],
                         ..Default::default()
-                    },
+                    }),
                     children: tab_buttons,
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("tab-content".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![content],
                 },
             ],
         }
     }
 }
+
+static NEXT_RATING_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Rating component - a row of stars that works like a slider from the keyboard
+pub struct Rating {
+    id: Option<String>,
+    value: f32,
+    scale: RatingScale,
+    label: String,
+    on_change: Option<Rc<dyn Fn(f32)>>,
+}
+
+/// Number of stars and step shared by a `Rating`'s handlers
+#[derive(Clone, Copy)]
+struct RatingScale {
+    max: u8,
+    half: bool,
+}
+
+impl RatingScale {
+    fn step(&self) -> f32 {
+        if self.half {
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    /// `value` snapped to the step and kept within 0..=max
+    fn snap(&self, value: f32) -> f32 {
+        ((value / self.step()).round() * self.step()).clamp(0.0, self.max as f32)
+    }
+
+    fn value_text(&self, value: f32) -> String {
+        format!("{} out of {} stars", value, self.max)
+    }
+
+    /// The value a key press moves `current` to, if the key means anything
+    fn value_for_key(&self, key: &str, current: f32) -> Option<f32> {
+        let step = self.step();
+        let max = self.max as f32;
+        let next = match key {
+            "ArrowRight" | "ArrowUp" => current + step,
+            "ArrowLeft" | "ArrowDown" => (current - step).max(step),
+            "Home" => step,
+            "End" => max,
+            "Backspace" | "Delete" | "0" => 0.0,
+            digit => digit.parse::<u8>().ok().filter(|d| (1..=self.max).contains(d))? as f32,
+        };
+        Some(self.snap(next))
+    }
+}
+
+/// The rating the element with `id` currently shows; the key handler may
+/// come from an older render than the value on screen
+fn live_rating(id: &str, fallback: f32) -> f32 {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(value) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(id))
+        .and_then(|e| e.get_attribute("aria-valuenow"))
+        .and_then(|v| v.parse().ok())
+    {
+        return value;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = id;
+    fallback
+}
+
+impl Rating {
+    pub fn new(value: f32) -> Self {
+        Rating {
+            id: None,
+            value,
+            scale: RatingScale { max: 5, half: false },
+            label: "Rating".to_string(),
+            on_change: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn max(mut self, max: u8) -> Self {
+        self.scale.max = max.max(1);
+        self
+    }
+
+    /// Allow half stars, from clicks on a star's left half or arrow keys
+    pub fn allow_half(mut self, half: bool) -> Self {
+        self.scale.half = half;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Without a handler the rating is display-only
+    pub fn on_change(mut self, handler: impl Fn(f32) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+
+    fn star(&self, index: usize, value: f32) -> Element {
+        let fill = (value - index as f32).clamp(0.0, 1.0);
+
+        let mut children = vec![
+            Element::Text("★".to_string()),
+            Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![(
+                        "style".to_string(),
+                        format!(
+                            "position: absolute; top: 0; left: 0; width: {}%; overflow: hidden; color: #f59e0b",
+                            fill * 100.0
+                        ),
+                    )],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text("★".to_string())],
+            },
+        ];
+
+        if let Some(on_change) = &self.on_change {
+            let halves: &[(f32, &str)] = if self.scale.half {
+                &[(0.5, "left: 0; width: 50%"), (1.0, "left: 50%; width: 50%")]
+            } else {
+                &[(1.0, "left: 0; width: 100%")]
+            };
+            for &(part, position) in halves {
+                let on_change = on_change.clone();
+                let value = index as f32 + part;
+                children.push(Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![(
+                            "style".to_string(),
+                            format!("position: absolute; top: 0; bottom: 0; {}; cursor: pointer", position),
+                        )],
+                        on_click: Some(Rc::new(move || on_change(value))),
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                });
+            }
+        }
+
+        Element::Node {
+            tag: "span".to_string(),
+            props: Box::new(Props {
+                attributes: vec![(
+                    "style".to_string(),
+                    "position: relative; display: inline-block; line-height: 1; color: #d1d5db".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children,
+        }
+    }
+}
+
+impl Component for Rating {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-rating-{}", NEXT_RATING_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let value = self.scale.snap(self.value);
+        let stars = (0..self.scale.max as usize).map(|i| self.star(i, value)).collect();
+
+        let (mut attributes, on_keydown) = match &self.on_change {
+            Some(on_change) => {
+                let attributes = vec![
+                    ("role".to_string(), "slider".to_string()),
+                    ("tabindex".to_string(), "0".to_string()),
+                    ("aria-label".to_string(), self.label.clone()),
+                    ("aria-valuemin".to_string(), "0".to_string()),
+                    ("aria-valuemax".to_string(), self.scale.max.to_string()),
+                    ("aria-valuenow".to_string(), value.to_string()),
+                    ("aria-valuetext".to_string(), self.scale.value_text(value)),
+                ];
+                let (id, scale) = (id.clone(), self.scale);
+                let on_change = on_change.clone();
+                let on_keydown = Rc::new(move |key: String| {
+                    let current = live_rating(&id, value);
+                    if let Some(next) = scale.value_for_key(&key, current) {
+                        if next != current {
+                            on_change(next);
+                        }
+                    }
+                }) as Rc<dyn Fn(String)>;
+                (attributes, Some(on_keydown))
+            }
+            None => (
+                vec![
+                    ("role".to_string(), "img".to_string()),
+                    (
+                        "aria-label".to_string(),
+                        format!("{}: {}", self.label, self.scale.value_text(value)),
+                    ),
+                ],
+                None,
+            ),
+        };
+
+        attributes.push((
+            "style".to_string(),
+            "display: inline-flex; gap: 0.125rem; font-size: 1.5rem".to_string(),
+        ));
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("rating".to_string()),
+                attributes,
+                on_keydown,
+                ..Default::default()
+            }),
+            children: stars,
+        }
+    }
+}
+
+static NEXT_TAG_INPUT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Tag input component - chips for a list of unique tags
+///
+/// Enter or a comma adds the typed tag, Backspace in an empty field removes
+/// the last one, and each chip has a remove button. The list is a `State`
+/// shared with the parent; `on_change` gets the new list after every edit,
+/// e.g. to copy it into a form.
+pub struct TagInput {
+    id: Option<String>,
+    tags: State<Vec<String>>,
+    draft: Rc<RefCell<String>>,
+    label: String,
+    placeholder: Option<String>,
+    max_tags: Option<usize>,
+    on_change: Option<Rc<dyn Fn(Vec<String>)>>,
+}
+
+impl TagInput {
+    pub fn new(tags: State<Vec<String>>) -> Self {
+        TagInput {
+            id: None,
+            tags,
+            draft: Rc::new(RefCell::new(String::new())),
+            label: "Tags".to_string(),
+            placeholder: None,
+            max_tags: None,
+            on_change: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn max_tags(mut self, max: usize) -> Self {
+        self.max_tags = Some(max);
+        self
+    }
+
+    pub fn on_change(mut self, handler: impl Fn(Vec<String>) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+
+}
+
+/// What a `TagInput`'s event handlers share
+#[derive(Clone)]
+struct TagHandle {
+    id: String,
+    tags: State<Vec<String>>,
+    draft: Rc<RefCell<String>>,
+    max_tags: Option<usize>,
+    on_change: Option<Rc<dyn Fn(Vec<String>)>>,
+}
+
+impl TagHandle {
+    fn update(&self, tags: Vec<String>) {
+        self.tags.set(tags.clone());
+        if let Some(on_change) = &self.on_change {
+            on_change(tags);
+        }
+    }
+
+    /// Add each tag that isn't empty or already there (ignoring case)
+    fn add(&self, new: &[&str]) {
+        let mut tags = self.tags.get();
+        let before = tags.len();
+        for tag in new.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+            if self.max_tags.is_some_and(|max| tags.len() >= max) {
+                break;
+            }
+            if !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                tags.push(tag.to_string());
+            }
+        }
+        if tags.len() != before {
+            self.update(tags);
+        }
+    }
+
+    fn remove(&self, tag: &str) {
+        let mut tags = self.tags.get();
+        tags.retain(|t| t != tag);
+        self.update(tags);
+    }
+
+    /// Replace the typed text, in the field too
+    fn set_draft(&self, text: &str) {
+        *self.draft.borrow_mut() = text.to_string();
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+
+            if let Some(input) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&format!("{}-input", self.id)))
+                .and_then(|e| e.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                input.set_value(text);
+            }
+        }
+    }
+
+    /// Typed or pasted text; everything before the last comma becomes tags
+    fn input(&self, text: &str) {
+        match text.rsplit_once(',') {
+            Some((done, rest)) => {
+                self.add(&done.split(',').collect::<Vec<_>>());
+                self.set_draft(rest.trim_start());
+            }
+            None => *self.draft.borrow_mut() = text.to_string(),
+        }
+    }
+
+    fn key(&self, key: &str) {
+        match key {
+            "Enter" => {
+                let draft = self.draft.borrow().clone();
+                self.add(&[&draft]);
+                self.set_draft("");
+            }
+            "Backspace" if self.draft.borrow().is_empty() => {
+                if let Some(last) = self.tags.get().last() {
+                    self.remove(last);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for TagInput {
+    fn render(&self) -> Element {
+        let handle = TagHandle {
+            id: use_widget_id(self.id.as_deref(), || {
+                format!("layer9-tags-{}", NEXT_TAG_INPUT_ID.fetch_add(1, Ordering::Relaxed))
+            }),
+            tags: use_widget_state(&self.tags),
+            draft: use_persisted(|| self.draft.clone()).borrow().clone(),
+            max_tags: self.max_tags,
+            on_change: self.on_change.clone(),
+        };
+        let id = handle.id.clone();
+        let tags = handle.tags.get();
+
+        let chips = tags
+            .iter()
+            .map(|tag| {
+                let on_remove = {
+                    let handle = handle.clone();
+                    let tag = tag.clone();
+                    Rc::new(move || handle.remove(&tag)) as Rc<dyn Fn()>
+                };
+                Element::Node {
+                    tag: "li".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![(
+                            "style".to_string(),
+                            "display: inline-flex; align-items: center; gap: 0.25rem; padding: 0.125rem 0.5rem; border-radius: 9999px; background: #e5e7eb; font-size: 0.875rem".to_string(),
+                        )],
+                        ..Default::default()
+                    }),
+                    children: vec![
+                        Element::Text(tag.clone()),
+                        Element::Node {
+                            tag: "button".to_string(),
+                            props: Box::new(Props {
+                                attributes: vec![
+                                    ("type".to_string(), "button".to_string()),
+                                    ("aria-label".to_string(), format!("Remove {}", tag)),
+                                    (
+                                        "style".to_string(),
+                                        "border: 0; background: none; padding: 0; cursor: pointer; line-height: 1".to_string(),
+                                    ),
+                                ],
+                                on_click: Some(on_remove),
+                                ..Default::default()
+                            }),
+                            children: vec![Element::Text("×".to_string())],
+                        },
+                    ],
+                }
+            })
+            .collect();
+
+        let full = self.max_tags.is_some_and(|max| tags.len() >= max);
+        let mut input_attributes = vec![
+            ("type".to_string(), "text".to_string()),
+            ("aria-label".to_string(), format!("Add to {}", self.label.to_lowercase())),
+            (
+                "style".to_string(),
+                "flex: 1; min-width: 6rem; border: 0; outline: none; padding: 0.25rem; font: inherit".to_string(),
+            ),
+        ];
+        if let Some(placeholder) = &self.placeholder {
+            input_attributes.push(("placeholder".to_string(), placeholder.clone()));
+        }
+        if full {
+            input_attributes.push(("disabled".to_string(), "disabled".to_string()));
+        }
+
+        let on_input = {
+            let handle = handle.clone();
+            Rc::new(move |text: String| handle.input(&text)) as Rc<dyn Fn(String)>
+        };
+        let on_keydown = Rc::new(move |key: String| handle.key(&key)) as Rc<dyn Fn(String)>;
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id.clone()),
+                class: Some("tag-input".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    "display: flex; flex-wrap: wrap; align-items: center; gap: 0.25rem; padding: 0.25rem; border: 1px solid #e5e7eb; border-radius: 0.25rem".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children: vec![
+                // Chips sit in their own list so adding one doesn't move the
+                // field and cost it focus
+                Element::Node {
+                    tag: "ul".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![
+                            ("aria-label".to_string(), self.label.clone()),
+                            (
+                                "style".to_string(),
+                                "display: contents; list-style: none; margin: 0; padding: 0".to_string(),
+                            ),
+                        ],
+                        ..Default::default()
+                    }),
+                    children: chips,
+                },
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props {
+                        id: Some(format!("{}-input", id)),
+                        attributes: input_attributes,
+                        on_input: Some(on_input),
+                        on_keydown: Some(on_keydown),
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                },
+            ],
+        }
+    }
+}
+
+static NEXT_DISCLOSURE_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Disclosure component - a button that shows and hides a region
+pub struct Disclosure {
+    id: Option<String>,
+    label: String,
+    children: Vec<Element>,
+    open: State<bool>,
+    animated: bool,
+}
+
+impl Disclosure {
+    pub fn new(label: impl Into<String>) -> Self {
+        Disclosure {
+            id: None,
+            label: label.into(),
+            children: vec![],
+            open: State::new(false),
+            animated: false,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn default_open(mut self, open: bool) -> Self {
+        self.open = State::new(open);
+        self
+    }
+
+    /// Share the open flag with the parent
+    pub fn state(mut self, open: State<bool>) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Slide the region open and closed instead of toggling it instantly
+    pub fn animated(mut self, animated: bool) -> Self {
+        self.animated = animated;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.get()
+    }
+
+    pub fn toggle(&self) {
+        self.open.set(!self.open.get());
+    }
+}
+
+impl Component for Disclosure {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-disclosure-{}", NEXT_DISCLOSURE_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let state = use_widget_state(&self.open);
+        let open = state.get();
+        let region_id = format!("{}-region", id);
+
+        let on_toggle = {
+            let state = state.clone();
+            Rc::new(move || state.set(!state.get())) as Rc<dyn Fn()>
+        };
+
+        let trigger = Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                id: Some(id.clone()),
+                attributes: vec![
+                    ("type".to_string(), "button".to_string()),
+                    ("aria-expanded".to_string(), open.to_string()),
+                    ("aria-controls".to_string(), region_id.clone()),
+                    (
+                        "style".to_string(),
+                        "display: inline-flex; align-items: center; gap: 0.5rem; border: 0; background: none; padding: 0.25rem 0; font: inherit; cursor: pointer".to_string(),
+                    ),
+                ],
+                on_click: Some(on_toggle),
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![
+                            ("aria-hidden".to_string(), "true".to_string()),
+                            (
+                                "style".to_string(),
+                                format!(
+                                    "display: inline-block; transition: transform 150ms ease; transform: rotate({}deg)",
+                                    if open { 90 } else { 0 }
+                                ),
+                            ),
+                        ],
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text("▸".to_string())],
+                },
+                Element::Text(self.label.clone()),
+            ],
+        };
+
+        let mut region_attrs = vec![
+            ("role".to_string(), "region".to_string()),
+            ("aria-labelledby".to_string(), id.clone()),
+        ];
+        let children = if self.animated {
+            // Grid rows animate between 0fr and 1fr without measuring the
+            // content; `visibility` keeps the collapsed content out of the
+            // tab order and the accessibility tree once the slide finishes
+            region_attrs.push((
+                "style".to_string(),
+                format!(
+                    "display: grid; grid-template-rows: {}; visibility: {}; transition: grid-template-rows 200ms ease, visibility 200ms",
+                    if open { "1fr" } else { "0fr" },
+                    if open { "visible" } else { "hidden" }
+                ),
+            ));
+            vec![Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("style".to_string(), "overflow: hidden; min-height: 0".to_string())],
+                    ..Default::default()
+                }),
+                children: self.children.clone(),
+            }]
+        } else {
+            if !open {
+                region_attrs.push(("hidden".to_string(), "hidden".to_string()));
+            }
+            self.children.clone()
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("disclosure".to_string()),
+                ..Default::default()
+            }),
+            children: vec![
+                trigger,
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        id: Some(region_id),
+                        attributes: region_attrs,
+                        ..Default::default()
+                    }),
+                    children,
+                },
+            ],
+        }
+    }
+}
+
+static NEXT_ACCORDION_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Accordion component - a stack of headers that each show and hide a panel
+///
+/// Only one panel is open at a time unless `allow_multiple` is set.
+/// Collapsed panels aren't rendered at all, so heavy content costs nothing
+/// until it's opened.
+pub struct Accordion {
+    id: Option<String>,
+    items: Vec<(Element, Element)>,
+    allow_multiple: bool,
+    open: State<Vec<usize>>,
+}
+
+impl Accordion {
+    /// `(header, content)` pairs
+    pub fn new(items: Vec<(Element, Element)>) -> Self {
+        Accordion {
+            id: None,
+            items,
+            allow_multiple: false,
+            open: State::new(vec![]),
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn allow_multiple(mut self, allow_multiple: bool) -> Self {
+        self.allow_multiple = allow_multiple;
+        self
+    }
+
+    /// Sections open at first; in single mode only the lowest of them
+    pub fn default_open(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut open: Vec<usize> = indices.into_iter().filter(|&i| i < self.items.len()).collect();
+        open.sort_unstable();
+        open.dedup();
+        self.open = State::new(open);
+        self
+    }
+
+    /// Share the open sections with the parent
+    pub fn state(mut self, open: State<Vec<usize>>) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Open sections in order; single mode honours only the first
+    fn open_sections(&self, state: &State<Vec<usize>>) -> Vec<usize> {
+        let mut open = state.get();
+        if !self.allow_multiple {
+            open.truncate(1);
+        }
+        open
+    }
+
+    pub fn is_open(&self, index: usize) -> bool {
+        self.open_sections(&self.open).contains(&index)
+    }
+
+    pub fn toggle(&self, index: usize) {
+        toggle_section(&self.open, index, self.allow_multiple);
+    }
+
+    fn header_id(id: &str, index: usize) -> String {
+        format!("{}-header-{}", id, index)
+    }
+}
+
+fn toggle_section(open: &State<Vec<usize>>, index: usize, allow_multiple: bool) {
+    let mut sections = open.get();
+    if !allow_multiple {
+        sections.truncate(1);
+    }
+    if let Some(position) = sections.iter().position(|&i| i == index) {
+        sections.remove(position);
+    } else if allow_multiple {
+        sections.push(index);
+        sections.sort_unstable();
+    } else {
+        sections = vec![index];
+    }
+    open.set(sections);
+}
+
+impl Component for Accordion {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-accordion-{}", NEXT_ACCORDION_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let state = use_widget_state(&self.open);
+        let open = self.open_sections(&state);
+        let count = self.items.len();
+
+        let sections = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, (header, content))| {
+                let expanded = open.contains(&index);
+                let header_id = Self::header_id(&id, index);
+                let panel_id = format!("{}-panel-{}", id, index);
+
+                let on_click = {
+                    let state = state.clone();
+                    let allow_multiple = self.allow_multiple;
+                    Rc::new(move || toggle_section(&state, index, allow_multiple)) as Rc<dyn Fn()>
+                };
+                // Enter and Space reach the button as clicks; the arrows
+                // move between headers
+                let on_keydown = {
+                    let id = id.clone();
+                    Rc::new(move |key: String| {
+                        let target = match key.as_str() {
+                            "ArrowDown" => (index + 1) % count,
+                            "ArrowUp" => (index + count - 1) % count,
+                            "Home" => 0,
+                            "End" => count - 1,
+                            _ => return,
+                        };
+                        focus_later(Self::header_id(&id, target));
+                    }) as Rc<dyn Fn(String)>
+                };
+
+                let button = Element::Node {
+                    tag: "button".to_string(),
+                    props: Box::new(Props {
+                        id: Some(header_id.clone()),
+                        attributes: vec![
+                            ("type".to_string(), "button".to_string()),
+                            ("aria-expanded".to_string(), expanded.to_string()),
+                            ("aria-controls".to_string(), panel_id.clone()),
+                            (
+                                "style".to_string(),
+                                "display: flex; width: 100%; align-items: center; justify-content: space-between; gap: 0.5rem; border: 0; background: none; padding: 0.75rem 0; font: inherit; text-align: start; cursor: pointer".to_string(),
+                            ),
+                        ],
+                        on_click: Some(on_click),
+                        on_keydown: Some(on_keydown),
+                        ..Default::default()
+                    }),
+                    children: vec![
+                        header.clone(),
+                        Element::Node {
+                            tag: "span".to_string(),
+                            props: Box::new(Props {
+                                attributes: vec![
+                                    ("aria-hidden".to_string(), "true".to_string()),
+                                    (
+                                        "style".to_string(),
+                                        format!(
+                                            "display: inline-block; transition: transform 150ms ease; transform: rotate({}deg)",
+                                            if expanded { 180 } else { 0 }
+                                        ),
+                                    ),
+                                ],
+                                ..Default::default()
+                            }),
+                            children: vec![Element::Text("▾".to_string())],
+                        },
+                    ],
+                };
+
+                let mut children = vec![Element::Node {
+                    tag: "h3".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![("style".to_string(), "margin: 0".to_string())],
+                        ..Default::default()
+                    }),
+                    children: vec![button],
+                }];
+                if expanded {
+                    children.push(Element::Node {
+                        tag: "div".to_string(),
+                        props: Box::new(Props {
+                            id: Some(panel_id),
+                            attributes: vec![
+                                ("role".to_string(), "region".to_string()),
+                                ("aria-labelledby".to_string(), header_id),
+                                ("style".to_string(), "padding-bottom: 0.75rem".to_string()),
+                            ],
+                            ..Default::default()
+                        }),
+                        children: vec![content.clone()],
+                    });
+                }
+
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("accordion-item".to_string()),
+                        attributes: vec![(
+                            "style".to_string(),
+                            "border-bottom: 1px solid #e5e7eb".to_string(),
+                        )],
+                        ..Default::default()
+                    }),
+                    children,
+                }
+            })
+            .collect();
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("accordion".to_string()),
+                ..Default::default()
+            }),
+            children: sections,
+        }
+    }
+}
+
+static NEXT_TOOLTIP_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Space between a tooltip and its trigger, in pixels
+const TOOLTIP_GAP: f64 = 6.0;
+
+/// Which side of its trigger a tooltip sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TooltipPlacement {
+    #[default]
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl TooltipPlacement {
+    pub fn opposite(self) -> Self {
+        match self {
+            TooltipPlacement::Top => TooltipPlacement::Bottom,
+            TooltipPlacement::Bottom => TooltipPlacement::Top,
+            TooltipPlacement::Left => TooltipPlacement::Right,
+            TooltipPlacement::Right => TooltipPlacement::Left,
+        }
+    }
+
+    /// Keep this side if the tooltip fits there, otherwise flip to the
+    /// opposite side when it has more room. `trigger` is the trigger's
+    /// viewport rect as `(top, left, bottom, right)`, `tip` the tooltip's
+    /// `(width, height)` and `viewport` the window's `(width, height)`.
+    pub fn fit(self, trigger: (f64, f64, f64, f64), tip: (f64, f64), viewport: (f64, f64)) -> Self {
+        let (top, left, bottom, right) = trigger;
+        let room = |placement| match placement {
+            TooltipPlacement::Top => top,
+            TooltipPlacement::Bottom => viewport.1 - bottom,
+            TooltipPlacement::Left => left,
+            TooltipPlacement::Right => viewport.0 - right,
+        };
+        let needed = match self {
+            TooltipPlacement::Top | TooltipPlacement::Bottom => tip.1,
+            TooltipPlacement::Left | TooltipPlacement::Right => tip.0,
+        } + TOOLTIP_GAP;
+
+        if room(self) < needed && room(self.opposite()) > room(self) {
+            self.opposite()
+        } else {
+            self
+        }
+    }
+
+    fn style(self) -> String {
+        let gap = format!("calc(100% + {}px)", TOOLTIP_GAP);
+        match self {
+            TooltipPlacement::Top => {
+                format!("bottom: {}; left: 50%; transform: translateX(-50%)", gap)
+            }
+            TooltipPlacement::Bottom => {
+                format!("top: {}; left: 50%; transform: translateX(-50%)", gap)
+            }
+            TooltipPlacement::Left => {
+                format!("right: {}; top: 50%; transform: translateY(-50%)", gap)
+            }
+            TooltipPlacement::Right => {
+                format!("left: {}; top: 50%; transform: translateY(-50%)", gap)
+            }
+        }
+    }
+}
+
+/// Tooltip component - a short hint shown beside its child on hover or
+/// focus, and on tap for touch devices
+pub struct Tooltip {
+    child: Element,
+    content: String,
+    id: Option<String>,
+    handle: TooltipHandle,
+}
+
+/// What the trigger's handlers share
+#[derive(Clone)]
+struct TooltipHandle {
+    id: String,
+    placement: TooltipPlacement,
+    delay_ms: u32,
+    shown: State<Option<TooltipPlacement>>,
+    #[cfg(target_arch = "wasm32")]
+    timer: Rc<RefCell<Option<gloo_timers::callback::Timeout>>>,
+}
+
+impl Tooltip {
+    pub fn new(child: Element, content: impl Into<String>) -> Self {
+        Tooltip {
+            child,
+            content: content.into(),
+            id: None,
+            handle: TooltipHandle {
+                // Resolved by the render
+                id: String::new(),
+                placement: TooltipPlacement::default(),
+                delay_ms: 400,
+                shown: State::new(None),
+                #[cfg(target_arch = "wasm32")]
+                timer: Rc::new(RefCell::new(None)),
+            },
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Preferred side; the tooltip flips when that side would overflow
+    /// the viewport
+    pub fn placement(mut self, placement: TooltipPlacement) -> Self {
+        self.handle.placement = placement;
+        self
+    }
+
+    /// How long hover or focus must last before the tooltip shows. Off the
+    /// browser there are no timers and it shows at once.
+    pub fn delay_ms(mut self, delay_ms: u32) -> Self {
+        self.handle.delay_ms = delay_ms;
+        self
+    }
+
+    /// Share the shown placement with the parent
+    pub fn state(mut self, shown: State<Option<TooltipPlacement>>) -> Self {
+        self.handle.shown = shown;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.handle.shown.get().is_some()
+    }
+}
+
+impl TooltipHandle {
+    fn anchor_id(&self) -> String {
+        format!("{}-anchor", self.id)
+    }
+
+    fn show_later(&self) {
+        #[cfg(target_arch = "wasm32")]
+        if self.delay_ms > 0 {
+            let handle = self.clone();
+            let timer = gloo_timers::callback::Timeout::new(self.delay_ms, move || {
+                handle.show_now()
+            });
+            *self.timer.borrow_mut() = Some(timer);
+            return;
+        }
+
+        self.show_now();
+    }
+
+    /// Show on the preferred side, or the opposite one if the tooltip
+    /// wouldn't fit there
+    fn show_now(&self) {
+        // A trigger removed while the delay ran has nothing to describe
+        #[cfg(target_arch = "wasm32")]
+        let Some(placement) = self.measured_placement() else {
+            return;
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let placement = self.placement;
+
+        if self.shown.get() != Some(placement) {
+            self.shown.set(Some(placement));
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn measured_placement(&self) -> Option<TooltipPlacement> {
+        use wasm_bindgen::JsCast;
+
+        let window = web_sys::window()?;
+        let document = window.document()?;
+        let anchor = document.get_element_by_id(&self.anchor_id())?.get_bounding_client_rect();
+        let tip = document
+            .get_element_by_id(&self.id)?
+            .dyn_into::<web_sys::HtmlElement>()
+            .ok()?;
+        let viewport = (
+            window.inner_width().ok()?.as_f64()?,
+            window.inner_height().ok()?.as_f64()?,
+        );
+
+        Some(self.placement.fit(
+            (anchor.top(), anchor.left(), anchor.bottom(), anchor.right()),
+            (tip.offset_width() as f64, tip.offset_height() as f64),
+            viewport,
+        ))
+    }
+
+    fn hide(&self) {
+        #[cfg(target_arch = "wasm32")]
+        self.timer.borrow_mut().take();
+
+        if self.shown.get().is_some() {
+            self.shown.set(None);
+        }
+    }
+}
+
+impl Component for Tooltip {
+    fn render(&self) -> Element {
+        let handle = &TooltipHandle {
+            id: use_widget_id(self.id.as_deref(), || {
+                format!("layer9-tooltip-{}", NEXT_TOOLTIP_ID.fetch_add(1, Ordering::Relaxed))
+            }),
+            shown: use_widget_state(&self.handle.shown),
+            #[cfg(target_arch = "wasm32")]
+            timer: crate::hooks::use_persisted(|| self.handle.timer.clone()).borrow().clone(),
+            ..self.handle.clone()
+        };
+        let shown = handle.shown.get();
+        let describedby = ("aria-describedby".to_string(), handle.id.clone());
+
+        // Text has nowhere to hold the description, and needs a tab stop
+        // to be reachable by keyboard
+        let trigger = match &self.child {
+            Element::Node { tag, props, children } => {
+                let mut props = props.clone();
+                props.attributes.push(describedby);
+                Element::Node {
+                    tag: tag.clone(),
+                    props,
+                    children: children.clone(),
+                }
+            }
+            child => Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![describedby, ("tabindex".to_string(), "0".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![child.clone()],
+            },
+        };
+
+        // Always rendered so it can be measured before it shows
+        let tip = Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(handle.id.clone()),
+                attributes: vec![
+                    ("role".to_string(), "tooltip".to_string()),
+                    (
+                        "style".to_string(),
+                        format!(
+                            "position: absolute; z-index: 50; {}; visibility: {}; pointer-events: none; white-space: nowrap; padding: 0.25rem 0.5rem; border-radius: 0.25rem; background: #111827; color: #ffffff; font-size: 0.75rem",
+                            shown.unwrap_or(handle.placement).style(),
+                            if shown.is_some() { "visible" } else { "hidden" }
+                        ),
+                    ),
+                ],
+                ..Default::default()
+            }),
+            children: vec![Element::Text(self.content.clone())],
+        };
+
+        let on_hover = {
+            let handle = handle.clone();
+            Rc::new(move |entered: bool| {
+                if entered {
+                    handle.show_later();
+                } else {
+                    handle.hide();
+                }
+            }) as Rc<dyn Fn(bool)>
+        };
+        let on_focus_in = {
+            let handle = handle.clone();
+            Rc::new(move || handle.show_later()) as Rc<dyn Fn()>
+        };
+        let on_focus_out = {
+            let handle = handle.clone();
+            Rc::new(move || handle.hide()) as Rc<dyn Fn()>
+        };
+        // Touch has no hover, so a tap shows it straight away
+        let on_click = {
+            let handle = handle.clone();
+            Rc::new(move || handle.show_now()) as Rc<dyn Fn()>
+        };
+        let on_keydown = {
+            let handle = handle.clone();
+            Rc::new(move |key: String| {
+                if key == "Escape" {
+                    handle.hide();
+                }
+            }) as Rc<dyn Fn(String)>
+        };
+
+        // The tooltip lives inside the anchor, so it goes when the trigger does
+        Element::Node {
+            tag: "span".to_string(),
+            props: Box::new(Props {
+                id: Some(handle.anchor_id()),
+                class: Some("tooltip".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    "position: relative; display: inline-block".to_string(),
+                )],
+                on_click: Some(on_click),
+                on_keydown: Some(on_keydown),
+                on_focus_in: Some(on_focus_in),
+                on_focus_out: Some(on_focus_out),
+                on_hover: Some(on_hover),
+                ..Default::default()
+            }),
+            children: vec![trigger, tip],
+        }
+    }
+}
+
+static NEXT_CODE_BLOCK_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// How long the copy button confirms a copy before resetting
+const COPY_CONFIRM_MS: u32 = 2000;
+
+/// Code block component - a snippet with a copy button, and an optional
+/// filename header and language label
+pub struct CodeBlock {
+    id: Option<String>,
+    code: String,
+    language: Option<String>,
+    filename: Option<String>,
+    prompt: Option<String>,
+    status: State<CopyStatus>,
+}
+
+impl CodeBlock {
+    pub fn new(code: impl Into<String>) -> Self {
+        CodeBlock {
+            id: None,
+            code: code.into(),
+            language: None,
+            filename: None,
+            prompt: None,
+            status: State::new(CopyStatus::Idle),
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Show each line as a terminal command after `prompt`, e.g. `"$"`;
+    /// the prompt isn't selectable or copied
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Share the copy status with the parent
+    pub fn state(mut self, status: State<CopyStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> CopyStatus {
+        self.status.get()
+    }
+
+    /// Copy the code and confirm on the button
+    pub fn copy(&self) {
+        copy_code(&self.code, self.status.clone());
+    }
+}
+
+fn copy_code(code: &str, status: State<CopyStatus>) {
+    copy_to_clipboard(code, COPY_CONFIRM_MS, move |copied| {
+        match copied {
+            CopyStatus::Copied => use_announce().polite("Copied to clipboard"),
+            CopyStatus::Failed => use_announce().polite("Couldn't copy to clipboard"),
+            CopyStatus::Idle => {}
+        }
+        status.set(copied);
+    });
+}
+
+impl Component for CodeBlock {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-code-{}", NEXT_CODE_BLOCK_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let state = use_widget_state(&self.status);
+        let status = state.get();
+        let muted = "font-size: 0.75rem; color: #94a3b8";
+
+        let mut header = vec![];
+        if let Some(filename) = &self.filename {
+            header.push(Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    class: Some("code-block-filename".to_string()),
+                    attributes: vec![("style".to_string(), "font-family: monospace; font-size: 0.8125rem".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text(filename.clone())],
+            });
+        }
+        if let Some(language) = &self.language {
+            header.push(Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    class: Some("code-block-language".to_string()),
+                    attributes: vec![(
+                        "style".to_string(),
+                        format!("{}; margin-left: auto; text-transform: uppercase; letter-spacing: 0.05em", muted),
+                    )],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text(language.clone())],
+            });
+        }
+
+        let on_copy = {
+            let code = self.code.clone();
+            let status = state.clone();
+            Rc::new(move || copy_code(&code, status.clone())) as Rc<dyn Fn()>
+        };
+        let copy_label = match &self.filename {
+            Some(filename) => format!("Copy {}", filename),
+            None => "Copy code".to_string(),
+        };
+        header.push(Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                class: Some("code-block-copy".to_string()),
+                attributes: vec![
+                    ("type".to_string(), "button".to_string()),
+                    ("aria-label".to_string(), copy_label),
+                    (
+                        "style".to_string(),
+                        format!(
+                            "{}; {}border: 1px solid #334155; border-radius: 0.25rem; background: none; padding: 0.125rem 0.5rem; cursor: pointer",
+                            muted,
+                            if self.language.is_none() { "margin-left: auto; " } else { "" }
+                        ),
+                    ),
+                ],
+                on_click: Some(on_copy),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(
+                match status {
+                    CopyStatus::Idle => "Copy",
+                    CopyStatus::Copied => "Copied!",
+                    CopyStatus::Failed => "Copy failed",
+                }
+                .to_string(),
+            )],
+        });
+
+        let lines = match &self.prompt {
+            Some(prompt) => self
+                .code
+                .lines()
+                .flat_map(|line| {
+                    [
+                        Element::Node {
+                            tag: "span".to_string(),
+                            props: Box::new(Props {
+                                attributes: vec![
+                                    ("aria-hidden".to_string(), "true".to_string()),
+                                    ("style".to_string(), "user-select: none; color: #64748b".to_string()),
+                                ],
+                                ..Default::default()
+                            }),
+                            children: vec![Element::Text(format!("{} ", prompt))],
+                        },
+                        Element::Text(format!("{}\n", line)),
+                    ]
+                })
+                .collect(),
+            None => vec![Element::Text(self.code.clone())],
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("code-block".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    "background: #0f172a; color: #e2e8f0; border-radius: 0.5rem; overflow: hidden".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("code-block-header".to_string()),
+                        attributes: vec![(
+                            "style".to_string(),
+                            "display: flex; align-items: center; gap: 0.75rem; padding: 0.5rem 0.75rem; border-bottom: 1px solid #1e293b".to_string(),
+                        )],
+                        ..Default::default()
+                    }),
+                    children: header,
+                },
+                Element::Node {
+                    tag: "pre".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![(
+                            "style".to_string(),
+                            "margin: 0; padding: 0.75rem; overflow-x: auto; font-size: 0.875rem".to_string(),
+                        )],
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Node {
+                        tag: "code".to_string(),
+                        props: Box::new(Props {
+                            class: self.language.as_ref().map(|language| format!("language-{}", language)),
+                            ..Default::default()
+                        }),
+                        children: lines,
+                    }],
+                },
+            ],
+        }
+    }
+}
+
+static NEXT_STEPS_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// One step of a `Steps` indicator
+#[derive(Clone)]
+pub struct Step {
+    label: String,
+    description: Option<String>,
+}
+
+impl Step {
+    pub fn new(label: impl Into<String>) -> Self {
+        Step {
+            label: label.into(),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl From<&str> for Step {
+    fn from(label: &str) -> Self {
+        Step::new(label)
+    }
+}
+
+/// Where a step stands relative to the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepState {
+    Completed,
+    Current,
+    Upcoming,
+}
+
+/// Which way a `Steps` indicator runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StepsOrientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+/// Steps component - progress through an ordered, multi-step flow
+///
+/// Steps up to the furthest one reached can be revisited through
+/// `on_select`; later steps can't be skipped to.
+pub struct Steps {
+    id: Option<String>,
+    steps: Vec<Step>,
+    current: usize,
+    furthest: usize,
+    orientation: StepsOrientation,
+    on_select: Option<Rc<dyn Fn(usize)>>,
+}
+
+impl Steps {
+    pub fn new(steps: impl IntoIterator<Item = impl Into<Step>>) -> Self {
+        Steps {
+            id: None,
+            steps: steps.into_iter().map(Into::into).collect(),
+            current: 0,
+            furthest: 0,
+            orientation: StepsOrientation::Horizontal,
+            on_select: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Index of the step the user is on
+    pub fn current(mut self, current: usize) -> Self {
+        self.current = current;
+        self.furthest = self.furthest.max(current);
+        self
+    }
+
+    /// Index of the furthest step reached, when the user has gone back
+    pub fn furthest(mut self, furthest: usize) -> Self {
+        self.furthest = furthest.max(self.current);
+        self
+    }
+
+    pub fn orientation(mut self, orientation: StepsOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Called with the index of a visited step the user clicks
+    pub fn on_select(mut self, handler: impl Fn(usize) + 'static) -> Self {
+        self.on_select = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn state(&self, index: usize) -> StepState {
+        match index.cmp(&self.current) {
+            std::cmp::Ordering::Less => StepState::Completed,
+            std::cmp::Ordering::Equal => StepState::Current,
+            std::cmp::Ordering::Greater => StepState::Upcoming,
+        }
+    }
+}
+
+impl Component for Steps {
+    fn render(&self) -> Element {
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-steps-{}", NEXT_STEPS_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let vertical = self.orientation == StepsOrientation::Vertical;
+
+        let items = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let state = self.state(index);
+                let (marker, marker_colors, label_color) = match state {
+                    StepState::Completed => ("✓".to_string(), "background: #4f46e5; color: white; border-color: #4f46e5", "#111827"),
+                    StepState::Current => ((index + 1).to_string(), "background: white; color: #4f46e5; border-color: #4f46e5", "#4f46e5"),
+                    StepState::Upcoming => ((index + 1).to_string(), "background: white; color: #6b7280; border-color: #d1d5db", "#6b7280"),
+                };
+
+                let mut text = vec![Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![("style".to_string(), format!("font-weight: 500; color: {}", label_color))],
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(step.label.clone())],
+                }];
+                if let Some(description) = &step.description {
+                    text.push(Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![("style".to_string(), "font-size: 0.75rem; color: #6b7280".to_string())],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text(description.clone())],
+                    });
+                }
+                if state == StepState::Completed {
+                    text.push(Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![("style".to_string(), VISUALLY_HIDDEN_STYLE.to_string())],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text(", completed".to_string())],
+                    });
+                }
+
+                let children = vec![
+                    Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![
+                                ("aria-hidden".to_string(), "true".to_string()),
+                                (
+                                    "style".to_string(),
+                                    format!(
+                                        "display: inline-flex; flex: none; align-items: center; justify-content: center; width: 2rem; height: 2rem; border: 2px solid; border-radius: 9999px; font-size: 0.875rem; font-weight: 600; {}",
+                                        marker_colors
+                                    ),
+                                ),
+                            ],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text(marker)],
+                    },
+                    Element::Node {
+                        tag: "span".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![(
+                                "style".to_string(),
+                                "display: flex; flex-direction: column; text-align: start".to_string(),
+                            )],
+                            ..Default::default()
+                        }),
+                        children: text,
+                    },
+                ];
+
+                let step_style = "display: flex; align-items: center; gap: 0.5rem; padding: 0; border: 0; background: none; font: inherit";
+                // Always a button when selectable, so the click handler bound
+                // on first render stays attached as the user moves on
+                let step_element = match &self.on_select {
+                    Some(on_select) => {
+                        let mut attributes = vec![
+                            ("type".to_string(), "button".to_string()),
+                            ("style".to_string(), format!("{}; cursor: pointer", step_style)),
+                        ];
+                        if index > self.furthest {
+                            attributes.push(("disabled".to_string(), "disabled".to_string()));
+                        }
+                        if state == StepState::Current {
+                            attributes.push(("aria-current".to_string(), "step".to_string()));
+                        }
+                        let on_select = on_select.clone();
+                        Element::Node {
+                            tag: "button".to_string(),
+                            props: Box::new(Props {
+                                attributes,
+                                on_click: Some(Rc::new(move || on_select(index))),
+                                ..Default::default()
+                            }),
+                            children,
+                        }
+                    }
+                    None => {
+                        let mut attributes = vec![("style".to_string(), step_style.to_string())];
+                        if state == StepState::Current {
+                            attributes.push(("aria-current".to_string(), "step".to_string()));
+                        }
+                        Element::Node {
+                            tag: "div".to_string(),
+                            props: Box::new(Props {
+                                attributes,
+                                ..Default::default()
+                            }),
+                            children,
+                        }
+                    }
+                };
+
+                // Line leading to the next step
+                let connector = Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        attributes: vec![
+                            ("aria-hidden".to_string(), "true".to_string()),
+                            (
+                                "style".to_string(),
+                                format!(
+                                    "flex: 1; {}; background: {}",
+                                    if vertical { "width: 2px; min-height: 1.5rem; margin-left: calc(1rem - 1px)" } else { "height: 2px; min-width: 1.5rem" },
+                                    if state == StepState::Completed { "#4f46e5" } else { "#d1d5db" }
+                                ),
+                            ),
+                        ],
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                };
+
+                let last = index + 1 == self.steps.len();
+                Element::Node {
+                    tag: "li".to_string(),
+                    props: Box::new(Props {
+                        class: Some(
+                            match state {
+                                StepState::Completed => "step step-completed",
+                                StepState::Current => "step step-current",
+                                StepState::Upcoming => "step step-upcoming",
+                            }
+                            .to_string(),
+                        ),
+                        attributes: vec![(
+                            "style".to_string(),
+                            format!(
+                                "display: flex; {} gap: 0.5rem; {}",
+                                if vertical { "flex-direction: column; align-items: stretch;" } else { "align-items: center;" },
+                                if last { "flex: none" } else { "flex: 1" }
+                            ),
+                        )],
+                        ..Default::default()
+                    }),
+                    children: if last { vec![step_element] } else { vec![step_element, connector] },
+                }
+            })
+            .collect();
+
+        Element::Node {
+            tag: "ol".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some(format!("steps steps-{}", if vertical { "vertical" } else { "horizontal" })),
+                attributes: vec![
+                    ("aria-label".to_string(), "Progress".to_string()),
+                    (
+                        "style".to_string(),
+                        format!(
+                            "display: flex; {} gap: 0.5rem; margin: 0; padding: 0; list-style: none",
+                            if vertical { "flex-direction: column;" } else { "align-items: center;" }
+                        ),
+                    ),
+                ],
+                ..Default::default()
+            }),
+            children: items,
+        }
+    }
+}
+
+static NEXT_SELECT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Select option
+pub struct SelectOption<T> {
+    pub label: String,
+    pub value: T,
+    pub disabled: bool,
+}
+
+#[derive(Clone, Default)]
+struct SelectState {
+    open: bool,
+    highlighted: Option<usize>,
+    typeahead: String,
+}
+
+/// Select component - a keyboard-operable listbox
+pub struct Select<T: Clone + PartialEq + 'static> {
+    id: Option<String>,
+    options: Rc<Vec<SelectOption<T>>>,
+    value: Option<T>,
+    on_change: Option<Rc<dyn Fn(T)>>,
+    placeholder: String,
+    state: State<SelectState>,
+}
+
+impl<T: Clone + PartialEq + 'static> Select<T> {
+    pub fn new(options: Vec<(String, T)>) -> Self {
+        Select {
+            id: None,
+            options: Rc::new(
+                options
+                    .into_iter()
+                    .map(|(label, value)| SelectOption {
+                        label,
+                        value,
+                        disabled: false,
+                    })
+                    .collect(),
+            ),
+            value: None,
+            on_change: None,
+            placeholder: "Select...".to_string(),
+            state: State::new(SelectState::default()),
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn value(mut self, value: Option<T>) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn on_change(mut self, handler: impl Fn(T) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Mark the option with this value as non-selectable
+    pub fn disable_option(mut self, value: T) -> Self {
+        if let Some(options) = Rc::get_mut(&mut self.options) {
+            for option in options.iter_mut().filter(|o| o.value == value) {
+                option.disabled = true;
+            }
+        }
+        self
+    }
+
+    fn option_id(id: &str, index: usize) -> String {
+        format!("{}-option-{}", id, index)
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let value = self.value.as_ref()?;
+        self.options.iter().position(|o| &o.value == value)
+    }
+}
+
+/// Next enabled option index in the given direction, wrapping around
+fn step_enabled<T>(options: &[SelectOption<T>], from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = options.len();
+    if len == 0 {
+        return None;
+    }
+
+    let start = match (from, forward) {
+        (Some(i), true) => i + 1,
+        (Some(i), false) => i + len - 1,
+        (None, true) => 0,
+        (None, false) => len - 1,
+    };
+
+    (0..len)
+        .map(|offset| {
+            if forward {
+                (start + offset) % len
+            } else {
+                (start + len - offset) % len
+            }
+        })
+        .find(|&i| !options[i].disabled)
+}
+
+fn handle_select_key<T: Clone + PartialEq>(
+    key: &str,
+    options: &[SelectOption<T>],
+    selected: Option<usize>,
+    state: &State<SelectState>,
+    on_change: Option<&Rc<dyn Fn(T)>>,
+) {
+    let mut next = state.get();
+
+    if !next.open {
+        if matches!(key, "ArrowDown" | "ArrowUp" | "Enter" | " ") {
+            next.open = true;
+            next.highlighted = selected
+                .filter(|&i| !options[i].disabled)
+                .or_else(|| step_enabled(options, None, key != "ArrowUp"));
+            state.set(next);
+        }
+        return;
+    }
+
+    match key {
+        "ArrowDown" => next.highlighted = step_enabled(options, next.highlighted, true),
+        "ArrowUp" => next.highlighted = step_enabled(options, next.highlighted, false),
+        "Home" => next.highlighted = step_enabled(options, None, true),
+        "End" => next.highlighted = step_enabled(options, None, false),
+        "Enter" | " " => {
+            if let Some(option) = next.highlighted.map(|i| &options[i]) {
+                if !option.disabled {
+                    if let Some(on_change) = on_change {
+                        on_change(option.value.clone());
+                    }
+                    next = SelectState::default();
+                }
+            }
+        }
+        "Escape" | "Tab" => next = SelectState::default(),
+        _ if key.chars().count() == 1 => {
+            // Type-ahead: jump to the first enabled option matching the typed prefix
+            next.typeahead.push_str(&key.to_lowercase());
+            let find = |prefix: &str| {
+                options
+                    .iter()
+                    .position(|o| !o.disabled && o.label.to_lowercase().starts_with(prefix))
+            };
+            let found = find(&next.typeahead).or_else(|| {
+                next.typeahead = key.to_lowercase();
+                find(&next.typeahead)
+            });
+            if found.is_some() {
+                next.highlighted = found;
+            }
+        }
+        _ => return,
+    }
+
+    state.set(next);
+}
+
+impl<T: Clone + PartialEq + 'static> Component for Select<T> {
+    fn render(&self) -> Element {
+        let state = use_widget_state(&self.state);
+        let current = state.get();
+        let selected = self.selected_index();
+        let id = use_widget_id(self.id.as_deref(), || {
+            format!("layer9-select-{}", NEXT_SELECT_ID.fetch_add(1, Ordering::Relaxed))
+        });
+        let listbox_id = format!("{}-listbox", id);
+
+        let label = selected
+            .map(|i| self.options[i].label.clone())
+            .unwrap_or_else(|| self.placeholder.clone());
+
+        let mut trigger_attrs = vec![
+            ("type".to_string(), "button".to_string()),
+            ("role".to_string(), "combobox".to_string()),
+            ("aria-haspopup".to_string(), "listbox".to_string()),
+            ("aria-controls".to_string(), listbox_id.clone()),
+            ("aria-expanded".to_string(), current.open.to_string()),
+            (
+                "style".to_string(),
+                style![px(3), py(2), border(), border_gray_200(), rounded(), bg_white()].build(),
+            ),
+        ];
+        if let (true, Some(index)) = (current.open, current.highlighted) {
+            trigger_attrs.push((
+                "aria-activedescendant".to_string(),
+                Self::option_id(&id, index),
+            ));
+        }
+
+        let on_keydown = {
+            let options = self.options.clone();
+            let state = state.clone();
+            let on_change = self.on_change.clone();
+            Rc::new(move |key: String| {
+                handle_select_key(&key, &options, selected, &state, on_change.as_ref());
+            }) as Rc<dyn Fn(String)>
+        };
+
+        let on_toggle = {
+            let state = state.clone();
+            let options = self.options.clone();
+            Rc::new(move || {
+                let mut next = state.get();
+                next.open = !next.open;
+                next.highlighted = if next.open {
+                    selected.or_else(|| step_enabled(&options, None, true))
+                } else {
+                    None
+                };
+                next.typeahead.clear();
+                state.set(next);
+            }) as Rc<dyn Fn()>
+        };
+
+        let mut children = vec![Element::Node {
+            tag: "button".to_string(),
+            props: Box::new(Props {
+                id: Some(id.clone()),
+                attributes: trigger_attrs,
+                on_click: Some(on_toggle),
+                on_keydown: Some(on_keydown),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(label)],
+        }];
+
+        if current.open {
+            let items = self
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| {
+                    let is_selected = Some(i) == selected;
+                    let is_highlighted = Some(i) == current.highlighted;
+
+                    let on_click = if option.disabled {
+                        None
+                    } else {
+                        let state = state.clone();
+                        let on_change = self.on_change.clone();
+                        let value = option.value.clone();
+                        Some(Rc::new(move || {
+                            if let Some(on_change) = &on_change {
+                                on_change(value.clone());
+                            }
+                            state.set(SelectState::default());
+                        }) as Rc<dyn Fn()>)
+                    };
+
+                    let background = if is_highlighted { "#f3f4f6" } else { "transparent" };
+                    let color = if option.disabled { "#9ca3af" } else { "inherit" };
+
+                    Element::Node {
+                        tag: "li".to_string(),
+                        props: Box::new(Props {
+                            id: Some(Self::option_id(&id, i)),
+                            attributes: vec![
+                                ("role".to_string(), "option".to_string()),
+                                ("tabindex".to_string(), "-1".to_string()),
+                                ("aria-selected".to_string(), is_selected.to_string()),
+                                ("aria-disabled".to_string(), option.disabled.to_string()),
+                                (
+                                    "style".to_string(),
+                                    format!(
+                                        "padding: 0.5rem 0.75rem; cursor: pointer; background: {}; color: {}",
+                                        background, color
+                                    ),
+                                ),
+                            ],
+                            on_click,
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text(option.label.clone())],
+                    }
+                })
+                .collect();
+
+            children.push(Element::Node {
+                tag: "ul".to_string(),
+                props: Box::new(Props {
+                    id: Some(listbox_id),
+                    attributes: vec![
+                        ("role".to_string(), "listbox".to_string()),
+                        (
+                            "style".to_string(),
+                            "position: absolute; z-index: 10; margin: 0; padding: 0.25rem 0; list-style: none; background: white; border: 1px solid #e5e7eb; border-radius: 0.25rem; min-width: 100%".to_string(),
+                        ),
+                    ],
+                    ..Default::default()
+                }),
+                children: items,
+            });
+        }
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("select".to_string()),
+                attributes: vec![("style".to_string(), "position: relative; display: inline-block".to_string())],
+                on_focus_out: Some(Rc::new(move || {
+                    if state.get().open {
+                        state.set(SelectState::default());
+                    }
+                })),
+                ..Default::default()
+            }),
+            children,
+        }
+    }
+}
+
+/// Sortable cell value for `Table` columns
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl SortValue {
+    fn compare(&self, other: &SortValue) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        match (self, other) {
+            (SortValue::Int(a), SortValue::Int(b)) => a.cmp(b),
+            (SortValue::Float(a), SortValue::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            (SortValue::Int(a), SortValue::Float(b)) => (*a as f64).partial_cmp(b).unwrap_or(Ordering::Equal),
+            (SortValue::Float(a), SortValue::Int(b)) => a.partial_cmp(&(*b as f64)).unwrap_or(Ordering::Equal),
+            (SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+            // Numbers sort before text when a column mixes both
+            (SortValue::Text(_), _) => Ordering::Greater,
+            (_, SortValue::Text(_)) => Ordering::Less,
+        }
+    }
+}
+
+impl From<i64> for SortValue {
+    fn from(value: i64) -> Self {
+        SortValue::Int(value)
+    }
+}
+
+impl From<f64> for SortValue {
+    fn from(value: f64) -> Self {
+        SortValue::Float(value)
+    }
+}
+
+impl From<String> for SortValue {
+    fn from(value: String) -> Self {
+        SortValue::Text(value)
+    }
+}
+
+impl From<&str> for SortValue {
+    fn from(value: &str) -> Self {
+        SortValue::Text(value.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+// Type aliases to simplify complex types
+type CellFn<R> = Rc<dyn Fn(&R) -> Element>;
+type SortKeyFn<R> = Rc<dyn Fn(&R) -> SortValue>;
+
+/// Table column definition
+pub struct Column<R> {
+    header: String,
+    cell: CellFn<R>,
+    sort_key: Option<SortKeyFn<R>>,
+}
+
+impl<R> Column<R> {
+    pub fn new(header: impl Into<String>, cell: impl Fn(&R) -> Element + 'static) -> Self {
+        Column {
+            header: header.into(),
+            cell: Rc::new(cell),
+            sort_key: None,
+        }
+    }
+
+    pub fn sort_key(mut self, key: impl Fn(&R) -> SortValue + 'static) -> Self {
+        self.sort_key = Some(Rc::new(key));
+        self
+    }
+}
+
+/// Table component with clickable column sorting
+pub struct Table<R: 'static> {
+    columns: Vec<Column<R>>,
+    rows: Rc<Vec<R>>,
+    sort: State<Option<(usize, SortDirection)>>,
+}
+
+impl<R: 'static> Table<R> {
+    pub fn new(columns: Vec<Column<R>>, rows: Vec<R>) -> Self {
+        Table {
+            columns,
+            rows: Rc::new(rows),
+            sort: State::new(None),
+        }
+    }
+
+    /// Initial sort column and direction
+    pub fn sorted_by(self, column: usize, direction: SortDirection) -> Self {
+        self.sort.set(Some((column, direction)));
+        self
+    }
+
+    /// Row indices in display order for the given sort
+    fn row_order(&self, sort: Option<(usize, SortDirection)>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+
+        if let Some((column, direction)) = sort {
+            if let Some(key) = self.columns.get(column).and_then(|c| c.sort_key.as_ref()) {
+                let keys: Vec<SortValue> = self.rows.iter().map(|row| key(row)).collect();
+                // `sort_by` is stable, so rows with equal keys keep their original order
+                order.sort_by(|&a, &b| {
+                    let ordering = keys[a].compare(&keys[b]);
+                    match direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
+                    }
+                });
+            }
+        }
+
+        order
+    }
+}
+
+impl<R: 'static> Component for Table<R> {
+    fn render(&self) -> Element {
+        let sort = use_widget_state(&self.sort);
+        let active = sort.get();
+        let cell_style = "padding: 0.5rem 0.75rem; border-bottom: 1px solid #e5e7eb; text-align: left";
+
+        let headers = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let mut attributes = vec![
+                    ("scope".to_string(), "col".to_string()),
+                    ("style".to_string(), cell_style.to_string()),
+                ];
+                let mut label = column.header.clone();
+                let mut on_click = None;
+
+                if column.sort_key.is_some() {
+                    let direction = active.filter(|(c, _)| *c == index).map(|(_, d)| d);
+                    let aria_sort = match direction {
+                        Some(SortDirection::Ascending) => "ascending",
+                        Some(SortDirection::Descending) => "descending",
+                        None => "none",
+                    };
+                    attributes.push(("aria-sort".to_string(), aria_sort.to_string()));
+                    attributes[1].1.push_str("; cursor: pointer; user-select: none");
+
+                    match direction {
+                        Some(SortDirection::Ascending) => label.push_str(" ▲"),
+                        Some(SortDirection::Descending) => label.push_str(" ▼"),
+                        None => {}
+                    }
+
+                    let sort = sort.clone();
+                    on_click = Some(Rc::new(move || {
+                        let next = match direction {
+                            Some(SortDirection::Ascending) => SortDirection::Descending,
+                            _ => SortDirection::Ascending,
+                        };
+                        sort.set(Some((index, next)));
+                    }) as Rc<dyn Fn()>);
+                }
+
+                Element::Node {
+                    tag: "th".to_string(),
+                    props: Box::new(Props {
+                        attributes,
+                        on_click,
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(label)],
+                }
+            })
+            .collect();
+
+        let rows = self
+            .row_order(active)
+            .into_iter()
+            .map(|index| {
+                let row = &self.rows[index];
+                Element::Node {
+                    tag: "tr".to_string(),
+                    props: Box::default(),
+                    children: self
+                        .columns
+                        .iter()
+                        .map(|column| Element::Node {
+                            tag: "td".to_string(),
+                            props: Box::new(Props {
+                                attributes: vec![("style".to_string(), cell_style.to_string())],
+                                ..Default::default()
+                            }),
+                            children: vec![(column.cell)(row)],
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Element::Node {
+            tag: "table".to_string(),
+            props: Box::new(Props {
+                attributes: vec![(
+                    "style".to_string(),
+                    "width: 100%; border-collapse: collapse".to_string(),
+                )],
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "thead".to_string(),
+                    props: Box::default(),
+                    children: vec![Element::Node {
+                        tag: "tr".to_string(),
+                        props: Box::default(),
+                        children: headers,
+                    }],
+                },
+                Element::Node {
+                    tag: "tbody".to_string(),
+                    props: Box::default(),
+                    children: rows,
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn trigger_props(element: &Element) -> Props {
+        let Element::Node { children, .. } = element else {
+            panic!("expected select container");
+        };
+        let Element::Node { props, .. } = &children[0] else {
+            panic!("expected trigger button");
+        };
+        (**props).clone()
+    }
+
+    fn press(select: &Select<u32>, key: &str) {
+        let props = trigger_props(&select.render());
+        (props.on_keydown.unwrap())(key.to_string());
+    }
+
+    #[test]
+    fn test_select_with_keyboard() {
+        let chosen = Rc::new(RefCell::new(None));
+        let chosen_ref = chosen.clone();
+
+        let select = Select::new(vec![
+            ("Small".to_string(), 1u32),
+            ("Medium".to_string(), 2),
+            ("Large".to_string(), 3),
+        ])
+        .disable_option(2)
+        .on_change(move |value| *chosen_ref.borrow_mut() = Some(value));
+
+        press(&select, "ArrowDown"); // open, highlight "Small"
+        press(&select, "ArrowDown"); // skip disabled "Medium"
+        press(&select, "Enter");
+
+        assert_eq!(*chosen.borrow(), Some(3));
+        assert!(!select.state.get().open);
+    }
+
+    #[test]
+    fn test_select_typeahead_and_escape() {
+        let select = Select::new(vec![
+            ("Apple".to_string(), 1u32),
+            ("Banana".to_string(), 2),
+            ("Blueberry".to_string(), 3),
+        ])
+        .id("fruit");
+
+        press(&select, "Enter");
+        press(&select, "b");
+        press(&select, "l");
+        assert_eq!(select.state.get().highlighted, Some(2));
+
+        let props = trigger_props(&select.render());
+        assert!(props
+            .attributes
+            .contains(&("aria-activedescendant".to_string(), Select::<u32>::option_id("fruit", 2))));
+
+        press(&select, "Escape");
+        assert!(!select.state.get().open);
+    }
+
+    fn header(table: &Table<(String, i64)>, index: usize) -> Props {
+        let Element::Node { children, .. } = table.render() else {
+            panic!("expected table");
+        };
+        let Element::Node { children: head_rows, .. } = &children[0] else {
+            panic!("expected thead");
+        };
+        let Element::Node { children: cells, .. } = &head_rows[0] else {
+            panic!("expected header row");
+        };
+        let Element::Node { props, .. } = &cells[index] else {
+            panic!("expected th");
+        };
+        (**props).clone()
+    }
+
+    fn first_column(table: &Table<(String, i64)>) -> Vec<String> {
+        let Element::Node { children, .. } = table.render() else {
+            panic!("expected table");
+        };
+        let Element::Node { children: rows, .. } = &children[1] else {
+            panic!("expected tbody");
+        };
+        rows.iter()
+            .map(|row| {
+                let Element::Node { children: cells, .. } = row else {
+                    panic!("expected tr");
+                };
+                let Element::Node { children: content, .. } = &cells[0] else {
+                    panic!("expected td");
+                };
+                match &content[0] {
+                    Element::Text(text) => text.clone(),
+                    _ => panic!("expected text cell"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_table_sorts_by_numeric_column() {
+        let table = Table::new(
+            vec![
+                Column::new("Name", |row: &(String, i64)| Element::Text(row.0.clone())),
+                Column::new("Posts", |row: &(String, i64)| Element::Text(row.1.to_string()))
+                    .sort_key(|row| SortValue::Int(row.1)),
+            ],
+            vec![
+                ("carol".to_string(), 7),
+                ("alice".to_string(), 2),
+                ("bob".to_string(), 7),
+                ("dave".to_string(), 1),
+            ],
+        );
+
+        assert!(header(&table, 0).on_click.is_none());
+        (header(&table, 1).on_click.unwrap())();
+        assert_eq!(first_column(&table), vec!["dave", "alice", "carol", "bob"]);
+        assert!(header(&table, 1)
+            .attributes
+            .contains(&("aria-sort".to_string(), "ascending".to_string())));
+
+        (header(&table, 1).on_click.unwrap())();
+        // Equal keys keep their original relative order
+        assert_eq!(first_column(&table), vec!["carol", "bob", "alice", "dave"]);
+        assert!(header(&table, 1)
+            .attributes
+            .contains(&("aria-sort".to_string(), "descending".to_string())));
+    }
+
+    #[test]
+    fn test_loading_button_ignores_clicks() {
+        let clicked = Rc::new(RefCell::new(false));
+        let clicked_ref = clicked.clone();
+        let button = Button::new("Save")
+            .variant(ButtonVariant::Secondary)
+            .on_click(move || *clicked_ref.borrow_mut() = true)
+            .loading(true);
+
+        let Element::Node { props, children, .. } = button.render() else {
+            panic!("expected button");
+        };
+        if let Some(handler) = props.on_click {
+            handler();
+        }
+
+        assert!(!*clicked.borrow());
+        assert!(props.attributes.contains(&("aria-busy".to_string(), "true".to_string())));
+        assert!(!props.attributes.iter().any(|(k, _)| k == "disabled"));
+        assert_eq!(children.len(), 2);
+    }
+
+    #[test]
+    fn test_disabled_button() {
+        let Element::Node { props, .. } = Button::new("Save").on_click(|| {}).disabled(true).render() else {
+            panic!("expected button");
+        };
+
+        assert!(props.on_click.is_none());
+        assert!(props.attributes.contains(&("disabled".to_string(), "disabled".to_string())));
+        assert!(props.attributes.contains(&("aria-disabled".to_string(), "true".to_string())));
+    }
+
+    #[test]
+    fn test_card_rows_by_its_own_width() {
+        let body = || vec![Element::Text("image".into()), Element::Text("text".into())];
+
+        let plain = Card::new().children(body()).render().to_html();
+        assert!(plain.ends_with(">imagetext</div>"));
+
+        let adaptive = Card::new()
+            .children(body())
+            .row_from(Breakpoint::Custom(420))
+            .render()
+            .to_html();
+        assert!(adaptive.contains("class=\"l9-container-"));
+        assert!(adaptive.contains(">imagetext</div></div></div>"));
+    }
+
+    #[test]
+    fn test_disclosure_toggles_region() {
+        let disclosure = Disclosure::new("Show advanced options")
+            .id("advanced")
+            .children(vec![Element::Text("Timeout".to_string())]);
+
+        let parts = |disclosure: &Disclosure| {
+            let Element::Node { children, .. } = disclosure.render() else {
+                panic!("expected disclosure");
+            };
+            let (Element::Node { props: trigger, .. }, Element::Node { props: region, .. }) =
+                (&children[0], &children[1])
+            else {
+                panic!("expected trigger and region");
+            };
+            (trigger.clone(), region.clone())
+        };
+
+        let (trigger, region) = parts(&disclosure);
+        assert!(trigger.attributes.contains(&("aria-expanded".to_string(), "false".to_string())));
+        assert!(trigger
+            .attributes
+            .contains(&("aria-controls".to_string(), "advanced-region".to_string())));
+        assert_eq!(region.id.as_deref(), Some("advanced-region"));
+        assert!(region.attributes.iter().any(|(k, _)| k == "hidden"));
+
+        (trigger.on_click.unwrap())();
+        let (trigger, region) = parts(&disclosure);
+        assert!(trigger.attributes.contains(&("aria-expanded".to_string(), "true".to_string())));
+        assert!(!region.attributes.iter().any(|(k, _)| k == "hidden"));
+
+        // The animated variant collapses with styles instead of `hidden`
+        let animated = Disclosure::new("More").animated(true);
+        let (_, region) = parts(&animated);
+        assert!(!region.attributes.iter().any(|(k, _)| k == "hidden"));
+        assert!(region
+            .attributes
+            .iter()
+            .any(|(k, v)| k == "style" && v.contains("grid-template-rows: 0fr")));
+    }
+
+    /// Builds its widgets afresh on every render, as components do
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Settings;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Settings {
+        fn render(&self) -> Element {
+            let text = |text: &str| Element::Text(text.to_string());
+            Element::Node {
+                tag: "div".to_string(),
+                props: Box::default(),
+                children: vec![
+                    Disclosure::new("More").id("more").children(vec![text("Timeout")]).render(),
+                    Accordion::new(vec![(text("Billing"), text("Monthly")), (text("Shipping"), text("Free"))])
+                        .id("faq")
+                        .render(),
+                    Tooltip::new(text("?"), "Help").id("tip").render(),
+                    CodeBlock::new("layer9 new app").id("install").render(),
+                    Select::new(vec![("Small".to_string(), 1u32), ("Large".to_string(), 2)])
+                        .id("size")
+                        .render(),
+                ],
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_widgets_keep_their_state_across_rerenders() {
+        let settings = Settings;
+        let mut tree = crate::testing::render(&settings);
+        tree.click("#more");
+        tree.click("#faq-header-1");
+        tree.click("#tip-anchor");
+        tree.click("button.code-block-copy");
+        tree.keydown("#size", "ArrowDown");
+        tree.keydown("#size", "ArrowDown");
+
+        tree.rerender();
+        let has = |selector: &str, name: &str, value: &str| match tree.find(selector) {
+            Some(Element::Node { props, .. }) => props.attributes.contains(&(name.to_string(), value.to_string())),
+            _ => panic!("no element matches `{}`", selector),
+        };
+        let expanded = |selector: &str| has(selector, "aria-expanded", "true");
+        assert!(expanded("#more"));
+        assert!(expanded("#faq-header-1"));
+        assert!(expanded("#size"));
+        assert!(has("#size", "aria-activedescendant", "size-option-1"));
+        let html = tree.to_html();
+        assert!(html.contains("visibility: visible"));
+        assert!(html.contains(">Copy failed</button>"));
+    }
+
+    /// Widgets without explicit ids, built afresh on every render
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Anonymous;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Anonymous {
+        fn render(&self) -> Element {
+            Element::Node {
+                tag: "div".to_string(),
+                props: Box::default(),
+                children: vec![
+                    NumberInput::<u32>::new().render(),
+                    Rating::new(3.0).on_change(|_| {}).render(),
+                    TagInput::new(State::new(vec![])).render(),
+                    Steps::new(["Account", "Profile"]).render(),
+                    Select::new(vec![("Small".to_string(), 1u32)]).render(),
+                Disclosure::new("More").render(),
+                Accordion::new(vec![(Element::Text("Q".to_string()), Element::Text("A".to_string()))]).render(),
+                Tooltip::new(Element::Text("?".to_string()), "Help").render(),
+                CodeBlock::new("cargo build").render(),
+                ],
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_generated_ids_survive_rerenders() {
+        let ids = |tree: &crate::testing::TestTree| -> Vec<String> {
+            let html = tree.to_html();
+            html.split(" id=\"").skip(1).map(|rest| rest[..rest.find('"').unwrap()].to_string()).collect()
+        };
+
+        let anonymous = Anonymous;
+        let mut tree = crate::testing::render(&anonymous);
+        let first = ids(&tree);
+        let prefixes = [
+            "layer9-number-",
+            "layer9-rating-",
+            "layer9-tags-",
+            "layer9-steps-",
+            "layer9-select-",
+            "layer9-disclosure-",
+            "layer9-accordion-",
+            "layer9-tooltip-",
+            "layer9-code-",
+        ];
+        for prefix in prefixes {
+            assert!(first.iter().any(|id| id.starts_with(prefix)), "no {} id in {:?}", prefix, first);
+        }
+
+        tree.rerender();
+        assert_eq!(ids(&tree), first);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Leaderboard;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Leaderboard {
+        fn render(&self) -> Element {
+            Table::new(
+                vec![
+                    Column::new("Name", |row: &(String, i64)| Element::Text(row.0.clone()))
+                        .sort_key(|row| SortValue::Text(row.0.clone())),
+                    Column::new("Posts", |row: &(String, i64)| Element::Text(row.1.to_string()))
+                        .sort_key(|row| SortValue::Int(row.1)),
+                ],
+                vec![("bob".to_string(), 2), ("carol".to_string(), 7), ("alice".to_string(), 1)],
+            )
+            .sorted_by(1, SortDirection::Descending)
+            .render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_table_keeps_its_sort_across_rerenders() {
+        let names = |tree: &crate::testing::TestTree| -> Vec<String> {
+            tree.find_all("td")
+                .into_iter()
+                .step_by(2)
+                .map(|cell| match cell {
+                    Element::Node { children, .. } => match &children[0] {
+                        Element::Text(text) => text.clone(),
+                        _ => panic!("expected text cell"),
+                    },
+                    _ => panic!("expected td"),
+                })
+                .collect()
+        };
+
+        let leaderboard = Leaderboard;
+        let mut tree = crate::testing::render(&leaderboard);
+        assert_eq!(names(&tree), vec!["carol", "bob", "alice"]);
+
+        tree.click("th");
+        tree.rerender();
+        assert_eq!(names(&tree), vec!["alice", "bob", "carol"]);
+        let Some(Element::Node { props, .. }) = tree.find("th") else {
+            panic!("expected th");
+        };
+        assert!(props.attributes.contains(&("aria-sort".to_string(), "ascending".to_string())));
+    }
+
+    #[test]
+    fn test_accordion_single_mode_keeps_one_section_open() {
+        let section = |name: &str| {
+            (Element::Text(name.to_string()), Element::Text(format!("{} answer", name)))
+        };
+        let accordion = Accordion::new(vec![
+            section("Billing"),
+            section("Shipping"),
+            section("Returns"),
+        ])
+        .id("faq")
+        .default_open([2, 0]);
+        assert!(accordion.is_open(0) && !accordion.is_open(2));
+
+        let header = |index: usize| {
+            let Element::Node { children, .. } = accordion.render() else {
+                panic!("expected accordion");
+            };
+            let Element::Node { children: parts, .. } = &children[index] else {
+                panic!("expected section");
+            };
+            let Element::Node { children: heading, .. } = &parts[0] else {
+                panic!("expected heading");
+            };
+            let Element::Node { props, .. } = &heading[0] else {
+                panic!("expected header button");
+            };
+            (props.clone(), parts.len())
+        };
+
+        let (billing, parts) = header(0);
+        assert!(billing.attributes.contains(&("aria-expanded".to_string(), "true".to_string())));
+        assert!(billing.attributes.contains(&("aria-controls".to_string(), "faq-panel-0".to_string())));
+        assert_eq!(parts, 2);
+
+        let (shipping, parts) = header(1);
+        assert_eq!(parts, 1, "collapsed panels aren't rendered");
+        (shipping.on_click.unwrap())();
+        assert_eq!(accordion.open.get(), vec![1]);
+        let html = accordion.render().to_html();
+        assert!(html.contains("Shipping answer") && !html.contains("Billing answer"));
+
+        // Clicking the open section closes it
+        (header(1).0.on_click.unwrap())();
+        assert!(accordion.open.get().is_empty());
+
+        let multiple = Accordion::new(vec![section("A"), section("B")])
+            .allow_multiple(true)
+            .default_open([1]);
+        multiple.toggle(0);
+        assert_eq!(multiple.open.get(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_progress_clamps_aria_value() {
+        let aria_now = |progress: Progress| {
+            let Element::Node { props, .. } = progress.render() else {
+                panic!("expected progress bar");
+            };
+            props
+                .attributes
+                .iter()
+                .find(|(name, _)| name == "aria-valuenow")
+                .map(|(_, value)| value.clone())
+        };
+
+        assert_eq!(aria_now(Progress::new(42.5)).as_deref(), Some("42.5"));
+        assert_eq!(aria_now(Progress::new(140.0)).as_deref(), Some("100"));
+        assert_eq!(aria_now(Progress::new(-3.0)).as_deref(), Some("0"));
+        assert_eq!(aria_now(Progress::new(f32::NAN)).as_deref(), Some("0"));
+        assert_eq!(aria_now(Progress::indeterminate()), None);
+
+        let html = Progress::indeterminate().label("Uploading").render().to_html();
+        assert!(html.contains(r#"role="progressbar""#) && html.contains(r#"aria-label="Uploading""#));
+        assert!(html.contains("progress-indeterminate"));
+    }
+
+    #[test]
+    fn test_avatar_falls_back_to_initials_when_the_image_fails() {
+        let avatar = Avatar::new().src("https://example.com/missing.png").name("Ada Lovelace");
+        assert!(avatar.shows_image());
+
+        let Element::Node { tag, props, .. } = avatar.render() else {
+            panic!("expected avatar image");
+        };
+        assert_eq!(tag, "img");
+        assert!(props.attributes.contains(&("alt".to_string(), "Ada Lovelace".to_string())));
+        (props.on_error.unwrap())();
+
+        let html = avatar.render().to_html();
+        assert!(html.contains(r#"class="avatar-initials""#) && html.contains(">AL<"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Profile(RefCell<&'static str>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for Profile {
+        fn render(&self) -> Element {
+            Avatar::new().src(*self.0.borrow()).name("Ada Lovelace").render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_avatar_remembers_a_failed_image_until_the_source_changes() {
+        let profile = Profile(RefCell::new("https://example.com/missing.png"));
+        let mut tree = crate::testing::render(&profile);
+        let Some(Element::Node { props, .. }) = tree.find("img") else {
+            panic!("expected avatar image");
+        };
+        (props.on_error.clone().unwrap())();
+
+        // The avatar rebuilt by the re-render skips the broken image
+        tree.rerender();
+        assert!(tree.find("img").is_none());
+        assert!(tree.find(".avatar-initials").is_some());
+
+        *profile.0.borrow_mut() = "https://example.com/ada.png";
+        tree.rerender();
+        assert!(tree.find("img").is_some());
+    }
+
+    #[test]
+    fn test_avatar_initials_edge_cases() {
+        assert_eq!(Avatar::new().name("grace").display_initials(), "G");
+        assert_eq!(Avatar::new().name(" Grace  Brewster Hopper ").display_initials(), "GH");
+        assert_eq!(Avatar::new().name("Ada").initials("AL").display_initials(), "AL");
+
+        let empty = Avatar::new().name("  ");
+        assert_eq!(empty.display_initials(), "");
+        assert_eq!(empty.color(), AVATAR_NEUTRAL);
+        assert!(empty.render().to_html().contains(r#"aria-hidden="true""#));
+    }
+
+    #[test]
+    fn test_tooltip_shows_on_hover_and_hides_on_blur() {
+        let tooltip = Tooltip::new(
+            Element::Node {
+                tag: "button".to_string(),
+                props: Box::default(),
+                children: vec![Element::Text("Save".to_string())],
+            },
+            "Save your changes",
+        )
+        .id("save-tip")
+        .placement(TooltipPlacement::Bottom);
+
+        let anchor = || {
+            let Element::Node { props, children, .. } = tooltip.render() else {
+                panic!("expected tooltip anchor");
+            };
+            (props, children)
+        };
+        let (props, children) = anchor();
+        let Element::Node { tag, props: trigger, .. } = &children[0] else {
+            panic!("expected trigger");
+        };
+        assert_eq!(tag, "button");
+        assert!(trigger.attributes.contains(&("aria-describedby".to_string(), "save-tip".to_string())));
+        let html = tooltip.render().to_html();
+        assert!(html.contains(r#"role="tooltip""#) && html.contains("visibility: hidden"));
+
+        (props.on_hover.as_ref().unwrap())(true);
+        assert!(tooltip.is_open());
+        let html = tooltip.render().to_html();
+        assert!(html.contains("visibility: visible") && html.contains("top: calc(100% + 6px)"));
+
+        (props.on_focus_out.as_ref().unwrap())();
+        assert!(!tooltip.is_open());
+
+        // A tap shows it, Escape dismisses it
+        (props.on_click.as_ref().unwrap())();
+        assert!(tooltip.is_open());
+        (props.on_keydown.as_ref().unwrap())("Escape".to_string());
+        assert!(!tooltip.is_open());
+
+        // Text children get a focusable wrapper to carry the description
+        let text = Tooltip::new(Element::Text("?".to_string()), "Help").id("help-tip");
+        assert!(text.render().to_html().contains(r#"aria-describedby="help-tip""#));
+    }
+
+    #[test]
+    fn test_tooltip_flips_when_it_would_overflow() {
+        let viewport = (800.0, 600.0);
+        let tip = (120.0, 30.0);
+
+        // Too close to the top edge, with room below
+        let near_top = (10.0, 300.0, 40.0, 380.0);
+        assert_eq!(TooltipPlacement::Top.fit(near_top, tip, viewport), TooltipPlacement::Bottom);
+        assert_eq!(TooltipPlacement::Bottom.fit(near_top, tip, viewport), TooltipPlacement::Bottom);
+
+        let near_right = (300.0, 700.0, 330.0, 780.0);
+        assert_eq!(TooltipPlacement::Right.fit(near_right, tip, viewport), TooltipPlacement::Left);
+
+        // Stays put when the other side is even tighter
+        let cramped = (10.0, 300.0, 595.0, 380.0);
+        assert_eq!(TooltipPlacement::Top.fit(cramped, tip, viewport), TooltipPlacement::Top);
+    }
+
+    #[test]
+    fn test_steps_states_and_navigation() {
+        let selected = Rc::new(RefCell::new(vec![]));
+        let steps = {
+            let selected = selected.clone();
+            Steps::new(["Account", "Security", "Profile"])
+                .current(1)
+                .on_select(move |index| selected.borrow_mut().push(index))
+        };
+        assert_eq!(steps.state(0), StepState::Completed);
+        assert_eq!(steps.state(1), StepState::Current);
+        assert_eq!(steps.state(2), StepState::Upcoming);
+
+        let Element::Node { children: items, .. } = steps.render() else {
+            panic!("expected list");
+        };
+        let buttons: Vec<Props> = items
+            .iter()
+            .map(|item| match item {
+                Element::Node { children, .. } => match &children[0] {
+                    Element::Node { props, .. } => (**props).clone(),
+                    _ => panic!("expected step"),
+                },
+                _ => panic!("expected item"),
+            })
+            .collect();
+
+        assert!(buttons[1].attributes.contains(&("aria-current".to_string(), "step".to_string())));
+        // Steps not reached yet can't be jumped to
+        assert!(!buttons[0].attributes.iter().any(|(k, _)| k == "disabled"));
+        assert!(buttons[2].attributes.iter().any(|(k, _)| k == "disabled"));
+
+        (buttons[0].on_click.clone().unwrap())();
+        assert_eq!(*selected.borrow(), vec![0]);
+
+        let vertical = Steps::new(["One", "Two"]).orientation(StepsOrientation::Vertical).render().to_html();
+        assert!(vertical.contains("steps-vertical"));
+        assert!(!vertical.contains("<button"));
+    }
+
+    #[test]
+    fn test_code_block_header_and_copy_status() {
+        let block = CodeBlock::new("cargo install layer9-cli\nlayer9 new app")
+            .filename("install.sh")
+            .language("bash")
+            .prompt("$");
+
+        let html = block.render().to_html();
+        assert!(html.contains("install.sh"));
+        assert!(html.contains(r#"class="language-bash""#));
+        assert!(html.contains(r#"aria-label="Copy install.sh""#));
+        assert_eq!(html.matches("$ </span>").count(), 2);
+        assert!(html.contains(">Copy</button>"));
+
+        // There's no clipboard outside the browser, so the copy fails visibly
+        block.copy();
+        assert_eq!(block.status(), CopyStatus::Failed);
+        assert!(block.render().to_html().contains(">Copy failed</button>"));
+    }
+
+    #[test]
+    fn test_number_input_steps_and_clamps() {
+        let emitted = Rc::new(RefCell::new(vec![]));
+        let emitted_ref = emitted.clone();
+        let age = |value: u32| {
+            let emitted_ref = emitted_ref.clone();
+            NumberInput::new()
+                .id("age")
+                .min(18u32)
+                .max(120)
+                .value(Some(value))
+                .on_change(move |value| emitted_ref.borrow_mut().push(value))
+        };
+        let parts = |input: &NumberInput<u32>| {
+            let Element::Node { children, .. } = input.render() else {
+                panic!("expected number input");
+            };
+            let props = |i: usize| match &children[i] {
+                Element::Node { props, .. } => props.clone(),
+                _ => panic!("expected node"),
+            };
+            (props(0), props(1), props(2))
+        };
+
+        let (_, field, increase) = parts(&age(119));
+        assert!(field.attributes.contains(&("role".to_string(), "spinbutton".to_string())));
+        assert!(field.attributes.contains(&("aria-valuemin".to_string(), "18".to_string())));
+        (increase.on_click.unwrap())();
+        (field.on_keydown.clone().unwrap())("ArrowDown".to_string());
+        (field.on_keydown.clone().unwrap())("Home".to_string());
+        assert_eq!(*emitted.borrow(), vec![120, 118, 18]);
+
+        // At the top of the range the increase stepper is disabled
+        let (decrease, _, increase) = parts(&age(120));
+        assert!(decrease.on_click.is_some());
+        assert!(increase.on_click.is_none());
+        assert!(increase.attributes.contains(&("disabled".to_string(), "disabled".to_string())));
+
+        // Typed values are clamped; text that isn't a number is dropped
+        emitted.borrow_mut().clear();
+        let (_, field, _) = parts(&age(30));
+        let commit = field.on_change.unwrap();
+        commit("150".to_string());
+        commit(" 42 ".to_string());
+        commit("forty".to_string());
+        assert_eq!(*emitted.borrow(), vec![120, 42]);
+    }
+
+    #[test]
+    fn test_number_range_rounds_to_step() {
+        let range = NumberRange {
+            min: Some(0.0),
+            max: None,
+            step: 0.1,
+        };
+        assert_eq!(range.step_from(Some(0.2), 1.0), 0.3);
+        assert_eq!(range.step_from(Some(0.05), -1.0), 0.0);
+        assert_eq!(range.step_from(None, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_rating_keyboard_and_half_stars() {
+        let chosen = Rc::new(RefCell::new(vec![]));
+        let chosen_ref = chosen.clone();
+        let rating = Rating::new(3.5)
+            .id("review")
+            .allow_half(true)
+            .on_change(move |value| chosen_ref.borrow_mut().push(value));
+
+        let Element::Node { props, children, .. } = rating.render() else {
+            panic!("expected rating");
+        };
+        assert!(props.attributes.contains(&("role".to_string(), "slider".to_string())));
+        assert!(props
+            .attributes
+            .contains(&("aria-valuetext".to_string(), "3.5 out of 5 stars".to_string())));
+
+        // The fourth star is half filled and has a click target per half
+        let Element::Node { children: star, .. } = &children[3] else {
+            panic!("expected star");
+        };
+        let Element::Node { props: fill, .. } = &star[1] else {
+            panic!("expected fill");
+        };
+        assert!(fill.attributes[0].1.contains("width: 50%"));
+        let Element::Node { props: left, .. } = &star[2] else {
+            panic!("expected left half");
+        };
+        (left.on_click.clone().unwrap())();
+
+        let press = props.on_keydown.unwrap();
+        for key in ["ArrowRight", "ArrowLeft", "End", "Home", "2", "9", "Enter"] {
+            press(key.to_string());
+        }
+        assert_eq!(*chosen.borrow(), vec![3.5, 4.0, 3.0, 5.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn test_rating_without_handler_is_display_only() {
+        let Element::Node { props, children, .. } = Rating::new(4.2).max(10).render() else {
+            panic!("expected rating");
+        };
+        assert!(props.on_keydown.is_none());
+        assert!(props
+            .attributes
+            .contains(&("aria-label".to_string(), "Rating: 4 out of 10 stars".to_string())));
+        assert_eq!(children.len(), 10);
+    }
+
+    #[test]
+    fn test_tag_input_adds_unique_tags_and_removes() {
+        let tags = State::new(vec!["rust".to_string()]);
+        let emitted = Rc::new(RefCell::new(vec![]));
+        let emitted_ref = emitted.clone();
+        let input = TagInput::new(tags.clone()).on_change(move |tags| *emitted_ref.borrow_mut() = tags);
+
+        let field = |input: &TagInput| {
+            let Element::Node { children, .. } = input.render() else {
+                panic!("expected tag input");
+            };
+            let Element::Node { props, .. } = &children[1] else {
+                panic!("expected field");
+            };
+            props.clone()
+        };
+        let props = field(&input);
+        let (type_text, press) = (props.on_input.unwrap(), props.on_keydown.unwrap());
+
+        // Commas split pasted text; duplicates are skipped whatever their case
+        type_text("wasm, Rust,ui".to_string());
+        assert_eq!(tags.get(), vec!["rust", "wasm"]);
+        press("Enter".to_string());
+        assert_eq!(tags.get(), vec!["rust", "wasm", "ui"]);
+        press("Enter".to_string());
+        assert_eq!(tags.get().len(), 3);
+
+        // Backspace only removes a chip once the field is empty
+        type_text("d".to_string());
+        press("Backspace".to_string());
+        assert_eq!(tags.get().len(), 3);
+        type_text(String::new());
+        press("Backspace".to_string());
+        assert_eq!(tags.get(), vec!["rust", "wasm"]);
+
+        let Element::Node { children, .. } = input.render() else {
+            panic!("expected tag input");
+        };
+        let Element::Node { children: chips, .. } = &children[0] else {
+            panic!("expected chips");
+        };
+        let Element::Node { children: chip, .. } = &chips[0] else {
+            panic!("expected chip");
+        };
+        let Element::Node { props: remove, .. } = &chip[1] else {
+            panic!("expected remove button");
+        };
+        assert!(remove.attributes.contains(&("aria-label".to_string(), "Remove rust".to_string())));
+        (remove.on_click.clone().unwrap())();
+        assert_eq!(*emitted.borrow(), vec!["wasm"]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    struct PostLabels;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for PostLabels {
+        fn render(&self) -> Element {
+            TagInput::new(State::new(vec![])).id("labels").render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_tag_input_keeps_tags_and_draft_across_rerenders() {
+        let labels = PostLabels;
+        let mut tree = crate::testing::render(&labels);
+        tree.input("#labels-input", "urgent,");
+        // Typing re-renders; the half-typed tag must survive it
+        tree.input("#labels-input", "bu");
+        tree.rerender();
+        tree.keydown("#labels-input", "Enter");
+
+        let chips: Vec<String> = tree.find_all("li").into_iter().map(crate::testing::text_content).collect();
+        assert_eq!(chips, vec!["urgent×", "bu×"]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_tooltip_shows_after_delay() {
+        let tooltip = Tooltip::new(Element::Text("?".to_string()), "Help")
+            .id("delayed-tip")
+            .delay_ms(50);
+        let Element::Node { props, .. } = tooltip.render() else {
+            panic!("expected tooltip anchor");
+        };
+        let body = web_sys::window().unwrap().document().unwrap().body().unwrap();
+        let node = tooltip.render().to_dom();
+        body.append_child(&node).unwrap();
+
+        (props.on_hover.as_ref().unwrap())(true);
+        assert!(!tooltip.is_open());
+
+        gloo_timers::future::TimeoutFuture::new(100).await;
+        assert!(tooltip.is_open());
+
+        (props.on_focus_out.as_ref().unwrap())();
+        assert!(!tooltip.is_open());
+        body.remove_child(&node).unwrap();
+    }
+}
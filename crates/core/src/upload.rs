@@ -1,10 +1,21 @@
 //! File upload support for Layer9
 
+use crate::component::{Component, Element, Props};
+use crate::hooks::{use_effect, use_widget_id};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{File, FormData, Response, Headers, Request, RequestInit};
+use web_sys::{File, FileList, FormData, Response, Headers, Request, RequestInit, XmlHttpRequest};
 use wasm_bindgen_futures::JsFuture;
 
+/// Progress callback: `(file_id, uploaded_bytes, total_bytes)`
+pub type ProgressFn = Rc<dyn Fn(&str, u64, u64)>;
+
+type ProgressListener = Rc<dyn Fn(f32)>;
+
 #[derive(Debug, Clone)]
 pub struct FileUpload {
     pub file: File,
@@ -18,12 +29,389 @@ pub enum UploadStatus {
     Uploading,
     Complete,
     Failed(String),
+    Cancelled,
+}
+
+impl UploadStatus {
+    /// Whether the upload has stopped for good
+    pub fn is_finished(&self) -> bool {
+        matches!(self, UploadStatus::Complete | UploadStatus::Failed(_) | UploadStatus::Cancelled)
+    }
+}
+
+/// Byte range of one chunk; `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkRange {
+    pub start: u64,
+    pub end: u64,
+    pub total: u64,
+}
+
+impl ChunkRange {
+    /// `Content-Range` header value, e.g. `bytes 0-1048575/10485760`
+    pub fn content_range(&self) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end.saturating_sub(1), self.total)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Resumable chunked upload of one file.
+///
+/// Tracks how many bytes the server has acknowledged, so a retry continues
+/// from the last acknowledged chunk instead of starting over.
+pub struct ChunkedUpload {
+    pub upload_id: String,
+    total: u64,
+    chunk_size: u64,
+    acknowledged: u64,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl ChunkedUpload {
+    pub fn new(upload_id: impl Into<String>, total: u64, chunk_size: u64) -> Self {
+        Self {
+            upload_id: upload_id.into(),
+            total,
+            chunk_size: chunk_size.max(1),
+            acknowledged: 0,
+            cancelled: Rc::new(Cell::new(false)),
+        }
+    }
+
+    /// Continue an upload the server has already received `acknowledged` bytes of
+    pub fn resume_from(mut self, acknowledged: u64) -> Self {
+        self.acknowledged = acknowledged.min(self.total);
+        self
+    }
+
+    /// Share a cancel flag; once set no further chunks are sent
+    pub fn with_cancel_flag(mut self, cancelled: Rc<Cell<bool>>) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
+    pub fn acknowledged(&self) -> u64 {
+        self.acknowledged
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.acknowledged >= self.total
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    /// Next range to send, starting at the last acknowledged byte
+    pub fn next_chunk(&self) -> Option<ChunkRange> {
+        if self.is_complete() {
+            return None;
+        }
+        Some(ChunkRange {
+            start: self.acknowledged,
+            end: (self.acknowledged + self.chunk_size).min(self.total),
+            total: self.total,
+        })
+    }
+
+    /// Send the remaining chunks in order.
+    ///
+    /// `send` returns how many leading bytes the server now holds, which may
+    /// be past the chunk just sent when the server already had later ones.
+    pub async fn run<S, Fut>(&mut self, mut send: S, on_progress: impl Fn(u64, u64)) -> Result<(), String>
+    where
+        S: FnMut(ChunkRange) -> Fut,
+        Fut: Future<Output = Result<u64, String>>,
+    {
+        while let Some(chunk) = self.next_chunk() {
+            if self.cancelled.get() {
+                return Err("Upload cancelled".to_string());
+            }
+
+            let received = send(chunk).await?.min(self.total);
+            if received <= self.acknowledged {
+                return Err(format!("Server did not acknowledge {}", chunk.content_range()));
+            }
+            self.acknowledged = received;
+            on_progress(self.acknowledged, self.total);
+        }
+        Ok(())
+    }
+}
+
+/// Bytes held by the server, from a `Range: bytes=0-N` response header
+fn parse_received_range(range: &str) -> Option<u64> {
+    let end = range.strip_prefix("bytes=")?.split('-').nth(1)?;
+    end.trim().parse::<u64>().ok().map(|end| end + 1)
+}
+
+/// Send one chunk with `Content-Range` and the upload id
+async fn send_chunk(url: &str, file: &File, upload_id: &str, chunk: ChunkRange) -> Result<u64, String> {
+    let blob = file
+        .slice_with_f64_and_f64(chunk.start as f64, chunk.end as f64)
+        .map_err(|_| "Failed to slice file")?;
+
+    let opts = RequestInit::new();
+    opts.set_method("PUT");
+    opts.set_mode(web_sys::RequestMode::Cors);
+    opts.set_body(&blob.into());
+
+    let headers = Headers::new().map_err(|_| "Failed to create headers")?;
+    headers
+        .set("Content-Range", &chunk.content_range())
+        .map_err(|_| "Failed to set Content-Range")?;
+    headers
+        .set("X-Upload-Id", upload_id)
+        .map_err(|_| "Failed to set upload id")?;
+    if let Some(token) = crate::auth::JwtAuthProvider::get_stored_token() {
+        headers.set("Authorization", &format!("Bearer {}", token))
+            .map_err(|_| "Failed to set auth header")?;
+    }
+    opts.set_headers(&headers.into());
+
+    let request = Request::new_with_str_and_init(url, &opts)
+        .map_err(|_| "Failed to create request")?;
+    let window = web_sys::window().ok_or("No window found")?;
+    let resp = JsFuture::from(window.fetch_with_request(&request)).await
+        .map_err(|_| "Chunk request failed")?;
+    let response: Response = resp.dyn_into()
+        .map_err(|_| "Failed to get response")?;
+
+    // 308 Resume Incomplete (or 2xx) may say how much the server already has
+    if response.ok() || response.status() == 308 {
+        let received = response
+            .headers()
+            .get("Range")
+            .ok()
+            .flatten()
+            .and_then(|range| parse_received_range(&range));
+        Ok(received.unwrap_or(chunk.end))
+    } else {
+        Err(format!("Chunk upload failed with status: {}", response.status()))
+    }
+}
+
+fn upload_form_data(file: &File) -> Result<FormData, String> {
+    let form_data = FormData::new().map_err(|_| "Failed to create form data")?;
+    form_data.append_with_blob("file", file).map_err(|_| "Failed to append file")?;
+
+    // Add file metadata
+    form_data.append_with_str("filename", &file.name()).map_err(|_| "Failed to append filename")?;
+    form_data.append_with_str("size", &file.size().to_string()).map_err(|_| "Failed to append size")?;
+    form_data.append_with_str("type", &file.type_()).map_err(|_| "Failed to append type")?;
+    Ok(form_data)
+}
+
+/// POST `file` with an `XMLHttpRequest`, reporting its upload progress events to `handle`
+async fn send_with_progress(url: &str, file: &File, handle: &UploadHandle) -> Result<String, String> {
+    let form_data = upload_form_data(file)?;
+    let xhr = XmlHttpRequest::new().map_err(|_| "Failed to create request")?;
+    xhr.open_with_async("POST", url, true)
+        .map_err(|_| "Failed to open request")?;
+    if let Some(token) = crate::auth::JwtAuthProvider::get_stored_token() {
+        xhr.set_request_header("Authorization", &format!("Bearer {}", token))
+            .map_err(|_| "Failed to set auth header")?;
+    }
+
+    let on_progress = {
+        let handle = handle.clone();
+        Closure::<dyn FnMut(web_sys::ProgressEvent)>::new(move |event: web_sys::ProgressEvent| {
+            if event.length_computable() {
+                handle.report_progress(event.loaded() as u64, event.total() as u64);
+            }
+        })
+    };
+    let upload = xhr.upload().map_err(|_| "Upload progress unavailable")?;
+    upload.set_onprogress(Some(on_progress.as_ref().unchecked_ref()));
+
+    // `loadend` fires after success, error and abort alike
+    let done = js_sys::Promise::new(&mut |resolve, _| xhr.set_onloadend(Some(&resolve)));
+    handle.start(Some(xhr.clone()));
+    xhr.send_with_opt_form_data(Some(&form_data))
+        .map_err(|_| "Upload request failed")?;
+    let _ = JsFuture::from(done).await;
+    upload.set_onprogress(None);
+
+    if handle.status() == UploadStatus::Cancelled {
+        return Err("Upload cancelled".to_string());
+    }
+    match xhr.status().unwrap_or(0) {
+        0 => Err("Upload request failed".to_string()),
+        200..=299 => {
+            let body = xhr.response_text().ok().flatten().unwrap_or_default();
+            Ok(if body.is_empty() {
+                format!("upload-{}", js_sys::Date::now() as u64)
+            } else {
+                body
+            })
+        }
+        status => Err(format!("Upload failed with status: {}", status)),
+    }
+}
+
+/// Upload `file` in ranged chunks, reporting each acknowledged chunk to `handle`
+async fn send_chunked(url: &str, file: &File, chunk_size: u64, handle: &UploadHandle) -> Result<String, String> {
+    let upload_id = format!("upload-{}", (js_sys::Math::random() * 1e16) as u64);
+    let mut upload = ChunkedUpload::new(upload_id.clone(), file.size() as u64, chunk_size)
+        .with_cancel_flag(handle.cancel_flag());
+    handle.start(None);
+    upload
+        .run(
+            |chunk| send_chunk(url, file, &upload_id, chunk),
+            |uploaded, total| handle.report_progress(uploaded, total),
+        )
+        .await?;
+    Ok(upload_id)
+}
+
+/// Stable id for a selected file, used in progress callbacks and for resuming
+pub fn file_id(file: &File) -> String {
+    format!("{}-{}-{}", file.name(), file.size() as u64, file.last_modified() as u64)
+}
+
+struct UploadState {
+    file_id: String,
+    file_name: String,
+    progress: Cell<f32>,
+    status: RefCell<UploadStatus>,
+    upload_id: RefCell<Option<String>>,
+    request: RefCell<Option<XmlHttpRequest>>,
+    cancelled: Rc<Cell<bool>>,
+    listeners: RefCell<Vec<ProgressListener>>,
+}
+
+/// Live view of one file's upload, returned by `handle_file_select`
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct UploadHandle {
+    state: Rc<UploadState>,
+}
+
+#[wasm_bindgen]
+impl UploadHandle {
+    pub fn file_id(&self) -> String {
+        self.state.file_id.clone()
+    }
+
+    pub fn file_name(&self) -> String {
+        self.state.file_name.clone()
+    }
+
+    /// Fraction uploaded, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        self.state.progress.get()
+    }
+
+    /// Server's id for the upload once it completes
+    pub fn upload_id(&self) -> Option<String> {
+        self.state.upload_id.borrow().clone()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.status.borrow().is_finished()
+    }
+
+    /// Abort the request and mark the upload `Cancelled`; no-op once finished
+    pub fn cancel(&self) {
+        if self.is_finished() {
+            return;
+        }
+        *self.state.status.borrow_mut() = UploadStatus::Cancelled;
+        self.state.cancelled.set(true);
+        if let Some(request) = self.state.request.borrow_mut().take() {
+            let _ = request.abort();
+        }
+    }
+}
+
+impl UploadHandle {
+    pub(crate) fn new(file_id: impl Into<String>, file_name: impl Into<String>) -> Self {
+        Self {
+            state: Rc::new(UploadState {
+                file_id: file_id.into(),
+                file_name: file_name.into(),
+                progress: Cell::new(0.0),
+                status: RefCell::new(UploadStatus::Pending),
+                upload_id: RefCell::new(None),
+                request: RefCell::new(None),
+                cancelled: Rc::new(Cell::new(false)),
+                listeners: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn status(&self) -> UploadStatus {
+        self.state.status.borrow().clone()
+    }
+
+    /// Called with the new fraction on each progress event
+    pub fn on_progress(&self, callback: impl Fn(f32) + 'static) {
+        self.state.listeners.borrow_mut().push(Rc::new(callback));
+    }
+
+    /// Mark the upload as started, keeping `request` so `cancel` can abort it
+    pub(crate) fn start(&self, request: Option<XmlHttpRequest>) {
+        if self.is_finished() {
+            return;
+        }
+        *self.state.status.borrow_mut() = UploadStatus::Uploading;
+        *self.state.request.borrow_mut() = request;
+    }
+
+    /// Record `loaded` of `total` bytes sent; ignored once finished
+    pub(crate) fn report_progress(&self, loaded: u64, total: u64) {
+        if self.is_finished() || total == 0 {
+            return;
+        }
+        // Only a successful response completes the upload
+        self.set_progress((loaded as f64 / total as f64).min(0.999) as f32);
+    }
+
+    /// Settle the upload; a cancelled upload stays cancelled
+    pub(crate) fn finish(&self, result: Result<String, String>) {
+        if self.is_finished() {
+            return;
+        }
+        self.state.request.borrow_mut().take();
+        match result {
+            Ok(upload_id) => {
+                *self.state.upload_id.borrow_mut() = Some(upload_id);
+                *self.state.status.borrow_mut() = UploadStatus::Complete;
+                self.set_progress(1.0);
+            }
+            Err(e) => *self.state.status.borrow_mut() = UploadStatus::Failed(e),
+        }
+    }
+
+    pub(crate) fn cancel_flag(&self) -> Rc<Cell<bool>> {
+        self.state.cancelled.clone()
+    }
+
+    fn set_progress(&self, progress: f32) {
+        self.state.progress.set(progress);
+        let listeners = self.state.listeners.borrow().clone();
+        for listener in listeners {
+            listener(progress);
+        }
+    }
 }
 
 pub struct FileUploadManager {
     pub(crate) uploads: Vec<FileUpload>,
     pub(crate) max_file_size: u64,
     pub(crate) allowed_types: Vec<String>,
+    pub(crate) chunk_size: Option<u64>,
+    pub(crate) on_progress: Option<ProgressFn>,
+    /// Per file id: upload id and bytes acknowledged so far
+    pub(crate) resumable: HashMap<String, (String, u64)>,
+    pub(crate) cancel_flags: HashMap<String, Rc<Cell<bool>>>,
 }
 
 impl Default for FileUploadManager {
@@ -43,6 +431,10 @@ impl FileUploadManager {
                 "image/gif".to_string(),
                 "application/pdf".to_string(),
             ],
+            chunk_size: None,
+            on_progress: None,
+            resumable: HashMap::new(),
+            cancel_flags: HashMap::new(),
         }
     }
     
@@ -55,6 +447,24 @@ impl FileUploadManager {
         self.allowed_types = types;
         self
     }
+
+    /// Upload files in ranged requests of `bytes` each instead of one POST
+    pub fn with_chunk_size(mut self, bytes: u64) -> Self {
+        self.chunk_size = Some(bytes.max(1));
+        self
+    }
+
+    pub fn on_progress(mut self, callback: impl Fn(&str, u64, u64) + 'static) -> Self {
+        self.on_progress = Some(Rc::new(callback));
+        self
+    }
+
+    /// Stop a chunked upload after the chunk in flight
+    pub fn cancel(&mut self, file_id: &str) {
+        if let Some(flag) = self.cancel_flags.get(file_id) {
+            flag.set(true);
+        }
+    }
     
     pub fn validate_file(&self, file: &File) -> Result<(), String> {
         // Check file size
@@ -73,75 +483,96 @@ impl FileUploadManager {
     }
     
     pub async fn upload_file(&mut self, file: File, url: &str) -> Result<String, String> {
-        // Validate file first
-        self.validate_file(&file)?;
-        
-        // Create form data
-        let form_data = FormData::new().map_err(|_| "Failed to create form data")?;
-        form_data.append_with_blob("file", &file).map_err(|_| "Failed to append file")?;
-        
-        // Add file metadata
-        form_data.append_with_str("filename", &file.name()).map_err(|_| "Failed to append filename")?;
-        form_data.append_with_str("size", &file.size().to_string()).map_err(|_| "Failed to append size")?;
-        form_data.append_with_str("type", &file.type_()).map_err(|_| "Failed to append type")?;
-        
-        // Create upload tracking
-        let mut upload = FileUpload {
-            file: file.clone(),
-            progress: 0.0,
-            status: UploadStatus::Uploading,
-        };
-        
-        // Create request
-        let opts = RequestInit::new();
-        opts.set_method("POST");
-        opts.set_mode(web_sys::RequestMode::Cors);
-        opts.set_body(&form_data.into());
-        
-        // Add auth header if we have a token
-        let headers = Headers::new().map_err(|_| "Failed to create headers")?;
-        if let Some(token) = crate::auth::JwtAuthProvider::get_stored_token() {
-            headers.set("Authorization", &format!("Bearer {}", token))
-                .map_err(|_| "Failed to set auth header")?;
+        if let Some(chunk_size) = self.chunk_size {
+            return self.upload_file_chunked(file, url, chunk_size).await;
         }
-        opts.set_headers(&headers.into());
-        
-        let request = Request::new_with_str_and_init(url, &opts)
-            .map_err(|_| "Failed to create request")?;
-        
-        let window = web_sys::window().ok_or("No window found")?;
-        
-        // Perform the upload
-        let promise = window.fetch_with_request(&request);
-        let resp = JsFuture::from(promise).await
-            .map_err(|_| "Upload request failed")?;
-        
-        let response: Response = resp.dyn_into()
-            .map_err(|_| "Failed to get response")?;
-        
-        if response.ok() {
-            upload.status = UploadStatus::Complete;
-            upload.progress = 100.0;
-            
-            // Get response body for upload ID
-            let body_promise = response.text()
-                .map_err(|_| "Failed to get response body")?;
-            let body = JsFuture::from(body_promise).await
-                .map_err(|_| "Failed to read response body")?;
-            
-            let upload_id = body.as_string().unwrap_or_else(|| {
-                format!("upload-{}", js_sys::Date::now() as u64)
+
+        self.validate_file(&file)?;
+
+        let handle = self.track(&file);
+        let result = send_with_progress(url, &file, &handle).await;
+        handle.finish(result.clone());
+
+        self.uploads.push(FileUpload {
+            file,
+            progress: handle.progress() as f64 * 100.0,
+            status: handle.status(),
+        });
+        result
+    }
+
+    /// New handle for `file`, wired to the manager's progress callback
+    pub(crate) fn track(&self, file: &File) -> UploadHandle {
+        let handle = UploadHandle::new(file_id(file), file.name());
+        if let Some(callback) = self.on_progress.clone() {
+            let (id, total) = (handle.file_id(), file.size() as u64);
+            handle.on_progress(move |progress| {
+                callback(&id, (progress as f64 * total as f64).round() as u64, total)
             });
-            
-            self.uploads.push(upload);
-            Ok(upload_id)
+        }
+        handle
+    }
+
+    /// Upload `file` in chunks, resuming where a previous attempt stopped
+    pub async fn upload_file_chunked(&mut self, file: File, url: &str, chunk_size: u64) -> Result<String, String> {
+        self.validate_file(&file)?;
+
+        let id = file_id(&file);
+        let (upload_id, acknowledged) = self
+            .resumable
+            .entry(id.clone())
+            .or_insert_with(|| (format!("upload-{}", (js_sys::Math::random() * 1e16) as u64), 0))
+            .clone();
+        let cancelled = Rc::new(Cell::new(false));
+        self.cancel_flags.insert(id.clone(), cancelled.clone());
+
+        let mut upload = ChunkedUpload::new(upload_id.clone(), file.size() as u64, chunk_size)
+            .resume_from(acknowledged)
+            .with_cancel_flag(cancelled);
+        let on_progress = self.on_progress.clone();
+        let result = upload
+            .run(
+                |chunk| send_chunk(url, &file, &upload_id, chunk),
+                |uploaded, total| {
+                    if let Some(callback) = &on_progress {
+                        callback(&id, uploaded, total);
+                    }
+                },
+            )
+            .await;
+
+        self.cancel_flags.remove(&id);
+        let progress = if upload.total == 0 {
+            100.0
         } else {
-            upload.status = UploadStatus::Failed(format!("HTTP {}", response.status()));
-            self.uploads.push(upload);
-            Err(format!("Upload failed with status: {}", response.status()))
+            upload.acknowledged() as f64 / upload.total as f64 * 100.0
+        };
+        let status = match &result {
+            Ok(()) => {
+                self.resumable.remove(&id);
+                UploadStatus::Complete
+            }
+            Err(e) => {
+                self.resumable.insert(id.clone(), (upload_id.clone(), upload.acknowledged()));
+                UploadStatus::Failed(e.clone())
+            }
+        };
+
+        match self.uploads.iter_mut().find(|u| file_id(&u.file) == id) {
+            Some(existing) => {
+                existing.progress = progress;
+                existing.status = status;
+            }
+            None => self.uploads.push(FileUpload {
+                file,
+                progress,
+                status,
+            }),
         }
+
+        result.map(|()| upload_id)
     }
-    
+
     pub fn get_uploads(&self) -> &Vec<FileUpload> {
         &self.uploads
     }
@@ -184,6 +615,9 @@ impl FileUploadManager {
 pub struct FileUploadComponent {
     pub(crate) manager: FileUploadManager,
     pub(crate) upload_url: String,
+    pub(crate) handles: Vec<UploadHandle>,
+    pub(crate) preview: bool,
+    pub(crate) previews: PreviewUrls,
 }
 
 impl Default for FileUploadComponent {
@@ -199,6 +633,9 @@ impl FileUploadComponent {
         Self {
             manager: FileUploadManager::new(),
             upload_url: "/api/upload".to_string(),
+            handles: Vec::new(),
+            preview: false,
+            previews: PreviewUrls::default(),
         }
     }
     
@@ -216,52 +653,83 @@ impl FileUploadComponent {
         self.manager = self.manager.with_allowed_types(types);
         self
     }
+
+    /// Show a thumbnail beside each selected image, and a file icon for other types
+    pub fn with_preview(mut self, enabled: bool) -> Self {
+        self.preview = enabled;
+        self
+    }
     
-    pub async fn handle_file_select(&mut self, files: web_sys::FileList) -> Result<Vec<String>, String> {
-        let mut file_vec = Vec::new();
-        
-        for i in 0..files.length() {
-            if let Some(file) = files.item(i) {
-                file_vec.push(file);
+    /// Start uploading the selected files, returning a handle per file.
+    ///
+    /// Files that fail validation get a handle that is already `Failed`.
+    pub fn handle_file_select(&mut self, files: web_sys::FileList) -> Vec<UploadHandle> {
+        let mut handles = Vec::new();
+
+        for file in (0..files.length()).filter_map(|i| files.item(i)) {
+            let handle = self.manager.track(&file);
+            if self.preview {
+                self.add_preview(&file);
             }
-        }
-        
-        let results = self.manager.upload_multiple(file_vec, &self.upload_url).await;
-        
-        let mut upload_ids = Vec::new();
-        for result in results {
-            match result {
-                Ok(id) => upload_ids.push(id),
-                Err(e) => return Err(e),
+            match self.manager.validate_file(&file) {
+                Ok(()) => {
+                    let url = self.upload_url.clone();
+                    let chunk_size = self.manager.chunk_size;
+                    let handle = handle.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        // Cancelled before the request went out
+                        if handle.is_finished() {
+                            return;
+                        }
+                        let result = match chunk_size {
+                            Some(chunk_size) => send_chunked(&url, &file, chunk_size, &handle).await,
+                            None => send_with_progress(&url, &file, &handle).await,
+                        };
+                        handle.finish(result);
+                    });
+                }
+                Err(e) => handle.finish(Err(e)),
             }
+            self.handles.push(handle.clone());
+            handles.push(handle);
         }
-        
-        Ok(upload_ids)
+
+        handles
     }
     
     pub fn get_upload_status(&self) -> String {
         let uploads = self.manager.get_uploads();
         let mut status_html = String::from("<div class='upload-status'>");
         
-        for upload in uploads {
-            let status_class = match &upload.status {
+        let items = uploads
+            .iter()
+            .map(|upload| (file_id(&upload.file), upload.file.name(), upload.status.clone(), upload.progress))
+            .chain(self.handles.iter().map(|handle| {
+                (handle.file_id(), handle.file_name(), handle.status(), handle.progress() as f64 * 100.0)
+            }));
+
+        for (id, name, status, progress) in items {
+            let status_class = match &status {
                 UploadStatus::Pending => "pending",
                 UploadStatus::Uploading => "uploading",
                 UploadStatus::Complete => "complete",
                 UploadStatus::Failed(_) => "failed",
+                UploadStatus::Cancelled => "cancelled",
             };
             
-            let status_text = match &upload.status {
+            let status_text = match &status {
                 UploadStatus::Pending => "Pending",
-                UploadStatus::Uploading => &format!("Uploading... {}%", upload.progress as u32),
+                UploadStatus::Uploading => &format!("Uploading... {}%", progress as u32),
                 UploadStatus::Complete => "Complete",
                 UploadStatus::Failed(e) => &format!("Failed: {}", e),
+                UploadStatus::Cancelled => "Cancelled",
             };
             
             status_html.push_str(&format!(
-                r#"<div class='upload-item {}'><span class='file-name'>{}</span><span class='status'>{}</span></div>"#,
+                r#"<div class='upload-item {}'>{}<span class='file-name'>{}</span><span class='status'>{}</span></div>"#,
                 status_class,
-                upload.file.name(),
+                self.preview_html(&id),
+                name,
                 status_text
             ));
         }
@@ -293,9 +761,412 @@ impl FileUploadComponent {
     
     pub fn clear_completed(&mut self) {
         self.manager.clear_completed();
+        self.handles.retain(|h| h.status() != UploadStatus::Complete);
+        self.prune_previews();
     }
     
     pub fn clear_failed(&mut self) {
         self.manager.clear_failed();
+        self.handles.retain(|h| !matches!(h.status(), UploadStatus::Failed(_)));
+        self.prune_previews();
+    }
+
+    /// Cancel `file_id` if it is still uploading and drop it from the list
+    pub fn remove_upload(&mut self, file_id: String) {
+        self.cancel_upload(file_id.clone());
+        self.manager.uploads.retain(|u| self::file_id(&u.file) != file_id);
+        self.handles.retain(|h| h.file_id() != file_id);
+        self.prune_previews();
+    }
+
+    /// Upload in ranged chunks of `bytes`, resuming failed uploads on retry
+    pub fn with_chunk_size(mut self, bytes: u64) -> Self {
+        self.manager = self.manager.with_chunk_size(bytes);
+        self
+    }
+
+    /// Cancel the upload of `file_id`
+    pub fn cancel_upload(&mut self, file_id: String) {
+        self.manager.cancel(&file_id);
+        for handle in self.handles.iter().filter(|h| h.file_id() == file_id) {
+            handle.cancel();
+        }
+    }
+}
+
+impl FileUploadComponent {
+    /// Called with `(file_id, uploaded_bytes, total_bytes)` after each chunk
+    pub fn on_progress(mut self, callback: impl Fn(&str, u64, u64) + 'static) -> Self {
+        self.manager = self.manager.on_progress(callback);
+        self
+    }
+
+    fn add_preview(&mut self, file: &File) {
+        if !file.type_().starts_with("image/") {
+            return;
+        }
+        let id = file_id(file);
+        if self.previews.0.contains_key(&id) {
+            return;
+        }
+        if let Ok(url) = web_sys::Url::create_object_url_with_blob(file) {
+            self.previews.0.insert(id, url);
+        }
+    }
+
+    fn preview_html(&self, id: &str) -> String {
+        if !self.preview {
+            return String::new();
+        }
+        match self.previews.0.get(id) {
+            // Sized inline so a huge photo can't blow out the row
+            Some(url) => format!(
+                r#"<img class='upload-preview' src='{}' alt='' width='48' height='48' style='width:48px;height:48px;object-fit:cover'>"#,
+                url
+            ),
+            None => r#"<span class='upload-preview file-icon' aria-hidden='true'><svg width='48' height='48' viewBox='0 0 24 24' fill='none' stroke='currentColor'><path d='M14 2H6a2 2 0 00-2 2v16a2 2 0 002 2h12a2 2 0 002-2V8zM14 2v6h6'/></svg></span>"#.to_string(),
+        }
+    }
+
+    /// Revoke object URLs of files no longer listed; revoked URLs are forgotten
+    fn prune_previews(&mut self) {
+        let live: Vec<String> = self
+            .manager
+            .uploads
+            .iter()
+            .map(|u| file_id(&u.file))
+            .chain(self.handles.iter().map(|h| h.file_id()))
+            .collect();
+        self.previews.0.retain(|id, url| {
+            let keep = live.contains(id);
+            if !keep {
+                let _ = web_sys::Url::revoke_object_url(url);
+            }
+            keep
+        });
+    }
+}
+
+/// Object URLs for image thumbnails, keyed by file id; revoked when dropped
+#[derive(Default)]
+pub(crate) struct PreviewUrls(pub(crate) HashMap<String, String>);
+
+impl Drop for PreviewUrls {
+    fn drop(&mut self) {
+        for url in self.0.values() {
+            let _ = web_sys::Url::revoke_object_url(url);
+        }
+    }
+}
+
+/// Whether `mime` is accepted; entries may be exact (`image/png`) or
+/// wildcards (`image/*`), and an empty list accepts everything
+pub fn mime_allowed(mime: &str, allowed: &[String]) -> bool {
+    allowed.is_empty()
+        || allowed.iter().any(|pattern| match pattern.strip_suffix("/*") {
+            Some(prefix) => mime
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == prefix),
+            None => pattern == mime,
+        })
+}
+
+type EventHandler = Closure<dyn FnMut(web_sys::Event)>;
+
+/// Drag-and-drop target that also opens the file picker on click
+#[derive(Clone)]
+pub struct DropZone {
+    allowed_types: Vec<String>,
+    multiple: bool,
+    label: String,
+    on_files: Option<Rc<dyn Fn(FileList)>>,
+}
+
+/// Class applied to the zone while files are dragged over it
+pub const DROP_ZONE_ACTIVE_CLASS: &str = "drag-active";
+
+static NEXT_DROP_ZONE_ID: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+impl Default for DropZone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DropZone {
+    pub fn new() -> Self {
+        DropZone {
+            allowed_types: Vec::new(),
+            multiple: true,
+            label: "Click to select files or drag and drop".to_string(),
+            on_files: None,
+        }
+    }
+
+    /// Only pass on files with these MIME types (`image/*` wildcards allowed)
+    pub fn allowed_types(mut self, types: Vec<String>) -> Self {
+        self.allowed_types = types;
+        self
+    }
+
+    pub fn multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Called with the accepted files from a drop or the file picker
+    pub fn on_files(mut self, callback: impl Fn(FileList) + 'static) -> Self {
+        self.on_files = Some(Rc::new(callback));
+        self
+    }
+
+    /// Wire drag/drop and click-to-browse onto an existing element.
+    ///
+    /// Listeners stay attached until the returned handle is dropped.
+    pub fn attach(&self, zone: &web_sys::Element) -> Result<DropZoneListeners, JsValue> {
+        let mut listeners = DropZoneListeners {
+            zone: zone.clone(),
+            input: None,
+            handlers: Vec::new(),
+        };
+
+        let drag_over = {
+            let zone = zone.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                if !carries_files(&event) {
+                    return;
+                }
+                // Without this the browser refuses the drop
+                event.prevent_default();
+                if let Some(transfer) = drag_data(&event) {
+                    transfer.set_drop_effect("copy");
+                }
+                zone.class_list().add_1(DROP_ZONE_ACTIVE_CLASS).ok();
+            })
+        };
+
+        let drag_leave = {
+            let zone = zone.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                let Some(event) = event.dyn_ref::<web_sys::DragEvent>() else {
+                    return;
+                };
+                // Moving onto a child element also fires dragleave on the zone
+                let related = event.related_target().and_then(|t| t.dyn_into::<web_sys::Node>().ok());
+                if related.is_some_and(|node| zone.contains(Some(&node))) {
+                    return;
+                }
+                zone.class_list().remove_1(DROP_ZONE_ACTIVE_CLASS).ok();
+            })
+        };
+
+        let on_drop = {
+            let zone = zone.clone();
+            let allowed = self.allowed_types.clone();
+            let on_files = self.on_files.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                if !carries_files(&event) {
+                    return;
+                }
+                // Stop the browser from navigating to the dropped file
+                event.prevent_default();
+                zone.class_list().remove_1(DROP_ZONE_ACTIVE_CLASS).ok();
+
+                let files = drag_data(&event).and_then(|transfer| transfer.files());
+                if let (Some(files), Some(on_files)) = (files, &on_files) {
+                    if let Some(accepted) = filter_files(&files, &allowed) {
+                        on_files(accepted);
+                    }
+                }
+            })
+        };
+
+        listeners.listen("dragenter", &drag_over)?;
+        listeners.listen("dragover", &drag_over)?;
+        listeners.handlers.push(("dragover", drag_over));
+        listeners.listen("dragleave", &drag_leave)?;
+        listeners.handlers.push(("dragleave", drag_leave));
+        listeners.listen("drop", &on_drop)?;
+        listeners.handlers.push(("drop", on_drop));
+
+        let input = zone
+            .query_selector("input[type=file]")?
+            .and_then(|input| input.dyn_into::<web_sys::HtmlInputElement>().ok());
+        if let Some(input) = input {
+            let open_picker = {
+                let input = input.clone();
+                Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                    // The input's own click bubbles up here; don't re-open
+                    let from_input = event
+                        .target()
+                        .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+                        .is_some_and(|target| target == input);
+                    if !from_input {
+                        input.click();
+                    }
+                })
+            };
+            listeners.listen("click", &open_picker)?;
+            listeners.handlers.push(("click", open_picker));
+
+            let picked = {
+                let input = input.clone();
+                let allowed = self.allowed_types.clone();
+                let on_files = self.on_files.clone();
+                Closure::<dyn FnMut(web_sys::Event)>::new(move |_event: web_sys::Event| {
+                    if let (Some(files), Some(on_files)) = (input.files(), &on_files) {
+                        if let Some(accepted) = filter_files(&files, &allowed) {
+                            on_files(accepted);
+                        }
+                    }
+                    // Let the same file be picked again
+                    input.set_value("");
+                })
+            };
+            input.add_event_listener_with_callback("change", picked.as_ref().unchecked_ref())?;
+            listeners.input = Some((input, picked));
+        }
+
+        Ok(listeners)
+    }
+}
+
+/// Event listeners installed by `DropZone::attach`; removed on drop
+pub struct DropZoneListeners {
+    zone: web_sys::Element,
+    input: Option<(web_sys::HtmlInputElement, EventHandler)>,
+    handlers: Vec<(&'static str, EventHandler)>,
+}
+
+impl DropZoneListeners {
+    fn listen(&self, event: &str, handler: &EventHandler) -> Result<(), JsValue> {
+        self.zone
+            .add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())
+    }
+}
+
+impl Drop for DropZoneListeners {
+    fn drop(&mut self) {
+        for (event, handler) in &self.handlers {
+            let handler = handler.as_ref().unchecked_ref();
+            self.zone.remove_event_listener_with_callback(event, handler).ok();
+            // dragenter shares the dragover handler
+            if *event == "dragover" {
+                self.zone.remove_event_listener_with_callback("dragenter", handler).ok();
+            }
+        }
+        if let Some((input, handler)) = &self.input {
+            input
+                .remove_event_listener_with_callback("change", handler.as_ref().unchecked_ref())
+                .ok();
+        }
+    }
+}
+
+fn drag_data(event: &web_sys::Event) -> Option<web_sys::DataTransfer> {
+    event.dyn_ref::<web_sys::DragEvent>()?.data_transfer()
+}
+
+/// Text or link drags have no "Files" type and are left to the browser
+fn carries_files(event: &web_sys::Event) -> bool {
+    drag_data(event)
+        .map(|transfer| transfer.types().includes(&JsValue::from_str("Files"), 0))
+        .unwrap_or(false)
+}
+
+/// Allowed files as a new `FileList`; `None` when nothing passes
+fn filter_files(files: &FileList, allowed: &[String]) -> Option<FileList> {
+    let accepted = web_sys::DataTransfer::new().ok()?;
+    for i in 0..files.length() {
+        if let Some(file) = files.item(i) {
+            if mime_allowed(&file.type_(), allowed) {
+                accepted.items().add_with_file(&file).ok()?;
+            }
+        }
+    }
+    accepted.files().filter(|files| files.length() > 0)
+}
+
+impl Component for DropZone {
+    fn render(&self) -> Element {
+        let id = use_widget_id(None, || {
+            format!(
+                "layer9-drop-zone-{}",
+                NEXT_DROP_ZONE_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            )
+        });
+        let zone = self.clone();
+        let zone_id = id.clone();
+        use_effect((), move || {
+            let listeners: Rc<RefCell<Option<DropZoneListeners>>> = Rc::new(RefCell::new(None));
+            let slot = listeners.clone();
+
+            // The element is attached after this render; wire it up on the next tick
+            let wire = Closure::once_into_js(move || {
+                let element = web_sys::window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.get_element_by_id(&zone_id));
+                if let Some(element) = element {
+                    *slot.borrow_mut() = zone.attach(&element).ok();
+                }
+            });
+            if let Some(window) = web_sys::window() {
+                window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(wire.unchecked_ref(), 0)
+                    .ok();
+            }
+
+            move || {
+                listeners.borrow_mut().take();
+            }
+        });
+
+        let mut input_attrs = vec![
+            ("type".to_string(), "file".to_string()),
+            ("style".to_string(), "display: none".to_string()),
+        ];
+        if self.multiple {
+            input_attrs.push(("multiple".to_string(), "true".to_string()));
+        }
+        if !self.allowed_types.is_empty() {
+            input_attrs.push(("accept".to_string(), self.allowed_types.join(",")));
+        }
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(id),
+                class: Some("upload-area drop-zone".to_string()),
+                attributes: vec![
+                    ("role".to_string(), "button".to_string()),
+                    ("tabindex".to_string(), "0".to_string()),
+                ],
+                ..Default::default()
+            }),
+            children: vec![
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props {
+                        class: Some("file-input".to_string()),
+                        attributes: input_attrs,
+                        ..Default::default()
+                    }),
+                    children: vec![],
+                },
+                Element::Node {
+                    tag: "span".to_string(),
+                    props: Box::new(Props {
+                        class: Some("upload-label".to_string()),
+                        ..Default::default()
+                    }),
+                    children: vec![Element::Text(self.label.clone())],
+                },
+            ],
+        }
     }
 }
@@ -7,6 +7,9 @@ mod tests {
     use wasm_bindgen_test::*;
     use web_sys::File;
     use js_sys::{Uint8Array, Array};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::JsValue;
     
     wasm_bindgen_test_configure!(run_in_browser);
 
@@ -327,4 +330,231 @@ mod tests {
         // Only uploading should remain
         assert_eq!(component.manager.uploads[0].status, UploadStatus::Uploading);
     }
-}
\ No newline at end of file
+
+    fn typed_file(name: &str, mime_type: &str) -> File {
+        let parts = Array::new();
+        parts.push(&JsValue::from_str("data"));
+        let bag = web_sys::FilePropertyBag::new();
+        bag.set_type(mime_type);
+        File::new_with_str_sequence_and_options(&parts, name, &bag).unwrap()
+    }
+
+    fn drag_event(kind: &str, transfer: &web_sys::DataTransfer) -> web_sys::DragEvent {
+        let init = web_sys::DragEventInit::new();
+        init.set_bubbles(true);
+        init.set_cancelable(true);
+        init.set_data_transfer(Some(transfer));
+        web_sys::DragEvent::new_with_event_init_dict(kind, &init).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_drop_zone_passes_dropped_files() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let zone = document.create_element("div").unwrap();
+        document.body().unwrap().append_child(&zone).unwrap();
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let listeners = {
+            let received = received.clone();
+            DropZone::new()
+                .allowed_types(vec!["image/*".to_string()])
+                .on_files(move |files| {
+                    for i in 0..files.length() {
+                        received.borrow_mut().push(files.item(i).unwrap().name());
+                    }
+                })
+                .attach(&zone)
+                .unwrap()
+        };
+
+        let transfer = web_sys::DataTransfer::new().unwrap();
+        transfer.items().add_with_file(&typed_file("photo.png", "image/png")).unwrap();
+        transfer.items().add_with_file(&typed_file("notes.txt", "text/plain")).unwrap();
+
+        zone.dispatch_event(&drag_event("dragover", &transfer)).unwrap();
+        assert!(zone.class_list().contains(DROP_ZONE_ACTIVE_CLASS));
+
+        // dispatch_event returns false when the default (opening the file) was prevented
+        assert!(!zone.dispatch_event(&drag_event("drop", &transfer)).unwrap());
+        assert_eq!(*received.borrow(), vec!["photo.png".to_string()]);
+        assert!(!zone.class_list().contains(DROP_ZONE_ACTIVE_CLASS));
+
+        // Dragging text instead of files is left alone
+        let text = web_sys::DataTransfer::new().unwrap();
+        text.set_data("text/plain", "hello").unwrap();
+        assert!(zone.dispatch_event(&drag_event("drop", &text)).unwrap());
+        assert_eq!(received.borrow().len(), 1);
+
+        drop(listeners);
+        zone.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_selected_image_gets_object_url_preview() {
+        let mut component = FileUploadComponent::new()
+            .with_preview(true)
+            .with_allowed_types(vec!["image/png".to_string(), "text/plain".to_string()]);
+
+        let transfer = web_sys::DataTransfer::new().unwrap();
+        transfer.items().add_with_file(&typed_file("photo.png", "image/png")).unwrap();
+        transfer.items().add_with_file(&typed_file("notes.txt", "text/plain")).unwrap();
+        let handles = component.handle_file_select(transfer.files().unwrap());
+        // Keep the test off the network
+        handles.iter().for_each(|h| h.cancel());
+
+        let document = web_sys::window().unwrap().document().unwrap();
+        let container = document.create_element("div").unwrap();
+        container.set_inner_html(&component.get_upload_status());
+
+        let preview = container.query_selector("img.upload-preview").unwrap().unwrap();
+        assert!(preview.get_attribute("src").unwrap().starts_with("blob:"));
+        assert!(container.query_selector(".upload-preview.file-icon").unwrap().is_some());
+
+        // Removing the image revokes and forgets its URL
+        component.remove_upload(handles[0].file_id());
+        assert!(component.previews.0.is_empty());
+        container.set_inner_html(&component.get_upload_status());
+        assert!(container.query_selector("img.upload-preview").unwrap().is_none());
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod native_tests {
+    use super::super::upload::*;
+    use futures::executor::block_on;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_10mb_buffer_splits_into_1mb_ranges() {
+        let buffer = vec![7u8; (10 * MB) as usize];
+        let sent = RefCell::new(Vec::new());
+        let progress = RefCell::new(Vec::new());
+
+        let mut upload = ChunkedUpload::new("upload-1", buffer.len() as u64, MB);
+        block_on(upload.run(
+            |chunk| {
+                let body = &buffer[chunk.start as usize..chunk.end as usize];
+                sent.borrow_mut().push((chunk.content_range(), body.len() as u64));
+                async move { Ok(chunk.end) }
+            },
+            |uploaded, total| progress.borrow_mut().push((uploaded, total)),
+        ))
+        .unwrap();
+
+        let sent = sent.into_inner();
+        assert_eq!(sent.len(), 10);
+        assert_eq!(sent[0], ("bytes 0-1048575/10485760".to_string(), MB));
+        assert_eq!(sent[1].0, "bytes 1048576-2097151/10485760");
+        assert_eq!(sent[9].0, "bytes 9437184-10485759/10485760");
+        assert!(sent.iter().all(|(_, len)| *len == MB));
+        assert_eq!(progress.into_inner().last(), Some(&(10 * MB, 10 * MB)));
+        assert!(upload.is_complete());
+    }
+
+    #[test]
+    fn test_skips_chunks_the_server_already_has() {
+        let starts = RefCell::new(Vec::new());
+        let mut upload = ChunkedUpload::new("upload-2", 5 * MB, MB);
+
+        // The server already holds the first three chunks from an earlier attempt
+        block_on(upload.run(
+            |chunk| {
+                starts.borrow_mut().push(chunk.start);
+                let received = if chunk.start == 0 { 3 * MB } else { chunk.end };
+                async move { Ok(received) }
+            },
+            |_, _| {},
+        ))
+        .unwrap();
+
+        assert_eq!(starts.into_inner(), vec![0, 3 * MB, 4 * MB]);
+    }
+
+    #[test]
+    fn test_cancel_stops_and_retry_resumes() {
+        let flag = Rc::new(Cell::new(false));
+        let mut upload = ChunkedUpload::new("upload-3", 4 * MB, MB).with_cancel_flag(flag.clone());
+        let result = block_on(upload.run(
+            |chunk| {
+                // Cancel while the second chunk is in flight
+                if chunk.start == MB {
+                    flag.set(true);
+                }
+                async move { Ok(chunk.end) }
+            },
+            |_, _| {},
+        ));
+        assert_eq!(result, Err("Upload cancelled".to_string()));
+        assert_eq!(upload.acknowledged(), 2 * MB);
+
+        let starts = RefCell::new(Vec::new());
+        let mut retry = ChunkedUpload::new("upload-3", 4 * MB, MB).resume_from(upload.acknowledged());
+        block_on(retry.run(
+            |chunk| {
+                starts.borrow_mut().push(chunk.start);
+                async move { Ok(chunk.end) }
+            },
+            |_, _| {},
+        ))
+        .unwrap();
+        assert_eq!(starts.into_inner(), vec![2 * MB, 3 * MB]);
+    }
+
+    #[test]
+    fn test_mime_allowed_wildcards() {
+        let allowed = vec!["image/*".to_string(), "application/pdf".to_string()];
+        assert!(mime_allowed("image/png", &allowed));
+        assert!(mime_allowed("application/pdf", &allowed));
+        assert!(!mime_allowed("text/plain", &allowed));
+        assert!(!mime_allowed("imagex/png", &allowed));
+        assert!(mime_allowed("text/plain", &[]));
+    }
+
+    fn recording_handle() -> (UploadHandle, Rc<RefCell<Vec<f32>>>) {
+        let handle = UploadHandle::new("photo.png-100-0", "photo.png");
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        handle.on_progress(move |progress| recorded.borrow_mut().push(progress));
+        (handle, events)
+    }
+
+    #[test]
+    fn test_cancel_stops_progress_events() {
+        let (handle, events) = recording_handle();
+        handle.start(None);
+        handle.report_progress(50, 100);
+        assert_eq!(handle.status(), UploadStatus::Uploading);
+
+        handle.cancel();
+        assert_eq!(handle.status(), UploadStatus::Cancelled);
+        assert!(handle.cancel_flag().get());
+
+        // Late events from the aborted request are dropped
+        handle.report_progress(80, 100);
+        handle.finish(Err("Upload request failed".to_string()));
+        assert_eq!(*events.borrow(), vec![0.5]);
+        assert_eq!(handle.progress(), 0.5);
+        assert_eq!(handle.status(), UploadStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_completed_upload_reaches_one_and_ignores_cancel() {
+        let (handle, events) = recording_handle();
+        handle.start(None);
+        // Every byte sent is not yet a stored upload
+        handle.report_progress(100, 100);
+        assert!(handle.progress() < 1.0);
+
+        handle.finish(Ok("upload-1".to_string()));
+        assert_eq!(handle.progress(), 1.0);
+        assert_eq!(events.borrow().last(), Some(&1.0));
+
+        handle.cancel();
+        assert_eq!(handle.status(), UploadStatus::Complete);
+        assert_eq!(handle.upload_id(), Some("upload-1".to_string()));
+    }
+}
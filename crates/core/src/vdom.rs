@@ -1,7 +1,10 @@
 //! Virtual DOM - L3
 
-use crate::component::{Element, Props};
-use std::collections::HashMap;
+use crate::component::{
+    flatten_fragments, remove_dom_attribute, set_dom_attribute, Element, Props, CONTROLLED_ATTRIBUTES,
+};
+use crate::transition;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::JsCast;
 use web_sys::{Element as DomElement, Node};
 
@@ -84,8 +87,8 @@ impl VDom {
                 Element::Node { tag: old_tag, props: old_props, children: old_children },
                 Element::Node { tag: new_tag, props: new_props, children: new_children }
             ) => {
-                if old_tag != new_tag {
-                    // Different tags - replace entire element
+                if old_tag != new_tag || old_props.key != new_props.key {
+                    // Different tags or items - replace entire element
                     patches.push(Patch::Replace {
                         path: path.to_vec(),
                         element: new.clone(),
@@ -104,6 +107,36 @@ impl VDom {
                     element: new.clone(),
                 });
             }
+
+            // Portals into the same target - diff children inside the host
+            (
+                Element::Portal { target: old_target, children: old_children },
+                Element::Portal { target: new_target, children: new_children },
+            ) if old_target == new_target => {
+                let host_patches = self.diff_children(old_children, new_children, &[]);
+                if !host_patches.is_empty() {
+                    patches.push(Patch::Portal {
+                        path: path.to_vec(),
+                        patches: host_patches,
+                    });
+                }
+            }
+
+            // Among children fragments are flattened away; anywhere else
+            // the whole node is rebuilt
+            (Element::Fragment(_), _) | (_, Element::Fragment(_)) => {
+                patches.push(Patch::Replace {
+                    path: path.to_vec(),
+                    element: new.clone(),
+                });
+            }
+
+            (Element::Portal { .. }, _) | (_, Element::Portal { .. }) => {
+                patches.push(Patch::Replace {
+                    path: path.to_vec(),
+                    element: new.clone(),
+                });
+            }
         }
 
         patches
@@ -167,9 +200,10 @@ impl VDom {
         let old_attrs: HashMap<_, _> = old_props.attributes.iter().cloned().collect();
         let new_attrs: HashMap<_, _> = new_props.attributes.iter().cloned().collect();
 
-        // Check for added or changed attributes
+        // Check for added or changed attributes. Controlled ones are set
+        // again regardless, in case the user changed the DOM property.
         for (key, value) in &new_attrs {
-            if old_attrs.get(key) != Some(value) {
+            if old_attrs.get(key) != Some(value) || CONTROLLED_ATTRIBUTES.contains(&key.as_str()) {
                 patches.push(Patch::SetAttribute {
                     path: path.to_vec(),
                     name: key.clone(),
@@ -191,8 +225,15 @@ impl VDom {
         patches
     }
 
-    /// Diff children using a simple algorithm
+    /// Diff children using a simple algorithm: keyed children by key,
+    /// others by index. Fragments are spliced in first, as in the DOM.
     fn diff_children(&self, old_children: &[Element], new_children: &[Element], path: &[usize]) -> Vec<Patch> {
+        let old_children = &flatten_fragments(old_children)[..];
+        let new_children = &flatten_fragments(new_children)[..];
+        if let (Some(old_keys), Some(new_keys)) = (unique_keys(old_children), unique_keys(new_children)) {
+            return self.diff_keyed_children(old_children, new_children, &old_keys, &new_keys, path);
+        }
+
         let mut patches = vec![];
         let old_len = old_children.len();
         let new_len = new_children.len();
@@ -202,7 +243,7 @@ impl VDom {
         for i in 0..min_len {
             let mut child_path = path.to_vec();
             child_path.push(i);
-            patches.extend(self.diff(&old_children[i], &new_children[i], &child_path));
+            patches.extend(self.diff(old_children[i], new_children[i], &child_path));
         }
 
         // Handle added children
@@ -210,7 +251,7 @@ impl VDom {
             patches.push(Patch::InsertChild {
                 path: path.to_vec(),
                 index: i,
-                element: child.clone(),
+                element: (*child).clone(),
             });
         }
 
@@ -225,16 +266,71 @@ impl VDom {
         patches
     }
 
+    /// Diff children that all have unique keys: removed items go, kept
+    /// items move into place and are diffed with their old selves, new
+    /// items are inserted
+    fn diff_keyed_children(
+        &self,
+        old_children: &[&Element],
+        new_children: &[&Element],
+        old_keys: &[&str],
+        new_keys: &[&str],
+        path: &[usize],
+    ) -> Vec<Patch> {
+        let mut patches = vec![];
+        let kept: HashSet<&str> = new_keys.iter().copied().collect();
+        let old_by_key: HashMap<&str, &Element> = old_keys.iter().copied().zip(old_children.iter().copied()).collect();
+
+        // Removals first, from the end so the indexes before stay valid
+        let mut current = old_keys.to_vec();
+        for index in (0..old_keys.len()).rev() {
+            if !kept.contains(old_keys[index]) {
+                patches.push(Patch::RemoveChild {
+                    path: path.to_vec(),
+                    index,
+                });
+                current.remove(index);
+            }
+        }
+
+        // Then left to right, everything before `index` is already in place
+        for (index, (key, child)) in new_keys.iter().zip(new_children).enumerate() {
+            match current.iter().position(|existing| existing == key) {
+                Some(at) => {
+                    if at != index {
+                        patches.push(Patch::MoveChild {
+                            path: path.to_vec(),
+                            from: at,
+                            to: index,
+                        });
+                        let moved = current.remove(at);
+                        current.insert(index, moved);
+                    }
+                    let mut child_path = path.to_vec();
+                    child_path.push(index);
+                    patches.extend(self.diff(old_by_key[key], child, &child_path));
+                }
+                None => {
+                    patches.push(Patch::InsertChild {
+                        path: path.to_vec(),
+                        index,
+                        element: (*child).clone(),
+                    });
+                    current.insert(index, *key);
+                }
+            }
+        }
+
+        patches
+    }
+
     /// Apply patches to the DOM
     pub fn apply_patches(&self, patches: &[Patch], root: &DomElement) {
         for patch in patches {
             match patch {
                 Patch::Replace { path, element } => {
                     if let Some(target) = self.find_node(root, path) {
-                        let new_node = element.to_dom();
-                        if let Some(parent) = target.parent_node() {
-                            parent.replace_child(&new_node, &target).unwrap();
-                        }
+                        transition::replace(&target, &element.to_dom());
                     }
                 }
                 
@@ -247,7 +343,7 @@ impl VDom {
                 Patch::SetAttribute { path, name, value } => {
                     if let Some(target) = self.find_node(root, path) {
                         if let Some(element) = target.dyn_ref::<DomElement>() {
-                            element.set_attribute(name, value).unwrap();
+                            set_dom_attribute(element, name, value);
                         }
                     }
                 }
@@ -255,48 +351,55 @@ impl VDom {
                 Patch::RemoveAttribute { path, name } => {
                     if let Some(target) = self.find_node(root, path) {
                         if let Some(element) = target.dyn_ref::<DomElement>() {
-                            element.remove_attribute(name).unwrap();
+                            remove_dom_attribute(element, name);
                         }
                     }
                 }
                 
                 Patch::InsertChild { path, index, element } => {
                     if let Some(parent) = self.find_node(root, path) {
-                        let new_child = element.to_dom();
-                        let children = parent.child_nodes();
-                        
-                        if *index < children.length() as usize {
-                            let before = children.item(*index as u32).unwrap();
-                            parent.insert_before(&new_child, Some(&before)).unwrap();
-                        } else {
-                            parent.append_child(&new_child).unwrap();
-                        }
+                        let before = transition::child_at(&parent, *index);
+                        transition::insert(&parent, &element.to_dom(), before.as_ref());
                     }
                 }
                 
-                Patch::RemoveChild { path, index } => {
+                Patch::MoveChild { path, from, to } => {
                     if let Some(parent) = self.find_node(root, path) {
-                        let children = parent.child_nodes();
-                        if let Some(child) = children.item(*index as u32) {
-                            parent.remove_child(&child).unwrap();
+                        if let Some(child) = transition::child_at(&parent, *from) {
+                            let before = transition::child_at(&parent, *to);
+                            parent.insert_before(&child, before.as_ref()).unwrap();
                         }
                     }
                 }
+
+                Patch::Portal { path, patches } => {
+                    if let Some(host) = self.find_node(root, path).and_then(|node| crate::portal::host(&node)) {
+                        self.apply_patches(patches, &host);
+                    }
+                }
+
+                Patch::RemoveChild { path, index } => {
+                    if let Some(child) = self
+                        .find_node(root, path)
+                        .and_then(|parent| transition::child_at(&parent, *index))
+                    {
+                        transition::remove(&child);
+                    }
+                }
             }
         }
+
+        // Removed `Head`s take their tags with them
+        crate::head::schedule_sync();
     }
 
-    /// Find a node in the DOM tree by path
+    /// Find a node in the DOM tree by path, not counting nodes that are
+    /// only still there to transition out
     fn find_node(&self, root: &DomElement, path: &[usize]) -> Option<Node> {
         let mut current: Node = root.clone().into();
         
         for &index in path {
-            let children = current.child_nodes();
-            if let Some(child) = children.item(index as u32) {
-                current = child;
-            } else {
-                return None;
-            }
+            current = transition::child_at(&current, index)?;
         }
         
         Some(current)
@@ -331,6 +434,30 @@ pub enum Patch {
         path: Vec<usize>,
         index: usize,
     },
+    /// Move the child at `from` to `to`, counted before the move
+    MoveChild {
+        path: Vec<usize>,
+        from: usize,
+        to: usize,
+    },
+    /// Patches to a portal's children, relative to its host element
+    Portal {
+        path: Vec<usize>,
+        patches: Vec<Patch>,
+    },
+}
+
+/// The children's keys, if every child has one and none repeats
+fn unique_keys<'e>(children: &[&'e Element]) -> Option<Vec<&'e str>> {
+    let keys = children
+        .iter()
+        .map(|child| match *child {
+            Element::Node { props, .. } => props.key.as_deref(),
+            _ => None,
+        })
+        .collect::<Option<Vec<&str>>>()?;
+    let unique: HashSet<&str> = keys.iter().copied().collect();
+    (unique.len() == keys.len()).then_some(keys)
 }
 
 #[cfg(test)]
@@ -367,7 +494,7 @@ mod tests {
         let old = Element::Text("Hello".to_string());
         let new = Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![],
         };
         let patches = vdom.diff(&old, &new, &[]);
@@ -382,18 +509,18 @@ mod tests {
         // Test class change
         let old = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("old".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![],
         };
         let new = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("new".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![],
         };
         let patches = vdom.diff(&old, &new, &[]);
@@ -414,14 +541,14 @@ mod tests {
         // Test adding children
         let old = Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![
                 Element::Text("Child 1".to_string()),
             ],
         };
         let new = Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![
                 Element::Text("Child 1".to_string()),
                 Element::Text("Child 2".to_string()),
@@ -434,7 +561,7 @@ mod tests {
         // Test removing children
         let old = Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![
                 Element::Text("Child 1".to_string()),
                 Element::Text("Child 2".to_string()),
@@ -442,7 +569,7 @@ mod tests {
         };
         let new = Element::Node {
             tag: "div".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![
                 Element::Text("Child 1".to_string()),
             ],
@@ -458,22 +585,22 @@ mod tests {
         
         let old = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("container".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Title".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         id: Some("para1".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Paragraph 1".to_string())],
                 },
             ],
@@ -481,28 +608,28 @@ mod tests {
         
         let new = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("container updated".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("New Title".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         id: Some("para1".to_string()),
                         attributes: vec![("data-test".to_string(), "value".to_string())],
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Paragraph 1".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Paragraph 2".to_string())],
                 },
             ],
@@ -517,4 +644,170 @@ mod tests {
         // 4. New p element
         assert!(patches.len() >= 4);
     }
+
+    #[test]
+    fn test_keyed_children_move_with_their_items() {
+        let vdom = VDom::new();
+        let list = |items: &[(&str, &str)]| Element::Node {
+            tag: "ul".to_string(),
+            props: Box::default(),
+            children: crate::component::list(items, |(key, text)| {
+                let item = Element::Node {
+                    tag: "li".to_string(),
+                    props: Box::default(),
+                    children: vec![Element::Text(text.to_string())],
+                };
+                (key.to_string(), item)
+            }),
+        };
+
+        // Removing the first item removes it, rather than rewriting the rest
+        let patches = vdom.diff(&list(&[("a", "A"), ("b", "B"), ("c", "C")]), &list(&[("b", "B"), ("c", "C")]), &[]);
+        assert!(matches!(patches.as_slice(), [Patch::RemoveChild { index: 0, .. }]));
+
+        // Reordering moves, then patches the moved item where it landed
+        let patches = vdom.diff(&list(&[("a", "A"), ("b", "B"), ("c", "C")]), &list(&[("c", "C!"), ("a", "A"), ("d", "D")]), &[]);
+        assert!(matches!(
+            patches.as_slice(),
+            [
+                Patch::RemoveChild { index: 1, .. },
+                Patch::MoveChild { from: 1, to: 0, .. },
+                Patch::UpdateText { path, text },
+                Patch::InsertChild { index: 2, .. },
+            ] if path == &vec![0, 0] && text == "C!"
+        ));
+
+        // Repeated keys fall back to matching by index
+        let patches = vdom.diff(&list(&[("a", "A"), ("a", "B")]), &list(&[("a", "B"), ("a", "A")]), &[]);
+        assert_eq!(patches.len(), 2);
+        assert!(patches.iter().all(|patch| matches!(patch, Patch::UpdateText { .. })));
+    }
+
+    #[test]
+    fn test_controlled_attributes_are_set_on_every_render() {
+        let vdom = VDom::new();
+        let checkbox = |checked: bool| Element::Node {
+            tag: "input".to_string(),
+            props: Box::new(Props::default().attr("type", "checkbox").checked(checked)),
+            children: vec![],
+        };
+
+        // Unchanged, `checked` is still set in case the user toggled it
+        let patches = vdom.diff(&checkbox(true), &checkbox(true), &[]);
+        assert!(matches!(
+            patches.as_slice(),
+            [Patch::SetAttribute { name, value, .. }] if name == "checked" && value.is_empty()
+        ));
+
+        let patches = vdom.diff(&checkbox(true), &checkbox(false), &[]);
+        assert!(matches!(
+            patches.as_slice(),
+            [Patch::SetAttribute { name, value, .. }] if name == "checked" && value == "false"
+        ));
+    }
+
+    #[test]
+    fn test_fragments_are_spliced_into_children() {
+        let vdom = VDom::new();
+        let row = |middle: Vec<Element>| Element::Node {
+            tag: "div".to_string(),
+            props: Box::default(),
+            children: vec![
+                Element::Text("a".to_string()),
+                Element::Fragment(middle),
+                Element::Text("b".to_string()),
+            ],
+        };
+
+        // An empty fragment holds no index: "b" is the second child
+        let patches = vdom.diff(&row(vec![]), &row(vec![Element::Text("x".to_string())]), &[]);
+        assert!(matches!(
+            patches.as_slice(),
+            [Patch::UpdateText { path, text }, Patch::InsertChild { index: 2, .. }]
+                if path == &vec![1] && text == "x"
+        ));
+
+        let patches = vdom.diff(&row(vec![Element::Text("x".to_string())]), &row(vec![]), &[]);
+        assert!(matches!(
+            patches.as_slice(),
+            [Patch::UpdateText { path, text }, Patch::RemoveChild { index: 2, .. }]
+                if path == &vec![1] && text == "b"
+        ));
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use crate::component::State;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn form(done: &State<bool>, note: &str) -> Element {
+        Element::Node {
+            tag: "form".to_string(),
+            props: Box::default(),
+            children: vec![
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props::default().attr("type", "checkbox").checked(done.get())),
+                    children: vec![],
+                },
+                Element::Node {
+                    tag: "input".to_string(),
+                    props: Box::new(Props::default().attr("value", note)),
+                    children: vec![],
+                },
+            ],
+        }
+    }
+
+    fn property(element: &DomElement, name: &str) -> wasm_bindgen::JsValue {
+        js_sys::Reflect::get(element, &name.into()).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_checked_property_tracks_state() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let root = document.create_element("div").unwrap();
+        document.body().unwrap().append_child(&root).unwrap();
+
+        let vdom = VDom::new();
+        let done = State::new(true);
+        let mut rendered = form(&done, "Milk");
+        root.append_child(&rendered.to_dom()).unwrap();
+        let inputs = root.query_selector_all("input").unwrap();
+        let checkbox: DomElement = inputs.item(0).unwrap().dyn_into().unwrap();
+        let note: DomElement = inputs.item(1).unwrap().dyn_into().unwrap();
+        assert_eq!(property(&checkbox, "checked"), true);
+
+        let render = |rendered: &mut Element, next: Element| {
+            vdom.apply_patches(&vdom.diff(rendered, &next, &[0]), &root);
+            *rendered = next;
+        };
+
+        // Unchecked by state: the attribute goes and the property follows
+        done.set(false);
+        render(&mut rendered, form(&done, "Milk"));
+        assert_eq!(property(&checkbox, "checked"), false);
+        assert!(!checkbox.has_attribute("checked"));
+
+        // The user ticks it, but the state says unchecked
+        js_sys::Reflect::set(&checkbox, &"checked".into(), &true.into()).unwrap();
+        render(&mut rendered, form(&done, "Milk"));
+        assert_eq!(property(&checkbox, "checked"), false);
+
+        done.set(true);
+        render(&mut rendered, form(&done, "Milk"));
+        assert_eq!(property(&checkbox, "checked"), true);
+
+        // `value` sets what the input shows, not only the attribute
+        js_sys::Reflect::set(&note, &"value".into(), &"Typed".into()).unwrap();
+        render(&mut rendered, form(&done, "Bread"));
+        assert_eq!(property(&note, "value"), "Bread");
+
+        root.remove();
+    }
 }
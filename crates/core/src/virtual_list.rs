@@ -0,0 +1,416 @@
+//! Virtual List - L5
+//!
+//! `VirtualList` shows a long list of fixed-height rows while only keeping
+//! the rows in view, plus a few either side, in the DOM. A spacer as tall
+//! as the whole list keeps the scrollbar honest and each rendered row is
+//! absolutely positioned at its offset.
+//!
+//! ```ignore
+//! VirtualList::new(todos, 48.0, |todo: &Todo| todo_row(todo))
+//!     .height(480.0)
+//!     .overscan(5)
+//! ```
+//!
+//! The window follows the container's scroll position and is recomputed
+//! when the container is resized. Rendered inside a component, the list
+//! keeps its id and scroll position in hook state, so a parent rebuilding
+//! it on every render doesn't reset it.
+
+use crate::component::{Component, Element, Props, State};
+use crate::hooks::use_persisted;
+use crate::reactive_v2::{get_current_component, queue_component_render};
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_VIRTUAL_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// A scrolling list that renders only its visible rows
+pub struct VirtualList<T> {
+    id: Option<String>,
+    items: Rc<Vec<T>>,
+    item_height: f64,
+    height: f64,
+    overscan: usize,
+    render_item: Rc<dyn Fn(&T) -> Element>,
+    viewport: State<(f64, f64)>,
+    /// Whether `viewport` came from the parent through `state`
+    shared_viewport: bool,
+}
+
+impl<T: 'static> VirtualList<T> {
+    /// `item_height` is every row's height in pixels
+    pub fn new(items: Vec<T>, item_height: f64, render_item: impl Fn(&T) -> Element + 'static) -> Self {
+        let height = 400.0;
+        VirtualList {
+            id: None,
+            items: Rc::new(items),
+            item_height: item_height.max(1.0),
+            height,
+            overscan: 3,
+            render_item: Rc::new(render_item),
+            viewport: State::new((0.0, height)),
+            shared_viewport: false,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Height of the scrolling container in pixels
+    pub fn height(mut self, height: f64) -> Self {
+        let (scroll_top, _) = self.viewport.get();
+        self.height = height;
+        if !self.shared_viewport {
+            self.viewport = State::new((scroll_top, height));
+        }
+        self
+    }
+
+    /// Rows rendered beyond each edge of the visible ones, so fast scrolling
+    /// doesn't show blanks
+    pub fn overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    /// Keep the scroll position and container height in the parent, as
+    /// `(scroll_top, height)`
+    pub fn state(mut self, viewport: State<(f64, f64)>) -> Self {
+        self.viewport = viewport;
+        self.shared_viewport = true;
+        self
+    }
+
+    /// Indices of the rows currently rendered
+    pub fn visible_range(&self) -> Range<usize> {
+        let (scroll_top, height) = self.handle().live_viewport();
+        window(self.items.len(), self.item_height, scroll_top, height, self.overscan)
+    }
+
+    /// Move the window, as scrolling or resizing the container does
+    pub fn scroll_to(&self, scroll_top: f64, height: f64) {
+        self.handle().update(scroll_top, height);
+    }
+
+    fn handle(&self) -> ListHandle {
+        ListHandle {
+            id: self.id.clone().unwrap_or_default(),
+            len: self.items.len(),
+            item_height: self.item_height,
+            overscan: self.overscan,
+            viewport: self.viewport.clone(),
+            component: None,
+        }
+    }
+
+    /// The handle for a render: the id and viewport of the first render,
+    /// unless given explicitly, and the component to re-render on scroll
+    fn use_handle(&self) -> ListHandle {
+        let persisted = use_persisted(|| {
+            let id = NEXT_VIRTUAL_LIST_ID.fetch_add(1, Ordering::Relaxed);
+            (format!("layer9-virtual-list-{}", id), self.viewport.clone())
+        });
+        let (id, viewport) = persisted.borrow().clone();
+        ListHandle {
+            id: self.id.clone().unwrap_or(id),
+            viewport: if self.shared_viewport { self.viewport.clone() } else { viewport },
+            component: get_current_component(),
+            ..self.handle()
+        }
+    }
+}
+
+/// Rows overlapping `[scroll_top, scroll_top + height)`, widened by
+/// `overscan`. Scrolling past the end counts as being at the end, so the
+/// last rows are always rendered whole.
+fn window(len: usize, item_height: f64, scroll_top: f64, height: f64, overscan: usize) -> Range<usize> {
+    let total = len as f64 * item_height;
+    let height = height.max(0.0);
+    let scroll_top = scroll_top.clamp(0.0, (total - height).max(0.0));
+
+    let first = (scroll_top / item_height).floor() as usize;
+    let last = ((scroll_top + height) / item_height).ceil() as usize;
+    first.saturating_sub(overscan).min(len)..last.saturating_add(overscan).min(len)
+}
+
+/// What the scroll and resize handlers share
+#[derive(Clone)]
+struct ListHandle {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    id: String,
+    len: usize,
+    item_height: f64,
+    overscan: usize,
+    viewport: State<(f64, f64)>,
+    /// The component rendering the list, re-rendered when the window moves
+    component: Option<u32>,
+}
+
+impl ListHandle {
+    fn range(&self, (scroll_top, height): (f64, f64)) -> Range<usize> {
+        window(self.len, self.item_height, scroll_top, height, self.overscan)
+    }
+
+    /// Re-render only when the rendered rows change, not on every scroll
+    /// event
+    fn update(&self, scroll_top: f64, height: f64) {
+        let current = self.viewport.get();
+        if self.range((scroll_top, height)) != self.range(current) {
+            self.viewport.set((scroll_top, height));
+            if let Some(component) = self.component {
+                queue_component_render(component);
+            }
+        }
+    }
+
+    /// The mounted container's scroll position, which outlasts a list
+    /// rebuilt by a re-render
+    fn live_viewport(&self) -> (f64, f64) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(container) = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(&self.id))
+        {
+            return (container.scroll_top() as f64, container.client_height() as f64);
+        }
+
+        self.viewport.get()
+    }
+
+    /// Watch the container for resizes once it is in the document
+    #[cfg(target_arch = "wasm32")]
+    fn observe_resize(&self) {
+        use wasm_bindgen::closure::Closure;
+        use wasm_bindgen::JsCast;
+
+        const OBSERVED: &str = "data-l9-observed";
+
+        let handle = self.clone();
+        gloo_timers::callback::Timeout::new(0, move || {
+            let Some(container) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&handle.id))
+            else {
+                return;
+            };
+            if container.has_attribute(OBSERVED) {
+                return;
+            }
+
+            let on_resize = {
+                let handle = handle.clone();
+                let container = container.clone();
+                Closure::<dyn FnMut(js_sys::Array)>::new(move |_entries: js_sys::Array| {
+                    handle.update(container.scroll_top() as f64, container.client_height() as f64);
+                })
+            };
+            if let Ok(observer) = web_sys::ResizeObserver::new(on_resize.as_ref().unchecked_ref()) {
+                observer.observe(&container);
+                let _ = container.set_attribute(OBSERVED, "");
+            }
+            on_resize.forget();
+        })
+        .forget();
+    }
+}
+
+impl<T: 'static> Component for VirtualList<T> {
+    fn render(&self) -> Element {
+        let handle = self.use_handle();
+        let range = handle.range(handle.live_viewport());
+
+        #[cfg(target_arch = "wasm32")]
+        handle.observe_resize();
+
+        let rows = range
+            .map(|index| Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    class: Some("virtual-list-item".to_string()),
+                    attributes: vec![
+                        ("role".to_string(), "listitem".to_string()),
+                        ("aria-posinset".to_string(), (index + 1).to_string()),
+                        ("aria-setsize".to_string(), self.items.len().to_string()),
+                        (
+                            "style".to_string(),
+                            format!(
+                                "position: absolute; top: {}px; left: 0; right: 0; height: {}px; overflow: hidden",
+                                index as f64 * self.item_height,
+                                self.item_height
+                            ),
+                        ),
+                    ],
+                    ..Default::default()
+                }),
+                children: vec![(self.render_item)(&self.items[index])],
+            })
+            .collect();
+
+        let on_scroll = {
+            let handle = handle.clone();
+            Rc::new(move |scroll_top: f64, height: f64| handle.update(scroll_top, height))
+                as Rc<dyn Fn(f64, f64)>
+        };
+
+        Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                id: Some(handle.id.clone()),
+                class: Some("virtual-list".to_string()),
+                attributes: vec![(
+                    "style".to_string(),
+                    format!("height: {}px; overflow-y: auto; position: relative", self.height),
+                )],
+                on_scroll: Some(on_scroll),
+                ..Default::default()
+            }),
+            children: vec![Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    class: Some("virtual-list-spacer".to_string()),
+                    attributes: vec![
+                        ("role".to_string(), "list".to_string()),
+                        (
+                            "style".to_string(),
+                            format!(
+                                "position: relative; height: {}px",
+                                self.items.len() as f64 * self.item_height
+                            ),
+                        ),
+                    ],
+                    ..Default::default()
+                }),
+                children: rows,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered_rows(list: &VirtualList<usize>) -> Vec<String> {
+        let Element::Node { children, .. } = list.render() else {
+            panic!("expected list container");
+        };
+        let Element::Node { children: rows, .. } = &children[0] else {
+            panic!("expected spacer");
+        };
+        rows.iter()
+            .map(|row| match row {
+                Element::Node { children, .. } => match &children[0] {
+                    Element::Text(text) => text.clone(),
+                    _ => panic!("expected row text"),
+                },
+                _ => panic!("expected row"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_renders_only_the_visible_window() {
+        let list = VirtualList::new((0..10_000).collect(), 20.0, |n: &usize| {
+            Element::Text(format!("Todo {}", n))
+        })
+        .height(400.0)
+        .overscan(3);
+
+        // 20 rows fill the 400px viewport, plus 3 overscan below
+        let rows = rendered_rows(&list);
+        assert_eq!(rows.len(), 23);
+        assert_eq!(rows[0], "Todo 0");
+        assert!(list.render().to_html().contains("height: 200000px"));
+
+        list.scroll_to(2_000.0, 400.0);
+        assert_eq!(list.visible_range(), 97..123);
+
+        // Growing the container widens the window
+        list.scroll_to(2_000.0, 800.0);
+        assert_eq!(list.visible_range(), 97..143);
+    }
+
+    #[test]
+    fn test_scrolling_to_the_bottom_renders_the_last_rows() {
+        let list = VirtualList::new((0..10_000).collect(), 20.0, |n: &usize| {
+            Element::Text(format!("Todo {}", n))
+        })
+        .height(390.0)
+        .overscan(0);
+
+        // Overshooting the end, as elastic scrolling can, still ends on the last row
+        list.scroll_to(1_000_000.0, 390.0);
+        let rows = rendered_rows(&list);
+        assert_eq!(rows.last().map(String::as_str), Some("Todo 9999"));
+        assert_eq!(list.visible_range(), 9980..10_000);
+
+        assert_eq!(window(0, 20.0, 0.0, 400.0, 3), 0..0);
+    }
+
+    /// Builds a fresh list on every render, as a parent component does
+    #[cfg(not(target_arch = "wasm32"))]
+    struct TodoPage;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl Component for TodoPage {
+        fn render(&self) -> Element {
+            VirtualList::new((0..10_000).collect(), 20.0, |n: &usize| Element::Text(format!("Todo {}", n)))
+                .height(400.0)
+                .overscan(0)
+                .render()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_scroll_position_and_id_survive_parent_rerenders() {
+        let page = TodoPage;
+        let mut tree = crate::testing::render(&page);
+        let (id, on_scroll) = match tree.find(".virtual-list") {
+            Some(Element::Node { props, .. }) => (props.id.clone(), props.on_scroll.clone().unwrap()),
+            _ => panic!("expected list container"),
+        };
+
+        on_scroll(2_000.0, 400.0);
+        tree.rerender();
+        match tree.find(".virtual-list") {
+            Some(Element::Node { props, .. }) => assert_eq!(props.id, id),
+            _ => panic!("expected list container"),
+        }
+        assert!(tree.find_by_text("Todo 0").is_none());
+        assert!(tree.find_by_text("Todo 100").is_some());
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_mounted_list_keeps_few_nodes() {
+        let list = VirtualList::new((0..10_000).collect(), 20.0, |n: &usize| {
+            Element::Text(format!("Todo {}", n))
+        })
+        .id("mounted-virtual-list")
+        .height(400.0);
+
+        let body = web_sys::window().unwrap().document().unwrap().body().unwrap();
+        let node = list.render().to_dom();
+        body.append_child(&node).unwrap();
+
+        let spacer = body
+            .query_selector("#mounted-virtual-list .virtual-list-spacer")
+            .unwrap()
+            .unwrap();
+        assert!(spacer.child_element_count() <= 30);
+
+        body.remove_child(&node).unwrap();
+    }
+}
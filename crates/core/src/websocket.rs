@@ -17,7 +17,7 @@ pub enum WsState {
 }
 
 /// WebSocket message
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum WsMessage {
     Text(String),
     Binary(Vec<u8>),
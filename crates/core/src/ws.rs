@@ -0,0 +1,647 @@
+//! Reactive WebSocket Client - L4/L5
+//!
+//! `WebSocketConnection` owns a socket, reconnects with exponential backoff
+//! after unexpected closes and fans incoming frames out to `on_message`
+//! subscribers. `use_websocket` wraps it in a hook that re-renders the
+//! component on every state change or message and closes the socket when the
+//! component unmounts.
+
+use crate::hooks::{use_effect, use_ref};
+use crate::reactive_v2::{get_current_component, queue_component_render};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::{Rc, Weak};
+
+pub use crate::websocket::WsMessage;
+
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::callback::Timeout;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, JsCast};
+
+type MessageHandler = Rc<dyn Fn(&WsMessage)>;
+type ChangeHandler = Rc<dyn Fn()>;
+
+/// Opens a socket for a URL, reporting its events through `SocketEvents`
+pub type SocketFactory = Rc<dyn Fn(&str, SocketEvents) -> Result<Box<dyn Socket>, WsError>>;
+
+/// Close code for a normal, intentional close
+const NORMAL_CLOSURE: u16 = 1000;
+
+/// Connection state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Open,
+    Closed,
+}
+
+/// WebSocket client errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsError {
+    Connect(String),
+    NotOpen,
+    Send(String),
+}
+
+impl std::fmt::Display for WsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WsError::Connect(message) => write!(f, "WebSocket connect failed: {}", message),
+            WsError::NotOpen => write!(f, "WebSocket is not open"),
+            WsError::Send(message) => write!(f, "WebSocket send failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for WsError {}
+
+/// Reconnection policy for unexpected closes
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    pub enabled: bool,
+    pub initial_delay_ms: u32,
+    pub max_delay_ms: u32,
+    /// `None` retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        ReconnectConfig {
+            enabled: true,
+            initial_delay_ms: 500,
+            max_delay_ms: 30_000,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Delay before reconnect attempt number `attempt` (starting at 0)
+    pub fn delay_for(&self, attempt: u32) -> u32 {
+        self.initial_delay_ms
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay_ms)
+    }
+}
+
+/// Connection options
+#[derive(Clone, Default)]
+pub struct WsOptions {
+    pub reconnect: ReconnectConfig,
+    /// Keep messages that arrive before any `on_message` subscriber and
+    /// deliver them to the first one
+    pub buffer_until_subscribed: bool,
+    /// Defaults to the browser `WebSocket`
+    pub factory: Option<SocketFactory>,
+}
+
+/// A single underlying socket
+pub trait Socket {
+    fn send_text(&self, text: &str) -> Result<(), WsError>;
+    fn send_bytes(&self, bytes: &[u8]) -> Result<(), WsError>;
+    fn close(&self);
+}
+
+/// Event sink handed to a socket when it is created
+#[derive(Clone)]
+pub struct SocketEvents {
+    inner: Weak<Inner>,
+}
+
+impl SocketEvents {
+    pub fn opened(&self) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.attempts.set(0);
+            inner.set_state(ConnectionState::Open);
+        }
+    }
+
+    pub fn message(&self, message: WsMessage) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.deliver(message);
+        }
+    }
+
+    pub fn closed(&self, code: u16) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.handle_close(code);
+        }
+    }
+}
+
+struct Inner {
+    url: String,
+    options: WsOptions,
+    socket: RefCell<Option<Box<dyn Socket>>>,
+    state: Cell<ConnectionState>,
+    last_message: RefCell<Option<WsMessage>>,
+    subscribers: RefCell<Vec<(usize, MessageHandler)>>,
+    next_subscriber: Cell<usize>,
+    buffer: RefCell<VecDeque<WsMessage>>,
+    change_handlers: RefCell<Vec<ChangeHandler>>,
+    attempts: Cell<u32>,
+    closed_by_user: Cell<bool>,
+    #[cfg(target_arch = "wasm32")]
+    reconnect_timer: RefCell<Option<Timeout>>,
+}
+
+impl Inner {
+    fn open_socket(self: &Rc<Self>) -> Result<(), WsError> {
+        self.set_state(ConnectionState::Connecting);
+        let events = SocketEvents {
+            inner: Rc::downgrade(self),
+        };
+        let socket = match &self.options.factory {
+            Some(factory) => factory(&self.url, events),
+            None => browser_socket(&self.url, events),
+        };
+
+        match socket {
+            Ok(socket) => {
+                *self.socket.borrow_mut() = Some(socket);
+                Ok(())
+            }
+            Err(error) => {
+                self.set_state(ConnectionState::Closed);
+                Err(error)
+            }
+        }
+    }
+
+    fn set_state(&self, state: ConnectionState) {
+        if self.state.replace(state) != state {
+            self.notify_change();
+        }
+    }
+
+    fn notify_change(&self) {
+        let handlers: Vec<ChangeHandler> = self.change_handlers.borrow().clone();
+        for handler in handlers {
+            handler();
+        }
+    }
+
+    fn deliver(&self, message: WsMessage) {
+        *self.last_message.borrow_mut() = Some(message.clone());
+
+        let subscribers: Vec<MessageHandler> = self
+            .subscribers
+            .borrow()
+            .iter()
+            .map(|(_, handler)| handler.clone())
+            .collect();
+        if subscribers.is_empty() && self.options.buffer_until_subscribed {
+            self.buffer.borrow_mut().push_back(message);
+        } else {
+            for handler in subscribers {
+                handler(&message);
+            }
+        }
+
+        self.notify_change();
+    }
+
+    // The closed socket stays stored until it is replaced: it is still running
+    // the close callback that got us here
+    fn handle_close(self: &Rc<Self>, code: u16) {
+        self.set_state(ConnectionState::Closed);
+
+        let reconnect = &self.options.reconnect;
+        let attempt = self.attempts.get();
+        let unexpected = code != NORMAL_CLOSURE && !self.closed_by_user.get();
+        if !unexpected || !reconnect.enabled || reconnect.max_attempts.is_some_and(|max| attempt >= max) {
+            return;
+        }
+
+        self.attempts.set(attempt + 1);
+        self.schedule_reconnect(reconnect.delay_for(attempt));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn schedule_reconnect(self: &Rc<Self>, delay_ms: u32) {
+        let inner = Rc::downgrade(self);
+        let timer = Timeout::new(delay_ms, move || {
+            if let Some(inner) = inner.upgrade() {
+                if !inner.closed_by_user.get() {
+                    let _ = inner.open_socket();
+                }
+            }
+        });
+        *self.reconnect_timer.borrow_mut() = Some(timer);
+    }
+
+    // There are no timers outside the browser, so retry straight away
+    #[cfg(not(target_arch = "wasm32"))]
+    fn schedule_reconnect(self: &Rc<Self>, _delay_ms: u32) {
+        let _ = self.open_socket();
+    }
+}
+
+/// Unsubscribes its `on_message` handler when dropped
+pub struct Subscription {
+    inner: Weak<Inner>,
+    id: usize,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.subscribers.borrow_mut().retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+/// Reconnecting WebSocket connection
+#[derive(Clone)]
+pub struct WebSocketConnection {
+    inner: Rc<Inner>,
+}
+
+impl WebSocketConnection {
+    /// Connect with the default options
+    pub fn connect(url: impl Into<String>) -> Result<Self, WsError> {
+        Self::connect_with(url, WsOptions::default())
+    }
+
+    pub fn connect_with(url: impl Into<String>, options: WsOptions) -> Result<Self, WsError> {
+        let inner = Rc::new(Inner {
+            url: url.into(),
+            options,
+            socket: RefCell::new(None),
+            state: Cell::new(ConnectionState::Connecting),
+            last_message: RefCell::new(None),
+            subscribers: RefCell::new(Vec::new()),
+            next_subscriber: Cell::new(0),
+            buffer: RefCell::new(VecDeque::new()),
+            change_handlers: RefCell::new(Vec::new()),
+            attempts: Cell::new(0),
+            closed_by_user: Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            reconnect_timer: RefCell::new(None),
+        });
+
+        inner.open_socket()?;
+        Ok(WebSocketConnection { inner })
+    }
+
+    pub fn send(&self, text: &str) -> Result<(), WsError> {
+        self.with_open_socket(|socket| socket.send_text(text))
+    }
+
+    pub fn send_bytes(&self, bytes: &[u8]) -> Result<(), WsError> {
+        self.with_open_socket(|socket| socket.send_bytes(bytes))
+    }
+
+    fn with_open_socket(&self, f: impl FnOnce(&dyn Socket) -> Result<(), WsError>) -> Result<(), WsError> {
+        if self.state() != ConnectionState::Open {
+            return Err(WsError::NotOpen);
+        }
+        match &*self.inner.socket.borrow() {
+            Some(socket) => f(socket.as_ref()),
+            None => Err(WsError::NotOpen),
+        }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.inner.state.get()
+    }
+
+    pub fn last_message(&self) -> Option<WsMessage> {
+        self.inner.last_message.borrow().clone()
+    }
+
+    /// Receive every incoming frame until the returned `Subscription` is dropped
+    pub fn on_message(&self, handler: impl Fn(&WsMessage) + 'static) -> Subscription {
+        let handler: MessageHandler = Rc::new(handler);
+        let id = self.inner.next_subscriber.get();
+        self.inner.next_subscriber.set(id + 1);
+        self.inner.subscribers.borrow_mut().push((id, handler.clone()));
+
+        let buffered: Vec<WsMessage> = self.inner.buffer.borrow_mut().drain(..).collect();
+        for message in &buffered {
+            handler(message);
+        }
+
+        Subscription {
+            inner: Rc::downgrade(&self.inner),
+            id,
+        }
+    }
+
+    /// Close the socket without reconnecting
+    pub fn close(&self) {
+        self.inner.closed_by_user.set(true);
+        #[cfg(target_arch = "wasm32")]
+        self.inner.reconnect_timer.borrow_mut().take();
+
+        let socket = self.inner.socket.borrow_mut().take();
+        if let Some(socket) = socket {
+            socket.close();
+        }
+        self.inner.set_state(ConnectionState::Closed);
+    }
+
+    fn on_change(&self, handler: impl Fn() + 'static) {
+        self.inner.change_handlers.borrow_mut().push(Rc::new(handler));
+    }
+}
+
+/// WebSocket state for a component, as of the current render
+#[derive(Clone)]
+pub struct WsHandle {
+    pub state: ConnectionState,
+    pub last_message: Option<WsMessage>,
+    connection: Option<WebSocketConnection>,
+}
+
+impl WsHandle {
+    pub fn send(&self, text: &str) -> Result<(), WsError> {
+        self.connection.as_ref().ok_or(WsError::NotOpen)?.send(text)
+    }
+
+    pub fn send_bytes(&self, bytes: &[u8]) -> Result<(), WsError> {
+        self.connection.as_ref().ok_or(WsError::NotOpen)?.send_bytes(bytes)
+    }
+
+    /// Subscribe to incoming frames; `None` if the socket could not be created
+    pub fn on_message(&self, handler: impl Fn(&WsMessage) + 'static) -> Option<Subscription> {
+        self.connection.as_ref().map(|connection| connection.on_message(handler))
+    }
+
+    pub fn close(&self) {
+        if let Some(connection) = &self.connection {
+            connection.close();
+        }
+    }
+
+    pub fn connection(&self) -> Option<&WebSocketConnection> {
+        self.connection.as_ref()
+    }
+}
+
+/// WebSocket hook with the default options
+pub fn use_websocket(url: &str) -> WsHandle {
+    use_websocket_with(url, WsOptions::default())
+}
+
+/// WebSocket hook - connects on first render, re-renders on every state change
+/// or message, and closes the connection when the component unmounts
+pub fn use_websocket_with(url: &str, options: WsOptions) -> WsHandle {
+    let connection = use_ref(None::<Option<WebSocketConnection>>);
+
+    if connection.borrow().is_none() {
+        let created = WebSocketConnection::connect_with(url, options).ok();
+        if let (Some(created), Some(component_id)) = (&created, get_current_component()) {
+            // Only re-render while the hook state (and so the component) is alive
+            let alive = Rc::downgrade(&connection);
+            created.on_change(move || {
+                if alive.strong_count() > 0 {
+                    queue_component_render(component_id);
+                }
+            });
+        }
+        *connection.borrow_mut() = Some(created);
+    }
+
+    let connection = connection.borrow().clone().flatten();
+
+    use_effect((), {
+        let connection = connection.clone();
+        move || {
+            move || {
+                if let Some(connection) = connection {
+                    connection.close();
+                }
+            }
+        }
+    });
+
+    WsHandle {
+        state: connection
+            .as_ref()
+            .map(|c| c.state())
+            .unwrap_or(ConnectionState::Closed),
+        last_message: connection.as_ref().and_then(|c| c.last_message()),
+        connection,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct BrowserSocket {
+    ws: web_sys::WebSocket,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(web_sys::MessageEvent)>,
+    _on_close: Closure<dyn FnMut(web_sys::CloseEvent)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Socket for BrowserSocket {
+    fn send_text(&self, text: &str) -> Result<(), WsError> {
+        self.ws
+            .send_with_str(text)
+            .map_err(|e| WsError::Send(format!("{:?}", e)))
+    }
+
+    fn send_bytes(&self, bytes: &[u8]) -> Result<(), WsError> {
+        self.ws
+            .send_with_u8_array(bytes)
+            .map_err(|e| WsError::Send(format!("{:?}", e)))
+    }
+
+    fn close(&self) {
+        let _ = self.ws.close_with_code(NORMAL_CLOSURE);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for BrowserSocket {
+    fn drop(&mut self) {
+        // Detach handlers so the dropped closures are never called
+        self.ws.set_onopen(None);
+        self.ws.set_onmessage(None);
+        self.ws.set_onclose(None);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn browser_socket(url: &str, events: SocketEvents) -> Result<Box<dyn Socket>, WsError> {
+    let ws = web_sys::WebSocket::new(url).map_err(|e| WsError::Connect(format!("{:?}", e)))?;
+    ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+    let on_open = {
+        let events = events.clone();
+        Closure::<dyn FnMut()>::new(move || events.opened())
+    };
+    let on_message = {
+        let events = events.clone();
+        Closure::<dyn FnMut(_)>::new(move |e: web_sys::MessageEvent| {
+            let data = e.data();
+            if let Some(text) = data.as_string() {
+                events.message(WsMessage::Text(text));
+            } else if let Ok(buffer) = data.dyn_into::<js_sys::ArrayBuffer>() {
+                events.message(WsMessage::Binary(js_sys::Uint8Array::new(&buffer).to_vec()));
+            }
+        })
+    };
+    let on_close = Closure::<dyn FnMut(_)>::new(move |e: web_sys::CloseEvent| events.closed(e.code()));
+
+    ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    Ok(Box::new(BrowserSocket {
+        ws,
+        _on_open: on_open,
+        _on_message: on_message,
+        _on_close: on_close,
+    }))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn browser_socket(_url: &str, _events: SocketEvents) -> Result<Box<dyn Socket>, WsError> {
+    Err(WsError::Connect(
+        "browser WebSocket is only available on wasm32; pass a socket factory".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::reset_hook_index;
+    use crate::reactive_v2::{init_renderer, with_current_component};
+
+    /// Mock socket; the test drives it through the captured `SocketEvents`
+    #[derive(Default)]
+    struct MockServer {
+        events: RefCell<Vec<SocketEvents>>,
+        sent: RefCell<Vec<WsMessage>>,
+    }
+
+    struct MockSocket {
+        server: Rc<MockServer>,
+    }
+
+    impl Socket for MockSocket {
+        fn send_text(&self, text: &str) -> Result<(), WsError> {
+            self.server.sent.borrow_mut().push(WsMessage::Text(text.to_string()));
+            Ok(())
+        }
+
+        fn send_bytes(&self, bytes: &[u8]) -> Result<(), WsError> {
+            self.server.sent.borrow_mut().push(WsMessage::Binary(bytes.to_vec()));
+            Ok(())
+        }
+
+        fn close(&self) {}
+    }
+
+    impl MockServer {
+        fn options(self: &Rc<Self>) -> WsOptions {
+            let server = self.clone();
+            WsOptions {
+                factory: Some(Rc::new(move |_url: &str, events: SocketEvents| {
+                    server.events.borrow_mut().push(events);
+                    Ok(Box::new(MockSocket { server: server.clone() }) as Box<dyn Socket>)
+                })),
+                ..Default::default()
+            }
+        }
+
+        fn current(&self) -> SocketEvents {
+            self.events.borrow().last().cloned().unwrap()
+        }
+    }
+
+    fn render<T>(f: impl FnOnce() -> T) -> T {
+        init_renderer();
+        reset_hook_index();
+        with_current_component(401, f)
+    }
+
+    fn text(message: &Option<WsMessage>) -> Option<String> {
+        match message {
+            Some(WsMessage::Text(text)) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_hook_updates_on_message() {
+        let server = Rc::new(MockServer::default());
+
+        let handle = render(|| use_websocket_with("ws://test/live", server.options()));
+        assert_eq!(handle.state, ConnectionState::Connecting);
+        assert_eq!(handle.send("too early"), Err(WsError::NotOpen));
+
+        server.current().opened();
+        server.current().message(WsMessage::Text("tick".to_string()));
+
+        let handle = render(|| use_websocket_with("ws://test/live", server.options()));
+        assert_eq!(handle.state, ConnectionState::Open);
+        assert_eq!(text(&handle.last_message), Some("tick".to_string()));
+
+        handle.send_bytes(&[1, 2]).unwrap();
+        assert!(matches!(&server.sent.borrow()[0], WsMessage::Binary(bytes) if bytes == &[1, 2]));
+        assert_eq!(server.events.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_buffers_until_subscribed() {
+        let server = Rc::new(MockServer::default());
+        let connection = WebSocketConnection::connect_with(
+            "ws://test/buffered",
+            WsOptions {
+                buffer_until_subscribed: true,
+                ..server.options()
+            },
+        )
+        .unwrap();
+
+        server.current().opened();
+        server.current().message(WsMessage::Text("early".to_string()));
+        server.current().message(WsMessage::Binary(vec![9]));
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = connection.on_message({
+            let received = received.clone();
+            move |message| received.borrow_mut().push(message.clone())
+        });
+
+        let received = received.borrow();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(&received[0], WsMessage::Text(text) if text == "early"));
+        assert!(matches!(&received[1], WsMessage::Binary(bytes) if bytes == &[9]));
+    }
+
+    #[test]
+    fn test_reconnects_only_after_unexpected_close() {
+        let server = Rc::new(MockServer::default());
+        let connection = WebSocketConnection::connect_with("ws://test/reconnect", server.options()).unwrap();
+        server.current().opened();
+
+        server.current().closed(1006);
+        assert_eq!(server.events.borrow().len(), 2);
+        assert_eq!(connection.state(), ConnectionState::Connecting);
+
+        server.current().opened();
+        connection.close();
+        server.current().closed(NORMAL_CLOSURE);
+        assert_eq!(server.events.borrow().len(), 2);
+        assert_eq!(connection.state(), ConnectionState::Closed);
+    }
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let config = ReconnectConfig {
+            initial_delay_ms: 500,
+            max_delay_ms: 4_000,
+            ..Default::default()
+        };
+        assert_eq!(config.delay_for(0), 500);
+        assert_eq!(config.delay_for(2), 2_000);
+        assert_eq!(config.delay_for(10), 4_000);
+        assert_eq!(config.delay_for(40), 4_000);
+    }
+}
@@ -0,0 +1,339 @@
+//! WebSocket Server - L4
+//!
+//! Server side of the `ws` client: a route that upgrades to a WebSocket and
+//! hands each connection to an async handler as a `WsSession`.
+//!
+//! ```ignore
+//! let live = WsEndpoint::new(|mut session| async move {
+//!     let updates = session.sender();
+//!     spawn_stats_feed(updates);
+//!     while let Some(message) = session.recv().await {
+//!         // Client messages, e.g. subscription changes
+//!     }
+//! })
+//! .with_jwt(Jwt::new(secret));
+//!
+//! let app = Router::new().nest("/ws", live.into_router());
+//! ```
+//!
+//! Authenticated endpoints read the token from `Authorization: Bearer ...` or,
+//! since browsers can't set headers on a WebSocket, a `token` query parameter.
+//! Connections without a valid token are refused with 401 before upgrading.
+
+use crate::auth::User;
+use crate::jwt::Jwt;
+use crate::middleware::MiddlewareError;
+use crate::websocket::WsMessage;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+type WsHandler = Arc<dyn Fn(WsSession) -> BoxFuture<'static, ()> + Send + Sync>;
+type VerifyToken = Arc<dyn Fn(&str) -> Option<User> + Send + Sync>;
+
+/// Cloneable handle for pushing messages to one client
+#[derive(Clone)]
+pub struct WsSender(UnboundedSender<WsMessage>);
+
+impl WsSender {
+    /// Queue a message; returns false once the client has disconnected
+    pub fn send(&self, message: WsMessage) -> bool {
+        self.0.unbounded_send(message).is_ok()
+    }
+
+    pub fn send_text(&self, text: impl Into<String>) -> bool {
+        self.send(WsMessage::Text(text.into()))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+/// One accepted connection, as seen by the handler
+pub struct WsSession {
+    user: Option<User>,
+    outgoing: WsSender,
+    incoming: UnboundedReceiver<WsMessage>,
+}
+
+impl WsSession {
+    /// The authenticated user, when the endpoint requires auth
+    pub fn user(&self) -> Option<&User> {
+        self.user.as_ref()
+    }
+
+    pub fn send(&self, message: WsMessage) -> bool {
+        self.outgoing.send(message)
+    }
+
+    pub fn send_text(&self, text: impl Into<String>) -> bool {
+        self.outgoing.send_text(text)
+    }
+
+    /// Handle for pushing from other tasks while this one reads
+    pub fn sender(&self) -> WsSender {
+        self.outgoing.clone()
+    }
+
+    /// Next message from the client; `None` once it disconnects
+    pub async fn recv(&mut self) -> Option<WsMessage> {
+        self.incoming.next().await
+    }
+}
+
+/// Transport side of a session: feeds client messages in, drains replies out
+pub struct WsPeer {
+    pub incoming: UnboundedSender<WsMessage>,
+    pub outgoing: UnboundedReceiver<WsMessage>,
+}
+
+/// A WebSocket route and the handler run for each connection
+#[derive(Clone)]
+pub struct WsEndpoint {
+    handler: WsHandler,
+    verify_token: Option<VerifyToken>,
+}
+
+impl WsEndpoint {
+    pub fn new<F, Fut>(handler: F) -> Self
+    where
+        F: Fn(WsSession) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        WsEndpoint {
+            handler: Arc::new(move |session| Box::pin(handler(session))),
+            verify_token: None,
+        }
+    }
+
+    /// Require a token that `verify_token` accepts, like `AuthMiddleware`
+    pub fn with_auth(mut self, verify_token: impl Fn(&str) -> Option<User> + Send + Sync + 'static) -> Self {
+        self.verify_token = Some(Arc::new(verify_token));
+        self
+    }
+
+    /// Require a valid, unexpired JWT signed with `jwt`'s secret
+    pub fn with_jwt(self, jwt: Jwt) -> Self {
+        self.with_auth(move |token| {
+            jwt.verify_token(token).ok().map(|claims| User {
+                id: claims.sub,
+                username: claims.username,
+                email: claims.email,
+                roles: claims.roles,
+            })
+        })
+    }
+
+    /// Check an upgrade request; `Ok(None)` for endpoints without auth
+    pub fn authorize(
+        &self,
+        headers: &HashMap<String, String>,
+        query: &str,
+    ) -> Result<Option<User>, MiddlewareError> {
+        let Some(verify_token) = &self.verify_token else {
+            return Ok(None);
+        };
+
+        let from_header = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+            .and_then(|(_, value)| value.strip_prefix("Bearer "))
+            .map(str::to_string);
+        let from_query = || {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "token")
+                .and_then(|(_, value)| urlencoding::decode(value).ok())
+                .map(|value| value.into_owned())
+        };
+
+        let user = from_header
+            .or_else(from_query)
+            .and_then(|token| verify_token(&token));
+        match user {
+            Some(user) => Ok(Some(user)),
+            None => Err(MiddlewareError {
+                status: 401,
+                message: "Unauthorized".to_string(),
+            }),
+        }
+    }
+
+    /// Start a session for an accepted connection.
+    ///
+    /// Returns the handler future to drive and the peer to wire to the socket.
+    pub fn open(&self, user: Option<User>) -> (BoxFuture<'static, ()>, WsPeer) {
+        let (incoming_tx, incoming_rx) = mpsc::unbounded();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
+        let session = WsSession {
+            user,
+            outgoing: WsSender(outgoing_tx),
+            incoming: incoming_rx,
+        };
+
+        (
+            (self.handler)(session),
+            WsPeer {
+                incoming: incoming_tx,
+                outgoing: outgoing_rx,
+            },
+        )
+    }
+
+    /// Axum router upgrading `GET /` to a WebSocket; nest it under the socket path
+    #[cfg(feature = "ssr")]
+    pub fn into_router(self) -> axum::Router {
+        use axum::{
+            extract::{
+                ws::{Message, WebSocket, WebSocketUpgrade},
+                RawQuery, State,
+            },
+            http::{HeaderMap, StatusCode},
+            response::{IntoResponse, Response},
+            routing::get,
+        };
+        use futures::SinkExt;
+
+        async fn serve(endpoint: Arc<WsEndpoint>, socket: WebSocket, user: Option<User>) {
+            let (handler, mut peer) = endpoint.open(user);
+            let (mut sink, mut stream) = socket.split();
+
+            tokio::spawn(handler);
+            let writer = tokio::spawn(async move {
+                while let Some(message) = peer.outgoing.next().await {
+                    let message = match message {
+                        WsMessage::Text(text) => Message::Text(text),
+                        WsMessage::Binary(bytes) => Message::Binary(bytes),
+                    };
+                    if sink.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                let _ = sink.close().await;
+            });
+
+            while let Some(Ok(message)) = stream.next().await {
+                let message = match message {
+                    Message::Text(text) => WsMessage::Text(text),
+                    Message::Binary(bytes) => WsMessage::Binary(bytes),
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                if peer.incoming.unbounded_send(message).is_err() {
+                    break;
+                }
+            }
+
+            // Ends the handler's `recv` loop; the writer stops once it drops its sender
+            drop(peer.incoming);
+            let _ = writer.await;
+        }
+
+        async fn upgrade(
+            State(endpoint): State<Arc<WsEndpoint>>,
+            RawQuery(query): RawQuery,
+            headers: HeaderMap,
+            ws: WebSocketUpgrade,
+        ) -> Response {
+            let headers: HashMap<String, String> = headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                .collect();
+
+            match endpoint.authorize(&headers, query.as_deref().unwrap_or_default()) {
+                Ok(user) => ws.on_upgrade(move |socket| serve(endpoint, socket, user)),
+                Err(error) => (
+                    StatusCode::from_u16(error.status).unwrap_or(StatusCode::UNAUTHORIZED),
+                    error.message,
+                )
+                    .into_response(),
+            }
+        }
+
+        axum::Router::new()
+            .route("/", get(upgrade))
+            .with_state(Arc::new(self))
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::jwt::{now_secs, JwtClaims};
+    use futures::executor::block_on;
+    use futures::future::join;
+
+    fn token(jwt: &Jwt, exp: u64) -> String {
+        jwt.create_token(&JwtClaims {
+            sub: "1".to_string(),
+            username: "ada".to_string(),
+            email: "ada@example.com".to_string(),
+            roles: vec!["admin".to_string()],
+            exp,
+            iat: now_secs(),
+            permissions: vec![],
+        })
+        .unwrap()
+    }
+
+    fn dashboard() -> WsEndpoint {
+        WsEndpoint::new(|mut session| async move {
+            let name = session.user().map(|u| u.username.clone()).unwrap_or_default();
+            session.send_text(format!("hello {}", name));
+            while let Some(WsMessage::Text(text)) = session.recv().await {
+                session.send_text(format!("stats:{}", text));
+            }
+        })
+        .with_jwt(Jwt::new("secret".to_string()))
+    }
+
+    #[test]
+    fn test_authenticated_socket_receives_pushes() {
+        let jwt = Jwt::new("secret".to_string());
+        let endpoint = dashboard();
+        let query = format!("token={}", token(&jwt, now_secs() + 60));
+
+        let user = endpoint.authorize(&HashMap::new(), &query).unwrap();
+        let (handler, mut peer) = endpoint.open(user);
+        peer.incoming.unbounded_send(WsMessage::Text("visitors".to_string())).unwrap();
+        drop(peer.incoming);
+
+        block_on(join(handler, async {
+            assert_eq!(peer.outgoing.next().await, Some(WsMessage::Text("hello ada".to_string())));
+            assert_eq!(peer.outgoing.next().await, Some(WsMessage::Text("stats:visitors".to_string())));
+            assert_eq!(peer.outgoing.next().await, None);
+        }));
+    }
+
+    #[test]
+    fn test_rejects_missing_bad_or_expired_tokens() {
+        let jwt = Jwt::new("secret".to_string());
+        let endpoint = dashboard();
+        let status = |headers: &[(&str, String)], query: &str| {
+            let headers = headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<HashMap<_, _>>();
+            endpoint.authorize(&headers, query).err().map(|e| e.status)
+        };
+
+        assert_eq!(status(&[], ""), Some(401));
+        assert_eq!(status(&[], "token=garbage"), Some(401));
+        assert_eq!(status(&[], &format!("token={}", token(&jwt, 1))), Some(401));
+
+        let forged = token(&Jwt::new("other".to_string()), now_secs() + 60);
+        assert_eq!(status(&[("authorization", format!("Bearer {}", forged))], ""), Some(401));
+
+        let valid = token(&jwt, now_secs() + 60);
+        assert_eq!(status(&[("authorization", format!("Bearer {}", valid))], ""), None);
+
+        let open = WsEndpoint::new(|_| async {});
+        assert!(open.authorize(&HashMap::new(), "").unwrap().is_none());
+    }
+}
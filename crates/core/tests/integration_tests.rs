@@ -43,38 +43,38 @@ fn test_counter_component_integration() {
             
             Element::Node {
                 tag: "div".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![
                     Element::Node {
                         tag: "h1".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Count: {}", count))],
                     },
                     Element::Node {
                         tag: "p".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Doubled: {}", doubled))],
                     },
                     Element::Node {
                         tag: "button".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             on_click: Some(Rc::new({
                                 let set_count = set_count.clone();
                                 move || set_count(count + 1)
                             })),
                             ..Props::default()
-                        },
+                        }),
                         children: vec![Element::Text("+1".to_string())],
                     },
                     Element::Node {
                         tag: "button".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             on_click: Some(Rc::new({
                                 let set_count = set_count.clone();
                                 move || set_count(count - 1)
                             })),
                             ..Props::default()
-                        },
+                        }),
                         children: vec![Element::Text("-1".to_string())],
                     },
                 ],
@@ -206,22 +206,22 @@ fn test_component_composition() {
         fn render(&self) -> Element {
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("card".to_string()),
                     ..Props::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "h2".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(self.title.clone())],
                     },
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("card-content".to_string()),
                             ..Props::default()
-                        },
+                        }),
                         children: self.children.clone(),
                     },
                 ],
@@ -240,12 +240,12 @@ fn test_component_composition() {
                 children: vec![
                     Element::Node {
                         tag: "p".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("ID: {}", self.user.id))],
                     },
                     Element::Node {
                         tag: "p".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Name: {}", self.user.name))],
                     },
                 ],
@@ -265,14 +265,14 @@ fn test_component_composition() {
             
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("dashboard".to_string()),
                     ..Props::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "h1".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text("User Dashboard".to_string())],
                     },
                 ]
@@ -313,19 +313,19 @@ fn test_effect_lifecycle() {
             
             Element::Node {
                 tag: "div".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![
                     Element::Node {
                         tag: "p".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Count: {}", count))],
                     },
                     Element::Node {
                         tag: "button".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             on_click: Some(Rc::new(move || set_count(count + 1))),
                             ..Props::default()
-                        },
+                        }),
                         children: vec![Element::Text("+1".to_string())],
                     },
                 ],
@@ -359,38 +359,38 @@ fn test_memo_optimization() {
             
             Element::Node {
                 tag: "div".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![
                     Element::Node {
                         tag: "h2".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Fibonacci({}) = {}", n, fib_result))],
                     },
                     Element::Node {
                         tag: "p".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(format!("Computations: {}", computation_count.borrow()))],
                     },
                     Element::Node {
                         tag: "button".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             on_click: Some(Rc::new({
                                 let set_n = set_n.clone();
                                 move || set_n(n + 1)
                             })),
                             ..Props::default()
-                        },
+                        }),
                         children: vec![Element::Text("+1".to_string())],
                     },
                     Element::Node {
                         tag: "button".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             on_click: Some(Rc::new({
                                 let set_n = set_n.clone();
                                 move || set_n(n - 1)
                             })),
                             ..Props::default()
-                        },
+                        }),
                         children: vec![Element::Text("-1".to_string())],
                     },
                 ],
@@ -0,0 +1,206 @@
+//! `#[derive(FormFields)]`
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, GenericArgument, LitStr, Path, PathArguments, Type};
+
+/// How a field's value is converted to and from the form's strings
+enum Conversion {
+    /// Stored as is
+    String,
+    /// `FromStr` / `ToString`
+    Parsed,
+    /// `Option` of a `FromStr` / `ToString` type; an empty string is `None`
+    Optional,
+}
+
+struct FormField {
+    ident: syn::Ident,
+    name: String,
+    conversion: Conversion,
+    ty: Type,
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input)?;
+    let fields = form_fields(&input)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let setters = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let key = &field.name;
+        let parse = quote_spanned! {field.ty.span()=>
+            value.parse().map_err(|_| format!("Invalid value for field {}", field))?
+        };
+        let assign = match field.conversion {
+            Conversion::String => quote! { self.#ident = value; },
+            Conversion::Parsed => quote! { self.#ident = #parse; },
+            Conversion::Optional => quote! {
+                self.#ident = if value.is_empty() { None } else { Some(#parse) };
+            },
+        };
+        quote! {
+            #key => {
+                #assign
+                Ok(())
+            }
+        }
+    });
+
+    let getters = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let key = &field.name;
+        let value = match field.conversion {
+            Conversion::String => quote! { self.#ident.clone() },
+            Conversion::Parsed => quote_spanned! {field.ty.span()=> self.#ident.to_string() },
+            Conversion::Optional => quote_spanned! {field.ty.span()=>
+                self.#ident.as_ref().map(|value| value.to_string()).unwrap_or_default()
+            },
+        };
+        quote! { #key => Some(#value), }
+    });
+
+    let names = fields.iter().map(|field| &field.name);
+
+    Ok(quote! {
+        impl #impl_generics #krate::form_traits::FormFields for #name #ty_generics #where_clause {
+            fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
+                match field {
+                    #(#setters)*
+                    _ => Err(format!("Unknown field: {}", field)),
+                }
+            }
+
+            fn get_field(&self, field: &str) -> Option<String> {
+                match field {
+                    #(#getters)*
+                    _ => None,
+                }
+            }
+
+            fn field_names(&self) -> Vec<&'static str> {
+                vec![#(#names),*]
+            }
+        }
+    })
+}
+
+/// `#[form(crate = "layer9_framework")]` for apps that reach layer9-core
+/// through another crate
+fn crate_path(input: &DeriveInput) -> syn::Result<Path> {
+    let mut krate = syn::parse_quote!(::layer9_core);
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("form")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `crate = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+fn form_fields(input: &DeriveInput) -> syn::Result<Vec<FormField>> {
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "FormFields can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FormFields can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut fields = Vec::new();
+    for field in named {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = ident.to_string();
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("form")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `skip` or `name = \"...\"`"))
+                }
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        fields.push(FormField {
+            ident,
+            name,
+            conversion: conversion(&field.ty)?,
+            ty: field.ty.clone(),
+        });
+    }
+    Ok(fields)
+}
+
+fn conversion(ty: &Type) -> syn::Result<Conversion> {
+    let unsupported = |what: &str| {
+        Err(syn::Error::new_spanned(
+            ty,
+            format!(
+                "FormFields can't convert {} to and from a form value; use a type that implements \
+                 `FromStr` and `ToString`, or mark the field `#[form(skip)]`",
+                what
+            ),
+        ))
+    };
+
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last().expect("non-empty path");
+            match last.ident.to_string().as_str() {
+                "String" => Ok(Conversion::String),
+                "Option" => match option_inner(&last.arguments) {
+                    Some(inner) if conversion(inner).is_ok_and(|c| !matches!(c, Conversion::Optional)) => {
+                        Ok(Conversion::Optional)
+                    }
+                    _ => unsupported("this `Option`"),
+                },
+                "Vec" | "HashMap" | "BTreeMap" | "HashSet" | "BTreeSet" => Err(syn::Error::new_spanned(
+                    ty,
+                    "FormFields can't derive collection fields; mark the field `#[form(skip)]` and \
+                     implement the array methods of `FormFields` by hand",
+                )),
+                _ => Ok(Conversion::Parsed),
+            }
+        }
+        Type::Reference(_) => unsupported("references"),
+        Type::Tuple(_) => unsupported("tuples"),
+        Type::Array(_) | Type::Slice(_) => unsupported("arrays"),
+        _ => unsupported("this type"),
+    }
+}
+
+fn option_inner(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(arguments) = arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -2,7 +2,9 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, ItemFn, ItemStruct};
+use syn::{parse_macro_input, DeriveInput, ItemFn, ItemStruct};
+
+mod form_fields;
 
 /// Macro for defining Layer9 apps
 #[proc_macro_attribute]
@@ -70,3 +72,26 @@ pub fn server(_args: TokenStream, input: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+/// Implement `FormFields` from a struct's named fields. `String` fields are
+/// stored as typed, other fields go through `FromStr`/`ToString`, and an
+/// empty value sets an `Option` field to `None`.
+///
+/// ```ignore
+/// #[derive(Clone, Default, FormFields)]
+/// struct Signup {
+///     email: String,
+///     age: u32,
+///     #[form(name = "referral-code")]
+///     referral: Option<String>,
+///     #[form(skip)]
+///     accepted_at: Option<Instant>,
+/// }
+/// ```
+#[proc_macro_derive(FormFields, attributes(form))]
+pub fn derive_form_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    form_fields::expand(input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
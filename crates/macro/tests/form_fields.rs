@@ -0,0 +1,57 @@
+use layer9_core::form_traits::FormFields;
+use layer9_macro::FormFields;
+
+#[derive(Clone, Default, Debug, PartialEq, FormFields)]
+struct Signup {
+    email: String,
+    age: u32,
+    #[form(name = "referral-code")]
+    referral: Option<String>,
+    newsletter: bool,
+    #[form(skip)]
+    #[allow(dead_code)]
+    attempts: Vec<u32>,
+}
+
+#[test]
+fn test_derived_fields_round_trip() {
+    let mut signup = Signup::default();
+    assert_eq!(signup.field_names(), vec!["email", "age", "referral-code", "newsletter"]);
+
+    signup.set_field("email", "ada@example.com".to_string()).unwrap();
+    signup.set_field("age", "36".to_string()).unwrap();
+    signup.set_field("referral-code", "LOVELACE".to_string()).unwrap();
+    signup.set_field("newsletter", "true".to_string()).unwrap();
+
+    assert_eq!(signup.age, 36);
+    assert_eq!(signup.referral.as_deref(), Some("LOVELACE"));
+    for name in signup.field_names() {
+        let mut copy = Signup::default();
+        copy.set_field(name, signup.get_field(name).unwrap()).unwrap();
+        assert_eq!(copy.get_field(name), signup.get_field(name));
+    }
+
+    signup.set_field("referral-code", String::new()).unwrap();
+    assert_eq!(signup.referral, None);
+    assert_eq!(signup.get_field("referral-code").as_deref(), Some(""));
+}
+
+#[test]
+fn test_bad_values_and_unknown_fields_are_errors() {
+    let mut signup = Signup::default();
+    assert_eq!(
+        signup.set_field("age", "thirty".to_string()),
+        Err("Invalid value for field age".to_string())
+    );
+    assert_eq!(
+        signup.set_field("attempts", "1".to_string()),
+        Err("Unknown field: attempts".to_string())
+    );
+    assert_eq!(signup.get_field("referral"), None);
+}
+
+#[test]
+fn test_compile_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,13 @@
+use layer9_macro::FormFields;
+
+struct Address {
+    street: String,
+}
+
+#[derive(FormFields)]
+struct Profile {
+    name: String,
+    address: Address,
+}
+
+fn main() {}
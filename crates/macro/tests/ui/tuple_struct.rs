@@ -0,0 +1,6 @@
+use layer9_macro::FormFields;
+
+#[derive(FormFields)]
+struct Email(String);
+
+fn main() {}
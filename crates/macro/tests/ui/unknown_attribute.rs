@@ -0,0 +1,9 @@
+use layer9_macro::FormFields;
+
+#[derive(FormFields)]
+struct Login {
+    #[form(rename = "user")]
+    username: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use layer9_macro::FormFields;
+
+#[derive(FormFields)]
+struct Contacts {
+    name: String,
+    emails: Vec<String>,
+}
+
+fn main() {}
@@ -75,19 +75,14 @@ impl Component for AsyncCounter {
             move || set_count(count - 1)
         };
         
-        let async_reset = {
+        // Loading flag and message follow the reset's status
+        let (reset_status, async_reset) = use_async({
             let set_count = set_count.clone();
-            let set_loading = set_loading.clone();
             let set_message = set_message.clone();
             move || {
                 let set_count = set_count.clone();
-                let set_loading = set_loading.clone();
                 let set_message = set_message.clone();
-                
-                set_loading(true);
-                set_message("Resetting...".to_string());
-                
-                spawn_local(async move {
+                async move {
                     // Simulate async reset
                     let window = web_sys::window().unwrap();
                     let promise = js_sys::Promise::new(&mut |resolve, _| {
@@ -96,14 +91,17 @@ impl Component for AsyncCounter {
                             800
                         ).unwrap();
                     });
-                    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+                    wasm_bindgen_futures::JsFuture::from(promise).await.map_err(|_| ())?;
                     
                     set_count(0);
-                    set_loading(false);
                     set_message("Reset complete! Fresh start! 🎯".to_string());
-                });
+                    Ok::<(), ()>(())
+                }
             }
-        };
+        });
+        let resetting = reset_status == AsyncStatus::Loading;
+        let loading = loading || resetting;
+        let message = if resetting { "Resetting...".to_string() } else { message };
         
         let async_random = {
             let set_count = set_count.clone();
@@ -138,40 +136,40 @@ impl Component for AsyncCounter {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("async-counter-app".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 // Inline styles
                 Element::Node {
                     tag: "style".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(ASYNC_STYLES.to_string())],
                 },
                 
                 // Background decorations
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("bg-decoration".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("pulse pulse-1".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("pulse pulse-2".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                     ],
@@ -180,26 +178,26 @@ impl Component for AsyncCounter {
                 // Main content
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("content-card".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         // Header
                         Element::Node {
                             tag: "header".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Node {
                                     tag: "h1".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("gradient-text".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Async".to_string())],
                                         },
                                         Element::Text(" Counter".to_string()),
@@ -207,10 +205,10 @@ impl Component for AsyncCounter {
                                 },
                                 Element::Node {
                                     tag: "p".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("subtitle".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Experience asynchronous state management".to_string())],
                                 },
                             ],
@@ -219,27 +217,27 @@ impl Component for AsyncCounter {
                         // Counter display
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some(if loading { "counter-display loading" } else { "counter-display" }.to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 if loading {
                                     Element::Node {
                                         tag: "div".to_string(),
-                                        props: Props {
+                                        props: Box::new(Props {
                                             class: Some("loading-spinner".to_string()),
                                             ..Default::default()
-                                        },
+                                        }),
                                         children: vec![],
                                     }
                                 } else {
                                     Element::Node {
                                         tag: "div".to_string(),
-                                        props: Props {
+                                        props: Box::new(Props {
                                             class: Some("counter-value".to_string()),
                                             ..Default::default()
-                                        },
+                                        }),
                                         children: vec![Element::Text(count.to_string())],
                                     }
                                 },
@@ -249,17 +247,17 @@ impl Component for AsyncCounter {
                         // Message display
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("message-display".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "p".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some(if loading { "message loading" } else { "message" }.to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text(message.clone())],
                                 },
                             ],
@@ -268,35 +266,35 @@ impl Component for AsyncCounter {
                         // Control buttons
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("controls".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 // Sync buttons
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("sync-controls".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "button".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("btn btn-decrement".to_string()),
                                                 on_click: Some(Rc::new(decrement)),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("− Decrement".to_string())],
                                         },
                                         Element::Node {
                                             tag: "button".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("btn btn-increment".to_string()),
                                                 on_click: Some(Rc::new(increment)),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("+ Increment".to_string())],
                                         },
                                     ],
@@ -305,25 +303,25 @@ impl Component for AsyncCounter {
                                 // Async buttons
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("async-controls".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "button".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some(if loading { "btn btn-async disabled" } else { "btn btn-async" }.to_string()),
-                                                on_click: if loading { None } else { Some(Rc::new(async_reset)) },
+                                                on_click: if loading { None } else { Some(async_reset.clone()) },
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![
                                                 Element::Node {
                                                     tag: "span".to_string(),
-                                                    props: Props {
+                                                    props: Box::new(Props {
                                                         class: Some("btn-icon".to_string()),
                                                         ..Default::default()
-                                                    },
+                                                    }),
                                                     children: vec![Element::Text("↺".to_string())],
                                                 },
                                                 Element::Text(" Async Reset".to_string()),
@@ -331,18 +329,18 @@ impl Component for AsyncCounter {
                                         },
                                         Element::Node {
                                             tag: "button".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some(if loading { "btn btn-async disabled" } else { "btn btn-async" }.to_string()),
                                                 on_click: if loading { None } else { Some(Rc::new(async_random)) },
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![
                                                 Element::Node {
                                                     tag: "span".to_string(),
-                                                    props: Props {
+                                                    props: Box::new(Props {
                                                         class: Some("btn-icon".to_string()),
                                                         ..Default::default()
-                                                    },
+                                                    }),
                                                     children: vec![Element::Text("🎲".to_string())],
                                                 },
                                                 Element::Text(" Random".to_string()),
@@ -356,18 +354,18 @@ impl Component for AsyncCounter {
                         // Footer
                         Element::Node {
                             tag: "footer".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Text("Powered by ".to_string()),
                                 Element::Node {
                                     tag: "a".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![
                                             ("href".to_string(), "https://github.com/anthropics/layer9".to_string()),
                                             ("target".to_string(), "_blank".to_string()),
                                         ],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Layer9".to_string())],
                                 },
                                 Element::Text(" • Async Rust Web Framework".to_string()),
@@ -13,6 +13,16 @@ pub async fn main() {
     
     console::log_1(&"=== Layer9 JWT Authentication Demo ===".into());
     
+    // Example 0: A session saved by example 5 on an earlier load survives the reload
+    console::log_1(&"\n0. Restored Session:".into());
+    {
+        let auth_service = AuthService::new(Box::new(token_demo_provider())).with_storage_key(SESSION_KEY);
+        match auth_service.get_current_user() {
+            Some(user) => console::log_1(&format!("✓ Restored session for {}", user.username).into()),
+            None => console::log_1(&"No saved session yet; reload after the demo finishes".into()),
+        }
+    }
+    
     // Example 1: Using default configuration (JWT with default secret)
     console::log_1(&"\n1. Default Configuration:".into());
     {
@@ -103,15 +113,7 @@ pub async fn main() {
     // Example 5: Token validation and refresh
     console::log_1(&"\n5. Token Validation and Refresh:".into());
     {
-        let mut jwt_provider = JwtAuthProvider::new("token-test-secret".to_string());
-        jwt_provider.add_user(
-            "tokenuser".to_string(),
-            "tokenpass".to_string(),
-            "token@example.com".to_string(),
-            vec!["admin".to_string()]
-        );
-        
-        let mut auth_service = AuthService::new(Box::new(jwt_provider));
+        let mut auth_service = AuthService::new(Box::new(token_demo_provider())).with_storage_key(SESSION_KEY);
         
         // Login to get a token
         match auth_service.login("tokenuser", "tokenpass").await {
@@ -150,4 +152,18 @@ pub async fn main() {
     }
     
     console::log_1(&"\n=== Demo Complete ===".into());
-}
\ No newline at end of file
+}
+
+/// Where example 5 keeps its session between page loads
+const SESSION_KEY: &str = "auth-jwt-demo.session";
+
+fn token_demo_provider() -> JwtAuthProvider {
+    let mut jwt_provider = JwtAuthProvider::new("token-test-secret".to_string());
+    jwt_provider.add_user(
+        "tokenuser".to_string(),
+        "tokenpass".to_string(),
+        "token@example.com".to_string(),
+        vec!["admin".to_string()]
+    );
+    jwt_provider
+}
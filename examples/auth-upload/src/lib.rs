@@ -8,7 +8,7 @@
 
 use layer9_core::{
     auth::{AuthService, JwtAuthProvider, AuthContext},
-    upload::FileUploadComponent,
+    upload::{FileUploadComponent, UploadHandle},
 };
 use wasm_bindgen::prelude::*;
 use web_sys::{console, FileList};
@@ -57,6 +57,7 @@ impl AuthUploadApp {
         let upload_component = FileUploadComponent::new()
             .with_url("/api/upload".to_string())
             .with_max_size(5 * 1024 * 1024) // 5MB
+            .with_preview(true)
             .with_allowed_types(vec![
                 "image/jpeg".to_string(),
                 "image/png".to_string(),
@@ -380,6 +381,21 @@ impl AuthUploadApp {
             align-items: center;
         }
         
+        .upload-preview {
+            flex-shrink: 0;
+            width: 48px;
+            height: 48px;
+            max-width: 48px;
+            max-height: 48px;
+            margin-right: 12px;
+            border-radius: 4px;
+            object-fit: cover;
+        }
+        
+        .upload-item .file-name {
+            flex: 1;
+        }
+        
         .upload-item.pending {
             background: #e6f3ff;
             color: #0066cc;
@@ -399,6 +415,11 @@ impl AuthUploadApp {
             background: #ffe6e6;
             color: #cc0000;
         }
+        
+        .upload-item.cancelled {
+            background: #f0f0f0;
+            color: #666666;
+        }
         "#
     }
     
@@ -434,9 +455,14 @@ impl AuthUploadApp {
                 const files = event.target.files;
                 if (files && files.length > 0) {
                     try {
-                        await window.wasmApp.upload_files(files);
-                        // Update UI to show upload progress
-                        window.updateUploadStatus();
+                        const handles = window.wasmApp.upload_files(files);
+                        // Refresh progress until every upload has finished
+                        const timer = setInterval(function() {
+                            window.app.updateUploadStatus();
+                            if (handles.every(function(h) { return h.is_finished(); })) {
+                                clearInterval(timer);
+                            }
+                        }, 200);
                     } catch (e) {
                         console.error('Upload failed:', e);
                     }
@@ -444,7 +470,6 @@ impl AuthUploadApp {
             },
             
             updateUploadStatus: function() {
-                // This would be called periodically to update upload progress
                 const statusHtml = window.wasmApp.get_upload_status();
                 const statusContainer = document.getElementById('upload-progress');
                 if (statusContainer) {
@@ -492,7 +517,7 @@ impl AuthUploadApp {
     }
     
     #[wasm_bindgen]
-    pub async fn upload_files(&mut self, files: FileList) -> Result<JsValue, JsValue> {
+    pub fn upload_files(&mut self, files: FileList) -> Result<Vec<UploadHandle>, JsValue> {
         let auth_context = self.auth_service.get_context();
         
         // Check if user has upload permission
@@ -500,17 +525,8 @@ impl AuthUploadApp {
             return Err(JsValue::from_str("You don't have permission to upload files"));
         }
         
-        // Use the file upload component
-        match self.upload_component.handle_file_select(files).await {
-            Ok(upload_ids) => {
-                let array = js_sys::Array::new();
-                for id in upload_ids {
-                    array.push(&JsValue::from_str(&id));
-                }
-                Ok(array.into())
-            }
-            Err(e) => Err(JsValue::from_str(&e))
-        }
+        // Uploads run in the background; each handle reports progress and can cancel
+        Ok(self.upload_component.handle_file_select(files))
     }
     
     #[wasm_bindgen]
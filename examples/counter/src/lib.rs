@@ -98,50 +98,54 @@ impl Component for CounterApp {
             std::cmp::Ordering::Equal => "zero",
         };
 
+        // The status only changes when the count crosses zero, so most
+        // clicks reuse its last render
+        let status = memo(counter_class, |&status| CounterStatus { status });
+
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("beautiful-counter".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 // Inline styles
                 Element::Node {
                     tag: "style".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(BEAUTIFUL_STYLES.to_string())],
                 },
                 
                 // Background decoration
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("bg-decoration".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("circle circle-1".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("circle circle-2".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("circle circle-3".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                     ],
@@ -150,26 +154,26 @@ impl Component for CounterApp {
                 // Main content
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("counter-content".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         // Header
                         Element::Node {
                             tag: "header".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Node {
                                     tag: "h1".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("gradient-text".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Layer9".to_string())],
                                         },
                                         Element::Text(" Counter".to_string()),
@@ -177,10 +181,10 @@ impl Component for CounterApp {
                                 },
                                 Element::Node {
                                     tag: "p".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("subtitle".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Beautiful reactive state management".to_string())],
                                 },
                             ],
@@ -189,25 +193,25 @@ impl Component for CounterApp {
                         // Counter display
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some(format!("counter-display {} {}", counter_class, animation_class)),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("counter-value".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text(count.to_string())],
                                 },
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("counter-label".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text(if count.abs() == 1 { "click" } else { "clicks" }.to_string())],
                                 },
                             ],
@@ -216,10 +220,10 @@ impl Component for CounterApp {
                         // Quick actions
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("quick-actions".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 create_quick_button("-10", increment_by(-10)),
                                 create_quick_button("-5", increment_by(-5)),
@@ -231,25 +235,25 @@ impl Component for CounterApp {
                         // Main controls
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("main-controls".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "button".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("btn btn-decrement".to_string()),
                                         on_click: Some(Rc::new(decrement)),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("btn-icon".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("−".to_string())],
                                         },
                                         Element::Text("Decrement".to_string()),
@@ -257,18 +261,18 @@ impl Component for CounterApp {
                                 },
                                 Element::Node {
                                     tag: "button".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("btn btn-reset".to_string()),
                                         on_click: Some(Rc::new(reset)),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("btn-icon".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("↺".to_string())],
                                         },
                                         Element::Text("Reset".to_string()),
@@ -276,18 +280,18 @@ impl Component for CounterApp {
                                 },
                                 Element::Node {
                                     tag: "button".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("btn btn-increment".to_string()),
                                         on_click: Some(Rc::new(increment)),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("btn-icon".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("+".to_string())],
                                         },
                                         Element::Text("Increment".to_string()),
@@ -299,12 +303,12 @@ impl Component for CounterApp {
                         // Statistics
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("stats".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
-                                create_stat("Status", match count.cmp(&0) { std::cmp::Ordering::Greater => "Positive", std::cmp::Ordering::Less => "Negative", std::cmp::Ordering::Equal => "Zero" }),
+                                status,
                                 create_stat("Distance from zero", &count.abs().to_string()),
                                 create_stat("Square", &(count * count).to_string()),
                             ],
@@ -313,18 +317,18 @@ impl Component for CounterApp {
                         // Footer
                         Element::Node {
                             tag: "footer".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Text("Built with ".to_string()),
                                 Element::Node {
                                     tag: "a".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![
                                             ("href".to_string(), "https://github.com/anthropics/layer9".to_string()),
                                             ("target".to_string(), "_blank".to_string()),
                                         ],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Layer9".to_string())],
                                 },
                                 Element::Text(" • Reactive Rust Web Framework".to_string()),
@@ -337,14 +341,29 @@ impl Component for CounterApp {
     }
 }
 
+struct CounterStatus {
+    status: &'static str,
+}
+
+impl Component for CounterStatus {
+    fn render(&self) -> Element {
+        let label = match self.status {
+            "positive" => "Positive",
+            "negative" => "Negative",
+            _ => "Zero",
+        };
+        create_stat("Status", label)
+    }
+}
+
 fn create_quick_button(label: &str, handler: impl Fn() + 'static) -> Element {
     Element::Node {
         tag: "button".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("quick-btn".to_string()),
             on_click: Some(Rc::new(handler)),
             ..Default::default()
-        },
+        }),
         children: vec![Element::Text(label.to_string())],
     }
 }
@@ -352,25 +371,25 @@ fn create_quick_button(label: &str, handler: impl Fn() + 'static) -> Element {
 fn create_stat(label: &str, value: &str) -> Element {
     Element::Node {
         tag: "div".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("stat".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("stat-label".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![Element::Text(label.to_string())],
             },
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("stat-value".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![Element::Text(value.to_string())],
             },
         ],
@@ -10,31 +10,59 @@
 
 use layer9_core::prelude::*;
 use wasm_bindgen::prelude::*;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Mobile browser chrome, matching each theme's background
+fn browser_chrome() -> ThemeColor {
+    ThemeColor::new("#ffffff", "#1a202c")
+}
+
+/// Variables the app's ThemeProvider sets for each theme
+fn theme_variables(is_dark: bool) -> HashMap<String, String> {
+    if is_dark {
+        css_props! {
+            "background" => "#1a202c",
+            "text" => "#e2e8f0",
+            "button-background" => "#4a5568",
+            "button-text" => "#e2e8f0"
+        }
+    } else {
+        css_props! {
+            "background" => "#ffffff",
+            "text" => "#2d3748",
+            "button-background" => "#e2e8f0",
+            "button-text" => "#2d3748"
+        }
+    }
+}
+
+fn color_scheme(is_dark: bool) -> ColorScheme {
+    if is_dark {
+        ColorScheme::Dark
+    } else {
+        ColorScheme::Light
+    }
+}
+
 /// Theme switcher component
 struct ThemeSwitcher {
-    is_dark: State<bool>,
+    is_dark: Rc<Cell<bool>>,
+    theme: ThemeHandle,
 }
 
 impl Component for ThemeSwitcher {
     fn render(&self) -> Element {
-        let is_dark = self.is_dark.get();
         let toggle_theme = {
-            let state = self.is_dark.clone();
+            let is_dark = self.is_dark.clone();
+            let theme = self.theme.clone();
+            // Swaps the mounted provider's variables, so nothing re-renders;
+            // the address bar follows
             move || {
-                let new_theme = !state.get();
-                state.set(new_theme);
-                
-                // Update CSS variables
-                let mut vars = CssVariables::new();
-                if new_theme {
-                    vars.set("background", "#1a202c");
-                    vars.set("text", "#e2e8f0");
-                } else {
-                    vars.set("background", "#ffffff");
-                    vars.set("text", "#2d3748");
-                }
+                is_dark.set(!is_dark.get());
+                theme.set(theme_variables(is_dark.get()));
+                browser_chrome().apply(color_scheme(is_dark.get()));
             }
         };
 
@@ -45,8 +73,8 @@ impl Component for ThemeSwitcher {
                 "top" => "1rem",
                 "right" => "1rem",
                 "padding" => "0.75rem 1.5rem",
-                "background-color" => if is_dark { "#4a5568" } else { "#e2e8f0" },
-                "color" => if is_dark { "#e2e8f0" } else { "#2d3748" },
+                "background-color" => "var(--button-background)",
+                "color" => "var(--button-text)",
                 "border" => "none",
                 "border-radius" => "9999px",
                 "cursor" => "pointer",
@@ -64,14 +92,12 @@ impl Component for ThemeSwitcher {
 
         Element::Node {
             tag: "button".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(button_style.build()),
                 on_click: Some(Rc::new(toggle_theme)),
                 ..Default::default()
-            },
-            children: vec![Element::Text(
-                if is_dark { "☀️ Light Mode" } else { "🌙 Dark Mode" }.to_string()
-            )],
+            }),
+            children: vec![Element::Text("🌓 Toggle Theme".to_string())],
         }
     }
 }
@@ -91,7 +117,10 @@ impl Component for HeroSection {
             })
             .keyframe("100%", css_props! {
                 "background-position" => "0% 50%"
-            });
+            })
+            .duration(8000)
+            .timing_function("ease")
+            .infinite();
 
         let hero_style = CssBuilder::new()
             .properties(css_props! {
@@ -102,7 +131,6 @@ impl Component for HeroSection {
                 "justify-content" => "center",
                 "background" => "linear-gradient(135deg, #667eea 0%, #764ba2 50%, #f093fb 100%)",
                 "background-size" => "200% 200%",
-                "animation" => "gradient-shift 8s ease infinite",
                 "color" => "white",
                 "text-align" => "center",
                 "padding" => "2rem"
@@ -132,25 +160,25 @@ impl Component for HeroSection {
 
         Element::Node {
             tag: "section".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(hero_style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(title_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("CSS-in-Rust Showcase".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(subtitle_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(
                         "Experience the power of runtime CSS generation with hover states, \
                          media queries, animations, and more!".to_string()
@@ -186,10 +214,10 @@ impl Component for CardGrid {
 
         Element::Node {
             tag: "section".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(grid_style.build()),
                 ..Default::default()
-            },
+            }),
             children: cards.into_iter().map(|(icon, title, desc)| {
                 Card { icon, title, description: desc }.render()
             }).collect(),
@@ -268,33 +296,33 @@ impl Component for Card {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(card_style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "span".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(icon_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(self.icon.to_string())],
                 },
                 Element::Node {
                     tag: "h3".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(title_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(self.title.to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(desc_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(self.description.to_string())],
                 },
             ],
@@ -328,32 +356,32 @@ impl Component for ButtonShowcase {
 
         Element::Node {
             tag: "section".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(section_style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h2".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(styles::heading().build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Interactive Buttons".to_string())],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(button_container.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "button".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some(styles::button_primary().build()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![Element::Text("Primary Button".to_string())],
                         },
                         SecondaryButton.render(),
@@ -395,10 +423,10 @@ impl Component for SecondaryButton {
 
         Element::Node {
             tag: "button".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text("Secondary Button".to_string())],
         }
     }
@@ -441,10 +469,10 @@ impl Component for GhostButton {
 
         Element::Node {
             tag: "button".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text("Ghost Button".to_string())],
         }
     }
@@ -488,15 +516,18 @@ impl Component for GradientButton {
 
         Element::Node {
             tag: "button".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text("Gradient Button".to_string())],
         }
     }
 }
 
+/// Id of the app's ThemeProvider, which the switcher's handle targets
+const THEME_ID: &str = "showcase-theme";
+
 /// Main app component
 struct CssShowcaseApp {
     theme_switcher: ThemeSwitcher,
@@ -504,9 +535,11 @@ struct CssShowcaseApp {
 
 impl CssShowcaseApp {
     fn new() -> Self {
+        let is_dark = false;
         Self {
             theme_switcher: ThemeSwitcher {
-                is_dark: use_state(|| false),
+                is_dark: Rc::new(Cell::new(is_dark)),
+                theme: ThemeProvider::new(theme_variables(is_dark)).id(THEME_ID).handle(),
             },
         }
     }
@@ -522,20 +555,23 @@ impl Component for CssShowcaseApp {
                 "transition" => "background-color 0.3s ease, color 0.3s ease"
             });
 
-        Element::Node {
-            tag: "div".to_string(),
-            props: Props {
-                class: Some(app_style.build()),
-                ..Default::default()
-            },
-            children: vec![
-                self.theme_switcher.render(),
-                HeroSection.render(),
-                CardGrid.render(),
-                ButtonShowcase.render(),
-                ResponsiveGrid.render(),
-            ],
-        }
+        ThemeProvider::new(theme_variables(self.theme_switcher.is_dark.get()))
+            .id(THEME_ID)
+            .children(vec![Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    class: Some(app_style.build()),
+                    ..Default::default()
+                }),
+                children: vec![
+                    self.theme_switcher.render(),
+                    HeroSection.render(),
+                    CardGrid.render(),
+                    ButtonShowcase.render(),
+                    ResponsiveGrid.render(),
+                ],
+            }])
+            .render()
     }
 }
 
@@ -583,40 +619,40 @@ impl Component for ResponsiveGrid {
 
         Element::Node {
             tag: "section".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some(section_style.build()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h2".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(styles::heading().build()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Responsive Grid".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         attributes: vec![("style".to_string(), "text-align: center; margin-bottom: 2rem; color: #718096;".to_string())],
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Resize your window to see the grid adapt!".to_string())],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(grid_style.build()),
                         ..Default::default()
-                    },
+                    }),
                     children: (1..=8).map(|i| {
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some(item_style_class.clone()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![Element::Text(format!("Item {}", i))],
                         }
                     }).collect(),
@@ -634,5 +670,6 @@ pub fn main() {
     
     // Create and mount the app
     let app = CssShowcaseApp::new();
+    browser_chrome().apply(color_scheme(app.theme_switcher.is_dark.get()));
     mount(Box::new(app), "app");
 }
\ No newline at end of file
@@ -2,12 +2,15 @@
 //! Demonstrates real database operations with Layer9
 
 use layer9_core::prelude::*;
+use layer9_core::form::{use_form, Form, FormConfig};
+use layer9_macro::FormFields;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 use std::rc::Rc;
 
 /// User model for database operations
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, FormFields)]
 pub struct User {
     pub id: Option<i64>,
     pub username: String,
@@ -36,8 +39,19 @@ impl Model for Post {
     const TABLE_NAME: &'static str = "posts";
 }
 
+impl HasMany<Post> for User {
+    const FOREIGN_KEY: &'static str = "user_id";
+}
+
+impl BelongsTo<User> for Post {
+    const FOREIGN_KEY: &'static str = "user_id";
+}
+
+/// Users shown per page of the list
+const USERS_PER_PAGE: u32 = 20;
+
 /// CRUD operations component
-#[derive(Debug, Clone, Default)]
+#[derive(Clone)]
 pub struct CrudApp {
     users: Vec<User>,
     posts: Vec<Post>,
@@ -46,6 +60,56 @@ pub struct CrudApp {
     new_post: Post,
     error: Option<String>,
     loading: bool,
+    /// Whether the edit form is open
+    editing: State<bool>,
+    /// The user being edited, loaded with `set_values`
+    edit_form: Form<User>,
+}
+
+impl Default for CrudApp {
+    fn default() -> Self {
+        let editing = use_state(|| false);
+        let edit_form = {
+            let editing = editing.clone();
+            use_form(FormConfig {
+                initial_values: User::default(),
+                validate: Some(Box::new(|user: &User| {
+                    let mut errors = HashMap::new();
+                    if user.username.trim().is_empty() {
+                        errors.insert("username".to_string(), vec!["Username is required".to_string()]);
+                    }
+                    if user.email.trim().is_empty() {
+                        errors.insert("email".to_string(), vec!["Email is required".to_string()]);
+                    }
+                    errors
+                })),
+                validate_field_async: HashMap::new(),
+                on_submit: Box::new(move |user| {
+                    let user = user.clone();
+                    let editing = editing.clone();
+                    Box::pin(async move {
+                        let repo = use_repository::<User>();
+                        let updated_user = repo.update_model(&user).await.map_err(|e| format!("{:?}", e))?;
+                        web_sys::console::log_1(&format!("Updated user: {:?}", updated_user).into());
+                        editing.set(false);
+                        Ok(())
+                    })
+                }),
+            })
+        };
+
+        CrudApp {
+            users: Vec::new(),
+            posts: Vec::new(),
+            selected_user: None,
+            new_user: User::default(),
+            new_post: Post::default(),
+            error: None,
+            loading: false,
+            editing,
+            edit_form,
+        }
+    }
 }
 
 impl Component for CrudApp {
@@ -53,7 +117,7 @@ impl Component for CrudApp {
         let mut children = vec![
             Element::Node {
                 tag: "h1".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Database CRUD Example".to_string())],
             }
         ];
@@ -67,7 +131,7 @@ impl Component for CrudApp {
             
             children.push(Element::Node {
                 tag: "div".to_string(),
-                props: error_props,
+                props: Box::new(error_props),
                 children: vec![Element::Text(error.clone())],
             });
         }
@@ -81,7 +145,7 @@ impl Component for CrudApp {
             
             children.push(Element::Node {
                 tag: "div".to_string(),
-                props: loading_props,
+                props: Box::new(loading_props),
                 children: vec![Element::Text("Loading...".to_string())],
             });
         }
@@ -102,7 +166,7 @@ impl Component for CrudApp {
 
         Element::Node {
             tag: "div".to_string(),
-            props: root_props,
+            props: Box::new(root_props),
             children,
         }
     }
@@ -119,20 +183,24 @@ impl CrudApp {
         let mut children = vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Users".to_string())],
             }
         ];
 
-        // Create user form
-        children.push(self.render_create_user_form());
+        // Create user form, or the edit form while a user is loaded into it
+        if self.editing.get() {
+            children.push(self.render_edit_user_form());
+        } else {
+            children.push(self.render_create_user_form());
+        }
 
         // Users list
         children.push(self.render_users_list());
 
         Element::Node {
             tag: "div".to_string(),
-            props: section_props,
+            props: Box::new(section_props),
             children,
         }
     }
@@ -157,7 +225,7 @@ impl CrudApp {
             
             Element::Node {
                 tag: "input".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![],
             }
         };
@@ -175,7 +243,7 @@ impl CrudApp {
             
             Element::Node {
                 tag: "input".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![],
             }
         };
@@ -191,18 +259,18 @@ impl CrudApp {
             
             Element::Node {
                 tag: "button".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![Element::Text("Create User".to_string())],
             }
         };
 
         Element::Node {
             tag: "div".to_string(),
-            props: form_props,
+            props: Box::new(form_props),
             children: vec![
                 Element::Node {
                     tag: "h3".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Create New User".to_string())],
                 },
                 username_input,
@@ -212,24 +280,113 @@ impl CrudApp {
         }
     }
 
-    fn render_users_list(&self) -> Element {
-        let list_props = Props {
-            class: Some("users-list".to_string()),
-            attributes: vec![("style".to_string(), "display: grid; grid-template-columns: repeat(auto-fill, minmax(250px, 1fr)); gap: 15px;".to_string())],
+    fn render_edit_user_form(&self) -> Element {
+        let form_props = Props {
+            class: Some("edit-user".to_string()),
+            attributes: vec![("style".to_string(), "background: #f5f5f5; padding: 15px; border-radius: 4px; margin-bottom: 20px;".to_string())],
             ..Default::default()
         };
+        let user = self.edit_form.values();
+        let errors = self.edit_form.errors();
 
-        let user_cards: Vec<Element> = self.users.iter().map(|user| {
-            self.render_user_card(user)
-        }).collect();
+        let input = |field: &'static str, input_type: &str, value: String| {
+            let form = self.edit_form.clone();
+            Element::Node {
+                tag: "input".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![
+                        ("type".to_string(), input_type.to_string()),
+                        ("name".to_string(), field.to_string()),
+                        ("value".to_string(), value),
+                        ("style".to_string(), "margin-right: 10px; padding: 5px;".to_string()),
+                    ],
+                    on_input: Some(Rc::new(move |value: String| {
+                        form.set_field_value(field, value);
+                    })),
+                    ..Default::default()
+                }),
+                children: vec![],
+            }
+        };
+
+        let save_button = {
+            let form = self.edit_form.clone();
+            Element::Node {
+                tag: "button".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("style".to_string(), "padding: 5px 15px; background: #007bff; color: white; border: none; border-radius: 4px; cursor: pointer; margin-right: 5px;".to_string())],
+                    on_click: Some(Rc::new(move || form.handle_submit()())),
+                    ..Default::default()
+                }),
+                children: vec![Element::Text("Save".to_string())],
+            }
+        };
+
+        // Back to the loaded record, so reopening doesn't show abandoned edits
+        let cancel_button = {
+            let form = self.edit_form.clone();
+            let editing = self.editing.clone();
+            Element::Node {
+                tag: "button".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("style".to_string(), "padding: 5px 15px; border: 1px solid #ccc; border-radius: 4px; cursor: pointer;".to_string())],
+                    on_click: Some(Rc::new(move || {
+                        form.reset();
+                        editing.set(false);
+                    })),
+                    ..Default::default()
+                }),
+                children: vec![Element::Text("Cancel".to_string())],
+            }
+        };
+
+        let mut children = vec![
+            Element::Node {
+                tag: "h3".to_string(),
+                props: Box::default(),
+                children: vec![Element::Text(format!("Edit {}", user.username))],
+            },
+            input("username", "text", user.username),
+            input("email", "email", user.email),
+            save_button,
+            cancel_button,
+        ];
+        let messages: Vec<&str> = ["username", "email"]
+            .iter()
+            .filter_map(|field| errors.get(*field))
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        if !messages.is_empty() {
+            children.push(Element::Node {
+                tag: "p".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("style".to_string(), "color: #c00; margin: 5px 0 0;".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text(messages.join(", "))],
+            });
+        }
 
         Element::Node {
             tag: "div".to_string(),
-            props: list_props,
-            children: user_cards,
+            props: Box::new(form_props),
+            children,
         }
     }
 
+    fn render_users_list(&self) -> Element {
+        let user_cards: Vec<Element> = self.users.iter().map(|user| {
+            self.render_user_card(user)
+        }).collect();
+
+        Grid::auto_fill("250px")
+            .gap(4)
+            .class("users-list")
+            .children(user_cards)
+            .render()
+    }
+
     fn render_user_card(&self, user: &User) -> Element {
         let card_props = Props {
             class: Some("user-card".to_string()),
@@ -245,11 +402,32 @@ impl CrudApp {
             
             Element::Node {
                 tag: "button".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![Element::Text("View Posts".to_string())],
             }
         };
 
+        // Opens the edit form pre-filled with this user
+        let edit_button = {
+            let form = self.edit_form.clone();
+            let editing = self.editing.clone();
+            let user = user.clone();
+            let props = Props {
+                attributes: vec![("style".to_string(), "padding: 5px 10px; background: #6c757d; color: white; border: none; border-radius: 4px; cursor: pointer; margin-right: 5px;".to_string())],
+                on_click: Some(Rc::new(move || {
+                    form.set_values(user.clone());
+                    editing.set(true);
+                })),
+                ..Default::default()
+            };
+            
+            Element::Node {
+                tag: "button".to_string(),
+                props: Box::new(props),
+                children: vec![Element::Text("Edit".to_string())],
+            }
+        };
+
         let delete_button = {
             let props = Props {
                 attributes: vec![("style".to_string(), "padding: 5px 10px; background: #dc3545; color: white; border: none; border-radius: 4px; cursor: pointer;".to_string())],
@@ -258,35 +436,35 @@ impl CrudApp {
             
             Element::Node {
                 tag: "button".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![Element::Text("Delete".to_string())],
             }
         };
 
         Element::Node {
             tag: "div".to_string(),
-            props: card_props,
+            props: Box::new(card_props),
             children: vec![
                 Element::Node {
                     tag: "h4".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(user.username.clone())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         attributes: vec![("style".to_string(), "color: #666; margin: 5px 0;".to_string())],
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text(user.email.clone())],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         attributes: vec![("style".to_string(), "margin-top: 10px;".to_string())],
                         ..Default::default()
-                    },
-                    children: vec![view_posts_button, delete_button],
+                    }),
+                    children: vec![view_posts_button, edit_button, delete_button],
                 },
             ],
         }
@@ -301,7 +479,7 @@ impl CrudApp {
         let mut children = vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text(format!("Posts by {}", user.username))],
             }
         ];
@@ -314,7 +492,7 @@ impl CrudApp {
 
         Element::Node {
             tag: "div".to_string(),
-            props: section_props,
+            props: Box::new(section_props),
             children,
         }
     }
@@ -328,11 +506,11 @@ impl CrudApp {
 
         Element::Node {
             tag: "div".to_string(),
-            props: form_props,
+            props: Box::new(form_props),
             children: vec![
                 Element::Node {
                     tag: "h3".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Create New Post".to_string())],
                 },
             ],
@@ -351,7 +529,7 @@ impl CrudApp {
 
         Element::Node {
             tag: "div".to_string(),
-            props: list_props,
+            props: Box::new(list_props),
             children: post_cards,
         }
     }
@@ -366,7 +544,7 @@ impl CrudApp {
         let mut children = vec![
             Element::Node {
                 tag: "h4".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text(post.title.clone())],
             }
         ];
@@ -374,20 +552,20 @@ impl CrudApp {
         if let Some(content) = &post.content {
             children.push(Element::Node {
                 tag: "p".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     attributes: vec![("style".to_string(), "color: #666; margin: 10px 0;".to_string())],
                     ..Default::default()
-                },
+                }),
                 children: vec![Element::Text(content.clone())],
             });
         }
 
         children.push(Element::Node {
             tag: "p".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: vec![("style".to_string(), "font-size: 0.9em; color: #999;".to_string())],
                 ..Default::default()
-            },
+            }),
             children: vec![Element::Text(if post.published { "Published" } else { "Draft" }.to_string())],
         });
 
@@ -399,7 +577,7 @@ impl CrudApp {
             
             Element::Node {
                 tag: "button".to_string(),
-                props,
+                props: Box::new(props),
                 children: vec![Element::Text("Delete".to_string())],
             }
         };
@@ -408,21 +586,27 @@ impl CrudApp {
 
         Element::Node {
             tag: "div".to_string(),
-            props: card_props,
+            props: Box::new(card_props),
             children,
         }
     }
 
-    /// Load all users from the database
-    pub fn load_users(&mut self) {
+    /// Load one page (1-based) of users from the database
+    pub fn load_users(&mut self, page: u32) {
         self.loading = true;
         self.error = None;
         
         wasm_bindgen_futures::spawn_local(async move {
             let repo = use_repository::<User>();
-            match repo.find_all().await {
+            match repo.paginate(page, USERS_PER_PAGE).await {
                 Ok(users) => {
-                    web_sys::console::log_1(&format!("Loaded {} users", users.len()).into());
+                    web_sys::console::log_1(
+                        &format!(
+                            "Loaded page {} of {} ({} users in total)",
+                            users.page, users.total_pages, users.total
+                        )
+                        .into(),
+                    );
                 }
                 Err(e) => {
                     web_sys::console::error_1(&format!("Failed to load users: {:?}", e).into());
@@ -442,11 +626,20 @@ impl CrudApp {
         self.loading = true;
         self.error = None;
         
+        // Through the users API rather than the repository, so the server
+        // also queues the welcome email
         wasm_bindgen_futures::spawn_local(async move {
-            let repo = use_repository::<User>();
-            match repo.insert(&user).await {
-                Ok(created_user) => {
-                    web_sys::console::log_1(&format!("Created user: {:?}", created_user).into());
+            match post("/api/users", &user).await {
+                Ok(response) if response.ok() => match response.json::<User>().await {
+                    Ok(created_user) => {
+                        web_sys::console::log_1(&format!("Created user: {:?}", created_user).into());
+                    }
+                    Err(e) => {
+                        web_sys::console::error_1(&format!("Failed to read created user: {:?}", e).into());
+                    }
+                },
+                Ok(response) => {
+                    web_sys::console::error_1(&format!("Failed to create user: HTTP {}", response.status()).into());
                 }
                 Err(e) => {
                     web_sys::console::error_1(&format!("Failed to create user: {:?}", e).into());
@@ -458,6 +651,24 @@ impl CrudApp {
         self.new_user = User::default();
     }
     
+    /// Save changes to an existing user
+    pub fn update_user(&mut self, user: User) {
+        self.loading = true;
+        self.error = None;
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let repo = use_repository::<User>();
+            match repo.update_model(&user).await {
+                Ok(updated_user) => {
+                    web_sys::console::log_1(&format!("Updated user: {:?}", updated_user).into());
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&format!("Failed to update user: {:?}", e).into());
+                }
+            }
+        });
+    }
+
     /// Delete a user
     pub fn delete_user(&mut self, id: i64) {
         self.loading = true;
@@ -483,13 +694,17 @@ impl CrudApp {
         self.posts.clear();
         
         wasm_bindgen_futures::spawn_local(async move {
-            let repo = use_repository::<Post>();
-            let query = repo.query().where_eq("user_id", user_id);
+            let repo = use_repository::<User>();
             
-            match query.execute(&use_db()).await {
-                Ok(posts) => {
+            match repo.with_many::<Post>(user_id).await {
+                Ok(Some((_, mut posts))) => {
+                    // Newest first
+                    posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
                     web_sys::console::log_1(&format!("Loaded {} posts", posts.len()).into());
                 }
+                Ok(None) => {
+                    web_sys::console::error_1(&format!("No user with id {}", user_id).into());
+                }
                 Err(e) => {
                     web_sys::console::error_1(&format!("Failed to load posts: {:?}", e).into());
                 }
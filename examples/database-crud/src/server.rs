@@ -1,13 +1,49 @@
 //! Server for Database CRUD Example
 
-use axum::Router;
+use axum::{extract::State, routing::post, Json, Router};
+use database_crud::User;
 use layer9_core::{
     db_api::create_db_api_router,
-    db::use_db,
+    db::{use_db, DatabaseConnection, DbError, Migration, Migrator, Repository},
+    jobs::JobQueue,
 };
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
+/// Payload of the job that greets a new user
+#[derive(Serialize, Deserialize)]
+struct WelcomeEmail {
+    to: String,
+    username: String,
+}
+
+#[derive(Clone)]
+struct UsersApi {
+    conn: Arc<Box<dyn DatabaseConnection>>,
+    jobs: JobQueue,
+}
+
+/// Insert the user and queue their welcome email, answering without
+/// waiting for it to be sent
+async fn create_user(
+    State(api): State<UsersApi>,
+    Json(user): Json<User>,
+) -> Result<Json<User>, DbError> {
+    let user = Repository::<User, _>::new(api.conn.clone()).insert(&user).await?;
+
+    // The account exists either way; a failed email is retried by the queue
+    let email = WelcomeEmail {
+        to: user.email.clone(),
+        username: user.username.clone(),
+    };
+    if let Err(e) = api.jobs.enqueue("send_welcome_email", &email) {
+        eprintln!("Couldn't queue the welcome email for {}: {}", user.email, e);
+    }
+    Ok(Json(user))
+}
+
 #[tokio::main]
 async fn main() {
     // Initialize environment
@@ -18,14 +54,54 @@ async fn main() {
         std::env::set_var("DATABASE_URL", "sqlite:crud_example.db");
     }
     
-    // Get database connection
-    let db_conn = use_db();
+    // Get database connection and bring its schema up to date
+    let migrator = Migrator::new(use_db())
+        .add_migration(Migration::new(
+            1,
+            "create_users",
+            "CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL UNIQUE,
+                email TEXT NOT NULL UNIQUE,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            "DROP TABLE users",
+        ))
+        .add_migration(Migration::new(
+            2,
+            "create_posts",
+            "CREATE TABLE IF NOT EXISTS posts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                content TEXT,
+                published BOOLEAN NOT NULL DEFAULT FALSE,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            "DROP TABLE posts",
+        ));
+    migrator.run().await.expect("Failed to run migrations");
+    let conn = Arc::new(migrator.into_connection());
+    
+    // Background work, like greeting new users; stands in for a real mailer
+    let jobs = JobQueue::new().register("send_welcome_email", |email: WelcomeEmail| async move {
+        println!("Welcome email sent to {} <{}>", email.username, email.to);
+        Ok(())
+    });
+    jobs.start(2);
     
     // Create database API router
-    let db_api = create_db_api_router(db_conn);
+    let db_api = create_db_api_router(conn.clone());
+    let users_api = Router::new()
+        .route("/", post(create_user))
+        .with_state(UsersApi { conn, jobs });
     
     // Build the main application
     let app = Router::new()
+        // Signup, which also sends the welcome email
+        .nest("/api/users", users_api)
         // Database API endpoints
         .nest("/api/db", db_api)
         // Serve static files
@@ -10,13 +10,16 @@ use wasm_bindgen::prelude::*;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+/// The registration wizard's steps, in order
+const WIZARD_STEPS: [&str; 3] = ["Account", "Security", "Profile"];
+
 #[derive(Clone, Default)]
 struct FormData {
     name: String,
     email: String,
     password: String,
     confirm_password: String,
-    age: String,
+    age: Option<u32>,
     terms: bool,
 }
 
@@ -86,54 +89,59 @@ impl Component for BeautifulForm {
             }
         };
         
-        let validate_age = |age: &str| -> Option<String> {
-            if age.is_empty() {
+        // The age stepper keeps the number within 18–120
+        let validate_age = |age: Option<u32>| -> Option<String> {
+            if age.is_none() {
                 Some("Age is required".to_string())
-            } else if let Ok(age_num) = age.parse::<i32>() {
-                if age_num < 18 {
-                    Some("You must be at least 18 years old".to_string())
-                } else if age_num > 120 {
-                    Some("Please enter a valid age".to_string())
-                } else {
-                    None
-                }
             } else {
-                Some("Please enter a valid number".to_string())
+                None
             }
         };
         
-        let validate_form = {
-            let form_data = form_data.clone();
-            move || -> FormErrors {
-                FormErrors {
-                    name: validate_name(&form_data.name),
-                    email: validate_email(&form_data.email),
-                    password: validate_password(&form_data.password),
-                    confirm_password: validate_confirm_password(&form_data.password, &form_data.confirm_password),
-                    age: validate_age(&form_data.age),
-                    terms: if !form_data.terms { Some("You must accept the terms".to_string()) } else { None },
-                }
+        let validate_form = move |form_data: &FormData| -> FormErrors {
+            FormErrors {
+                name: validate_name(&form_data.name),
+                email: validate_email(&form_data.email),
+                password: validate_password(&form_data.password),
+                confirm_password: validate_confirm_password(&form_data.password, &form_data.confirm_password),
+                age: validate_age(form_data.age),
+                terms: if !form_data.terms { Some("You must accept the terms".to_string()) } else { None },
+            }
+        };
+        
+        // Handlers bound on the first render stay attached, so the wizard
+        // reads the current step and data through refs rather than capturing
+        // this render's values
+        let (step, set_step) = use_state(0usize);
+        let (furthest, set_furthest) = use_state(0usize);
+        let latest = use_ref(FormData::default());
+        *latest.borrow_mut() = form_data.clone();
+        let position = use_ref((0usize, 0usize));
+        *position.borrow_mut() = (step, furthest);
+        
+        let go_to = {
+            let position = position.clone();
+            move |target: usize| {
+                let (_, furthest) = *position.borrow();
+                set_step(target);
+                set_furthest(furthest.max(target));
             }
         };
         
         let handle_submit = {
-            let form_data = form_data.clone();
+            let latest = latest.clone();
             let set_errors = set_errors.clone();
             let set_submitted = set_submitted.clone();
             let set_submitting = set_submitting.clone();
-            let validate_form = validate_form.clone();
+            let go_to = go_to.clone();
             move || {
-                let validation_errors = validate_form();
+                let validation_errors = validate_form(&latest.borrow());
                 set_errors(validation_errors.clone());
                 
-                let has_errors = validation_errors.name.is_some() ||
-                    validation_errors.email.is_some() ||
-                    validation_errors.password.is_some() ||
-                    validation_errors.confirm_password.is_some() ||
-                    validation_errors.age.is_some() ||
-                    validation_errors.terms.is_some();
-                
-                if !has_errors {
+                // Send the user back to the first step with a problem
+                if let Some(step) = first_step_with_errors(&validation_errors) {
+                    go_to(step);
+                } else {
                     set_submitting(true);
                     // Simulate API call
                     let window = web_sys::window().unwrap();
@@ -177,10 +185,6 @@ impl Component for BeautifulForm {
                     data.confirm_password = value.clone();
                     errs.confirm_password = validate_confirm_password(&data.password, &value);
                 },
-                "age" => {
-                    data.age = value.clone();
-                    errs.age = validate_age(&value);
-                },
                 "terms" => {
                     data.terms = !data.terms;
                     errs.terms = if !data.terms { Some("You must accept the terms".to_string()) } else { None };
@@ -191,55 +195,101 @@ impl Component for BeautifulForm {
             set_form_data(data);
             set_errors(errs);
         };
+        
+        let update_age = {
+            let form_data = form_data.clone();
+            let errors = errors.clone();
+            let set_form_data = set_form_data.clone();
+            let set_errors = set_errors.clone();
+            move |age: u32| {
+                let mut data = form_data.clone();
+                let mut errs = errors.clone();
+                data.age = Some(age);
+                errs.age = validate_age(data.age);
+                set_form_data(data);
+                set_errors(errs);
+            }
+        };
+
+        // Check the current step before moving on; the last step submits
+        let handle_next = {
+            let position = position.clone();
+            let set_errors = set_errors.clone();
+            let go_to = go_to.clone();
+            move || {
+                let (step, _) = *position.borrow();
+                if step + 1 == WIZARD_STEPS.len() {
+                    handle_submit();
+                    return;
+                }
+                
+                let step_errors = errors_for_step(validate_form(&latest.borrow()), step);
+                let valid = first_step_with_errors(&step_errors).is_none();
+                set_errors(step_errors);
+                if valid {
+                    go_to(step + 1);
+                }
+            }
+        };
+        
+        let handle_back = {
+            let go_to = go_to.clone();
+            move || {
+                let (step, _) = *position.borrow();
+                if step > 0 {
+                    go_to(step - 1);
+                }
+            }
+        };
 
         if submitted {
             // Success state
             return Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("form-container".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "style".to_string(),
-                        props: Props::default(),
+                        props: Box::default(),
                         children: vec![Element::Text(FORM_STYLES.to_string())],
                     },
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("success-card".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             Element::Node {
                                 tag: "div".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("success-icon".to_string()),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![Element::Text("✓".to_string())],
                             },
                             Element::Node {
                                 tag: "h2".to_string(),
-                                props: Props::default(),
+                                props: Box::default(),
                                 children: vec![Element::Text("Registration Successful!".to_string())],
                             },
                             Element::Node {
                                 tag: "p".to_string(),
-                                props: Props::default(),
+                                props: Box::default(),
                                 children: vec![Element::Text(format!("Welcome, {}! Your account has been created.", form_data.name))],
                             },
                             Element::Node {
                                 tag: "button".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("btn btn-primary".to_string()),
                                     on_click: Some(Rc::new(move || {
                                         web_sys::window().unwrap().location().reload().unwrap();
                                     })),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![Element::Text("Create Another Account".to_string())],
                             },
                         ],
@@ -250,40 +300,40 @@ impl Component for BeautifulForm {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("form-container".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 // Inline styles
                 Element::Node {
                     tag: "style".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(FORM_STYLES.to_string())],
                 },
                 
                 // Background decoration
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("bg-decoration".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("shape shape-1".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("shape shape-2".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         },
                     ],
@@ -292,184 +342,181 @@ impl Component for BeautifulForm {
                 // Form card
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("form-card".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         // Header
                         Element::Node {
                             tag: "header".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Node {
                                     tag: "h1".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![
                                         Element::Text("Create ".to_string()),
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("gradient-text".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Account".to_string())],
                                         },
                                     ],
                                 },
                                 Element::Node {
                                     tag: "p".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("subtitle".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Experience real-time validation with Layer9".to_string())],
                                 },
                             ],
                         },
                         
+                        // Wizard progress
+                        Steps::new(WIZARD_STEPS)
+                            .current(step)
+                            .furthest(furthest)
+                            .on_select(go_to)
+                            .render(),
+                        
                         // Form
                         Element::Node {
                             tag: "form".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
-                                // Name field
-                                create_input_field(
-                                    "Name",
-                                    "text",
-                                    "name",
-                                    &form_data.name,
-                                    errors.name.as_ref(),
-                                    "Enter your full name",
-                                    update_field.clone()
-                                ),
-                                
-                                // Email field
-                                create_input_field(
-                                    "Email",
-                                    "email",
-                                    "email",
-                                    &form_data.email,
-                                    errors.email.as_ref(),
-                                    "your@email.com",
-                                    update_field.clone()
-                                ),
-                                
-                                // Password field
-                                create_input_field(
-                                    "Password",
-                                    "password",
-                                    "password",
-                                    &form_data.password,
-                                    errors.password.as_ref(),
-                                    "At least 8 characters",
-                                    update_field.clone()
-                                ),
+                                // One panel per step, all kept in the page so
+                                // each field keeps its own input handler
+                                wizard_panel(step == 0, vec![
+                                    // Name field
+                                    create_input_field(
+                                        "Name",
+                                        "text",
+                                        "name",
+                                        &form_data.name,
+                                        errors.name.as_ref(),
+                                        "Enter your full name",
+                                        update_field.clone()
+                                    ),
                                 
-                                // Confirm password field
-                                create_input_field(
-                                    "Confirm Password",
-                                    "password",
-                                    "confirm_password",
-                                    &form_data.confirm_password,
-                                    errors.confirm_password.as_ref(),
-                                    "Re-enter your password",
-                                    update_field.clone()
-                                ),
+                                    // Email field
+                                    create_input_field(
+                                        "Email",
+                                        "email",
+                                        "email",
+                                        &form_data.email,
+                                        errors.email.as_ref(),
+                                        "your@email.com",
+                                        update_field.clone()
+                                    ),
+                                ]),
+
+                                wizard_panel(step == 1, vec![
+                                    // Password field
+                                    create_input_field(
+                                        "Password",
+                                        "password",
+                                        "password",
+                                        &form_data.password,
+                                        errors.password.as_ref(),
+                                        "At least 8 characters",
+                                        update_field.clone()
+                                    ),
                                 
-                                // Age field
-                                create_input_field(
-                                    "Age",
-                                    "number",
-                                    "age",
-                                    &form_data.age,
-                                    errors.age.as_ref(),
-                                    "18",
-                                    update_field.clone()
-                                ),
+                                    // Confirm password field
+                                    create_input_field(
+                                        "Confirm Password",
+                                        "password",
+                                        "confirm_password",
+                                        &form_data.confirm_password,
+                                        errors.confirm_password.as_ref(),
+                                        "Re-enter your password",
+                                        update_field.clone()
+                                    ),
+                                ]),
+
+                                wizard_panel(step == 2, vec![
+                                    // Age field
+                                    create_age_field(form_data.age, errors.age.as_ref(), update_age),
                                 
-                                // Terms checkbox
-                                Element::Node {
-                                    tag: "div".to_string(),
-                                    props: Props {
-                                        class: Some("checkbox-group".to_string()),
-                                        ..Default::default()
-                                    },
-                                    children: vec![
-                                        Element::Node {
-                                            tag: "label".to_string(),
-                                            props: Props {
-                                                class: Some("checkbox-label".to_string()),
-                                                ..Default::default()
-                                            },
-                                            children: vec![
-                                                Element::Node {
-                                                    tag: "input".to_string(),
-                                                    props: Props {
-                                                        attributes: vec![
-                                                            ("type".to_string(), "checkbox".to_string()),
-                                                            if form_data.terms { ("checked".to_string(), "checked".to_string()) } else { ("".to_string(), "".to_string()) },
-                                                        ].into_iter().filter(|(k, _)| !k.is_empty()).collect(),
-                                                        on_click: Some(Rc::new({
-                                                            let update_field = update_field.clone();
-                                                            move || update_field("terms", String::new())
-                                                        })),
-                                                        ..Default::default()
+                                    // Terms checkbox
+                                    Element::Node {
+                                        tag: "div".to_string(),
+                                        props: Box::new(Props {
+                                            class: Some("checkbox-group".to_string()),
+                                            ..Default::default()
+                                        }),
+                                        children: vec![
+                                            Element::Node {
+                                                tag: "label".to_string(),
+                                                props: Box::new(Props {
+                                                    class: Some("checkbox-label".to_string()),
+                                                    ..Default::default()
+                                                }),
+                                                children: vec![
+                                                    Element::Node {
+                                                        tag: "input".to_string(),
+                                                        props: Box::new(
+                                                            Props {
+                                                                attributes: vec![("type".to_string(), "checkbox".to_string())],
+                                                                on_click: Some(Rc::new({
+                                                                    let update_field = update_field.clone();
+                                                                    move || update_field("terms", String::new())
+                                                                })),
+                                                                ..Default::default()
+                                                            }
+                                                            .checked(form_data.terms),
+                                                        ),
+                                                        children: vec![],
                                                     },
-                                                    children: vec![],
-                                                },
-                                                Element::Text(" I accept the ".to_string()),
-                                                Element::Node {
-                                                    tag: "a".to_string(),
-                                                    props: Props {
-                                                        attributes: vec![("href".to_string(), "#".to_string())],
-                                                        ..Default::default()
+                                                    Element::Text(" I accept the ".to_string()),
+                                                    Element::Node {
+                                                        tag: "a".to_string(),
+                                                        props: Box::new(Props {
+                                                            attributes: vec![("href".to_string(), "#".to_string())],
+                                                            ..Default::default()
+                                                        }),
+                                                        children: vec![Element::Text("terms and conditions".to_string())],
                                                     },
-                                                    children: vec![Element::Text("terms and conditions".to_string())],
-                                                },
-                                            ],
-                                        },
-                                        if let Some(error) = &errors.terms {
-                                            Element::Node {
+                                                ],
+                                            },
+                                            show(errors.terms.is_some(), || Element::Node {
                                                 tag: "span".to_string(),
-                                                props: Props {
+                                                props: Box::new(Props {
                                                     class: Some("error-message".to_string()),
                                                     ..Default::default()
-                                                },
-                                                children: vec![Element::Text(error.clone())],
-                                            }
-                                        } else {
-                                            Element::Node {
-                                                tag: "span".to_string(),
-                                                props: Props::default(),
-                                                children: vec![],
-                                            }
-                                        },
-                                    ],
-                                },
-                                
-                                // Submit button
+                                                }),
+                                                children: vec![Element::Text(errors.terms.clone().unwrap_or_default())],
+                                            }),
+                                        ],
+                                    },
+                                ]),
+
+                                // Wizard navigation
                                 Element::Node {
-                                    tag: "button".to_string(),
-                                    props: Props {
-                                        class: Some(if submitting { "btn btn-primary loading" } else { "btn btn-primary" }.to_string()),
-                                        attributes: vec![("type".to_string(), "button".to_string())],
-                                        on_click: if submitting { None } else { Some(Rc::new(handle_submit)) },
+                                    tag: "div".to_string(),
+                                    props: Box::new(Props {
+                                        class: Some("wizard-actions".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
-                                        if submitting {
-                                            Element::Node {
-                                                tag: "span".to_string(),
-                                                props: Props {
-                                                    class: Some("spinner".to_string()),
-                                                    ..Default::default()
-                                                },
-                                                children: vec![],
-                                            }
-                                        } else {
-                                            Element::Text("Create Account".to_string())
-                                        },
+                                        Button::new("Back")
+                                            .class("btn btn-secondary")
+                                            .button_type("button")
+                                            .disabled(step == 0)
+                                            .on_click(handle_back)
+                                            .render(),
+                                        Button::new(if step + 1 == WIZARD_STEPS.len() { "Create Account" } else { "Next" })
+                                            .class("btn btn-primary")
+                                            .button_type("button")
+                                            .loading(submitting)
+                                            .on_click(handle_next)
+                                            .render(),
                                     ],
                                 },
                             ],
@@ -478,18 +525,18 @@ impl Component for BeautifulForm {
                         // Footer
                         Element::Node {
                             tag: "footer".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Text("Built with ".to_string()),
                                 Element::Node {
                                     tag: "a".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![
                                             ("href".to_string(), "https://github.com/anthropics/layer9".to_string()),
                                             ("target".to_string(), "_blank".to_string()),
                                         ],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Layer9".to_string())],
                                 },
                                 Element::Text(" • Form Validation Demo".to_string()),
@@ -502,6 +549,45 @@ impl Component for BeautifulForm {
     }
 }
 
+/// One wizard step's fields, hidden unless it's the current step
+fn wizard_panel(current: bool, fields: Vec<Element>) -> Element {
+    let mut attributes = vec![];
+    if !current {
+        attributes.push(("hidden".to_string(), "hidden".to_string()));
+    }
+    Element::Node {
+        tag: "div".to_string(),
+        props: Box::new(Props {
+            class: Some("wizard-panel".to_string()),
+            attributes,
+            ..Default::default()
+        }),
+        children: fields,
+    }
+}
+
+/// Index of the first wizard step with a validation error
+fn first_step_with_errors(errors: &FormErrors) -> Option<usize> {
+    if errors.name.is_some() || errors.email.is_some() {
+        Some(0)
+    } else if errors.password.is_some() || errors.confirm_password.is_some() {
+        Some(1)
+    } else if errors.age.is_some() || errors.terms.is_some() {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Only the errors for the fields on `step`
+fn errors_for_step(errors: FormErrors, step: usize) -> FormErrors {
+    match step {
+        0 => FormErrors { name: errors.name, email: errors.email, ..Default::default() },
+        1 => FormErrors { password: errors.password, confirm_password: errors.confirm_password, ..Default::default() },
+        _ => FormErrors { age: errors.age, terms: errors.terms, ..Default::default() },
+    }
+}
+
 fn create_input_field(
     label: &str,
     input_type: &str,
@@ -513,28 +599,39 @@ fn create_input_field(
 ) -> Element {
     let field_name = field_name.to_string();
     let has_error = error.is_some();
+    let message_id = format!("{}-error", field_name);
+
+    let mut input_attributes = vec![
+        ("type".to_string(), input_type.to_string()),
+        ("name".to_string(), field_name.clone()),
+        ("placeholder".to_string(), placeholder.to_string()),
+        ("value".to_string(), value.to_string()),
+    ];
+    if has_error {
+        input_attributes.push(("aria-invalid".to_string(), "true".to_string()));
+        input_attributes.push(("aria-describedby".to_string(), message_id.clone()));
+    }
     
     Element::Node {
         tag: "div".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some(if has_error { "form-group error" } else { "form-group" }.to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "label".to_string(),
-                props: Props::default(),
+                props: Box::new(Props {
+                    attributes: vec![("for".to_string(), field_name.clone())],
+                    ..Default::default()
+                }),
                 children: vec![Element::Text(label.to_string())],
             },
             Element::Node {
                 tag: "input".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     id: Some(field_name.clone()),
-                    attributes: vec![
-                        ("type".to_string(), input_type.to_string()),
-                        ("placeholder".to_string(), placeholder.to_string()),
-                        ("value".to_string(), value.to_string()),
-                    ],
+                    attributes: input_attributes,
                     on_click: Some(Rc::new({
                         let field_name = field_name.clone();
                         move || {
@@ -549,27 +646,77 @@ fn create_input_field(
                         }
                     })),
                     ..Default::default()
-                },
+                }),
                 children: vec![],
             },
-            if let Some(err) = error {
-                Element::Node {
+            show_else(
+                has_error,
+                || Element::Node {
                     tag: "span".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("error-message".to_string()),
+                        id: Some(message_id.clone()),
+                        attributes: vec![("role".to_string(), "alert".to_string())],
                         ..Default::default()
-                    },
-                    children: vec![Element::Text(err.clone())],
-                }
-            } else {
-                Element::Node {
+                    }),
+                    children: vec![Element::Text(error.cloned().unwrap_or_default())],
+                },
+                || Element::Node {
                     tag: "span".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("helper-text".to_string()),
+                        id: Some(message_id.clone()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![],
-                }
+                },
+            ),
+        ],
+    }
+}
+
+fn create_age_field(age: Option<u32>, error: Option<&String>, update_age: impl Fn(u32) + 'static) -> Element {
+    let mut input = NumberInput::new()
+        .id("age")
+        .min(18u32)
+        .max(120)
+        .value(age)
+        .placeholder("18")
+        .on_change(update_age)
+        .attribute("aria-describedby", "age-error");
+    if error.is_some() {
+        input = input.attribute("aria-invalid", "true");
+    }
+
+    Element::Node {
+        tag: "div".to_string(),
+        props: Box::new(Props {
+            class: Some(if error.is_some() { "form-group error" } else { "form-group" }.to_string()),
+            ..Default::default()
+        }),
+        children: vec![
+            Element::Node {
+                tag: "label".to_string(),
+                props: Box::new(Props {
+                    attributes: vec![("for".to_string(), "age".to_string())],
+                    ..Default::default()
+                }),
+                children: vec![Element::Text("Age".to_string())],
+            },
+            input.render(),
+            Element::Node {
+                tag: "span".to_string(),
+                props: Box::new(Props {
+                    class: Some(if error.is_some() { "error-message" } else { "helper-text" }.to_string()),
+                    id: Some("age-error".to_string()),
+                    attributes: if error.is_some() {
+                        vec![("role".to_string(), "alert".to_string())]
+                    } else {
+                        vec![]
+                    },
+                    ..Default::default()
+                }),
+                children: error.map(|e| Element::Text(e.clone())).into_iter().collect(),
             },
         ],
     }
@@ -750,6 +897,31 @@ const FORM_STYLES: &str = r#"
         box-shadow: 0 0 0 3px rgba(59, 130, 246, 0.1);
     }
     
+    .number-input {
+        display: flex !important;
+        border: 2px solid #e2e8f0 !important;
+        border-radius: 10px !important;
+        transition: all 0.3s ease;
+    }
+    
+    .number-input input[type="text"] {
+        border: 0;
+        border-radius: 0;
+    }
+    
+    .number-input input:focus {
+        box-shadow: none;
+    }
+    
+    .number-input:focus-within {
+        border-color: var(--form-primary) !important;
+        box-shadow: 0 0 0 3px rgba(59, 130, 246, 0.1);
+    }
+    
+    .form-group.error .number-input {
+        border-color: var(--form-error) !important;
+    }
+    
     .form-group.error input {
         border-color: var(--form-error);
     }
@@ -841,6 +1013,29 @@ const FORM_STYLES: &str = r#"
         box-shadow: 0 6px 20px rgba(59, 130, 246, 0.4);
     }
     
+    .btn-secondary {
+        background: #f1f5f9;
+        color: #334155;
+    }
+    
+    .btn:disabled {
+        opacity: 0.5;
+        cursor: not-allowed;
+    }
+    
+    .steps {
+        margin-bottom: 28px;
+    }
+    
+    .wizard-actions {
+        display: flex;
+        gap: 12px;
+    }
+    
+    .wizard-actions .btn {
+        flex: 1;
+    }
+    
     .btn.loading {
         opacity: 0.8;
         cursor: not-allowed;
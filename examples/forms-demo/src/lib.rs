@@ -1,5 +1,6 @@
 use layer9_core::prelude::*;
-use layer9_core::form::{use_form, Form, FormConfig, FormFields};
+use layer9_core::form::{async_validator, use_form, Form, FormConfig};
+use layer9_macro::FormFields;
 use layer9_core::component::{Element, Props};
 use layer9_core::reactive_v2::mount;
 use serde::{Deserialize, Serialize};
@@ -8,42 +9,17 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::Event;
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, FormFields)]
 struct LoginForm {
     username: String,
     password: String,
-}
-
-impl FormFields for LoginForm {
-    fn get_field(&self, field: &str) -> Option<String> {
-        match field {
-            "username" => Some(self.username.clone()),
-            "password" => Some(self.password.clone()),
-            _ => None,
-        }
-    }
-
-    fn set_field(&mut self, field: &str, value: String) -> Result<(), String> {
-        match field {
-            "username" => {
-                self.username = value;
-                Ok(())
-            }
-            "password" => {
-                self.password = value;
-                Ok(())
-            }
-            _ => Err(format!("Unknown field: {}", field)),
-        }
-    }
-    
-    fn field_names(&self) -> Vec<&'static str> {
-        vec!["username", "password"]
-    }
+    /// Left empty to sign in to the default server
+    server: String,
 }
 
 struct LoginComponent {
     form: Form<LoginForm>,
+    advanced: State<bool>,
 }
 
 impl LoginComponent {
@@ -77,6 +53,15 @@ impl LoginComponent {
                 
                 errors
             })),
+            validate_field_async: HashMap::from([(
+                "username".to_string(),
+                async_validator(|username: String| async move {
+                    // Stand-in for asking the server whether the name is free
+                    ["admin", "root"]
+                        .contains(&username.to_lowercase().as_str())
+                        .then(|| format!("Username \"{}\" is taken", username))
+                }),
+            )]),
             on_submit: Box::new(|values| {
                 let values_clone = values.clone();
                 Box::pin(async move {
@@ -87,7 +72,10 @@ impl LoginComponent {
             }),
         });
         
-        LoginComponent { form }
+        LoginComponent {
+            form,
+            advanced: use_state(|| false),
+        }
     }
 }
 
@@ -107,36 +95,33 @@ impl Component for LoginComponent {
             form_clone.set_field_value("password", value);
         };
         
+        let form_clone = self.form.clone();
+        let server_handler = move |value: String| {
+            form_clone.set_field_value("server", value);
+        };
+        
         let form_clone = self.form.clone();
         let submit_handler = move |_event: Event| {
             form_clone.handle_submit()();
         };
         
-        let username_error = if let Some(errs) = errors.get("username") {
-            Element::Node {
-                tag: "div".to_string(),
-                props: Props {
-                    class: Some("error".to_string()),
-                    ..Default::default()
-                },
-                children: vec![Element::Text(errs.join(", "))],
-            }
-        } else {
-            Element::Text("".to_string())
-        };
+        let username_error = show(errors.contains_key("username"), || Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("error".to_string()),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(errors["username"].join(", "))],
+        });
         
-        let password_error = if let Some(errs) = errors.get("password") {
-            Element::Node {
-                tag: "div".to_string(),
-                props: Props {
-                    class: Some("error".to_string()),
-                    ..Default::default()
-                },
-                children: vec![Element::Text(errs.join(", "))],
-            }
-        } else {
-            Element::Text("".to_string())
-        };
+        let password_error = show(errors.contains_key("password"), || Element::Node {
+            tag: "div".to_string(),
+            props: Box::new(Props {
+                class: Some("error".to_string()),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(errors["password"].join(", "))],
+        });
         
         let button_text = if is_submitting {
             "Logging in..."
@@ -147,7 +132,7 @@ impl Component for LoginComponent {
         // Create input elements with proper attributes
         let username_input = Element::Node {
             tag: "input".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: vec![
                     ("type".to_string(), "text".to_string()),
                     ("value".to_string(), values.username.clone()),
@@ -155,13 +140,13 @@ impl Component for LoginComponent {
                 ],
                 on_change: Some(Rc::new(username_handler)),
                 ..Default::default()
-            },
+            }),
             children: vec![],
         };
         
         let password_input = Element::Node {
             tag: "input".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 attributes: vec![
                     ("type".to_string(), "password".to_string()),
                     ("value".to_string(), values.password.clone()),
@@ -169,39 +154,76 @@ impl Component for LoginComponent {
                 ],
                 on_change: Some(Rc::new(password_handler)),
                 ..Default::default()
-            },
+            }),
             children: vec![],
         };
         
+        let advanced_options = Disclosure::new("Show advanced options")
+            .id("advanced-options")
+            .state(self.advanced.clone())
+            .animated(true)
+            .children(vec![Element::Node {
+                tag: "div".to_string(),
+                props: Box::new(Props {
+                    class: Some("form-group".to_string()),
+                    ..Default::default()
+                }),
+                children: vec![
+                    Element::Node {
+                        tag: "label".to_string(),
+                        props: Box::new(Props {
+                            attributes: vec![("for".to_string(), "server".to_string())],
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text("Server".to_string())],
+                    },
+                    Element::Node {
+                        tag: "input".to_string(),
+                        props: Box::new(Props {
+                            id: Some("server".to_string()),
+                            attributes: vec![
+                                ("type".to_string(), "text".to_string()),
+                                ("value".to_string(), values.server.clone()),
+                                ("placeholder".to_string(), "Default server".to_string()),
+                            ],
+                            on_change: Some(Rc::new(server_handler)),
+                            ..Default::default()
+                        }),
+                        children: vec![],
+                    },
+                ],
+            }])
+            .render();
+        
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("container".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Login Form Demo".to_string())],
                 },
                 Element::Node {
                     tag: "form".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         on_submit: Some(Rc::new(submit_handler)),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("form-group".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "label".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![Element::Text("Username".to_string())],
                                 },
                                 username_input,
@@ -210,42 +232,43 @@ impl Component for LoginComponent {
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("form-group".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "label".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![Element::Text("Password".to_string())],
                                 },
                                 password_input,
                                 password_error,
                             ],
                         },
+                        advanced_options,
                         Element::Node {
                             tag: "button".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![Element::Text(button_text.to_string())],
                         },
                     ],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("debug".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "h3".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![Element::Text("Form State Debug".to_string())],
                         },
                         Element::Node {
                             tag: "pre".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![Element::Text(format!("{:#?}", values))],
                         },
                     ],
@@ -308,6 +331,26 @@ pub fn main() {
             border-color: #4CAF50;
         }
         
+        .disclosure {
+            margin-bottom: 1.5rem;
+        }
+        
+        .disclosure > button {
+            width: auto;
+            padding: 0.25rem 0;
+            color: #555;
+            background: none;
+        }
+        
+        .disclosure > button:hover {
+            background: none;
+            color: #333;
+        }
+        
+        .disclosure .form-group {
+            margin: 0.75rem 0 0;
+        }
+        
         .error {
             color: #d32f2f;
             font-size: 0.875rem;
@@ -1,11 +1,10 @@
 //! GitHub Dashboard - 2lab.ai style in Layer9
 
+use layer9_core::auth::{OAuthConfig, OAuthProvider};
+use layer9_core::i18n::{use_date_format, use_number_format};
 use layer9_core::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
-use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
 
 // L9: Philosophy
 struct DashboardPhilosophy;
@@ -83,56 +82,13 @@ struct Language {
 }
 
 // L5: Components
-struct GitHubDashboard {
-    stats: Rc<RefCell<Option<GitHubStats>>>,
-    loading: Rc<RefCell<bool>>,
-    error: Rc<RefCell<Option<String>>>,
-}
-
-impl GitHubDashboard {
-    fn new() -> Self {
-        let dashboard = GitHubDashboard {
-            stats: Rc::new(RefCell::new(None)),
-            loading: Rc::new(RefCell::new(true)),
-            error: Rc::new(RefCell::new(None)),
-        };
-
-        // Fetch stats on mount
-        dashboard.fetch_stats();
-
-        dashboard
-    }
-
-    fn fetch_stats(&self) {
-        let stats = self.stats.clone();
-        let loading = self.loading.clone();
-        let error = self.error.clone();
-
-        spawn_local(async move {
-            *loading.borrow_mut() = true;
-
-            match fetch_github_stats().await {
-                Ok(data) => {
-                    *stats.borrow_mut() = Some(data);
-                    *error.borrow_mut() = None;
-                }
-                Err(e) => {
-                    *error.borrow_mut() = Some(e);
-                }
-            }
-
-            *loading.borrow_mut() = false;
-        });
-    }
-}
+struct GitHubDashboard;
 
 impl Component for GitHubDashboard {
     fn render(&self) -> Element {
-        let loading = *self.loading.borrow();
-        let error = self.error.borrow().clone();
-        let stats = self.stats.borrow().clone();
+        let stats = use_query("github-stats", fetch_github_stats);
 
-        if loading {
+        if stats.is_loading {
             return view! {
                 <div class="loading">
                     <h2>"Loading GitHub statistics..."</h2>
@@ -140,16 +96,16 @@ impl Component for GitHubDashboard {
             };
         }
 
-        if let Some(err) = error {
+        if let Some(err) = stats.error {
             return view! {
                 <div class="error">
                     <h2>"Error Loading Stats"</h2>
-                    <p>{err}</p>
+                    <p>{err.to_string()}</p>
                 </div>
             };
         }
 
-        if let Some(stats) = stats {
+        if let Some(stats) = stats.data {
             view! {
                 <div class="dashboard">
                     {StatsGrid::new(&stats).render()}
@@ -180,32 +136,35 @@ impl<'a> StatsGrid<'a> {
 
 impl<'a> Component for StatsGrid<'a> {
     fn render(&self) -> Element {
-        let grid_style = style![grid, lg_grid_cols(4), gap(6),];
+        let numbers = use_number_format();
 
-        view! {
-            <div style={grid_style.build()}>
-                {StatCard::new(
+        Grid::cols(1)
+            .gap(6)
+            .responsive(Breakpoint::Lg, 4)
+            .children(vec![
+                StatCard::new(
                     "Total Commits",
-                    &self.stats.commits.total_count.to_string(),
+                    &numbers.format(self.stats.commits.total_count as f64),
                     "Building consciousness, commit by commit"
-                ).render()}
-                {StatCard::new(
+                ).render(),
+                StatCard::new(
                     "Contributors",
-                    &self.stats.contributors.total_count.to_string(),
+                    &numbers.format(self.stats.contributors.total_count as f64),
                     "Minds merging into HAL9"
-                ).render()}
-                {StatCard::new(
+                ).render(),
+                StatCard::new(
                     "Repository Size",
-                    &format!("{:.1} MB", self.stats.repository.disk_usage as f32 / 1024.0),
+                    // GitHub reports disk usage in KB
+                    &numbers.bytes(self.stats.repository.disk_usage as f64 * 1024.0),
                     "Consciousness compressed"
-                ).render()}
-                {StatCard::new(
+                ).render(),
+                StatCard::new(
                     "Open Issues",
-                    &self.stats.repository.open_issues.to_string(),
+                    &numbers.format(self.stats.repository.open_issues as f64),
                     "Reality bugs to fix"
-                ).render()}
-            </div>
-        }
+                ).render(),
+            ])
+            .render()
     }
 }
 
@@ -284,14 +243,16 @@ impl<'a> RecentCommits<'a> {
 impl<'a> Component for RecentCommits<'a> {
     fn render(&self) -> Element {
         let mut commit_elements = vec![];
+        let dates = use_date_format();
 
         for commit in &self.commits.recent_commits {
+            let committed = dates.relative(js_sys::Date::parse(&commit.date));
             commit_elements.push(view! {
                 <div class="commit-item">
                     {Badge::new(&commit.sha).render()}
                     <div class="commit-details">
                         <p class="commit-message">{&commit.message}</p>
-                        <p class="commit-meta">{&commit.author}" • "{&commit.date}</p>
+                        <p class="commit-meta">{&commit.author}" • "{&committed}</p>
                     </div>
                 </div>
             });
@@ -302,7 +263,7 @@ impl<'a> Component for RecentCommits<'a> {
                 view! { <h3>"Recent Commits"</h3> },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: commit_elements,
                 },
             ])
@@ -334,7 +295,9 @@ impl<'a> Component for LanguageBreakdown<'a> {
                             {format!("{:.1}% ({} lines)", lang.percentage, lang.lines)}
                         </span>
                     </div>
-                    {Progress::new(lang.percentage).render()}
+                    {Progress::new(lang.percentage)
+                        .label(format!("{} share of the codebase", lang.name))
+                        .render()}
                 </div>
             });
         }
@@ -344,7 +307,7 @@ impl<'a> Component for LanguageBreakdown<'a> {
                 view! { <h3>"Technology Stack"</h3> },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: lang_elements,
                 },
             ])
@@ -372,7 +335,7 @@ impl<'a> Component for TopContributors<'a> {
                 <div class="contributor-item">
                     {Avatar::new()
                         .src(&contributor.avatar_url)
-                        .alt(&contributor.login)
+                        .name(&contributor.login)
                         .render()}
                     <div class="contributor-info">
                         <p class="contributor-name">{&contributor.login}</p>
@@ -387,7 +350,7 @@ impl<'a> Component for TopContributors<'a> {
                 view! { <h3>"Top Contributors"</h3> },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: contributor_elements,
                 },
             ])
@@ -439,23 +402,102 @@ async fn fetch_github_stats() -> Result<GitHubStats, String> {
     })
 }
 
+// GitHub sign-in; the client secret lives on the server behind /api/auth/oauth/token
+const GITHUB_CLIENT_ID: &str = match option_env!("GITHUB_CLIENT_ID") {
+    Some(id) => id,
+    None => "",
+};
+const CALLBACK_PATH: &str = "/auth/callback";
+
+fn github_auth() -> AuthService {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    let config = OAuthConfig::new(GITHUB_CLIENT_ID, format!("{}{}", origin, CALLBACK_PATH));
+    AuthService::new(Box::new(OAuthProvider::github(config)))
+}
+
+fn login_with_github() {
+    if let Err(e) = use_auth().login_with_oauth() {
+        web_sys::console::error_1(&e.into());
+    }
+}
+
+// Finishes the GitHub redirect, then heads back to the dashboard
+struct AuthCallback;
+
+impl Component for AuthCallback {
+    fn render(&self) -> Element {
+        let (error, set_error) = use_state_hook(None::<String>);
+
+        use_effect((), move || {
+            let query = web_sys::window()
+                .and_then(|w| w.location().search().ok())
+                .unwrap_or_default();
+            let auth = use_auth();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth.handle_oauth_callback(&query).await {
+                    Ok(()) => {
+                        let _ = navigate("/");
+                    }
+                    Err(e) => set_error(Some(e)),
+                }
+            });
+            || {}
+        });
+
+        match error {
+            None => view! { <p class="auth-callback">"Signing in with GitHub..."</p> },
+            Some(error) => view! {
+                <div class="auth-callback">
+                    <p>{error}</p>
+                    {Button::new("Try again")
+                        .variant(ButtonVariant::Primary)
+                        .on_click(login_with_github)
+                        .render()}
+                </div>
+            },
+        }
+    }
+}
+
 // Main app
 #[wasm_bindgen]
 pub struct App;
 
 impl Layer9App for App {
     fn routes(&self) -> Vec<Route> {
-        vec![Route {
-            path: "/".to_string(),
-            handler: RouteHandler::Page(|| {
-                Page::new()
-                    .title("HAL9 Development Dashboard - 2lab.ai")
-                    .component(MainPage)
-            }),
-        }]
+        vec![
+            Route {
+                path: "/".to_string(),
+                handler: RouteHandler::Page(|| {
+                    Page::new()
+                        .title("HAL9 Development Dashboard - 2lab.ai")
+                        .component(MainPage)
+                }),
+                middleware: vec![],
+            },
+            Route {
+                path: CALLBACK_PATH.to_string(),
+                handler: RouteHandler::Page(|| {
+                    Page::new()
+                        .title("Signing in - HAL9 Development Dashboard")
+                        .component(AuthCallback)
+                }),
+                middleware: vec![],
+            },
+        ]
     }
 
     fn initialize(&self) {
+        // Shares one GitHub-backed service, with any saved session, through use_auth()
+        AuthServiceProvider::new(github_auth());
+        // A saved GitHub session signs in once GitHub confirms its token
+        wasm_bindgen_futures::spawn_local(async {
+            if let Err(e) = use_auth().resume_session().await {
+                web_sys::console::warn_1(&e.into());
+            }
+        });
         inject_global_styles();
         web_sys::console::log_1(&"Layer9 GitHub Dashboard initialized!".into());
     }
@@ -490,16 +532,17 @@ impl Component for MainPage {
                     <h1>"HAL9 Development Dashboard"</h1>
                     <p>"Real-time insights into the consciousness being built"</p>
                     {if auth.user.is_some() {
-                        view! { <p>"Welcome, "{auth.user.unwrap().name}</p> }
+                        view! { <p>"Welcome, "{auth.user.clone().unwrap().username}</p> }
                     } else {
                         Button::new("Login with GitHub")
                             .variant(ButtonVariant::Primary)
+                            .on_click(login_with_github)
                             .render()
                     }}
                 </header>
 
                 <main>
-                    {Protected::new(GitHubDashboard::new())
+                    {Protected::wrap(GitHubDashboard)
                         .fallback(LoginPrompt)
                         .render()}
                 </main>
@@ -524,6 +567,7 @@ impl Component for LoginPrompt {
                     <p>"Sign in to view the HAL9 development dashboard"</p>
                     {Button::new("Login with GitHub")
                         .variant(ButtonVariant::Primary)
+                        .on_click(login_with_github)
                         .render()}
                 </div>
             }])
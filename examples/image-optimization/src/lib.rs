@@ -190,15 +190,15 @@ impl Component for ImageGallery {
     fn render(&self) -> Element {
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("image-gallery".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 // Title
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Layer9 Image Optimization Demo".to_string())],
                 },
                 
@@ -230,14 +230,14 @@ impl Component for ImageGallery {
 fn render_hero_section() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("hero-section".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Hero Image with Responsive Loading".to_string())],
             },
             Image::new("/images/hero.jpg")
@@ -255,22 +255,22 @@ fn render_hero_section() -> Element {
 fn render_lazy_gallery() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("lazy-gallery".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Lazy Loaded Image Gallery".to_string())],
             },
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("grid".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: (1..=12).map(|i| {
                     LazyImage::new(format!("/images/gallery/photo-{}.jpg", i))
                         .alt(format!("Gallery photo {}", i))
@@ -293,14 +293,14 @@ fn render_lazy_gallery() -> Element {
 fn render_art_direction() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("art-direction".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Art Direction with Picture Element".to_string())],
             },
             Picture::new(
@@ -330,14 +330,14 @@ fn render_art_direction() -> Element {
 fn render_blur_placeholder() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("blur-placeholder".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Image with Blur Placeholder".to_string())],
             },
             Image::new("/images/landscape.jpg")
@@ -355,22 +355,22 @@ fn render_blur_placeholder() -> Element {
 fn render_format_demo() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("format-demo".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Format Optimization Demo".to_string())],
             },
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("format-grid".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     render_format_item("Original JPEG", "/images/sample.jpg", "Original JPEG image"),
                     render_format_item("Optimized WebP", "/_layer9/image?src=/images/sample.jpg&f=webp", "WebP optimized image"),
@@ -384,11 +384,11 @@ fn render_format_demo() -> Element {
 fn render_format_item(title: &str, src: &str, alt: &str) -> Element {
     Element::Node {
         tag: "div".to_string(),
-        props: Props::default(),
+        props: Box::default(),
         children: vec![
             Element::Node {
                 tag: "h3".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text(title.to_string())],
             },
             Image::new(src)
@@ -403,14 +403,14 @@ fn render_format_item(title: &str, src: &str, alt: &str) -> Element {
 fn render_background_demo() -> Element {
     Element::Node {
         tag: "section".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some("background-demo".to_string()),
             ..Default::default()
-        },
+        }),
         children: vec![
             Element::Node {
                 tag: "h2".to_string(),
-                props: Props::default(),
+                props: Box::default(),
                 children: vec![Element::Text("Background Image Optimization".to_string())],
             },
             BackgroundImage::new("/images/background.jpg")
@@ -418,19 +418,19 @@ fn render_background_demo() -> Element {
                 .children(vec![
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("content-overlay".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             Element::Node {
                                 tag: "h3".to_string(),
-                                props: Props::default(),
+                                props: Box::default(),
                                 children: vec![Element::Text("Content over optimized background".to_string())],
                             },
                             Element::Node {
                                 tag: "p".to_string(),
-                                props: Props::default(),
+                                props: Box::default(),
                                 children: vec![Element::Text("Background images are also lazily loaded and optimized".to_string())],
                             },
                         ],
@@ -451,43 +451,43 @@ impl Component for QualityDemo {
         
         Element::Node {
             tag: "section".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("quality-demo".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h2".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Quality Settings Demo".to_string())],
                 },
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("quality-demo-container".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("controls".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "label".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![("for".to_string(), "quality-slider".to_string())],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Text(format!("Quality: {}%", quality_value)),
                                     ],
                                 },
                                 Element::Node {
                                     tag: "input".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![
                                             ("type".to_string(), "range".to_string()),
                                             ("id".to_string(), "quality-slider".to_string()),
@@ -496,17 +496,17 @@ impl Component for QualityDemo {
                                             ("value".to_string(), quality_value.to_string()),
                                         ],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![],
                                 },
                             ],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("quality-comparison".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Image::new("/images/quality-test.jpg")
                                     .alt(format!("Image at {}% quality", quality_value))
@@ -133,32 +133,32 @@ impl Component for MemoryGame {
 
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("memory-game".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 // Inline styles
                 Element::Node {
                     tag: "style".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text(GAME_STYLES.to_string())],
                 },
                 
                 // Background animation
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("bg-animation".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: (0..5).map(|i| {
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some(format!("star star-{}", i + 1)),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![],
                         }
                     }).collect(),
@@ -167,26 +167,26 @@ impl Component for MemoryGame {
                 // Game container
                 Element::Node {
                     tag: "div".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("game-container".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         // Header
                         Element::Node {
                             tag: "header".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Node {
                                     tag: "h1".to_string(),
-                                    props: Props::default(),
+                                    props: Box::default(),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("gradient-text".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Memory".to_string())],
                                         },
                                         Element::Text(" Game".to_string()),
@@ -194,10 +194,10 @@ impl Component for MemoryGame {
                                 },
                                 Element::Node {
                                     tag: "p".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("subtitle".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Match the cards to win!".to_string())],
                                 },
                             ],
@@ -206,57 +206,57 @@ impl Component for MemoryGame {
                         // Stats
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("stats".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("stat".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("stat-label".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Moves".to_string())],
                                         },
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("stat-value".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text(moves.to_string())],
                                         },
                                     ],
                                 },
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("stat".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("stat-label".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text("Matches".to_string())],
                                         },
                                         Element::Node {
                                             tag: "span".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("stat-value".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![Element::Text(format!("{}/8", cards.iter().filter(|c| c.is_matched).count() / 2))],
                                         },
                                     ],
@@ -267,14 +267,14 @@ impl Component for MemoryGame {
                         // Game board
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("game-board".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: cards.iter().enumerate().map(|(idx, card)| {
                                 Element::Node {
                                     tag: "div".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some(
                                             if card.is_matched {
                                                 "card matched".to_string()
@@ -286,29 +286,29 @@ impl Component for MemoryGame {
                                         ),
                                         on_click: Some(Rc::new(handle_card_click(idx))),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![
                                         Element::Node {
                                             tag: "div".to_string(),
-                                            props: Props {
+                                            props: Box::new(Props {
                                                 class: Some("card-inner".to_string()),
                                                 ..Default::default()
-                                            },
+                                            }),
                                             children: vec![
                                                 Element::Node {
                                                     tag: "div".to_string(),
-                                                    props: Props {
+                                                    props: Box::new(Props {
                                                         class: Some("card-front".to_string()),
                                                         ..Default::default()
-                                                    },
+                                                    }),
                                                     children: vec![Element::Text("?".to_string())],
                                                 },
                                                 Element::Node {
                                                     tag: "div".to_string(),
-                                                    props: Props {
+                                                    props: Box::new(Props {
                                                         class: Some("card-back".to_string()),
                                                         ..Default::default()
-                                                    },
+                                                    }),
                                                     children: vec![Element::Text(card.emoji.clone())],
                                                 },
                                             ],
@@ -322,28 +322,28 @@ impl Component for MemoryGame {
                         if game_won {
                             Element::Node {
                                 tag: "div".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("win-screen".to_string()),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![
                                     Element::Node {
                                         tag: "h2".to_string(),
-                                        props: Props::default(),
+                                        props: Box::default(),
                                         children: vec![Element::Text("🎉 Congratulations! 🎉".to_string())],
                                     },
                                     Element::Node {
                                         tag: "p".to_string(),
-                                        props: Props::default(),
+                                        props: Box::default(),
                                         children: vec![Element::Text(format!("You won in {} moves!", moves))],
                                     },
                                     Element::Node {
                                         tag: "button".to_string(),
-                                        props: Props {
+                                        props: Box::new(Props {
                                             class: Some("btn btn-primary".to_string()),
                                             on_click: Some(Rc::new(reset_game.clone())),
                                             ..Default::default()
-                                        },
+                                        }),
                                         children: vec![Element::Text("Play Again".to_string())],
                                     },
                                 ],
@@ -351,11 +351,11 @@ impl Component for MemoryGame {
                         } else {
                             Element::Node {
                                 tag: "button".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("btn btn-secondary".to_string()),
                                     on_click: Some(Rc::new(reset_game)),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![Element::Text("New Game".to_string())],
                             }
                         },
@@ -363,18 +363,18 @@ impl Component for MemoryGame {
                         // Footer
                         Element::Node {
                             tag: "footer".to_string(),
-                            props: Props::default(),
+                            props: Box::default(),
                             children: vec![
                                 Element::Text("Built with ".to_string()),
                                 Element::Node {
                                     tag: "a".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         attributes: vec![
                                             ("href".to_string(), "https://github.com/anthropics/layer9".to_string()),
                                             ("target".to_string(), "_blank".to_string()),
                                         ],
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("Layer9".to_string())],
                                 },
                                 Element::Text(" • Interactive Game Demo".to_string()),
@@ -1,6 +1,7 @@
-use layer9_core::middleware_v2::{Context, Middleware, MiddlewareError, MiddlewareStack, Next, Request, Response, RouteParams};
+use layer9_core::middleware_v2::{AuthMiddleware, RateLimitMiddleware, Context, Middleware, MiddlewareError, MiddlewareStack, Next, Request, Response, RouteParams};
 use layer9_core::prelude::*;
 use std::collections::HashMap;
+use std::time::Duration;
 use async_trait::async_trait;
 use wasm_bindgen::prelude::*;
 use web_sys::console;
@@ -20,7 +21,7 @@ impl Middleware for AddHeaderMiddleware {
         ctx.request.headers.insert(self.header_name.clone(), self.header_value.clone());
         
         // Call next middleware
-        let mut response = next().await?;
+        let mut response = next(ctx).await?;
         
         // Also add to response
         response.headers.insert(self.header_name.clone(), self.header_value.clone());
@@ -37,11 +38,11 @@ struct ModifyBodyMiddleware {
 
 #[async_trait(?Send)]
 impl Middleware for ModifyBodyMiddleware {
-    async fn handle(&self, _ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
         console::log_1(&format!("Middleware 2: Processing with prefix '{}'", self.prefix).into());
         
         // Call next middleware
-        let mut response = next().await?;
+        let mut response = next(ctx).await?;
         
         // Modify the body
         if let Some(body) = response.body {
@@ -60,16 +61,13 @@ struct FinalResponseMiddleware {
 
 #[async_trait(?Send)]
 impl Middleware for FinalResponseMiddleware {
-    async fn handle(&self, ctx: &mut Context, next: Next) -> Result<Response, MiddlewareError> {
+    async fn handle(&self, _ctx: &mut Context, _next: Next) -> Result<Response, MiddlewareError> {
         console::log_1(&format!("Middleware 3: Setting final response to '{}'", self.message).into());
         
-        // Set a response in context
-        ctx.response = Response::new()
+        // Returning without calling next ends the chain with this response
+        Ok(Response::new()
             .with_status(200)
-            .with_body(self.message.clone());
-        
-        // Call next (which should just return the response)
-        next().await
+            .with_body(self.message.clone()))
     }
 }
 
@@ -113,6 +111,7 @@ pub async fn main() {
                 headers: HashMap::new(),
                 body: None,
                 user: None,
+                peer_addr: None,
             },
             response: Response::new(),
             state: HashMap::new(),
@@ -158,6 +157,7 @@ pub async fn main() {
                 headers: HashMap::new(),
                 body: None,
                 user: None,
+                peer_addr: None,
             },
             response: Response::new(),
             state: HashMap::new(),
@@ -191,6 +191,7 @@ pub async fn main() {
                 headers: HashMap::new(),
                 body: None,
                 user: None,
+                peer_addr: None,
             },
             response: Response::new().with_status(204).with_body("Default response"),
             state: HashMap::new(),
@@ -237,6 +238,7 @@ pub async fn main() {
                 headers: HashMap::new(),
                 body: None,
                 user: None,
+                peer_addr: None,
             },
             response: Response::new().with_body("Test"),
             state: HashMap::new(),
@@ -260,5 +262,86 @@ pub async fn main() {
         }
     }
     
+    // Test 5: Auth middleware rejects anonymous requests when required
+    console::log_1(&"\n=== Test 5: Auth Middleware ===".into());
+    {
+        let stack = MiddlewareStack::new()
+            .use_middleware(AuthMiddleware::with_secret("test-secret").require_auth(true))
+            .use_middleware(FinalResponseMiddleware {
+                message: "Should not reach here".to_string(),
+            });
+        
+        let ctx = Context {
+            request: Request {
+                method: Method::GET,
+                url: "/account".to_string(),
+                headers: HashMap::from([("Authorization".to_string(), "Bearer not-a-token".to_string())]),
+                body: None,
+                user: None,
+                peer_addr: None,
+            },
+            response: Response::new(),
+            state: HashMap::new(),
+            params: RouteParams {
+                params: HashMap::new(),
+                query: HashMap::new(),
+            },
+        };
+        
+        match stack.run(ctx).await {
+            Ok(_) => {
+                console::log_1(&"Unexpected success!".into());
+            }
+            Err(e) => {
+                console::log_1(&format!("Expected error: {} (status: {})", e.message, e.status).into());
+                assert_eq!(e.status, 401);
+            }
+        }
+    }
+    
+    // Test 6: Rate limiting answers 429 once the limit is used up
+    console::log_1(&"\n=== Test 6: Rate Limit Middleware ===".into());
+    {
+        let stack = MiddlewareStack::new()
+            .use_middleware(RateLimitMiddleware::new(2, Duration::from_secs(60), RateLimitMiddleware::client_key(0)))
+            .use_middleware(FinalResponseMiddleware {
+                message: "OK".to_string(),
+            });
+        
+        for attempt in 1..=3 {
+            let ctx = Context {
+                request: Request {
+                    method: Method::GET,
+                    url: "/api".to_string(),
+                    headers: HashMap::new(),
+                    body: None,
+                    user: None,
+                    peer_addr: Some("203.0.113.7".parse().unwrap()),
+                },
+                response: Response::new(),
+                state: HashMap::new(),
+                params: RouteParams {
+                    params: HashMap::new(),
+                    query: HashMap::new(),
+                },
+            };
+            
+            match stack.run(ctx).await {
+                Ok(response) => {
+                    console::log_1(&format!("Request {}: status {}, headers {:?}", attempt, response.status, response.headers).into());
+                    if attempt < 3 {
+                        assert_eq!(response.status, 200);
+                    } else {
+                        assert_eq!(response.status, 429);
+                        assert!(response.headers.contains_key("Retry-After"));
+                    }
+                }
+                Err(e) => {
+                    console::log_1(&format!("Error: {} (status: {})", e.message, e.status).into());
+                }
+            }
+        }
+    }
+    
     console::log_1(&"\nAll tests completed!".into());
 }
\ No newline at end of file
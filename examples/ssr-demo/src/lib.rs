@@ -15,6 +15,70 @@ pub struct Todo {
     pub done: bool,
 }
 
+/// Site navigation, shared by every page. The server renders it; the client
+/// takes it over so it responds to the keyboard.
+pub fn site_nav() -> MenuBar {
+    MenuBar::new(
+        "Main",
+        vec![
+            MenuItem::link("Home", "/"),
+            MenuItem::link("Todos", "/todos"),
+            MenuItem::submenu(
+                "About",
+                vec![
+                    MenuItem::link("Overview", "/about"),
+                    MenuItem::link("Server rendering", "/about#ssr"),
+                    MenuItem::link("Hydration", "/about#hydration"),
+                ],
+            ),
+        ],
+    )
+    .id("site-menu")
+}
+
+/// Element with a class and children, for building pages without markup strings.
+#[cfg(not(target_arch = "wasm32"))]
+fn el(tag: &str, class: &str, children: Vec<Element>) -> Element {
+    Element::Node {
+        tag: tag.to_string(),
+        props: Box::new(Props {
+            class: (!class.is_empty()).then(|| class.to_string()),
+            ..Default::default()
+        }),
+        children,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn text(tag: &str, content: &str) -> Element {
+    el(tag, "", vec![Element::Text(content.to_string())])
+}
+
+/// Page frame shared by every route: a sticky header holding the site nav,
+/// with the page content scrolling beneath it.
+#[cfg(not(target_arch = "wasm32"))]
+fn shell(content: Vec<Element>) -> String {
+    let nav = Element::Node {
+        tag: "nav".to_string(),
+        props: Box::new(Props {
+            id: Some("site-nav".to_string()),
+            ..Default::default()
+        }),
+        children: vec![site_nav().render()],
+    };
+
+    AppShell::new()
+        .header(el("div", "container", vec![text("h1", "Layer9 SSR Demo"), nav]))
+        .footer(el(
+            "div",
+            "container",
+            vec![text("p", "Built with Layer9 - Web Architecture Rust Platform")],
+        ))
+        .child(el("div", "container", content))
+        .render()
+        .to_html()
+}
+
 /// Home page component
 pub struct HomePage;
 
@@ -34,115 +98,124 @@ impl Default for HomePage {
 #[async_trait]
 impl SSRComponent for HomePage {
     fn render_to_string(&self, ctx: &SSRContext) -> String {
-        let todos_html = if let Some(todos_json) = ctx.props.get("todos") {
+        let todo_items = if let Some(todos_json) = ctx.props.get("todos") {
             if let Ok(todos) = serde_json::from_str::<Vec<Todo>>(todos_json) {
-                todos.iter()
+                todos
+                    .iter()
                     .map(|todo| {
-                        format!(
-                            r#"<li class="todo-item">
-                                <input type="checkbox" {} />
-                                <span class="{}">{}</span>
-                            </li>"#,
-                            if todo.done { "checked" } else { "" },
-                            if todo.done { "done" } else { "" },
-                            todo.text
+                        let mut checkbox = Props::default();
+                        checkbox.attributes.push(("type".to_string(), "checkbox".to_string()));
+                        if todo.done {
+                            checkbox.attributes.push(("checked".to_string(), "checked".to_string()));
+                        }
+                        el(
+                            "li",
+                            "todo-item",
+                            vec![
+                                Element::Node {
+                                    tag: "input".to_string(),
+                                    props: Box::new(checkbox),
+                                    children: vec![],
+                                },
+                                el(
+                                    "span",
+                                    if todo.done { "done" } else { "" },
+                                    vec![Element::Text(todo.text.clone())],
+                                ),
+                            ],
                         )
                     })
-                    .collect::<Vec<_>>()
-                    .join("\n")
+                    .collect()
             } else {
-                "<li>Failed to load todos</li>".to_string()
+                vec![text("li", "Failed to load todos")]
             }
         } else {
-            "<li>Loading todos...</li>".to_string()
+            vec![text("li", "Loading todos...")]
         };
 
+        let page = shell(vec![
+            el(
+                "section",
+                "hero",
+                vec![
+                    text("h2", "Welcome to Layer9 SSR"),
+                    text("p", "This demo shows server-side rendering with hydration."),
+                ],
+            ),
+            el(
+                "section",
+                "todos",
+                vec![text("h3", "Recent Todos"), el("ul", "todo-list", todo_items)],
+            ),
+        ]);
+
         format!(
-            r#"
-            <div class="container">
-                <header>
-                    <h1>Layer9 SSR Demo</h1>
-                    <nav>
-                        <a href="/">Home</a>
-                        <a href="/todos">Todos</a>
-                        <a href="/about">About</a>
-                    </nav>
-                </header>
-                
-                <main>
-                    <section class="hero">
-                        <h2>Welcome to Layer9 SSR</h2>
-                        <p>This demo shows server-side rendering with hydration.</p>
-                    </section>
-                    
-                    <section class="todos">
-                        <h3>Recent Todos</h3>
-                        <ul class="todo-list">
-                            {}
-                        </ul>
-                    </section>
-                </main>
-                
-                <footer>
-                    <p>Built with Layer9 - Web Architecture Rust Platform</p>
-                </footer>
-                
-                <style>
-                    body {{
-                        font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
-                        margin: 0;
-                        padding: 0;
-                        background: #f5f5f5;
-                    }}
-                    .container {{
-                        max-width: 1200px;
-                        margin: 0 auto;
-                        padding: 20px;
-                    }}
-                    header {{
-                        background: #333;
-                        color: white;
-                        padding: 20px;
-                        margin: -20px -20px 20px;
-                    }}
-                    nav a {{
-                        color: white;
-                        text-decoration: none;
-                        margin-right: 20px;
-                    }}
-                    .hero {{
-                        background: white;
-                        padding: 40px;
-                        text-align: center;
-                        border-radius: 8px;
-                        margin-bottom: 20px;
-                    }}
-                    .todos {{
-                        background: white;
-                        padding: 20px;
-                        border-radius: 8px;
-                    }}
-                    .todo-list {{
-                        list-style: none;
-                        padding: 0;
-                    }}
-                    .todo-item {{
-                        padding: 10px;
-                        border-bottom: 1px solid #eee;
-                    }}
-                    .done {{
-                        text-decoration: line-through;
-                        opacity: 0.6;
-                    }}
-                    footer {{
-                        text-align: center;
-                        margin-top: 40px;
-                        color: #666;
-                    }}
-                </style>
-            </div>
+            r#"{}
+            <style>
+                body {{
+                    font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+                    margin: 0;
+                    padding: 0;
+                    background: #f5f5f5;
+                }}
+                .container {{
+                    max-width: 1200px;
+                    margin: 0 auto;
+                    padding: 20px;
+                }}
+                .app-shell-header {{
+                    background: #333;
+                    color: white;
+                }}
+                .menubar > li > [role="menuitem"] {{
+                    color: white;
+                    text-decoration: none;
+                    background: none;
+                    border: 0;
+                    font: inherit;
+                    cursor: pointer;
+                }}
+                .menubar [role="menu"] [role="menuitem"] {{
+                    display: block;
+                    padding: 8px 12px;
+                    color: #333;
+                    text-decoration: none;
+                }}
+                .menubar [role="menuitem"]:focus {{
+                    outline: 2px solid #4f9cf9;
+                    outline-offset: 2px;
+                }}
+                .hero {{
+                    background: white;
+                    padding: 40px;
+                    text-align: center;
+                    border-radius: 8px;
+                    margin-bottom: 20px;
+                }}
+                .todos {{
+                    background: white;
+                    padding: 20px;
+                    border-radius: 8px;
+                }}
+                .todo-list {{
+                    list-style: none;
+                    padding: 0;
+                }}
+                .todo-item {{
+                    padding: 10px;
+                    border-bottom: 1px solid #eee;
+                }}
+                .done {{
+                    text-decoration: line-through;
+                    opacity: 0.6;
+                }}
+                .app-shell-footer {{
+                    text-align: center;
+                    color: #666;
+                }}
+            </style>
             "#,
-            todos_html
+            page
         )
     }
 
@@ -169,27 +242,37 @@ pub struct TodosPage;
 #[async_trait]
 impl SSRComponent for TodosPage {
     fn render_to_string(&self, _ctx: &SSRContext) -> String {
-        r#"
-        <div class="container">
-            <header>
-                <h1>Layer9 SSR Demo</h1>
-                <nav>
-                    <a href="/">Home</a>
-                    <a href="/todos">Todos</a>
-                    <a href="/about">About</a>
-                </nav>
-            </header>
-            
-            <main>
-                <h2>Todo List</h2>
-                <div class="todo-app">
-                    <input type="text" placeholder="What needs to be done?" />
-                    <button>Add Todo</button>
-                    <ul class="todo-list" id="todos"></ul>
-                </div>
-            </main>
-        </div>
-        "#.to_string()
+        let mut input = Props::default();
+        input.attributes = vec![
+            ("type".to_string(), "text".to_string()),
+            ("placeholder".to_string(), "What needs to be done?".to_string()),
+        ];
+        let list = Props {
+            class: Some("todo-list".to_string()),
+            id: Some("todos".to_string()),
+            ..Default::default()
+        };
+
+        shell(vec![
+            text("h2", "Todo List"),
+            el(
+                "div",
+                "todo-app",
+                vec![
+                    Element::Node {
+                        tag: "input".to_string(),
+                        props: Box::new(input),
+                        children: vec![],
+                    },
+                    text("button", "Add Todo"),
+                    Element::Node {
+                        tag: "ul".to_string(),
+                        props: Box::new(list),
+                        children: vec![],
+                    },
+                ],
+            ),
+        ])
     }
 }
 
@@ -200,30 +283,30 @@ pub struct AboutPage;
 #[async_trait]
 impl SSRComponent for AboutPage {
     fn render_to_string(&self, _ctx: &SSRContext) -> String {
-        r#"
-        <div class="container">
-            <header>
-                <h1>Layer9 SSR Demo</h1>
-                <nav>
-                    <a href="/">Home</a>
-                    <a href="/todos">Todos</a>
-                    <a href="/about">About</a>
-                </nav>
-            </header>
-            
-            <main>
-                <h2>About Layer9 SSR</h2>
-                <p>This demo showcases Layer9's server-side rendering capabilities:</p>
-                <ul>
-                    <li>Server-side data fetching</li>
-                    <li>HTML generation on the server</li>
-                    <li>Client-side hydration</li>
-                    <li>SEO-friendly pages</li>
-                    <li>Fast initial page loads</li>
-                </ul>
-            </main>
-        </div>
-        "#.to_string()
+        let anchored = |id: &str, content: &str| Element::Node {
+            tag: "li".to_string(),
+            props: Box::new(Props {
+                id: Some(id.to_string()),
+                ..Default::default()
+            }),
+            children: vec![Element::Text(content.to_string())],
+        };
+
+        shell(vec![
+            text("h2", "About Layer9 SSR"),
+            text("p", "This demo showcases Layer9's server-side rendering capabilities:"),
+            el(
+                "ul",
+                "",
+                vec![
+                    text("li", "Server-side data fetching"),
+                    anchored("ssr", "HTML generation on the server"),
+                    anchored("hydration", "Client-side hydration"),
+                    text("li", "SEO-friendly pages"),
+                    text("li", "Fast initial page loads"),
+                ],
+            ),
+        ])
     }
 }
 
@@ -320,5 +403,11 @@ pub fn main() {
         if js_sys::Reflect::has(&window, &"__SSR_CONTEXT__".into()).unwrap_or(false) {
             hydrate_app();
         }
+
+        // Swap the static nav for the interactive menubar
+        if let Some(nav) = window.document().and_then(|d| d.get_element_by_id("site-nav")) {
+            nav.set_inner_html("");
+            mount(Box::new(site_nav()), "site-nav");
+        }
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use layer9_framework::prelude::*;
-use layer9_framework::hooks::use_state;
+use layer9_framework::hooks::{use_ref, use_state};
+use layer9_framework::i18n::{init_i18n, use_i18n, Locale, Messages, TranslationCatalog, TranslationValue};
 use layer9_framework::reactive_v2::mount;
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
@@ -16,14 +17,17 @@ struct Todo {
     text: String,
     completed: bool,
     created_at: String,
+    #[serde(default)]
+    labels: Vec<String>,
 }
 
 impl Todo {
-    fn new(text: String) -> Self {
+    fn new(text: String, labels: Vec<String>) -> Self {
         Todo {
             id: Uuid::new_v4().to_string(),
             text,
             completed: false,
+            labels,
             created_at: {
                 // Use JavaScript Date for WASM compatibility
                 let date = js_sys::Date::new_0();
@@ -52,10 +56,13 @@ impl Component for TodoApp {
     fn render(&self) -> Element {
         let (todos, set_todos) = use_state(Vec::<Todo>::new());
         let (filter, set_filter) = use_state(Filter::All);
+        // Labels for the next todo, cleared once it is added
+        let labels = use_ref(State::new(Vec::<String>::new())).borrow().clone();
 
         // Calculate stats
         let active_count = todos.iter().filter(|t| !t.completed).count();
         let completed_count = todos.iter().filter(|t| t.completed).count();
+        let i18n = use_i18n();
 
         // Filter todos
         let filtered_todos: Vec<Todo> = todos
@@ -72,6 +79,7 @@ impl Component for TodoApp {
         let add_todo_handler = {
             let todos = todos.clone();
             let set_todos = set_todos.clone();
+            let labels = labels.clone();
             Rc::new(move || {
                 let window = web_sys::window().unwrap();
                 let document = window.document().unwrap();
@@ -80,9 +88,10 @@ impl Component for TodoApp {
                         let text = input.value();
                         if !text.trim().is_empty() {
                             let mut new_todos = todos.clone();
-                            new_todos.push(Todo::new(text.trim().to_string()));
+                            new_todos.push(Todo::new(text.trim().to_string(), labels.get()));
                             set_todos(new_todos);
                             input.set_value("");
+                            labels.set(vec![]);
                         }
                     }
                 }
@@ -92,28 +101,28 @@ impl Component for TodoApp {
         // Build UI elements
         let style_element = Element::Node {
             tag: "style".to_string(),
-            props: Props::default(),
+            props: Box::default(),
             children: vec![Element::Text(STYLES.to_string())],
         };
 
         let header = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("header".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "h1".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![Element::Text("Layer9 Todo".to_string())],
                 },
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("subtitle".to_string()),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("A beautiful todo app built with Rust and WASM".to_string())],
                 },
             ],
@@ -121,14 +130,14 @@ impl Component for TodoApp {
 
         let input_section = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("input-section".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "input".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("todo-input".to_string()),
                         id: Some("todo-input".to_string()),
                         attributes: vec![
@@ -136,47 +145,60 @@ impl Component for TodoApp {
                             ("placeholder".to_string(), "What needs to be done?".to_string()),
                         ],
                         ..Default::default()
-                    },
+                    }),
                     children: vec![],
                 },
                 Element::Node {
                     tag: "button".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some("add-btn".to_string()),
                         on_click: Some(add_todo_handler),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![Element::Text("Add".to_string())],
                 },
+                Element::Node {
+                    tag: "div".to_string(),
+                    props: Box::new(Props {
+                        class: Some("label-input".to_string()),
+                        ..Default::default()
+                    }),
+                    children: vec![TagInput::new(labels)
+                        .id("todo-labels")
+                        .label("Labels")
+                        .placeholder("Add labels, e.g. home, urgent")
+                        .max_tags(5)
+                        .render()],
+                },
             ],
         };
 
         let todos_container = if filtered_todos.is_empty() && todos.is_empty() {
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("todos-container".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("empty-state".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             Element::Node {
                                 tag: "p".to_string(),
-                                props: Props::default(),
+                                props: Box::default(),
                                 children: vec![Element::Text("No todos yet!".to_string())],
                             },
                             Element::Node {
                                 tag: "p".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("hint".to_string()),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![Element::Text("Add one above to get started".to_string())],
                             },
                         ],
@@ -186,29 +208,32 @@ impl Component for TodoApp {
         } else if filtered_todos.is_empty() {
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("todos-container".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("empty-state".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             Element::Node {
                                 tag: "p".to_string(),
-                                props: Props::default(),
-                                children: vec![Element::Text(format!("No {} todos", filter_text(&filter)))],
+                                props: Box::default(),
+                                children: vec![Element::Text((i18n.translate)(
+                                    "todos.empty",
+                                    Some(&[("filter".to_string(), filter_text(&filter).to_string())].into()),
+                                ))],
                             },
                         ],
                     },
                 ],
             }
         } else {
-            let todo_items: Vec<Element> = filtered_todos.iter().map(|todo| {
+            let todo_items = list(&filtered_todos, |todo| {
                 let todo_id = todo.id.clone();
                 let is_completed = todo.completed;
                 
@@ -221,28 +246,25 @@ impl Component for TodoApp {
                 let set_todos_clone_delete = set_todos.clone();
                 let todos_clone_delete = todos.clone();
                 
-                Element::Node {
+                let item = Element::Node {
                     tag: "li".to_string(),
-                    props: Props {
+                    props: Box::new(Props {
                         class: Some(if is_completed { "todo-item completed".to_string() } else { "todo-item".to_string() }),
                         ..Default::default()
-                    },
+                    }),
                     children: vec![
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("todo-content".to_string()),
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![
                                 Element::Node {
                                     tag: "input".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("todo-checkbox".to_string()),
-                                        attributes: vec![
-                                            ("type".to_string(), "checkbox".to_string()),
-                                            if is_completed { ("checked".to_string(), "checked".to_string()) } else { ("".to_string(), "".to_string()) },
-                                        ].into_iter().filter(|(k, _)| !k.is_empty()).collect(),
+                                        attributes: vec![("type".to_string(), "checkbox".to_string())],
                                         on_click: Some(Rc::new(move || {
                                             let mut new_todos = todos_clone.clone();
                                             if let Some(todo) = new_todos.iter_mut().find(|t| t.id == todo_id_toggle) {
@@ -251,20 +273,21 @@ impl Component for TodoApp {
                                             set_todos_clone(new_todos);
                                         })),
                                         ..Default::default()
-                                    },
+                                    }
+                                    .checked(is_completed)),
                                     children: vec![],
                                 },
                                 Element::Node {
                                     tag: "span".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("todo-text".to_string()),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text(todo.text.clone())],
                                 },
                                 Element::Node {
                                     tag: "button".to_string(),
-                                    props: Props {
+                                    props: Box::new(Props {
                                         class: Some("delete-btn".to_string()),
                                         on_click: Some(Rc::new(move || {
                                             let new_todos = todos_clone_delete.iter()
@@ -274,147 +297,143 @@ impl Component for TodoApp {
                                             set_todos_clone_delete(new_todos);
                                         })),
                                         ..Default::default()
-                                    },
+                                    }),
                                     children: vec![Element::Text("×".to_string())],
                                 },
                             ],
                         },
                         Element::Node {
                             tag: "div".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 class: Some("todo-meta".to_string()),
                                 ..Default::default()
-                            },
-                            children: vec![Element::Text(todo.created_at.clone())],
+                            }),
+                            children: std::iter::once(Element::Text(todo.created_at.clone()))
+                                .chain(todo.labels.iter().map(|label| Element::Node {
+                                    tag: "span".to_string(),
+                                    props: Box::new(Props {
+                                        class: Some("todo-label".to_string()),
+                                        ..Default::default()
+                                    }),
+                                    children: vec![Element::Text(label.clone())],
+                                }))
+                                .collect(),
                         },
                     ],
-                }
-            }).collect();
+                };
+                (todo_id, item)
+            });
 
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("todos-container".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "ul".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("todo-list".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: todo_items,
                     },
                 ],
             }
         };
 
-        let footer = if !todos.is_empty() {
+        let footer = show(!todos.is_empty(), || {
             let set_filter_clone = set_filter.clone();
             let set_todos_clone = set_todos.clone();
             let todos_clone = todos.clone();
             
             Element::Node {
                 tag: "div".to_string(),
-                props: Props {
+                props: Box::new(Props {
                     class: Some("footer".to_string()),
                     ..Default::default()
-                },
+                }),
                 children: vec![
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("stats".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             Element::Node {
                                 tag: "span".to_string(),
-                                props: Props::default(),
-                                children: vec![Element::Text(format!("{} active", active_count))],
+                                props: Box::default(),
+                                children: vec![Element::Text((i18n.plural)("todos.active", active_count as i32, None))],
                             },
                             Element::Node {
                                 tag: "span".to_string(),
-                                props: Props {
+                                props: Box::new(Props {
                                     class: Some("dot".to_string()),
                                     ..Default::default()
-                                },
+                                }),
                                 children: vec![Element::Text("•".to_string())],
                             },
                             Element::Node {
                                 tag: "span".to_string(),
-                                props: Props::default(),
-                                children: vec![Element::Text(format!("{} completed", completed_count))],
+                                props: Box::default(),
+                                children: vec![Element::Text((i18n.plural)("todos.completed", completed_count as i32, None))],
                             },
                         ],
                     },
                     Element::Node {
                         tag: "div".to_string(),
-                        props: Props {
+                        props: Box::new(Props {
                             class: Some("filters".to_string()),
                             ..Default::default()
-                        },
+                        }),
                         children: vec![
                             create_filter_button(&filter, &set_filter_clone, Filter::All),
                             create_filter_button(&filter, &set_filter_clone, Filter::Active),
                             create_filter_button(&filter, &set_filter_clone, Filter::Completed),
                         ],
                     },
-                    if completed_count > 0 {
-                        Element::Node {
-                            tag: "button".to_string(),
-                            props: Props {
-                                class: Some("clear-btn".to_string()),
-                                on_click: Some(Rc::new(move || {
-                                    let new_todos = todos_clone.iter()
-                                        .filter(|t| !t.completed)
-                                        .cloned()
-                                        .collect();
-                                    set_todos_clone(new_todos);
-                                })),
-                                ..Default::default()
-                            },
-                            children: vec![Element::Text("Clear completed".to_string())],
-                        }
-                    } else {
-                        Element::Node {
-                            tag: "div".to_string(),
-                            props: Props::default(),
-                            children: vec![],
-                        }
-                    },
+                    show(completed_count > 0, || Element::Node {
+                        tag: "button".to_string(),
+                        props: Box::new(Props {
+                            class: Some("clear-btn".to_string()),
+                            on_click: Some(Rc::new(move || {
+                                let new_todos = todos_clone.iter()
+                                    .filter(|t| !t.completed)
+                                    .cloned()
+                                    .collect();
+                                set_todos_clone(new_todos);
+                            })),
+                            ..Default::default()
+                        }),
+                        children: vec![Element::Text("Clear completed".to_string())],
+                    }),
                 ],
             }
-        } else {
-            Element::Node {
-                tag: "div".to_string(),
-                props: Props::default(),
-                children: vec![],
-            }
-        };
+        });
 
         let info = Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("info".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 Element::Node {
                     tag: "p".to_string(),
-                    props: Props::default(),
+                    props: Box::default(),
                     children: vec![
                         Element::Text("Built with ".to_string()),
                         Element::Node {
                             tag: "a".to_string(),
-                            props: Props {
+                            props: Box::new(Props {
                                 attributes: vec![
                                     ("href".to_string(), "https://github.com/anthropics/layer9".to_string()),
                                     ("target".to_string(), "_blank".to_string()),
                                 ],
                                 ..Default::default()
-                            },
+                            }),
                             children: vec![Element::Text("Layer9".to_string())],
                         },
                         Element::Text(" - A Reactive Rust Web Framework".to_string()),
@@ -426,10 +445,10 @@ impl Component for TodoApp {
         // Main app container
         Element::Node {
             tag: "div".to_string(),
-            props: Props {
+            props: Box::new(Props {
                 class: Some("todo-app".to_string()),
                 ..Default::default()
-            },
+            }),
             children: vec![
                 style_element,
                 header,
@@ -449,13 +468,13 @@ fn create_filter_button(current_filter: &Filter, set_filter: &(impl Fn(Filter) +
     
     Element::Node {
         tag: "button".to_string(),
-        props: Props {
+        props: Box::new(Props {
             class: Some(if is_active { "filter-btn active".to_string() } else { "filter-btn".to_string() }),
             on_click: Some(Rc::new(move || {
                 set_filter_clone(target_filter_clone.clone());
             })),
             ..Default::default()
-        },
+        }),
         children: vec![Element::Text(filter_text(&target_filter).to_string())],
     }
 }
@@ -468,6 +487,18 @@ fn filter_text(filter: &Filter) -> &str {
     }
 }
 
+/// Count- and filter-dependent wording lives in the catalog, not in `render`
+fn messages() -> Messages {
+    [
+        ("todos.empty", "{filter, select, all {No todos yet} other {No {filter} todos}}"),
+        ("todos.active", "{count, plural, one {# item left} other {# items left}}"),
+        ("todos.completed", "{count} completed"),
+    ]
+    .into_iter()
+    .map(|(key, text)| (key.to_string(), TranslationValue::Text(text.to_string())))
+    .collect()
+}
+
 const STYLES: &str = r#"
     :root {
         --primary: #6366f1;
@@ -531,6 +562,7 @@ const STYLES: &str = r#"
     
     .input-section {
         display: flex;
+        flex-wrap: wrap;
         padding: 20px;
         background: var(--gray-100);
         border-bottom: 1px solid var(--gray-200);
@@ -653,6 +685,20 @@ const STYLES: &str = r#"
         color: var(--gray-400);
     }
     
+    .label-input {
+        flex-basis: 100%;
+        background: white;
+        border-radius: 10px;
+    }
+    
+    .todo-label {
+        margin-left: 8px;
+        padding: 1px 8px;
+        border-radius: 9999px;
+        background: var(--gray-100);
+        color: var(--gray-600);
+    }
+    
     .footer {
         display: flex;
         align-items: center;
@@ -754,6 +800,7 @@ const STYLES: &str = r#"
 #[wasm_bindgen(start)]
 pub fn main() {
     web_sys::console::log_1(&"Layer9 Todo App starting...".into());
+    init_i18n(TranslationCatalog::new().add_locale(Locale::EnUS, messages()));
     mount(Box::new(TodoApp), "root");
     web_sys::console::log_1(&"Layer9 Todo App mounted successfully!".into());
 }
\ No newline at end of file